@@ -0,0 +1,130 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use actix_web::body::to_bytes_limited;
+use actix_web::body::BodyStream;
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::FromRequest;
+use actix_web::HttpMessage;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+use actix_web::ResponseError;
+use std::future::Future;
+use std::pin::Pin;
+use thiserror::Error;
+
+pub use crate::blob::LBSBlob;
+
+/// Content type of requests and responses.
+pub const CONTENT_TYPE: &str = "application/x-lbs";
+
+const DEFAULT_CONFIG: LBSConfig = LBSConfig {
+    limit: 2 * 1024 * 1024,
+    content_type_required: true,
+};
+
+/// Extractor configuration, which may be set with `App::app_data()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LBSConfig {
+    limit: usize,
+    content_type_required: bool,
+}
+
+/// Failure of `LBSBlob` extractor.
+#[derive(Error, Debug)]
+pub enum LBSPayloadError {
+    #[error("content type must be {CONTENT_TYPE}")]
+    ContentType,
+    #[error("payload exceeds limit of {0} bytes")]
+    Overflow(usize),
+    #[error("{0}")]
+    Payload(actix_web::Error),
+    #[error(transparent)]
+    Decode(#[from] LBSError),
+}
+
+impl LBSConfig {
+    /// Maximum payload size in bytes, 2 MiB by default.
+    #[inline]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Whether requests without content type are rejected, true by default.
+    /// Requests of other content types are always rejected.
+    #[inline]
+    pub fn content_type_required(mut self, content_type_required: bool) -> Self {
+        self.content_type_required = content_type_required;
+        self
+    }
+
+    #[inline]
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>().unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+impl Default for LBSConfig {
+    #[inline]
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+impl ResponseError for LBSPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Overflow(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Payload(e) => e.as_response_error().status_code(),
+            Self::Decode(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Extracts value from request body, see `LBSConfig`. Decoding fails if body has trailing bytes.
+impl<T: LBSRead + 'static> FromRequest for LBSBlob<T> {
+    type Error = LBSPayloadError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = *LBSConfig::from_req(req);
+        let content_type = req.content_type();
+
+        if (content_type.is_empty() && config.content_type_required)
+            || (!content_type.is_empty() && content_type != CONTENT_TYPE)
+        {
+            return Box::pin(std::future::ready(Err(LBSPayloadError::ContentType)));
+        }
+
+        let stream = BodyStream::new(payload.take());
+
+        Box::pin(async move {
+            let body = to_bytes_limited(stream, config.limit)
+                .await
+                .map_err(|_| LBSPayloadError::Overflow(config.limit))?
+                .map_err(|e| LBSPayloadError::Payload(e.into()))?;
+
+            Ok(Self(from_slice_exact(&body)?))
+        })
+    }
+}
+
+/// Responds with encoded value, or with `500 Internal Server Error` if it can't be encoded.
+impl<T: LBSWrite> Responder for LBSBlob<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut body = Vec::new();
+
+        match self.0.lbs_write(&mut body) {
+            Ok(()) => HttpResponse::Ok().content_type(CONTENT_TYPE).body(body),
+            Err(e) => HttpResponse::from_error(actix_web::error::ErrorInternalServerError(e)),
+        }
+    }
+}
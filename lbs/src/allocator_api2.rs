@@ -0,0 +1,198 @@
+use crate::ctx::LBSReadCtx;
+use crate::ctx::LBSWriteCtx;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::vectored::VectoredWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::boxed::Box;
+use allocator_api2::vec::Vec;
+use std::io::Read;
+use std::io::Write;
+
+/// Decodes vector into given allocator, like an arena or pool.
+pub fn read_vec_in<T, R, A>(r: &mut R, d: &mut Decoder, alloc: A) -> Result<Vec<T, A>, LBSError>
+where
+    T: LBSRead,
+    R: Read,
+    A: Allocator,
+{
+    let l = read_len_with(r, d)?;
+    let mut v = Vec::with_capacity_in(capacity::<T>(l), alloc);
+
+    for _ in 0..l {
+        v.push(T::lbs_read_with(r, d)?);
+    }
+
+    Ok(v)
+}
+
+/// Decodes boxed value into given allocator, like an arena or pool.
+#[inline]
+pub fn read_box_in<T, R, A>(r: &mut R, d: &mut Decoder, alloc: A) -> Result<Box<T, A>, LBSError>
+where
+    T: LBSRead,
+    R: Read,
+    A: Allocator,
+{
+    Ok(Box::new_in(T::lbs_read_with(r, d)?, alloc))
+}
+
+impl<T: LBSWrite, A: Allocator> LBSWrite for Vec<T, A> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_vectored(v)
+    }
+}
+
+/// Allocators, which can be created with `Default`, are used by derived implementations as is.
+impl<T: LBSRead, A: Allocator + Default> LBSRead for Vec<T, A> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        read_vec_in(r, d, A::default())
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            T::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Other allocators are passed as context, see `#[lbs(ctx(Type))]`.
+impl<T: LBSWrite, A: Allocator> LBSWriteCtx<A> for Vec<T, A> {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, _ctx: &mut A) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        _ctx: &mut A,
+    ) -> Result<(), LBSError> {
+        self.lbs_write_with(w, e)
+    }
+}
+
+impl<T: LBSRead, A: Allocator + Clone> LBSReadCtx<A> for Vec<T, A> {
+    #[inline]
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut A) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx_with(r, &mut Decoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        ctx: &mut A,
+    ) -> Result<Self, LBSError> {
+        read_vec_in(r, d, ctx.clone())
+    }
+}
+
+impl<T: LBSSchema, A: Allocator> LBSSchema for Vec<T, A> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::List(std::boxed::Box::new(T::lbs_schema()))
+    }
+}
+
+impl<T: LBSWrite, A: Allocator> LBSWrite for Box<T, A> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        (**self).lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        (**self).lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        (**self).lbs_write_vectored(v)
+    }
+}
+
+impl<T: LBSRead, A: Allocator + Default> LBSRead for Box<T, A> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        read_box_in(r, d, A::default())
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+}
+
+impl<T: LBSWrite, A: Allocator> LBSWriteCtx<A> for Box<T, A> {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, _ctx: &mut A) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        _ctx: &mut A,
+    ) -> Result<(), LBSError> {
+        self.lbs_write_with(w, e)
+    }
+}
+
+impl<T: LBSRead, A: Allocator + Clone> LBSReadCtx<A> for Box<T, A> {
+    #[inline]
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut A) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx_with(r, &mut Decoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        ctx: &mut A,
+    ) -> Result<Self, LBSError> {
+        read_box_in(r, d, ctx.clone())
+    }
+}
+
+impl<T: LBSSchema, A: Allocator> LBSSchema for Box<T, A> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
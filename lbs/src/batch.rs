@@ -0,0 +1,168 @@
+use crate::crc32::checksum;
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::read::read_bytes_into;
+use crate::read::read_len_with;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Records are preallocated up to this count, longer batches grow as they are read.
+const MAX_PREALLOCATED: usize = 4096;
+
+/// Batch of records, each with its own length and CRC-32.
+///
+/// Encoded form is record count, then (length, checksum) pair for every record, then records.
+/// Reading `Batch<T>` fails on the first bad record, while `BatchReader` skips bad records
+/// and reports their indexes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Batch<T>(pub Vec<T>);
+
+/// Reader, which yields records of a batch one by one.
+///
+/// Records with mismatching checksum or which fail to decode are skipped, their indexes are collected
+/// in `failed()`. I/O errors end iteration, because position of the next record is unknown then.
+pub struct BatchReader<R, T> {
+    r: R,
+    headers: Vec<(usize, u32)>,
+    next: usize,
+    failed: Vec<usize>,
+    buf: Vec<u8>,
+    record: PhantomData<T>,
+}
+
+/// Writes records as a batch. Returns number of bytes written.
+pub fn write<T: LBSWrite, W: Write>(w: &mut W, records: &[T]) -> Result<usize, LBSError> {
+    let mut headers = Vec::with_capacity(records.len());
+    let mut body = Vec::new();
+
+    for record in records {
+        let start = body.len();
+        record.lbs_write(&mut body)?;
+        headers.push((body.len() - start, checksum(&body[start..])));
+    }
+
+    write_len(w, records.len())?;
+
+    for (len, crc) in headers.iter() {
+        write_len(w, *len)?;
+        crc.lbs_write(w)?;
+    }
+
+    w.write_all(&body)?;
+    Ok(4 + headers.len() * 8 + body.len())
+}
+
+impl<T: LBSWrite> LBSWrite for Batch<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        write(w, &self.0).map(|_| ())
+    }
+}
+
+impl<T: LBSRead> LBSRead for Batch<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut reader = BatchReader::new_with(r, d)?;
+        let mut records = Vec::with_capacity(reader.len().min(MAX_PREALLOCATED));
+
+        for (_, record) in &mut reader {
+            records.push(record?);
+        }
+
+        Ok(Self(records))
+    }
+}
+
+impl<R: Read, T: LBSRead> BatchReader<R, T> {
+    /// Reads batch header.
+    #[inline]
+    pub fn new(r: R) -> Result<Self, LBSError> {
+        Self::new_with(r, &Decoder::new())
+    }
+
+    /// Same as `new()`, but record count and lengths must be within `Limits::max_len` of `d`.
+    pub fn new_with(mut r: R, d: &Decoder) -> Result<Self, LBSError> {
+        let count = read_len_with(&mut r, d)?;
+        let mut headers = Vec::with_capacity(count.min(MAX_PREALLOCATED));
+
+        for _ in 0..count {
+            headers.push((read_len_with(&mut r, d)?, u32::lbs_read(&mut r)?));
+        }
+
+        Ok(Self {
+            r,
+            headers,
+            next: 0,
+            failed: Vec::new(),
+            buf: Vec::new(),
+            record: PhantomData,
+        })
+    }
+
+    /// Number of records in the batch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Indexes of records, which were skipped so far.
+    #[inline]
+    pub fn failed(&self) -> &[usize] {
+        &self.failed
+    }
+
+    /// Reads the next valid record, skipping bad ones.
+    /// Returns `None` after the last record or an I/O error.
+    pub fn next_valid(&mut self) -> Option<Result<(usize, T), LBSError>> {
+        while let Some((i, record)) = self.next() {
+            match record {
+                Ok(record) => return Some(Ok((i, record))),
+                Err(_) if self.failed.last() == Some(&i) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read, T: LBSRead> Iterator for BatchReader<R, T> {
+    /// Record index and either record or the reason it was skipped.
+    type Item = (usize, Result<T, LBSError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.next;
+        let (len, crc) = *self.headers.get(i)?;
+        self.next += 1;
+
+        // Buffer grows as bytes arrive, so lengths of truncated batches don't allocate
+        if let Err(e) = read_bytes_into(&mut self.r, &mut self.buf, len) {
+            self.next = self.headers.len();
+            return Some((i, Err(e)));
+        }
+
+        let result = match checksum(&self.buf) == crc {
+            true => from_slice_exact(&self.buf),
+            false => Err(LBSError::ChecksumMismatch),
+        };
+
+        if result.is_err() {
+            self.failed.push(i);
+        }
+
+        Some((i, result))
+    }
+}
@@ -0,0 +1,36 @@
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+/// Value, which is stored in encoded form by storage integrations, like `sqlx` or `redis` features.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LBSBlob<T>(pub T);
+
+impl<T> LBSBlob<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for LBSBlob<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for LBSBlob<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for LBSBlob<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
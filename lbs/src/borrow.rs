@@ -0,0 +1,317 @@
+use crate::decoder::Decoder;
+use crate::encoder::SHARED_STRING_FLAG;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::stats;
+use crate::LBSRead;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Types, which may be decoded from a byte slice borrowing strings and bytes from it.
+///
+/// Same encoding as of `LBSRead`, but `&'de str`, `&'de [u8]`, `Cow<'de, str>` and
+/// `Cow<'de, [u8]>` point into the input instead of allocating. Implementation can be derived
+/// with `#[derive(LBSReadRef)]`, including for structs and enums with lifetimes.
+///
+/// With shared strings, strings are still borrowed where they are defined, but references to
+/// them can only be read as owned values: `Cow` becomes `Owned` and `&str` fails.
+pub trait LBSReadRef<'de>: Sized {
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+macro_rules! impl_read_ref_owned {
+    ($($t:ty),*) => {
+        $(
+            impl<'de> LBSReadRef<'de> for $t {
+                #[inline]
+                fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+                    <$t as LBSRead>::lbs_read_with(r, d)
+                }
+            }
+        )*
+    };
+}
+
+impl_read_ref_owned!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+impl_read_ref_owned!((), bool, char, String, Arc<str>, Infallible);
+impl_read_ref_owned!(Duration, SystemTime, Ipv4Addr, Ipv6Addr, IpAddr);
+
+impl<'de: 'a, 'a> LBSReadRef<'de> for &'a [u8] {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        take(r, l)
+    }
+}
+
+impl<'de: 'a, 'a> LBSReadRef<'de> for &'a str {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        match read_str_ref(r, d)? {
+            Cow::Borrowed(s) => Ok(s),
+            Cow::Owned(_) => Err(LBSError::Parsing(
+                "shared string can't be borrowed".to_string(),
+            )),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> LBSReadRef<'de> for Cow<'a, str> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        read_str_ref(r, d)
+    }
+}
+
+impl<'de: 'a, 'a> LBSReadRef<'de> for Cow<'a, [u8]> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        <&[u8]>::lbs_read_ref(r, d).map(Cow::Borrowed)
+    }
+}
+
+impl<'de, T1: LBSReadRef<'de>, T2: LBSReadRef<'de>> LBSReadRef<'de> for (T1, T2) {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok((T1::lbs_read_ref(r, d)?, T2::lbs_read_ref(r, d)?))
+    }
+}
+
+impl<'de, T1, T2, T3> LBSReadRef<'de> for (T1, T2, T3)
+where
+    T1: LBSReadRef<'de>,
+    T2: LBSReadRef<'de>,
+    T3: LBSReadRef<'de>,
+{
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok((
+            T1::lbs_read_ref(r, d)?,
+            T2::lbs_read_ref(r, d)?,
+            T3::lbs_read_ref(r, d)?,
+        ))
+    }
+}
+
+impl<'de, T: LBSReadRef<'de>> LBSReadRef<'de> for Range<T> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let start = T::lbs_read_ref(r, d)?;
+        let end = T::lbs_read_ref(r, d)?;
+        Ok(Range { start, end })
+    }
+}
+
+impl<'de, T: LBSReadRef<'de>> LBSReadRef<'de> for Box<T> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_ref(r, d)?))
+    }
+}
+
+impl<'de, T: LBSReadRef<'de>> LBSReadRef<'de> for Arc<T> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_ref(r, d)?))
+    }
+}
+
+impl<'de, T: LBSReadRef<'de>> LBSReadRef<'de> for Option<T> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        match read_option_tag(r)? {
+            true => Ok(Some(T::lbs_read_ref(r, d)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<'de, T: LBSReadRef<'de>> LBSReadRef<'de> for Vec<T> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_read_ref(r, d)?);
+        }
+
+        Ok(v)
+    }
+}
+
+impl<'de, K, V, S> LBSReadRef<'de> for HashMap<K, V, S>
+where
+    K: LBSReadRef<'de> + Eq + Hash,
+    V: LBSReadRef<'de>,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::default());
+        }
+
+        stats::allocation(d);
+        let mut hm = Self::with_capacity_and_hasher(capacity::<(K, V)>(l), S::default());
+
+        for _ in 0..l {
+            let k = K::lbs_read_ref(r, d)?;
+            let v = V::lbs_read_ref(r, d)?;
+            hm.insert(k, v);
+        }
+
+        Ok(hm)
+    }
+}
+
+impl<'de, K, S> LBSReadRef<'de> for HashSet<K, S>
+where
+    K: LBSReadRef<'de> + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::default());
+        }
+
+        stats::allocation(d);
+        let mut hs = Self::with_capacity_and_hasher(capacity::<K>(l), S::default());
+
+        for _ in 0..l {
+            hs.insert(K::lbs_read_ref(r, d)?);
+        }
+
+        Ok(hs)
+    }
+}
+
+impl<'de, K: LBSReadRef<'de> + Ord, V: LBSReadRef<'de>> LBSReadRef<'de> for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut bm = Self::new();
+
+        if l > 0 {
+            stats::allocation(d);
+        }
+
+        for _ in 0..l {
+            let k = K::lbs_read_ref(r, d)?;
+            let v = V::lbs_read_ref(r, d)?;
+            bm.insert(k, v);
+        }
+
+        Ok(bm)
+    }
+}
+
+impl<'de, K: LBSReadRef<'de> + Ord> LBSReadRef<'de> for BTreeSet<K> {
+    #[inline]
+    fn lbs_read_ref(r: &mut &'de [u8], d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut bs = Self::new();
+
+        if l > 0 {
+            stats::allocation(d);
+        }
+
+        for _ in 0..l {
+            bs.insert(K::lbs_read_ref(r, d)?);
+        }
+
+        Ok(bs)
+    }
+}
+
+/// Decodes a value, which must occupy the whole slice, borrowing from it.
+#[inline]
+pub fn from_slice<'de, T: LBSReadRef<'de>>(bytes: &'de [u8]) -> Result<T, LBSError> {
+    from_slice_with(bytes, &mut Decoder::new())
+}
+
+/// Same as `from_slice()`, but reuses state of the given decoder.
+#[inline]
+pub fn from_slice_with<'de, T: LBSReadRef<'de>>(
+    bytes: &'de [u8],
+    d: &mut Decoder,
+) -> Result<T, LBSError> {
+    let mut r = bytes;
+    let value = d.decode_ref(&mut r)?;
+
+    match r.len() {
+        0 => Ok(value),
+        n => Err(LBSError::TrailingBytes(n)),
+    }
+}
+
+/// Used by derived implementations.
+#[inline]
+pub fn read_with<'de, T: LBSReadRef<'de>>(
+    r: &mut &'de [u8],
+    d: &mut Decoder,
+) -> Result<T, LBSError> {
+    d.enter()?;
+    let result = T::lbs_read_ref(r, d);
+    d.leave();
+    result
+}
+
+/// Splits first `l` bytes off the input.
+#[inline]
+fn take<'de>(r: &mut &'de [u8], l: usize) -> Result<&'de [u8], LBSError> {
+    if r.len() < l {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let (bytes, rest) = r.split_at(l);
+    *r = rest;
+    Ok(bytes)
+}
+
+/// Borrowed string, or owned copy of a shared one it references.
+#[inline]
+fn read_str_ref<'de>(r: &mut &'de [u8], d: &mut Decoder) -> Result<Cow<'de, str>, LBSError> {
+    let l = u32::lbs_read(r)?;
+
+    if d.shares_strings() && l & SHARED_STRING_FLAG != 0 {
+        return Ok(Cow::Owned(d.shared_str(l)?.to_string()));
+    }
+
+    let l = d.check_len(l as usize)?;
+    let s = d.str_from_input(take(r, l)?)?;
+
+    if d.shares_strings() {
+        d.share_str(s);
+    }
+
+    Ok(Cow::Borrowed(s))
+}
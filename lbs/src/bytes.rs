@@ -0,0 +1,213 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::read::read_bytes;
+use crate::read::read_len;
+use crate::read::read_len_with;
+use crate::read::skip_bytes;
+use crate::read::MAX_PREALLOCATION;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::vectored::VectoredWriter;
+use crate::write::encoded_len;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use std::io::Read;
+use std::io::Write;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Encodes value into `Bytes` of exact capacity, which may be passed to tokio or hyper without copying.
+/// Value is encoded twice, first to measure it, see `encoded_len()`.
+#[inline]
+pub fn to_bytes<T: LBSWrite + ?Sized>(value: &T) -> Result<Bytes, LBSError> {
+    let mut w = BytesMut::with_capacity(encoded_len(value)?).writer();
+    value.lbs_write(&mut w)?;
+    Ok(w.into_inner().freeze())
+}
+
+/// Encodes value into a `BufMut`, like `BytesMut` or a chunk of a pooled buffer.
+/// Adapter of the buffer to `io::Write` never fails, so errors are only those of the value,
+/// and byte sequences are put at once.
+#[inline]
+pub fn write_buf<T: LBSWrite + ?Sized, B: BufMut>(buf: &mut B, value: &T) -> Result<(), LBSError> {
+    value.lbs_write(&mut buf.writer())
+}
+
+/// Decodes a value from the beginning of a `Buf`, like `Bytes` or a chain of chunks, and advances
+/// past it. Adapter of the buffer to `io::Read` never fails, so errors are only those of input.
+#[inline]
+pub fn read_buf<T: LBSRead, B: Buf>(buf: &mut B) -> Result<T, LBSError> {
+    T::lbs_read(&mut buf.reader())
+}
+
+/// Decodes a value, which must occupy the whole buffer. Fields of `Bytes` and
+/// `bytestring::ByteString` types share the buffer instead of allocating.
+#[inline]
+pub fn from_bytes<T: LBSRead>(bytes: &Bytes) -> Result<T, LBSError> {
+    from_bytes_with(bytes, &mut Decoder::new())
+}
+
+/// Same as `from_bytes()`, but reuses state of the given decoder.
+pub fn from_bytes_with<T: LBSRead>(bytes: &Bytes, d: &mut Decoder) -> Result<T, LBSError> {
+    let pos = Arc::new(AtomicUsize::new(0));
+
+    let mut r = SourceReader {
+        rest: bytes,
+        pos: pos.clone(),
+    };
+
+    let previous = d.replace_source(Some(Source {
+        bytes: bytes.clone(),
+        pos,
+    }));
+
+    let result = d.decode(&mut r);
+    d.replace_source(previous);
+    let value = result?;
+
+    match r.rest.len() {
+        0 => Ok(value),
+        n => Err(LBSError::TrailingBytes(n)),
+    }
+}
+
+/// Input of `from_bytes()` and position of its reader.
+#[derive(Debug)]
+pub(crate) struct Source {
+    bytes: Bytes,
+    pos: Arc<AtomicUsize>,
+}
+
+impl Source {
+    /// Slice of the input, which was just read and equals `read`.
+    #[inline]
+    pub(crate) fn last(&self, read: &[u8]) -> Option<Bytes> {
+        let end = self.pos.load(Ordering::Relaxed);
+        let start = end.checked_sub(read.len())?;
+
+        match self.bytes.get(start..end) == Some(read) {
+            true => Some(self.bytes.slice(start..end)),
+            false => None,
+        }
+    }
+}
+
+/// Reader of `from_bytes()` input, which tracks its position.
+struct SourceReader<'a> {
+    rest: &'a [u8],
+    pos: Arc<AtomicUsize>,
+}
+
+impl Read for SourceReader<'_> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.rest.read(buf)?;
+        self.pos.fetch_add(n, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl LBSWrite for Bytes {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        Ok(w.write_all(self)?)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        v.write_borrowed(self);
+        Ok(())
+    }
+}
+
+impl LBSRead for Bytes {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let l = read_len(r)?;
+        Ok(Self::from(read_bytes(r, l)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        d.read_shared_bytes(r)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        skip_bytes(r, l)
+    }
+}
+
+impl LBSWrite for BytesMut {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_ref().lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.len()
+    }
+}
+
+impl LBSRead for BytesMut {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut buf = Self::new();
+        buf.lbs_read_into_with(r, d)?;
+        Ok(buf)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        skip_bytes(r, l)
+    }
+
+    /// Buffer keeps its capacity and grows as bytes arrive, see `MAX_PREALLOCATION`.
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        self.clear();
+        self.reserve(l.min(MAX_PREALLOCATION));
+
+        if std::io::copy(&mut r.take(l as u64), &mut (&mut *self).writer())? < l as u64 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl LBSSchema for BytesMut {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::List(Box::new(Schema::U8))
+    }
+}
+
+impl LBSSchema for Bytes {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::List(Box::new(Schema::U8))
+    }
+}
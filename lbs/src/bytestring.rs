@@ -0,0 +1,72 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::read::read_len;
+use crate::read::skip_bytes;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::vectored::VectoredWriter;
+use crate::Encoder;
+use crate::LBSRead;
+use crate::LBSWrite;
+use bytestring::ByteString;
+use std::io::Read;
+use std::io::Write;
+
+impl LBSWrite for ByteString {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        (**self).lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        (**self).lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        (**self).lbs_write_vectored(v)
+    }
+}
+
+/// Decoded with `lbs::from_bytes()`, strings share the input buffer instead of allocating.
+impl LBSRead for ByteString {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self::from(String::lbs_read(r)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if d.shares_strings() {
+            return Ok(Self::from(&*d.read_shared_str(r)?));
+        }
+
+        let bytes = d.read_shared_bytes(r)?;
+
+        if d.trusts_utf8() {
+            // SAFETY: caller of trust_utf8() guarantees input is valid UTF-8.
+            return Ok(unsafe { Self::from_bytes_unchecked(bytes) });
+        }
+
+        Self::try_from(bytes).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        // Skipped strings still have to be added to the shared table
+        if d.shares_strings() {
+            return d.read_shared_str(r).map(|_| ());
+        }
+
+        let l = read_len(r)?;
+        skip_bytes(r, l)
+    }
+}
+
+impl LBSSchema for ByteString {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::String
+    }
+}
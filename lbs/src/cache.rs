@@ -0,0 +1,197 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::vectored::VectoredWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Value stored in a cache, together with metadata needed to decide whether it can be used.
+///
+/// Encoded as creation time, optional TTL, schema fingerprint and flags, followed by the value,
+/// so `read_fresh()` can reject stale entries and entries written with a different schema
+/// without decoding the value. Meaning of flags is up to the application. Entries without TTL
+/// never expire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cached<T> {
+    pub created_at: SystemTime,
+    pub ttl: Option<Duration>,
+    pub fingerprint: u64,
+    pub flags: u32,
+    pub value: T,
+}
+
+impl<T> Cached<T> {
+    /// Wraps a value created now.
+    #[inline]
+    pub fn new(value: T, ttl: Option<Duration>, fingerprint: u64) -> Self {
+        Self {
+            created_at: SystemTime::now(),
+            ttl,
+            fingerprint,
+            flags: 0,
+            value,
+        }
+    }
+
+    #[inline]
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Time after which the entry is stale, `None` if it never expires or the time overflows.
+    #[inline]
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.created_at.checked_add(self.ttl?)
+    }
+
+    #[inline]
+    pub fn is_fresh_at(&self, now: SystemTime) -> bool {
+        is_fresh_at(self.created_at, self.ttl, now)
+    }
+
+    #[inline]
+    pub fn is_fresh(&self) -> bool {
+        self.is_fresh_at(SystemTime::now())
+    }
+
+    /// Time left until the entry is stale, `None` if it never expires.
+    #[inline]
+    pub fn remaining_at(&self, now: SystemTime) -> Option<Duration> {
+        let expires_at = self.expires_at()?;
+        Some(expires_at.duration_since(now).unwrap_or(Duration::ZERO))
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: LBSSchema> Cached<T> {
+    /// Wraps a value created now, with fingerprint of its schema.
+    #[inline]
+    pub fn for_schema(value: T, ttl: Option<Duration>) -> Self {
+        Self::new(value, ttl, T::lbs_schema().fingerprint())
+    }
+}
+
+/// Reads an entry, unless it is stale at `now` (`Ok(None)`) or has a different fingerprint
+/// (`LBSError::SchemaMismatch`). Value of a rejected entry isn't decoded.
+pub fn read_fresh<T: LBSRead, R: Read>(
+    r: &mut R,
+    fingerprint: u64,
+    now: SystemTime,
+) -> Result<Option<Cached<T>>, LBSError> {
+    let created_at = SystemTime::lbs_read(r)?;
+    let ttl = Option::<Duration>::lbs_read(r)?;
+
+    if u64::lbs_read(r)? != fingerprint {
+        return Err(LBSError::SchemaMismatch);
+    }
+
+    if !is_fresh_at(created_at, ttl, now) {
+        return Ok(None);
+    }
+
+    Ok(Some(Cached {
+        created_at,
+        ttl,
+        fingerprint,
+        flags: u32::lbs_read(r)?,
+        value: T::lbs_read(r)?,
+    }))
+}
+
+#[inline]
+fn is_fresh_at(created_at: SystemTime, ttl: Option<Duration>, now: SystemTime) -> bool {
+    match ttl {
+        Some(ttl) => match now.duration_since(created_at) {
+            Ok(age) => age < ttl,
+            // Created "in the future" by a host with a clock ahead of ours
+            Err(_) => true,
+        },
+        None => true,
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for Cached<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.created_at.lbs_write(w)?;
+        self.ttl.lbs_write(w)?;
+        self.fingerprint.lbs_write(w)?;
+        self.flags.lbs_write(w)?;
+        self.value.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.created_at.lbs_write_with(w, e)?;
+        self.ttl.lbs_write_with(w, e)?;
+        self.fingerprint.lbs_write_with(w, e)?;
+        self.flags.lbs_write_with(w, e)?;
+        self.value.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.created_at.lbs_size_hint()
+            + self.ttl.lbs_size_hint()
+            + self.fingerprint.lbs_size_hint()
+            + self.flags.lbs_size_hint()
+            + self.value.lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.created_at.lbs_write_vectored(v)?;
+        self.ttl.lbs_write_vectored(v)?;
+        self.fingerprint.lbs_write_vectored(v)?;
+        self.flags.lbs_write_vectored(v)?;
+        self.value.lbs_write_vectored(v)
+    }
+}
+
+impl<T: LBSRead> LBSRead for Cached<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self {
+            created_at: SystemTime::lbs_read(r)?,
+            ttl: Option::<Duration>::lbs_read(r)?,
+            fingerprint: u64::lbs_read(r)?,
+            flags: u32::lbs_read(r)?,
+            value: T::lbs_read(r)?,
+        })
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self {
+            created_at: SystemTime::lbs_read_with(r, d)?,
+            ttl: Option::<Duration>::lbs_read_with(r, d)?,
+            fingerprint: u64::lbs_read_with(r, d)?,
+            flags: u32::lbs_read_with(r, d)?,
+            value: T::lbs_read_with(r, d)?,
+        })
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for Cached<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![
+            SystemTime::lbs_schema(),
+            Option::<Duration>::lbs_schema(),
+            u64::lbs_schema(),
+            u32::lbs_schema(),
+            T::lbs_schema(),
+        ])
+    }
+}
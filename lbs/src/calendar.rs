@@ -0,0 +1,75 @@
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Calendar date as a year and a day of that year, starting with 1.
+///
+/// Written as `i32` year followed by `u16` ordinal, 6 bytes in total. Dates of `chrono` and
+/// `time` are written the same way, so they may be exchanged between both and this type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrdinalDate {
+    year: i32,
+    ordinal: u16,
+}
+
+impl OrdinalDate {
+    /// Date for a year and a day of it, None if the year has no such day.
+    #[inline]
+    pub fn new(year: i32, ordinal: u16) -> Option<Self> {
+        let days = if is_leap_year(year) { 366 } else { 365 };
+
+        match (1..=days).contains(&ordinal) {
+            true => Some(Self { year, ordinal }),
+            false => None,
+        }
+    }
+
+    #[inline]
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    #[inline]
+    pub fn ordinal(&self) -> u16 {
+        self.ordinal
+    }
+}
+
+/// Gregorian leap year, proleptic for years before 1582.
+#[inline]
+pub fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+impl LBSWrite for OrdinalDate {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.year.lbs_write(w)?;
+        self.ordinal.lbs_write(w)
+    }
+}
+
+impl LBSRead for OrdinalDate {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let year = i32::lbs_read(r)?;
+        let ordinal = u16::lbs_read(r)?;
+        Self::new(year, ordinal).ok_or(LBSError::OutOfRange("OrdinalDate"))
+    }
+}
+
+impl LBSSchema for OrdinalDate {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::I32, Schema::U16])
+    }
+}
+
+impl LBSMaxSize for OrdinalDate {
+    const LBS_MAX_SIZE: usize = 6;
+}
@@ -0,0 +1,121 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::LBSRead;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+/// Number of bytes captured by `CaptureReader::new()`.
+pub const DEFAULT_CAPTURE_LIMIT: usize = 64 * 1024;
+
+/// Reader, which copies consumed bytes into a capture buffer, up to a limit, so input,
+/// which failed to decode, may be attached to the error and reproduced later.
+#[derive(Debug)]
+pub struct CaptureReader<R> {
+    inner: R,
+    captured: Vec<u8>,
+    limit: usize,
+    offset: u64,
+}
+
+/// Prefix of input consumed before a decoding error, see `LBSError::capture()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    /// Number of bytes consumed before the error.
+    pub offset: u64,
+    /// First consumed bytes, at most as many as the capture limit.
+    pub bytes: Vec<u8>,
+}
+
+impl<R: Read> CaptureReader<R> {
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self::with_limit(inner, DEFAULT_CAPTURE_LIMIT)
+    }
+
+    #[inline]
+    pub fn with_limit(inner: R, limit: usize) -> Self {
+        Self {
+            inner,
+            captured: Vec::new(),
+            limit,
+            offset: 0,
+        }
+    }
+
+    /// Bytes captured since creation or the last `clear()`.
+    #[inline]
+    pub fn captured(&self) -> &[u8] {
+        &self.captured
+    }
+
+    /// Number of bytes consumed since creation or the last `clear()`.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Resets capture, like between messages of a stream.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.captured.clear();
+        self.offset = 0;
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decodes a value from a fresh capture. On error, attaches captured bytes to it.
+    #[inline]
+    pub fn decode<T: LBSRead>(&mut self) -> Result<T, LBSError> {
+        self.decode_with(&mut Decoder::new())
+    }
+
+    /// Same as `decode()`, but reuses state of the given decoder.
+    pub fn decode_with<T: LBSRead>(&mut self, d: &mut Decoder) -> Result<T, LBSError> {
+        self.clear();
+
+        d.decode(self).map_err(|e| {
+            let capture = Capture {
+                offset: self.offset,
+                bytes: self.captured.clone(),
+            };
+
+            LBSError::Captured(capture, e.into())
+        })
+    }
+}
+
+impl<R: Read> Read for CaptureReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let room = self.limit.saturating_sub(self.captured.len());
+        self.captured.extend_from_slice(&buf[..n.min(room)]);
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl Capture {
+    /// Whether all consumed bytes fit into the capture.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.bytes.len() as u64 == self.offset
+    }
+
+    /// Writes captured bytes into a sidecar file, which may be decoded again to reproduce the error.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LBSError> {
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(&self.bytes)?;
+        Ok(file.sync_all()?)
+    }
+}
+
+/// Decodes a value, attaching input consumed before an error to it, see `CaptureReader`.
+#[inline]
+pub fn read_captured<T: LBSRead, R: Read>(r: &mut R) -> Result<T, LBSError> {
+    CaptureReader::new(r).decode()
+}
@@ -0,0 +1,239 @@
+use crate::error::LBSError;
+use crate::options::Options;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use digest::Digest;
+use digest::Output;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// Number of the next temporary file of `FsBackend`, unique within process.
+static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+
+/// Storage of encoded values by their hashes.
+pub trait Backend {
+    /// Stores bytes under a hash, returns false if they are already there.
+    fn put(&mut self, hash: &[u8], bytes: &[u8]) -> Result<bool, LBSError>;
+
+    fn get(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, LBSError>;
+
+    fn contains(&self, hash: &[u8]) -> Result<bool, LBSError>;
+
+    /// Returns false if there was nothing to remove.
+    fn remove(&mut self, hash: &[u8]) -> Result<bool, LBSError>;
+}
+
+/// Content-addressed store of values.
+///
+/// Values are encoded canonically (see `Options::canonical()`) and keyed by hash of their
+/// encoding, so equal values are stored once. Iteration order of `HashMap` and `HashSet` isn't
+/// canonical, equal maps may produce different hashes: use `BTreeMap` and `BTreeSet` instead.
+/// `get()` verifies hash of stored bytes and fails with `LBSError::ChecksumMismatch` if differs.
+#[derive(Debug)]
+pub struct Store<B, D> {
+    backend: B,
+    options: Options,
+    buf: Vec<u8>,
+    hasher: PhantomData<D>,
+}
+
+impl<B: Backend, D: Digest> Store<B, D> {
+    #[inline]
+    pub fn new(backend: B) -> Self {
+        let mut options = Options::new();
+        options.canonical(true);
+
+        Self {
+            backend,
+            options,
+            buf: Vec::new(),
+            hasher: PhantomData,
+        }
+    }
+
+    /// Stores a value, unless an equal one is already there, and returns its hash.
+    pub fn put<T: LBSWrite + ?Sized>(&mut self, value: &T) -> Result<Output<D>, LBSError> {
+        self.buf.clear();
+        self.options.encoder().encode(value, &mut self.buf)?;
+
+        let hash = D::digest(&self.buf);
+        self.backend.put(&hash, &self.buf)?;
+        Ok(hash)
+    }
+
+    /// Value stored under a hash.
+    pub fn get<T: LBSRead>(&self, hash: &Output<D>) -> Result<Option<T>, LBSError> {
+        let Some(bytes) = self.backend.get(hash)? else {
+            return Ok(None);
+        };
+
+        if D::digest(&bytes) != *hash {
+            return Err(LBSError::ChecksumMismatch);
+        }
+
+        from_slice_exact(&bytes).map(Some)
+    }
+
+    #[inline]
+    pub fn contains(&self, hash: &Output<D>) -> Result<bool, LBSError> {
+        self.backend.contains(hash)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, hash: &Output<D>) -> Result<bool, LBSError> {
+        self.backend.remove(hash)
+    }
+
+    #[inline]
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    #[inline]
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+}
+
+/// Backend keeping every value in a file named by hex of its hash.
+///
+/// Files are spread over subdirectories named by first byte of hash and are written into
+/// a temporary file first, so concurrent writers and crashes never leave a partial value.
+#[derive(Debug, Clone)]
+pub struct FsBackend {
+    dir: PathBuf,
+}
+
+impl FsBackend {
+    /// Uses a directory, creating it if there is none.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, LBSError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// First byte names subdirectory and the rest names file, so hash needs at least two.
+    fn path(&self, hash: &[u8]) -> Result<PathBuf, LBSError> {
+        if hash.len() < 2 {
+            return Err(LBSError::Parsing(
+                "hash is shorter than 2 bytes".to_string(),
+            ));
+        }
+
+        let hex = hex(hash);
+        Ok(self.dir.join(&hex[..2]).join(&hex[2..]))
+    }
+}
+
+impl Backend for FsBackend {
+    fn put(&mut self, hash: &[u8], bytes: &[u8]) -> Result<bool, LBSError> {
+        let path = self.path(hash)?;
+
+        if path.exists() {
+            return Ok(false);
+        }
+
+        std::fs::create_dir_all(path.parent().unwrap())?;
+
+        // Writers of the same value in other threads and processes use their own files
+        let mut tmp_path = path.clone().into_os_string();
+        let n = NEXT_TMP.fetch_add(1, Ordering::Relaxed);
+        tmp_path.push(format!(".{}.{}.tmp", std::process::id(), n));
+
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(true)
+    }
+
+    fn get(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, LBSError> {
+        match std::fs::read(self.path(hash)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    #[inline]
+    fn contains(&self, hash: &[u8]) -> Result<bool, LBSError> {
+        Ok(self.path(hash)?.exists())
+    }
+
+    fn remove(&mut self, hash: &[u8]) -> Result<bool, LBSError> {
+        match std::fs::remove_file(self.path(hash)?) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Backend keeping values in memory, for tests and caches.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    values: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryBackend {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl Backend for MemoryBackend {
+    #[inline]
+    fn put(&mut self, hash: &[u8], bytes: &[u8]) -> Result<bool, LBSError> {
+        if self.values.contains_key(hash) {
+            return Ok(false);
+        }
+
+        self.values.insert(hash.to_vec(), bytes.to_vec());
+        Ok(true)
+    }
+
+    #[inline]
+    fn get(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, LBSError> {
+        Ok(self.values.get(hash).cloned())
+    }
+
+    #[inline]
+    fn contains(&self, hash: &[u8]) -> Result<bool, LBSError> {
+        Ok(self.values.contains_key(hash))
+    }
+
+    #[inline]
+    fn remove(&mut self, hash: &[u8]) -> Result<bool, LBSError> {
+        Ok(self.values.remove(hash).is_some())
+    }
+}
+
+/// Lowercase hex of a hash, as used in file names of `FsBackend`.
+pub fn hex(hash: &[u8]) -> String {
+    hash.iter()
+        .fold(String::with_capacity(hash.len() * 2), |mut s, b| {
+            let _ = write!(s, "{:02x}", b);
+            s
+        })
+}
@@ -0,0 +1,344 @@
+use crate::crc32::checksum;
+use crate::diff::apply;
+use crate::diff::diff;
+use crate::diff::LBSDiff;
+use crate::diff::Patch;
+use crate::error::LBSError;
+use crate::journal;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+// Directory holds generations, every generation is a full snapshot of the state and a journal of
+// patches made against it. Full file is header, then u32 payload length, u32 CRC-32 of payload
+// and payload itself. First journal record binds it to the full file: fingerprint, generation and
+// CRC-32 of its payload. Other records are patches, each against the state left by the previous.
+
+const MAGIC: [u8; 4] = *b"LBSC";
+pub const FORMAT_VERSION: u16 = 1;
+pub const DEFAULT_FULL_EVERY: u64 = 1024;
+
+const FULL_EXTENSION: &str = "full";
+const DELTA_EXTENSION: &str = "delta";
+const TMP_EXTENSION: &str = "tmp";
+
+/// State persisted as a chain of periodic full snapshots and patches between them.
+///
+/// Every `record()` appends a patch from the previous state, produced by `LBSDiff`, to the
+/// journal of the current generation. After `full_every` patches a new generation is started
+/// with a full snapshot, and files of older generations are removed once it is on disk.
+/// `open()` loads the latest full snapshot and applies its patches, verifying checksums and
+/// that patches belong to the snapshot. Torn final patch, left by a crash, is discarded.
+///
+/// Fingerprint is an arbitrary number chosen by the application for the state schema, a chain
+/// with a different one is refused with `LBSError::SchemaMismatch`.
+#[derive(Debug)]
+pub struct Chain<T> {
+    dir: PathBuf,
+    fingerprint: u64,
+    generation: u64,
+    deltas: journal::Writer,
+    delta_count: u64,
+    full_every: u64,
+    state: T,
+}
+
+impl<T: LBSWrite + LBSRead + LBSDiff + Default> Chain<T> {
+    /// Opens chain in a directory, creating it with default state if there is none.
+    pub fn open<P: AsRef<Path>>(dir: P, fingerprint: u64) -> Result<Self, LBSError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let Some(generation) = latest_generation(&dir)? else {
+            let state = T::default();
+            let crc = write_full(&dir, fingerprint, 0, &state)?;
+            let deltas = create_deltas(&dir, fingerprint, 0, crc)?;
+
+            return Ok(Self {
+                dir,
+                fingerprint,
+                generation: 0,
+                deltas,
+                delta_count: 0,
+                full_every: DEFAULT_FULL_EVERY,
+                state,
+            });
+        };
+
+        let (mut state, crc) = read_full::<T>(&dir, fingerprint, generation)?;
+        let delta_path = file_path(&dir, generation, DELTA_EXTENSION);
+        let mut delta_count = 0;
+
+        // Journal without a complete first record was being created during a crash
+        let bound = match delta_path.exists() {
+            true => {
+                let mut r = journal::Reader::open(&delta_path)?;
+
+                match r.read::<(u64, u64, u32)>()? {
+                    Some(binding) if binding == (fingerprint, generation, crc) => {
+                        while let Some(patch) = r.read::<Patch>()? {
+                            state = apply(state, &patch)?;
+                            delta_count += 1;
+                        }
+                        true
+                    }
+                    Some(_) => {
+                        return Err(LBSError::Parsing(format!(
+                            "deltas of generation {} don't belong to its snapshot",
+                            generation
+                        )))
+                    }
+                    None => false,
+                }
+            }
+            false => false,
+        };
+
+        let deltas = match bound {
+            true => journal::Writer::open(&delta_path)?,
+            false => create_deltas(&dir, fingerprint, generation, crc)?,
+        };
+
+        remove_older(&dir, generation)?;
+
+        Ok(Self {
+            dir,
+            fingerprint,
+            generation,
+            deltas,
+            delta_count,
+            full_every: DEFAULT_FULL_EVERY,
+            state,
+        })
+    }
+
+    /// Sets number of patches, after which `record()` starts a new generation.
+    #[inline]
+    pub fn with_full_every(mut self, full_every: u64) -> Self {
+        self.full_every = full_every.max(1);
+        self
+    }
+
+    /// Records new state, returns false and writes nothing if it didn't change.
+    /// Patch may stay buffered until `sync()`.
+    pub fn record(&mut self, state: &T) -> Result<bool, LBSError> {
+        let patch = diff(&self.state, state)?;
+
+        if patch.is_empty() {
+            return Ok(false);
+        }
+
+        self.deltas.append(&patch)?;
+        self.state = apply(std::mem::take(&mut self.state), &patch)?;
+        self.delta_count += 1;
+
+        if self.delta_count >= self.full_every {
+            self.checkpoint()?;
+        }
+
+        Ok(true)
+    }
+
+    /// Starts a new generation with a full snapshot of the current state.
+    pub fn checkpoint(&mut self) -> Result<(), LBSError> {
+        let generation = self.generation + 1;
+
+        self.deltas.sync()?;
+        let crc = write_full(&self.dir, self.fingerprint, generation, &self.state)?;
+        self.deltas = create_deltas(&self.dir, self.fingerprint, generation, crc)?;
+        self.generation = generation;
+        self.delta_count = 0;
+
+        remove_older(&self.dir, generation)
+    }
+
+    /// Writes buffered patches and waits until they reach the disk.
+    #[inline]
+    pub fn sync(&mut self) -> Result<(), LBSError> {
+        self.deltas.sync()
+    }
+
+    #[inline]
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    #[inline]
+    pub fn into_state(self) -> T {
+        self.state
+    }
+
+    /// Number of the current full snapshot, starting with 0.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Number of patches recorded since the current full snapshot.
+    #[inline]
+    pub fn delta_count(&self) -> u64 {
+        self.delta_count
+    }
+}
+
+fn file_path(dir: &Path, generation: u64, extension: &str) -> PathBuf {
+    dir.join(format!("{:016x}.{}", generation, extension))
+}
+
+/// Generation and extension of a chain file.
+fn parse_file_name(path: &Path) -> Option<(u64, &str)> {
+    let stem = path.file_stem()?.to_str()?;
+    let extension = path.extension()?.to_str()?;
+    let generation = u64::from_str_radix(stem.split('.').next()?, 16).ok()?;
+    Some((generation, extension))
+}
+
+fn latest_generation(dir: &Path) -> Result<Option<u64>, LBSError> {
+    let mut latest = None;
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if let Some((generation, FULL_EXTENSION)) = parse_file_name(&path) {
+            latest = latest.max(Some(generation));
+        }
+    }
+
+    Ok(latest)
+}
+
+/// Removes files of older generations and leftovers of unfinished full snapshots.
+fn remove_older(dir: &Path, generation: u64) -> Result<(), LBSError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        match parse_file_name(&path) {
+            Some((_, TMP_EXTENSION)) => std::fs::remove_file(&path)?,
+            Some((g, FULL_EXTENSION | DELTA_EXTENSION)) if g < generation => {
+                std::fs::remove_file(&path)?
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes full snapshot into a temporary file and renames it, returns CRC-32 of payload.
+fn write_full<T: LBSWrite>(
+    dir: &Path,
+    fingerprint: u64,
+    generation: u64,
+    state: &T,
+) -> Result<u32, LBSError> {
+    let path = file_path(dir, generation, FULL_EXTENSION);
+    let tmp_path = path.with_extension(format!("{}.{}", FULL_EXTENSION, TMP_EXTENSION));
+
+    let mut payload = Vec::new();
+    state.lbs_write(&mut payload)?;
+
+    let len: u32 = payload
+        .len()
+        .try_into()
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "state is too large"))?;
+
+    let crc = checksum(&payload);
+    let mut file = BufWriter::new(File::create(&tmp_path)?);
+
+    file.write_all(&MAGIC)?;
+    FORMAT_VERSION.lbs_write(&mut file)?;
+    fingerprint.lbs_write(&mut file)?;
+    generation.lbs_write(&mut file)?;
+    len.lbs_write(&mut file)?;
+    crc.lbs_write(&mut file)?;
+    file.write_all(&payload)?;
+
+    let file = file.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, &path)?;
+    sync_dir(dir)?;
+
+    Ok(crc)
+}
+
+/// Reads full snapshot, returns state and CRC-32 of its payload.
+fn read_full<T: LBSRead>(
+    dir: &Path,
+    fingerprint: u64,
+    generation: u64,
+) -> Result<(T, u32), LBSError> {
+    let mut file = std::io::BufReader::new(File::open(file_path(dir, generation, FULL_EXTENSION))?);
+
+    let mut magic = [0; 4];
+    file.read_exact(&mut magic)?;
+
+    if magic != MAGIC {
+        return Err(LBSError::Parsing("not a chain snapshot file".to_string()));
+    }
+
+    let version = u16::lbs_read(&mut file)?;
+
+    if version != FORMAT_VERSION {
+        return Err(LBSError::Parsing(format!(
+            "unsupported chain snapshot version {}",
+            version
+        )));
+    }
+
+    if u64::lbs_read(&mut file)? != fingerprint {
+        return Err(LBSError::SchemaMismatch);
+    }
+
+    if u64::lbs_read(&mut file)? != generation {
+        return Err(LBSError::Parsing(format!(
+            "snapshot file of generation {} is misplaced",
+            generation
+        )));
+    }
+
+    let len = u32::lbs_read(&mut file)?;
+    let crc = u32::lbs_read(&mut file)?;
+
+    let mut payload = Vec::new();
+    file.take(len as u64).read_to_end(&mut payload)?;
+
+    if payload.len() != len as usize || checksum(&payload) != crc {
+        return Err(LBSError::ChecksumMismatch);
+    }
+
+    Ok((from_slice_exact(&payload)?, crc))
+}
+
+/// Creates journal of a generation, starting with a record binding it to the full snapshot.
+fn create_deltas(
+    dir: &Path,
+    fingerprint: u64,
+    generation: u64,
+    crc: u32,
+) -> Result<journal::Writer, LBSError> {
+    let path = file_path(dir, generation, DELTA_EXTENSION);
+    File::create(&path)?;
+
+    let mut deltas = journal::Writer::open(&path)?;
+    deltas.append(&(fingerprint, generation, crc))?;
+    deltas.sync()?;
+    sync_dir(dir)?;
+
+    Ok(deltas)
+}
+
+/// Makes renames and new files durable. Directories can't be opened for that on Windows.
+#[inline]
+fn sync_dir(dir: &Path) -> Result<(), LBSError> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
@@ -1,9 +1,15 @@
 use super::LBSRead;
 use super::LBSWrite;
+use crate::calendar::OrdinalDate;
+use crate::decoder::Decoder;
 use crate::error::LBSError;
+use crate::unit::LBSUnitRead;
+use crate::unit::LBSUnitWrite;
 use chrono::prelude::*;
+use chrono::IsoWeek;
 use std::io::Read;
 use std::io::Write;
+use std::time::SystemTime;
 
 impl LBSWrite for DateTime<Utc> {
     #[inline]
@@ -23,3 +29,96 @@ impl LBSRead for DateTime<Utc> {
             .ok_or(LBSError::InvalidTimestamp)
     }
 }
+
+impl LBSUnitWrite for DateTime<Utc> {
+    #[inline]
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError> {
+        SystemTime::from(*self).lbs_unit_write(w, nanos_per_unit)
+    }
+}
+
+impl LBSUnitRead for DateTime<Utc> {
+    #[inline]
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError> {
+        let since_epoch = SystemTime::lbs_unit_read(r, d, nanos_per_unit)?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|_| LBSError::InvalidTimestamp)?;
+
+        i64::try_from(since_epoch.as_secs())
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, since_epoch.subsec_nanos()))
+            .ok_or(LBSError::InvalidTimestamp)
+    }
+}
+
+/// Written as `i32` year followed by `u16` day of the year, like `calendar::OrdinalDate`.
+impl LBSWrite for NaiveDate {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.year().lbs_write(w)?;
+        (self.ordinal() as u16).lbs_write(w)
+    }
+}
+
+impl LBSRead for NaiveDate {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let date = OrdinalDate::lbs_read(r)?;
+        NaiveDate::from_yo_opt(date.year(), date.ordinal() as u32)
+            .ok_or(LBSError::OutOfRange("NaiveDate"))
+    }
+}
+
+/// Written as `i32` ISO year followed by `u8` week number, starting with 1.
+impl LBSWrite for IsoWeek {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.year().lbs_write(w)?;
+        (self.week() as u8).lbs_write(w)
+    }
+}
+
+impl LBSRead for IsoWeek {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let year = i32::lbs_read(r)?;
+        let week = u8::lbs_read(r)?;
+        NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Mon)
+            .map(|date| date.iso_week())
+            .ok_or(LBSError::OutOfRange("IsoWeek"))
+    }
+}
+
+/// Written as `u8` number of days since Monday.
+impl LBSWrite for Weekday {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        (self.num_days_from_monday() as u8).lbs_write(w)
+    }
+}
+
+impl LBSRead for Weekday {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Weekday::try_from(u8::lbs_read(r)?).map_err(|_| LBSError::OutOfRange("Weekday"))
+    }
+}
+
+/// Written as `u8` number of the month, starting with 1 for January.
+impl LBSWrite for Month {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        (self.number_from_month() as u8).lbs_write(w)
+    }
+}
+
+impl LBSRead for Month {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Month::try_from(u8::lbs_read(r)?).map_err(|_| LBSError::OutOfRange("Month"))
+    }
+}
@@ -0,0 +1,233 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// Length of fragment header: u32 message id, u16 fragment index and u16 fragment count.
+pub const FRAGMENT_HEADER_LEN: usize = 8;
+
+/// Maximum number of incomplete messages kept by `Reassembler::new()`.
+pub const DEFAULT_MAX_PENDING: usize = 64;
+
+/// Maximum number of payload bytes of incomplete messages kept by `Reassembler::new()`.
+pub const DEFAULT_MAX_BYTES: usize = 16 << 20;
+
+/// Splitter of encoded messages into fragments, which fit into datagrams of `mtu` bytes,
+/// like for UDP transports. Fragments are reassembled with `Reassembler`.
+#[derive(Debug, Clone)]
+pub struct Chunker {
+    mtu: usize,
+    next_id: u32,
+}
+
+/// Collector of fragments, which may arrive in any order, into messages.
+///
+/// Fragments of at most `max_pending` incomplete messages are kept, the oldest ones are dropped
+/// when more arrive, so lost fragments don't leak memory. Their payloads take at most
+/// `max_bytes`, the oldest messages are dropped to fit a new fragment, and a message,
+/// which doesn't fit alone, fails with `LBSError::LimitExceeded`.
+#[derive(Debug)]
+pub struct Reassembler<T> {
+    max_pending: usize,
+    max_bytes: usize,
+    bytes: usize,
+    pending: HashMap<u32, Pending>,
+    order: VecDeque<u32>,
+    ty: PhantomData<fn() -> T>,
+}
+
+/// Fragments are kept by index as they arrive, so a claimed count doesn't allocate anything.
+#[derive(Debug)]
+struct Pending {
+    fragments: BTreeMap<u16, Vec<u8>>,
+    count: u16,
+    bytes: usize,
+}
+
+impl Chunker {
+    /// Panics if `mtu` can't fit fragment header and at least one byte of a message.
+    #[inline]
+    pub fn new(mtu: usize) -> Self {
+        assert!(
+            mtu > FRAGMENT_HEADER_LEN,
+            "mtu must exceed fragment header length"
+        );
+        Self { mtu, next_id: 0 }
+    }
+
+    /// Encodes value and splits it into fragments of at most `mtu` bytes, each with header.
+    /// Every message gets the next id, which wraps around.
+    pub fn chunk<T: LBSWrite + ?Sized>(&mut self, value: &T) -> Result<Vec<Vec<u8>>, LBSError> {
+        let mut buf = Vec::new();
+        value.lbs_write(&mut buf)?;
+
+        let payload_len = self.mtu - FRAGMENT_HEADER_LEN;
+        let max_len = payload_len.saturating_mul(u16::MAX as usize);
+
+        if buf.len() > max_len {
+            return Err(LBSError::FrameTooLarge {
+                len: buf.len(),
+                max_len,
+            });
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        // Empty message still takes one fragment
+        let count = buf.len().div_ceil(payload_len).max(1) as u16;
+
+        let fragments = (0..count)
+            .map(|index| {
+                let start = index as usize * payload_len;
+                let payload = &buf[start..buf.len().min(start + payload_len)];
+                let mut fragment = Vec::with_capacity(FRAGMENT_HEADER_LEN + payload.len());
+                fragment.extend_from_slice(&id.to_le_bytes());
+                fragment.extend_from_slice(&index.to_le_bytes());
+                fragment.extend_from_slice(&count.to_le_bytes());
+                fragment.extend_from_slice(payload);
+                fragment
+            })
+            .collect();
+
+        Ok(fragments)
+    }
+}
+
+impl<T: LBSRead> Reassembler<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_max_pending(DEFAULT_MAX_PENDING)
+    }
+
+    #[inline]
+    pub fn with_max_pending(max_pending: usize) -> Self {
+        Self {
+            max_pending,
+            max_bytes: DEFAULT_MAX_BYTES,
+            bytes: 0,
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+            ty: PhantomData,
+        }
+    }
+
+    /// Sets maximum number of payload bytes of incomplete messages.
+    #[inline]
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Consumes fragment and returns decoded message, if it's complete.
+    /// Repeated fragments are ignored. Fails if fragment is malformed or doesn't match previous
+    /// fragments of the message, which is dropped then.
+    pub fn push(&mut self, fragment: &[u8]) -> Result<Option<T>, LBSError> {
+        let mut header = fragment;
+        let id = u32::lbs_read(&mut header)?;
+        let index = u16::lbs_read(&mut header)?;
+        let count = u16::lbs_read(&mut header)?;
+        let payload = header;
+
+        if index >= count {
+            self.remove(id);
+            return Err(LBSError::Parsing(format!(
+                "fragment {} of message {} is out of {}",
+                index, id, count
+            )));
+        }
+
+        // Single fragment messages aren't buffered
+        if count == 1 {
+            self.remove(id);
+            return from_slice_exact(payload).map(Some);
+        }
+
+        if !self.pending.contains_key(&id) {
+            if self.pending.len() >= self.max_pending {
+                if let Some(&oldest) = self.order.front() {
+                    self.remove(oldest);
+                }
+            }
+
+            self.order.push_back(id);
+            self.pending.insert(
+                id,
+                Pending {
+                    fragments: BTreeMap::new(),
+                    count,
+                    bytes: 0,
+                },
+            );
+        }
+
+        let pending = self.pending.get_mut(&id).unwrap();
+
+        if pending.count != count {
+            self.remove(id);
+            return Err(LBSError::Parsing(format!(
+                "fragment count of message {} changed",
+                id
+            )));
+        }
+
+        if pending.fragments.contains_key(&index) {
+            return Ok(None);
+        }
+
+        if pending.bytes + payload.len() > self.max_bytes {
+            self.remove(id);
+            return Err(LBSError::LimitExceeded("reassembler bytes"));
+        }
+
+        // Oldest messages are dropped to fit the fragment
+        while self.bytes + payload.len() > self.max_bytes {
+            let oldest = self.order.iter().copied().find(|i| *i != id).unwrap();
+            self.remove(oldest);
+        }
+
+        let pending = self.pending.get_mut(&id).unwrap();
+        pending.fragments.insert(index, payload.to_vec());
+        pending.bytes += payload.len();
+        self.bytes += payload.len();
+
+        if pending.fragments.len() < pending.count as usize {
+            return Ok(None);
+        }
+
+        let pending = self.remove(id).unwrap();
+        let buf: Vec<u8> = pending.fragments.into_values().flatten().collect();
+        from_slice_exact(&buf).map(Some)
+    }
+
+    /// Number of incomplete messages.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Number of payload bytes of incomplete messages.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.bytes
+    }
+
+    #[inline]
+    fn remove(&mut self, id: u32) -> Option<Pending> {
+        let pending = self.pending.remove(&id)?;
+        self.order.retain(|i| *i != id);
+        self.bytes -= pending.bytes;
+        Some(pending)
+    }
+}
+
+impl<T: LBSRead> Default for Reassembler<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::read_field_count;
+use crate::read::read_field_id;
+use crate::read::read_len;
+use crate::read::skip_bytes;
+use crate::write::write_field_id;
+use crate::write::write_len;
+use std::io::Read;
+use std::io::Take;
+use std::io::Write;
+
+/// Structs, which batches can be encoded column by column (struct of arrays).
+///
+/// Encoded form is row count, column count, then for each column its field id, length in bytes
+/// and values of this field for all rows. Values of a column are encoded as usual, but they are contiguous,
+/// which makes batches of many small structs faster to process and much better compressible.
+/// Unknown columns are skipped by their length.
+///
+/// Implementation can be derived with `#[derive(LBSColumnar)]`.
+pub trait LBSColumnar: Sized {
+    fn lbs_write_columns<W: Write>(
+        rows: &[Self],
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError>;
+
+    fn lbs_read_columns<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Vec<Self>, LBSError>;
+}
+
+/// Writes batch in columnar form.
+#[inline]
+pub fn write<T: LBSColumnar, W: Write>(w: &mut W, rows: &[T]) -> Result<(), LBSError> {
+    T::lbs_write_columns(rows, w, &mut Encoder::new())
+}
+
+/// Reads batch written by `write()`.
+#[inline]
+pub fn read<T: LBSColumnar, R: Read>(r: &mut R) -> Result<Vec<T>, LBSError> {
+    T::lbs_read_columns(r, &mut Decoder::new())
+}
+
+/// Writes a single column, calling `f` for every row. Used by derived implementations.
+pub fn write_column<T, W, F>(
+    w: &mut W,
+    buf: &mut Vec<u8>,
+    id: u16,
+    rows: &[T],
+    mut f: F,
+) -> Result<(), LBSError>
+where
+    W: Write,
+    F: FnMut(&T, &mut Vec<u8>) -> Result<(), LBSError>,
+{
+    buf.clear();
+
+    for row in rows {
+        f(row, buf)?;
+    }
+
+    write_field_id(w, id)?;
+    write_len(w, buf.len())?;
+    Ok(w.write_all(buf)?)
+}
+
+/// Reads columns and calls `f` for each one with a reader limited to its values.
+/// `f` returns false if it doesn't know the column, which is then skipped.
+/// Used by derived implementations.
+pub fn read_columns<R, F>(r: &mut R, mut f: F) -> Result<(), LBSError>
+where
+    R: Read,
+    F: FnMut(u16, &mut Take<&mut R>) -> Result<bool, LBSError>,
+{
+    for _ in 0..read_field_count(r)? {
+        let id = read_field_id(r)?;
+        let len = read_len(r)?;
+        let mut column = r.take(len as u64);
+
+        if !f(id, &mut column)? {
+            skip_bytes(&mut column, len)?;
+        }
+
+        match column.limit() {
+            0 => {}
+            n => return Err(LBSError::TrailingBytes(n as usize).with_field(id)),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::varint;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+
+/// Tag in the low bits of the leading varint.
+const TAG_BITS: u32 = 2;
+const TAG_NANOS: u64 = 0;
+const TAG_SECS: u64 = 1;
+const TAG_FULL: u64 = 2;
+
+/// Largest value, which fits into the leading varint next to the tag.
+const MAX_TAGGED: u64 = u64::MAX >> TAG_BITS;
+
+/// Durations, which can be encoded as a varint instead of fixed 12 bytes.
+///
+/// Leading varint holds a 2-bit tag and total nanoseconds of sub-second or fractional durations,
+/// or seconds of whole-second ones, so 1.5ms takes 4 bytes and an hour takes 2 bytes.
+/// Durations too long for that (over 146 years) are written as the tag followed by the regular
+/// 12 bytes. Encoding is lossless, but differs from the regular one, so both sides must use it.
+pub trait LBSCompactDurationWrite {
+    fn lbs_compact_duration_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSCompactDurationWrite`.
+pub trait LBSCompactDurationRead: Sized {
+    fn lbs_compact_duration_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+impl LBSCompactDurationWrite for Duration {
+    #[inline]
+    fn lbs_compact_duration_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        if self.subsec_nanos() == 0 && self.as_secs() <= MAX_TAGGED {
+            return varint::write_u64(w, self.as_secs() << TAG_BITS | TAG_SECS);
+        }
+
+        match u64::try_from(self.as_nanos()) {
+            Ok(nanos) if nanos <= MAX_TAGGED => varint::write_u64(w, nanos << TAG_BITS | TAG_NANOS),
+            _ => {
+                varint::write_u64(w, TAG_FULL)?;
+                self.lbs_write(w)
+            }
+        }
+    }
+}
+
+impl LBSCompactDurationRead for Duration {
+    #[inline]
+    fn lbs_compact_duration_read<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+        let v = varint::read_u64(r)?;
+
+        match (v & ((1 << TAG_BITS) - 1), v >> TAG_BITS) {
+            (TAG_NANOS, nanos) => Ok(Duration::from_nanos(nanos)),
+            (TAG_SECS, secs) => Ok(Duration::from_secs(secs)),
+            (TAG_FULL, 0) => {
+                let secs = u64::lbs_read(r)?;
+                let nanos = u32::lbs_read(r)?;
+
+                if nanos >= 1_000_000_000 {
+                    return Err(LBSError::InvalidTimestamp);
+                }
+
+                Ok(Duration::new(secs, nanos))
+            }
+            _ => Err(LBSError::InvalidTimestamp),
+        }
+    }
+}
+
+impl<T: LBSCompactDurationWrite> LBSCompactDurationWrite for Option<T> {
+    #[inline]
+    fn lbs_compact_duration_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_compact_duration_write(w)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSCompactDurationRead> LBSCompactDurationRead for Option<T> {
+    #[inline]
+    fn lbs_compact_duration_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_compact_duration_read(r, d)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSCompactDurationWrite> LBSCompactDurationWrite for Vec<T> {
+    #[inline]
+    fn lbs_compact_duration_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for e in self {
+            e.lbs_compact_duration_write(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSCompactDurationRead> LBSCompactDurationRead for Vec<T> {
+    #[inline]
+    fn lbs_compact_duration_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_compact_duration_read(r, d)?);
+        }
+
+        Ok(v)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSCompactDurationWrite, W: Write>(
+    value: &T,
+    w: &mut W,
+    _e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_compact_duration_write(w)
+}
+
+#[inline]
+pub fn read<T: LBSCompactDurationRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_compact_duration_read(r, d)
+}
@@ -0,0 +1,92 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::str::FromStr;
+
+/// Value written as a string, like `Uuid`, `IpNet`, `Fraction` and `Decimal` were written by
+/// former releases, so fields of these types may keep the string encoding until every peer is
+/// upgraded, as encodings can't be mixed.
+///
+/// Written as `value.to_string()` and read with `FromStr`, like `V1<Uuid>` as `"67e55044-..."`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct V1<T>(pub T);
+
+impl<T> V1<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for V1<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for V1<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for V1<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Display> LBSWrite for V1<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.0.to_string().lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.0.to_string().lbs_write_with(w, e)
+    }
+}
+
+impl<T: FromStr> LBSRead for V1<T>
+where
+    T::Err: Display,
+{
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        T::from_str(d.read_str(r)?)
+            .map(Self)
+            .map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        String::lbs_skip(r, d)
+    }
+}
+
+impl<T> LBSSchema for V1<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::String
+    }
+}
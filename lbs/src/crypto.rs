@@ -0,0 +1,97 @@
+use crate::error::LBSError;
+use crate::frame::DEFAULT_MAX_FRAME_LEN;
+use crate::read::from_slice_exact;
+use crate::read::read_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::aead::AeadCore;
+use chacha20poly1305::aead::KeyInit;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::Payload;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::Nonce;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+/// Encrypts encoded value with ChaCha20-Poly1305 and writes it as a frame:
+/// u32 length of the rest, random nonce, ciphertext and tag.
+///
+/// Length prefix is authenticated as associated data, so truncated or extended frames are rejected.
+pub fn encrypt_write<T: LBSWrite + ?Sized, W: Write>(
+    w: &mut W,
+    key: &[u8; KEY_LEN],
+    value: &T,
+) -> Result<(), LBSError> {
+    let mut plaintext = Vec::new();
+    value.lbs_write(&mut plaintext)?;
+
+    let len: u32 = (NONCE_LEN + plaintext.len() + TAG_LEN)
+        .try_into()
+        .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+
+    let prefix = len.to_le_bytes();
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key.into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &prefix,
+            },
+        )
+        .map_err(|_| LBSError::AuthenticationFailed)?;
+
+    w.write_all(&prefix)?;
+    w.write_all(&nonce)?;
+    Ok(w.write_all(&ciphertext)?)
+}
+
+/// Reads a frame written by `encrypt_write()`, which must not exceed `DEFAULT_MAX_FRAME_LEN`.
+#[inline]
+pub fn decrypt_read<T: LBSRead, R: Read>(r: &mut R, key: &[u8; KEY_LEN]) -> Result<T, LBSError> {
+    decrypt_read_with_limit(r, key, DEFAULT_MAX_FRAME_LEN)
+}
+
+/// Reads a frame written by `encrypt_write()`.
+///
+/// Fails with `LBSError::AuthenticationFailed` if key is wrong or frame was tampered with.
+/// Nothing is decoded until frame is authenticated.
+pub fn decrypt_read_with_limit<T: LBSRead, R: Read>(
+    r: &mut R,
+    key: &[u8; KEY_LEN],
+    max_len: usize,
+) -> Result<T, LBSError> {
+    let len = read_len(r)?;
+
+    if len > max_len {
+        return Err(LBSError::FrameTooLarge { len, max_len });
+    }
+
+    if len < NONCE_LEN + TAG_LEN {
+        return Err(LBSError::AuthenticationFailed);
+    }
+
+    let mut frame = vec![0; len];
+    r.read_exact(&mut frame)?;
+
+    let prefix = (len as u32).to_le_bytes();
+    let (nonce, ciphertext) = frame.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(key.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &prefix,
+            },
+        )
+        .map_err(|_| LBSError::AuthenticationFailed)?;
+
+    from_slice_exact(&plaintext)
+}
@@ -0,0 +1,219 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::write::write_len;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Same as `LBSWrite`, but encoding depends on a user context, like protocol version or tenant
+/// configuration.
+///
+/// Derived with `#[lbs(ctx(Type))]` container attribute, instead of `LBSWrite`. Then fields marked
+/// with `#[lbs(ctx)]` receive the context, while others are written as usual.
+pub trait LBSWriteCtx<C: ?Sized> {
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut C) -> Result<(), LBSError>;
+
+    /// Same as `lbs_write_ctx()`, but applies encoding options.
+    /// Containers must propagate this call to their elements.
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        _e: &mut Encoder,
+        ctx: &mut C,
+    ) -> Result<(), LBSError> {
+        self.lbs_write_ctx(w, ctx)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        true
+    }
+}
+
+/// Same as `LBSRead`, but decoding depends on a user context, see `LBSWriteCtx`.
+pub trait LBSReadCtx<C: ?Sized>: Sized {
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut C) -> Result<Self, LBSError>;
+
+    /// Same as `lbs_read_ctx()`, but reuses state of the given decoder.
+    /// Containers must propagate decoder to their elements.
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        _d: &mut Decoder,
+        ctx: &mut C,
+    ) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx(r, ctx)
+    }
+}
+
+#[inline]
+pub fn write_with<T, C, W>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+    ctx: &mut C,
+) -> Result<(), LBSError>
+where
+    T: LBSWriteCtx<C> + ?Sized,
+    C: ?Sized,
+    W: Write,
+{
+    value.lbs_write_ctx_with(w, e, ctx)
+}
+
+#[inline]
+pub fn read_with<T, C, R>(r: &mut R, d: &mut Decoder, ctx: &mut C) -> Result<T, LBSError>
+where
+    T: LBSReadCtx<C>,
+    C: ?Sized,
+    R: Read,
+{
+    d.enter()?;
+    let result = T::lbs_read_ctx_with(r, d, ctx);
+    d.leave();
+    result
+}
+
+impl<T: LBSWriteCtx<C> + ?Sized, C: ?Sized> LBSWriteCtx<C> for Box<T> {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut C) -> Result<(), LBSError> {
+        self.lbs_write_ctx_with(w, &mut Encoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        ctx: &mut C,
+    ) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_ctx_with(w, e, ctx)
+    }
+}
+
+impl<T: LBSWriteCtx<C>, C: ?Sized> LBSWriteCtx<C> for Option<T> {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut C) -> Result<(), LBSError> {
+        self.lbs_write_ctx_with(w, &mut Encoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        ctx: &mut C,
+    ) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            (1_u8).lbs_write(w)?;
+            v.lbs_write_ctx_with(w, e, ctx)
+        } else {
+            (0_u8).lbs_write(w)
+        }
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: LBSWriteCtx<C>, C: ?Sized> LBSWriteCtx<C> for [T] {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut C) -> Result<(), LBSError> {
+        self.lbs_write_ctx_with(w, &mut Encoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        ctx: &mut C,
+    ) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for v in self {
+            v.lbs_write_ctx_with(w, e, ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSWriteCtx<C>, C: ?Sized> LBSWriteCtx<C> for Vec<T> {
+    #[inline]
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut C) -> Result<(), LBSError> {
+        self.lbs_write_ctx_with(w, &mut Encoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_write_ctx_with<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+        ctx: &mut C,
+    ) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_ctx_with(w, e, ctx)
+    }
+}
+
+impl<T: LBSReadCtx<C>, C: ?Sized> LBSReadCtx<C> for Box<T> {
+    #[inline]
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut C) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx_with(r, &mut Decoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        ctx: &mut C,
+    ) -> Result<Self, LBSError> {
+        Ok(Box::new(T::lbs_read_ctx_with(r, d, ctx)?))
+    }
+}
+
+impl<T: LBSReadCtx<C>, C: ?Sized> LBSReadCtx<C> for Option<T> {
+    #[inline]
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut C) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx_with(r, &mut Decoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        ctx: &mut C,
+    ) -> Result<Self, LBSError> {
+        match read_option_tag(r)? {
+            true => Ok(Some(T::lbs_read_ctx_with(r, d, ctx)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<T: LBSReadCtx<C>, C: ?Sized> LBSReadCtx<C> for Vec<T> {
+    #[inline]
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut C) -> Result<Self, LBSError> {
+        Self::lbs_read_ctx_with(r, &mut Decoder::new(), ctx)
+    }
+
+    #[inline]
+    fn lbs_read_ctx_with<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        ctx: &mut C,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::new();
+
+        for _ in 0..l {
+            v.push(T::lbs_read_ctx_with(r, d, ctx)?);
+        }
+
+        Ok(v)
+    }
+}
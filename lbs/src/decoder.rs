@@ -0,0 +1,544 @@
+use crate::encoder::SHARED_STRING_FLAG;
+use crate::error::LBSError;
+use crate::limits::Limits;
+use crate::metrics;
+use crate::metrics::LBSMetrics;
+use crate::metrics::Metrics;
+use crate::read::read_bytes_into;
+use crate::read::read_len;
+use crate::stats::DecodeStats;
+use crate::LBSRead;
+use std::collections::HashSet;
+use std::io::Read;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Reusable decoding state.
+///
+/// Owns scratch buffers which are reused between values, so long-running consumers
+/// don't pay for temporary allocations on every message.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    scratch: Vec<u8>,
+    interner: Option<HashSet<Arc<str>>>,
+    shared_strings: Option<Vec<Arc<str>>>,
+    /// Largest size of the shared string table, if it persists across messages.
+    dictionary: Option<usize>,
+    metrics: Option<Metrics>,
+    version: Option<u16>,
+    stats: Option<DecodeStats>,
+    lenient_bools: bool,
+    time_base: Option<SystemTime>,
+    limits: Limits,
+    /// Nesting of values being read, see `Limits::max_depth`.
+    depth: usize,
+    #[cfg(feature = "unchecked-utf8")]
+    trusted_utf8: bool,
+    #[cfg(feature = "bytes")]
+    source: Option<crate::bytes::Source>,
+}
+
+impl Decoder {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            scratch: Vec::new(),
+            interner: None,
+            shared_strings: None,
+            dictionary: None,
+            metrics: None,
+            version: None,
+            stats: None,
+            lenient_bools: false,
+            time_base: None,
+            limits: Limits::new(),
+            depth: 0,
+            #[cfg(feature = "unchecked-utf8")]
+            trusted_utf8: false,
+            #[cfg(feature = "bytes")]
+            source: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            scratch: Vec::with_capacity(capacity),
+            interner: None,
+            shared_strings: None,
+            dictionary: None,
+            metrics: None,
+            version: None,
+            stats: None,
+            lenient_bools: false,
+            time_base: None,
+            limits: Limits::new(),
+            depth: 0,
+            #[cfg(feature = "unchecked-utf8")]
+            trusted_utf8: false,
+            #[cfg(feature = "bytes")]
+            source: None,
+        }
+    }
+
+    /// Disables UTF-8 validation of decoded strings.
+    ///
+    /// # Safety
+    ///
+    /// Every string decoded by this decoder must be valid UTF-8.
+    /// Use it only for input produced by trusted peers.
+    #[cfg(feature = "unchecked-utf8")]
+    #[inline]
+    pub unsafe fn trust_utf8(&mut self, trusted: bool) {
+        self.trusted_utf8 = trusted;
+    }
+
+    /// Makes bools encoded as bytes other than 0 and 1 to be read as false, instead of failing
+    /// with `LBSError::InvalidBool`. Only for input of producers, which don't follow the format.
+    #[inline]
+    pub fn lenient_bools(&mut self, enabled: bool) {
+        self.lenient_bools = enabled;
+    }
+
+    #[inline]
+    pub(crate) fn lenient_bools_enabled(&self) -> bool {
+        self.lenient_bools
+    }
+
+    /// Enables or disables interning of decoded `Arc<str>` values.
+    ///
+    /// When enabled, identical strings share one allocation, across all values
+    /// decoded by this decoder, until `clear_interned()` is called.
+    #[inline]
+    pub fn intern_strings(&mut self, enabled: bool) {
+        match (enabled, self.interner.is_some()) {
+            (true, false) => self.interner = Some(HashSet::new()),
+            (false, true) => self.interner = None,
+            _ => {}
+        }
+    }
+
+    /// Forgets all interned strings.
+    #[inline]
+    pub fn clear_interned(&mut self) {
+        if let Some(interner) = &mut self.interner {
+            interner.clear();
+        }
+    }
+
+    /// Enables or disables reading of strings written with `Encoder::share_strings()`.
+    ///
+    /// Strings are decoded once per message, references to them are resolved from the table,
+    /// which is reset by `decode()` and `clear_shared()`. Decoded `Arc<str>` values share
+    /// allocations of the table.
+    #[inline]
+    pub fn share_strings(&mut self, enabled: bool) {
+        match (enabled, self.shared_strings.is_some()) {
+            (true, false) => self.shared_strings = Some(Vec::new()),
+            (false, true) => {
+                self.shared_strings = None;
+                self.dictionary = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Enables reading of strings written with `Encoder::dictionary()`, which must have
+    /// the same `max_strings`. Table of shared strings persists across messages and must be
+    /// cleared with `clear_shared()` whenever the encoder's one is.
+    #[inline]
+    pub fn dictionary(&mut self, max_strings: usize) {
+        self.share_strings(true);
+        self.dictionary = Some(max_strings);
+    }
+
+    /// Forgets all shared strings.
+    #[inline]
+    pub fn clear_shared(&mut self) {
+        if let Some(shared) = &mut self.shared_strings {
+            shared.clear();
+        }
+    }
+
+    /// Makes values to be read as of given schema version, so fields tagged with
+    /// `#[lbs(since(<u16>))]` / `#[lbs(removed(<u16>))]` are only required if present in it,
+    /// others are left default when missing. Without version, values are read as of the latest one.
+    #[inline]
+    pub fn for_version(&mut self, version: u16) {
+        self.version = Some(version);
+    }
+
+    /// Source schema version, see `for_version()`.
+    #[inline]
+    pub fn version(&self) -> Option<u16> {
+        self.version
+    }
+
+    /// Limits decoded input, see `Limits`.
+    #[inline]
+    pub fn limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Fails if length of a string or a collection exceeds `Limits::max_len`.
+    #[inline]
+    pub(crate) fn check_len(&self, l: usize) -> Result<usize, LBSError> {
+        match self.limits.max_len {
+            Some(max_len) if l > max_len => Err(LBSError::LimitExceeded("length")),
+            _ => Ok(l),
+        }
+    }
+
+    /// Enters a nested value, failing if it's deeper than `Limits::max_depth`.
+    /// Must be followed by `leave()`, whatever the result of reading the value.
+    #[inline]
+    pub(crate) fn enter(&mut self) -> Result<(), LBSError> {
+        if self
+            .limits
+            .max_depth
+            .is_some_and(|max_depth| self.depth >= max_depth)
+        {
+            return Err(LBSError::LimitExceeded("depth"));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Makes `decode()` and reads of deprecated fields to report to given metrics
+    /// instead of global ones.
+    #[inline]
+    pub fn metrics(&mut self, metrics: Arc<dyn LBSMetrics>) {
+        self.metrics = Some(Metrics(metrics));
+    }
+
+    #[inline]
+    pub(crate) fn metrics_handle(&self) -> &Option<Metrics> {
+        &self.metrics
+    }
+
+    /// Makes decoder to collect statistics of decoded values, like unknown fields,
+    /// until they are taken with `take_stats()`. Nothing is counted without it.
+    #[inline]
+    pub fn collect_stats(&mut self) {
+        self.stats = Some(DecodeStats::default());
+    }
+
+    /// Statistics collected since `collect_stats()`, which stops collecting them.
+    #[inline]
+    pub fn take_stats(&mut self) -> Option<DecodeStats> {
+        self.stats.take()
+    }
+
+    #[inline]
+    pub(crate) fn stats_mut(&mut self) -> Option<&mut DecodeStats> {
+        self.stats.as_mut()
+    }
+
+    /// Decodes a value, reusing this decoder's buffers.
+    #[inline]
+    pub fn decode<T: LBSRead, R: Read>(&mut self, r: &mut R) -> Result<T, LBSError> {
+        self.start_message();
+
+        // Message, which ends exactly at the limit, is read successfully
+        if let Some(max_total_bytes) = self.limits.max_total_bytes {
+            let mut limited = r.take(max_total_bytes.saturating_add(1));
+            let result = self.decode_unlimited(&mut limited);
+
+            return match limited.limit() {
+                0 => Err(LBSError::LimitExceeded("total bytes")),
+                _ => result,
+            };
+        }
+
+        self.decode_unlimited(r)
+    }
+
+    #[inline]
+    fn decode_unlimited<T: LBSRead, R: Read>(&mut self, r: &mut R) -> Result<T, LBSError> {
+        let metrics = metrics::resolve(&self.metrics);
+
+        if metrics.is_none() && !cfg!(feature = "tracing") {
+            return T::lbs_read_with(r, self);
+        }
+
+        let type_name = std::any::type_name::<T>();
+        metrics::measure_read(metrics.as_deref(), type_name, r, |r| {
+            T::lbs_read_with(r, self)
+        })
+    }
+
+    /// Same as `decode()`, but reads from `AsyncRead`. Metrics aren't recorded.
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub async fn decode_async<T, R>(&mut self, r: &mut R) -> Result<T, LBSError>
+    where
+        T: crate::tokio::LBSReadAsync,
+        R: tokio::io::AsyncRead + Unpin + Send,
+    {
+        self.start_message();
+
+        if let Some(max_total_bytes) = self.limits.max_total_bytes {
+            use tokio::io::AsyncReadExt;
+
+            let mut limited = r.take(max_total_bytes.saturating_add(1));
+            let result = T::lbs_read_async(&mut limited, self).await;
+
+            return match limited.limit() {
+                0 => Err(LBSError::LimitExceeded("total bytes")),
+                _ => result,
+            };
+        }
+
+        T::lbs_read_async(r, self).await
+    }
+
+    /// Same as `decode()`, but borrows strings and bytes from the input, see `LBSReadRef`.
+    /// Metrics aren't recorded.
+    #[inline]
+    pub fn decode_ref<'de, T: crate::borrow::LBSReadRef<'de>>(
+        &mut self,
+        r: &mut &'de [u8],
+    ) -> Result<T, LBSError> {
+        self.start_message();
+        T::lbs_read_ref(r, self)
+    }
+
+    /// Forgets state, which is scoped to a single message.
+    #[inline]
+    fn start_message(&mut self) {
+        if self.dictionary.is_none() {
+            self.clear_shared();
+        }
+
+        self.time_base = None;
+        self.depth = 0;
+    }
+
+    /// Base of `#[lbs(relative_time)]` timestamps, if one was read in the current message.
+    #[inline]
+    pub(crate) fn time_base(&self) -> Option<SystemTime> {
+        self.time_base
+    }
+
+    #[inline]
+    pub(crate) fn set_time_base(&mut self, time: SystemTime) {
+        self.time_base = Some(time);
+    }
+
+    /// Reads length-prefixed bytes into the scratch buffer.
+    ///
+    /// Returned slice is valid until the next call which uses the scratch buffer.
+    #[inline]
+    pub fn read_bytes<R: Read>(&mut self, r: &mut R) -> Result<&[u8], LBSError> {
+        let l = self.check_len(read_len(r)?)?;
+        read_bytes_into(r, &mut self.scratch, l)?;
+        Ok(&self.scratch)
+    }
+
+    /// Reads length-prefixed string into the scratch buffer.
+    ///
+    /// Returned slice is valid until the next call which uses the scratch buffer.
+    #[inline]
+    pub fn read_str<R: Read>(&mut self, r: &mut R) -> Result<&str, LBSError> {
+        let l = self.check_len(read_len(r)?)?;
+        self.read_str_exact(r, l)
+    }
+
+    #[inline]
+    pub(crate) fn shares_strings(&self) -> bool {
+        self.shared_strings.is_some()
+    }
+
+    /// Reads string or reference to a shared one. Must be called only if strings are shared.
+    pub(crate) fn read_shared_str<R: Read>(&mut self, r: &mut R) -> Result<Arc<str>, LBSError> {
+        let mut buf = [0; 4];
+        r.read_exact(&mut buf)?;
+
+        match u32::from_le_bytes(buf) {
+            l if l & SHARED_STRING_FLAG != 0 => self.shared_str(l),
+            l => {
+                let l = self.check_len(l as usize)?;
+                read_bytes_into(r, &mut self.scratch, l)?;
+                self.share_scratch_str()
+            }
+        }
+    }
+
+    /// Shared string by its reference, which is the length prefix with `SHARED_STRING_FLAG` set.
+    #[inline]
+    pub(crate) fn shared_str(&self, reference: u32) -> Result<Arc<str>, LBSError> {
+        let index = (reference & !SHARED_STRING_FLAG) as usize;
+        self.shared_strings
+            .as_ref()
+            .and_then(|shared| shared.get(index))
+            .cloned()
+            .ok_or_else(|| LBSError::Parsing(format!("shared string {} is not defined", index)))
+    }
+
+    /// Adds string in the scratch buffer to the shared table.
+    #[inline]
+    pub(crate) fn share_scratch_str(&mut self) -> Result<Arc<str>, LBSError> {
+        let s = Arc::<str>::from(utf8(&self.scratch, self.trusts_utf8())?);
+
+        let max_strings = self.max_shared_strings();
+
+        if let Some(shared) = &mut self.shared_strings {
+            if !s.is_empty() && shared.len() < max_strings {
+                shared.push(s.clone());
+            }
+        }
+
+        Ok(s)
+    }
+
+    /// Largest size of the shared string table, same as of the encoder's one.
+    #[inline]
+    fn max_shared_strings(&self) -> usize {
+        self.dictionary.map_or(SHARED_STRING_FLAG as usize, |max| {
+            max.min(SHARED_STRING_FLAG as usize)
+        })
+    }
+
+    /// Adds string borrowed from input to the shared table.
+    #[inline]
+    pub(crate) fn share_str(&mut self, s: &str) {
+        let max_strings = self.max_shared_strings();
+
+        if let Some(shared) = &mut self.shared_strings {
+            if !s.is_empty() && shared.len() < max_strings {
+                shared.push(Arc::from(s));
+            }
+        }
+    }
+
+    /// Validates string borrowed from input, unless UTF-8 is trusted.
+    #[inline]
+    pub(crate) fn str_from_input<'de>(&self, bytes: &'de [u8]) -> Result<&'de str, LBSError> {
+        utf8(bytes, self.trusts_utf8())
+    }
+
+    /// Reads string of given length into the scratch buffer.
+    #[inline]
+    fn read_str_exact<R: Read>(&mut self, r: &mut R, l: usize) -> Result<&str, LBSError> {
+        read_bytes_into(r, &mut self.scratch, l)?;
+        utf8(&self.scratch, self.trusts_utf8())
+    }
+
+    /// Scratch buffer to be overwritten, like by `read::read_bytes_into()`.
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub(crate) fn scratch_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.scratch
+    }
+
+    /// Reads length-prefixed string as `Arc<str>`, interning it if enabled.
+    #[inline]
+    pub(crate) fn read_arc_str<R: Read>(&mut self, r: &mut R) -> Result<Arc<str>, LBSError> {
+        if self.shares_strings() {
+            return self.read_shared_str(r);
+        }
+
+        let l = self.check_len(read_len(r)?)?;
+        read_bytes_into(r, &mut self.scratch, l)?;
+        self.scratch_arc_str()
+    }
+
+    /// Converts string in the scratch buffer into `Arc<str>`, interning it if enabled.
+    #[inline]
+    pub(crate) fn scratch_arc_str(&mut self) -> Result<Arc<str>, LBSError> {
+        let s = utf8(&self.scratch, self.trusts_utf8())?;
+
+        let Some(interner) = &mut self.interner else {
+            return Ok(Arc::from(s));
+        };
+
+        Ok(match interner.get(s) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned = Arc::<str>::from(s);
+                interner.insert(interned.clone());
+                interned
+            }
+        })
+    }
+
+    /// Sets input, which `Bytes` are sliced from, returning the previous one.
+    #[cfg(feature = "bytes")]
+    #[inline]
+    pub(crate) fn replace_source(
+        &mut self,
+        source: Option<crate::bytes::Source>,
+    ) -> Option<crate::bytes::Source> {
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Reads length-prefixed bytes, slicing them from the input set by `lbs::from_bytes()`
+    /// if they were read from it, or copying them otherwise.
+    #[cfg(feature = "bytes")]
+    pub(crate) fn read_shared_bytes<R: Read>(
+        &mut self,
+        r: &mut R,
+    ) -> Result<bytes::Bytes, LBSError> {
+        let l = self.check_len(read_len(r)?)?;
+        read_bytes_into(r, &mut self.scratch, l)?;
+
+        // Reader may be nested, like a decompressed field, so bytes are checked to be the input ones
+        Ok(match self.source {
+            Some(ref source) => source
+                .last(&self.scratch)
+                .unwrap_or_else(|| bytes::Bytes::copy_from_slice(&self.scratch)),
+            None => bytes::Bytes::copy_from_slice(&self.scratch),
+        })
+    }
+
+    /// Whether UTF-8 validation is disabled by `trust_utf8()`.
+    #[inline]
+    pub(crate) fn trusts_utf8(&self) -> bool {
+        #[cfg(feature = "unchecked-utf8")]
+        return self.trusted_utf8;
+        #[cfg(not(feature = "unchecked-utf8"))]
+        return false;
+    }
+
+    /// Converts decoded bytes into string, validating them unless UTF-8 is trusted.
+    #[inline]
+    pub(crate) fn string_from_utf8(&self, bytes: Vec<u8>) -> Result<String, LBSError> {
+        #[cfg(feature = "unchecked-utf8")]
+        if self.trusted_utf8 {
+            // SAFETY: caller of trust_utf8() guarantees input is valid UTF-8.
+            return Ok(unsafe { String::from_utf8_unchecked(bytes) });
+        }
+
+        String::from_utf8(bytes).map_err(|e| {
+            let message = e.to_string();
+            // Rejected bytes may still be sensitive
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(&mut e.into_bytes());
+            LBSError::Parsing(message)
+        })
+    }
+
+    /// Overwrites scratch buffer with zeros, including its spare capacity.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub(crate) fn zeroize_scratch(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.scratch);
+    }
+}
+
+/// Validates string, unless UTF-8 is trusted.
+#[inline]
+fn utf8(bytes: &[u8], trusted: bool) -> Result<&str, LBSError> {
+    if trusted {
+        // SAFETY: caller of trust_utf8() guarantees input is valid UTF-8.
+        return Ok(unsafe { std::str::from_utf8_unchecked(bytes) });
+    }
+
+    std::str::from_utf8(bytes).map_err(|e| LBSError::Parsing(e.to_string()))
+}
@@ -0,0 +1,61 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::read_len_with;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Codec of `#[lbs(delimited)]` fields, which are preceded by u32 length of their encoded value.
+///
+/// Value is read from its own bytes only, so a malformed one doesn't desynchronize the reader
+/// and fails with `LBSError::TrailingBytes` if it doesn't occupy them. Skipped values, like
+/// fields left out of projection, are consumed by length without decoding.
+/// Length is the only addition to the regular encoding of value, which follows it verbatim.
+#[inline]
+pub fn write<T: LBSWrite, W: Write>(value: &T, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+    let mut buf = Vec::new();
+    value.lbs_write_with(&mut buf, e)?;
+    write_len(w, buf.len())?;
+    Ok(w.write_all(&buf)?)
+}
+
+#[inline]
+pub fn read<T: LBSRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    let buf = read_body(r, d)?;
+    let mut body = buf.as_slice();
+    let value = T::lbs_read_with(&mut body, d)?;
+
+    match body.len() {
+        0 => Ok(value),
+        n => Err(LBSError::TrailingBytes(n)),
+    }
+}
+
+/// Consumes delimited value without decoding it.
+#[inline]
+pub fn skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+    let l = read_len_with(r, d)? as u64;
+
+    if std::io::copy(&mut r.take(l), &mut std::io::sink())? < l {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+/// Reads body of delimited value. Buffer grows as bytes arrive, so untrusted length
+/// doesn't make it allocate more than input has, and length is bounded by `Limits::max_len`.
+#[inline]
+fn read_body<R: Read>(r: &mut R, d: &Decoder) -> Result<Vec<u8>, LBSError> {
+    let l = read_len_with(r, d)?;
+    let mut buf = Vec::new();
+
+    if r.take(l as u64).read_to_end(&mut buf)? < l {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(buf)
+}
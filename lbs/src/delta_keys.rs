@@ -0,0 +1,148 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::read_len_with;
+use crate::varint;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::io::Read;
+use std::io::Write;
+
+/// Unsigned integer keys, which can be encoded as varint deltas.
+pub trait LBSDeltaKey: Copy + Ord + Into<u64> + TryFrom<u64> {}
+
+impl LBSDeltaKey for u8 {}
+impl LBSDeltaKey for u16 {}
+impl LBSDeltaKey for u32 {}
+impl LBSDeltaKey for u64 {}
+
+/// Maps with unsigned integer keys, which can be encoded as ascending key deltas.
+///
+/// Encoded form is length, then entries in ascending key order, each as varint difference
+/// from the previous key (the first one from zero) followed by value. Mostly contiguous keys
+/// take a byte each, instead of the regular full-width little-endian key.
+pub trait LBSDeltaKeysWrite {
+    fn lbs_delta_keys_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSDeltaKeysWrite`.
+pub trait LBSDeltaKeysRead: Sized {
+    fn lbs_delta_keys_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+impl<K: LBSDeltaKey, V: LBSWrite> LBSDeltaKeysWrite for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_delta_keys_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_entries(w, e, self.iter())
+    }
+}
+
+impl<K: LBSDeltaKey, V: LBSRead> LBSDeltaKeysRead for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_delta_keys_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut bm = Self::new();
+        read_entries(r, d, |k, v| {
+            bm.insert(k, v);
+        })?;
+        Ok(bm)
+    }
+}
+
+impl<K: LBSDeltaKey, V: LBSWrite, S> LBSDeltaKeysWrite for HashMap<K, V, S> {
+    #[inline]
+    fn lbs_delta_keys_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by_key(|(k, _)| **k);
+
+        write_len(w, entries.len())?;
+        write_entries(w, e, entries.into_iter())
+    }
+}
+
+impl<K, V, S> LBSDeltaKeysRead for HashMap<K, V, S>
+where
+    K: LBSDeltaKey + Hash,
+    V: LBSRead,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn lbs_delta_keys_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut hm = Self::with_hasher(S::default());
+        read_entries(r, d, |k, v| {
+            hm.insert(k, v);
+        })?;
+        Ok(hm)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSDeltaKeysWrite, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_delta_keys_write(w, e)
+}
+
+#[inline]
+pub fn read<T: LBSDeltaKeysRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_delta_keys_read(r, d)
+}
+
+/// Writes entries, which must be in ascending key order.
+#[inline]
+fn write_entries<'a, K, V, W>(
+    w: &mut W,
+    e: &mut Encoder,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) -> Result<(), LBSError>
+where
+    K: LBSDeltaKey + 'a,
+    V: LBSWrite + 'a,
+    W: Write,
+{
+    let mut prev = 0u64;
+
+    for (k, v) in entries {
+        let k: u64 = (*k).into();
+        varint::write_u64(w, k - prev)?;
+        v.lbs_write_with(w, e)?;
+        prev = k;
+    }
+
+    Ok(())
+}
+
+/// Reads entries, rejecting keys, which are repeated or don't fit into `K`.
+#[inline]
+fn read_entries<K, V, R, F>(r: &mut R, d: &mut Decoder, mut f: F) -> Result<(), LBSError>
+where
+    K: LBSDeltaKey,
+    V: LBSRead,
+    R: Read,
+    F: FnMut(K, V),
+{
+    let mut prev = 0u64;
+
+    for i in 0..read_len_with(r, d)? {
+        let delta = varint::read_u64(r)?;
+
+        if i > 0 && delta == 0 {
+            return Err(LBSError::Parsing(format!("repeated map key {}", prev)));
+        }
+
+        let k = prev.checked_add(delta).ok_or(LBSError::OutOfRange("u64"))?;
+        let key = K::try_from(k).map_err(|_| LBSError::OutOfRange(std::any::type_name::<K>()))?;
+
+        f(key, V::lbs_read_with(r, d)?);
+        prev = k;
+    }
+
+    Ok(())
+}
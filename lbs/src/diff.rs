@@ -0,0 +1,523 @@
+use crate::error::LBSError;
+use crate::read::read_field_count;
+use crate::read::read_field_id;
+use crate::read::read_len;
+use crate::write::write_field_id;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::io::Read;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+const TAG_REPLACE: u8 = 0;
+const TAG_PATCH: u8 = 1;
+
+/// Types, which changes can be encoded as a patch.
+///
+/// Structs (via `#[derive(LBSDiff)]`) record only changed fields by their ids, recursing into them.
+/// Options and enums recurse if both sides have the same variant, vectors patch changed elements
+/// and append new ones, maps patch changed values and record removed and inserted entries.
+/// Other types are replaced as a whole. Patch is only valid for the value it was made against.
+pub trait LBSDiff {
+    /// Appends patch transforming `self` into `other` to `w`.
+    /// Returns false and appends nothing if values are equal.
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError>;
+
+    /// Applies patch produced by `lbs_diff()`.
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError>;
+}
+
+/// Encoded difference between two values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patch(Vec<u8>);
+
+impl Patch {
+    /// True if values were equal.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for Patch {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl LBSWrite for Patch {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.0.lbs_write(w)
+    }
+}
+
+impl LBSRead for Patch {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self(Vec::lbs_read(r)?))
+    }
+}
+
+/// Returns patch transforming `a` into `b`.
+#[inline]
+pub fn diff<T: LBSDiff + ?Sized>(a: &T, b: &T) -> Result<Patch, LBSError> {
+    let mut buf = Vec::new();
+    a.lbs_diff(b, &mut buf)?;
+    Ok(Patch(buf))
+}
+
+/// Applies patch produced by `diff(&a, &b)` and returns `b`.
+#[inline]
+pub fn apply<T: LBSDiff>(mut a: T, patch: &Patch) -> Result<T, LBSError> {
+    if patch.is_empty() {
+        return Ok(a);
+    }
+
+    let mut r = patch.as_bytes();
+    a.lbs_apply(&mut r)?;
+
+    match r.len() {
+        0 => Ok(a),
+        n => Err(LBSError::TrailingBytes(n)),
+    }
+}
+
+/// Writes field count placeholder, calls `f` to diff fields and patches the count.
+/// Used by derived implementations.
+#[inline]
+pub fn diff_fields<F>(w: &mut Vec<u8>, f: F) -> Result<bool, LBSError>
+where
+    F: FnOnce(&mut Vec<u8>, &mut u16) -> Result<(), LBSError>,
+{
+    let start = w.len();
+    let mut count = 0;
+    w.extend_from_slice(&[0, 0]);
+    f(w, &mut count)?;
+
+    if count == 0 {
+        w.truncate(start);
+        return Ok(false);
+    }
+
+    w[start..start + 2].copy_from_slice(&count.to_le_bytes());
+    Ok(true)
+}
+
+/// Writes id and patch of a single field, if it has changed.
+/// Used by derived implementations.
+#[inline]
+pub fn diff_field<T: LBSDiff + ?Sized>(
+    w: &mut Vec<u8>,
+    count: &mut u16,
+    id: u16,
+    a: &T,
+    b: &T,
+) -> Result<(), LBSError> {
+    let start = w.len();
+    write_field_id(w, id)?;
+
+    if a.lbs_diff(b, w)? {
+        *count += 1;
+    } else {
+        w.truncate(start);
+    }
+
+    Ok(())
+}
+
+/// Reads patched field ids and calls `f` to apply each one.
+/// Used by derived implementations.
+#[inline]
+pub fn apply_fields<R, F>(r: &mut R, mut f: F) -> Result<(), LBSError>
+where
+    R: Read,
+    F: FnMut(&mut R, u16) -> Result<(), LBSError>,
+{
+    for _ in 0..read_field_count(r)? {
+        let id = read_field_id(r)?;
+        f(r, id)?;
+    }
+    Ok(())
+}
+
+/// Error for a patched field id, which is unknown to the type.
+#[inline]
+pub fn unknown_field(id: u16) -> LBSError {
+    LBSError::Parsing(format!("unknown field {} in patch", id))
+}
+
+/// Records `b` as a whole. Used by derived implementations for enums with different variants.
+#[inline]
+pub fn diff_replace<T: LBSWrite + ?Sized>(w: &mut Vec<u8>, b: &T) -> Result<bool, LBSError> {
+    w.push(TAG_REPLACE);
+    b.lbs_write(w)?;
+    Ok(true)
+}
+
+/// Records patch of the inner value. Used by derived implementations for enums with the same variant.
+#[inline]
+pub fn diff_nested<T: LBSDiff + ?Sized>(w: &mut Vec<u8>, a: &T, b: &T) -> Result<bool, LBSError> {
+    let start = w.len();
+    w.push(TAG_PATCH);
+
+    if a.lbs_diff(b, w)? {
+        Ok(true)
+    } else {
+        w.truncate(start);
+        Ok(false)
+    }
+}
+
+/// Reads tag written by `diff_replace()` or `diff_nested()`. Returns true for replacement.
+#[inline]
+pub fn read_replace_tag<R: Read>(r: &mut R) -> Result<bool, LBSError> {
+    match u8::lbs_read(r)? {
+        TAG_REPLACE => Ok(true),
+        TAG_PATCH => Ok(false),
+        _ => Err(LBSError::UnexpectedVariant),
+    }
+}
+
+macro_rules! impl_diff_replace {
+    ($($t:ty),*) => {
+        $(
+            impl LBSDiff for $t {
+                #[inline]
+                fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+                    if self == other {
+                        return Ok(false);
+                    }
+                    other.lbs_write(w)?;
+                    Ok(true)
+                }
+
+                #[inline]
+                fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+                    *self = Self::lbs_read(r)?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_diff_replace!(u8, u16, u32, u64, usize, u128);
+impl_diff_replace!(i8, i16, i32, i64, isize, i128);
+impl_diff_replace!(f32, f64, (), bool, char, String);
+impl_diff_replace!(Duration, SystemTime, Ipv4Addr, Ipv6Addr, IpAddr);
+
+impl<T: LBSWrite + LBSRead + Eq + Hash, S: BuildHasher + Default> LBSDiff for HashSet<T, S> {
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        if self == other {
+            return Ok(false);
+        }
+        other.lbs_write(w)?;
+        Ok(true)
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        *self = Self::lbs_read(r)?;
+        Ok(())
+    }
+}
+
+impl<T: LBSWrite + LBSRead + Ord> LBSDiff for BTreeSet<T> {
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        if self == other {
+            return Ok(false);
+        }
+        other.lbs_write(w)?;
+        Ok(true)
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        *self = Self::lbs_read(r)?;
+        Ok(())
+    }
+}
+
+impl<T: LBSDiff + ?Sized> LBSDiff for Box<T> {
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        self.as_ref().lbs_diff(other.as_ref(), w)
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        self.as_mut().lbs_apply(r)
+    }
+}
+
+impl<T: LBSDiff + LBSWrite + LBSRead> LBSDiff for Option<T> {
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        match (self, other) {
+            (Some(a), Some(b)) => diff_nested(w, a, b),
+            (None, None) => Ok(false),
+            _ => diff_replace(w, other),
+        }
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        match (read_replace_tag(r)?, self.as_mut()) {
+            (true, _) => *self = Self::lbs_read(r)?,
+            (false, Some(v)) => v.lbs_apply(r)?,
+            (false, None) => return Err(LBSError::UnexpectedVariant),
+        }
+        Ok(())
+    }
+}
+
+/// Vector patch is new length, changed elements of the common prefix as (index, patch) pairs
+/// and appended elements.
+impl<T: LBSDiff + LBSWrite + LBSRead> LBSDiff for Vec<T> {
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        let start = w.len();
+        write_len(w, other.len())?;
+
+        let count_at = w.len();
+        let mut count: u32 = 0;
+        write_len(w, 0)?;
+
+        for (i, (a, b)) in self.iter().zip(other).enumerate() {
+            let element_at = w.len();
+            write_len(w, i)?;
+
+            if a.lbs_diff(b, w)? {
+                count += 1;
+            } else {
+                w.truncate(element_at);
+            }
+        }
+
+        if count == 0 && self.len() == other.len() {
+            w.truncate(start);
+            return Ok(false);
+        }
+
+        w[count_at..count_at + 4].copy_from_slice(&count.to_le_bytes());
+
+        for element in other.iter().skip(self.len()) {
+            element.lbs_write(w)?;
+        }
+
+        Ok(true)
+    }
+
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        let l = read_len(r)?;
+        self.truncate(l);
+
+        for _ in 0..read_len(r)? {
+            let i = read_len(r)?;
+            self.get_mut(i)
+                .ok_or_else(|| LBSError::Parsing(format!("patched index {} is out of range", i)))?
+                .lbs_apply(r)?;
+        }
+
+        while self.len() < l {
+            self.push(T::lbs_read(r)?);
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, S> LBSDiff for HashMap<K, V, S>
+where
+    K: LBSWrite + LBSRead + Eq + Hash,
+    V: LBSDiff + LBSWrite + LBSRead,
+    S: BuildHasher,
+{
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        diff_map(self, other, self.iter(), other.iter(), w)
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        apply_map(self, r)
+    }
+}
+
+impl<K, V> LBSDiff for BTreeMap<K, V>
+where
+    K: LBSWrite + LBSRead + Ord,
+    V: LBSDiff + LBSWrite + LBSRead,
+{
+    #[inline]
+    fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> Result<bool, LBSError> {
+        diff_map(self, other, self.iter(), other.iter(), w)
+    }
+
+    #[inline]
+    fn lbs_apply<R: Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        apply_map(self, r)
+    }
+}
+
+/// Operations shared by maps for diffing.
+trait Map<K, V> {
+    fn get(&self, k: &K) -> Option<&V>;
+    fn get_mut(&mut self, k: &K) -> Option<&mut V>;
+    fn remove(&mut self, k: &K) -> Option<V>;
+    fn insert(&mut self, k: K, v: V) -> Option<V>;
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> Map<K, V> for HashMap<K, V, S> {
+    #[inline]
+    fn get(&self, k: &K) -> Option<&V> {
+        HashMap::get(self, k)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        HashMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn remove(&mut self, k: &K) -> Option<V> {
+        HashMap::remove(self, k)
+    }
+
+    #[inline]
+    fn insert(&mut self, k: K, v: V) -> Option<V> {
+        HashMap::insert(self, k, v)
+    }
+}
+
+impl<K: Ord, V> Map<K, V> for BTreeMap<K, V> {
+    #[inline]
+    fn get(&self, k: &K) -> Option<&V> {
+        BTreeMap::get(self, k)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn remove(&mut self, k: &K) -> Option<V> {
+        BTreeMap::remove(self, k)
+    }
+
+    #[inline]
+    fn insert(&mut self, k: K, v: V) -> Option<V> {
+        BTreeMap::insert(self, k, v)
+    }
+}
+
+/// Map patch is removed keys, then (key, patch) pairs of changed values, then inserted entries.
+fn diff_map<'a, K, V, M>(
+    a: &M,
+    b: &M,
+    a_entries: impl Iterator<Item = (&'a K, &'a V)>,
+    b_entries: impl Iterator<Item = (&'a K, &'a V)>,
+    w: &mut Vec<u8>,
+) -> Result<bool, LBSError>
+where
+    K: LBSWrite + 'a,
+    V: LBSDiff + LBSWrite + 'a,
+    M: Map<K, V>,
+{
+    let (mut removed, mut changed, mut inserted) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut removed_count, mut changed_count, mut inserted_count) = (0, 0, 0);
+
+    for (k, _) in a_entries {
+        if b.get(k).is_none() {
+            k.lbs_write(&mut removed)?;
+            removed_count += 1;
+        }
+    }
+
+    for (k, v) in b_entries {
+        match a.get(k) {
+            Some(old) => {
+                let start = changed.len();
+                k.lbs_write(&mut changed)?;
+
+                if old.lbs_diff(v, &mut changed)? {
+                    changed_count += 1;
+                } else {
+                    changed.truncate(start);
+                }
+            }
+            None => {
+                k.lbs_write(&mut inserted)?;
+                v.lbs_write(&mut inserted)?;
+                inserted_count += 1;
+            }
+        }
+    }
+
+    if removed_count == 0 && changed_count == 0 && inserted_count == 0 {
+        return Ok(false);
+    }
+
+    write_len(w, removed_count)?;
+    w.write_all(&removed)?;
+    write_len(w, changed_count)?;
+    w.write_all(&changed)?;
+    write_len(w, inserted_count)?;
+    w.write_all(&inserted)?;
+    Ok(true)
+}
+
+fn apply_map<K, V, M, R>(m: &mut M, r: &mut R) -> Result<(), LBSError>
+where
+    K: LBSRead,
+    V: LBSDiff + LBSRead,
+    M: Map<K, V>,
+    R: Read,
+{
+    let missing = || LBSError::Parsing("patched key is missing".to_string());
+
+    for _ in 0..read_len(r)? {
+        m.remove(&K::lbs_read(r)?).ok_or_else(missing)?;
+    }
+
+    for _ in 0..read_len(r)? {
+        let k = K::lbs_read(r)?;
+        m.get_mut(&k).ok_or_else(missing)?.lbs_apply(r)?;
+    }
+
+    for _ in 0..read_len(r)? {
+        let k = K::lbs_read(r)?;
+        let v = V::lbs_read(r)?;
+
+        if m.insert(k, v).is_some() {
+            return Err(LBSError::Parsing("inserted key already exists".to_string()));
+        }
+    }
+
+    Ok(())
+}
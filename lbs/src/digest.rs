@@ -0,0 +1,106 @@
+use crate::error::LBSError;
+use crate::LBSWrite;
+use digest::Digest;
+use digest::Output;
+use digest::Update;
+use std::io::Read;
+use std::io::Write;
+
+/// Writer adapter, which computes hash of everything written through it.
+#[derive(Debug)]
+pub struct HashingWriter<W, H> {
+    inner: W,
+    hasher: H,
+}
+
+impl<W, H> HashingWriter<W, H> {
+    #[inline]
+    pub fn new(inner: W, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    #[inline]
+    pub fn into_parts(self) -> (W, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<W: Write, H: Update> Write for HashingWriter<W, H> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader adapter, which computes hash of everything read through it.
+#[derive(Debug)]
+pub struct HashingReader<R, H> {
+    inner: R,
+    hasher: H,
+}
+
+impl<R, H> HashingReader<R, H> {
+    #[inline]
+    pub fn new(inner: R, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    #[inline]
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<R: Read, H: Update> Read for HashingReader<R, H> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Computes hash of encoded value without buffering it.
+#[inline]
+pub fn hash<D: Digest + Update, T: LBSWrite + ?Sized>(value: &T) -> Result<Output<D>, LBSError> {
+    let mut w = HashingWriter::new(std::io::sink(), D::new());
+    value.lbs_write(&mut w)?;
+    Ok(w.hasher.finalize())
+}
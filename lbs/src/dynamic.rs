@@ -0,0 +1,914 @@
+use crate::capture::CaptureReader;
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::indexed::IndexWriter;
+use crate::read::from_slice_exact;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::schema::EnumSchema;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::schema::StructSchema;
+use crate::varint::LBSVarintRead;
+use crate::varint::LBSVarintWrite;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::Write;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Same as in derived implementations.
+const PACKED_BOOLS_ID: u16 = u16::MAX;
+/// Codec of `#[lbs(varint)]` fields, which is mapped natively, unless registered as a kind.
+const VARINT_CODEC: &str = "varint";
+
+/// Value of any type, which may be inspected and modified by generic code, like middleware, then
+/// handed back to typed code.
+///
+/// Values are mapped from and to encoded form by schema, see `Value::read()` and `Value::write()`,
+/// or from and to types, which describe themselves, see `Value::from_typed()` and `Value::to_typed()`.
+/// Integers are widened to 64 bits, unless they are 128 bits wide, floats are widened to `f64`,
+/// and they are narrowed back on write.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    F64(f64),
+    Char(char),
+    String(String),
+    Duration(Duration),
+    SystemTime(SystemTime),
+    IpAddr(IpAddr),
+    Option(Option<Box<Value>>),
+    /// Elements of vectors, slices, sets, tuples and ranges.
+    List(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    /// Present fields by id. Absent optional fields are written as missing.
+    Struct(BTreeMap<u16, Value>),
+    Enum(u16, Option<Box<Value>>),
+    /// Value of a custom kind registered in `Kinds`, by kind name, as encoded bytes.
+    Custom(String, Vec<u8>),
+}
+
+impl Value {
+    /// Maps typed value.
+    #[inline]
+    pub fn from_typed<T: LBSWrite + LBSSchema + ?Sized>(value: &T) -> Result<Self, LBSError> {
+        let mut buf = Vec::new();
+        value.lbs_write(&mut buf)?;
+        let mut bytes = buf.as_slice();
+        let value = Self::read(&mut bytes, &T::lbs_schema())?;
+
+        match bytes.len() {
+            0 => Ok(value),
+            n => Err(LBSError::TrailingBytes(n)),
+        }
+    }
+
+    /// Maps back to typed value.
+    #[inline]
+    pub fn to_typed<T: LBSRead + LBSSchema>(&self) -> Result<T, LBSError> {
+        let mut buf = Vec::new();
+        self.write(&mut buf, &T::lbs_schema())?;
+        from_slice_exact(&buf)
+    }
+
+    /// Reads value encoded as described by schema, like one fetched from a registry.
+    /// Values with custom codecs, except `varint`, and opaque types can't be mapped.
+    #[inline]
+    pub fn read<R: Read>(r: &mut R, schema: &Schema) -> Result<Self, LBSError> {
+        Self::read_with(r, schema, &Kinds::new())
+    }
+
+    /// Same as `read()`, but opaque types and codec fields of registered kinds are mapped into
+    /// `Value::Custom`.
+    #[inline]
+    pub fn read_with<R: Read>(r: &mut R, schema: &Schema, kinds: &Kinds) -> Result<Self, LBSError> {
+        Self::read_with_decoder(r, schema, kinds, &mut Decoder::new())
+    }
+
+    /// Same as `read_with()`, but lengths and nesting of structs and enums, including recursive
+    /// ones, are bounded by `Limits` of the decoder, so untrusted input can't exhaust the stack.
+    #[inline]
+    pub fn read_with_decoder<R: Read>(
+        r: &mut R,
+        schema: &Schema,
+        kinds: &Kinds,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let mut r: Input = r;
+        Mapper::new(kinds, d).read(&mut r, schema)
+    }
+
+    /// Writes value encoded as described by schema. Fails if value doesn't match the schema.
+    #[inline]
+    pub fn write<W: Write>(&self, w: &mut W, schema: &Schema) -> Result<(), LBSError> {
+        self.write_with(w, schema, &Kinds::new())
+    }
+
+    /// Same as `write()`, but `Value::Custom` of registered kinds may be written.
+    #[inline]
+    pub fn write_with<W: Write>(
+        &self,
+        w: &mut W,
+        schema: &Schema,
+        kinds: &Kinds,
+    ) -> Result<(), LBSError> {
+        Mapper::new(kinds, &mut Decoder::new()).write(self, w, schema)
+    }
+
+    /// Field of a struct by id.
+    #[inline]
+    pub fn field(&self, id: u16) -> Option<&Value> {
+        match self {
+            Self::Struct(fields) => fields.get(&id),
+            _ => None,
+        }
+    }
+
+    /// Mutable field of a struct by id.
+    #[inline]
+    pub fn field_mut(&mut self, id: u16) -> Option<&mut Value> {
+        match self {
+            Self::Struct(fields) => fields.get_mut(&id),
+            _ => None,
+        }
+    }
+}
+
+/// Writes value as JSON, pretty-printed with `{:#}`, for logs and debugging tools.
+///
+/// Structs are objects keyed by field ids, enums are objects of a single id key or ids of unit
+/// variants, options are their values or `null`. Durations and times are seconds, possibly
+/// since Unix epoch, non-finite floats are `null` and custom values are objects of a single
+/// kind key and hex bytes.
+///
+/// Values are decoded only by schema, as encoded ones carry no type tags: decode messages of
+/// other versions by their own schema, like one from a registry, or see `inspect` to annotate
+/// bytes which fail to decode.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let indent = f.alternate().then_some(0);
+        write_json(f, self, indent)
+    }
+}
+
+type DecodeFn = dyn Fn(&mut dyn Read) -> Result<Vec<u8>, LBSError> + Send + Sync;
+type EncodeFn = dyn Fn(&[u8], &mut dyn Write) -> Result<(), LBSError> + Send + Sync;
+
+/// Custom kinds of values, which aren't known to dynamic mapping natively, like opaque types of
+/// other crates or fields with codecs.
+///
+/// Kind is named after an opaque type, like `MacAddr`, or after a codec, like `sparse`, and is
+/// used wherever such type or codec is met. Its values are kept as `Value::Custom` with encoded
+/// bytes, which are written back as they are, so tooling can pass them through unchanged.
+#[derive(Clone, Default)]
+pub struct Kinds {
+    kinds: HashMap<String, Kind>,
+}
+
+#[derive(Clone)]
+struct Kind {
+    decode: Arc<DecodeFn>,
+    encode: Arc<EncodeFn>,
+}
+
+impl Kinds {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers kind of values of `T`. Bytes are validated by decoding them on write.
+    pub fn register<T: LBSRead + 'static>(&mut self, name: impl Into<String>) -> &mut Self {
+        self.register_with(
+            name,
+            |r| {
+                let mut r = CaptureReader::with_limit(r, usize::MAX);
+                T::lbs_read(&mut r)?;
+                Ok(r.captured().to_vec())
+            },
+            |bytes, w| {
+                from_slice_exact::<T>(bytes)?;
+                Ok(w.write_all(bytes)?)
+            },
+        )
+    }
+
+    /// Registers kind with callbacks. `decode` reads a single value and returns its bytes,
+    /// `encode` writes bytes of a value, failing if they aren't valid.
+    pub fn register_with<D, E>(
+        &mut self,
+        name: impl Into<String>,
+        decode: D,
+        encode: E,
+    ) -> &mut Self
+    where
+        D: Fn(&mut dyn Read) -> Result<Vec<u8>, LBSError> + Send + Sync + 'static,
+        E: Fn(&[u8], &mut dyn Write) -> Result<(), LBSError> + Send + Sync + 'static,
+    {
+        let kind = Kind {
+            decode: Arc::new(decode),
+            encode: Arc::new(encode),
+        };
+
+        self.kinds.insert(name.into(), kind);
+        self
+    }
+
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.kinds.contains_key(name)
+    }
+}
+
+impl std::fmt::Debug for Kinds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.kinds.keys()).finish()
+    }
+}
+
+/// Readers are type-erased, as nested indexed structs would wrap them into each other endlessly.
+type Input<'a> = &'a mut dyn Read;
+
+struct Mapper<'k, 'd> {
+    /// Structs and enums met so far, to resolve `Schema::Ref`.
+    named: HashMap<String, Schema>,
+    kinds: &'k Kinds,
+    /// Limits of values, which are read.
+    d: &'d mut Decoder,
+}
+
+impl<'k, 'd> Mapper<'k, 'd> {
+    #[inline]
+    fn new(kinds: &'k Kinds, d: &'d mut Decoder) -> Self {
+        Self {
+            named: HashMap::new(),
+            kinds,
+            d,
+        }
+    }
+
+    /// Reads a value nested into a struct or an enum, tracking depth like typed reads.
+    #[inline]
+    fn nested<F>(&mut self, f: F) -> Result<Value, LBSError>
+    where
+        F: FnOnce(&mut Self) -> Result<Value, LBSError>,
+    {
+        self.d.enter()?;
+        let result = f(self);
+        self.d.leave();
+        result
+    }
+
+    fn read(&mut self, r: &mut Input, schema: &Schema) -> Result<Value, LBSError> {
+        let value = match schema {
+            Schema::Unit => Value::Unit,
+            Schema::Bool => Value::Bool(bool::lbs_read(r)?),
+            Schema::U8 => Value::U64(u8::lbs_read(r)? as u64),
+            Schema::U16 => Value::U64(u16::lbs_read(r)? as u64),
+            Schema::U32 => Value::U64(u32::lbs_read(r)? as u64),
+            Schema::U64 => Value::U64(u64::lbs_read(r)?),
+            Schema::U128 => Value::U128(u128::lbs_read(r)?),
+            Schema::I8 => Value::I64(i8::lbs_read(r)? as i64),
+            Schema::I16 => Value::I64(i16::lbs_read(r)? as i64),
+            Schema::I32 => Value::I64(i32::lbs_read(r)? as i64),
+            Schema::I64 => Value::I64(i64::lbs_read(r)?),
+            Schema::I128 => Value::I128(i128::lbs_read(r)?),
+            Schema::F32 => Value::F64(f32::lbs_read(r)? as f64),
+            Schema::F64 => Value::F64(f64::lbs_read(r)?),
+            Schema::Char => Value::Char(char::lbs_read(r)?),
+            Schema::String => Value::String(String::lbs_read_with(r, self.d)?),
+            Schema::Duration => Value::Duration(Duration::lbs_read(r)?),
+            Schema::SystemTime => Value::SystemTime(SystemTime::lbs_read(r)?),
+            Schema::Ipv4Addr => Value::IpAddr(IpAddr::V4(LBSRead::lbs_read(r)?)),
+            Schema::Ipv6Addr => Value::IpAddr(IpAddr::V6(LBSRead::lbs_read(r)?)),
+            Schema::IpAddr => Value::IpAddr(IpAddr::lbs_read(r)?),
+            Schema::Option(inner) => match read_option_tag(r)? {
+                true => Value::Option(Some(Box::new(self.read(r, inner)?))),
+                false => Value::Option(None),
+            },
+            Schema::List(inner) => {
+                let len = read_len_with(r, self.d)?;
+                let mut elements = Vec::new();
+
+                for _ in 0..len {
+                    elements.push(self.read(r, inner)?);
+                }
+
+                Value::List(elements)
+            }
+            Schema::Map(kv) => {
+                let len = read_len_with(r, self.d)?;
+                let mut entries = Vec::new();
+
+                for _ in 0..len {
+                    entries.push((self.read(r, &kv.0)?, self.read(r, &kv.1)?));
+                }
+
+                Value::Map(entries)
+            }
+            Schema::Tuple(elements) => Value::List(
+                elements
+                    .iter()
+                    .map(|e| self.read(r, e))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Schema::Range(inner) => Value::List(vec![self.read(r, inner)?, self.read(r, inner)?]),
+            Schema::Struct(s) => {
+                self.named
+                    .entry(s.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                self.nested(|mapper| match s.indexed {
+                    true => mapper.read_indexed_struct(r, s),
+                    false => mapper.read_struct(r, s),
+                })?
+            }
+            Schema::Enum(e) => {
+                self.named
+                    .entry(e.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                let id = match e.compact {
+                    true => crate::varint::read_u16(r)?,
+                    false => u16::lbs_read(r)?,
+                };
+
+                let variant = e
+                    .variants
+                    .iter()
+                    .find(|v| v.id == id)
+                    .ok_or(LBSError::UnexpectedVariant)?;
+
+                let payload = match variant.schema {
+                    Some(ref payload) => {
+                        Some(Box::new(self.nested(|mapper| mapper.read(r, payload))?))
+                    }
+                    None => None,
+                };
+
+                Value::Enum(id, payload)
+            }
+            Schema::Ref(name) => {
+                let schema = self.resolve(name)?;
+                self.read(r, &schema)?
+            }
+            Schema::Opaque(name) => match self.kinds.kinds.get(name) {
+                Some(kind) => Value::Custom(name.clone(), (kind.decode)(r)?),
+                None => return Err(opaque(name)),
+            },
+        };
+
+        Ok(value)
+    }
+
+    fn read_struct(&mut self, r: &mut Input, s: &StructSchema) -> Result<Value, LBSError> {
+        let count = match s.compact {
+            true => crate::read::read_compact_field_count(r)?,
+            false => crate::read::read_field_count(r)?,
+        };
+
+        let mut fields = BTreeMap::new();
+        let mut prev_id = None;
+
+        for _ in 0..count {
+            let id = match s.compact {
+                true => crate::read::read_compact_field_id(r, &mut prev_id)?,
+                false => crate::read::read_field_id(r)?,
+            };
+
+            // Packed bools are expanded into separate fields
+            if id == PACKED_BOOLS_ID && s.pack_bools {
+                crate::read::read_packed_bools(r, |id, value| {
+                    if s.fields.iter().any(|f| f.id == id) {
+                        fields.insert(id, Value::Bool(value));
+                    }
+                })
+                .map_err(|e| e.with_field(id))?;
+                continue;
+            }
+
+            let value = self.read_field(r, s, id).map_err(|e| e.with_field(id))?;
+            fields.insert(id, value);
+        }
+
+        Ok(Value::Struct(fields))
+    }
+
+    fn read_indexed_struct(&mut self, r: &mut Input, s: &StructSchema) -> Result<Value, LBSError> {
+        let mut fields = BTreeMap::new();
+
+        // Unknown fields are skipped by their length
+        crate::indexed::read_fields(r, |id, r| {
+            let mut r: Input = r;
+
+            if !s.fields.iter().any(|f| f.id == id) {
+                return Ok(false);
+            }
+
+            let value = self
+                .read_field(&mut r, s, id)
+                .map_err(|e| e.with_field(id))?;
+            fields.insert(id, value);
+            Ok(true)
+        })?;
+
+        Ok(Value::Struct(fields))
+    }
+
+    fn read_field(&mut self, r: &mut Input, s: &StructSchema, id: u16) -> Result<Value, LBSError> {
+        let field = s
+            .fields
+            .iter()
+            .find(|f| f.id == id)
+            .ok_or_else(|| LBSError::Parsing(format!("unknown field {} of {}", id, s.name)))?;
+
+        match field.codec {
+            Some(ref codec) => match self.kinds.kinds.get(codec) {
+                Some(kind) => Ok(Value::Custom(codec.clone(), (kind.decode)(r)?)),
+                None if codec == VARINT_CODEC => self.read_varint(r, &field.schema),
+                None => Err(codec_field(codec)),
+            },
+            None => self.read(r, &field.schema),
+        }
+    }
+
+    /// Reads value written by `#[lbs(varint)]`, see `varint::LBSVarintRead`.
+    fn read_varint(&mut self, r: &mut Input, schema: &Schema) -> Result<Value, LBSError> {
+        let value = match schema {
+            Schema::U8 | Schema::I8 => self.read(r, schema)?,
+            Schema::U16 => Value::U64(u16::lbs_varint_read(r, self.d)? as u64),
+            Schema::U32 => Value::U64(u32::lbs_varint_read(r, self.d)? as u64),
+            Schema::U64 => Value::U64(u64::lbs_varint_read(r, self.d)?),
+            Schema::I16 => Value::I64(i16::lbs_varint_read(r, self.d)? as i64),
+            Schema::I32 => Value::I64(i32::lbs_varint_read(r, self.d)? as i64),
+            Schema::I64 => Value::I64(i64::lbs_varint_read(r, self.d)?),
+            Schema::String => Value::String(String::lbs_varint_read(r, self.d)?),
+            Schema::Option(inner) => match read_option_tag(r)? {
+                true => Value::Option(Some(Box::new(self.read_varint(r, inner)?))),
+                false => Value::Option(None),
+            },
+            Schema::List(inner) => {
+                let len = usize::lbs_varint_read(r, self.d)?;
+                let len = self.d.check_len(len)?;
+                let mut elements = Vec::new();
+
+                for _ in 0..len {
+                    elements.push(self.read_varint(r, inner)?);
+                }
+
+                Value::List(elements)
+            }
+            _ => return Err(codec_field(VARINT_CODEC)),
+        };
+
+        Ok(value)
+    }
+
+    fn write_field<W: Write>(
+        &mut self,
+        value: &Value,
+        w: &mut W,
+        field: &crate::schema::FieldSchema,
+    ) -> Result<(), LBSError> {
+        match field.codec {
+            Some(ref codec) => match value {
+                Value::Custom(kind, bytes) if kind == codec => self.write_custom(kind, bytes, w),
+                _ if codec == VARINT_CODEC && !self.kinds.contains(codec) => {
+                    self.write_varint(value, w, &field.schema)
+                }
+                _ => Err(codec_field(codec)),
+            },
+            None => self.write(value, w, &field.schema),
+        }
+    }
+
+    /// Writes value as `#[lbs(varint)]` does, see `varint::LBSVarintWrite`.
+    fn write_varint<W: Write>(
+        &mut self,
+        value: &Value,
+        w: &mut W,
+        schema: &Schema,
+    ) -> Result<(), LBSError> {
+        match (schema, value) {
+            (Schema::U8 | Schema::I8, _) => self.write(value, w, schema),
+            (Schema::U16, Value::U64(v)) => narrow::<u16, _>(*v)?.lbs_varint_write(w),
+            (Schema::U32, Value::U64(v)) => narrow::<u32, _>(*v)?.lbs_varint_write(w),
+            (Schema::U64, Value::U64(v)) => v.lbs_varint_write(w),
+            (Schema::I16, Value::I64(v)) => narrow::<i16, _>(*v)?.lbs_varint_write(w),
+            (Schema::I32, Value::I64(v)) => narrow::<i32, _>(*v)?.lbs_varint_write(w),
+            (Schema::I64, Value::I64(v)) => v.lbs_varint_write(w),
+            (Schema::String, Value::String(v)) => v.lbs_varint_write(w),
+            (Schema::Option(_), Value::Option(None)) => 0u8.lbs_write(w),
+            (Schema::Option(inner), Value::Option(Some(v))) => {
+                1u8.lbs_write(w)?;
+                self.write_varint(v, w, inner)
+            }
+            (Schema::List(inner), Value::List(elements)) => {
+                elements.len().lbs_varint_write(w)?;
+                elements
+                    .iter()
+                    .try_for_each(|e| self.write_varint(e, w, inner))
+            }
+            _ => Err(codec_field(VARINT_CODEC)),
+        }
+    }
+
+    #[inline]
+    fn write_custom<W: Write>(&self, kind: &str, bytes: &[u8], w: &mut W) -> Result<(), LBSError> {
+        match self.kinds.kinds.get(kind) {
+            Some(k) => (k.encode)(bytes, w),
+            None => Err(LBSError::Parsing(format!("unknown kind {}", kind))),
+        }
+    }
+
+    fn write<W: Write>(
+        &mut self,
+        value: &Value,
+        w: &mut W,
+        schema: &Schema,
+    ) -> Result<(), LBSError> {
+        match (schema, value) {
+            (Schema::Unit, Value::Unit) => Ok(()),
+            (Schema::Bool, Value::Bool(v)) => v.lbs_write(w),
+            (Schema::U8, Value::U64(v)) => narrow::<u8, _>(*v)?.lbs_write(w),
+            (Schema::U16, Value::U64(v)) => narrow::<u16, _>(*v)?.lbs_write(w),
+            (Schema::U32, Value::U64(v)) => narrow::<u32, _>(*v)?.lbs_write(w),
+            (Schema::U64, Value::U64(v)) => v.lbs_write(w),
+            (Schema::U128, Value::U128(v)) => v.lbs_write(w),
+            (Schema::I8, Value::I64(v)) => narrow::<i8, _>(*v)?.lbs_write(w),
+            (Schema::I16, Value::I64(v)) => narrow::<i16, _>(*v)?.lbs_write(w),
+            (Schema::I32, Value::I64(v)) => narrow::<i32, _>(*v)?.lbs_write(w),
+            (Schema::I64, Value::I64(v)) => v.lbs_write(w),
+            (Schema::I128, Value::I128(v)) => v.lbs_write(w),
+            (Schema::F32, Value::F64(v)) => (*v as f32).lbs_write(w),
+            (Schema::F64, Value::F64(v)) => v.lbs_write(w),
+            (Schema::Char, Value::Char(v)) => v.lbs_write(w),
+            (Schema::String, Value::String(v)) => v.lbs_write(w),
+            (Schema::Duration, Value::Duration(v)) => v.lbs_write(w),
+            (Schema::SystemTime, Value::SystemTime(v)) => v.lbs_write(w),
+            (Schema::Ipv4Addr, Value::IpAddr(IpAddr::V4(v))) => v.lbs_write(w),
+            (Schema::Ipv6Addr, Value::IpAddr(IpAddr::V6(v))) => v.lbs_write(w),
+            (Schema::IpAddr, Value::IpAddr(v)) => v.lbs_write(w),
+            (Schema::Option(_), Value::Option(None)) => 0u8.lbs_write(w),
+            (Schema::Option(inner), Value::Option(Some(v))) => {
+                1u8.lbs_write(w)?;
+                self.write(v, w, inner)
+            }
+            (Schema::List(inner), Value::List(elements)) => {
+                write_len(w, elements.len())?;
+                elements.iter().try_for_each(|e| self.write(e, w, inner))
+            }
+            (Schema::Map(kv), Value::Map(entries)) => {
+                write_len(w, entries.len())?;
+                entries.iter().try_for_each(|(k, v)| {
+                    self.write(k, w, &kv.0)?;
+                    self.write(v, w, &kv.1)
+                })
+            }
+            (Schema::Tuple(schemas), Value::List(elements)) if schemas.len() == elements.len() => {
+                schemas
+                    .iter()
+                    .zip(elements)
+                    .try_for_each(|(s, e)| self.write(e, w, s))
+            }
+            (Schema::Range(inner), Value::List(elements)) if elements.len() == 2 => {
+                self.write(&elements[0], w, inner)?;
+                self.write(&elements[1], w, inner)
+            }
+            (Schema::Struct(s), Value::Struct(fields)) => {
+                self.named
+                    .entry(s.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                match s.indexed {
+                    true => self.write_indexed_struct(fields, w, s),
+                    false => self.write_struct(fields, w, s),
+                }
+            }
+            (Schema::Enum(e), Value::Enum(id, payload)) => {
+                self.named
+                    .entry(e.name.clone())
+                    .or_insert_with(|| schema.clone());
+                self.write_enum(*id, payload.as_deref(), w, e)
+            }
+            (Schema::Ref(name), value) => {
+                let schema = self.resolve(name)?;
+                self.write(value, w, &schema)
+            }
+            (Schema::Opaque(name), Value::Custom(kind, bytes)) if kind == name => {
+                self.write_custom(kind, bytes, w)
+            }
+            (Schema::Opaque(name), _) => Err(opaque(name)),
+            (schema, value) => Err(LBSError::Parsing(format!(
+                "value {:?} doesn't match schema {:?}",
+                value, schema
+            ))),
+        }
+    }
+
+    /// Fields are written in the same order and form as by derived implementations.
+    fn write_struct<W: Write>(
+        &mut self,
+        fields: &BTreeMap<u16, Value>,
+        w: &mut W,
+        s: &StructSchema,
+    ) -> Result<(), LBSError> {
+        let mut schemas: Vec<_> = s.fields.iter().collect();
+
+        if s.compact {
+            schemas.sort_by_key(|f| f.id);
+        }
+
+        check_unknown_fields(fields, s)?;
+
+        let packed = |f: &crate::schema::FieldSchema| {
+            s.pack_bools && f.schema == Schema::Bool && f.codec.is_none()
+        };
+
+        let written: Vec<_> = schemas
+            .iter()
+            .filter(|f| !packed(f))
+            .filter_map(|f| match fields.get(&f.id) {
+                Some(Value::Option(None)) | None => None,
+                Some(value) => Some((*f, value)),
+            })
+            .collect();
+
+        let packed: Vec<_> = schemas.iter().filter(|f| packed(f)).collect();
+        let count = written.len() + !packed.is_empty() as usize;
+        let count =
+            u16::try_from(count).map_err(|_| LBSError::Parsing("too many fields".to_string()))?;
+
+        match s.compact {
+            true => crate::write::write_compact_field_count(w, count)?,
+            false => crate::write::write_field_count(w, count)?,
+        }
+
+        let mut prev_id = 0u16;
+
+        for (field, value) in written {
+            match s.compact {
+                true => crate::write::write_compact_field_id(w, &mut prev_id, field.id)?,
+                false => crate::write::write_field_id(w, field.id)?,
+            }
+
+            self.write_field(value, w, field)
+                .map_err(|e| e.with_field(field.id))?;
+        }
+
+        if !packed.is_empty() {
+            let base = packed.iter().map(|f| f.id).min().unwrap();
+            let max = packed.iter().map(|f| f.id).max().unwrap();
+            let mut bits = vec![false; (max - base) as usize + 1];
+
+            for f in &packed {
+                bits[(f.id - base) as usize] = match fields.get(&f.id) {
+                    Some(Value::Bool(v)) => *v,
+                    None => false,
+                    Some(other) => {
+                        return Err(LBSError::Parsing(format!(
+                            "value {:?} doesn't match schema {:?}",
+                            other,
+                            Schema::Bool
+                        ))
+                        .with_field(f.id))
+                    }
+                };
+            }
+
+            match s.compact {
+                true => crate::write::write_compact_field_id(w, &mut prev_id, PACKED_BOOLS_ID)?,
+                false => crate::write::write_field_id(w, PACKED_BOOLS_ID)?,
+            }
+
+            crate::write::write_packed_bools(w, base, &bits)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_indexed_struct<W: Write>(
+        &mut self,
+        fields: &BTreeMap<u16, Value>,
+        w: &mut W,
+        s: &StructSchema,
+    ) -> Result<(), LBSError> {
+        check_unknown_fields(fields, s)?;
+
+        let mut schemas: Vec<_> = s.fields.iter().collect();
+        schemas.sort_by_key(|f| f.id);
+
+        let mut index = IndexWriter::new();
+
+        for field in schemas {
+            let value = match fields.get(&field.id) {
+                Some(Value::Option(None)) | None => continue,
+                Some(value) => value,
+            };
+
+            index.field(field.id, |w| {
+                self.write_field(value, w, field)
+                    .map_err(|e| e.with_field(field.id))
+            })?;
+        }
+
+        index.finish(w)
+    }
+
+    fn write_enum<W: Write>(
+        &mut self,
+        id: u16,
+        payload: Option<&Value>,
+        w: &mut W,
+        e: &EnumSchema,
+    ) -> Result<(), LBSError> {
+        let variant = e
+            .variants
+            .iter()
+            .find(|v| v.id == id)
+            .ok_or(LBSError::UnexpectedVariant)?;
+
+        match e.compact {
+            true => crate::varint::write_u16(w, id)?,
+            false => crate::write::write_field_id(w, id)?,
+        }
+
+        match (&variant.schema, payload) {
+            (Some(schema), Some(payload)) => self.write(payload, w, schema),
+            (None, None) => Ok(()),
+            _ => Err(LBSError::Parsing(format!(
+                "payload of variant {} doesn't match schema",
+                variant.name
+            ))),
+        }
+    }
+
+    #[inline]
+    fn resolve(&self, name: &str) -> Result<Schema, LBSError> {
+        self.named
+            .get(name)
+            .cloned()
+            .ok_or_else(|| LBSError::Parsing(format!("unknown type {}", name)))
+    }
+}
+
+#[inline]
+fn check_unknown_fields(fields: &BTreeMap<u16, Value>, s: &StructSchema) -> Result<(), LBSError> {
+    match fields
+        .keys()
+        .find(|id| !s.fields.iter().any(|f| f.id == **id))
+    {
+        Some(id) => Err(LBSError::Parsing(format!(
+            "unknown field {} of {}",
+            id, s.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[inline]
+fn narrow<T: TryFrom<V>, V: Copy + std::fmt::Display>(v: V) -> Result<T, LBSError> {
+    T::try_from(v).map_err(|_| {
+        LBSError::Parsing(format!(
+            "{} is out of range of {}",
+            v,
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+#[inline]
+fn opaque(name: &str) -> LBSError {
+    LBSError::Parsing(format!("opaque {} can't be mapped", name))
+}
+
+#[inline]
+fn codec_field(codec: &str) -> LBSError {
+    LBSError::Parsing(format!("field is encoded by {}", codec))
+}
+
+fn write_json<W: std::fmt::Write>(
+    w: &mut W,
+    value: &Value,
+    indent: Option<usize>,
+) -> std::fmt::Result {
+    match value {
+        Value::Unit | Value::Option(None) => w.write_str("null"),
+        Value::Bool(v) => write!(w, "{}", v),
+        Value::U64(v) => write!(w, "{}", v),
+        Value::I64(v) => write!(w, "{}", v),
+        Value::U128(v) => write!(w, "{}", v),
+        Value::I128(v) => write!(w, "{}", v),
+        Value::F64(v) if v.is_finite() => write!(w, "{}", v),
+        Value::F64(_) => w.write_str("null"),
+        Value::Char(v) => write_json_str(w, v.encode_utf8(&mut [0; 4])),
+        Value::String(v) => write_json_str(w, v),
+        Value::Duration(v) => write!(w, "{}", v.as_secs_f64()),
+        Value::SystemTime(v) => match v.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since) => write!(w, "{}", since.as_secs_f64()),
+            Err(e) => write!(w, "-{}", e.duration().as_secs_f64()),
+        },
+        Value::IpAddr(v) => write_json_str(w, &v.to_string()),
+        Value::Option(Some(v)) => write_json(w, v, indent),
+        Value::List(elements) => write_json_seq(w, ('[', ']'), elements, indent, |w, e, indent| {
+            write_json(w, e, indent)
+        }),
+        Value::Map(entries) => {
+            write_json_seq(w, ('[', ']'), entries, indent, |w, (k, v), indent| {
+                write_json_seq(w, ('[', ']'), &[k, v], indent, |w, e, indent| {
+                    write_json(w, e, indent)
+                })
+            })
+        }
+        Value::Struct(fields) => {
+            write_json_seq(w, ('{', '}'), fields, indent, |w, (id, v), indent| {
+                write_json_key(w, &id.to_string(), indent)?;
+                write_json(w, v, indent)
+            })
+        }
+        Value::Enum(id, None) => write!(w, "{}", id),
+        Value::Enum(id, Some(v)) => write_json_seq(w, ('{', '}'), [v], indent, |w, v, indent| {
+            write_json_key(w, &id.to_string(), indent)?;
+            write_json(w, v, indent)
+        }),
+        Value::Custom(kind, bytes) => {
+            write_json_seq(w, ('{', '}'), [bytes], indent, |w, bytes, indent| {
+                write_json_key(w, kind, indent)?;
+                w.write_char('"')?;
+                bytes.iter().try_for_each(|b| write!(w, "{:02x}", b))?;
+                w.write_char('"')
+            })
+        }
+    }
+}
+
+/// Writes elements in brackets, each on its own line if pretty-printed.
+fn write_json_seq<W: std::fmt::Write, I: IntoIterator>(
+    w: &mut W,
+    (open, close): (char, char),
+    elements: I,
+    indent: Option<usize>,
+    mut write_element: impl FnMut(&mut W, I::Item, Option<usize>) -> std::fmt::Result,
+) -> std::fmt::Result {
+    w.write_char(open)?;
+    let inner = indent.map(|i| i + 2);
+    let mut empty = true;
+
+    for (i, e) in elements.into_iter().enumerate() {
+        if i > 0 {
+            w.write_char(',')?;
+        }
+
+        if let Some(inner) = inner {
+            write!(w, "\n{:1$}", "", inner)?;
+        }
+
+        write_element(w, e, inner)?;
+        empty = false;
+    }
+
+    if let (Some(indent), false) = (indent, empty) {
+        write!(w, "\n{:1$}", "", indent)?;
+    }
+
+    w.write_char(close)
+}
+
+fn write_json_key<W: std::fmt::Write>(
+    w: &mut W,
+    key: &str,
+    indent: Option<usize>,
+) -> std::fmt::Result {
+    write_json_str(w, key)?;
+    w.write_str(if indent.is_some() { ": " } else { ":" })
+}
+
+fn write_json_str<W: std::fmt::Write>(w: &mut W, s: &str) -> std::fmt::Result {
+    w.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+
+    w.write_char('"')
+}
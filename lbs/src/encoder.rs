@@ -0,0 +1,289 @@
+use crate::error::LBSError;
+use crate::metrics;
+use crate::metrics::LBSMetrics;
+use crate::metrics::Metrics;
+use crate::write::CountingWriter;
+use crate::LBSWrite;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Set in place of string length, when the rest of it is an index into the shared string table.
+pub(crate) const SHARED_STRING_FLAG: u32 = 1 << 31;
+
+/// Encoding options.
+///
+/// Threaded through `LBSWrite::lbs_write_with()`, so options apply to nested values too.
+#[derive(Debug, Default, Clone)]
+pub struct Encoder {
+    canonical_nan: bool,
+    canonical_zero: bool,
+    shared_strings: Option<HashMap<Box<str>, u32>>,
+    /// Largest size of the shared string table, if it persists across messages.
+    dictionary: Option<usize>,
+    metrics: Option<Metrics>,
+    version: Option<u16>,
+    time_base: Option<SystemTime>,
+    /// Droppable fields of this priority or lower are omitted.
+    dropped: Option<u8>,
+    /// Lowest priority of droppable fields written since `encode()` started.
+    kept: Option<u8>,
+}
+
+impl Encoder {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            canonical_nan: false,
+            canonical_zero: false,
+            shared_strings: None,
+            dictionary: None,
+            metrics: None,
+            version: None,
+            time_base: None,
+            dropped: None,
+            kept: None,
+        }
+    }
+
+    /// Makes every NaN to be written as the same canonical NaN,
+    /// so semantically equal values produce identical bytes.
+    #[inline]
+    pub fn canonicalize_nan(&mut self, enabled: bool) -> &mut Self {
+        self.canonical_nan = enabled;
+        self
+    }
+
+    /// Makes negative zero to be written as positive zero.
+    #[inline]
+    pub fn canonicalize_zero(&mut self, enabled: bool) -> &mut Self {
+        self.canonical_zero = enabled;
+        self
+    }
+
+    /// Makes repeated strings to be written once and referenced by index thereafter.
+    ///
+    /// First occurrence of a string is written as usual and appended to the table,
+    /// later ones are written as its index with the high bit of length set.
+    /// Decoder must have `Decoder::share_strings()` enabled to read such values.
+    /// Table is shared by all string types and is reset by `encode()` and `clear_shared()`,
+    /// so it spans a single message.
+    #[inline]
+    pub fn share_strings(&mut self, enabled: bool) -> &mut Self {
+        match (enabled, self.shared_strings.is_some()) {
+            (true, false) => self.shared_strings = Some(HashMap::new()),
+            (false, true) => {
+                self.shared_strings = None;
+                self.dictionary = None;
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Makes table of shared strings to persist across messages, as a dictionary of a long-lived
+    /// stream, so strings repeated in many messages, like host names, are written once per
+    /// stream. Table keeps at most `max_strings` strings, which are the first ones written,
+    /// later strings are always written as usual.
+    ///
+    /// Decoder must have `Decoder::dictionary()` enabled with the same `max_strings` and must
+    /// read every message in the order they were written. Lost or failed message desynchronizes
+    /// tables, then both sides must `clear_shared()` or start a new stream.
+    #[inline]
+    pub fn dictionary(&mut self, max_strings: usize) -> &mut Self {
+        self.share_strings(true);
+        self.dictionary = Some(max_strings);
+        self
+    }
+
+    #[inline]
+    pub fn shares_strings(&self) -> bool {
+        self.shared_strings.is_some()
+    }
+
+    /// Forgets all shared strings.
+    #[inline]
+    pub fn clear_shared(&mut self) {
+        if let Some(shared) = &mut self.shared_strings {
+            shared.clear();
+        }
+    }
+
+    /// Makes values to be written as of given schema version, so fields tagged with
+    /// `#[lbs(since(<u16>))]` / `#[lbs(removed(<u16>))]` are written only if present in it.
+    /// Without version, values are written as of the latest one.
+    #[inline]
+    pub fn for_version(&mut self, version: u16) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Target schema version, see `for_version()`.
+    #[inline]
+    pub fn version(&self) -> Option<u16> {
+        self.version
+    }
+
+    /// Makes `encode()` to report to given metrics instead of global ones.
+    #[inline]
+    pub fn metrics(&mut self, metrics: Arc<dyn LBSMetrics>) -> &mut Self {
+        self.metrics = Some(Metrics(metrics));
+        self
+    }
+
+    /// Encodes a value with this encoder's options and returns number of bytes written.
+    #[inline]
+    pub fn encode<T: LBSWrite + ?Sized, W: Write>(
+        &mut self,
+        value: &T,
+        w: &mut W,
+    ) -> Result<usize, LBSError> {
+        if self.dictionary.is_none() {
+            self.clear_shared();
+        }
+
+        self.time_base = None;
+        self.kept = None;
+
+        let metrics = metrics::resolve(&self.metrics);
+
+        if metrics.is_none() && !cfg!(feature = "tracing") {
+            let mut w = CountingWriter::new(w);
+            value.lbs_write_with(&mut w, self)?;
+            return Ok(w.count());
+        }
+
+        let type_name = std::any::type_name::<T>();
+        metrics::measure_write(metrics.as_deref(), type_name, w, |w| {
+            value.lbs_write_with(w, self)
+        })
+    }
+
+    /// Encodes a value into at most `budget` bytes and returns number of bytes written.
+    ///
+    /// If encoding is larger, fields with `#[lbs(droppable(priority = <u8>))]` are omitted,
+    /// lower priorities first, until it fits. Nothing is written, if it doesn't fit even without
+    /// droppable fields, which fails with `LBSError::LimitExceeded`. Value is encoded once per
+    /// dropped priority, so state like shared strings is kept only from the attempt, which fits.
+    pub fn encode_within<T: LBSWrite + ?Sized, W: Write>(
+        &mut self,
+        value: &T,
+        w: &mut W,
+        budget: usize,
+    ) -> Result<usize, LBSError> {
+        let mut buf = Vec::with_capacity(budget.min(value.lbs_size_hint()));
+        let mut attempt = self.clone();
+
+        loop {
+            buf.clear();
+            attempt.encode(value, &mut buf)?;
+
+            if buf.len() <= budget {
+                break;
+            }
+
+            let lowest = attempt.kept;
+            attempt = self.clone();
+            attempt.dropped = match lowest {
+                Some(lowest) => Some(lowest),
+                None => return Err(LBSError::LimitExceeded("budget")),
+            };
+        }
+
+        w.write_all(&buf)?;
+        attempt.dropped = self.dropped;
+        *self = attempt;
+        Ok(buf.len())
+    }
+
+    /// Omits droppable fields of given priority or lower, like `encode_within()` when over budget.
+    #[inline]
+    pub fn drop_fields(&mut self, priority: Option<u8>) -> &mut Self {
+        self.dropped = priority;
+        self
+    }
+
+    /// Whether droppable field of given priority is written, which is then remembered.
+    #[doc(hidden)]
+    #[inline]
+    pub fn keeps(&mut self, priority: u8) -> bool {
+        if self.dropped.is_some_and(|dropped| priority <= dropped) {
+            return false;
+        }
+
+        self.kept = Some(self.kept.map_or(priority, |lowest| lowest.min(priority)));
+        true
+    }
+
+    /// Takes state left by encoding a chunk of elements with a copy of this encoder.
+    /// Returns false, if the chunk depends on elements before it, as relative timestamps do,
+    /// so it can't be encoded in parallel.
+    #[inline]
+    #[cfg(feature = "rayon")]
+    pub(crate) fn merge_chunk(&mut self, chunk: &Encoder) -> bool {
+        if chunk.time_base != self.time_base {
+            return false;
+        }
+
+        if let Some(priority) = chunk.kept {
+            self.kept = Some(self.kept.map_or(priority, |lowest| lowest.min(priority)));
+        }
+
+        true
+    }
+
+    /// Sets base of `#[lbs(relative_time)]` timestamps and returns the previous one.
+    #[inline]
+    pub(crate) fn replace_time_base(&mut self, time: SystemTime) -> Option<SystemTime> {
+        self.time_base.replace(time)
+    }
+
+    /// Writes string, or reference to it if it was shared already.
+    #[inline]
+    pub(crate) fn write_str<W: Write>(&mut self, s: &str, w: &mut W) -> Result<(), LBSError> {
+        let Some(shared) = &mut self.shared_strings else {
+            return s.lbs_write(w);
+        };
+
+        if let Some(index) = shared.get(s) {
+            return (SHARED_STRING_FLAG | index).lbs_write(w);
+        }
+
+        if s.len() >= SHARED_STRING_FLAG as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "string is too long to be shared",
+            )
+            .into());
+        }
+
+        let max_strings = self.dictionary.map_or(SHARED_STRING_FLAG as usize, |max| {
+            max.min(SHARED_STRING_FLAG as usize)
+        });
+
+        if !s.is_empty() && shared.len() < max_strings {
+            shared.insert(s.into(), shared.len() as u32);
+        }
+
+        s.lbs_write(w)
+    }
+
+    #[inline]
+    pub(crate) fn f32(&self, v: f32) -> f32 {
+        match v {
+            _ if self.canonical_nan && v.is_nan() => f32::NAN,
+            _ if self.canonical_zero && v == 0.0 => 0.0,
+            _ => v,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn f64(&self, v: f64) -> f64 {
+        match v {
+            _ if self.canonical_nan && v.is_nan() => f64::NAN,
+            _ if self.canonical_zero && v == 0.0 => 0.0,
+            _ => v,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::capture::Capture;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,27 +11,91 @@ pub enum LBSError {
     InvalidTimestamp,
     #[error("invalid char")]
     InvalidChar,
+    #[error("invalid bool {0}")]
+    InvalidBool(u8),
+    #[error("invalid varint")]
+    InvalidVarint,
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("frame of {len} bytes exceeds maximum of {max_len} bytes")]
+    FrameTooLarge { len: usize, max_len: usize },
+    #[error("checksum mismatch")]
+    ChecksumMismatch,
+    #[error("schema mismatch")]
+    SchemaMismatch,
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u16),
+    #[error("version {0} is registered for another type")]
+    VersionConflict(u16),
+    #[error("authentication failed")]
+    AuthenticationFailed,
+    #[error("more than one field of oneof group {0} is set")]
+    OneofConflict(&'static str),
+    #[error("{0} can't be encoded as fixed point with scale {1}")]
+    FixedPointOutOfRange(f64, u64),
+    #[error("value is out of range of {0}")]
+    OutOfRange(&'static str),
+    #[error("{0} limit exceeded")]
+    LimitExceeded(&'static str),
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error("{0}")]
     Parsing(String),
     #[error("field {0}: {1}")]
     WithField(u16, Box<LBSError>),
+    #[error("{1} (at byte {offset}, {len} bytes captured)", offset = .0.offset, len = .0.bytes.len())]
+    Captured(Capture, Box<LBSError>),
 }
 
 impl LBSError {
     pub fn is_eof(&self) -> bool {
         match self {
             Self::Io(e) => e.kind() == std::io::ErrorKind::UnexpectedEof,
-            Self::WithField(_, e) => e.is_eof(),
+            Self::WithField(_, e) | Self::Captured(_, e) => e.is_eof(),
             _ => false,
         }
     }
 
+    /// Wraps error into context of the field with given id.
+    /// Errors of nested values are wrapped by every enclosing field, see `field_path()`.
     pub fn with_field(self, field_id: u16) -> Self {
+        Self::WithField(field_id, self.into())
+    }
+
+    /// Ids of fields from the outermost to the innermost one, in which error occurred.
+    pub fn field_path(&self) -> Vec<u16> {
+        let mut path = Vec::new();
+        let mut e = self.without_capture();
+
+        while let Self::WithField(id, inner) = e {
+            path.push(*id);
+            e = inner;
+        }
+
+        path
+    }
+
+    /// Error without field context.
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::WithField(_, e) | Self::Captured(_, e) => e.root_cause(),
+            e => e,
+        }
+    }
+
+    /// Input consumed before the error, if it was read with `lbs::capture::CaptureReader`.
+    pub fn capture(&self) -> Option<&Capture> {
+        match self {
+            Self::Captured(capture, _) => Some(capture),
+            _ => None,
+        }
+    }
+
+    /// Error without captured input.
+    pub fn without_capture(&self) -> &Self {
         match self {
-            Self::WithField(..) => self,
-            other => Self::WithField(field_id, other.into()),
+            Self::Captured(_, e) => e,
+            e => e,
         }
     }
 }
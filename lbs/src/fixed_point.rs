@@ -0,0 +1,152 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::varint;
+use crate::write::write_len;
+use std::io::Read;
+use std::io::Write;
+
+/// Floats, which can be encoded as integers scaled by a constant, like cents of a price.
+///
+/// Value is multiplied by scale, rounded to the nearest integer and written as zigzag varint,
+/// so encoding doesn't depend on float representation and small values take few bytes.
+/// Decoded value is the integer divided by scale, which encodes back to the same integer,
+/// while it fits into mantissa (2^24 for f32 and 2^53 for f64).
+/// Scale isn't written, so reader must use the same one, and the varint replaces the regular
+/// 4 or 8 bytes of IEEE 754 representation.
+pub trait LBSFixedPointWrite {
+    fn lbs_fixed_point_write<W: Write>(&self, w: &mut W, scale: u64) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSFixedPointWrite`.
+pub trait LBSFixedPointRead: Sized {
+    fn lbs_fixed_point_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        scale: u64,
+    ) -> Result<Self, LBSError>;
+}
+
+impl LBSFixedPointWrite for f64 {
+    #[inline]
+    fn lbs_fixed_point_write<W: Write>(&self, w: &mut W, scale: u64) -> Result<(), LBSError> {
+        let scaled = (self * scale as f64).round();
+
+        // i64::MAX isn't representable as f64, so upper bound is exclusive
+        if !(scaled >= i64::MIN as f64 && scaled < i64::MAX as f64) {
+            return Err(LBSError::FixedPointOutOfRange(*self, scale));
+        }
+
+        let scaled = scaled as i64;
+        varint::write_u64(w, ((scaled << 1) ^ (scaled >> 63)) as u64)
+    }
+}
+
+impl LBSFixedPointRead for f64 {
+    #[inline]
+    fn lbs_fixed_point_read<R: Read>(
+        r: &mut R,
+        _d: &mut Decoder,
+        scale: u64,
+    ) -> Result<Self, LBSError> {
+        let zigzag = varint::read_u64(r)?;
+        let scaled = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        Ok(scaled as f64 / scale as f64)
+    }
+}
+
+impl LBSFixedPointWrite for f32 {
+    #[inline]
+    fn lbs_fixed_point_write<W: Write>(&self, w: &mut W, scale: u64) -> Result<(), LBSError> {
+        (*self as f64).lbs_fixed_point_write(w, scale)
+    }
+}
+
+impl LBSFixedPointRead for f32 {
+    #[inline]
+    fn lbs_fixed_point_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        scale: u64,
+    ) -> Result<Self, LBSError> {
+        Ok(f64::lbs_fixed_point_read(r, d, scale)? as f32)
+    }
+}
+
+impl<T: LBSFixedPointWrite> LBSFixedPointWrite for Option<T> {
+    #[inline]
+    fn lbs_fixed_point_write<W: Write>(&self, w: &mut W, scale: u64) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_fixed_point_write(w, scale)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSFixedPointRead> LBSFixedPointRead for Option<T> {
+    #[inline]
+    fn lbs_fixed_point_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        scale: u64,
+    ) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_fixed_point_read(r, d, scale)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSFixedPointWrite> LBSFixedPointWrite for Vec<T> {
+    #[inline]
+    fn lbs_fixed_point_write<W: Write>(&self, w: &mut W, scale: u64) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for e in self {
+            e.lbs_fixed_point_write(w, scale)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSFixedPointRead> LBSFixedPointRead for Vec<T> {
+    #[inline]
+    fn lbs_fixed_point_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        scale: u64,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_fixed_point_read(r, d, scale)?);
+        }
+
+        Ok(v)
+    }
+}
+
+/// Codec of `#[lbs(fixed_point(<u64>))]` fields, with scale as parameter.
+pub struct FixedPoint<const SCALE: u64>;
+
+impl<const SCALE: u64> FixedPoint<SCALE> {
+    #[inline]
+    pub fn write<T: LBSFixedPointWrite, W: Write>(
+        value: &T,
+        w: &mut W,
+        _e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        value.lbs_fixed_point_write(w, SCALE)
+    }
+
+    #[inline]
+    pub fn read<T: LBSFixedPointRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+        T::lbs_fixed_point_read(r, d, SCALE)
+    }
+}
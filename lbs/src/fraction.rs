@@ -1,39 +1,97 @@
 use super::LBSRead;
 use super::LBSWrite;
 use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
 use fraction::Decimal;
 use fraction::Fraction;
+use fraction::GenericFraction;
+use fraction::Sign;
 use std::io::Read;
 use std::io::Write;
 
+/// Written as `bool` sign, which is true if negative, followed by `u64` numerator and
+/// denominator. Infinities have 1 and NaN has 0 as numerator over 0.
 impl LBSWrite for Fraction {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.to_string().lbs_write(w)
+        let (sign, numer, denom) = match *self {
+            GenericFraction::Rational(sign, ref ratio) => (sign, *ratio.numer(), *ratio.denom()),
+            GenericFraction::Infinity(sign) => (sign, 1, 0),
+            GenericFraction::NaN => (Sign::Plus, 0, 0),
+        };
+
+        (sign == Sign::Minus).lbs_write(w)?;
+        numer.lbs_write(w)?;
+        denom.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        17
     }
 }
 
+/// Written as fraction followed by `u8` precision.
 impl LBSWrite for Decimal {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.to_string().lbs_write(w)
+        // Fraction of a decimal is reachable only by mapping it
+        let mut written = Ok(());
+        self.clone()
+            .map_mut(|fraction| written = fraction.lbs_write(w));
+        written?;
+        self.get_precision().lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        18
     }
 }
 
 impl LBSRead for Fraction {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        String::lbs_read(r)?
-            .parse::<Self>()
-            .map_err(|e| LBSError::Parsing(e.to_string()))
+        let negative = bool::lbs_read(r)?;
+        let numer = u64::lbs_read(r)?;
+        let denom = u64::lbs_read(r)?;
+
+        // Zero denominator gives infinity or NaN
+        Ok(match negative {
+            true => Self::new_neg(numer, denom),
+            false => Self::new(numer, denom),
+        })
     }
 }
 
 impl LBSRead for Decimal {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        String::lbs_read(r)?
-            .parse::<Self>()
-            .map_err(|e| LBSError::Parsing(e.to_string()))
+        let fraction = Fraction::lbs_read(r)?;
+        Ok(Self::from_fraction(fraction).set_precision(u8::lbs_read(r)?))
+    }
+}
+
+impl LBSSchema for Fraction {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::Bool, Schema::U64, Schema::U64])
+    }
+}
+
+impl LBSSchema for Decimal {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::Bool, Schema::U64, Schema::U64, Schema::U8])
     }
 }
+
+impl LBSMaxSize for Fraction {
+    const LBS_MAX_SIZE: usize = 17;
+}
+
+impl LBSMaxSize for Decimal {
+    const LBS_MAX_SIZE: usize = 18;
+}
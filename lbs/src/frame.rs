@@ -0,0 +1,237 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::read::read_len;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Maximum frame length accepted by `read_frame()`.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Writes value as a frame: u32 length followed by encoded value.
+/// Returns number of bytes written, including length.
+#[inline]
+pub fn write_frame<T: LBSWrite + ?Sized, W: Write>(
+    w: &mut W,
+    value: &T,
+) -> Result<usize, LBSError> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf)?;
+    write_len(w, buf.len())?;
+    w.write_all(&buf)?;
+    Ok(4 + buf.len())
+}
+
+/// Reads a frame written by `write_frame()`, which must not exceed `DEFAULT_MAX_FRAME_LEN`.
+#[inline]
+pub fn read_frame<T: LBSRead, R: Read>(r: &mut R) -> Result<T, LBSError> {
+    read_frame_with_limit(r, DEFAULT_MAX_FRAME_LEN)
+}
+
+/// Reads a frame written by `write_frame()`.
+///
+/// Fails with `LBSError::FrameTooLarge` before buffering anything if frame length exceeds `max_len`,
+/// and with `LBSError::TrailingBytes` if value doesn't occupy the whole frame.
+#[inline]
+pub fn read_frame_with_limit<T: LBSRead, R: Read>(
+    r: &mut R,
+    max_len: usize,
+) -> Result<T, LBSError> {
+    let len = read_len(r)?;
+
+    if len > max_len {
+        return Err(LBSError::FrameTooLarge { len, max_len });
+    }
+
+    let mut buf = vec![0; len];
+    r.read_exact(&mut buf)?;
+    from_slice_exact(&buf)
+}
+
+/// First bytes of a frame written by `Framing`.
+pub const FRAME_MAGIC: [u8; 2] = *b"LB";
+
+/// Version of frame header written by `Framing`.
+pub const FRAME_VERSION: u8 = 1;
+
+/// Size of frame header without checksum: magic, version, flags and u32 payload length.
+pub const FRAME_HEADER_LEN: usize = 8;
+
+/// Flag of a header, which is followed by u32 CRC-32 of payload.
+const FLAG_CHECKSUM: u8 = 1;
+
+/// Framing with a header, which tells a stream of frames from garbage and detects corruption.
+///
+/// Frame is `FRAME_MAGIC`, `FRAME_VERSION`, flags byte, u32 payload length, optional u32 CRC-32
+/// of payload and payload, which is encoded value. Checksum is written if enabled and verified
+/// whenever present, so readers needn't agree with writers on it. Frames of `write_frame()`
+/// have no header and aren't readable this way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framing {
+    checksum: bool,
+    max_len: usize,
+}
+
+/// Parsed frame header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Header {
+    /// Size of header itself.
+    pub(crate) size: usize,
+    pub(crate) payload_len: usize,
+    pub(crate) checksum: Option<u32>,
+}
+
+impl Framing {
+    /// Framing without checksum, which accepts payloads up to `DEFAULT_MAX_FRAME_LEN`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            checksum: false,
+            max_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Makes frames to carry CRC-32 of payload.
+    #[inline]
+    pub fn checksum(&mut self, enabled: bool) -> &mut Self {
+        self.checksum = enabled;
+        self
+    }
+
+    /// Makes frames with longer payload to fail with `LBSError::FrameTooLarge`
+    /// before buffering them.
+    #[inline]
+    pub fn max_len(&mut self, max_len: usize) -> &mut Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Size of header of frames written.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        match self.checksum {
+            true => FRAME_HEADER_LEN + 4,
+            false => FRAME_HEADER_LEN,
+        }
+    }
+
+    /// Writes value as a frame and returns number of bytes written, including header.
+    pub fn write<T: LBSWrite + ?Sized, W: Write>(
+        &self,
+        w: &mut W,
+        value: &T,
+    ) -> Result<usize, LBSError> {
+        let mut buf = vec![0; self.header_len()];
+        value.lbs_write(&mut buf)?;
+        self.fill_header(&mut buf)?;
+        w.write_all(&buf)?;
+        Ok(buf.len())
+    }
+
+    /// Reads a frame written by `write()`.
+    ///
+    /// Fails with `LBSError::Parsing` if frame doesn't start with `FRAME_MAGIC`,
+    /// with `LBSError::UnsupportedVersion` if its version isn't `FRAME_VERSION`,
+    /// with `LBSError::ChecksumMismatch` if payload is corrupted,
+    /// and with `LBSError::TrailingBytes` if value doesn't occupy the whole payload.
+    pub fn read<T: LBSRead, R: Read>(&self, r: &mut R) -> Result<T, LBSError> {
+        let mut buf = [0; FRAME_HEADER_LEN + 4];
+        r.read_exact(&mut buf[..FRAME_HEADER_LEN])?;
+
+        // Checksum is known to follow only after flags are checked
+        let size = self.parse_header(&buf[..FRAME_HEADER_LEN])?.size;
+        r.read_exact(&mut buf[FRAME_HEADER_LEN..size])?;
+        let header = self.parse_header(&buf[..size])?;
+
+        let mut payload = vec![0; header.payload_len];
+        r.read_exact(&mut payload)?;
+        Self::decode(&header, &payload)
+    }
+
+    /// Writes header into the beginning of `frame`, which is followed by payload.
+    pub(crate) fn fill_header(&self, frame: &mut [u8]) -> Result<(), LBSError> {
+        let header_len = self.header_len();
+        let payload = &frame[header_len..];
+
+        let payload_len = u32::try_from(payload.len())
+            .map_err(|_| LBSError::Parsing("frame payload exceeds u32".to_string()))?;
+
+        let checksum = self.checksum.then(|| crate::crc32::checksum(payload));
+
+        frame[..2].copy_from_slice(&FRAME_MAGIC);
+        frame[2] = FRAME_VERSION;
+        frame[3] = if checksum.is_some() { FLAG_CHECKSUM } else { 0 };
+        frame[4..8].copy_from_slice(&payload_len.to_le_bytes());
+
+        if let Some(checksum) = checksum {
+            frame[8..12].copy_from_slice(&checksum.to_le_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// Parses header from the beginning of `buf`, which holds at least `FRAME_HEADER_LEN` bytes.
+    /// Checksum is read only if `buf` holds it too.
+    pub(crate) fn parse_header(&self, buf: &[u8]) -> Result<Header, LBSError> {
+        if buf[..2] != FRAME_MAGIC {
+            return Err(LBSError::Parsing("not an lbs frame".to_string()));
+        }
+
+        if buf[2] != FRAME_VERSION {
+            return Err(LBSError::UnsupportedVersion(buf[2] as u16));
+        }
+
+        let flags = buf[3];
+
+        if flags & !FLAG_CHECKSUM != 0 {
+            return Err(LBSError::Parsing(format!(
+                "unknown frame flags {:#x}",
+                flags
+            )));
+        }
+
+        let payload_len = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as usize;
+
+        if payload_len > self.max_len {
+            return Err(LBSError::FrameTooLarge {
+                len: payload_len,
+                max_len: self.max_len,
+            });
+        }
+
+        let (size, checksum) = match flags & FLAG_CHECKSUM != 0 {
+            true if buf.len() >= FRAME_HEADER_LEN + 4 => (
+                FRAME_HEADER_LEN + 4,
+                Some(u32::from_le_bytes(buf[8..12].try_into().unwrap())),
+            ),
+            true => (FRAME_HEADER_LEN + 4, None),
+            false => (FRAME_HEADER_LEN, None),
+        };
+
+        Ok(Header {
+            size,
+            payload_len,
+            checksum,
+        })
+    }
+
+    /// Verifies payload of a frame and decodes it.
+    pub(crate) fn decode<T: LBSRead>(header: &Header, payload: &[u8]) -> Result<T, LBSError> {
+        if let Some(checksum) = header.checksum {
+            if crate::crc32::checksum(payload) != checksum {
+                return Err(LBSError::ChecksumMismatch);
+            }
+        }
+
+        from_slice_exact(payload)
+    }
+}
+
+impl Default for Framing {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,103 @@
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSWrite;
+use std::hash::Hasher;
+use std::io::Write;
+
+/// Hashes canonical encoding of a value with a new hasher of type `H`.
+///
+/// Unlike `std::hash::Hash`, hash depends only on encoded bytes, so it's stable across processes,
+/// builds and platforms as long as the wire format and hasher are. Use a hasher with stable
+/// algorithm and fixed keys, like `Fnv1a`, rather than `DefaultHasher` of `RandomState`.
+/// Iteration order of `HashMap` and `HashSet` isn't canonical, so equal ones may hash differently:
+/// use `BTreeMap` and `BTreeSet` instead.
+#[inline]
+pub fn stable_hash<T: LBSWrite + ?Sized, H: Hasher + Default>(value: &T) -> Result<u64, LBSError> {
+    stable_hash_with(value, H::default())
+}
+
+/// Hashes canonical encoding of a value with the given hasher, see `stable_hash()`.
+#[inline]
+pub fn stable_hash_with<T: LBSWrite + ?Sized, H: Hasher>(
+    value: &T,
+    hasher: H,
+) -> Result<u64, LBSError> {
+    let mut e = Encoder::new();
+    e.canonicalize_nan(true).canonicalize_zero(true);
+
+    let mut w = HashWriter::new(hasher);
+    value.lbs_write_with(&mut w, &mut e)?;
+    Ok(w.finish())
+}
+
+/// Writer, which feeds bytes written into a hasher instead of buffering them.
+#[derive(Debug, Clone, Default)]
+pub struct HashWriter<H> {
+    hasher: H,
+}
+
+impl<H: Hasher> HashWriter<H> {
+    #[inline]
+    pub fn new(hasher: H) -> Self {
+        Self { hasher }
+    }
+
+    #[inline]
+    pub fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.hasher
+    }
+}
+
+impl<H: Hasher> Write for HashWriter<H> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.hasher.write(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a hasher, which is stable and unkeyed, like hash of `Schema::fingerprint()`.
+/// It's fast on short inputs, but isn't resistant to collisions crafted on purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+}
+
+impl Default for Fnv1a {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Fnv1a {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |hash, b| {
+            (hash ^ *b as u64).wrapping_mul(Self::PRIME)
+        });
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,206 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len;
+use crate::read::read_len_with;
+use crate::read::skip_bytes;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::stats;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use http::HeaderMap;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use http::Uri;
+use std::io::Read;
+use std::io::Write;
+
+impl LBSWrite for Method {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_str().lbs_write(w)
+    }
+}
+
+impl LBSWrite for StatusCode {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_u16().lbs_write(w)
+    }
+}
+
+impl LBSWrite for Uri {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.to_string().lbs_write(w)
+    }
+}
+
+impl LBSWrite for HeaderName {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_str().lbs_write(w)
+    }
+}
+
+/// Written as bytes, as values aren't required to be UTF-8.
+impl LBSWrite for HeaderValue {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_bytes().lbs_write(w)
+    }
+}
+
+/// Written as a list of name and value pairs in iteration order, so repeated headers
+/// are kept.
+impl LBSWrite for HeaderMap {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, _e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for (name, value) in self {
+            name.lbs_write(w)?;
+            value.lbs_write(w)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl LBSRead for Method {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Method::from_bytes(d.read_bytes(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl LBSRead for StatusCode {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        StatusCode::from_u16(u16::lbs_read(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl LBSRead for Uri {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Uri::try_from(d.read_bytes(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl LBSRead for HeaderName {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        HeaderName::from_bytes(d.read_bytes(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl LBSRead for HeaderValue {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        HeaderValue::from_bytes(d.read_bytes(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl LBSRead for HeaderMap {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        let mut map = Self::try_with_capacity(capacity::<(HeaderName, HeaderValue)>(l))
+            .map_err(|e| LBSError::Parsing(e.to_string()))?;
+
+        for _ in 0..l {
+            let name = HeaderName::lbs_read_with(r, d)?;
+            let value = HeaderValue::lbs_read_with(r, d)?;
+            map.try_append(name, value)
+                .map_err(|e| LBSError::Parsing(e.to_string()))?;
+        }
+
+        Ok(map)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            let l = read_len(r)?;
+            skip_bytes(r, l)?;
+            let l = read_len(r)?;
+            skip_bytes(r, l)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_http_schema {
+    ($($t:ty => $schema:expr),*) => {
+        $(
+            impl LBSSchema for $t {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    $schema
+                }
+            }
+        )*
+    };
+}
+
+impl_http_schema!(
+    Method => Schema::String,
+    StatusCode => Schema::U16,
+    Uri => Schema::String,
+    HeaderName => Schema::String,
+    HeaderValue => Schema::List(Box::new(Schema::U8)),
+    HeaderMap => Schema::List(Box::new(Schema::Tuple(vec![
+        HeaderName::lbs_schema(),
+        HeaderValue::lbs_schema(),
+    ])))
+);
+
+impl LBSMaxSize for StatusCode {
+    const LBS_MAX_SIZE: usize = 2;
+}
@@ -0,0 +1,121 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::write::encoded_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use http_body::Body;
+use http_body::Frame;
+use http_body::SizeHint;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+/// Body of hyper or reqwest request or response, which encodes value of type `T`.
+///
+/// Exact size of the body is known in advance, so hyper sends it with `content-length` header.
+/// Value is encoded only when body is polled and is sent as a single frame.
+/// With reqwest it may be used via `reqwest::Body::wrap()`.
+#[derive(Debug)]
+pub struct LBSBody<T> {
+    value: Option<T>,
+    len: usize,
+}
+
+// Value is never pinned
+impl<T> Unpin for LBSBody<T> {}
+
+impl<T: LBSWrite> LBSBody<T> {
+    /// Measures encoded size of the value, see `encoded_len()`.
+    #[inline]
+    pub fn new(value: T) -> Result<Self, LBSError> {
+        Ok(Self {
+            len: encoded_len(&value)?,
+            value: Some(value),
+        })
+    }
+
+    /// Number of bytes in the body.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: LBSWrite> Body for LBSBody<T> {
+    type Data = Bytes;
+    type Error = LBSError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        let Some(value) = this.value.take() else {
+            return Poll::Ready(None);
+        };
+
+        let mut buf = Vec::with_capacity(this.len);
+        let frame = value.lbs_write(&mut buf).map(|_| Frame::data(buf.into()));
+        Poll::Ready(Some(frame))
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.value.is_none()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        match self.value {
+            Some(_) => SizeHint::with_exact(self.len as u64),
+            None => SizeHint::with_exact(0),
+        }
+    }
+}
+
+/// Collects body of at most `limit` bytes and decodes it. Decoding fails if body has trailing bytes.
+///
+/// Bodies, which are known to exceed the limit from their size hint, are rejected without reading.
+pub async fn from_body<T, B>(body: B, limit: usize) -> Result<T, LBSError>
+where
+    T: LBSRead,
+    B: Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let too_large = |len| LBSError::FrameTooLarge {
+        len,
+        max_len: limit,
+    };
+
+    let lower = body.size_hint().lower();
+    if lower > limit as u64 {
+        return Err(too_large(lower.try_into().unwrap_or(usize::MAX)));
+    }
+
+    let mut body = std::pin::pin!(body);
+    let mut buf = Vec::new();
+
+    while let Some(frame) = std::future::poll_fn(|cx| body.as_mut().poll_frame(cx)).await {
+        let frame = frame.map_err(|e| LBSError::Io(std::io::Error::other(e)))?;
+
+        // Trailers are ignored
+        if let Ok(data) = frame.into_data() {
+            if buf.len() + data.remaining() > limit {
+                return Err(too_large(buf.len() + data.remaining()));
+            }
+
+            buf.put(data);
+        }
+    }
+
+    from_slice_exact(&buf)
+}
@@ -0,0 +1,85 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::LBSRead;
+use std::marker::PhantomData;
+use std::task::Poll;
+
+/// Decoder of `T` values from bytes, which arrive in arbitrary chunks, like from non-blocking
+/// sockets or io_uring completions.
+///
+/// Incomplete message is buffered and decoding is retried from its start when more bytes are fed,
+/// so chunks should be reasonably large compared to messages.
+#[derive(Debug)]
+pub struct IncrementalDecoder<T> {
+    decoder: Decoder,
+    buf: Vec<u8>,
+    /// Whether buffered bytes are known to be an incomplete message.
+    incomplete: bool,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T: LBSRead> IncrementalDecoder<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_decoder(Decoder::new())
+    }
+
+    /// Decodes with options of the given decoder.
+    /// Metrics of the decoder aren't reported, since decoding may be retried.
+    #[inline]
+    pub fn with_decoder(decoder: Decoder) -> Self {
+        Self {
+            decoder,
+            buf: Vec::new(),
+            incomplete: false,
+            ty: PhantomData,
+        }
+    }
+
+    /// Consumes next chunk of bytes and returns next complete value, if any.
+    ///
+    /// A single chunk may contain several values, they are returned by subsequent calls,
+    /// so `feed(&[])` should be called until it's pending, before waiting for more bytes.
+    /// On error, buffered bytes are discarded.
+    pub fn feed(&mut self, bytes: &[u8]) -> Poll<Result<T, LBSError>> {
+        if bytes.is_empty() && (self.incomplete || self.buf.is_empty()) {
+            return Poll::Pending;
+        }
+
+        self.buf.extend_from_slice(bytes);
+        self.decoder.clear_shared();
+
+        let mut rest = self.buf.as_slice();
+
+        match T::lbs_read_with(&mut rest, &mut self.decoder) {
+            Ok(value) => {
+                let consumed = self.buf.len() - rest.len();
+                self.buf.drain(..consumed);
+                self.incomplete = false;
+                Poll::Ready(Ok(value))
+            }
+            Err(e) if e.is_eof() => {
+                self.incomplete = true;
+                Poll::Pending
+            }
+            Err(e) => {
+                self.buf.clear();
+                self.incomplete = false;
+                Poll::Ready(Err(e))
+            }
+        }
+    }
+
+    /// Number of bytes, which are fed but not yet decoded.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+impl<T: LBSRead> Default for IncrementalDecoder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
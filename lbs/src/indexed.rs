@@ -0,0 +1,238 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::read::read_len;
+use crate::read::skip_value;
+use crate::write::write_field_count;
+use crate::write::write_field_id;
+use crate::write::write_len;
+use crate::LBSRead;
+use std::io::Read;
+use std::io::Take;
+use std::io::Write;
+
+/// Size of a single index entry: id, offset and length.
+const ENTRY_LEN: usize = 10;
+
+/// Writer of a struct with indexed container, see `#[lbs(indexed)]`.
+///
+/// Encoded form is field count, then table of (id, offset, length) entries sorted by id,
+/// then total length of values, then values in the same order. Offsets are relative to the first value,
+/// so `IndexedView` can decode any field without scanning its predecessors.
+#[derive(Debug, Default)]
+pub struct IndexWriter {
+    entries: Vec<IndexEntry>,
+    values: Vec<u8>,
+}
+
+/// Location of a single field value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub id: u16,
+    pub offset: u32,
+    pub len: u32,
+}
+
+impl IndexWriter {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends field value written by `f`. Fields must be appended in ascending id order.
+    #[inline]
+    pub fn field<F>(&mut self, id: u16, f: F) -> Result<(), LBSError>
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<(), LBSError>,
+    {
+        debug_assert!(self.entries.last().is_none_or(|last| last.id < id));
+
+        let offset = self.values.len();
+        f(&mut self.values)?;
+
+        self.entries.push(IndexEntry {
+            id,
+            offset: to_u32(offset)?,
+            len: to_u32(self.values.len() - offset)?,
+        });
+
+        Ok(())
+    }
+
+    /// Writes index and values.
+    pub fn finish<W: Write>(self, w: &mut W) -> Result<(), LBSError> {
+        let count = self
+            .entries
+            .len()
+            .try_into()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+
+        write_field_count(w, count)?;
+
+        for entry in &self.entries {
+            write_field_id(w, entry.id)?;
+            w.write_all(&entry.offset.to_le_bytes())?;
+            w.write_all(&entry.len.to_le_bytes())?;
+        }
+
+        write_len(w, self.values.len())?;
+        Ok(w.write_all(&self.values)?)
+    }
+}
+
+/// Reads index and calls `f` for each field with a reader limited to its value.
+/// `f` returns false if it doesn't know or need the field, which is then skipped.
+/// Used by derived implementations.
+pub fn read_fields<R, F>(r: &mut R, mut f: F) -> Result<(), LBSError>
+where
+    R: Read,
+    F: FnMut(u16, &mut Take<&mut R>) -> Result<bool, LBSError>,
+{
+    let count = u16::lbs_read(r)?;
+    let mut entries = Vec::with_capacity(count as usize);
+
+    for _ in 0..count {
+        entries.push(IndexEntry {
+            id: u16::lbs_read(r)?,
+            offset: u32::lbs_read(r)?,
+            len: u32::lbs_read(r)?,
+        });
+    }
+
+    let total = read_len(r)?;
+    let mut position = 0;
+
+    for entry in entries {
+        if entry.offset as usize != position {
+            return Err(LBSError::Parsing(format!(
+                "field {} is at offset {} instead of {}",
+                entry.id, entry.offset, position
+            )));
+        }
+
+        let mut value = r.take(entry.len as u64);
+
+        if !f(entry.id, &mut value)? {
+            skip_value(&mut value, entry.len as usize)?;
+        }
+
+        match value.limit() {
+            0 => position += entry.len as usize,
+            n => return Err(LBSError::TrailingBytes(n as usize).with_field(entry.id)),
+        }
+    }
+
+    match total.checked_sub(position) {
+        Some(0) => Ok(()),
+        Some(n) => Err(LBSError::TrailingBytes(n)),
+        None => Err(LBSError::Parsing(format!(
+            "fields take {} bytes instead of {}",
+            position, total
+        ))),
+    }
+}
+
+/// Random access to fields of a struct encoded with indexed container.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedView<'a> {
+    index: &'a [u8],
+    values: &'a [u8],
+}
+
+impl<'a> IndexedView<'a> {
+    /// Parses index at the beginning of `bytes`. Trailing bytes after the value are allowed,
+    /// see `encoded_len()`.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, LBSError> {
+        let eof = || LBSError::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+
+        let count = u16::lbs_read(&mut bytes.get(..2).ok_or_else(eof)?)? as usize;
+        let index_end = 2 + count * ENTRY_LEN;
+        let index = bytes.get(2..index_end).ok_or_else(eof)?;
+        let total = read_len(&mut bytes.get(index_end..).ok_or_else(eof)?)?;
+        let values_start = index_end + 4;
+        let values = bytes
+            .get(values_start..values_start + total)
+            .ok_or_else(eof)?;
+
+        Ok(Self { index, values })
+    }
+
+    /// Number of indexed fields.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len() / ENTRY_LEN
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Number of bytes taken by the whole encoded value.
+    #[inline]
+    pub fn encoded_len(&self) -> usize {
+        2 + self.index.len() + 4 + self.values.len()
+    }
+
+    /// Entry of the `i`-th field in ascending id order.
+    #[inline]
+    pub fn entry(&self, i: usize) -> IndexEntry {
+        let mut e = &self.index[i * ENTRY_LEN..(i + 1) * ENTRY_LEN];
+        // Entry is in bounds, so reads can't fail
+        IndexEntry {
+            id: u16::lbs_read(&mut e).unwrap(),
+            offset: u32::lbs_read(&mut e).unwrap(),
+            len: u32::lbs_read(&mut e).unwrap(),
+        }
+    }
+
+    /// Encoded value of field with given id.
+    pub fn raw(&self, id: u16) -> Result<Option<&'a [u8]>, LBSError> {
+        let (mut lo, mut hi) = (0, self.len());
+
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let entry = self.entry(mid);
+
+            if entry.id == id {
+                let start = entry.offset as usize;
+                let end = start + entry.len as usize;
+                return self.values.get(start..end).map(Some).ok_or_else(|| {
+                    LBSError::Parsing(format!("field {} is out of bounds", id)).with_field(id)
+                });
+            }
+
+            match entry.id < id {
+                true => lo = mid + 1,
+                false => hi = mid,
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decodes field with given id, if present.
+    #[inline]
+    pub fn get<T: LBSRead>(&self, id: u16) -> Result<Option<T>, LBSError> {
+        self.get_with(id, &mut Decoder::new())
+    }
+
+    /// Same as `get()`, but reuses state of the given decoder.
+    pub fn get_with<T: LBSRead>(&self, id: u16, d: &mut Decoder) -> Result<Option<T>, LBSError> {
+        let Some(mut raw) = self.raw(id)? else {
+            return Ok(None);
+        };
+
+        let value = T::lbs_read_with(&mut raw, d).map_err(|e| e.with_field(id))?;
+
+        match raw.len() {
+            0 => Ok(Some(value)),
+            n => Err(LBSError::TrailingBytes(n).with_field(id)),
+        }
+    }
+}
+
+#[inline]
+fn to_u32(n: usize) -> Result<u32, LBSError> {
+    n.try_into()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err).into())
+}
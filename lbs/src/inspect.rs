@@ -0,0 +1,437 @@
+use crate::error::LBSError;
+use crate::read::compact_field_id;
+use crate::read::duration;
+use crate::read::option_tag;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::schema::StructSchema;
+use crate::write::BEFORE_EPOCH_FLAG;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+
+/// Bytes shown on a single line.
+const BYTES_PER_LINE: usize = 8;
+
+/// Strings longer than this are shortened.
+const MAX_STRING_CHARS: usize = 64;
+
+/// Same as in derived implementations.
+const PACKED_BOOLS_ID: u16 = u16::MAX;
+
+/// Formatter of encoded `T`, which shows offsets and raw bytes interleaved with field ids, names
+/// and decoded values, as described by `T::lbs_schema()`.
+///
+/// Bytes, which can't be annotated, like values with custom codecs or malformed input,
+/// are dumped after the reason.
+pub struct Annotated<'a, T: ?Sized> {
+    bytes: &'a [u8],
+    schema: Schema,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: LBSSchema + ?Sized> Annotated<'a, T> {
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            schema: T::lbs_schema(),
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> fmt::Display for Annotated<'a, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        annotate(f, self.bytes, &self.schema)
+    }
+}
+
+impl<'a, T: ?Sized> fmt::Debug for Annotated<'a, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        annotate(f, self.bytes, &self.schema)
+    }
+}
+
+/// Writes bytes annotated with an arbitrary schema, like one fetched from a registry.
+pub fn annotate<W: fmt::Write>(w: &mut W, bytes: &[u8], schema: &Schema) -> fmt::Result {
+    let mut walker = Walker {
+        w,
+        bytes,
+        pos: 0,
+        named: HashMap::new(),
+    };
+
+    let stop = match walker.value(schema, "", 0) {
+        Ok(()) if walker.pos == bytes.len() => return Ok(()),
+        Ok(()) => Stop::Invalid("trailing bytes".to_string()),
+        Err(stop) => stop,
+    };
+
+    let reason = match stop {
+        Stop::Fmt => return Err(fmt::Error),
+        Stop::Truncated => "truncated".to_string(),
+        Stop::Invalid(reason) => reason,
+    };
+
+    let start = walker.pos;
+    walker.pos = bytes.len();
+
+    match walker.item(start, 0, &format!("<{}>", reason)) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(fmt::Error),
+    }
+}
+
+enum Stop {
+    Truncated,
+    Invalid(String),
+    Fmt,
+}
+
+#[inline]
+fn invalid(e: LBSError) -> Stop {
+    Stop::Invalid(e.to_string())
+}
+
+impl From<fmt::Error> for Stop {
+    #[inline]
+    fn from(_: fmt::Error) -> Self {
+        Self::Fmt
+    }
+}
+
+struct Walker<'a, 'w, W> {
+    w: &'w mut W,
+    bytes: &'a [u8],
+    pos: usize,
+    /// Structs and enums met so far, to resolve `Schema::Ref`.
+    named: HashMap<String, Schema>,
+}
+
+impl<'a, 'w, W: fmt::Write> Walker<'a, 'w, W> {
+    fn value(&mut self, schema: &Schema, label: &str, depth: usize) -> Result<(), Stop> {
+        let start = self.pos;
+
+        let text = match schema {
+            Schema::Unit => "()".to_string(),
+            Schema::Bool => match self.take(1)?[0] {
+                0 => "false".to_string(),
+                1 => "true".to_string(),
+                b => format!("bool {}", b),
+            },
+            Schema::U8 => self.take(1)?[0].to_string(),
+            Schema::U16 => self.uint(2)?.to_string(),
+            Schema::U32 => self.uint(4)?.to_string(),
+            Schema::U64 => self.uint(8)?.to_string(),
+            Schema::U128 => self.uint(16)?.to_string(),
+            Schema::I8 => (self.take(1)?[0] as i8).to_string(),
+            Schema::I16 => (self.uint(2)? as i16).to_string(),
+            Schema::I32 => (self.uint(4)? as i32).to_string(),
+            Schema::I64 => (self.uint(8)? as i64).to_string(),
+            Schema::I128 => (self.uint(16)? as i128).to_string(),
+            Schema::F32 => f32::from_bits(self.uint(4)? as u32).to_string(),
+            Schema::F64 => f64::from_bits(self.uint(8)? as u64).to_string(),
+            Schema::Char => match char::from_u32(self.uint(4)? as u32) {
+                Some(c) => format!("{:?}", c),
+                None => return Err(Stop::Invalid("invalid char".to_string())),
+            },
+            Schema::String => {
+                let len = self.uint(4)? as usize;
+                let s = String::from_utf8_lossy(self.take(len)?);
+                match s.chars().count() > MAX_STRING_CHARS {
+                    true => format!(
+                        "{:?}...",
+                        s.chars().take(MAX_STRING_CHARS).collect::<String>()
+                    ),
+                    false => format!("{:?}", s),
+                }
+            }
+            Schema::Duration => {
+                let (secs, nanos) = (self.uint(8)? as u64, self.uint(4)? as u32);
+                format!("{:?}", duration(secs, nanos).map_err(invalid)?)
+            }
+            Schema::SystemTime => {
+                let (secs, nanos) = (self.uint(8)? as u64, self.uint(4)? as u32);
+                let since = duration(secs, nanos & !BEFORE_EPOCH_FLAG).map_err(invalid)?;
+                match nanos & BEFORE_EPOCH_FLAG {
+                    0 => format!("{:?} after epoch", since),
+                    _ => format!("{:?} before epoch", since),
+                }
+            }
+            Schema::Ipv4Addr => Ipv4Addr::from(self.uint(4)? as u32).to_string(),
+            Schema::Ipv6Addr => Ipv6Addr::from(self.uint(16)?).to_string(),
+            Schema::IpAddr => match self.take(1)?[0] {
+                1 => Ipv4Addr::from(self.uint(4)? as u32).to_string(),
+                _ => Ipv6Addr::from(self.uint(16)?).to_string(),
+            },
+            Schema::Option(inner) => match option_tag(self.take(1)?[0]).map_err(invalid)? {
+                false => "None".to_string(),
+                true => {
+                    self.item(start, depth, &format!("{}Some", label))?;
+                    return self.value(inner, "", depth + 1);
+                }
+            },
+            Schema::List(inner) => {
+                let len = self.uint(4)?;
+                self.item(start, depth, &format!("{}{} elements", label, len))?;
+
+                for i in 0..len {
+                    self.value(inner, &format!("[{}] ", i), depth + 1)?;
+                }
+
+                return Ok(());
+            }
+            Schema::Map(kv) => {
+                let len = self.uint(4)?;
+                self.item(start, depth, &format!("{}{} entries", label, len))?;
+
+                for i in 0..len {
+                    self.value(&kv.0, &format!("[{}] key ", i), depth + 1)?;
+                    self.value(&kv.1, &format!("[{}] value ", i), depth + 1)?;
+                }
+
+                return Ok(());
+            }
+            Schema::Tuple(elements) => {
+                self.item(start, depth, &format!("{}tuple", label))?;
+
+                for (i, element) in elements.iter().enumerate() {
+                    self.value(element, &format!(".{} ", i), depth + 1)?;
+                }
+
+                return Ok(());
+            }
+            Schema::Range(inner) => {
+                self.item(start, depth, &format!("{}range", label))?;
+                self.value(inner, "start ", depth + 1)?;
+                return self.value(inner, "end ", depth + 1);
+            }
+            Schema::Struct(s) => {
+                self.named
+                    .entry(s.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                return match s.indexed {
+                    true => self.indexed_struct(s, label, depth),
+                    false => self.struct_fields(s, label, depth),
+                };
+            }
+            Schema::Enum(e) => {
+                self.named
+                    .entry(e.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                let id = match e.compact {
+                    true => self.varint()?,
+                    false => self.uint(2)? as u16,
+                };
+
+                let Some(variant) = e.variants.iter().find(|v| v.id == id) else {
+                    return Err(Stop::Invalid(format!(
+                        "unknown variant {} of {}",
+                        id, e.name
+                    )));
+                };
+
+                self.item(
+                    start,
+                    depth,
+                    &format!("{}{}::{} ({})", label, e.name, variant.name, id),
+                )?;
+
+                return match variant.schema {
+                    Some(ref payload) => self.value(payload, "", depth + 1),
+                    None => Ok(()),
+                };
+            }
+            Schema::Ref(name) => {
+                let Some(schema) = self.named.get(name).cloned() else {
+                    return Err(Stop::Invalid(format!("unknown type {}", name)));
+                };
+                return self.value(&schema, label, depth);
+            }
+            Schema::Opaque(name) => return Err(Stop::Invalid(format!("opaque {}", name))),
+        };
+
+        self.item(start, depth, &format!("{}{}", label, text))
+    }
+
+    fn struct_fields(&mut self, s: &StructSchema, label: &str, depth: usize) -> Result<(), Stop> {
+        let start = self.pos;
+        let count = match s.compact {
+            true => self.varint()?,
+            false => self.uint(2)? as u16,
+        };
+
+        self.item(
+            start,
+            depth,
+            &format!("{}{}, {} fields", label, s.name, count),
+        )?;
+        let mut prev_id = None;
+
+        for _ in 0..count {
+            let start = self.pos;
+            let id = match s.compact {
+                true => {
+                    let id = compact_field_id(prev_id, self.varint()?).map_err(invalid)?;
+                    prev_id = Some(id);
+                    id
+                }
+                false => self.uint(2)? as u16,
+            };
+
+            if id == PACKED_BOOLS_ID && s.pack_bools {
+                self.packed_bools(s, start, depth + 1)?;
+                continue;
+            }
+
+            self.field(s, id, start, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn indexed_struct(&mut self, s: &StructSchema, label: &str, depth: usize) -> Result<(), Stop> {
+        let start = self.pos;
+        let count = self.uint(2)?;
+        self.item(
+            start,
+            depth,
+            &format!("{}{}, {} indexed fields", label, s.name, count),
+        )?;
+
+        let mut ids = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            let start = self.pos;
+            let (id, offset, len) = (self.uint(2)? as u16, self.uint(4)?, self.uint(4)?);
+            ids.push(id);
+            self.item(
+                start,
+                depth + 1,
+                &format!("index [{}] offset {} length {}", id, offset, len),
+            )?;
+        }
+
+        let start = self.pos;
+        let total = self.uint(4)?;
+        self.item(start, depth + 1, &format!("{} bytes of values", total))?;
+
+        for id in ids {
+            self.field(s, id, self.pos, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes field id, which was read since `start`, and the field value.
+    fn field(&mut self, s: &StructSchema, id: u16, start: usize, depth: usize) -> Result<(), Stop> {
+        let Some(field) = s.fields.iter().find(|f| f.id == id) else {
+            return Err(Stop::Invalid(format!("unknown field {} of {}", id, s.name)));
+        };
+
+        self.item(start, depth, &format!("[{}] {}", id, field.name))?;
+
+        match field.codec {
+            Some(ref codec) => Err(Stop::Invalid(format!(
+                "field {} is encoded by {}",
+                id, codec
+            ))),
+            None => self.value(&field.schema, "", depth + 1),
+        }
+    }
+
+    fn packed_bools(&mut self, s: &StructSchema, start: usize, depth: usize) -> Result<(), Stop> {
+        let (base, count) = (self.uint(2)? as u16, self.uint(2)? as u16);
+        let bytes = self.take(count.div_ceil(8) as usize)?;
+
+        let bools: Vec<String> = (0..count)
+            .map(|i| {
+                let id = base.wrapping_add(i);
+                let name = s.fields.iter().find(|f| f.id == id).map(|f| &f.name);
+                let value = bytes[i as usize / 8] & (1 << (i % 8)) != 0;
+                match name {
+                    Some(name) => format!("[{}] {} {}", id, name, value),
+                    None => format!("[{}] {}", id, value),
+                }
+            })
+            .collect();
+
+        self.item(start, depth, &format!("packed bools {}", bools.join(", ")))
+    }
+
+    #[inline]
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Stop> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(n))
+            .ok_or(Stop::Truncated)?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn uint(&mut self, n: usize) -> Result<u128, Stop> {
+        let bytes = self.take(n)?;
+        Ok(bytes.iter().rev().fold(0u128, |v, b| (v << 8) | *b as u128))
+    }
+
+    #[inline]
+    fn varint(&mut self) -> Result<u16, Stop> {
+        let mut rest = &self.bytes[self.pos..];
+        let len = rest.len();
+
+        match crate::varint::read_u16(&mut rest) {
+            Ok(v) => {
+                self.pos += len - rest.len();
+                Ok(v)
+            }
+            Err(e) if e.is_eof() => Err(Stop::Truncated),
+            Err(e) => Err(Stop::Invalid(e.to_string())),
+        }
+    }
+
+    /// Writes bytes from `start` to the current position, with text on the first line.
+    fn item(&mut self, start: usize, depth: usize, text: &str) -> Result<(), Stop> {
+        let bytes = &self.bytes[start..self.pos];
+        let mut chunks = bytes.chunks(BYTES_PER_LINE);
+        let first = chunks.next().unwrap_or_default();
+
+        writeln!(
+            self.w,
+            "{:06x}  {:<width$}  {:indent$}{}",
+            start,
+            hex(first),
+            "",
+            text,
+            width = BYTES_PER_LINE * 3 - 1,
+            indent = depth * 2
+        )?;
+
+        for (i, chunk) in chunks.enumerate() {
+            writeln!(
+                self.w,
+                "{:06x}  {}",
+                start + (i + 1) * BYTES_PER_LINE,
+                hex(chunk)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
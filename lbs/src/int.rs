@@ -0,0 +1,141 @@
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+
+macro_rules! impl_narrow_int {
+    ($($(#[$doc:meta])* $t:ident($p:ty, $bytes:literal, $min:literal, $max:literal)),*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $t($p);
+
+            impl $t {
+                pub const BITS: u32 = $bytes * 8;
+                pub const MIN: Self = Self($min);
+                pub const MAX: Self = Self($max);
+
+                /// Returns None, if value doesn't fit.
+                #[inline]
+                pub const fn new(v: $p) -> Option<Self> {
+                    match v >= Self::MIN.0 && v <= Self::MAX.0 {
+                        true => Some(Self(v)),
+                        false => None,
+                    }
+                }
+
+                /// Keeps only the low bits of value, like an `as` cast.
+                #[inline]
+                pub const fn wrapping(v: $p) -> Self {
+                    let shift = <$p>::BITS - Self::BITS;
+                    Self((v << shift) >> shift)
+                }
+
+                #[inline]
+                pub const fn get(self) -> $p {
+                    self.0
+                }
+
+                #[inline]
+                pub const fn to_le_bytes(self) -> [u8; $bytes] {
+                    let bytes = self.0.to_le_bytes();
+                    let mut buf = [0; $bytes];
+                    let mut i = 0;
+
+                    while i < $bytes {
+                        buf[i] = bytes[i];
+                        i += 1;
+                    }
+
+                    buf
+                }
+
+                /// Sign-extends bytes of signed types.
+                #[inline]
+                pub const fn from_le_bytes(buf: [u8; $bytes]) -> Self {
+                    let mut bytes = [0; size_of::<$p>()];
+                    let mut i = 0;
+
+                    while i < $bytes {
+                        bytes[i] = buf[i];
+                        i += 1;
+                    }
+
+                    Self::wrapping(<$p>::from_le_bytes(bytes))
+                }
+            }
+
+            impl From<$t> for $p {
+                #[inline]
+                fn from(v: $t) -> Self {
+                    v.0
+                }
+            }
+
+            impl TryFrom<$p> for $t {
+                type Error = LBSError;
+
+                #[inline]
+                fn try_from(v: $p) -> Result<Self, LBSError> {
+                    Self::new(v).ok_or(LBSError::OutOfRange(stringify!($t)))
+                }
+            }
+
+            impl fmt::Display for $t {
+                #[inline]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.0.fmt(f)
+                }
+            }
+
+            #[doc = concat!("Written as ", $bytes, " little-endian bytes.")]
+            impl LBSWrite for $t {
+                #[inline]
+                fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    Ok(w.write_all(&self.to_le_bytes())?)
+                }
+
+                #[inline]
+                fn lbs_size_hint(&self) -> usize {
+                    $bytes
+                }
+            }
+
+            impl LBSRead for $t {
+                #[inline]
+                fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+                    let mut buf = [0; $bytes];
+                    r.read_exact(&mut buf)?;
+                    Ok(Self::from_le_bytes(buf))
+                }
+            }
+
+            impl LBSSchema for $t {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    Schema::Opaque(stringify!($t).to_string())
+                }
+            }
+
+            impl LBSMaxSize for $t {
+                const LBS_MAX_SIZE: usize = $bytes;
+            }
+        )*
+    };
+}
+
+impl_narrow_int!(
+    /// Unsigned 24-bit integer, like sample of 24-bit audio.
+    U24(u32, 3, 0, 0xff_ffff),
+    /// Unsigned 48-bit integer, like timestamp or offset of a media container.
+    U48(u64, 6, 0, 0xffff_ffff_ffff),
+    /// Signed 24-bit integer.
+    I24(i32, 3, -0x80_0000, 0x7f_ffff),
+    /// Signed 48-bit integer.
+    I48(i64, 6, -0x8000_0000_0000, 0x7fff_ffff_ffff)
+);
@@ -1,22 +1,43 @@
 use super::LBSRead;
 use super::LBSWrite;
 use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
 use ipnet::IpNet;
 use std::io::Read;
 use std::io::Write;
-use std::str::FromStr;
+use std::net::IpAddr;
 
+/// Written as address followed by `u8` prefix length.
 impl LBSWrite for IpNet {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.to_string().lbs_write(w)
+        self.addr().lbs_write(w)?;
+        self.prefix_len().lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.addr().lbs_size_hint() + 1
     }
 }
 
 impl LBSRead for IpNet {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let s = String::lbs_read(r)?;
-        IpNet::from_str(&s).map_err(|e| LBSError::Parsing(e.to_string()))
+        let addr = IpAddr::lbs_read(r)?;
+        IpNet::new(addr, u8::lbs_read(r)?).map_err(|e| LBSError::Parsing(e.to_string()))
     }
 }
+
+impl LBSSchema for IpNet {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::IpAddr, Schema::U8])
+    }
+}
+
+impl LBSMaxSize for IpNet {
+    const LBS_MAX_SIZE: usize = IpAddr::LBS_MAX_SIZE + 1;
+}
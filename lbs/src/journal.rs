@@ -0,0 +1,186 @@
+use crate::crc32::checksum;
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+
+// Record is u32 payload length, u32 CRC-32 of payload, u32 CRC-32 of these two and payload
+// itself, which is encoded value. Header has its own checksum, so a corrupted length isn't
+// mistaken for a torn record, which would truncate valid records after it.
+const HEADER_LEN: u64 = 12;
+
+/// Appends records to a journal file.
+#[derive(Debug)]
+pub struct Writer {
+    file: BufWriter<File>,
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    /// Opens or creates journal file for appending.
+    ///
+    /// Torn or corrupted final record, left by a crash in the middle of a write, is truncated.
+    /// Corrupted record followed by others, or with corrupted header, fails with
+    /// `LBSError::ChecksumMismatch`, leaving the file untouched, as records after it may still
+    /// be valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, LBSError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let valid_len = scan(&mut file)?;
+
+        if valid_len < file.metadata()?.len() {
+            file.set_len(valid_len)?;
+            file.sync_data()?;
+        }
+
+        file.seek(SeekFrom::Start(valid_len))?;
+
+        Ok(Self {
+            file: BufWriter::new(file),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Appends a record. Record may stay buffered until `flush()` or `sync()`.
+    pub fn append<T: LBSWrite + ?Sized>(&mut self, value: &T) -> Result<(), LBSError> {
+        self.buf.clear();
+        value.lbs_write(&mut self.buf)?;
+
+        let len: u32 = self
+            .buf
+            .len()
+            .try_into()
+            .map_err(|_| std::io::Error::new(ErrorKind::InvalidInput, "record is too large"))?;
+
+        let mut header = [0; HEADER_LEN as usize];
+        header[..4].copy_from_slice(&len.to_le_bytes());
+        header[4..8].copy_from_slice(&checksum(&self.buf).to_le_bytes());
+        let header_crc = checksum(&header[..8]);
+        header[8..].copy_from_slice(&header_crc.to_le_bytes());
+
+        self.file.write_all(&header)?;
+        Ok(self.file.write_all(&self.buf)?)
+    }
+
+    /// Writes buffered records to the file.
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), LBSError> {
+        Ok(self.file.flush()?)
+    }
+
+    /// Writes buffered records to the file and waits until they reach the disk.
+    #[inline]
+    pub fn sync(&mut self) -> Result<(), LBSError> {
+        self.file.flush()?;
+        Ok(self.file.get_ref().sync_data()?)
+    }
+}
+
+/// Reads records from a journal file.
+#[derive(Debug)]
+pub struct Reader {
+    file: BufReader<File>,
+    remaining: u64,
+    buf: Vec<u8>,
+}
+
+impl Reader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, LBSError> {
+        let file = File::open(path)?;
+
+        Ok(Self {
+            remaining: file.metadata()?.len(),
+            file: BufReader::new(file),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Reads next record. Returns `None` at the end of journal, including torn final record.
+    /// Corrupted records fail with `LBSError::ChecksumMismatch`.
+    pub fn read<T: LBSRead>(&mut self) -> Result<Option<T>, LBSError> {
+        match read_record(&mut self.file, &mut self.buf, self.remaining)? {
+            Record::Valid => {
+                self.remaining -= HEADER_LEN + self.buf.len() as u64;
+                from_slice_exact(&self.buf).map(Some)
+            }
+            Record::Torn => Ok(None),
+            Record::Corrupted | Record::CorruptedHeader => Err(LBSError::ChecksumMismatch),
+        }
+    }
+}
+
+enum Record {
+    Valid,
+    Torn,
+    Corrupted,
+    /// Length is unknown, so the record can't be told from following ones.
+    CorruptedHeader,
+}
+
+/// Returns length of the valid part of journal, which is followed by torn final record, if any.
+fn scan(file: &mut File) -> Result<u64, LBSError> {
+    let mut remaining = file.metadata()?.len();
+    let mut valid_len = 0;
+    let mut r = BufReader::new(&mut *file);
+    let mut buf = Vec::new();
+
+    loop {
+        let record = read_record(&mut r, &mut buf, remaining)?;
+        let record_len = HEADER_LEN + buf.len() as u64;
+
+        match record {
+            Record::Valid => {
+                valid_len += record_len;
+                remaining -= record_len;
+            }
+            Record::Corrupted if record_len < remaining => return Err(LBSError::ChecksumMismatch),
+            Record::CorruptedHeader => return Err(LBSError::ChecksumMismatch),
+            Record::Corrupted | Record::Torn => return Ok(valid_len),
+        }
+    }
+}
+
+fn read_record<R: Read>(r: &mut R, buf: &mut Vec<u8>, remaining: u64) -> Result<Record, LBSError> {
+    if remaining < HEADER_LEN {
+        return Ok(Record::Torn);
+    }
+
+    let mut header = [0; HEADER_LEN as usize];
+    r.read_exact(&mut header)?;
+
+    let len = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let header_crc = u32::from_le_bytes(header[8..].try_into().unwrap());
+
+    if checksum(&header[..8]) != header_crc {
+        return Ok(Record::CorruptedHeader);
+    }
+
+    if remaining - HEADER_LEN < len as u64 {
+        return Ok(Record::Torn);
+    }
+
+    buf.clear();
+    buf.resize(len as usize, 0);
+    r.read_exact(buf)?;
+
+    match checksum(buf) == crc {
+        true => Ok(Record::Valid),
+        false => Ok(Record::Corrupted),
+    }
+}
@@ -0,0 +1,233 @@
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use std::fmt;
+
+/// Same as in derived implementations.
+const PACKED_BOOLS_ID: u16 = u16::MAX;
+
+/// Wire layout of a struct or enum: entries in the order they are written, with their ids
+/// and fixed sizes.
+///
+/// Layout is rendered by `Display` into a stable text, which is meant to be compared with
+/// a snapshot in tests, so accidental wire format changes show up in code review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub name: String,
+    pub kind: Kind,
+    pub header: Header,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Struct,
+    Enum,
+}
+
+/// Encoding of field count and ids, or of variant id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Header {
+    Regular,
+    Compact,
+    Indexed,
+}
+
+/// Field of a struct or variant of an enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub id: u16,
+    pub name: String,
+    /// Type of a field or variant payload, if any.
+    pub ty: Option<String>,
+    /// Size of the value in bytes, if it doesn't depend on the value.
+    pub size: Option<usize>,
+    /// Bool, which is packed into a bit under id `u16::MAX`.
+    pub packed: bool,
+}
+
+impl Layout {
+    /// Layout of a struct or enum described by schema, None for other types.
+    pub fn from_schema(schema: &Schema) -> Option<Self> {
+        match schema {
+            Schema::Struct(s) => {
+                let header = match (s.compact, s.indexed) {
+                    (_, true) => Header::Indexed,
+                    (true, false) => Header::Compact,
+                    (false, false) => Header::Regular,
+                };
+
+                let mut fields: Vec<_> = s.fields.iter().collect();
+
+                if header != Header::Regular {
+                    fields.sort_by_key(|f| f.id);
+                }
+
+                let is_packed = |f: &&crate::schema::FieldSchema| {
+                    s.pack_bools && f.schema == Schema::Bool && f.codec.is_none()
+                };
+
+                // Packed bools are written last, as a single field
+                let (packed, regular): (Vec<_>, Vec<_>) = fields.into_iter().partition(is_packed);
+
+                let entries = regular
+                    .into_iter()
+                    .map(|f| Entry {
+                        id: f.id,
+                        name: f.name.clone(),
+                        ty: Some(match f.codec {
+                            Some(ref codec) => format!("{} as {}", type_name(&f.schema), codec),
+                            None => type_name(&f.schema),
+                        }),
+                        size: match f.codec {
+                            Some(_) => None,
+                            None => fixed_size(&f.schema),
+                        },
+                        packed: false,
+                    })
+                    .chain(packed.into_iter().map(|f| Entry {
+                        id: f.id,
+                        name: f.name.clone(),
+                        ty: Some(type_name(&f.schema)),
+                        size: None,
+                        packed: true,
+                    }))
+                    .collect();
+
+                Some(Self {
+                    name: s.name.clone(),
+                    kind: Kind::Struct,
+                    header,
+                    entries,
+                })
+            }
+            Schema::Enum(e) => Some(Self {
+                name: e.name.clone(),
+                kind: Kind::Enum,
+                header: match e.compact {
+                    true => Header::Compact,
+                    false => Header::Regular,
+                },
+                entries: e
+                    .variants
+                    .iter()
+                    .map(|v| Entry {
+                        id: v.id,
+                        name: v.name.clone(),
+                        ty: v.schema.as_ref().map(type_name),
+                        size: match v.schema {
+                            Some(ref schema) => fixed_size(schema),
+                            None => Some(0),
+                        },
+                        packed: false,
+                    })
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Layout of `T`.
+///
+/// # Panics
+///
+/// Panics if `T` is neither a struct nor an enum.
+pub fn layout<T: LBSSchema + ?Sized>() -> Layout {
+    Layout::from_schema(&T::lbs_schema()).unwrap_or_else(|| {
+        panic!(
+            "{} is neither a struct nor an enum",
+            std::any::type_name::<T>()
+        )
+    })
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            Kind::Struct => "struct",
+            Kind::Enum => "enum",
+        };
+
+        let header = match self.header {
+            Header::Regular => "regular",
+            Header::Compact => "compact",
+            Header::Indexed => "indexed",
+        };
+
+        writeln!(f, "{} {} ({} header)", kind, self.name, header)?;
+
+        for entry in &self.entries {
+            write!(f, "  #{} {}", entry.id, entry.name)?;
+
+            if let Some(ref ty) = entry.ty {
+                write!(f, ": {}", ty)?;
+            }
+
+            match (entry.packed, entry.size) {
+                (true, _) => writeln!(f, " [bit of #{}]", PACKED_BOOLS_ID)?,
+                (false, Some(size)) => writeln!(f, " [{} bytes]", size)?,
+                (false, None) => writeln!(f, " [variable]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Size of encoded value, if it doesn't depend on the value.
+fn fixed_size(schema: &Schema) -> Option<usize> {
+    match schema {
+        Schema::Unit => Some(0),
+        Schema::Bool | Schema::U8 | Schema::I8 => Some(1),
+        Schema::U16 | Schema::I16 => Some(2),
+        Schema::U32 | Schema::I32 | Schema::F32 | Schema::Char | Schema::Ipv4Addr => Some(4),
+        Schema::U64 | Schema::I64 | Schema::F64 => Some(8),
+        Schema::Duration | Schema::SystemTime => Some(12),
+        Schema::U128 | Schema::I128 | Schema::Ipv6Addr => Some(16),
+        Schema::Tuple(elements) => elements.iter().map(fixed_size).sum(),
+        Schema::Range(inner) => fixed_size(inner).map(|size| size * 2),
+        _ => None,
+    }
+}
+
+/// Rust-like name of a described type.
+fn type_name(schema: &Schema) -> String {
+    match schema {
+        Schema::Unit => "()".to_string(),
+        Schema::Bool => "bool".to_string(),
+        Schema::U8 => "u8".to_string(),
+        Schema::U16 => "u16".to_string(),
+        Schema::U32 => "u32".to_string(),
+        Schema::U64 => "u64".to_string(),
+        Schema::U128 => "u128".to_string(),
+        Schema::I8 => "i8".to_string(),
+        Schema::I16 => "i16".to_string(),
+        Schema::I32 => "i32".to_string(),
+        Schema::I64 => "i64".to_string(),
+        Schema::I128 => "i128".to_string(),
+        Schema::F32 => "f32".to_string(),
+        Schema::F64 => "f64".to_string(),
+        Schema::Char => "char".to_string(),
+        Schema::String => "String".to_string(),
+        Schema::Duration => "Duration".to_string(),
+        Schema::SystemTime => "SystemTime".to_string(),
+        Schema::Ipv4Addr => "Ipv4Addr".to_string(),
+        Schema::Ipv6Addr => "Ipv6Addr".to_string(),
+        Schema::IpAddr => "IpAddr".to_string(),
+        Schema::Option(inner) => format!("Option<{}>", type_name(inner)),
+        Schema::List(inner) => format!("[{}]", type_name(inner)),
+        Schema::Map(kv) => format!("{{{}: {}}}", type_name(&kv.0), type_name(&kv.1)),
+        Schema::Tuple(elements) => format!(
+            "({})",
+            elements
+                .iter()
+                .map(type_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Schema::Range(inner) => format!("Range<{}>", type_name(inner)),
+        Schema::Struct(s) => s.name.clone(),
+        Schema::Enum(e) => e.name.clone(),
+        Schema::Ref(name) | Schema::Opaque(name) => name.clone(),
+    }
+}
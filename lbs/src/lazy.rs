@@ -0,0 +1,177 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::vectored::VectoredWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// Value, which is decoded on first access.
+///
+/// Reading captures encoded bytes of the value by skipping it, so wire format is the same as of `T`.
+/// Value is decoded and cached by `get()`. Until it is accessed mutably, writing copies captured bytes
+/// back untouched, ignoring encoder options.
+pub struct Lazy<T> {
+    raw: Option<Vec<u8>>,
+    value: OnceLock<T>,
+}
+
+impl<T> Lazy<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            raw: None,
+            value: OnceLock::from(value),
+        }
+    }
+
+    /// Encoded bytes captured at read time, if value wasn't modified since.
+    #[inline]
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// True if value was already decoded.
+    #[inline]
+    pub fn is_decoded(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl<T: LBSRead> Lazy<T> {
+    /// Returns value, decoding it on first call.
+    #[inline]
+    pub fn get(&self) -> Result<&T, LBSError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+
+        let raw = self.raw.as_deref().unwrap_or_default();
+        let _ = self.value.set(from_slice_exact(raw)?);
+        Ok(self.value.get().unwrap())
+    }
+
+    /// Returns value for modification. Captured bytes are dropped, so value is encoded on write.
+    #[inline]
+    pub fn get_mut(&mut self) -> Result<&mut T, LBSError> {
+        self.get()?;
+        self.raw = None;
+        Ok(self.value.get_mut().unwrap())
+    }
+
+    #[inline]
+    pub fn into_inner(mut self) -> Result<T, LBSError> {
+        self.get()?;
+        Ok(self.value.take().unwrap())
+    }
+}
+
+impl<T> From<T> for Lazy<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for Lazy<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for Lazy<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.value.get(), &self.raw) {
+            (Some(value), _) => f.debug_tuple("Lazy").field(value).finish(),
+            (None, raw) => f
+                .debug_struct("Lazy")
+                .field("raw_len", &raw.as_ref().map_or(0, Vec::len))
+                .finish(),
+        }
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for Lazy<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        match (&self.raw, self.value.get()) {
+            (Some(raw), _) => Ok(w.write_all(raw)?),
+            (None, Some(value)) => value.lbs_write_with(w, e),
+            (None, None) => unreachable!("lazy value has neither bytes nor value"),
+        }
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        match (&self.raw, self.value.get()) {
+            (Some(raw), _) => {
+                v.write_borrowed(raw);
+                Ok(())
+            }
+            (None, Some(value)) => value.lbs_write_vectored(v),
+            (None, None) => unreachable!("lazy value has neither bytes nor value"),
+        }
+    }
+}
+
+impl<T: LBSRead> LBSRead for Lazy<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut capture = Capture {
+            inner: r,
+            buf: Vec::new(),
+        };
+
+        T::lbs_skip(&mut capture, d)?;
+
+        Ok(Self {
+            raw: Some(capture.buf),
+            value: OnceLock::new(),
+        })
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+}
+
+/// Reader, which keeps copy of all bytes read through it.
+struct Capture<'a, R> {
+    inner: &'a mut R,
+    buf: Vec<u8>,
+}
+
+impl<'a, R: Read> Read for Capture<'a, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.buf.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
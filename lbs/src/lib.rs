@@ -1,9 +1,87 @@
+// Allows derived implementations inside of this crate
+extern crate self as lbs;
+
+pub use decoder::Decoder;
+pub use encoder::Encoder;
 pub use lbs_derive::*;
+pub use options::from_slice;
+pub use options::from_slice_with;
+pub use options::to_vec;
+pub use options::to_vec_with;
+pub use options::to_vec_within;
+pub use options::Options;
+pub use raw::RawValue;
 pub use read::LBSRead;
+pub use stats::from_slice_with_stats;
+pub use unknown::UnknownFields;
+pub use write::eq_encoded;
 pub use write::LBSWrite;
 
+#[cfg(feature = "bytes")]
+pub use crate::bytes::from_bytes;
+#[cfg(feature = "bytes")]
+pub use crate::bytes::from_bytes_with;
+#[cfg(feature = "bytes")]
+pub use crate::bytes::read_buf;
+#[cfg(feature = "bytes")]
+pub use crate::bytes::to_bytes;
+#[cfg(feature = "bytes")]
+pub use crate::bytes::write_buf;
+
+pub mod batch;
+pub mod blob;
+pub mod borrow;
+pub mod cache;
+pub mod calendar;
+pub mod capture;
+pub mod chunk;
+pub mod columnar;
+pub mod compact_duration;
+pub mod compat;
+pub mod crc32;
+pub mod ctx;
+pub mod decoder;
+pub mod delimited;
+pub mod delta_keys;
+pub mod diff;
+pub mod dynamic;
+pub mod encoder;
 pub mod error;
+pub mod fixed_point;
+pub mod frame;
+pub mod hash;
+pub mod incremental;
+pub mod indexed;
+pub mod inspect;
+pub mod int;
+pub mod layout;
+pub mod lazy;
+pub mod limits;
+pub mod metrics;
+pub mod migrate;
+pub mod options;
+pub mod owned;
+pub mod raw;
 pub mod read;
+pub mod relative_time;
+pub mod remote;
+pub mod schema;
+pub mod sink;
+pub mod size;
+pub mod sparse;
+pub mod spill;
+pub mod stats;
+pub mod testing;
+pub mod tracked;
+pub mod transcode;
+pub mod try_from;
+pub mod unit;
+pub mod unknown;
+pub mod utf8;
+pub mod varint;
+pub mod vectored;
+pub mod visit;
+pub mod widen;
 pub mod write;
 
 #[cfg(feature = "chrono")]
@@ -26,3 +104,99 @@ mod fraction;
 
 #[cfg(feature = "ordered-float")]
 mod ordered_float;
+
+#[cfg(feature = "parking_lot")]
+mod parking_lot;
+
+#[cfg(feature = "triomphe")]
+mod triomphe;
+
+#[cfg(feature = "macaddr")]
+mod macaddr;
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "rgb")]
+mod rgb;
+
+#[cfg(feature = "palette")]
+mod palette;
+
+#[cfg(feature = "bytes")]
+mod bytes;
+
+#[cfg(feature = "bytestring")]
+mod bytestring;
+
+#[cfg(feature = "digest")]
+pub mod digest;
+
+#[cfg(feature = "cas")]
+pub mod cas;
+
+#[cfg(feature = "rayon")]
+pub mod rayon;
+
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+#[cfg(feature = "journal")]
+pub mod journal;
+
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+
+#[cfg(feature = "chain")]
+pub mod chain;
+
+#[cfg(feature = "mmap")]
+pub mod mmap;
+
+#[cfg(feature = "registry")]
+pub mod registry;
+
+#[cfg(feature = "crypto")]
+pub mod crypto;
+
+#[cfg(feature = "signing")]
+pub mod signing;
+
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(feature = "sled")]
+pub mod sled;
+
+#[cfg(feature = "rdkafka")]
+pub mod rdkafka;
+
+#[cfg(feature = "tonic")]
+pub mod tonic;
+
+#[cfg(feature = "actix-web")]
+pub mod actix_web;
+
+#[cfg(feature = "http-body")]
+pub mod http_body;
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+#[cfg(feature = "tokio-util")]
+pub mod tokio_util;
+
+#[cfg(feature = "allocator-api2")]
+pub mod allocator_api2;
+
+#[cfg(feature = "zeroize")]
+pub mod zeroize;
+
+#[cfg(feature = "secrecy")]
+pub mod secrecy;
+
+#[cfg(feature = "serde")]
+pub mod serde;
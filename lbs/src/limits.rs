@@ -0,0 +1,39 @@
+/// Limits of decoded input, which protect decoders of untrusted input from exhausting memory,
+/// stack or time. Default limits are none.
+///
+/// Set with `Decoder::limits()` or `Options::limits()`. Exceeded limit fails decoding with
+/// `LBSError::LimitExceeded`. Regardless of limits, collections and strings never preallocate
+/// more than `read::MAX_PREALLOCATION` bytes upfront and grow as their contents arrive, so
+/// a short input can't make decoder allocate much.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// Largest length of a string, byte array or number of elements of a collection.
+    pub max_len: Option<usize>,
+    /// Largest nesting of struct fields and enum variants.
+    pub max_depth: Option<usize>,
+    /// Largest size of a whole message, enforced by `Decoder::decode()` and
+    /// `Decoder::decode_async()`.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Limits {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            max_len: None,
+            max_depth: None,
+            max_total_bytes: None,
+        }
+    }
+
+    /// Limits for messages received from the network: 1 MiB lengths, 64 nested values and
+    /// 16 MiB messages.
+    #[inline]
+    pub const fn untrusted() -> Self {
+        Self {
+            max_len: Some(1 << 20),
+            max_depth: Some(64),
+            max_total_bytes: Some(16 << 20),
+        }
+    }
+}
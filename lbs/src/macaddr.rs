@@ -0,0 +1,105 @@
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use macaddr::MacAddr;
+use macaddr::MacAddr6;
+use macaddr::MacAddr8;
+use std::io::Read;
+use std::io::Write;
+
+/// Written as 6 raw bytes.
+impl LBSWrite for MacAddr6 {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        Ok(w.write_all(self.as_bytes())?)
+    }
+}
+
+/// Written as 8 raw bytes.
+impl LBSWrite for MacAddr8 {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        Ok(w.write_all(self.as_bytes())?)
+    }
+}
+
+/// Written as `true` followed by 6 bytes, or `false` followed by 8 bytes, like `IpAddr`.
+impl LBSWrite for MacAddr {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        match self {
+            MacAddr::V6(addr) => {
+                true.lbs_write(w)?;
+                addr.lbs_write(w)
+            }
+            MacAddr::V8(addr) => {
+                false.lbs_write(w)?;
+                addr.lbs_write(w)
+            }
+        }
+    }
+}
+
+impl LBSRead for MacAddr6 {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let mut buf = [0; 6];
+        r.read_exact(&mut buf)?;
+        Ok(Self::from(buf))
+    }
+}
+
+impl LBSRead for MacAddr8 {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let mut buf = [0; 8];
+        r.read_exact(&mut buf)?;
+        Ok(Self::from(buf))
+    }
+}
+
+impl LBSRead for MacAddr {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(match bool::lbs_read(r)? {
+            true => MacAddr::V6(MacAddr6::lbs_read(r)?),
+            false => MacAddr::V8(MacAddr8::lbs_read(r)?),
+        })
+    }
+}
+
+impl LBSSchema for MacAddr6 {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::U8; 6])
+    }
+}
+
+impl LBSSchema for MacAddr8 {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::U8; 8])
+    }
+}
+
+impl LBSSchema for MacAddr {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Opaque("MacAddr".to_string())
+    }
+}
+
+impl LBSMaxSize for MacAddr6 {
+    const LBS_MAX_SIZE: usize = 6;
+}
+
+impl LBSMaxSize for MacAddr8 {
+    const LBS_MAX_SIZE: usize = 8;
+}
+
+impl LBSMaxSize for MacAddr {
+    const LBS_MAX_SIZE: usize = 9;
+}
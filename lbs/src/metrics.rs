@@ -0,0 +1,213 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::Instant;
+
+static GLOBAL: OnceLock<Arc<dyn LBSMetrics>> = OnceLock::new();
+
+/// Receiver of encoding and decoding events.
+///
+/// Events are reported by `Encoder::encode()` and `Decoder::decode()`, either to metrics set with
+/// `Encoder::metrics()` / `Decoder::metrics()`, or to global ones, see `set_global()`.
+/// Nested values aren't reported separately. Without metrics nothing is measured.
+///
+/// With `tracing` feature, the same calls are also wrapped into `lbs_encode` / `lbs_decode` spans
+/// with type name, byte size and duration, and failures are logged with field path.
+pub trait LBSMetrics: Send + Sync {
+    /// Value of type `type_name` was encoded into `bytes` bytes.
+    #[inline]
+    fn encoded(&self, _type_name: &'static str, _bytes: usize, _elapsed: Duration) {}
+
+    /// Value of type `type_name` was decoded from `bytes` bytes.
+    #[inline]
+    fn decoded(&self, _type_name: &'static str, _bytes: usize, _elapsed: Duration) {}
+
+    /// Encoding of a value of type `type_name` failed.
+    #[inline]
+    fn encode_failed(&self, _type_name: &'static str, _error: &LBSError) {}
+
+    /// Decoding of a value of type `type_name` failed after reading `bytes` bytes.
+    #[inline]
+    fn decode_failed(&self, _type_name: &'static str, _bytes: usize, _error: &LBSError) {}
+
+    /// Field `id` of type `type_name`, which is marked `deprecated_id` with `note`, was read.
+    /// Reported for every occurrence, including nested values.
+    #[inline]
+    fn deprecated_field(&self, _type_name: &'static str, _id: u16, _note: &'static str) {}
+}
+
+/// Shared metrics handle, which is stored by encoders and decoders.
+#[derive(Clone)]
+pub(crate) struct Metrics(pub(crate) Arc<dyn LBSMetrics>);
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+/// Installs metrics, which receive events of encoders and decoders without their own ones.
+/// Global metrics may be set only once, otherwise they are returned back.
+#[inline]
+pub fn set_global(metrics: Arc<dyn LBSMetrics>) -> Result<(), Arc<dyn LBSMetrics>> {
+    GLOBAL.set(metrics)
+}
+
+/// Global metrics, if set.
+#[inline]
+pub fn global() -> Option<&'static Arc<dyn LBSMetrics>> {
+    GLOBAL.get()
+}
+
+/// Metrics of an encoder or decoder, or global ones.
+#[inline]
+pub(crate) fn resolve(metrics: &Option<Metrics>) -> Option<Arc<dyn LBSMetrics>> {
+    match metrics {
+        Some(metrics) => Some(metrics.0.clone()),
+        None => global().cloned(),
+    }
+}
+
+/// Reports reading of a deprecated field, used by derived implementations.
+#[doc(hidden)]
+#[cold]
+pub fn deprecated_field(d: &Decoder, type_name: &'static str, id: u16, note: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(type_name, id, note, "deprecated field read");
+
+    if let Some(metrics) = resolve(d.metrics_handle()) {
+        metrics.deprecated_field(type_name, id, note);
+    }
+}
+
+/// Reports encoding of a value by `f` and returns number of bytes written.
+pub(crate) fn measure_write<W, F>(
+    metrics: Option<&dyn LBSMetrics>,
+    type_name: &'static str,
+    w: &mut W,
+    f: F,
+) -> Result<usize, LBSError>
+where
+    W: Write,
+    F: FnOnce(&mut Counting<&mut W>) -> Result<(), LBSError>,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        "lbs_encode",
+        type_name,
+        bytes = tracing::field::Empty,
+        duration_us = tracing::field::Empty,
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+
+    let start = Instant::now();
+    let mut counting = Counting::new(w);
+    let result = f(&mut counting);
+    let elapsed = start.elapsed();
+
+    #[cfg(feature = "tracing")]
+    record(&span, counting.count, elapsed, result.as_ref().err());
+
+    match (metrics, &result) {
+        (Some(metrics), Ok(())) => metrics.encoded(type_name, counting.count, elapsed),
+        (Some(metrics), Err(e)) => metrics.encode_failed(type_name, e),
+        (None, _) => {}
+    }
+
+    result.map(|_| counting.count)
+}
+
+/// Reports decoding of a value by `f`.
+pub(crate) fn measure_read<T, R, F>(
+    metrics: Option<&dyn LBSMetrics>,
+    type_name: &'static str,
+    r: &mut R,
+    f: F,
+) -> Result<T, LBSError>
+where
+    R: Read,
+    F: FnOnce(&mut Counting<&mut R>) -> Result<T, LBSError>,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        "lbs_decode",
+        type_name,
+        bytes = tracing::field::Empty,
+        duration_us = tracing::field::Empty,
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+
+    let start = Instant::now();
+    let mut counting = Counting::new(r);
+    let result = f(&mut counting);
+    let elapsed = start.elapsed();
+
+    #[cfg(feature = "tracing")]
+    record(&span, counting.count, elapsed, result.as_ref().err());
+
+    match (metrics, &result) {
+        (Some(metrics), Ok(_)) => metrics.decoded(type_name, counting.count, elapsed),
+        (Some(metrics), Err(e)) => metrics.decode_failed(type_name, counting.count, e),
+        (None, _) => {}
+    }
+
+    result
+}
+
+#[cfg(feature = "tracing")]
+fn record(span: &tracing::Span, bytes: usize, elapsed: Duration, error: Option<&LBSError>) {
+    span.record("bytes", bytes);
+    span.record("duration_us", elapsed.as_micros() as u64);
+
+    if let Some(e) = error {
+        tracing::warn!(
+            field_path = ?e.field_path(),
+            error = %e.root_cause(),
+            "{} failed",
+            span.metadata().map_or("lbs", |m| m.name())
+        );
+    }
+}
+
+/// Adapter, which counts bytes passed through it.
+pub(crate) struct Counting<T> {
+    inner: T,
+    count: usize,
+}
+
+impl<T> Counting<T> {
+    #[inline]
+    fn new(inner: T) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for Counting<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> Read for Counting<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::write::CountingWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Type tagged with a schema version.
+/// Implemented by derive for types with `#[lbs(version(<u16>))]` container attribute.
+pub trait Versioned {
+    const VERSION: u16;
+}
+
+type ReadFn<T> = Box<dyn Fn(&mut dyn Read) -> Result<T, LBSError> + Send + Sync>;
+type UpgradeFn<T> = Arc<dyn Fn(Box<dyn Any>) -> Result<T, LBSError> + Send + Sync>;
+
+struct Step<T> {
+    /// Type registered for the version, which upgrader accepts.
+    type_id: TypeId,
+    read: ReadFn<T>,
+    upgrade: UpgradeFn<T>,
+}
+
+/// Takes value of the type registered for `version`.
+#[inline]
+fn downcast<V: 'static>(value: Box<dyn Any>, version: u16) -> Result<V, LBSError> {
+    value
+        .downcast::<V>()
+        .map(|value| *value)
+        .map_err(|_| LBSError::VersionConflict(version))
+}
+
+/// Registry of upgraders, which decodes any registered historical version of `T` and upgrades it to `T`.
+///
+/// Upgraders are registered from the latest version backwards: `V2 -> V3`, then `V1 -> V2`, and so on.
+pub struct Migrations<T> {
+    steps: HashMap<u16, Step<T>>,
+}
+
+impl<T: LBSRead + Versioned + 'static> Migrations<T> {
+    pub fn new() -> Self {
+        let mut steps = HashMap::new();
+
+        steps.insert(
+            T::VERSION,
+            Step {
+                type_id: TypeId::of::<T>(),
+                read: Box::new(|mut r: &mut dyn Read| T::lbs_read(&mut r)) as ReadFn<T>,
+                upgrade: Arc::new(|value: Box<dyn Any>| downcast::<T>(value, T::VERSION))
+                    as UpgradeFn<T>,
+            },
+        );
+
+        Self { steps }
+    }
+
+    /// Registers upgrader from `Old` to `New`. Upgrader for `New` must be registered before.
+    ///
+    /// Fails with `LBSError::UnsupportedVersion` if version of `New` isn't registered, and with
+    /// `LBSError::VersionConflict` if it's registered for another type or version of `Old`
+    /// is registered already, like when two types declare the same version.
+    pub fn register<Old, New, F>(mut self, upgrade: F) -> Result<Self, LBSError>
+    where
+        Old: LBSRead + Versioned + 'static,
+        New: Versioned + 'static,
+        F: Fn(Old) -> New + Send + Sync + 'static,
+    {
+        let next = match self.steps.get(&New::VERSION) {
+            Some(step) if step.type_id == TypeId::of::<New>() => step.upgrade.clone(),
+            Some(_) => return Err(LBSError::VersionConflict(New::VERSION)),
+            None => return Err(LBSError::UnsupportedVersion(New::VERSION)),
+        };
+
+        if self.steps.contains_key(&Old::VERSION) {
+            return Err(LBSError::VersionConflict(Old::VERSION));
+        }
+
+        let upgrade: UpgradeFn<T> = Arc::new(move |value: Box<dyn Any>| {
+            next(Box::new(upgrade(downcast::<Old>(value, Old::VERSION)?)))
+        });
+
+        let read_upgrade = upgrade.clone();
+        let read: ReadFn<T> =
+            Box::new(move |mut r: &mut dyn Read| read_upgrade(Box::new(Old::lbs_read(&mut r)?)));
+
+        let step = Step {
+            type_id: TypeId::of::<Old>(),
+            read,
+            upgrade,
+        };
+
+        self.steps.insert(Old::VERSION, step);
+        Ok(self)
+    }
+
+    /// Reads value written by `write()` with any registered version and upgrades it to `T`.
+    pub fn migrate_read<R: Read>(&self, r: &mut R) -> Result<T, LBSError> {
+        let version = u16::lbs_read(r)?;
+
+        match self.steps.get(&version) {
+            Some(step) => (step.read)(r),
+            None => Err(LBSError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl<T: LBSRead + Versioned + 'static> Default for Migrations<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes version of `T` followed by value. Returns number of bytes written.
+#[inline]
+pub fn write<T: LBSWrite + Versioned, W: Write>(w: &mut W, value: &T) -> Result<usize, LBSError> {
+    let mut w = CountingWriter::new(w);
+    T::VERSION.lbs_write(&mut w)?;
+    value.lbs_write(&mut w)?;
+    Ok(w.count())
+}
+
+/// Writes `version` followed by value as of that schema version, so fields tagged with
+/// `#[lbs(since(<u16>))]` / `#[lbs(removed(<u16>))]` are written only if present in it.
+/// Returns number of bytes written.
+#[inline]
+pub fn write_as<T: LBSWrite + ?Sized, W: Write>(
+    w: &mut W,
+    value: &T,
+    version: u16,
+) -> Result<usize, LBSError> {
+    let mut w = CountingWriter::new(w);
+    version.lbs_write(&mut w)?;
+    value.lbs_write_with(&mut w, Encoder::new().for_version(version))?;
+    Ok(w.count())
+}
+
+/// Reads value written by `write_as()` as of schema version it was written with.
+/// Fields missing in that version are left default. Returns value and the version.
+#[inline]
+pub fn read_as<T: LBSRead, R: Read>(r: &mut R) -> Result<(T, u16), LBSError> {
+    let version = u16::lbs_read(r)?;
+    let mut d = Decoder::new();
+    d.for_version(version);
+    Ok((T::lbs_read_with(r, &mut d)?, version))
+}
+
+/// Whether field, which appears in schema version `since` and is removed in `removed` one,
+/// is present in `version`, or in the latest one if it's None. Used by derived implementations.
+#[inline]
+pub fn is_present(version: Option<u16>, since: u16, removed: Option<u16>) -> bool {
+    match version {
+        Some(version) => version >= since && removed.is_none_or(|removed| version < removed),
+        None => removed.is_none(),
+    }
+}
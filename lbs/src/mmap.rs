@@ -0,0 +1,162 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::frame::DEFAULT_MAX_FRAME_LEN;
+use crate::indexed::IndexedView;
+use crate::LBSRead;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::Path;
+
+const LEN_PREFIX: usize = 4;
+
+/// Memory-mapped file of frames written by `lbs::frame::write_frame()`.
+///
+/// Framing is validated once on open, then frames are handed out as borrowed slices of the mapping,
+/// which are decoded only on request.
+#[derive(Debug)]
+pub struct MappedFrames {
+    map: Mmap,
+    count: usize,
+}
+
+/// Single frame of a `MappedFrames`.
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    bytes: &'a [u8],
+}
+
+/// Iterator over frames of a `MappedFrames`.
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    rest: &'a [u8],
+}
+
+impl MappedFrames {
+    /// Maps file and validates its framing, see `open_with_limit()`.
+    ///
+    /// # Safety
+    ///
+    /// File must not be modified or truncated while mapped, see `memmap2::Mmap::map()`.
+    #[inline]
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, LBSError> {
+        Self::open_with_limit(path, DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Maps file and validates its framing. Fails with `LBSError::FrameTooLarge`
+    /// if any frame exceeds `max_len` and with EOF if the last frame is truncated.
+    ///
+    /// # Safety
+    ///
+    /// File must not be modified or truncated while mapped, see `memmap2::Mmap::map()`.
+    #[inline]
+    pub unsafe fn open_with_limit<P: AsRef<Path>>(
+        path: P,
+        max_len: usize,
+    ) -> Result<Self, LBSError> {
+        let file = File::open(path)?;
+        Self::from_mmap(Mmap::map(&file)?, max_len)
+    }
+
+    /// Validates framing of an already mapped file.
+    pub fn from_mmap(map: Mmap, max_len: usize) -> Result<Self, LBSError> {
+        let mut rest = &map[..];
+        let mut count = 0;
+
+        while !rest.is_empty() {
+            let (_, tail) = split_frame(rest, max_len)?;
+            rest = tail;
+            count += 1;
+        }
+
+        Ok(Self { map, count })
+    }
+
+    /// Number of frames.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Frames<'_> {
+        Frames { rest: &self.map }
+    }
+}
+
+impl<'a> IntoIterator for &'a MappedFrames {
+    type Item = Frame<'a>;
+    type IntoIter = Frames<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Frame<'a> {
+    /// Encoded value, borrowed from the mapping.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Decodes value, which must occupy the whole frame.
+    #[inline]
+    pub fn decode<T: LBSRead>(&self) -> Result<T, LBSError> {
+        self.decode_with(&mut Decoder::new())
+    }
+
+    /// Same as `decode()`, but reuses state of the given decoder.
+    #[inline]
+    pub fn decode_with<T: LBSRead>(&self, d: &mut Decoder) -> Result<T, LBSError> {
+        let mut bytes = self.bytes;
+        let value = d.decode(&mut bytes)?;
+
+        match bytes.len() {
+            0 => Ok(value),
+            n => Err(LBSError::TrailingBytes(n)),
+        }
+    }
+
+    /// Random access view of a value encoded with `#[lbs(indexed)]` container.
+    #[inline]
+    pub fn view(&self) -> Result<IndexedView<'a>, LBSError> {
+        IndexedView::new(self.bytes)
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        // Framing was validated on open
+        let (bytes, rest) = split_frame(self.rest, usize::MAX).unwrap();
+        self.rest = rest;
+        Some(Frame { bytes })
+    }
+}
+
+fn split_frame(bytes: &[u8], max_len: usize) -> Result<(&[u8], &[u8]), LBSError> {
+    let eof = || LBSError::from(std::io::Error::from(ErrorKind::UnexpectedEof));
+
+    let mut prefix = bytes.get(..LEN_PREFIX).ok_or_else(eof)?;
+    let len = u32::lbs_read(&mut prefix)? as usize;
+
+    if len > max_len {
+        return Err(LBSError::FrameTooLarge { len, max_len });
+    }
+
+    let frame = bytes.get(LEN_PREFIX..LEN_PREFIX + len).ok_or_else(eof)?;
+    Ok((frame, &bytes[LEN_PREFIX + len..]))
+}
@@ -0,0 +1,168 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::limits::Limits;
+use crate::LBSRead;
+use crate::LBSWrite;
+
+/// Runtime format options, which configure both sides of a connection at once.
+///
+/// Options are applied by creating `Encoder` and `Decoder` with `encoder()` and `decoder()`,
+/// which thread them through derived implementations into nested values.
+/// Parts of the format, which are chosen per type, like varint ids of `#[lbs(compact)]`,
+/// must be the same on both sides and aren't covered. Integers are always little-endian.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    canonical: bool,
+    share_strings: bool,
+    intern_strings: bool,
+    lenient_bools: bool,
+    version: Option<u16>,
+    exact: bool,
+    limits: Limits,
+}
+
+impl Options {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            canonical: false,
+            share_strings: false,
+            intern_strings: false,
+            lenient_bools: false,
+            version: None,
+            exact: false,
+            limits: Limits::new(),
+        }
+    }
+
+    /// Makes semantically equal values to produce identical bytes, so they may be hashed
+    /// or compared. See `Encoder::canonicalize_nan()` and `Encoder::canonicalize_zero()`.
+    #[inline]
+    pub fn canonical(&mut self, enabled: bool) -> &mut Self {
+        self.canonical = enabled;
+        self
+    }
+
+    /// Makes repeated strings to be written once, see `Encoder::share_strings()`.
+    #[inline]
+    pub fn share_strings(&mut self, enabled: bool) -> &mut Self {
+        self.share_strings = enabled;
+        self
+    }
+
+    /// Makes equal decoded `Arc<str>` values to share memory, see `Decoder::intern_strings()`.
+    #[inline]
+    pub fn intern_strings(&mut self, enabled: bool) -> &mut Self {
+        self.intern_strings = enabled;
+        self
+    }
+
+    /// Makes bools other than 0 and 1 to be read as false, see `Decoder::lenient_bools()`.
+    #[inline]
+    pub fn lenient_bools(&mut self, enabled: bool) -> &mut Self {
+        self.lenient_bools = enabled;
+        self
+    }
+
+    /// Makes values to be written and read as of given schema version,
+    /// see `Encoder::for_version()` and `Decoder::for_version()`.
+    #[inline]
+    pub fn for_version(&mut self, version: u16) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Makes `from_slice_with()` to fail with `LBSError::TrailingBytes`,
+    /// if value doesn't occupy the whole slice.
+    #[inline]
+    pub fn exact(&mut self, enabled: bool) -> &mut Self {
+        self.exact = enabled;
+        self
+    }
+
+    /// Limits input of decoders, see `Decoder::limits()`.
+    #[inline]
+    pub fn limits(&mut self, limits: Limits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Encoder with these options.
+    #[inline]
+    pub fn encoder(&self) -> Encoder {
+        let mut e = Encoder::new();
+
+        e.canonicalize_nan(self.canonical)
+            .canonicalize_zero(self.canonical)
+            .share_strings(self.share_strings);
+
+        if let Some(version) = self.version {
+            e.for_version(version);
+        }
+
+        e
+    }
+
+    /// Decoder with these options.
+    #[inline]
+    pub fn decoder(&self) -> Decoder {
+        let mut d = Decoder::new();
+
+        d.share_strings(self.share_strings);
+        d.intern_strings(self.intern_strings);
+        d.lenient_bools(self.lenient_bools);
+        d.limits(self.limits);
+
+        if let Some(version) = self.version {
+            d.for_version(version);
+        }
+
+        d
+    }
+}
+
+/// Encodes a value into a vector allocated once, with capacity of `LBSWrite::lbs_size_hint()`.
+#[inline]
+pub fn to_vec<T: LBSWrite + ?Sized>(value: &T) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::with_capacity(value.lbs_size_hint());
+    value.lbs_write(&mut buf)?;
+    Ok(buf)
+}
+
+/// Encodes a value with given options.
+#[inline]
+pub fn to_vec_with<T: LBSWrite + ?Sized>(
+    value: &T,
+    options: &Options,
+) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::with_capacity(value.lbs_size_hint());
+    options.encoder().encode(value, &mut buf)?;
+    Ok(buf)
+}
+
+/// Encodes a value into at most `budget` bytes, omitting droppable fields if needed,
+/// see `Encoder::encode_within()`.
+#[inline]
+pub fn to_vec_within<T: LBSWrite + ?Sized>(value: &T, budget: usize) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    Encoder::new().encode_within(value, &mut buf, budget)?;
+    Ok(buf)
+}
+
+/// Decodes a value from the beginning of a slice, same as `from_slice_with()` with default options.
+#[inline]
+pub fn from_slice<T: LBSRead>(mut bytes: &[u8]) -> Result<T, LBSError> {
+    T::lbs_read(&mut bytes)
+}
+
+/// Decodes a value with given options.
+#[inline]
+pub fn from_slice_with<T: LBSRead>(mut bytes: &[u8], options: &Options) -> Result<T, LBSError> {
+    let value = options.decoder().decode(&mut bytes)?;
+
+    match (options.exact, bytes.len()) {
+        (true, n) if n > 0 => Err(LBSError::TrailingBytes(n)),
+        _ => Ok(value),
+    }
+}
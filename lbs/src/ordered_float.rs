@@ -1,3 +1,5 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
 use crate::error::LBSError;
 use crate::LBSRead;
 use crate::LBSWrite;
@@ -10,6 +12,11 @@ impl<T: LBSWrite> LBSWrite for OrderedFloat<T> {
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
         self.0.lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.0.lbs_write_with(w, e)
+    }
 }
 
 impl<T: LBSRead> LBSRead for OrderedFloat<T> {
@@ -17,4 +24,9 @@ impl<T: LBSRead> LBSRead for OrderedFloat<T> {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(OrderedFloat(T::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(OrderedFloat(T::lbs_read_with(r, d)?))
+    }
 }
@@ -0,0 +1,165 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::frame::DEFAULT_MAX_FRAME_LEN;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Write;
+
+/// Result, which gives the buffer back regardless of success, like in tokio-uring and monoio.
+pub type BufResult<T, B> = (Result<T, LBSError>, B);
+
+/// Owned buffer, which may be submitted to completion-based IO, like io_uring.
+pub trait OwnedBuf: AsRef<[u8]> + AsMut<[u8]> {
+    /// Removes all bytes, keeping capacity.
+    fn clear(&mut self);
+
+    fn extend_from_slice(&mut self, bytes: &[u8]);
+}
+
+impl OwnedBuf for Vec<u8> {
+    #[inline]
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        Vec::extend_from_slice(self, bytes)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl OwnedBuf for bytes::BytesMut {
+    #[inline]
+    fn clear(&mut self) {
+        bytes::BytesMut::clear(self)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        bytes::BytesMut::extend_from_slice(self, bytes)
+    }
+}
+
+/// Writer, which appends to an owned buffer.
+struct Appender<'a, B>(&'a mut B);
+
+impl<B: OwnedBuf> Write for Appender<'_, B> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Replaces contents of the buffer with encoded value and returns its length.
+/// On error the buffer is returned empty.
+#[inline]
+pub fn encode<T: LBSWrite + ?Sized, B: OwnedBuf>(value: &T, buf: B) -> BufResult<usize, B> {
+    encode_with(value, buf, &mut Encoder::new())
+}
+
+/// Same as `encode()`, but reuses state of the given encoder.
+pub fn encode_with<T: LBSWrite + ?Sized, B: OwnedBuf>(
+    value: &T,
+    mut buf: B,
+    e: &mut Encoder,
+) -> BufResult<usize, B> {
+    buf.clear();
+    let result = e.encode(value, &mut Appender(&mut buf));
+
+    if result.is_err() {
+        buf.clear();
+    }
+
+    (result, buf)
+}
+
+/// Same as `encode()`, but writes value as a frame, see `lbs::frame::write_frame()`.
+/// Length includes the prefix.
+pub fn encode_frame<T: LBSWrite + ?Sized, B: OwnedBuf>(
+    value: &T,
+    mut buf: B,
+) -> BufResult<usize, B> {
+    buf.clear();
+    buf.extend_from_slice(&[0; 4]);
+
+    let result = Encoder::new()
+        .encode(value, &mut Appender(&mut buf))
+        .and_then(|len| {
+            let prefix: u32 = len.try_into().map_err(|_| LBSError::FrameTooLarge {
+                len,
+                max_len: u32::MAX as usize,
+            })?;
+            buf.as_mut()[..4].copy_from_slice(&prefix.to_le_bytes());
+            Ok(4 + len)
+        });
+
+    if result.is_err() {
+        buf.clear();
+    }
+
+    (result, buf)
+}
+
+/// Decodes a value, which must occupy the whole buffer.
+#[inline]
+pub fn decode<T: LBSRead, B: OwnedBuf>(buf: B) -> BufResult<T, B> {
+    decode_with(buf, &mut Decoder::new())
+}
+
+/// Same as `decode()`, but reuses state of the given decoder.
+pub fn decode_with<T: LBSRead, B: OwnedBuf>(buf: B, d: &mut Decoder) -> BufResult<T, B> {
+    let mut bytes = buf.as_ref();
+
+    let result = d.decode(&mut bytes).and_then(|value| match bytes.len() {
+        0 => Ok(value),
+        n => Err(LBSError::TrailingBytes(n)),
+    });
+
+    (result, buf)
+}
+
+/// Decodes a frame at the start of the buffer, which may be filled by several reads.
+///
+/// Returns value with number of consumed bytes, or None if the frame is not complete yet.
+/// Fails with `LBSError::FrameTooLarge` as soon as length exceeds `DEFAULT_MAX_FRAME_LEN`.
+#[inline]
+pub fn decode_frame<T: LBSRead, B: OwnedBuf>(buf: B) -> BufResult<Option<(T, usize)>, B> {
+    decode_frame_with_limit(buf, DEFAULT_MAX_FRAME_LEN)
+}
+
+/// Same as `decode_frame()`, but with given maximum frame length.
+pub fn decode_frame_with_limit<T: LBSRead, B: OwnedBuf>(
+    buf: B,
+    max_len: usize,
+) -> BufResult<Option<(T, usize)>, B> {
+    let bytes = buf.as_ref();
+
+    let Some(prefix) = bytes.get(..4) else {
+        return (Ok(None), buf);
+    };
+
+    let len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+
+    if len > max_len {
+        return (Err(LBSError::FrameTooLarge { len, max_len }), buf);
+    }
+
+    let Some(mut frame) = bytes.get(4..4 + len) else {
+        return (Ok(None), buf);
+    };
+
+    let result = T::lbs_read(&mut frame).and_then(|value| match frame.len() {
+        0 => Ok(Some((value, 4 + len))),
+        n => Err(LBSError::TrailingBytes(n)),
+    });
+
+    (result, buf)
+}
@@ -0,0 +1,111 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use palette::rgb::Rgb;
+use palette::Alpha;
+use std::io::Read;
+use std::io::Write;
+
+/// Encoded as `(red, green, blue)`. RGB standard isn't encoded, as it's a part of the type.
+impl<S, T: LBSWrite> LBSWrite for Rgb<S, T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.red.lbs_write_with(w, e)?;
+        self.green.lbs_write_with(w, e)?;
+        self.blue.lbs_write_with(w, e)
+    }
+}
+
+/// Encoded as `(color, alpha)`, so `Srgba` is encoded as `(red, green, blue, alpha)`.
+impl<C: LBSWrite, T: LBSWrite> LBSWrite for Alpha<C, T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.color.lbs_write_with(w, e)?;
+        self.alpha.lbs_write_with(w, e)
+    }
+}
+
+impl<S, T: LBSRead> LBSRead for Rgb<S, T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let red = T::lbs_read_with(r, d)?;
+        let green = T::lbs_read_with(r, d)?;
+        let blue = T::lbs_read_with(r, d)?;
+        Ok(Self::new(red, green, blue))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)?;
+        T::lbs_skip(r, d)?;
+        T::lbs_skip(r, d)
+    }
+}
+
+impl<C: LBSRead, T: LBSRead> LBSRead for Alpha<C, T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let color = C::lbs_read_with(r, d)?;
+        let alpha = T::lbs_read_with(r, d)?;
+        Ok(Self { color, alpha })
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        C::lbs_skip(r, d)?;
+        T::lbs_skip(r, d)
+    }
+}
+
+impl<S, T: LBSSchema> LBSSchema for Rgb<S, T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![T::lbs_schema(), T::lbs_schema(), T::lbs_schema()])
+    }
+}
+
+/// Components of the color are flattened, like on wire.
+impl<C: LBSSchema, T: LBSSchema> LBSSchema for Alpha<C, T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        let mut components = match C::lbs_schema() {
+            Schema::Tuple(components) => components,
+            schema => vec![schema],
+        };
+        components.push(T::lbs_schema());
+        Schema::Tuple(components)
+    }
+}
+
+impl<S, T: LBSMaxSize> LBSMaxSize for Rgb<S, T> {
+    const LBS_MAX_SIZE: usize = 3 * T::LBS_MAX_SIZE;
+}
+
+impl<C: LBSMaxSize, T: LBSMaxSize> LBSMaxSize for Alpha<C, T> {
+    const LBS_MAX_SIZE: usize = C::LBS_MAX_SIZE + T::LBS_MAX_SIZE;
+}
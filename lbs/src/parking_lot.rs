@@ -0,0 +1,172 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use std::io::Read;
+use std::io::Write;
+
+/// Value is written under the lock, which is held until it's fully written.
+impl<T: LBSWrite> LBSWrite for Mutex<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.lock().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.lock().lbs_must_write()
+    }
+}
+
+/// Value is written under the read lock, so concurrent readers aren't blocked.
+impl<T: LBSWrite> LBSWrite for RwLock<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.read().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.read().lbs_must_write()
+    }
+}
+
+/// Written as `Option<T>`. Uninitialized cell isn't written as a struct field, so such
+/// fields should be marked with `#[lbs(optional)]`.
+impl<T: LBSWrite> LBSWrite for OnceCell<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.get().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.get().is_some()
+    }
+}
+
+impl<T: LBSRead> LBSRead for Mutex<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.get_mut().lbs_read_into_with(r, d)
+    }
+
+    #[inline]
+    fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.get_mut().lbs_merge_with(r, d)
+    }
+}
+
+impl<T: LBSRead> LBSRead for RwLock<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.get_mut().lbs_read_into_with(r, d)
+    }
+
+    #[inline]
+    fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.get_mut().lbs_merge_with(r, d)
+    }
+}
+
+impl<T: LBSRead> LBSRead for OnceCell<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(match Option::<T>::lbs_read_with(r, d)? {
+            Some(value) => Self::with_value(value),
+            None => Self::new(),
+        })
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Option::<T>::lbs_skip(r, d)
+    }
+}
+
+macro_rules! impl_lock_schema {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSSchema> LBSSchema for $t<T> {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    T::lbs_schema()
+                }
+            }
+
+            impl<T: LBSMaxSize> LBSMaxSize for $t<T> {
+                const LBS_MAX_SIZE: usize = T::LBS_MAX_SIZE;
+            }
+        )*
+    };
+}
+
+impl_lock_schema!(Mutex, RwLock);
+
+impl<T: LBSSchema> LBSSchema for OnceCell<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Option::<T>::lbs_schema()
+    }
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for OnceCell<T> {
+    const LBS_MAX_SIZE: usize = Option::<T>::LBS_MAX_SIZE;
+}
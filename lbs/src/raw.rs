@@ -0,0 +1,122 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Encoded value of a type, which isn't known yet, like an extension payload tagged by
+/// a separate discriminator field.
+///
+/// Wire format is u32 length followed by the encoded value, same as of `#[lbs(delimited)]`
+/// fields and `Vec<u8>`, so a producer writes the concrete type as a delimited field, and
+/// a gateway reads it as `RawValue`, passes it along verbatim and decodes it with `decode()`
+/// once the discriminator tells the type.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct RawValue(Vec<u8>);
+
+impl RawValue {
+    /// Wraps bytes of an encoded value.
+    #[inline]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Encodes a value.
+    #[inline]
+    pub fn from_value<T: LBSWrite + ?Sized>(value: &T) -> Result<Self, LBSError> {
+        Self::from_value_with(value, &mut Encoder::new())
+    }
+
+    #[inline]
+    pub fn from_value_with<T: LBSWrite + ?Sized>(
+        value: &T,
+        e: &mut Encoder,
+    ) -> Result<Self, LBSError> {
+        let mut buf = Vec::with_capacity(value.lbs_size_hint());
+        value.lbs_write_with(&mut buf, e)?;
+        Ok(Self(buf))
+    }
+
+    /// Decodes the value, which must occupy all the bytes.
+    #[inline]
+    pub fn decode<T: LBSRead>(&self) -> Result<T, LBSError> {
+        from_slice_exact(&self.0)
+    }
+
+    #[inline]
+    pub fn decode_with<T: LBSRead>(&self, d: &mut Decoder) -> Result<T, LBSError> {
+        let mut body = self.0.as_slice();
+        let value = T::lbs_read_with(&mut body, d)?;
+
+        match body.len() {
+            0 => Ok(value),
+            n => Err(LBSError::TrailingBytes(n)),
+        }
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for RawValue {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Bytes are written verbatim, ignoring encoder options.
+impl LBSWrite for RawValue {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        write_len(w, self.0.len())?;
+        Ok(w.write_all(&self.0)?)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.0.len()
+    }
+}
+
+impl LBSRead for RawValue {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self(Vec::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.0.lbs_read_into_with(r, d)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Vec::<u8>::lbs_skip(r, d)
+    }
+}
+
+impl LBSSchema for RawValue {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Vec::<u8>::lbs_schema()
+    }
+}
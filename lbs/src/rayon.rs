@@ -0,0 +1,143 @@
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::write::write_len;
+use crate::LBSWrite;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Collections shorter than this are encoded sequentially.
+pub const PARALLEL_THRESHOLD: usize = 4096;
+
+/// Collections, which can be encoded in parallel.
+///
+/// Elements are encoded into per-chunk buffers using rayon thread pool,
+/// then chunks are written one after another. Wire format is the same as for sequential encoding,
+/// so elements, which depend on encoder state of the preceding ones, like shared strings and
+/// relative timestamps, are encoded sequentially.
+pub trait LBSParWrite {
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>;
+}
+
+impl<T: LBSWrite + Sync> LBSParWrite for [T] {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_chunks(w, e, self, |element, buf, e| element.lbs_write_with(buf, e))
+    }
+}
+
+impl<T: LBSWrite + Sync> LBSParWrite for Vec<T> {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_slice().lbs_par_write(w, e)
+    }
+}
+
+impl<T: LBSWrite + Sync, S> LBSParWrite for HashSet<T, S> {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_chunks(w, e, &self.iter().collect::<Vec<_>>(), |element, buf, e| {
+            element.lbs_write_with(buf, e)
+        })
+    }
+}
+
+impl<T: LBSWrite + Sync> LBSParWrite for BTreeSet<T> {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_chunks(w, e, &self.iter().collect::<Vec<_>>(), |element, buf, e| {
+            element.lbs_write_with(buf, e)
+        })
+    }
+}
+
+impl<K: LBSWrite + Sync, V: LBSWrite + Sync, S> LBSParWrite for HashMap<K, V, S> {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_chunks(w, e, &self.iter().collect::<Vec<_>>(), |(k, v), buf, e| {
+            k.lbs_write_with(buf, e)?;
+            v.lbs_write_with(buf, e)
+        })
+    }
+}
+
+impl<K: LBSWrite + Sync, V: LBSWrite + Sync> LBSParWrite for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_par_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_chunks(w, e, &self.iter().collect::<Vec<_>>(), |(k, v), buf, e| {
+            k.lbs_write_with(buf, e)?;
+            v.lbs_write_with(buf, e)
+        })
+    }
+}
+
+/// Writes collection in parallel. Used by derive for fields with `#[lbs(parallel)]` attribute.
+#[inline]
+pub fn write<T: LBSParWrite + ?Sized, W: Write>(value: &T, w: &mut W) -> Result<(), LBSError> {
+    value.lbs_par_write(w, &mut Encoder::new())
+}
+
+/// Same as `write()`, but applies encoding options. Every chunk is encoded with a copy of `e`.
+#[inline]
+pub fn write_with<T: LBSParWrite + ?Sized, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_par_write(w, e)
+}
+
+fn write_chunks<T, W, F>(
+    w: &mut W,
+    e: &mut Encoder,
+    elements: &[T],
+    write_element: F,
+) -> Result<(), LBSError>
+where
+    T: Sync,
+    W: Write,
+    F: Fn(&T, &mut Vec<u8>, &mut Encoder) -> Result<(), LBSError> + Sync,
+{
+    if elements.len() >= PARALLEL_THRESHOLD && !e.shares_strings() {
+        let chunk_size =
+            (elements.len() / rayon::current_num_threads()).max(PARALLEL_THRESHOLD / 4);
+
+        let chunks = elements
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut buf = Vec::new();
+                let mut chunk_e = e.clone();
+                for element in chunk {
+                    write_element(element, &mut buf, &mut chunk_e)?;
+                }
+                Ok((buf, chunk_e))
+            })
+            .collect::<Result<Vec<(Vec<u8>, Encoder)>, LBSError>>()?;
+
+        let mut merged = e.clone();
+        if chunks
+            .iter()
+            .all(|(_, chunk_e)| merged.merge_chunk(chunk_e))
+        {
+            for (chunk, _) in chunks {
+                w.write_all(&chunk)?;
+            }
+            *e = merged;
+            return Ok(());
+        }
+    }
+
+    let mut buf = Vec::new();
+    for element in elements {
+        write_element(element, &mut buf, e)?;
+    }
+    Ok(w.write_all(&buf)?)
+}
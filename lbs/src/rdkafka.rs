@@ -0,0 +1,120 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::schema::LBSSchema;
+use crate::LBSRead;
+use crate::LBSWrite;
+use rdkafka::message::Message;
+use std::marker::PhantomData;
+
+/// Length of optional schema fingerprint prefix.
+const FINGERPRINT_LEN: usize = 8;
+
+/// Serializer of Kafka message keys or payloads.
+///
+/// Fingerprint of the schema may be prepended to every message, so consumers can reject messages
+/// of other types, see `LBSDeserializer::with_schema()`.
+#[derive(Debug, Clone, Copy)]
+pub struct LBSSerializer<T: ?Sized> {
+    fingerprint: Option<u64>,
+    ty: PhantomData<fn(&T)>,
+}
+
+/// Deserializer of Kafka message keys or payloads written by `LBSSerializer`.
+#[derive(Debug, Clone, Copy)]
+pub struct LBSDeserializer<T> {
+    fingerprint: Option<u64>,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T: LBSWrite + ?Sized> LBSSerializer<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            fingerprint: None,
+            ty: PhantomData,
+        }
+    }
+
+    /// Prepends schema fingerprint of `T` to every message.
+    #[inline]
+    pub fn with_schema() -> Self
+    where
+        T: LBSSchema,
+    {
+        Self {
+            fingerprint: Some(T::lbs_schema().fingerprint()),
+            ty: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn serialize(&self, value: &T) -> Result<Vec<u8>, LBSError> {
+        let mut buf = Vec::new();
+
+        if let Some(fingerprint) = self.fingerprint {
+            fingerprint.lbs_write(&mut buf)?;
+        }
+
+        value.lbs_write(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<T: LBSWrite + ?Sized> Default for LBSSerializer<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LBSRead> LBSDeserializer<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            fingerprint: None,
+            ty: PhantomData,
+        }
+    }
+
+    /// Expects schema fingerprint of `T` before every message, fails with `SchemaMismatch` otherwise.
+    #[inline]
+    pub fn with_schema() -> Self
+    where
+        T: LBSSchema,
+    {
+        Self {
+            fingerprint: Some(T::lbs_schema().fingerprint()),
+            ty: PhantomData,
+        }
+    }
+
+    pub fn deserialize(&self, bytes: &[u8]) -> Result<T, LBSError> {
+        let Some(fingerprint) = self.fingerprint else {
+            return from_slice_exact(bytes);
+        };
+
+        match bytes.split_at_checked(FINGERPRINT_LEN) {
+            Some((prefix, rest)) if prefix == fingerprint.to_le_bytes() => from_slice_exact(rest),
+            _ => Err(LBSError::SchemaMismatch),
+        }
+    }
+
+    /// Deserializes message payload. Messages without payload, like tombstones, are `None`.
+    #[inline]
+    pub fn payload<M: Message>(&self, message: &M) -> Result<Option<T>, LBSError> {
+        message.payload().map(|p| self.deserialize(p)).transpose()
+    }
+
+    /// Deserializes message key, if present.
+    #[inline]
+    pub fn key<M: Message>(&self, message: &M) -> Result<Option<T>, LBSError> {
+        message.key().map(|k| self.deserialize(k)).transpose()
+    }
+}
+
+impl<T: LBSRead> Default for LBSDeserializer<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
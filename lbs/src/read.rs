@@ -1,24 +1,247 @@
+use crate::decoder::Decoder;
 use crate::error::LBSError;
+use crate::schema::Schema;
+use crate::stats;
+use crate::write::BEFORE_EPOCH_FLAG;
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::ffi::OsString;
 use std::hash::BuildHasher;
 use std::hash::Hash;
+use std::io::BufRead;
 use std::io::Read;
 use std::mem::size_of;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::Ipv6Addr;
+use std::num::NonZeroI128;
+use std::num::NonZeroI16;
+use std::num::NonZeroI32;
+use std::num::NonZeroI64;
+use std::num::NonZeroI8;
+use std::num::NonZeroIsize;
+use std::num::NonZeroU128;
+use std::num::NonZeroU16;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
+use std::num::Saturating;
+use std::num::Wrapping;
+use std::ops::Bound;
+use std::ops::ControlFlow;
 use std::ops::Range;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
 use std::time::SystemTime;
 
 pub trait LBSRead: Sized {
     fn lbs_read<R: std::io::Read>(r: &mut R) -> Result<Self, LBSError>;
+
+    /// Same as `lbs_read()`, but reuses state of the given decoder.
+    /// Containers must propagate decoder to their elements.
+    #[inline]
+    fn lbs_read_with<R: std::io::Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+        Self::lbs_read(r)
+    }
+
+    /// Consumes encoded value without constructing it.
+    /// Default implementation reads the value and drops it.
+    #[inline]
+    fn lbs_skip<R: std::io::Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Self::lbs_read_with(r, d).map(|_| ())
+    }
+
+    /// Same as `lbs_read_with()`, but decodes only struct fields with given ids and skips others,
+    /// leaving them default. Checks of required fields are relaxed accordingly.
+    /// Types other than structs are read as a whole.
+    #[inline]
+    fn lbs_read_projected<R: std::io::Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        _fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, d)
+    }
+
+    /// Same as `lbs_read_with()`, but also returns ids of struct fields, which were present in
+    /// encoded value, to tell absent fields from ones encoded with default values.
+    /// Types other than structs report no fields.
+    #[inline]
+    fn lbs_read_with_report<R: std::io::Read>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<(Self, FieldSet), LBSError> {
+        Self::lbs_read_with(r, d).map(|v| (v, FieldSet::new()))
+    }
+
+    /// Same as `lbs_read()`, but decodes into existing value, so strings, vectors and maps
+    /// keep their capacity. On error the value is left partially decoded.
+    #[inline]
+    fn lbs_read_into<R: std::io::Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        self.lbs_read_into_with(r, &mut Decoder::new())
+    }
+
+    /// Same as `lbs_read_into()`, but reuses state of the given decoder.
+    /// Default implementation reads a new value and replaces the existing one.
+    #[inline]
+    fn lbs_read_into_with<R: std::io::Read>(
+        &mut self,
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<(), LBSError> {
+        *self = Self::lbs_read_with(r, d)?;
+        Ok(())
+    }
+
+    /// Overlays encoded value onto existing one, like protobuf merge: present struct fields are
+    /// merged recursively, absent ones are left untouched and required ones are not checked.
+    /// Collections are replaced, unless fields are marked with `#[lbs(append)]`.
+    #[inline]
+    fn lbs_merge<R: std::io::Read>(&mut self, r: &mut R) -> Result<(), LBSError> {
+        self.lbs_merge_with(r, &mut Decoder::new())
+    }
+
+    /// Same as `lbs_merge()`, but reuses state of the given decoder.
+    /// Default implementation reads a new value and replaces the existing one.
+    #[inline]
+    fn lbs_merge_with<R: std::io::Read>(
+        &mut self,
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<(), LBSError> {
+        *self = Self::lbs_read_with(r, d)?;
+        Ok(())
+    }
+
+    /// Reads `l` elements of a sequence, which length was already read.
+    /// Allows `Vec<u8>` to be read with a single `read_exact()`.
+    #[inline]
+    fn lbs_read_vec<R: std::io::Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        l: usize,
+    ) -> Result<Vec<Self>, LBSError>
+    where
+        Self: Sized,
+    {
+        let mut v = Vec::with_capacity(capacity::<Self>(l));
+
+        for _ in 0..l {
+            v.push(Self::lbs_read_with(r, d)?);
+        }
+
+        Ok(v)
+    }
+
+    /// Same as `lbs_read_vec()`, but decodes existing elements in place and appends missing ones.
+    #[inline]
+    fn lbs_read_vec_into<R: std::io::Read>(
+        v: &mut Vec<Self>,
+        r: &mut R,
+        d: &mut Decoder,
+        l: usize,
+    ) -> Result<(), LBSError>
+    where
+        Self: Sized,
+    {
+        v.truncate(l);
+        v.reserve(capacity::<Self>(l - v.len()));
+
+        for e in v.iter_mut() {
+            e.lbs_read_into_with(r, d)?;
+        }
+
+        for _ in v.len()..l {
+            v.push(Self::lbs_read_with(r, d)?);
+        }
+
+        Ok(())
+    }
+
+    /// Consumes `l` elements of a sequence, which length was already read.
+    /// Allows bytes to be skipped at once.
+    #[inline]
+    fn lbs_skip_seq<R: std::io::Read>(r: &mut R, d: &mut Decoder, l: usize) -> Result<(), LBSError>
+    where
+        Self: Sized,
+    {
+        for _ in 0..l {
+            Self::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Set of field ids, see `LBSRead::lbs_read_with_report()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSet {
+    bits: Vec<u64>,
+}
+
+impl FieldSet {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, id: u16) {
+        let (word, bit) = (id as usize / 64, id % 64);
+
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+
+        self.bits[word] |= 1 << bit;
+    }
+
+    #[inline]
+    pub fn contains(&self, id: u16) -> bool {
+        self.bits
+            .get(id as usize / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// Ids in ascending order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word, bits)| {
+            (0..64)
+                .filter(move |bit| bits & (1 << bit) != 0)
+                .map(move |bit| (word * 64 + bit) as u16)
+        })
+    }
+}
+
+/// Reads value and ids of its present fields, see `LBSRead::lbs_read_with_report()`.
+#[inline]
+pub fn read_with_report<T: LBSRead, R: Read>(r: &mut R) -> Result<(T, FieldSet), LBSError> {
+    T::lbs_read_with_report(r, &mut Decoder::new())
 }
 
 macro_rules! impl_read_primitive {
@@ -30,11 +253,50 @@ macro_rules! impl_read_primitive {
                 r.read_exact(&mut buf)?;
                 Ok(Self::from_le_bytes(buf))
             }
+
+            #[inline]
+            fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+                skip_bytes(r, $l)
+            }
         }
     };
 }
 
-impl_read_primitive!(u8, 1);
+/// Sequences of bytes are read and skipped at once.
+impl LBSRead for u8 {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let mut buf = [0; 1];
+        r.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+        skip_bytes(r, 1)
+    }
+
+    #[inline]
+    fn lbs_read_vec<R: Read>(r: &mut R, _d: &mut Decoder, l: usize) -> Result<Vec<Self>, LBSError> {
+        read_bytes(r, l)
+    }
+
+    #[inline]
+    fn lbs_read_vec_into<R: Read>(
+        v: &mut Vec<Self>,
+        r: &mut R,
+        _d: &mut Decoder,
+        l: usize,
+    ) -> Result<(), LBSError> {
+        read_bytes_into(r, v, l)
+    }
+
+    #[inline]
+    fn lbs_skip_seq<R: Read>(r: &mut R, _d: &mut Decoder, l: usize) -> Result<(), LBSError> {
+        skip_bytes(r, l)
+    }
+}
+
 impl_read_primitive!(u16, 2);
 impl_read_primitive!(u32, 4);
 impl_read_primitive!(u64, 8);
@@ -48,6 +310,30 @@ impl_read_primitive!(i64, 8);
 impl_read_primitive!(isize, 8);
 impl_read_primitive!(i128, 16);
 
+macro_rules! impl_read_non_zero {
+    ($($t:ident => $p:ty),*) => {
+        $(
+            /// Zero is rejected as out of range.
+            impl LBSRead for $t {
+                #[inline]
+                fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+                    Self::new(<$p>::lbs_read(r)?).ok_or(LBSError::OutOfRange(stringify!($t)))
+                }
+
+                #[inline]
+                fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    <$p>::lbs_skip(r, d)
+                }
+            }
+        )*
+    };
+}
+
+impl_read_non_zero!(NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64);
+impl_read_non_zero!(NonZeroUsize => usize, NonZeroU128 => u128);
+impl_read_non_zero!(NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64);
+impl_read_non_zero!(NonZeroIsize => isize, NonZeroI128 => i128);
+
 impl_read_primitive!(f32, 4);
 impl_read_primitive!(f64, 8);
 
@@ -61,23 +347,45 @@ impl LBSRead for () {
 impl<T1: LBSRead, T2: LBSRead> LBSRead for (T1, T2) {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        Ok((T1::lbs_read(r)?, T2::lbs_read(r)?))
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok((T1::lbs_read_with(r, d)?, T2::lbs_read_with(r, d)?))
     }
 }
 
 impl<T1: LBSRead, T2: LBSRead, T3: LBSRead> LBSRead for (T1, T2, T3) {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        Ok((T1::lbs_read(r)?, T2::lbs_read(r)?, T3::lbs_read(r)?))
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok((
+            T1::lbs_read_with(r, d)?,
+            T2::lbs_read_with(r, d)?,
+            T3::lbs_read_with(r, d)?,
+        ))
     }
 }
 
 impl LBSRead for bool {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let mut buf = [0; 1];
-        r.read_exact(&mut buf)?;
-        Ok(u8::from_le_bytes(buf) == 1)
+        bool_from_byte(u8::lbs_read(r)?, false)
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        bool_from_byte(u8::lbs_read(r)?, d.lenient_bools_enabled())
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+        skip_bytes(r, 1)
     }
 }
 
@@ -86,7 +394,12 @@ impl LBSRead for char {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         let mut buf = [0; 4];
         r.read_exact(&mut buf)?;
-        Self::from_u32(u32::from_le_bytes(buf)).ok_or_else(|| LBSError::InvalidChar)
+        Self::from_u32(u32::from_le_bytes(buf)).ok_or(LBSError::InvalidChar)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+        skip_bytes(r, 4)
     }
 }
 
@@ -99,10 +412,91 @@ impl LBSRead for String {
             return Ok(Self::new());
         }
 
-        let mut buf = vec![0; l];
-        r.read_exact(&mut buf)?;
+        let buf = read_bytes(r, l)?;
         Self::from_utf8(buf).map_err(|e| LBSError::Parsing(e.to_string()))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if d.shares_strings() {
+            return Ok(Self::from(&*d.read_shared_str(r)?));
+        }
+
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        let buf = read_bytes(r, l)?;
+        d.string_from_utf8(buf)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        // Skipped strings still have to be added to the shared table
+        if d.shares_strings() {
+            return d.read_shared_str(r).map(|_| ());
+        }
+
+        let l = read_len_with(r, d)?;
+        skip_bytes(r, l)
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        if d.shares_strings() {
+            self.clear();
+            self.push_str(&d.read_shared_str(r)?);
+            return Ok(());
+        }
+
+        let l = read_len_with(r, d)?;
+        let mut buf = std::mem::take(self).into_bytes();
+        read_bytes_into(r, &mut buf, l)?;
+        *self = d.string_from_utf8(buf)?;
+        Ok(())
+    }
+}
+
+macro_rules! impl_read_os_string {
+    ($($t:ty),*) => {
+        $(
+            /// Read as a string.
+            impl LBSRead for $t {
+                #[inline]
+                fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+                    Ok(Self::from(String::lbs_read(r)?))
+                }
+
+                #[inline]
+                fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+                    Ok(Self::from(String::lbs_read_with(r, d)?))
+                }
+
+                #[inline]
+                fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    String::lbs_skip(r, d)
+                }
+            }
+        )*
+    };
+}
+
+impl_read_os_string!(OsString, PathBuf);
+
+/// Makes duration of encoded parts, rejecting nanoseconds of a second or more, which could
+/// overflow seconds.
+#[inline]
+pub(crate) fn duration(secs: u64, nanos: u32) -> Result<Duration, LBSError> {
+    match nanos < 1_000_000_000 {
+        true => Ok(Duration::new(secs, nanos)),
+        false => Err(LBSError::Parsing(format!(
+            "{} nanoseconds exceed a second",
+            nanos
+        ))),
+    }
 }
 
 impl LBSRead for Duration {
@@ -110,16 +504,36 @@ impl LBSRead for Duration {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         let secs = u64::lbs_read(r)?;
         let nanos = u32::lbs_read(r)?;
-        Ok(Self::new(secs, nanos))
+        duration(secs, nanos)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+        skip_bytes(r, 12)
     }
 }
 
 impl LBSRead for SystemTime {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let secs = u64::lbs_read(r)?;
+        let nanos = u32::lbs_read(r)?;
+
+        if nanos & BEFORE_EPOCH_FLAG != 0 {
+            let dur = duration(secs, nanos & !BEFORE_EPOCH_FLAG)?;
+            return Self::UNIX_EPOCH
+                .checked_sub(dur)
+                .ok_or(LBSError::InvalidTimestamp);
+        }
+
         Self::UNIX_EPOCH
-            .checked_add(Duration::lbs_read(r)?)
-            .ok_or_else(|| LBSError::InvalidTimestamp)
+            .checked_add(duration(secs, nanos)?)
+            .ok_or(LBSError::InvalidTimestamp)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<(), LBSError> {
+        skip_bytes(r, 12)
     }
 }
 
@@ -150,17 +564,92 @@ impl LBSRead for IpAddr {
 impl<T: LBSRead + PartialOrd> LBSRead for Range<T> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let start = T::lbs_read(r)?;
-        let end = T::lbs_read(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let start = T::lbs_read_with(r, d)?;
+        let end = T::lbs_read_with(r, d)?;
         Ok(Range { start, end })
     }
 }
 
+impl<T: LBSRead> LBSRead for RangeInclusive<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let start = T::lbs_read_with(r, d)?;
+        let end = T::lbs_read_with(r, d)?;
+        Ok(RangeInclusive::new(start, end))
+    }
+}
+
+impl<T: LBSRead> LBSRead for Bound<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        match read_field_id(r)? {
+            0 => Ok(Self::Included(T::lbs_read_with(r, d)?)),
+            1 => Ok(Self::Excluded(T::lbs_read_with(r, d)?)),
+            2 => Ok(Self::Unbounded),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        match read_field_id(r)? {
+            0 | 1 => T::lbs_skip(r, d),
+            2 => Ok(()),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
 impl<T: LBSRead> LBSRead for Box<T> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::new(T::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_projected(r, d, fields)?))
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        (**self).lbs_read_into_with(r, d)
+    }
+
+    #[inline]
+    fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        (**self).lbs_merge_with(r, d)
+    }
 }
 
 impl<T: LBSRead> LBSRead for Rc<T> {
@@ -168,6 +657,26 @@ impl<T: LBSRead> LBSRead for Rc<T> {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::new(T::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_projected(r, d, fields)?))
+    }
 }
 
 impl<T: LBSRead> LBSRead for Arc<T> {
@@ -175,13 +684,88 @@ impl<T: LBSRead> LBSRead for Arc<T> {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::new(T::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_projected(r, d, fields)?))
+    }
+}
+
+/// Ordering and arithmetic adapters are read as their values.
+macro_rules! impl_read_newtype {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSRead> LBSRead for $t<T> {
+                #[inline]
+                fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+                    Ok(Self(T::lbs_read(r)?))
+                }
+
+                #[inline]
+                fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+                    Ok(Self(T::lbs_read_with(r, d)?))
+                }
+
+                #[inline]
+                fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    T::lbs_skip(r, d)
+                }
+
+                #[inline]
+                fn lbs_read_projected<R: Read>(
+                    r: &mut R,
+                    d: &mut Decoder,
+                    fields: &[u16],
+                ) -> Result<Self, LBSError> {
+                    Ok(Self(T::lbs_read_projected(r, d, fields)?))
+                }
+
+                #[inline]
+                fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    self.0.lbs_read_into_with(r, d)
+                }
+
+                #[inline]
+                fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    self.0.lbs_merge_with(r, d)
+                }
+            }
+        )*
+    };
 }
 
+impl_read_newtype!(Reverse, Wrapping, Saturating);
+
 impl LBSRead for Arc<str> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::from(String::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        d.read_arc_str(r)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        String::lbs_skip(r, d)
+    }
 }
 
 impl<'a, T: LBSRead + ToOwned> LBSRead for Cow<'a, T> {
@@ -189,6 +773,11 @@ impl<'a, T: LBSRead + ToOwned> LBSRead for Cow<'a, T> {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::Owned(T::lbs_read(r)?.to_owned()))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::Owned(T::lbs_read_with(r, d)?.to_owned()))
+    }
 }
 
 impl<'a, T> LBSRead for Cow<'a, [T]>
@@ -200,6 +789,15 @@ where
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::Owned(<Vec<T> as LBSRead>::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::Owned(<Vec<T> as LBSRead>::lbs_read_with(r, d)?))
+    }
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        <Vec<T> as LBSRead>::lbs_skip(r, d)
+    }
 }
 
 impl<'a> LBSRead for Cow<'a, str> {
@@ -207,38 +805,329 @@ impl<'a> LBSRead for Cow<'a, str> {
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
         Ok(Self::Owned(String::lbs_read(r)?))
     }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::Owned(String::lbs_read_with(r, d)?))
+    }
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        String::lbs_skip(r, d)
+    }
 }
 
 impl<T: LBSRead> LBSRead for Option<T> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let mut buf = [0; 1];
-        r.read_exact(&mut buf)?;
-        if u8::from_le_bytes(buf) == 1 {
-            Ok(Some(T::lbs_read(r)?))
-        } else {
-            Ok(None)
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_read_with(r, d)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        match read_option_tag(r)? {
+            true => T::lbs_skip(r, d),
+            false => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        match read_option_tag(r)? {
+            true => Ok(Some(T::lbs_read_projected(r, d, fields)?)),
+            false => Ok(None),
+        }
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        if !read_option_tag(r)? {
+            *self = None;
+            return Ok(());
+        }
+
+        match self {
+            Some(value) => value.lbs_read_into_with(r, d),
+            None => {
+                *self = Some(T::lbs_read_with(r, d)?);
+                Ok(())
+            }
+        }
+    }
+
+    #[inline]
+    fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        if !read_option_tag(r)? {
+            *self = None;
+            return Ok(());
+        }
+
+        match self {
+            Some(value) => value.lbs_merge_with(r, d),
+            None => {
+                *self = Some(T::lbs_read_with(r, d)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<B: LBSRead, C: LBSRead> LBSRead for ControlFlow<B, C> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        match read_field_id(r)? {
+            0 => Ok(Self::Continue(C::lbs_read_with(r, d)?)),
+            1 => Ok(Self::Break(B::lbs_read_with(r, d)?)),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        match read_field_id(r)? {
+            0 => C::lbs_skip(r, d),
+            1 => B::lbs_skip(r, d),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
+impl<T: LBSRead, E: LBSRead> LBSRead for Result<T, E> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        match read_field_id(r)? {
+            0 => Ok(Ok(T::lbs_read_with(r, d)?)),
+            1 => Ok(Err(E::lbs_read_with(r, d)?)),
+            _ => Err(LBSError::UnexpectedVariant),
         }
     }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        match read_field_id(r)? {
+            0 => T::lbs_skip(r, d),
+            1 => E::lbs_skip(r, d),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
+/// Never read successfully, as there is no value to construct. Input claiming to contain
+/// one is rejected as an unexpected variant.
+impl LBSRead for Infallible {
+    #[inline]
+    fn lbs_read<R: Read>(_: &mut R) -> Result<Self, LBSError> {
+        Err(LBSError::UnexpectedVariant)
+    }
+}
+
+impl<T: LBSRead> LBSRead for OnceLock<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(match Option::<T>::lbs_read_with(r, d)? {
+            Some(value) => Self::from(value),
+            None => Self::new(),
+        })
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Option::<T>::lbs_skip(r, d)
+    }
 }
 
 impl<T: LBSRead> LBSRead for Vec<T> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
 
         if l == 0 {
             return Ok(Self::new());
         }
 
-        let mut v = Self::with_capacity(l);
+        stats::allocation(d);
+        T::lbs_read_vec(r, d, l)
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        T::lbs_skip_seq(r, d, l)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
 
         for _ in 0..l {
-            v.push(T::lbs_read(r)?);
+            v.push(T::lbs_read_projected(r, d, fields)?);
         }
 
         Ok(v)
     }
+
+    /// Existing elements are decoded in place, missing ones are appended.
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        T::lbs_read_vec_into(self, r, d, l)
+    }
+}
+
+/// Length other than `N` is rejected.
+impl<T: LBSRead, const N: usize> LBSRead for [T; N] {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        check_array_len::<N>(read_len_with(r, d)?)?;
+        Ok(array_from_vec(T::lbs_read_vec(r, d, N)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Vec::<T>::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        check_array_len::<N>(read_len_with(r, d)?)?;
+        let mut v = Vec::with_capacity(N);
+
+        for _ in 0..N {
+            v.push(T::lbs_read_projected(r, d, fields)?);
+        }
+
+        Ok(array_from_vec(v))
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        check_array_len::<N>(read_len_with(r, d)?)?;
+
+        for v in self.iter_mut() {
+            v.lbs_read_into_with(r, d)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that length of an encoded array is `N`.
+#[inline]
+pub(crate) fn check_array_len<const N: usize>(l: usize) -> Result<(), LBSError> {
+    if l != N {
+        return Err(LBSError::Parsing(format!(
+            "expected array of {} elements, got {}",
+            N, l
+        )));
+    }
+
+    Ok(())
+}
+
+/// Converts vector of exactly `N` elements into an array.
+#[inline]
+fn array_from_vec<T, const N: usize>(v: Vec<T>) -> [T; N] {
+    match v.try_into() {
+        Ok(array) => array,
+        Err(_) => unreachable!("vector has exactly N elements"),
+    }
+}
+
+impl<T: LBSRead> LBSRead for VecDeque<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        Ok(T::lbs_read_vec(r, d, l)?.into())
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Vec::<T>::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Ok(Vec::<T>::lbs_read_projected(r, d, fields)?.into())
+    }
+
+    /// Existing elements are decoded in place, missing ones are appended.
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        self.truncate(l);
+        self.reserve(capacity::<T>(l - self.len()));
+
+        for v in self.iter_mut() {
+            v.lbs_read_into_with(r, d)?;
+        }
+
+        for _ in self.len()..l {
+            self.push_back(T::lbs_read_with(r, d)?);
+        }
+
+        Ok(())
+    }
 }
 
 impl<K, V, S> LBSRead for HashMap<K, V, S>
@@ -249,22 +1138,52 @@ where
 {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
 
         if l == 0 {
             return Ok(Self::default());
         }
 
-        let mut hm = Self::with_capacity_and_hasher(l, S::default());
+        stats::allocation(d);
+        let mut hm = Self::with_capacity_and_hasher(capacity::<(K, V)>(l), S::default());
 
         for _ in 0..l {
-            let k = K::lbs_read(r)?;
-            let v = V::lbs_read(r)?;
+            let k = K::lbs_read_with(r, d)?;
+            let v = V::lbs_read_with(r, d)?;
             hm.insert(k, v);
         }
 
         Ok(hm)
     }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            K::lbs_skip(r, d)?;
+            V::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        self.clear();
+        self.reserve(capacity::<(K, V)>(l));
+
+        for _ in 0..l {
+            let k = K::lbs_read_with(r, d)?;
+            let v = V::lbs_read_with(r, d)?;
+            self.insert(k, v);
+        }
+
+        Ok(())
+    }
 }
 
 impl<K, S> LBSRead for HashSet<K, S>
@@ -274,58 +1193,184 @@ where
 {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
 
         if l == 0 {
             return Ok(Self::default());
         }
 
-        let mut hs = Self::with_capacity_and_hasher(l, S::default());
+        stats::allocation(d);
+        let mut hs = Self::with_capacity_and_hasher(capacity::<K>(l), S::default());
 
         for _ in 0..l {
-            hs.insert(K::lbs_read(r)?);
+            hs.insert(K::lbs_read_with(r, d)?);
         }
 
         Ok(hs)
     }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            K::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_read_into_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let l = read_len_with(r, d)?;
+        self.clear();
+        self.reserve(capacity::<K>(l));
+
+        for _ in 0..l {
+            self.insert(K::lbs_read_with(r, d)?);
+        }
+
+        Ok(())
+    }
 }
 
 impl<K: LBSRead + Ord, V: LBSRead> LBSRead for BTreeMap<K, V> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
         let mut bm = Self::new();
 
         if l == 0 {
             return Ok(bm);
         }
 
+        stats::allocation(d);
+
         for _ in 0..l {
-            let k = K::lbs_read(r)?;
-            let v = V::lbs_read(r)?;
+            let k = K::lbs_read_with(r, d)?;
+            let v = V::lbs_read_with(r, d)?;
             bm.insert(k, v);
         }
 
         Ok(bm)
     }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            K::lbs_skip(r, d)?;
+            V::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSRead + Ord> LBSRead for BinaryHeap<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Vec::lbs_read_with(r, d)?.into())
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Vec::<T>::lbs_skip(r, d)
+    }
 }
 
 impl<K: LBSRead + Ord> LBSRead for BTreeSet<K> {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
         let mut bm = Self::new();
 
         if l == 0 {
             return Ok(bm);
         }
 
+        stats::allocation(d);
+
         for _ in 0..l {
-            bm.insert(K::lbs_read(r)?);
+            bm.insert(K::lbs_read_with(r, d)?);
         }
 
         Ok(bm)
     }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        for _ in 0..read_len_with(r, d)? {
+            K::lbs_skip(r, d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads value decoding only struct fields with given ids, see `LBSRead::lbs_read_projected()`.
+#[inline]
+pub fn project<T: LBSRead, R: Read>(r: &mut R, fields: &[u16]) -> Result<T, LBSError> {
+    T::lbs_read_projected(r, &mut Decoder::new(), fields)
+}
+
+/// Consumes exactly `n` bytes.
+#[inline]
+pub fn skip_bytes<R: Read>(r: &mut R, n: usize) -> Result<(), LBSError> {
+    let skipped = std::io::copy(&mut r.take(n as u64), &mut std::io::sink())?;
+
+    match skipped == n as u64 {
+        true => Ok(()),
+        false => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+    }
+}
+
+/// Description of an encoded value, which is enough to skip it, see `skip_value()`.
+#[derive(Debug, Clone, Copy)]
+pub enum ValueInfo<'a> {
+    /// Value of known byte length, like a field of an indexed struct.
+    Len(usize),
+    /// Value of known schema, like a field of a newer version of a type.
+    Schema(&'a Schema),
+}
+
+impl From<usize> for ValueInfo<'_> {
+    #[inline]
+    fn from(len: usize) -> Self {
+        Self::Len(len)
+    }
+}
+
+impl<'a> From<&'a Schema> for ValueInfo<'a> {
+    #[inline]
+    fn from(schema: &'a Schema) -> Self {
+        Self::Schema(schema)
+    }
+}
+
+/// Consumes a single encoded value without decoding it, so hand-written readers may hop over
+/// fields they don't understand. Values with custom codecs and opaque types can't be skipped by
+/// schema, as their length is unknown.
+#[inline]
+pub fn skip_value<'a, R: Read>(r: &mut R, info: impl Into<ValueInfo<'a>>) -> Result<(), LBSError> {
+    match info.into() {
+        ValueInfo::Len(len) => skip_bytes(r, len),
+        ValueInfo::Schema(schema) => crate::visit::visit(r, schema, &mut crate::visit::Skip),
+    }
 }
 
 #[inline]
@@ -338,14 +1383,287 @@ pub fn read_field_id<R: Read>(r: &mut R) -> Result<u16, LBSError> {
     u16::lbs_read(r)
 }
 
+/// Reads field count of a struct with compact header.
+#[inline]
+pub fn read_compact_field_count<R: Read>(r: &mut R) -> Result<u16, LBSError> {
+    crate::varint::read_u16(r)
+}
+
+/// Reads field id of a struct with compact header, encoded as a delta from the previous one.
+/// `prev` is None before the first field, which is a delta from zero.
+#[inline]
+pub fn read_compact_field_id<R: Read>(r: &mut R, prev: &mut Option<u16>) -> Result<u16, LBSError> {
+    let id = compact_field_id(*prev, crate::varint::read_u16(r)?)?;
+    *prev = Some(id);
+    Ok(id)
+}
+
+/// Adds delta to the previous field id, rejecting zero one after the first field,
+/// which would repeat its id.
+#[inline]
+pub(crate) fn compact_field_id(prev: Option<u16>, delta: u16) -> Result<u16, LBSError> {
+    match prev {
+        None => Ok(delta),
+        Some(_) if delta == 0 => Err(LBSError::Parsing("zero field id delta".to_string())),
+        Some(prev) => prev.checked_add(delta).ok_or(LBSError::InvalidVarint),
+    }
+}
+
+/// Reads bools of a struct with packed bools, calling `f` with id and value of every bool.
+#[inline]
+pub fn read_packed_bools<R: Read, F: FnMut(u16, bool)>(
+    r: &mut R,
+    mut f: F,
+) -> Result<(), LBSError> {
+    let base = u16::lbs_read(r)?;
+    let count = u16::lbs_read(r)?;
+    let mut byte = 0u8;
+
+    for i in 0..count {
+        if i % 8 == 0 {
+            byte = u8::lbs_read(r)?;
+        }
+
+        let id = base
+            .checked_add(i)
+            .ok_or_else(|| LBSError::Parsing("packed bool id overflows u16".to_string()))?;
+        f(id, byte & (1 << (i % 8)) != 0);
+    }
+
+    Ok(())
+}
+
 #[inline]
 pub fn read<T: LBSRead, R: Read>(r: &mut R) -> Result<T, LBSError> {
     T::lbs_read(r)
 }
 
+#[inline]
+pub fn read_with<T: LBSRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    d.enter()?;
+    let result = T::lbs_read_with(r, d);
+    d.leave();
+    result
+}
+
+/// Decodes into existing value, see `LBSRead::lbs_read_into()`.
+#[inline]
+pub fn read_into<T: LBSRead, R: Read>(value: &mut T, r: &mut R) -> Result<(), LBSError> {
+    value.lbs_read_into(r)
+}
+
+#[inline]
+pub fn read_into_with<T: LBSRead, R: Read>(
+    value: &mut T,
+    r: &mut R,
+    d: &mut Decoder,
+) -> Result<(), LBSError> {
+    d.enter()?;
+    let result = value.lbs_read_into_with(r, d);
+    d.leave();
+    result
+}
+
+/// Overlays encoded value onto existing one, see `LBSRead::lbs_merge()`.
+#[inline]
+pub fn merge<T: LBSRead, R: Read>(value: &mut T, r: &mut R) -> Result<(), LBSError> {
+    value.lbs_merge(r)
+}
+
+#[inline]
+pub fn merge_with<T: LBSRead, R: Read>(
+    value: &mut T,
+    r: &mut R,
+    d: &mut Decoder,
+) -> Result<(), LBSError> {
+    d.enter()?;
+    let result = value.lbs_merge_with(r, d);
+    d.leave();
+    result
+}
+
+/// Decodes a value from buffered reader, see `BufReadAdapter`.
+#[inline]
+pub fn read_buf<T: LBSRead, R: BufRead>(r: &mut R) -> Result<T, LBSError> {
+    T::lbs_read(&mut BufReadAdapter::new(r))
+}
+
+/// Same as `read_buf()`, but reuses state of the given decoder.
+#[inline]
+pub fn read_buf_with<T: LBSRead, R: BufRead>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_read_with(&mut BufReadAdapter::new(r), d)
+}
+
+/// Reader, which copies small reads of primitives and strings directly from the internal buffer of
+/// `BufRead`, using `fill_buf()` and `consume()`.
+///
+/// Values are decoded with many reads of 1 to 16 bytes, and every one of them is a short copy then,
+/// unless it crosses the end of the buffer.
+#[derive(Debug)]
+pub struct BufReadAdapter<R> {
+    inner: R,
+}
+
+impl<R: BufRead> BufReadAdapter<R> {
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    #[cold]
+    fn read_exact_slow(&mut self, mut buf: &mut [u8]) -> std::io::Result<()> {
+        while !buf.is_empty() {
+            let available = self.inner.fill_buf()?;
+
+            if available.is_empty() {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            self.inner.consume(n);
+            buf = &mut buf[n..];
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for BufReadAdapter<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.inner.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.inner.consume(n);
+        Ok(n)
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        let available = self.inner.fill_buf()?;
+
+        match available.get(..buf.len()) {
+            Some(available) => {
+                buf.copy_from_slice(available);
+                self.inner.consume(buf.len());
+                Ok(())
+            }
+            None => self.read_exact_slow(buf),
+        }
+    }
+}
+
+impl<R: BufRead> BufRead for BufReadAdapter<R> {
+    #[inline]
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+/// Decodes a value, which must occupy the whole slice.
+/// Fails with `LBSError::TrailingBytes` if any bytes remain after the value.
+#[inline]
+pub fn from_slice_exact<T: LBSRead>(mut bytes: &[u8]) -> Result<T, LBSError> {
+    let value = T::lbs_read(&mut bytes)?;
+
+    match bytes.len() {
+        0 => Ok(value),
+        n => Err(LBSError::TrailingBytes(n)),
+    }
+}
+
+/// Reads the rest of input, like payload of an unknown enum variant.
+#[inline]
+pub fn read_to_end<R: Read>(r: &mut R) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Bool encoded as given byte, which must be 0 or 1 unless bools are lenient.
+#[inline]
+pub(crate) fn bool_from_byte(byte: u8, lenient: bool) -> Result<bool, LBSError> {
+    match (byte, lenient) {
+        (0, _) => Ok(false),
+        (1, _) => Ok(true),
+        (_, true) => Ok(false),
+        (byte, false) => Err(LBSError::InvalidBool(byte)),
+    }
+}
+
+/// Reads tag of an optional value, which is 1 if value follows. Tags other than 0 and 1 fail
+/// with `LBSError::Parsing`, so every value has a single encoding.
+#[inline]
+pub fn read_option_tag<R: Read>(r: &mut R) -> Result<bool, LBSError> {
+    option_tag(u8::lbs_read(r)?)
+}
+
+#[inline]
+pub(crate) fn option_tag(tag: u8) -> Result<bool, LBSError> {
+    match tag {
+        0 => Ok(false),
+        1 => Ok(true),
+        tag => Err(LBSError::Parsing(format!("invalid option tag {}", tag))),
+    }
+}
+
 #[inline]
 pub fn read_len<R: Read>(r: &mut R) -> Result<usize, LBSError> {
     let mut buf = [0; size_of::<u32>()];
     r.read_exact(&mut buf)?;
     Ok(u32::from_le_bytes(buf) as usize)
 }
+
+/// Reads length, failing if it exceeds `Limits::max_len` of the decoder.
+#[inline]
+pub fn read_len_with<R: Read>(r: &mut R, d: &Decoder) -> Result<usize, LBSError> {
+    d.check_len(read_len(r)?)
+}
+
+/// Largest number of bytes, which are preallocated for a collection or a string before its
+/// contents arrive. Longer ones grow as they are read, so untrusted lengths don't make decoder
+/// allocate more than input has.
+pub const MAX_PREALLOCATION: usize = 64 * 1024;
+
+/// Capacity to preallocate for `l` elements of `T`. Used by implementations of collections.
+#[inline]
+pub fn capacity<T>(l: usize) -> usize {
+    l.min(MAX_PREALLOCATION / size_of::<T>().max(1))
+}
+
+/// Replaces contents of a buffer with exactly `l` bytes. Buffer grows as bytes arrive,
+/// see `MAX_PREALLOCATION`.
+#[inline]
+pub fn read_bytes_into<R: Read>(r: &mut R, buf: &mut Vec<u8>, l: usize) -> Result<(), LBSError> {
+    buf.clear();
+
+    if l <= MAX_PREALLOCATION {
+        buf.resize(l, 0);
+        return Ok(r.read_exact(buf)?);
+    }
+
+    if r.take(l as u64).read_to_end(buf)? < l {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+/// Reads exactly `l` bytes into a new vector, see `read_bytes_into()`.
+#[inline]
+pub fn read_bytes<R: Read>(r: &mut R, l: usize) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    read_bytes_into(r, &mut buf, l)?;
+    Ok(buf)
+}
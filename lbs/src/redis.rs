@@ -0,0 +1,36 @@
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use redis::FromRedisValue;
+use redis::ParsingError;
+use redis::RedisWrite;
+use redis::ToRedisArgs;
+use redis::ToSingleRedisArg;
+use redis::Value;
+
+pub use crate::blob::LBSBlob;
+
+/// Written as a single binary argument.
+///
+/// Redis arguments can't fail, so this panics if value can't be encoded, like a string longer than 4 GiB.
+impl<T: LBSWrite> ToRedisArgs for LBSBlob<T> {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        self.0
+            .lbs_write(&mut out.writer_for_next_arg())
+            .expect("value must be encodable");
+    }
+}
+
+impl<T: LBSWrite> ToSingleRedisArg for LBSBlob<T> {}
+
+/// Read from a bulk string. Missing keys should be read as `Option<LBSBlob<T>>`.
+impl<T: LBSRead> FromRedisValue for LBSBlob<T> {
+    fn from_redis_value(v: Value) -> Result<Self, ParsingError> {
+        match v {
+            Value::BulkString(bytes) => Ok(Self(
+                from_slice_exact(&bytes).map_err(|e| ParsingError::from(e.to_string()))?,
+            )),
+            other => Err(format!("{:?} is not a bulk string", other).into()),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::LBSError;
+use crate::rpc::serve;
+use crate::rpc::Client;
+use crate::rpc::RpcError;
+use crate::rpc::Service;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// Schema registry protocol over `lbs::rpc`: schemas are stored and fetched by their fingerprints.
+pub struct RegistryService;
+
+impl Service for RegistryService {
+    type Request = Request;
+    type Response = Response;
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Stores schema, replies with `Response::Registered`.
+    #[lbs(id(0))]
+    Register(Schema),
+    /// Replies with `Response::Fetched`.
+    #[lbs(id(1))]
+    Fetch(u64),
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+pub enum Response {
+    /// Fingerprint of the registered schema.
+    #[lbs(id(0))]
+    Registered(u64),
+    #[lbs(id(1))]
+    Fetched(Option<Schema>),
+}
+
+/// Registry server state.
+#[derive(Debug, Default)]
+pub struct Registry {
+    schemas: RwLock<HashMap<u64, Schema>>,
+}
+
+impl Registry {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores schema and returns its fingerprint.
+    /// Fails if another schema with the same fingerprint is stored already.
+    pub fn insert(&self, schema: Schema) -> Result<u64, String> {
+        let fingerprint = schema.fingerprint();
+        let mut schemas = self.schemas.write().unwrap();
+
+        match schemas.get(&fingerprint) {
+            Some(existing) if *existing != schema => {
+                Err(format!("fingerprint {:016x} collision", fingerprint))
+            }
+            Some(_) => Ok(fingerprint),
+            None => {
+                schemas.insert(fingerprint, schema);
+                Ok(fingerprint)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, fingerprint: u64) -> Option<Schema> {
+        self.schemas.read().unwrap().get(&fingerprint).cloned()
+    }
+
+    /// Handles a single request.
+    pub fn handle(&self, request: Request) -> Result<Response, String> {
+        match request {
+            Request::Register(schema) => Ok(Response::Registered(self.insert(schema)?)),
+            Request::Fetch(fingerprint) => Ok(Response::Fetched(self.get(fingerprint))),
+        }
+    }
+
+    /// Accepts connections and serves each one on its own thread.
+    pub fn serve(self: Arc<Self>, listener: &TcpListener) -> Result<(), RpcError> {
+        serve::<RegistryService, _>(listener, move |request| self.handle(request))
+    }
+}
+
+/// Registry client, which caches fetched and registered schemas.
+pub struct RegistryClient {
+    client: Client<RegistryService>,
+    cache: HashMap<u64, Schema>,
+}
+
+impl RegistryClient {
+    #[inline]
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, RpcError> {
+        Ok(Self::new(Client::connect(addr)?))
+    }
+
+    #[inline]
+    pub fn new(client: Client<RegistryService>) -> Self {
+        Self {
+            client,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Registers schema of `T` and returns its fingerprint. Usually called at startup for local types.
+    #[inline]
+    pub fn register<T: LBSSchema + ?Sized>(&mut self) -> Result<u64, RpcError> {
+        self.register_schema(T::lbs_schema())
+    }
+
+    /// Registers schema and returns its fingerprint.
+    pub fn register_schema(&mut self, schema: Schema) -> Result<u64, RpcError> {
+        let fingerprint = schema.fingerprint();
+
+        if self.cache.contains_key(&fingerprint) {
+            return Ok(fingerprint);
+        }
+
+        match self.client.call(&Request::Register(schema.clone()))? {
+            Response::Registered(registered) if registered == fingerprint => {
+                self.cache.insert(fingerprint, schema);
+                Ok(fingerprint)
+            }
+            _ => Err(LBSError::UnexpectedVariant.into()),
+        }
+    }
+
+    /// Returns schema with given fingerprint, fetching it from the registry if it isn't cached.
+    pub fn fetch(&mut self, fingerprint: u64) -> Result<Option<&Schema>, RpcError> {
+        if !self.cache.contains_key(&fingerprint) {
+            match self.client.call(&Request::Fetch(fingerprint))? {
+                Response::Fetched(Some(schema)) if schema.fingerprint() == fingerprint => {
+                    self.cache.insert(fingerprint, schema);
+                }
+                Response::Fetched(Some(_)) => return Err(LBSError::SchemaMismatch.into()),
+                Response::Fetched(None) => return Ok(None),
+                Response::Registered(_) => return Err(LBSError::UnexpectedVariant.into()),
+            }
+        }
+
+        Ok(self.cache.get(&fingerprint))
+    }
+
+    /// Returns cached schema without contacting the registry.
+    #[inline]
+    pub fn cached(&self, fingerprint: u64) -> Option<&Schema> {
+        self.cache.get(&fingerprint)
+    }
+}
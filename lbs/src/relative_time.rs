@@ -0,0 +1,163 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::varint;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Tag in the low bit of the leading varint.
+const TAG_DELTA: u64 = 0;
+const TAG_FULL: u64 = 1;
+
+/// Bounds of deltas, which fit into the leading varint as zigzag next to the tag.
+const MIN_DELTA: i64 = -(1 << 62);
+const MAX_DELTA: i64 = (1 << 62) - 1;
+
+/// Timestamps, which can be encoded as deltas from the previous timestamp of the same message.
+///
+/// Every timestamp is a tagged zigzag varint of nanoseconds since the previous one written with
+/// `#[lbs(relative_time)]` anywhere in the message, or since the Unix epoch for the first one,
+/// so timestamps of a batch of events take a few bytes each. Deltas over 146 years are written
+/// as the tag followed by the regular 12 bytes. Base is kept by `Encoder` and `Decoder` and is
+/// reset by `encode()` and `decode()`, so both sides must read and write the same fields in
+/// the same order: selective reads of indexed structs and fields unknown to a reader break it.
+pub trait LBSRelativeTimeWrite {
+    fn lbs_relative_time_write<W: Write>(&self, w: &mut W, e: &mut Encoder)
+        -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSRelativeTimeWrite`.
+pub trait LBSRelativeTimeRead: Sized {
+    fn lbs_relative_time_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+impl LBSRelativeTimeWrite for SystemTime {
+    #[inline]
+    fn lbs_relative_time_write<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        let base = e.replace_time_base(*self).unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let nanos = match self.duration_since(base) {
+            Ok(dur) => i64::try_from(dur.as_nanos()).ok(),
+            Err(err) => i64::try_from(err.duration().as_nanos()).ok().map(|n| -n),
+        };
+
+        match nanos {
+            Some(delta) if (MIN_DELTA..=MAX_DELTA).contains(&delta) => {
+                let zigzag = ((delta << 1) ^ (delta >> 63)) as u64;
+                varint::write_u64(w, zigzag << 1 | TAG_DELTA)
+            }
+            _ => {
+                varint::write_u64(w, TAG_FULL)?;
+                self.lbs_write(w)
+            }
+        }
+    }
+}
+
+impl LBSRelativeTimeRead for SystemTime {
+    #[inline]
+    fn lbs_relative_time_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let v = varint::read_u64(r)?;
+
+        let time = match (v & 1, v >> 1) {
+            (TAG_DELTA, zigzag) => {
+                let base = d.time_base().unwrap_or(SystemTime::UNIX_EPOCH);
+                let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                let dur = Duration::from_nanos(delta.unsigned_abs());
+
+                match delta < 0 {
+                    true => base.checked_sub(dur),
+                    false => base.checked_add(dur),
+                }
+                .ok_or(LBSError::InvalidTimestamp)?
+            }
+            (TAG_FULL, 0) => SystemTime::lbs_read(r)?,
+            _ => return Err(LBSError::InvalidTimestamp),
+        };
+
+        d.set_time_base(time);
+        Ok(time)
+    }
+}
+
+impl<T: LBSRelativeTimeWrite> LBSRelativeTimeWrite for Option<T> {
+    #[inline]
+    fn lbs_relative_time_write<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_relative_time_write(w, e)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSRelativeTimeRead> LBSRelativeTimeRead for Option<T> {
+    #[inline]
+    fn lbs_relative_time_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_relative_time_read(r, d)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSRelativeTimeWrite> LBSRelativeTimeWrite for Vec<T> {
+    #[inline]
+    fn lbs_relative_time_write<W: Write>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for v in self {
+            v.lbs_relative_time_write(w, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSRelativeTimeRead> LBSRelativeTimeRead for Vec<T> {
+    #[inline]
+    fn lbs_relative_time_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_relative_time_read(r, d)?);
+        }
+
+        Ok(v)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSRelativeTimeWrite, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_relative_time_write(w, e)
+}
+
+#[inline]
+pub fn read<T: LBSRelativeTimeRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_relative_time_read(r, d)
+}
@@ -0,0 +1,27 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use std::io::Read;
+use std::io::Write;
+
+/// Foreign type, which is written like the local mirror `M` derived with
+/// `#[lbs(remote(<type>))]`.
+///
+/// Neither `LBSWrite` nor the foreign type belong to the crate, which needs the type written,
+/// so the mirror, which repeats fields of the type, stands for it. Fields of the foreign type
+/// are written with `#[lbs(with(<mirror>))]`, which calls `M::write()`.
+pub trait LBSRemoteWrite<M> {
+    fn lbs_remote_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>;
+
+    #[inline]
+    fn lbs_remote_size_hint(&self) -> usize {
+        0
+    }
+}
+
+/// Foreign type, which is read like the local mirror `M` derived with `#[lbs(remote(<type>))]`.
+///
+/// Fields of the foreign type are read with `#[lbs(with(<mirror>))]`, which calls `M::read()`.
+pub trait LBSRemoteRead<M>: Sized {
+    fn lbs_remote_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
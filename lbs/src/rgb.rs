@@ -0,0 +1,72 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use rgb::Bgr;
+use rgb::Bgra;
+use rgb::Rgb;
+use rgb::Rgba;
+use std::io::Read;
+use std::io::Write;
+
+/// Pixels are encoded as tuples of their components in memory order, like `(r, g, b)`.
+macro_rules! impl_pixel {
+    ($($t:ident<$($g:ident),*> { $($field:ident: $ft:ident),* }),*) => {
+        $(
+            impl<$($g: LBSWrite),*> LBSWrite for $t<$($g),*> {
+                #[inline]
+                fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    self.lbs_write_with(w, &mut Encoder::new())
+                }
+
+                #[inline]
+                fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+                    $(self.$field.lbs_write_with(w, e)?;)*
+                    Ok(())
+                }
+            }
+
+            impl<$($g: LBSRead),*> LBSRead for $t<$($g),*> {
+                #[inline]
+                fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+                    Self::lbs_read_with(r, &mut Decoder::new())
+                }
+
+                #[inline]
+                fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+                    Ok(Self {
+                        $($field: $ft::lbs_read_with(r, d)?),*
+                    })
+                }
+
+                #[inline]
+                fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+                    $($ft::lbs_skip(r, d)?;)*
+                    Ok(())
+                }
+            }
+
+            impl<$($g: LBSSchema),*> LBSSchema for $t<$($g),*> {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    Schema::Tuple(vec![$($ft::lbs_schema()),*])
+                }
+            }
+
+            impl<$($g: LBSMaxSize),*> LBSMaxSize for $t<$($g),*> {
+                const LBS_MAX_SIZE: usize = 0 $(+ $ft::LBS_MAX_SIZE)*;
+            }
+        )*
+    };
+}
+
+impl_pixel!(
+    Rgb<T> { r: T, g: T, b: T },
+    Rgba<T, A> { r: T, g: T, b: T, a: A },
+    Bgr<T> { b: T, g: T, r: T },
+    Bgra<T, A> { b: T, g: T, r: T, a: A }
+);
@@ -0,0 +1,306 @@
+use crate::error::LBSError;
+use crate::frame::read_frame;
+use crate::frame::write_frame;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use thiserror::Error;
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+/// Service definition: usually a pair of derived enums with one variant per method.
+pub trait Service {
+    type Request: LBSWrite + LBSRead + Send + 'static;
+    type Response: LBSWrite + LBSRead + Send + 'static;
+}
+
+#[derive(Error, Debug)]
+pub enum RpcError {
+    /// Handler failed with the given message.
+    #[error("remote: {0}")]
+    Remote(String),
+    #[error("deadline exceeded")]
+    DeadlineExceeded,
+    #[error(transparent)]
+    Codec(LBSError),
+}
+
+impl From<LBSError> for RpcError {
+    #[inline]
+    fn from(e: LBSError) -> Self {
+        match e {
+            LBSError::Io(e) => e.into(),
+            other => Self::Codec(other),
+        }
+    }
+}
+
+impl From<std::io::Error> for RpcError {
+    #[inline]
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            // Socket read timeout
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => Self::DeadlineExceeded,
+            _ => Self::Codec(e.into()),
+        }
+    }
+}
+
+/// Blocking client. Calls are sent one at a time over a single connection.
+pub struct Client<S: Service> {
+    stream: TcpStream,
+    next_id: u64,
+    service: PhantomData<S>,
+}
+
+impl<S: Service> Client<S> {
+    #[inline]
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, RpcError> {
+        Ok(Self::new(TcpStream::connect(addr)?))
+    }
+
+    #[inline]
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            next_id: 0,
+            service: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn call(&mut self, request: &S::Request) -> Result<S::Response, RpcError> {
+        self.call_inner(request, None)
+    }
+
+    /// Same as `call()`, but fails with `RpcError::DeadlineExceeded` if response takes longer than `deadline`.
+    /// Late response may be left partially read, so client should be reconnected after such an error.
+    /// Deadline is also sent to the server, which replies with the same error if handler is late.
+    #[inline]
+    pub fn call_with_deadline(
+        &mut self,
+        request: &S::Request,
+        deadline: Duration,
+    ) -> Result<S::Response, RpcError> {
+        self.call_inner(request, Some(deadline))
+    }
+
+    fn call_inner(
+        &mut self,
+        request: &S::Request,
+        deadline: Option<Duration>,
+    ) -> Result<S::Response, RpcError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        write_frame(&mut self.stream, &RequestFrame::new(id, deadline, request))?;
+
+        let deadline = deadline.map(|d| Instant::now() + d);
+
+        loop {
+            let timeout = match deadline {
+                Some(deadline) => Some(
+                    deadline
+                        .checked_duration_since(Instant::now())
+                        .filter(|d| !d.is_zero())
+                        .ok_or(RpcError::DeadlineExceeded)?,
+                ),
+                None => None,
+            };
+
+            self.stream.set_read_timeout(timeout)?;
+            let response: ResponseFrame<S::Response> = read_frame(&mut self.stream)?;
+
+            // Responses to calls, which had timed out before, are dropped
+            if response.id == id {
+                return response.result;
+            }
+        }
+    }
+}
+
+/// Delay of accepting after process ran out of file descriptors, so that closed connections
+/// free some instead of failing accept in a busy loop.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Reports failed accept, which doesn't stop the server, as most such errors, like aborted
+/// connections, concern a single client. Returns delay before accepting again.
+pub(crate) fn accept_failed(e: &std::io::Error) -> Option<Duration> {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(error = %e, "accepting connection failed");
+
+    // ENFILE and EMFILE
+    match cfg!(unix) && matches!(e.raw_os_error(), Some(23 | 24)) {
+        true => Some(ACCEPT_BACKOFF),
+        false => None,
+    }
+}
+
+/// Accepts connections and serves each one on its own thread.
+///
+/// Handler errors are propagated to the client as `RpcError::Remote`. Failed accepts are logged
+/// with `tracing` feature and skipped, after a delay if process is out of file descriptors.
+pub fn serve<S, H>(listener: &TcpListener, handler: H) -> Result<(), RpcError>
+where
+    S: Service,
+    H: Fn(S::Request) -> Result<S::Response, String> + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                if let Some(delay) = accept_failed(&e) {
+                    std::thread::sleep(delay);
+                }
+                continue;
+            }
+        };
+        let handler = handler.clone();
+        std::thread::spawn(move || serve_connection::<S, _, _>(stream, handler.as_ref()));
+    }
+
+    Ok(())
+}
+
+/// Serves requests from a single connection until it's closed.
+pub fn serve_connection<S, H, T>(mut stream: T, handler: &H) -> Result<(), RpcError>
+where
+    S: Service,
+    H: Fn(S::Request) -> Result<S::Response, String>,
+    T: Read + Write,
+{
+    loop {
+        let request: RequestFrame<S::Request> = match read_frame(&mut stream) {
+            Ok(request) => request,
+            Err(e) if e.is_eof() => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let deadline = request.deadline();
+        let started = Instant::now();
+        let result = handler(request.body);
+        let expired = deadline.is_some_and(|d| started.elapsed() > d);
+
+        let response = ResponseFrame {
+            id: request.id,
+            result: match result {
+                _ if expired => Err(RpcError::DeadlineExceeded),
+                Ok(ref response) => Ok(response),
+                Err(message) => Err(RpcError::Remote(message)),
+            },
+        };
+
+        write_frame(&mut stream, &response)?;
+    }
+}
+
+//
+// Envelopes.
+//
+
+const RESULT_OK: u8 = 0;
+const RESULT_REMOTE: u8 = 1;
+const RESULT_DEADLINE_EXCEEDED: u8 = 2;
+
+/// Request envelope: message id, deadline in milliseconds (0 if none), request.
+pub(crate) struct RequestFrame<T> {
+    pub(crate) id: u64,
+    pub(crate) deadline_ms: u64,
+    pub(crate) body: T,
+}
+
+impl<T> RequestFrame<T> {
+    #[inline]
+    pub(crate) fn new(id: u64, deadline: Option<Duration>, body: T) -> Self {
+        Self {
+            id,
+            deadline_ms: deadline.map_or(0, |d| (d.as_millis() as u64).max(1)),
+            body,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn deadline(&self) -> Option<Duration> {
+        match self.deadline_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for RequestFrame<&T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.id.lbs_write(w)?;
+        self.deadline_ms.lbs_write(w)?;
+        self.body.lbs_write(w)
+    }
+}
+
+impl<T: LBSRead> LBSRead for RequestFrame<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self {
+            id: u64::lbs_read(r)?,
+            deadline_ms: u64::lbs_read(r)?,
+            body: T::lbs_read(r)?,
+        })
+    }
+}
+
+/// Response envelope: message id, result tag, then response or error message.
+pub(crate) struct ResponseFrame<T> {
+    pub(crate) id: u64,
+    pub(crate) result: Result<T, RpcError>,
+}
+
+impl<T: LBSWrite> LBSWrite for ResponseFrame<&T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.id.lbs_write(w)?;
+
+        match &self.result {
+            Ok(response) => {
+                RESULT_OK.lbs_write(w)?;
+                response.lbs_write(w)
+            }
+            Err(RpcError::DeadlineExceeded) => RESULT_DEADLINE_EXCEEDED.lbs_write(w),
+            Err(RpcError::Remote(message)) => {
+                RESULT_REMOTE.lbs_write(w)?;
+                message.lbs_write(w)
+            }
+            Err(e) => {
+                RESULT_REMOTE.lbs_write(w)?;
+                e.to_string().lbs_write(w)
+            }
+        }
+    }
+}
+
+impl<T: LBSRead> LBSRead for ResponseFrame<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let id = u64::lbs_read(r)?;
+
+        let result = match u8::lbs_read(r)? {
+            RESULT_OK => Ok(T::lbs_read(r)?),
+            RESULT_REMOTE => Err(RpcError::Remote(String::lbs_read(r)?)),
+            RESULT_DEADLINE_EXCEEDED => Err(RpcError::DeadlineExceeded),
+            _ => return Err(LBSError::UnexpectedVariant),
+        };
+
+        Ok(Self { id, result })
+    }
+}
@@ -0,0 +1,166 @@
+use super::RequestFrame;
+use super::ResponseFrame;
+use super::RpcError;
+use super::Service;
+use crate::error::LBSError;
+use crate::frame::DEFAULT_MAX_FRAME_LEN;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::net::ToSocketAddrs;
+
+/// Asynchronous client. Calls are sent one at a time over a single connection.
+pub struct Client<S: Service> {
+    stream: TcpStream,
+    next_id: u64,
+    service: PhantomData<S>,
+}
+
+impl<S: Service> Client<S> {
+    #[inline]
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, RpcError> {
+        Ok(Self::new(TcpStream::connect(addr).await?))
+    }
+
+    #[inline]
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            next_id: 0,
+            service: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub async fn call(&mut self, request: &S::Request) -> Result<S::Response, RpcError> {
+        self.call_inner(request, None).await
+    }
+
+    /// Same as `call()`, but fails with `RpcError::DeadlineExceeded` if response takes longer than `deadline`.
+    /// Late response may be left partially read, so client should be reconnected after such an error.
+    #[inline]
+    pub async fn call_with_deadline(
+        &mut self,
+        request: &S::Request,
+        deadline: Duration,
+    ) -> Result<S::Response, RpcError> {
+        tokio::time::timeout(deadline, self.call_inner(request, Some(deadline)))
+            .await
+            .map_err(|_| RpcError::DeadlineExceeded)?
+    }
+
+    async fn call_inner(
+        &mut self,
+        request: &S::Request,
+        deadline: Option<Duration>,
+    ) -> Result<S::Response, RpcError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let frame = encode_frame(&RequestFrame::new(id, deadline, request))?;
+        self.stream.write_all(&frame).await?;
+
+        loop {
+            let response: ResponseFrame<S::Response> = read_frame(&mut self.stream).await?;
+
+            // Responses to calls, which had timed out before, are dropped
+            if response.id == id {
+                return response.result;
+            }
+        }
+    }
+}
+
+/// Accepts connections and serves each one on its own task.
+///
+/// Handler errors are propagated to the client as `RpcError::Remote`. Failed accepts are logged
+/// with `tracing` feature and skipped, after a delay if process is out of file descriptors.
+pub async fn serve<S, H, F>(listener: &TcpListener, handler: H) -> Result<(), RpcError>
+where
+    S: Service,
+    H: Fn(S::Request) -> F + Send + Sync + 'static,
+    F: Future<Output = Result<S::Response, String>> + Send,
+{
+    let handler = Arc::new(handler);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                if let Some(delay) = super::accept_failed(&e) {
+                    tokio::time::sleep(delay).await;
+                }
+                continue;
+            }
+        };
+        let handler = handler.clone();
+        tokio::spawn(async move { serve_connection::<S, _, _, _>(stream, handler.as_ref()).await });
+    }
+}
+
+/// Serves requests from a single connection until it's closed.
+pub async fn serve_connection<S, H, F, T>(mut stream: T, handler: &H) -> Result<(), RpcError>
+where
+    S: Service,
+    H: Fn(S::Request) -> F,
+    F: Future<Output = Result<S::Response, String>>,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        let request: RequestFrame<S::Request> = match read_frame(&mut stream).await {
+            Ok(request) => request,
+            Err(e) if e.is_eof() => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let deadline = request.deadline();
+        let started = Instant::now();
+        let result = handler(request.body).await;
+        let expired = deadline.is_some_and(|d| started.elapsed() > d);
+
+        let frame = encode_frame(&ResponseFrame {
+            id: request.id,
+            result: match result {
+                _ if expired => Err(RpcError::DeadlineExceeded),
+                Ok(ref response) => Ok(response),
+                Err(message) => Err(RpcError::Remote(message)),
+            },
+        })?;
+
+        stream.write_all(&frame).await?;
+    }
+}
+
+/// Frame is encoded before awaiting, so borrowed values aren't held across await points.
+#[inline]
+fn encode_frame<T: LBSWrite>(value: &T) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    crate::frame::write_frame(&mut buf, value)?;
+    Ok(buf)
+}
+
+async fn read_frame<T: LBSRead, R: AsyncRead + Unpin>(r: &mut R) -> Result<T, LBSError> {
+    let len = r.read_u32_le().await? as usize;
+
+    if len > DEFAULT_MAX_FRAME_LEN {
+        return Err(LBSError::FrameTooLarge {
+            len,
+            max_len: DEFAULT_MAX_FRAME_LEN,
+        });
+    }
+
+    let mut buf = vec![0; len];
+    r.read_exact(&mut buf).await?;
+    from_slice_exact(&buf)
+}
@@ -0,0 +1,571 @@
+use crate::hash::Fnv1a;
+use crate::lazy::Lazy;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::hash::Hasher;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::num::NonZeroI128;
+use std::num::NonZeroI16;
+use std::num::NonZeroI32;
+use std::num::NonZeroI64;
+use std::num::NonZeroI8;
+use std::num::NonZeroIsize;
+use std::num::NonZeroU128;
+use std::num::NonZeroU16;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
+use std::num::Saturating;
+use std::num::Wrapping;
+use std::ops::Bound;
+use std::ops::ControlFlow;
+use std::ops::Range;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Types, which can describe their wire format.
+///
+/// Implementation can be derived with `#[derive(LBSSchema)]`.
+pub trait LBSSchema {
+    fn lbs_schema() -> Schema;
+}
+
+/// Description of a type's wire format.
+///
+/// Descriptors are encodable themselves, so they can be stored or exchanged, see `fingerprint()`.
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Schema {
+    #[default]
+    #[lbs(id(0))]
+    Unit,
+    #[lbs(id(1))]
+    Bool,
+    #[lbs(id(2))]
+    U8,
+    #[lbs(id(3))]
+    U16,
+    #[lbs(id(4))]
+    U32,
+    #[lbs(id(5))]
+    U64,
+    #[lbs(id(6))]
+    U128,
+    #[lbs(id(7))]
+    I8,
+    #[lbs(id(8))]
+    I16,
+    #[lbs(id(9))]
+    I32,
+    #[lbs(id(10))]
+    I64,
+    #[lbs(id(11))]
+    I128,
+    #[lbs(id(12))]
+    F32,
+    #[lbs(id(13))]
+    F64,
+    #[lbs(id(14))]
+    Char,
+    #[lbs(id(15))]
+    String,
+    #[lbs(id(16))]
+    Duration,
+    #[lbs(id(17))]
+    SystemTime,
+    #[lbs(id(18))]
+    Ipv4Addr,
+    #[lbs(id(19))]
+    Ipv6Addr,
+    #[lbs(id(20))]
+    IpAddr,
+    #[lbs(id(21))]
+    Option(Box<Schema>),
+    /// Vectors, slices and sets.
+    #[lbs(id(22))]
+    List(Box<Schema>),
+    #[lbs(id(23))]
+    Map(Box<(Schema, Schema)>),
+    #[lbs(id(24))]
+    Tuple(Vec<Schema>),
+    #[lbs(id(25))]
+    Range(Box<Schema>),
+    #[lbs(id(26))]
+    Struct(StructSchema),
+    #[lbs(id(27))]
+    Enum(EnumSchema),
+    /// Struct or enum with the given name, which is being described already (recursive type).
+    #[lbs(id(28))]
+    Ref(String),
+    /// Type with custom encoding, which can't be described.
+    #[lbs(id(29))]
+    Opaque(String),
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StructSchema {
+    #[lbs(id(0))]
+    pub name: String,
+    #[lbs(id(1))]
+    pub fields: Vec<FieldSchema>,
+    #[lbs(id(2))]
+    pub compact: bool,
+    #[lbs(id(3))]
+    pub pack_bools: bool,
+    #[lbs(id(4))]
+    pub indexed: bool,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldSchema {
+    #[lbs(id(0))]
+    pub id: u16,
+    #[lbs(id(1))]
+    pub name: String,
+    #[lbs(id(2))]
+    pub required: bool,
+    /// Name of a field codec, like `sparse` or `utf8`, which overrides encoding of the type.
+    #[lbs(id(3))]
+    pub codec: Option<String>,
+    #[lbs(id(4))]
+    pub schema: Schema,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EnumSchema {
+    #[lbs(id(0))]
+    pub name: String,
+    #[lbs(id(1))]
+    pub variants: Vec<VariantSchema>,
+    #[lbs(id(2))]
+    pub compact: bool,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VariantSchema {
+    #[lbs(id(0))]
+    pub id: u16,
+    #[lbs(id(1))]
+    pub name: String,
+    #[lbs(id(2))]
+    pub schema: Option<Schema>,
+}
+
+impl Schema {
+    /// Stable 64-bit FNV-1a hash of the encoded descriptor, including names.
+    pub fn fingerprint(&self) -> u64 {
+        let mut buf = Vec::new();
+        // Writing into a vector can't fail
+        self.lbs_write(&mut buf).unwrap();
+        let mut hasher = Fnv1a::new();
+        hasher.write(&buf);
+        hasher.finish()
+    }
+}
+
+/// Compile-time summary of a struct or enum wire format, see `assert_schema_compatible!()`.
+/// Generated by `#[derive(LBSSchema)]`.
+#[derive(Debug, Clone, Copy)]
+pub struct Shape {
+    pub is_enum: bool,
+    pub compact: bool,
+    pub pack_bools: bool,
+    pub indexed: bool,
+    /// Fields or variants.
+    pub entries: &'static [ShapeEntry],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeEntry {
+    pub id: u16,
+    pub required: bool,
+    /// Hash of type and codec spelling, as types can't be compared at compile time.
+    pub ty: u64,
+}
+
+/// Result of `check_shapes()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeCompatibility {
+    Compatible,
+    /// Struct became an enum or vice versa, or container attributes differ.
+    ContainerChanged,
+    /// Field or variant of the writer is unknown to the reader.
+    Dropped(u16),
+    /// Required field is unknown to the writer.
+    Missing(u16),
+    /// Field or variant type differs, or field became required.
+    Changed(u16),
+}
+
+/// Checks, that data written by `writer` is decodable by `reader`, by the same rules as
+/// `testing::Compatibility`. Readers may know more enum variants than writers.
+pub const fn check_shapes(writer: &Shape, reader: &Shape) -> ShapeCompatibility {
+    if writer.is_enum != reader.is_enum
+        || writer.compact != reader.compact
+        || writer.pack_bools != reader.pack_bools
+        || writer.indexed != reader.indexed
+    {
+        return ShapeCompatibility::ContainerChanged;
+    }
+
+    let mut i = 0;
+    while i < writer.entries.len() {
+        let w = &writer.entries[i];
+
+        match find_entry(reader.entries, w.id) {
+            None => return ShapeCompatibility::Dropped(w.id),
+            Some(r) if r.ty != w.ty || (r.required && !w.required) => {
+                return ShapeCompatibility::Changed(w.id)
+            }
+            Some(_) => {}
+        }
+
+        i += 1;
+    }
+
+    let mut i = 0;
+    while !reader.is_enum && i < reader.entries.len() {
+        let r = &reader.entries[i];
+
+        if r.required && find_entry(writer.entries, r.id).is_none() {
+            return ShapeCompatibility::Missing(r.id);
+        }
+
+        i += 1;
+    }
+
+    ShapeCompatibility::Compatible
+}
+
+const fn find_entry(entries: &[ShapeEntry], id: u16) -> Option<&ShapeEntry> {
+    let mut i = 0;
+    while i < entries.len() {
+        if entries[i].id == id {
+            return Some(&entries[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Fails compilation unless data written by the first type is decodable by the second one,
+/// both deriving `LBSSchema`. Field types are compared by their spelling, so aliases or
+/// differently qualified paths of the same type are reported as changes.
+///
+/// ```ignore
+/// lbs::assert_schema_compatible!(UserV1, UserV2);
+/// ```
+#[macro_export]
+macro_rules! assert_schema_compatible {
+    ($writer:ty, $reader:ty $(,)?) => {
+        const _: () =
+            match $crate::schema::check_shapes(&<$writer>::__LBS_SHAPE, &<$reader>::__LBS_SHAPE) {
+                $crate::schema::ShapeCompatibility::Compatible => {}
+                $crate::schema::ShapeCompatibility::ContainerChanged => panic!(concat!(
+                    stringify!($writer),
+                    " is not decodable as ",
+                    stringify!($reader),
+                    ": container changed"
+                )),
+                $crate::schema::ShapeCompatibility::Dropped(_) => panic!(concat!(
+                    stringify!($writer),
+                    " is not decodable as ",
+                    stringify!($reader),
+                    ": field or variant is unknown to the reader"
+                )),
+                $crate::schema::ShapeCompatibility::Missing(_) => panic!(concat!(
+                    stringify!($writer),
+                    " is not decodable as ",
+                    stringify!($reader),
+                    ": required field is unknown to the writer"
+                )),
+                $crate::schema::ShapeCompatibility::Changed(_) => panic!(concat!(
+                    stringify!($writer),
+                    " is not decodable as ",
+                    stringify!($reader),
+                    ": field type changed or field became required"
+                )),
+            };
+    };
+}
+
+thread_local! {
+    static DESCRIBING: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Calls `f` to describe type `T` named `name`, unless it is described already up the stack,
+/// in which case `Schema::Ref(name)` is returned. Used by derived implementations.
+pub fn describe<T: ?Sized, F: FnOnce() -> Schema>(name: &str, f: F) -> Schema {
+    let type_name = std::any::type_name::<T>();
+
+    if DESCRIBING.with(|stack| stack.borrow().contains(&type_name)) {
+        return Schema::Ref(name.to_string());
+    }
+
+    DESCRIBING.with(|stack| stack.borrow_mut().push(type_name));
+    let schema = f();
+    DESCRIBING.with(|stack| stack.borrow_mut().pop());
+    schema
+}
+
+macro_rules! impl_schema {
+    ($($t:ty => $s:ident),*) => {
+        $(
+            impl LBSSchema for $t {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    Schema::$s
+                }
+            }
+        )*
+    };
+}
+
+impl_schema!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, usize => U64, u128 => U128);
+impl_schema!(i8 => I8, i16 => I16, i32 => I32, i64 => I64, isize => I64, i128 => I128);
+impl_schema!(f32 => F32, f64 => F64, () => Unit, bool => Bool, char => Char);
+impl_schema!(str => String, String => String, Duration => Duration, SystemTime => SystemTime);
+impl_schema!(Ipv4Addr => Ipv4Addr, Ipv6Addr => Ipv6Addr, IpAddr => IpAddr);
+impl_schema!(OsStr => String, OsString => String, Path => String, PathBuf => String);
+impl_schema!(NonZeroU8 => U8, NonZeroU16 => U16, NonZeroU32 => U32, NonZeroU64 => U64);
+impl_schema!(NonZeroUsize => U64, NonZeroU128 => U128, NonZeroI8 => I8, NonZeroI16 => I16);
+impl_schema!(NonZeroI32 => I32, NonZeroI64 => I64, NonZeroIsize => I64, NonZeroI128 => I128);
+
+macro_rules! impl_schema_wrapper {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSSchema + ?Sized> LBSSchema for $t<T> {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    T::lbs_schema()
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_wrapper!(Box, Rc, Arc);
+
+macro_rules! impl_schema_newtype {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSSchema> LBSSchema for $t<T> {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    T::lbs_schema()
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_newtype!(Reverse, Wrapping, Saturating);
+
+impl<T: LBSSchema + ?Sized> LBSSchema for &T {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
+
+impl<'a, T: LBSSchema + ToOwned + ?Sized> LBSSchema for Cow<'a, T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for Lazy<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for Option<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Option(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<B: LBSSchema, C: LBSSchema> LBSSchema for ControlFlow<B, C> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Enum(EnumSchema {
+            name: "ControlFlow".to_string(),
+            variants: vec![
+                VariantSchema {
+                    id: 0,
+                    name: "Continue".to_string(),
+                    schema: Some(C::lbs_schema()),
+                },
+                VariantSchema {
+                    id: 1,
+                    name: "Break".to_string(),
+                    schema: Some(B::lbs_schema()),
+                },
+            ],
+            compact: false,
+        })
+    }
+}
+
+impl<T: LBSSchema, E: LBSSchema> LBSSchema for Result<T, E> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Enum(EnumSchema {
+            name: "Result".to_string(),
+            variants: vec![
+                VariantSchema {
+                    id: 0,
+                    name: "Ok".to_string(),
+                    schema: Some(T::lbs_schema()),
+                },
+                VariantSchema {
+                    id: 1,
+                    name: "Err".to_string(),
+                    schema: Some(E::lbs_schema()),
+                },
+            ],
+            compact: false,
+        })
+    }
+}
+
+/// Enum without variants.
+impl LBSSchema for Infallible {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Enum(EnumSchema {
+            name: "Infallible".to_string(),
+            variants: Vec::new(),
+            compact: false,
+        })
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for OnceLock<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Option(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for Range<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Range(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for RangeInclusive<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Range(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<T: LBSSchema> LBSSchema for Bound<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Enum(EnumSchema {
+            name: "Bound".to_string(),
+            variants: vec![
+                VariantSchema {
+                    id: 0,
+                    name: "Included".to_string(),
+                    schema: Some(T::lbs_schema()),
+                },
+                VariantSchema {
+                    id: 1,
+                    name: "Excluded".to_string(),
+                    schema: Some(T::lbs_schema()),
+                },
+                VariantSchema {
+                    id: 2,
+                    name: "Unbounded".to_string(),
+                    schema: None,
+                },
+            ],
+            compact: false,
+        })
+    }
+}
+
+macro_rules! impl_schema_list {
+    ($($t:ty),*) => {
+        $(
+            impl<T: LBSSchema> LBSSchema for $t {
+                #[inline]
+                fn lbs_schema() -> Schema {
+                    Schema::List(Box::new(T::lbs_schema()))
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_list!([T], Vec<T>, VecDeque<T>, BTreeSet<T>, BinaryHeap<T>);
+
+impl<T: LBSSchema, const N: usize> LBSSchema for [T; N] {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::List(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<T: LBSSchema, S> LBSSchema for HashSet<T, S> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::List(Box::new(T::lbs_schema()))
+    }
+}
+
+impl<K: LBSSchema, V: LBSSchema, S> LBSSchema for HashMap<K, V, S> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Map(Box::new((K::lbs_schema(), V::lbs_schema())))
+    }
+}
+
+impl<K: LBSSchema, V: LBSSchema> LBSSchema for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Map(Box::new((K::lbs_schema(), V::lbs_schema())))
+    }
+}
+
+impl<T1: LBSSchema, T2: LBSSchema> LBSSchema for (T1, T2) {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![T1::lbs_schema(), T2::lbs_schema()])
+    }
+}
+
+impl<T1: LBSSchema, T2: LBSSchema, T3: LBSSchema> LBSSchema for (T1, T2, T3) {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![T1::lbs_schema(), T2::lbs_schema(), T3::lbs_schema()])
+    }
+}
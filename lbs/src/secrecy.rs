@@ -0,0 +1,99 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::LBSRead;
+use crate::LBSWrite;
+use secrecy::ExposeSecret;
+use secrecy::Secret;
+use std::io::Read;
+use std::io::Write;
+use zeroize::Zeroize;
+
+/// Secrets, which are written by exposing them.
+///
+/// `Secret<T>` doesn't implement `LBSWrite`, so it can't be serialized by accident. Derived
+/// implementations write fields of types `Secret<T>`, `SecretString`, `SecretVec<T>`, or
+/// options of them, only if container has `#[lbs(expose_secrets)]` attribute, and fail to
+/// compile otherwise. Wire format is the same as of `T`.
+pub trait LBSSecretWrite {
+    fn lbs_write_secret<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>;
+
+    fn lbs_must_write_secret(&self) -> bool;
+}
+
+impl<T: Zeroize + LBSWrite> LBSSecretWrite for Secret<T> {
+    #[inline]
+    fn lbs_write_secret<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.expose_secret().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write_secret(&self) -> bool {
+        self.expose_secret().lbs_must_write()
+    }
+}
+
+impl<T: LBSSecretWrite> LBSSecretWrite for Option<T> {
+    #[inline]
+    fn lbs_write_secret<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            (1_u8).lbs_write(w)?;
+            v.lbs_write_secret(w, e)
+        } else {
+            (0_u8).lbs_write(w)
+        }
+    }
+
+    #[inline]
+    fn lbs_must_write_secret(&self) -> bool {
+        self.is_some()
+    }
+}
+
+/// Writes exposed secret. Used by derived implementations.
+#[inline]
+pub fn write<T: LBSSecretWrite, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_write_secret(w, e)
+}
+
+/// Used by derived implementations.
+#[inline]
+pub fn must_write<T: LBSSecretWrite>(value: &T) -> bool {
+    value.lbs_must_write_secret()
+}
+
+/// Decoded value is wrapped right away, and scratch buffer of the decoder is zeroized,
+/// like for `Sensitive<T>`.
+impl<T: Zeroize + LBSRead> LBSRead for Secret<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let value = T::lbs_read_with(r, d).map(Self::new);
+        d.zeroize_scratch();
+        value
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let result = T::lbs_skip(r, d);
+        d.zeroize_scratch();
+        result
+    }
+}
+
+impl<T: Zeroize + LBSSchema> LBSSchema for Secret<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
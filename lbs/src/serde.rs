@@ -0,0 +1,851 @@
+use crate::error::LBSError;
+use crate::read::option_tag;
+use crate::read::read_field_count;
+use crate::read::read_field_id;
+use crate::read::read_len;
+use crate::write::write_field_count;
+use crate::write::write_field_id;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use serde::de;
+use serde::de::DeserializeOwned;
+use serde::de::IntoDeserializer;
+use serde::ser;
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+
+/// Encodes value implementing `serde::Serialize` in LBS format, see `Serializer`.
+#[inline]
+pub fn to_writer<T: Serialize + ?Sized, W: Write>(w: &mut W, value: &T) -> Result<(), LBSError> {
+    value.serialize(&mut Serializer::new(w))
+}
+
+#[inline]
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// Decodes value implementing `serde::Deserialize` from LBS format, see `Deserializer`.
+#[inline]
+pub fn from_reader<T: DeserializeOwned, R: Read>(r: &mut R) -> Result<T, LBSError> {
+    T::deserialize(&mut Deserializer::new(r))
+}
+
+/// Same as `from_reader()`, but fails if bytes remain after the value.
+#[inline]
+pub fn from_slice<T: DeserializeOwned>(mut bytes: &[u8]) -> Result<T, LBSError> {
+    let value = from_reader(&mut bytes)?;
+
+    if !bytes.is_empty() {
+        return Err(LBSError::TrailingBytes(bytes.len()));
+    }
+
+    Ok(value)
+}
+
+impl ser::Error for LBSError {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Parsing(msg.to_string())
+    }
+}
+
+impl de::Error for LBSError {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Parsing(msg.to_string())
+    }
+}
+
+/// Serde serializer, which writes values like their `#[derive(LBSWrite)]` counterparts.
+///
+/// Primitives, strings, options, sequences and maps are written like the built-in types.
+/// Structs are written with field ids equal to positions of fields, counting ones skipped by
+/// `skip_serializing_if`, and enums with variant ids equal to positions of variants.
+/// Tuple structs and tuple variants are structs with unnamed fields. `None` fields are written,
+/// as serializer can't tell options from other values. Sequences must know their length.
+pub struct Serializer<W> {
+    w: W,
+}
+
+impl<W: Write> Serializer<W> {
+    #[inline]
+    pub fn new(w: W) -> Self {
+        Self { w }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
+    #[inline]
+    fn write<T: LBSWrite + ?Sized>(&mut self, value: &T) -> Result<(), LBSError> {
+        value.lbs_write(&mut self.w)
+    }
+
+    #[inline]
+    fn write_struct(&mut self, len: usize) -> Result<Compound<'_, W>, LBSError> {
+        write_field_count(&mut self.w, field_id(len)?)?;
+        Ok(Compound::new(self, true))
+    }
+}
+
+#[inline]
+fn field_id(index: usize) -> Result<u16, LBSError> {
+    index
+        .try_into()
+        .map_err(|_| LBSError::OutOfRange("field or variant id"))
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = LBSError;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<(), LBSError> {
+        self.write(&v)
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<(), LBSError> {
+        self.write(v)
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), LBSError> {
+        self.write(v)
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<(), LBSError> {
+        self.write(&0u8)
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), LBSError> {
+        self.write(&1u8)?;
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<(), LBSError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), LBSError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), LBSError> {
+        write_field_id(&mut self.w, field_id(variant_index as usize)?)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), LBSError> {
+        write_field_count(&mut self.w, 1)?;
+        write_field_id(&mut self.w, 0)?;
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), LBSError> {
+        write_field_id(&mut self.w, field_id(variant_index as usize)?)?;
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a, W>, LBSError> {
+        let len = len.ok_or_else(|| LBSError::Parsing("sequence of unknown length".into()))?;
+        write_len(&mut self.w, len)?;
+        Ok(Compound::new(self, false))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a, W>, LBSError> {
+        Ok(Compound::new(self, false))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, LBSError> {
+        self.write_struct(len)
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, LBSError> {
+        write_field_id(&mut self.w, field_id(variant_index as usize)?)?;
+        self.write_struct(len)
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a, W>, LBSError> {
+        let len = len.ok_or_else(|| LBSError::Parsing("map of unknown length".into()))?;
+        write_len(&mut self.w, len)?;
+        Ok(Compound::new(self, false))
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, LBSError> {
+        self.write_struct(len)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, W>, LBSError> {
+        write_field_id(&mut self.w, field_id(variant_index as usize)?)?;
+        self.write_struct(len)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializer of elements of sequences and tuples, entries of maps and fields of structs.
+pub struct Compound<'a, W> {
+    ser: &'a mut Serializer<W>,
+    /// Elements are struct fields, which are preceded by their ids.
+    fields: bool,
+    next_id: usize,
+}
+
+impl<'a, W: Write> Compound<'a, W> {
+    #[inline]
+    fn new(ser: &'a mut Serializer<W>, fields: bool) -> Self {
+        Self {
+            ser,
+            fields,
+            next_id: 0,
+        }
+    }
+
+    #[inline]
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LBSError> {
+        if self.fields {
+            write_field_id(&mut self.ser.w, field_id(self.next_id)?)?;
+            self.next_id += 1;
+        }
+
+        value.serialize(&mut *self.ser)
+    }
+}
+
+macro_rules! impl_serialize_compound {
+    ($($t:ident => $f:ident),*) => {
+        $(
+            impl<W: Write> ser::$t for Compound<'_, W> {
+                type Ok = ();
+                type Error = LBSError;
+
+                #[inline]
+                fn $f<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LBSError> {
+                    self.element(value)
+                }
+
+                #[inline]
+                fn end(self) -> Result<(), LBSError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_compound!(
+    SerializeSeq => serialize_element,
+    SerializeTuple => serialize_element,
+    SerializeTupleStruct => serialize_field,
+    SerializeTupleVariant => serialize_field
+);
+
+impl<W: Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = LBSError;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), LBSError> {
+        key.serialize(&mut *self.ser)
+    }
+
+    #[inline]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), LBSError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), LBSError> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_serialize_struct {
+    ($($t:ident),*) => {
+        $(
+            impl<W: Write> ser::$t for Compound<'_, W> {
+                type Ok = ();
+                type Error = LBSError;
+
+                #[inline]
+                fn serialize_field<T: Serialize + ?Sized>(
+                    &mut self,
+                    _key: &'static str,
+                    value: &T,
+                ) -> Result<(), LBSError> {
+                    self.element(value)
+                }
+
+                /// Skipped field still takes its id, so ids of the following ones don't change.
+                #[inline]
+                fn skip_field(&mut self, _key: &'static str) -> Result<(), LBSError> {
+                    self.next_id += 1;
+                    Ok(())
+                }
+
+                #[inline]
+                fn end(self) -> Result<(), LBSError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_struct!(SerializeStruct, SerializeStructVariant);
+
+/// Serde deserializer of values written by `Serializer` or by their `#[derive(LBSWrite)]`
+/// counterparts, see `Serializer` for the layout.
+///
+/// LBS format doesn't describe itself, so `deserialize_any()` and values of unknown type,
+/// like unknown struct fields, aren't supported. Absent fields are reported as missing,
+/// which serde resolves to `None` for options and to defaults for `#[serde(default)]` fields.
+pub struct Deserializer<R> {
+    r: R,
+}
+
+impl<R: Read> Deserializer<R> {
+    #[inline]
+    pub fn new(r: R) -> Self {
+        Self { r }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+
+    #[inline]
+    fn read<T: LBSRead>(&mut self) -> Result<T, LBSError> {
+        T::lbs_read(&mut self.r)
+    }
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = LBSError;
+
+    #[inline]
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, LBSError> {
+        Err(LBSError::Parsing(
+            "LBS format isn't self-describing, type of value must be known".into(),
+        ))
+    }
+
+    #[inline]
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_bool(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_i8(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_i16(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_i32(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_i64(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_i128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_i128(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u8(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u16(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u32(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u64(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_u128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u128(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_f32(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_f64(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_char(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_string(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_string(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_byte_buf(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_byte_buf(self.read()?)
+    }
+
+    #[inline]
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        match option_tag(self.read::<u8>()?)? {
+            true => visitor.visit_some(self),
+            false => visitor.visit_none(),
+        }
+    }
+
+    #[inline]
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        match read_field_count(&mut self.r)? {
+            0 => Err(LBSError::RequiredButMissing.with_field(0)),
+            _ => match read_field_id(&mut self.r)? {
+                0 => visitor.visit_newtype_struct(self),
+                id => Err(unknown_field(id)),
+            },
+        }
+    }
+
+    #[inline]
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        let len = read_len(&mut self.r)?;
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+            next_id: None,
+        })
+    }
+
+    #[inline]
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: len,
+            next_id: None,
+        })
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        let count = read_field_count(&mut self.r)?;
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining: count as usize,
+            next_id: Some(0),
+        })
+    }
+
+    #[inline]
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        let len = read_len(&mut self.r)?;
+        visitor.visit_map(Entries {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    #[inline]
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        let count = read_field_count(&mut self.r)?;
+        visitor.visit_map(Fields {
+            de: self,
+            remaining: count,
+        })
+    }
+
+    #[inline]
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        visitor.visit_enum(self)
+    }
+
+    #[inline]
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, LBSError> {
+        visitor.visit_u16(read_field_id(&mut self.r)?)
+    }
+
+    #[inline]
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        Err(LBSError::Parsing(
+            "LBS format isn't self-describing, values of unknown type can't be skipped".into(),
+        ))
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+#[inline]
+fn unknown_field(id: u16) -> LBSError {
+    LBSError::Parsing(format!("unexpected field {}", id))
+}
+
+/// Elements of sequences and tuples, or unnamed fields of tuple structs, which are preceded by
+/// their ids starting at `next_id`.
+struct Elements<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: usize,
+    next_id: Option<u16>,
+}
+
+impl<'de, R: Read> de::SeqAccess<'de> for Elements<'_, R> {
+    type Error = LBSError;
+
+    #[inline]
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, LBSError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        // Unnamed fields are read by position, so none may be absent
+        if let Some(ref mut next_id) = self.next_id {
+            let id = read_field_id(&mut self.de.r)?;
+
+            if id != *next_id {
+                return Err(unknown_field(id));
+            }
+
+            *next_id += 1;
+        }
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Entries of maps.
+struct Entries<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: usize,
+}
+
+impl<'de, R: Read> de::MapAccess<'de> for Entries<'_, R> {
+    type Error = LBSError;
+
+    #[inline]
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, LBSError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    #[inline]
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, LBSError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Fields of structs, which keys are their ids.
+struct Fields<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: u16,
+}
+
+impl<'de, R: Read> de::MapAccess<'de> for Fields<'_, R> {
+    type Error = LBSError;
+
+    #[inline]
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, LBSError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        let id = read_field_id(&mut self.de.r)?;
+        seed.deserialize(IntoDeserializer::<LBSError>::into_deserializer(id as u64))
+            .map(Some)
+    }
+
+    #[inline]
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, LBSError> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+impl<'de, R: Read> de::EnumAccess<'de> for &mut Deserializer<R> {
+    type Error = LBSError;
+    type Variant = Self;
+
+    #[inline]
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), LBSError> {
+        let id = read_field_id(&mut self.r)?;
+        let variant = seed
+            .deserialize(IntoDeserializer::<LBSError>::into_deserializer(id as u64))
+            .map_err(|_| LBSError::UnexpectedVariant)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R: Read> de::VariantAccess<'de> for &mut Deserializer<R> {
+    type Error = LBSError;
+
+    #[inline]
+    fn unit_variant(self) -> Result<(), LBSError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, LBSError> {
+        seed.deserialize(self)
+    }
+
+    #[inline]
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        de::Deserializer::deserialize_tuple_struct(self, "", len, visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, LBSError> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use ed25519_dalek::Signature;
+use ed25519_dalek::Signer;
+use std::marker::PhantomData;
+
+pub use ed25519_dalek::SigningKey;
+pub use ed25519_dalek::VerifyingKey;
+
+/// Encoded value with detached Ed25519 signature.
+///
+/// Signature covers key id and payload, which is decoded only after successful verification.
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage<T> {
+    #[lbs(id(0))]
+    payload: Vec<u8>,
+    #[lbs(id(1))]
+    key_id: String,
+    #[lbs(id(2))]
+    signature: Vec<u8>,
+    #[lbs(id(3), skip)]
+    value: PhantomData<T>,
+}
+
+impl<T> Default for SignedMessage<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            payload: Vec::new(),
+            key_id: String::new(),
+            signature: Vec::new(),
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T> SignedMessage<T> {
+    /// Id of the key, which was used for signing. Allows receiver to choose verifying key.
+    #[inline]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Encoded value, which is not verified yet.
+    #[inline]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    #[inline]
+    fn signed_bytes(key_id: &str, payload: &[u8]) -> Result<Vec<u8>, LBSError> {
+        let mut buf = Vec::with_capacity(4 + key_id.len() + payload.len());
+        key_id.lbs_write(&mut buf)?;
+        buf.extend_from_slice(payload);
+        Ok(buf)
+    }
+}
+
+impl<T: LBSWrite> SignedMessage<T> {
+    /// Encodes and signs value.
+    pub fn sign(value: &T, key_id: &str, key: &SigningKey) -> Result<Self, LBSError> {
+        let mut payload = Vec::new();
+        value.lbs_write(&mut payload)?;
+        let signature = key.sign(&Self::signed_bytes(key_id, &payload)?);
+
+        Ok(Self {
+            payload,
+            key_id: key_id.to_string(),
+            signature: signature.to_bytes().to_vec(),
+            value: PhantomData,
+        })
+    }
+}
+
+impl<T: LBSRead> SignedMessage<T> {
+    /// Verifies signature and decodes value.
+    /// Fails with `LBSError::AuthenticationFailed` if signature doesn't match. Verification is
+    /// strict, so weak keys and malleable signatures are rejected.
+    pub fn verify(&self, key: &VerifyingKey) -> Result<T, LBSError> {
+        let signature =
+            Signature::from_slice(&self.signature).map_err(|_| LBSError::AuthenticationFailed)?;
+
+        key.verify_strict(
+            &Self::signed_bytes(&self.key_id, &self.payload)?,
+            &signature,
+        )
+        .map_err(|_| LBSError::AuthenticationFailed)?;
+
+        from_slice_exact(&self.payload)
+    }
+
+    /// Same as `verify()`, but looks up verifying key by key id.
+    /// Fails with `LBSError::AuthenticationFailed` if key is unknown.
+    #[inline]
+    pub fn verify_with<F>(&self, lookup: F) -> Result<T, LBSError>
+    where
+        F: FnOnce(&str) -> Option<VerifyingKey>,
+    {
+        let key = lookup(&self.key_id).ok_or(LBSError::AuthenticationFailed)?;
+        self.verify(&key)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSWrite;
+use std::io::Write;
+
+/// Output of encoded bytes, which is easier to implement for a new backend than `io::Write`.
+///
+/// All `LBSWrite` implementations, including derived ones, are written against `io::Write`,
+/// so sinks are adapted to it by `Sink` and backends don't touch them. Implemented for
+/// `Vec<u8>`, `&mut [u8]`, `BytesMut` (with `bytes` feature) and any writer wrapped into `Io`.
+pub trait LBSWriter {
+    /// Appends all bytes or fails.
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()>;
+}
+
+impl LBSWriter for Vec<u8> {
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Slice is filled from the start and advanced past put bytes.
+impl LBSWriter for &mut [u8] {
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if bytes.len() > self.len() {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+
+        let (head, tail) = std::mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl LBSWriter for bytes::BytesMut {
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Sink of an `io::Write`.
+#[derive(Debug, Default)]
+pub struct Io<W>(pub W);
+
+impl<W: Write> LBSWriter for Io<W> {
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.0.write_all(bytes)
+    }
+}
+
+/// Adapter of a sink to `io::Write`.
+#[derive(Debug)]
+pub struct Sink<'s, S: ?Sized>(pub &'s mut S);
+
+impl<S: LBSWriter + ?Sized> Write for Sink<'_, S> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.put(buf)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.0.put(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encodes value into a sink.
+#[inline]
+pub fn write<T, S>(value: &T, sink: &mut S) -> Result<(), LBSError>
+where
+    T: LBSWrite + ?Sized,
+    S: LBSWriter + ?Sized,
+{
+    value.lbs_write(&mut Sink(sink))
+}
+
+/// Same as `write()`, but applies encoding options.
+#[inline]
+pub fn write_with<T, S>(value: &T, sink: &mut S, e: &mut Encoder) -> Result<(), LBSError>
+where
+    T: LBSWrite + ?Sized,
+    S: LBSWriter + ?Sized,
+{
+    value.lbs_write_with(&mut Sink(sink), e)
+}
@@ -0,0 +1,125 @@
+use std::cmp::Reverse;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::num::NonZeroI128;
+use std::num::NonZeroI16;
+use std::num::NonZeroI32;
+use std::num::NonZeroI64;
+use std::num::NonZeroI8;
+use std::num::NonZeroIsize;
+use std::num::NonZeroU128;
+use std::num::NonZeroU16;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
+use std::num::Saturating;
+use std::num::Wrapping;
+use std::ops::Bound;
+use std::ops::ControlFlow;
+use std::ops::Range;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Types, which encoded size never exceeds a known constant, so they may be written into
+/// stack buffers, like `[0u8; T::LBS_MAX_SIZE]`.
+///
+/// Implementation can be derived with `#[derive(LBSMaxSize)]` for structs and enums composed
+/// only of such types. Variable-size types, like strings and vectors, don't implement it.
+pub trait LBSMaxSize {
+    const LBS_MAX_SIZE: usize;
+}
+
+/// Largest number of bytes of a varint-encoded `u16`.
+pub const VARINT_U16_MAX_SIZE: usize = 3;
+
+/// Maximum of two sizes, usable in constant expressions. Used by derived implementations.
+#[inline]
+pub const fn max(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+macro_rules! impl_max_size {
+    ($($t:ty => $size:expr),*) => {
+        $(
+            impl LBSMaxSize for $t {
+                const LBS_MAX_SIZE: usize = $size;
+            }
+        )*
+    };
+}
+
+impl_max_size!(u8 => 1, u16 => 2, u32 => 4, u64 => 8, usize => size_of::<usize>(), u128 => 16);
+impl_max_size!(i8 => 1, i16 => 2, i32 => 4, i64 => 8, isize => size_of::<isize>(), i128 => 16);
+impl_max_size!(f32 => 4, f64 => 8, () => 0, bool => 1, char => 4, Infallible => 0);
+impl_max_size!(Duration => 12, SystemTime => 12, Ipv4Addr => 4, Ipv6Addr => 16, IpAddr => 17);
+impl_max_size!(NonZeroU8 => 1, NonZeroU16 => 2, NonZeroU32 => 4, NonZeroU64 => 8);
+impl_max_size!(NonZeroUsize => size_of::<usize>(), NonZeroU128 => 16);
+impl_max_size!(NonZeroI8 => 1, NonZeroI16 => 2, NonZeroI32 => 4, NonZeroI64 => 8);
+impl_max_size!(NonZeroIsize => size_of::<isize>(), NonZeroI128 => 16);
+
+macro_rules! impl_max_size_wrapper {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSMaxSize> LBSMaxSize for $t<T> {
+                const LBS_MAX_SIZE: usize = T::LBS_MAX_SIZE;
+            }
+        )*
+    };
+}
+
+impl_max_size_wrapper!(Box, Rc, Arc, Reverse, Wrapping, Saturating);
+
+impl<T: LBSMaxSize> LBSMaxSize for &T {
+    const LBS_MAX_SIZE: usize = T::LBS_MAX_SIZE;
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for Option<T> {
+    const LBS_MAX_SIZE: usize = 1 + T::LBS_MAX_SIZE;
+}
+
+impl<B: LBSMaxSize, C: LBSMaxSize> LBSMaxSize for ControlFlow<B, C> {
+    const LBS_MAX_SIZE: usize = 2 + max(B::LBS_MAX_SIZE, C::LBS_MAX_SIZE);
+}
+
+impl<T: LBSMaxSize, E: LBSMaxSize> LBSMaxSize for Result<T, E> {
+    const LBS_MAX_SIZE: usize = 2 + max(T::LBS_MAX_SIZE, E::LBS_MAX_SIZE);
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for Bound<T> {
+    const LBS_MAX_SIZE: usize = 2 + T::LBS_MAX_SIZE;
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for OnceLock<T> {
+    const LBS_MAX_SIZE: usize = 1 + T::LBS_MAX_SIZE;
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for Range<T> {
+    const LBS_MAX_SIZE: usize = 2 * T::LBS_MAX_SIZE;
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for RangeInclusive<T> {
+    const LBS_MAX_SIZE: usize = 2 * T::LBS_MAX_SIZE;
+}
+
+impl<T: LBSMaxSize, const N: usize> LBSMaxSize for [T; N] {
+    const LBS_MAX_SIZE: usize = 4 + N * T::LBS_MAX_SIZE;
+}
+
+impl<T1: LBSMaxSize, T2: LBSMaxSize> LBSMaxSize for (T1, T2) {
+    const LBS_MAX_SIZE: usize = T1::LBS_MAX_SIZE + T2::LBS_MAX_SIZE;
+}
+
+impl<T1: LBSMaxSize, T2: LBSMaxSize, T3: LBSMaxSize> LBSMaxSize for (T1, T2, T3) {
+    const LBS_MAX_SIZE: usize = T1::LBS_MAX_SIZE + T2::LBS_MAX_SIZE + T3::LBS_MAX_SIZE;
+}
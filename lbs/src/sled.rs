@@ -0,0 +1,124 @@
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use sled::IVec;
+use sled::Tree;
+use std::marker::PhantomData;
+
+/// Encodes value into `IVec`.
+#[inline]
+pub fn to_ivec<T: LBSWrite + ?Sized>(value: &T) -> Result<IVec, LBSError> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf)?;
+    Ok(buf.into())
+}
+
+/// Decodes value from `IVec`, which must not have trailing bytes.
+#[inline]
+pub fn from_ivec<T: LBSRead>(bytes: &IVec) -> Result<T, LBSError> {
+    from_slice_exact(bytes)
+}
+
+/// Tree with encoded keys and values.
+///
+/// Keys are ordered by their encoded bytes, so integers, which are little-endian, aren't iterated
+/// in numeric order.
+#[derive(Debug, Clone)]
+pub struct LBSTree<K, V> {
+    tree: Tree,
+    types: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K: LBSWrite + LBSRead, V: LBSWrite + LBSRead> LBSTree<K, V> {
+    #[inline]
+    pub fn new(tree: Tree) -> Self {
+        Self {
+            tree,
+            types: PhantomData,
+        }
+    }
+
+    /// Opens tree with given name in the database.
+    #[inline]
+    pub fn open<N: AsRef<[u8]>>(db: &sled::Db, name: N) -> Result<Self, LBSError> {
+        Ok(Self::new(db.open_tree(name).map_err(io)?))
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Inserts value and returns the previous one.
+    #[inline]
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, LBSError> {
+        let previous = self
+            .tree
+            .insert(to_ivec(key)?, to_ivec(value)?)
+            .map_err(io)?;
+        previous.as_ref().map(from_ivec).transpose()
+    }
+
+    #[inline]
+    pub fn get(&self, key: &K) -> Result<Option<V>, LBSError> {
+        let value = self.tree.get(to_ivec(key)?).map_err(io)?;
+        value.as_ref().map(from_ivec).transpose()
+    }
+
+    /// Removes value and returns it.
+    #[inline]
+    pub fn remove(&self, key: &K) -> Result<Option<V>, LBSError> {
+        let value = self.tree.remove(to_ivec(key)?).map_err(io)?;
+        value.as_ref().map(from_ivec).transpose()
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> Result<bool, LBSError> {
+        self.tree.contains_key(to_ivec(key)?).map_err(io)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Iterates over decoded entries in order of encoded keys.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Result<(K, V), LBSError>> {
+        self.tree.iter().map(decode_entry)
+    }
+
+    /// Removes and returns the entry with the smallest encoded key, so the tree may be used as a queue.
+    #[inline]
+    pub fn pop_min(&self) -> Result<Option<(K, V)>, LBSError> {
+        self.tree
+            .pop_min()
+            .transpose()
+            .map(decode_entry)
+            .transpose()
+    }
+
+    #[inline]
+    pub fn flush(&self) -> Result<(), LBSError> {
+        self.tree.flush().map(|_| ()).map_err(io)
+    }
+}
+
+#[inline]
+fn decode_entry<K: LBSRead, V: LBSRead>(
+    entry: sled::Result<(IVec, IVec)>,
+) -> Result<(K, V), LBSError> {
+    let (key, value) = entry.map_err(io)?;
+    Ok((from_ivec(&key)?, from_ivec(&value)?))
+}
+
+#[inline]
+fn io(e: sled::Error) -> LBSError {
+    LBSError::Io(e.into())
+}
@@ -1,6 +1,9 @@
 use super::LBSRead;
 use super::LBSWrite;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
 use crate::error::LBSError;
+use crate::vectored::VectoredWriter;
 use smallvec::Array;
 use smallvec::SmallVec;
 use std::io::Read;
@@ -13,12 +16,23 @@ where
 {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
         crate::write::write_len(w, self.len())?;
-        for e in self {
-            e.lbs_write(w)?;
+        for element in self {
+            element.lbs_write_with(w, e)?;
         }
         Ok(())
     }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        crate::write::write_len(v, self.len())?;
+        <A as Array>::Item::lbs_write_slice_vectored(self, v)
+    }
 }
 
 impl<A> LBSRead for SmallVec<A>
@@ -28,16 +42,21 @@ where
 {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let l = crate::read::read_len(r)?;
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = crate::read::read_len_with(r, d)?;
 
         if l == 0 {
             return Ok(Self::new());
         }
 
-        let mut v = Self::with_capacity(l);
+        let mut v = Self::with_capacity(crate::read::capacity::<<A as Array>::Item>(l));
 
         for _ in 0..l {
-            v.push(<A as Array>::Item::lbs_read(r)?);
+            v.push(<A as Array>::Item::lbs_read_with(r, d)?);
         }
 
         Ok(v)
@@ -0,0 +1,255 @@
+use crate::error::LBSError;
+use crate::write::CountingWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::path::PathBuf;
+
+// File is header, then records (encoded key followed by encoded value),
+// then index (u64 offset of every record).
+
+const MAGIC: [u8; 4] = *b"LBSS";
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Snapshot header.
+///
+/// Fingerprints are arbitrary numbers chosen by the application for its key and value schemas.
+/// Reader refuses snapshots with different fingerprints, so a changed schema is detected on open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub key_fingerprint: u64,
+    pub value_fingerprint: u64,
+    pub count: u64,
+    index_offset: u64,
+}
+
+impl Header {
+    const LEN: u64 = 4 + 2 + 8 * 4;
+
+    fn write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        w.write_all(&MAGIC)?;
+        self.version.lbs_write(w)?;
+        self.key_fingerprint.lbs_write(w)?;
+        self.value_fingerprint.lbs_write(w)?;
+        self.count.lbs_write(w)?;
+        self.index_offset.lbs_write(w)
+    }
+
+    fn read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(LBSError::Parsing("not a snapshot file".to_string()));
+        }
+
+        let header = Self {
+            version: u16::lbs_read(r)?,
+            key_fingerprint: u64::lbs_read(r)?,
+            value_fingerprint: u64::lbs_read(r)?,
+            count: u64::lbs_read(r)?,
+            index_offset: u64::lbs_read(r)?,
+        };
+
+        if header.version != FORMAT_VERSION {
+            return Err(LBSError::Parsing(format!(
+                "unsupported snapshot version {}",
+                header.version
+            )));
+        }
+
+        if header.index_offset == 0 {
+            return Err(LBSError::Parsing("incomplete snapshot".to_string()));
+        }
+
+        Ok(header)
+    }
+}
+
+/// Writes snapshot into a temporary file, which replaces target file on `finish()`.
+#[derive(Debug)]
+pub struct Writer<K, V> {
+    file: BufWriter<File>,
+    path: PathBuf,
+    tmp_path: PathBuf,
+    header: Header,
+    offset: u64,
+    index: Vec<u64>,
+    types: PhantomData<(K, V)>,
+}
+
+impl<K: LBSWrite, V: LBSWrite> Writer<K, V> {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        key_fingerprint: u64,
+        value_fingerprint: u64,
+    ) -> Result<Self, LBSError> {
+        let path = path.as_ref().to_path_buf();
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let header = Header {
+            version: FORMAT_VERSION,
+            key_fingerprint,
+            value_fingerprint,
+            count: 0,
+            index_offset: 0,
+        };
+
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        header.write(&mut file)?;
+
+        Ok(Self {
+            file,
+            path,
+            tmp_path,
+            header,
+            offset: Header::LEN,
+            index: Vec::new(),
+            types: PhantomData,
+        })
+    }
+
+    pub fn append(&mut self, key: &K, value: &V) -> Result<(), LBSError> {
+        let mut w = CountingWriter::new(&mut self.file);
+
+        key.lbs_write(&mut w)?;
+        value.lbs_write(&mut w)?;
+
+        self.index.push(self.offset);
+        self.offset += w.count() as u64;
+        Ok(())
+    }
+
+    /// Writes index, completes header and atomically replaces target file.
+    pub fn finish(mut self) -> Result<(), LBSError> {
+        for offset in &self.index {
+            offset.lbs_write(&mut self.file)?;
+        }
+
+        self.header.count = self.index.len() as u64;
+        self.header.index_offset = self.offset;
+
+        let mut file = self.file.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        self.header.write(&mut file)?;
+        file.sync_all()?;
+
+        Ok(std::fs::rename(&self.tmp_path, &self.path)?)
+    }
+}
+
+/// Reads snapshot records sequentially or by position.
+#[derive(Debug)]
+pub struct Reader<K, V> {
+    file: BufReader<File>,
+    header: Header,
+    index: Vec<u64>,
+    types: PhantomData<(K, V)>,
+}
+
+impl<K: LBSRead, V: LBSRead> Reader<K, V> {
+    /// Opens snapshot, failing with `LBSError::SchemaMismatch` if fingerprints differ.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        key_fingerprint: u64,
+        value_fingerprint: u64,
+    ) -> Result<Self, LBSError> {
+        let mut file = BufReader::new(File::open(path)?);
+        let header = Header::read(&mut file)?;
+
+        if header.key_fingerprint != key_fingerprint
+            || header.value_fingerprint != value_fingerprint
+        {
+            return Err(LBSError::SchemaMismatch);
+        }
+
+        file.seek(SeekFrom::Start(header.index_offset))?;
+        let mut index = Vec::with_capacity(header.count.min(1 << 20) as usize);
+
+        for _ in 0..header.count {
+            index.push(u64::lbs_read(&mut file)?);
+        }
+
+        Ok(Self {
+            file,
+            header,
+            index,
+            types: PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Reads `i`-th record.
+    pub fn read_at(&mut self, i: usize) -> Result<(K, V), LBSError> {
+        let offset = *self.index.get(i).ok_or_else(|| {
+            LBSError::Parsing(format!("record {} is out of {}", i, self.index.len()))
+        })?;
+
+        self.file.seek(SeekFrom::Start(offset))?;
+        Ok((K::lbs_read(&mut self.file)?, V::lbs_read(&mut self.file)?))
+    }
+
+    /// Iterates over all records in order.
+    pub fn iter(&mut self) -> Result<Iter<'_, K, V>, LBSError> {
+        if let Some(offset) = self.index.first() {
+            self.file.seek(SeekFrom::Start(*offset))?;
+        }
+
+        Ok(Iter {
+            file: &mut self.file,
+            remaining: self.index.len(),
+            types: PhantomData,
+        })
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    file: &'a mut BufReader<File>,
+    remaining: usize,
+    types: PhantomData<(K, V)>,
+}
+
+impl<'a, K: LBSRead, V: LBSRead> Iterator for Iter<'a, K, V> {
+    type Item = Result<(K, V), LBSError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        let record = K::lbs_read(self.file).and_then(|k| Ok((k, V::lbs_read(self.file)?)));
+
+        if record.is_err() {
+            self.remaining = 0;
+        }
+
+        Some(record)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_bytes;
+use crate::read::read_len_with;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::io::Read;
+use std::io::Write;
+
+/// Collections of optionals, which can be encoded with a presence bitmap.
+///
+/// Encoded form is length, then bitmap with bit `i` set if `i`-th element is present,
+/// then elements without option tags. Map keys are always written, only values are optional.
+/// Bitmap takes `(length + 7) / 8` bytes, so it's smaller than the regular one byte tag per
+/// element, and a regular reader would misread it as the first tags.
+pub trait LBSSparseWrite {
+    fn lbs_sparse_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSSparseWrite`.
+pub trait LBSSparseRead: Sized {
+    fn lbs_sparse_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+impl<T: LBSWrite> LBSSparseWrite for Vec<Option<T>> {
+    #[inline]
+    fn lbs_sparse_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_bitmap(w, self.iter())?;
+
+        for element in self.iter().flatten() {
+            element.lbs_write_with(w, e)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: LBSRead> LBSSparseRead for Vec<Option<T>> {
+    #[inline]
+    fn lbs_sparse_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let bitmap = read_bitmap(r, l)?;
+        let mut v = Self::with_capacity(capacity::<Option<T>>(l));
+
+        for i in 0..l {
+            v.push(read_present(r, d, &bitmap, i)?);
+        }
+
+        Ok(v)
+    }
+}
+
+impl<K: LBSWrite, V: LBSWrite, S> LBSSparseWrite for HashMap<K, Option<V>, S> {
+    #[inline]
+    fn lbs_sparse_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_bitmap(w, self.values())?;
+
+        for (k, v) in self {
+            k.lbs_write_with(w, e)?;
+            if let Some(v) = v {
+                v.lbs_write_with(w, e)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, S> LBSSparseRead for HashMap<K, Option<V>, S>
+where
+    K: LBSRead + Eq + Hash,
+    V: LBSRead,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn lbs_sparse_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let bitmap = read_bitmap(r, l)?;
+        let mut hm = Self::with_capacity_and_hasher(capacity::<(K, Option<V>)>(l), S::default());
+
+        for i in 0..l {
+            let k = K::lbs_read_with(r, d)?;
+            let v = read_present(r, d, &bitmap, i)?;
+            hm.insert(k, v);
+        }
+
+        Ok(hm)
+    }
+}
+
+impl<K: LBSWrite, V: LBSWrite> LBSSparseWrite for BTreeMap<K, Option<V>> {
+    #[inline]
+    fn lbs_sparse_write<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        write_bitmap(w, self.values())?;
+
+        for (k, v) in self {
+            k.lbs_write_with(w, e)?;
+            if let Some(v) = v {
+                v.lbs_write_with(w, e)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K: LBSRead + Ord, V: LBSRead> LBSSparseRead for BTreeMap<K, Option<V>> {
+    #[inline]
+    fn lbs_sparse_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let bitmap = read_bitmap(r, l)?;
+        let mut bm = Self::new();
+
+        for i in 0..l {
+            let k = K::lbs_read_with(r, d)?;
+            let v = read_present(r, d, &bitmap, i)?;
+            bm.insert(k, v);
+        }
+
+        Ok(bm)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSSparseWrite, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_sparse_write(w, e)
+}
+
+#[inline]
+pub fn read<T: LBSSparseRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_sparse_read(r, d)
+}
+
+#[inline]
+fn write_bitmap<'a, T: 'a, W: Write>(
+    w: &mut W,
+    values: impl Iterator<Item = &'a Option<T>>,
+) -> Result<(), LBSError> {
+    let mut byte = 0u8;
+    let mut bits = 0;
+
+    for v in values {
+        byte |= (v.is_some() as u8) << bits;
+        bits += 1;
+
+        if bits == 8 {
+            w.write_all(&[byte])?;
+            byte = 0;
+            bits = 0;
+        }
+    }
+
+    if bits > 0 {
+        w.write_all(&[byte])?;
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn read_bitmap<R: Read>(r: &mut R, l: usize) -> Result<Vec<u8>, LBSError> {
+    read_bytes(r, l.div_ceil(8))
+}
+
+#[inline]
+fn read_present<T: LBSRead, R: Read>(
+    r: &mut R,
+    d: &mut Decoder,
+    bitmap: &[u8],
+    i: usize,
+) -> Result<Option<T>, LBSError> {
+    match bitmap[i / 8] & (1 << (i % 8)) {
+        0 => Ok(None),
+        _ => Ok(Some(T::lbs_read_with(r, d)?)),
+    }
+}
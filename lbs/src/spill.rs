@@ -0,0 +1,215 @@
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSWrite;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// Threshold of `to_spill()`.
+pub const DEFAULT_SPILL_THRESHOLD: usize = 16 * 1024 * 1024;
+
+/// Sink, which keeps written bytes in memory until they exceed `threshold`,
+/// then moves them into a temporary file and writes the rest there.
+///
+/// Written bytes are handed out by `finish()` as a `Spilled` reader, which may be streamed
+/// to the network. Temporary file is removed when the reader or the sink is dropped.
+#[derive(Debug)]
+pub struct SpillWriter {
+    threshold: usize,
+    dir: PathBuf,
+    memory: Vec<u8>,
+    file: Option<(BufWriter<File>, TempPath)>,
+    len: u64,
+}
+
+/// Bytes written to a `SpillWriter`, either in memory or in a temporary file.
+#[derive(Debug)]
+pub struct Spilled {
+    inner: Inner,
+    len: u64,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Memory(Cursor<Vec<u8>>),
+    File(File, TempPath),
+}
+
+/// Path of a temporary file, which is removed on drop.
+#[derive(Debug)]
+struct TempPath(PathBuf);
+
+impl SpillWriter {
+    /// Spills into `std::env::temp_dir()`.
+    #[inline]
+    pub fn new(threshold: usize) -> Self {
+        Self::in_dir(threshold, std::env::temp_dir())
+    }
+
+    /// Spills into given directory.
+    #[inline]
+    pub fn in_dir<P: AsRef<Path>>(threshold: usize, dir: P) -> Self {
+        Self {
+            threshold,
+            dir: dir.as_ref().to_path_buf(),
+            memory: Vec::new(),
+            file: None,
+            len: 0,
+        }
+    }
+
+    /// Number of bytes written so far.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether written bytes have been moved into a temporary file.
+    #[inline]
+    pub fn is_spilled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Flushes temporary file, if any, and rewinds written bytes for reading.
+    pub fn finish(self) -> Result<Spilled, LBSError> {
+        let len = self.len;
+
+        let inner = match self.file {
+            Some((writer, path)) => {
+                let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+                file.seek(SeekFrom::Start(0))?;
+                Inner::File(file, path)
+            }
+            None => Inner::Memory(Cursor::new(self.memory)),
+        };
+
+        Ok(Spilled { inner, len })
+    }
+
+    fn spill(&mut self) -> std::io::Result<()> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        let name = format!(
+            "lbs-spill-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let path = TempPath(self.dir.join(name));
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path.0)?;
+
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&self.memory)?;
+        self.memory = Vec::new();
+        self.file = Some((writer, path));
+        Ok(())
+    }
+}
+
+impl Write for SpillWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.file.is_none() && self.memory.len() + buf.len() > self.threshold {
+            self.spill()?;
+        }
+
+        let n = match self.file {
+            Some((ref mut writer, _)) => writer.write(buf)?,
+            None => {
+                self.memory.extend_from_slice(buf);
+                buf.len()
+            }
+        };
+
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file {
+            Some((ref mut writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Spilled {
+    /// Number of bytes.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether bytes are read from a temporary file.
+    #[inline]
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.inner, Inner::File(..))
+    }
+
+    /// Path of the temporary file, if bytes are spilled. File is removed when `self` is dropped.
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        match self.inner {
+            Inner::File(_, ref path) => Some(&path.0),
+            Inner::Memory(_) => None,
+        }
+    }
+}
+
+impl Read for Spilled {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.inner {
+            Inner::Memory(ref mut cursor) => cursor.read(buf),
+            Inner::File(ref mut file, _) => file.read(buf),
+        }
+    }
+}
+
+impl Drop for TempPath {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Encodes value into memory, or into a temporary file if it exceeds `DEFAULT_SPILL_THRESHOLD`.
+#[inline]
+pub fn to_spill<T: LBSWrite + ?Sized>(value: &T) -> Result<Spilled, LBSError> {
+    to_spill_with(value, &mut Encoder::new(), DEFAULT_SPILL_THRESHOLD)
+}
+
+/// Same as `to_spill()`, but with given threshold and reusing state of the given encoder.
+pub fn to_spill_with<T: LBSWrite + ?Sized>(
+    value: &T,
+    e: &mut Encoder,
+    threshold: usize,
+) -> Result<Spilled, LBSError> {
+    let mut w = SpillWriter::new(threshold);
+    e.encode(value, &mut w)?;
+    w.finish()
+}
@@ -0,0 +1,59 @@
+use crate::read::from_slice_exact;
+use crate::LBSRead;
+use crate::LBSWrite;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::Database;
+use sqlx::Decode;
+use sqlx::Encode;
+use sqlx::Type;
+
+pub use crate::blob::LBSBlob;
+
+/// Works with every database, which supports `Vec<u8>` and `&[u8]` columns, like `BYTEA` in Postgres
+/// or `BLOB` in SQLite. Decoding fails if column has trailing bytes.
+impl<T, DB> Type<DB> for LBSBlob<T>
+where
+    DB: Database,
+    Vec<u8>: Type<DB>,
+{
+    #[inline]
+    fn type_info() -> DB::TypeInfo {
+        <Vec<u8> as Type<DB>>::type_info()
+    }
+
+    #[inline]
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Vec<u8> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, T, DB> Encode<'q, DB> for LBSBlob<T>
+where
+    T: LBSWrite,
+    DB: Database,
+    Vec<u8>: Encode<'q, DB>,
+{
+    #[inline]
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        let mut bytes = Vec::new();
+        self.0.lbs_write(&mut bytes)?;
+        <Vec<u8> as Encode<'q, DB>>::encode(bytes, buf)
+    }
+}
+
+impl<'r, T, DB> Decode<'r, DB> for LBSBlob<T>
+where
+    T: LBSRead,
+    DB: Database,
+    &'r [u8]: Decode<'r, DB>,
+{
+    #[inline]
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let bytes = <&'r [u8] as Decode<'r, DB>>::decode(value)?;
+        Ok(Self(from_slice_exact(bytes)?))
+    }
+}
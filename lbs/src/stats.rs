@@ -0,0 +1,68 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::LBSRead;
+use std::collections::BTreeSet;
+
+/// Statistics of decoded values, see `from_slice_with_stats()` and `Decoder::collect_stats()`.
+///
+/// Unknown fields are ones, which were written by a newer producer and aren't declared by the
+/// consumer, so their appearance signals schema drift.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// Number of consumed bytes, set by `from_slice_with_stats()`.
+    pub bytes: usize,
+    /// Number of read struct fields, including unknown ones and fields of nested structs.
+    pub fields: usize,
+    /// Number of unknown struct fields.
+    pub unknown_fields: usize,
+    /// Distinct unknown fields as type names and field ids.
+    pub unknown_ids: BTreeSet<(&'static str, u16)>,
+    /// Estimated number of heap allocations: one per non-empty string, collection or box.
+    pub allocations: usize,
+}
+
+impl DecodeStats {
+    #[inline]
+    pub fn has_unknown_fields(&self) -> bool {
+        self.unknown_fields > 0
+    }
+}
+
+/// Decodes a value and collects its statistics.
+#[inline]
+pub fn from_slice_with_stats<T: LBSRead>(bytes: &[u8]) -> Result<(T, DecodeStats), LBSError> {
+    let mut d = Decoder::new();
+    d.collect_stats();
+
+    let mut r = bytes;
+    let value = T::lbs_read_with(&mut r, &mut d)?;
+
+    let mut stats = d.take_stats().unwrap_or_default();
+    stats.bytes = bytes.len() - r.len();
+    Ok((value, stats))
+}
+
+/// Counts a read struct field. Used by derived implementations.
+#[inline]
+pub fn field(d: &mut Decoder) {
+    if let Some(stats) = d.stats_mut() {
+        stats.fields += 1;
+    }
+}
+
+/// Counts an unknown struct field. Used by derived implementations.
+#[inline]
+pub fn unknown_field(d: &mut Decoder, type_name: &'static str, id: u16) {
+    if let Some(stats) = d.stats_mut() {
+        stats.unknown_fields += 1;
+        stats.unknown_ids.insert((type_name, id));
+    }
+}
+
+/// Counts an allocation of a decoded value.
+#[inline]
+pub(crate) fn allocation(d: &mut Decoder) {
+    if let Some(stats) = d.stats_mut() {
+        stats.allocations += 1;
+    }
+}
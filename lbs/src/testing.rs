@@ -0,0 +1,607 @@
+use crate::dynamic::Value;
+use crate::error::LBSError;
+use crate::read::from_slice_exact;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::schema::StructSchema;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Unstructured;
+#[cfg(feature = "proptest")]
+use proptest::strategy::Strategy;
+#[cfg(feature = "proptest")]
+use proptest::test_runner::TestCaseError;
+#[cfg(feature = "proptest")]
+use proptest::test_runner::TestRunner;
+
+/// Body of a fuzz target for type `T`, which panics if any check fails.
+///
+/// Input bytes are decoded as `T`, which must not panic, and any successfully decoded value
+/// must survive a round-trip. Then the same bytes are used to generate an arbitrary `T`,
+/// which must survive a round-trip too. Values with NaN floats never compare equal,
+/// so such types need `PartialEq`, which treats NaNs as equal.
+///
+/// ```ignore
+/// fuzz_target!(|bytes: &[u8]| lbs::testing::roundtrip_fuzz::<MyType>(bytes));
+/// ```
+#[cfg(feature = "arbitrary")]
+pub fn roundtrip_fuzz<T>(bytes: &[u8])
+where
+    T: LBSRead + LBSWrite + PartialEq + Debug + for<'a> Arbitrary<'a>,
+{
+    if let Ok(value) = T::lbs_read(&mut &bytes[..]) {
+        assert_roundtrip(&value);
+    }
+
+    if let Ok(value) = T::arbitrary_take_rest(Unstructured::new(bytes)) {
+        assert_roundtrip(&value);
+    }
+}
+
+/// Panics unless value is decoded back equal after encoding, with no bytes left.
+///
+/// ```ignore
+/// lbs::assert_roundtrip!(MyType::default());
+/// ```
+#[macro_export]
+macro_rules! assert_roundtrip {
+    ($value:expr) => {
+        $crate::testing::assert_roundtrip(&$value)
+    };
+}
+
+/// Panics unless data written by the first struct type is decodable by the second one.
+///
+/// Field schemas of both types are compared, failure lists field ids by category, see `Compatibility`.
+/// With values, each one is also encoded as the first type and decoded as the second one.
+///
+/// ```ignore
+/// lbs::assert_compatible!(UserV1, UserV2);
+/// lbs::assert_compatible!(UserV2, UserV1, UserV2::default());
+/// ```
+#[macro_export]
+macro_rules! assert_compatible {
+    ($writer:ty, $reader:ty $(, $value:expr)* $(,)?) => {{
+        $crate::testing::assert_compatible::<$writer, $reader>();
+        $($crate::testing::assert_decodable_as::<$writer, $reader>(&$value);)*
+    }};
+}
+
+/// Differences between fields of struct, which writes data, and struct, which reads it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Compatibility {
+    /// Fields unknown to the reader. Values can't be skipped without knowing their types,
+    /// so readers fail on such fields, unless they are absent (`None`).
+    pub dropped: Vec<u16>,
+    /// Optional fields unknown to the writer, which are left default.
+    pub defaulted: Vec<u16>,
+    /// Required fields unknown to the writer, which make reading fail.
+    pub missing: Vec<u16>,
+    /// Fields, which types or codecs differ, or which became required.
+    pub changed: Vec<u16>,
+    /// Container attributes, like `compact` or `indexed`, differ.
+    pub container_changed: bool,
+}
+
+impl Compatibility {
+    /// Compares fields of writer and reader by ids.
+    pub fn new(writer: &StructSchema, reader: &StructSchema) -> Self {
+        let mut result = Self {
+            container_changed: writer.compact != reader.compact
+                || writer.pack_bools != reader.pack_bools
+                || writer.indexed != reader.indexed,
+            ..Self::default()
+        };
+
+        for w in &writer.fields {
+            match reader.fields.iter().find(|r| r.id == w.id) {
+                None => result.dropped.push(w.id),
+                // Optional field of the writer may be absent, while the reader requires it
+                Some(r)
+                    if r.codec != w.codec
+                        || (r.required && !w.required)
+                        || !compatible(&w.schema, &r.schema) =>
+                {
+                    result.changed.push(w.id)
+                }
+                Some(_) => {}
+            }
+        }
+
+        for r in &reader.fields {
+            if !writer.fields.iter().any(|w| w.id == r.id) {
+                match r.required {
+                    true => result.missing.push(r.id),
+                    false => result.defaulted.push(r.id),
+                }
+            }
+        }
+
+        result
+    }
+
+    /// True if every value written by the writer is decodable by the reader.
+    #[inline]
+    pub fn is_compatible(&self) -> bool {
+        self.dropped.is_empty()
+            && self.missing.is_empty()
+            && self.changed.is_empty()
+            && !self.container_changed
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dropped {:?}, defaulted {:?}, missing {:?}, changed {:?}",
+            self.dropped, self.defaulted, self.missing, self.changed
+        )?;
+
+        if self.container_changed {
+            write!(f, ", container changed")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Used by `assert_compatible!()`.
+pub fn assert_compatible<W: LBSSchema, R: LBSSchema>() {
+    let (writer, reader) = match (W::lbs_schema(), R::lbs_schema()) {
+        (Schema::Struct(writer), Schema::Struct(reader)) => (writer, reader),
+        _ => panic!("only structs can be checked for compatibility"),
+    };
+
+    let compatibility = Compatibility::new(&writer, &reader);
+
+    if !compatibility.is_compatible() {
+        panic!(
+            "{} is not decodable as {}: {}",
+            writer.name, reader.name, compatibility
+        );
+    }
+}
+
+/// Used by `assert_compatible!()`.
+pub fn assert_decodable_as<W: LBSWrite + Debug, R: LBSRead>(value: &W) {
+    if let Err(e) = decodable_as::<W, R>(value) {
+        panic!("{}", e);
+    }
+}
+
+/// Writes encoded value into golden file, creating parent directories.
+/// Golden files are usually versioned, like `fixtures/user_v3.bin`, and kept forever.
+pub fn write_golden<T: LBSWrite + ?Sized, P: AsRef<Path>>(
+    path: P,
+    value: &T,
+) -> Result<(), LBSError> {
+    let path = path.as_ref();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf)?;
+    Ok(std::fs::write(path, buf)?)
+}
+
+/// Panics unless golden file written by `write_golden()` is decoded equal to `expected`.
+/// Mismatch is reported as a diff of debug representations, line per field.
+pub fn check_golden<T, P>(path: P, expected: &T)
+where
+    T: LBSRead + PartialEq + Debug,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => panic!(
+            "golden file {} can't be read: {}, write it with write_golden()",
+            path.display(),
+            e
+        ),
+    };
+
+    let decoded = match from_slice_exact::<T>(&bytes) {
+        Ok(decoded) => decoded,
+        Err(e) => panic!("golden file {} failed to decode: {}", path.display(), e),
+    };
+
+    if decoded != *expected {
+        panic!(
+            "golden file {} differs (-golden +expected):\n{}",
+            path.display(),
+            debug_diff(&decoded, expected)
+        );
+    }
+}
+
+/// Panics unless `value` is decoded back equal after encoding, with no bytes left.
+pub fn assert_roundtrip<T>(value: &T)
+where
+    T: LBSRead + LBSWrite + PartialEq + Debug,
+{
+    if let Err(e) = roundtrip(value) {
+        panic!("{}", e);
+    }
+}
+
+/// Runs property test, which checks that every value generated by `strategy` survives a round-trip.
+/// Failing value is shrunk and reported by panic. Number of cases is taken from `PROPTEST_CASES`.
+#[cfg(feature = "proptest")]
+pub fn check_roundtrip<S>(strategy: S)
+where
+    S: Strategy,
+    S::Value: LBSRead + LBSWrite + PartialEq,
+{
+    run(&strategy, |value| roundtrip(&value))
+}
+
+/// Runs property test, which checks that every value generated by `strategy`, encoded as `T`,
+/// is decoded as `U` with no bytes left.
+///
+/// Schema evolution is checked by calling it in both directions for two versions of a type:
+/// `check_compatible::<_, V1>(v2_strategy)` for old readers and `check_compatible::<_, V2>(v1_strategy)`
+/// for new ones.
+#[cfg(feature = "proptest")]
+pub fn check_compatible<S, U>(strategy: S)
+where
+    S: Strategy,
+    S::Value: LBSWrite,
+    U: LBSRead,
+{
+    run(&strategy, |value| decodable_as::<_, U>(&value))
+}
+
+/// Generates random valid payload described by `schema`, like one fetched from a registry, for
+/// load testing decoders and services without their Rust types. Same seed gives the same payload.
+///
+/// Optional fields are present at random, collections are short and nesting is limited, so
+/// recursive types stay small. Opaque types and required fields with codecs can't be generated.
+pub fn gen_payload(schema: &Schema, seed: u64) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    gen_value(schema, seed)?.write(&mut buf, schema)?;
+    Ok(buf)
+}
+
+/// Generates random value described by `schema`, see `gen_payload()`.
+pub fn gen_value(schema: &Schema, seed: u64) -> Result<Value, LBSError> {
+    Generator {
+        state: seed,
+        named: HashMap::new(),
+    }
+    .value(schema, 0)
+}
+
+#[cfg(feature = "proptest")]
+fn run<S, F>(strategy: &S, check: F)
+where
+    S: Strategy,
+    F: Fn(S::Value) -> Result<(), String>,
+{
+    let result =
+        TestRunner::default().run(strategy, |value| check(value).map_err(TestCaseError::fail));
+
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
+
+fn roundtrip<T>(value: &T) -> Result<(), String>
+where
+    T: LBSRead + LBSWrite + PartialEq + Debug,
+{
+    let mut buf = Vec::new();
+    value
+        .lbs_write(&mut buf)
+        .map_err(|e| format!("{:?} failed to encode: {}", value, e))?;
+
+    match from_slice_exact::<T>(&buf) {
+        Ok(decoded) if decoded == *value => Ok(()),
+        Ok(decoded) => Err(format!("{:?} was decoded as {:?}", value, decoded)),
+        Err(e) => Err(format!("{:?} failed to decode: {}", value, e)),
+    }
+}
+
+fn decodable_as<W: LBSWrite + Debug, R: LBSRead>(value: &W) -> Result<(), String> {
+    let mut buf = Vec::new();
+    value
+        .lbs_write(&mut buf)
+        .map_err(|e| format!("{:?} failed to encode: {}", value, e))?;
+
+    match from_slice_exact::<R>(&buf) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!(
+            "{:?} failed to decode as {}: {}",
+            value,
+            std::any::type_name::<R>(),
+            e
+        )),
+    }
+}
+
+/// Whether values of writer schema are decodable as reader schema. Type names are ignored.
+fn compatible(writer: &Schema, reader: &Schema) -> bool {
+    match (writer, reader) {
+        // Optional fields are encoded as their values, when present
+        (Schema::Option(w), r) => compatible(w, r),
+        (w, Schema::Option(r)) => compatible(w, r),
+        (Schema::List(w), Schema::List(r)) | (Schema::Range(w), Schema::Range(r)) => {
+            compatible(w, r)
+        }
+        (Schema::Map(w), Schema::Map(r)) => compatible(&w.0, &r.0) && compatible(&w.1, &r.1),
+        (Schema::Tuple(w), Schema::Tuple(r)) => {
+            w.len() == r.len() && w.iter().zip(r).all(|(w, r)| compatible(w, r))
+        }
+        (Schema::Struct(w), Schema::Struct(r)) => Compatibility::new(w, r).is_compatible(),
+        (Schema::Enum(w), Schema::Enum(r)) => {
+            w.compact == r.compact
+                && w.variants.iter().all(|w| {
+                    r.variants.iter().any(|r| {
+                        r.id == w.id
+                            && match (&w.schema, &r.schema) {
+                                (None, None) => true,
+                                (Some(w), Some(r)) => compatible(w, r),
+                                _ => false,
+                            }
+                    })
+                })
+        }
+        // Recursive types are compared at the first level only
+        (Schema::Ref(_), _) | (_, Schema::Ref(_)) => true,
+        (w, r) => w == r,
+    }
+}
+
+/// Line diff of pretty debug representations, only changed lines are shown.
+fn debug_diff<T: Debug>(old: &T, new: &T) -> String {
+    let old = format!("{:#?}", old);
+    let new = format!("{:#?}", new);
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence lengths of suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = String::new();
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+/// Nesting, after which collections are empty, options are absent and unit variants are preferred.
+const GEN_SHALLOW_DEPTH: usize = 6;
+
+/// Nesting, after which generation fails, as schema can't be satisfied by a finite value.
+const GEN_MAX_DEPTH: usize = 64;
+
+/// Deterministic generator of random values, based on SplitMix64.
+struct Generator {
+    state: u64,
+    /// Structs and enums by name, which schema refers to.
+    named: HashMap<String, Schema>,
+}
+
+impl Generator {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Random number below `n`, which must be positive.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    /// Random unsigned integer of the given width, which is often an edge case.
+    fn uint(&mut self, bits: u32) -> u64 {
+        let max = u64::MAX >> (64 - bits);
+        match self.below(8) {
+            0 => 0,
+            1 => max,
+            _ => self.next() & max,
+        }
+    }
+
+    fn int(&mut self, bits: u32) -> i64 {
+        let shift = 64 - bits;
+        match self.below(8) {
+            0 => 0,
+            1 => i64::MIN >> shift,
+            2 => i64::MAX >> shift,
+            _ => ((self.next() as i64) << shift) >> shift,
+        }
+    }
+
+    /// Length of a collection or a string.
+    fn len(&mut self, depth: usize) -> usize {
+        match depth < GEN_SHALLOW_DEPTH {
+            true => self.below(5) as usize,
+            false => 0,
+        }
+    }
+
+    fn string(&mut self, depth: usize) -> String {
+        const CHARS: &[char] = &['a', 'z', 'A', '0', '9', ' ', '-', '_', 'é', 'ж', '€', '😀'];
+        (0..self.len(depth) * 3)
+            .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+            .collect()
+    }
+
+    fn value(&mut self, schema: &Schema, depth: usize) -> Result<Value, LBSError> {
+        if depth > GEN_MAX_DEPTH {
+            return Err(LBSError::Parsing(
+                "schema is too deep to generate values".to_string(),
+            ));
+        }
+
+        Ok(match schema {
+            Schema::Unit => Value::Unit,
+            Schema::Bool => Value::Bool(self.chance()),
+            Schema::U8 => Value::U64(self.uint(8)),
+            Schema::U16 => Value::U64(self.uint(16)),
+            Schema::U32 => Value::U64(self.uint(32)),
+            Schema::U64 => Value::U64(self.uint(64)),
+            Schema::U128 => Value::U128(((self.uint(64) as u128) << 64) | self.uint(64) as u128),
+            Schema::I8 => Value::I64(self.int(8)),
+            Schema::I16 => Value::I64(self.int(16)),
+            Schema::I32 => Value::I64(self.int(32)),
+            Schema::I64 => Value::I64(self.int(64)),
+            Schema::I128 => Value::I128(((self.int(64) as i128) << 64) | self.uint(64) as i128),
+            // Finite values, which survive narrowing to f32
+            Schema::F32 | Schema::F64 => Value::F64(self.int(24) as f64 / 1024.0),
+            Schema::Char => {
+                Value::Char(char::from_u32(self.below(0xd800) as u32).unwrap_or_default())
+            }
+            Schema::String => Value::String(self.string(depth)),
+            Schema::Duration => Value::Duration(Duration::new(
+                self.below(1 << 40),
+                self.below(1_000_000_000) as u32,
+            )),
+            Schema::SystemTime => Value::SystemTime(
+                SystemTime::UNIX_EPOCH
+                    + Duration::new(self.below(1 << 34), self.below(1000) as u32),
+            ),
+            Schema::Ipv4Addr => Value::IpAddr(IpAddr::from((self.next() as u32).to_be_bytes())),
+            Schema::Ipv6Addr => Value::IpAddr(IpAddr::from(
+                (((self.next() as u128) << 64) | self.next() as u128).to_be_bytes(),
+            )),
+            Schema::IpAddr => match self.chance() {
+                true => self.value(&Schema::Ipv4Addr, depth)?,
+                false => self.value(&Schema::Ipv6Addr, depth)?,
+            },
+            Schema::Option(inner) => match depth < GEN_SHALLOW_DEPTH && self.chance() {
+                true => Value::Option(Some(Box::new(self.value(inner, depth + 1)?))),
+                false => Value::Option(None),
+            },
+            Schema::List(inner) => Value::List(
+                (0..self.len(depth))
+                    .map(|_| self.value(inner, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Schema::Map(kv) => Value::Map(
+                (0..self.len(depth))
+                    .map(|_| Ok((self.value(&kv.0, depth + 1)?, self.value(&kv.1, depth + 1)?)))
+                    .collect::<Result<_, LBSError>>()?,
+            ),
+            Schema::Tuple(schemas) => Value::List(
+                schemas
+                    .iter()
+                    .map(|s| self.value(s, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Schema::Range(inner) => Value::List(vec![
+                self.value(inner, depth + 1)?,
+                self.value(inner, depth + 1)?,
+            ]),
+            Schema::Struct(s) => {
+                self.named.insert(s.name.clone(), schema.clone());
+                self.struct_value(s, depth)?
+            }
+            Schema::Enum(e) => {
+                self.named.insert(e.name.clone(), schema.clone());
+
+                // Deep values end with unit variants, if there are any
+                let units: Vec<_> = e.variants.iter().filter(|v| v.schema.is_none()).collect();
+                let variant = match (depth < GEN_SHALLOW_DEPTH, units.is_empty()) {
+                    (false, false) => units[self.below(units.len() as u64) as usize],
+                    _ if e.variants.is_empty() => {
+                        return Err(LBSError::Parsing(format!(
+                            "enum {} has no variants",
+                            e.name
+                        )))
+                    }
+                    _ => &e.variants[self.below(e.variants.len() as u64) as usize],
+                };
+
+                let payload = match variant.schema {
+                    Some(ref schema) => Some(Box::new(self.value(schema, depth + 1)?)),
+                    None => None,
+                };
+                Value::Enum(variant.id, payload)
+            }
+            Schema::Ref(name) => {
+                let schema = self
+                    .named
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| LBSError::Parsing(format!("unknown type {}", name)))?;
+                self.value(&schema, depth)?
+            }
+            Schema::Opaque(name) => {
+                return Err(LBSError::Parsing(format!(
+                    "opaque {} can't be generated",
+                    name
+                )))
+            }
+        })
+    }
+
+    fn struct_value(&mut self, s: &StructSchema, depth: usize) -> Result<Value, LBSError> {
+        let mut fields = BTreeMap::new();
+
+        for field in &s.fields {
+            if let Some(ref codec) = field.codec {
+                match field.required {
+                    true => {
+                        return Err(LBSError::Parsing(format!(
+                            "field {} of {} is encoded by {}",
+                            field.name, s.name, codec
+                        )))
+                    }
+                    false => continue,
+                }
+            }
+
+            if field.required || self.chance() {
+                fields.insert(field.id, self.value(&field.schema, depth + 1)?);
+            }
+        }
+
+        Ok(Value::Struct(fields))
+    }
+}
@@ -1,9 +1,13 @@
 use super::LBSRead;
 use super::LBSWrite;
+use crate::calendar::OrdinalDate;
 use crate::error::LBSError;
 use std::io::Read;
 use std::io::Write;
+use time::Date;
+use time::Month;
 use time::OffsetDateTime;
+use time::Weekday;
 
 impl LBSWrite for OffsetDateTime {
     #[inline]
@@ -22,3 +26,54 @@ impl LBSRead for OffsetDateTime {
             .map_err(|e| LBSError::Parsing(e.to_string()))
     }
 }
+
+/// Written as `i32` year followed by `u16` day of the year, like `calendar::OrdinalDate`.
+impl LBSWrite for Date {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.year().lbs_write(w)?;
+        self.ordinal().lbs_write(w)
+    }
+}
+
+impl LBSRead for Date {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        let date = OrdinalDate::lbs_read(r)?;
+        Date::from_ordinal_date(date.year(), date.ordinal())
+            .map_err(|_| LBSError::OutOfRange("Date"))
+    }
+}
+
+/// Written as `u8` number of the month, starting with 1 for January.
+impl LBSWrite for Month {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        u8::from(*self).lbs_write(w)
+    }
+}
+
+impl LBSRead for Month {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Month::try_from(u8::lbs_read(r)?).map_err(|_| LBSError::OutOfRange("Month"))
+    }
+}
+
+/// Written as `u8` number of days since Monday, like `chrono::Weekday`.
+impl LBSWrite for Weekday {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.number_days_from_monday().lbs_write(w)
+    }
+}
+
+impl LBSRead for Weekday {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        match u8::lbs_read(r)? {
+            days @ 0..=6 => Ok(Weekday::Monday.nth_next(days)),
+            _ => Err(LBSError::OutOfRange("Weekday")),
+        }
+    }
+}
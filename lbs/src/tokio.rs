@@ -0,0 +1,131 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::frame::DEFAULT_MAX_FRAME_LEN;
+use crate::LBSRead;
+use tokio::io::AsyncReadExt;
+
+pub mod read;
+pub mod write;
+
+pub use read::LBSReadAsync;
+pub use tokio::io::AsyncRead;
+pub use tokio::io::AsyncWrite;
+pub use write::LBSWriteAsync;
+
+const LEN_PREFIX: usize = 4;
+
+/// Asynchronous reader of frames written by `lbs::frame::write_frame()`.
+///
+/// Reading is cancellation safe: bytes of an incomplete frame are kept in the reader's buffer,
+/// so if a `read_frame()` future is dropped, like in `tokio::select!` or on timeout, the next call
+/// continues from where it stopped, and the stream never gets desynchronized.
+/// Values are decoded only after the whole frame is buffered.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    max_len: usize,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    /// Accepts frames up to `DEFAULT_MAX_FRAME_LEN`.
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self::with_limit(inner, DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Fails with `LBSError::FrameTooLarge` before buffering a frame, which exceeds `max_len`.
+    #[inline]
+    pub fn with_limit(inner: R, max_len: usize) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            max_len,
+        }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading from the inner reader directly desynchronizes frames.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Bytes of an incomplete frame are lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Bytes read, but not consumed by frames yet.
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Reads the next frame. Returns None if the stream ends between frames,
+    /// and fails with `UnexpectedEof` if it ends inside of a frame.
+    #[inline]
+    pub async fn read_frame<T: LBSRead>(&mut self) -> Result<Option<T>, LBSError> {
+        self.read_frame_with(&mut Decoder::new()).await
+    }
+
+    /// Same as `read_frame()`, but reuses state of the given decoder.
+    pub async fn read_frame_with<T: LBSRead>(
+        &mut self,
+        d: &mut Decoder,
+    ) -> Result<Option<T>, LBSError> {
+        loop {
+            if let Some(len) = self.frame_len()? {
+                if self.buf.len() >= LEN_PREFIX + len {
+                    return self.decode(len, d).map(Some);
+                }
+
+                self.buf.reserve(LEN_PREFIX + len - self.buf.len());
+            }
+
+            // Unlike read_exact(), read_buf() is cancellation safe
+            if self.inner.read_buf(&mut self.buf).await? == 0 {
+                return match self.buf.is_empty() {
+                    true => Ok(None),
+                    false => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+                };
+            }
+        }
+    }
+
+    /// Length of the buffered frame, if its prefix is complete.
+    #[inline]
+    fn frame_len(&self) -> Result<Option<usize>, LBSError> {
+        let Some(prefix) = self.buf.get(..LEN_PREFIX) else {
+            return Ok(None);
+        };
+
+        let len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+
+        match len > self.max_len {
+            true => Err(LBSError::FrameTooLarge {
+                len,
+                max_len: self.max_len,
+            }),
+            false => Ok(Some(len)),
+        }
+    }
+
+    /// Decodes the buffered frame and removes it from the buffer, even if it's malformed.
+    fn decode<T: LBSRead>(&mut self, len: usize, d: &mut Decoder) -> Result<T, LBSError> {
+        let mut frame = &self.buf[LEN_PREFIX..LEN_PREFIX + len];
+
+        let result = d.decode(&mut frame).and_then(|value| match frame.len() {
+            0 => Ok(value),
+            n => Err(LBSError::TrailingBytes(n)),
+        });
+
+        self.buf.drain(..LEN_PREFIX + len);
+        result
+    }
+}
@@ -0,0 +1,843 @@
+use crate::decoder::Decoder;
+use crate::encoder::SHARED_STRING_FLAG;
+use crate::error::LBSError;
+use crate::int::I24;
+use crate::int::I48;
+use crate::int::U24;
+use crate::int::U48;
+use crate::raw::RawValue;
+use crate::read::capacity;
+use crate::read::check_array_len;
+use crate::read::compact_field_id;
+use crate::read::duration;
+use crate::read::option_tag;
+use crate::read::MAX_PREALLOCATION;
+use crate::stats;
+use crate::varint;
+use crate::write::BEFORE_EPOCH_FLAG;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::ffi::OsString;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::num::NonZeroI128;
+use std::num::NonZeroI16;
+use std::num::NonZeroI32;
+use std::num::NonZeroI64;
+use std::num::NonZeroI8;
+use std::num::NonZeroIsize;
+use std::num::NonZeroU128;
+use std::num::NonZeroU16;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
+use std::num::Saturating;
+use std::num::Wrapping;
+use std::ops::Bound;
+use std::ops::ControlFlow;
+use std::ops::Range;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::SystemTime;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+
+/// Asynchronous counterpart of `LBSRead`, which decodes values directly from `AsyncRead`,
+/// such as a socket, without buffering whole messages first.
+///
+/// Implementation can be derived with `#[derive(LBSReadAsync)]`. Values are read with many small
+/// reads, so unbuffered readers should be wrapped into `tokio::io::BufReader`. Reading isn't
+/// cancellation safe: a dropped future leaves the reader in the middle of a value.
+pub trait LBSReadAsync: Sized + Send {
+    fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> impl Future<Output = Result<Self, LBSError>> + Send;
+}
+
+macro_rules! impl_read_async_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl LBSReadAsync for $t {
+                #[inline]
+                async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+                    r: &mut R,
+                    _d: &mut Decoder,
+                ) -> Result<Self, LBSError> {
+                    let mut buf = [0; size_of::<$t>()];
+                    r.read_exact(&mut buf).await?;
+                    Ok(Self::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_read_async_primitive!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// Pointer-sized integers are always encoded with 8 bytes.
+impl LBSReadAsync for usize {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(u64::lbs_read_async(r, d).await? as usize)
+    }
+}
+
+impl LBSReadAsync for isize {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(i64::lbs_read_async(r, d).await? as isize)
+    }
+}
+
+macro_rules! impl_read_async_non_zero {
+    ($($t:ident => $p:ty),*) => {
+        $(
+            impl LBSReadAsync for $t {
+                #[inline]
+                async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+                    r: &mut R,
+                    d: &mut Decoder,
+                ) -> Result<Self, LBSError> {
+                    Self::new(<$p>::lbs_read_async(r, d).await?)
+                        .ok_or(LBSError::OutOfRange(stringify!($t)))
+                }
+            }
+        )*
+    };
+}
+
+impl_read_async_non_zero!(NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32);
+impl_read_async_non_zero!(NonZeroU64 => u64, NonZeroUsize => usize, NonZeroU128 => u128);
+impl_read_async_non_zero!(NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32);
+impl_read_async_non_zero!(NonZeroI64 => i64, NonZeroIsize => isize, NonZeroI128 => i128);
+
+macro_rules! impl_read_async_narrow_int {
+    ($($t:ident => $bytes:literal),*) => {
+        $(
+            impl LBSReadAsync for $t {
+                #[inline]
+                async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+                    r: &mut R,
+                    _d: &mut Decoder,
+                ) -> Result<Self, LBSError> {
+                    let mut buf = [0; $bytes];
+                    r.read_exact(&mut buf).await?;
+                    Ok(Self::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_read_async_narrow_int!(U24 => 3, U48 => 6, I24 => 3, I48 => 6);
+
+impl LBSReadAsync for RawValue {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::from_bytes(Vec::lbs_read_async(r, d).await?))
+    }
+}
+
+impl LBSReadAsync for () {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        _r: &mut R,
+        _d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(())
+    }
+}
+
+impl<T1: LBSReadAsync, T2: LBSReadAsync> LBSReadAsync for (T1, T2) {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok((
+            T1::lbs_read_async(r, d).await?,
+            T2::lbs_read_async(r, d).await?,
+        ))
+    }
+}
+
+impl<T1: LBSReadAsync, T2: LBSReadAsync, T3: LBSReadAsync> LBSReadAsync for (T1, T2, T3) {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok((
+            T1::lbs_read_async(r, d).await?,
+            T2::lbs_read_async(r, d).await?,
+            T3::lbs_read_async(r, d).await?,
+        ))
+    }
+}
+
+impl LBSReadAsync for bool {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let byte = u8::lbs_read_async(r, d).await?;
+        crate::read::bool_from_byte(byte, d.lenient_bools_enabled())
+    }
+}
+
+impl LBSReadAsync for char {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Self::from_u32(u32::lbs_read_async(r, d).await?).ok_or(LBSError::InvalidChar)
+    }
+}
+
+impl LBSReadAsync for String {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        if d.shares_strings() {
+            return Ok(Self::from(&*read_shared_str(r, d).await?));
+        }
+
+        let l = read_len_with(r, d).await?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        let mut buf = Vec::new();
+        read_bytes_into(r, &mut buf, l).await?;
+        d.string_from_utf8(buf)
+    }
+}
+
+macro_rules! impl_read_async_os_string {
+    ($($t:ty),*) => {
+        $(
+            impl LBSReadAsync for $t {
+                #[inline]
+                async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+                    r: &mut R,
+                    d: &mut Decoder,
+                ) -> Result<Self, LBSError> {
+                    Ok(Self::from(String::lbs_read_async(r, d).await?))
+                }
+            }
+        )*
+    };
+}
+
+impl_read_async_os_string!(OsString, PathBuf);
+
+impl LBSReadAsync for Arc<str> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        if d.shares_strings() {
+            return read_shared_str(r, d).await;
+        }
+
+        let l = read_len_with(r, d).await?;
+        read_bytes_into(r, d.scratch_mut(), l).await?;
+        d.scratch_arc_str()
+    }
+}
+
+impl LBSReadAsync for Duration {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let secs = u64::lbs_read_async(r, d).await?;
+        let nanos = u32::lbs_read_async(r, d).await?;
+        duration(secs, nanos)
+    }
+}
+
+impl LBSReadAsync for SystemTime {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let secs = u64::lbs_read_async(r, d).await?;
+        let nanos = u32::lbs_read_async(r, d).await?;
+
+        if nanos & BEFORE_EPOCH_FLAG != 0 {
+            let dur = duration(secs, nanos & !BEFORE_EPOCH_FLAG)?;
+            return Self::UNIX_EPOCH
+                .checked_sub(dur)
+                .ok_or(LBSError::InvalidTimestamp);
+        }
+
+        Self::UNIX_EPOCH
+            .checked_add(duration(secs, nanos)?)
+            .ok_or(LBSError::InvalidTimestamp)
+    }
+}
+
+impl LBSReadAsync for Ipv4Addr {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::from(u32::lbs_read_async(r, d).await?))
+    }
+}
+
+impl LBSReadAsync for Ipv6Addr {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::from(u128::lbs_read_async(r, d).await?))
+    }
+}
+
+impl LBSReadAsync for IpAddr {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(match bool::lbs_read_async(r, d).await? {
+            true => IpAddr::V4(Ipv4Addr::lbs_read_async(r, d).await?),
+            false => IpAddr::V6(Ipv6Addr::lbs_read_async(r, d).await?),
+        })
+    }
+}
+
+impl<T: LBSReadAsync + PartialOrd> LBSReadAsync for Range<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let start = T::lbs_read_async(r, d).await?;
+        let end = T::lbs_read_async(r, d).await?;
+        Ok(Range { start, end })
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for RangeInclusive<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let start = T::lbs_read_async(r, d).await?;
+        let end = T::lbs_read_async(r, d).await?;
+        Ok(RangeInclusive::new(start, end))
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for Bound<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        match read_field_id(r).await? {
+            0 => Ok(Self::Included(T::lbs_read_async(r, d).await?)),
+            1 => Ok(Self::Excluded(T::lbs_read_async(r, d).await?)),
+            2 => Ok(Self::Unbounded),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for Box<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_async(r, d).await?))
+    }
+}
+
+impl<T: LBSReadAsync + Sync> LBSReadAsync for Arc<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_async(r, d).await?))
+    }
+}
+
+/// Ordering and arithmetic adapters are read as their values.
+macro_rules! impl_read_async_newtype {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSReadAsync> LBSReadAsync for $t<T> {
+                #[inline]
+                async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+                    r: &mut R,
+                    d: &mut Decoder,
+                ) -> Result<Self, LBSError> {
+                    Ok(Self(T::lbs_read_async(r, d).await?))
+                }
+            }
+        )*
+    };
+}
+
+impl_read_async_newtype!(Reverse, Wrapping, Saturating);
+
+impl<'a, T> LBSReadAsync for Cow<'a, T>
+where
+    T: LBSReadAsync + ToOwned + Sync,
+    T::Owned: Send,
+{
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::Owned(T::lbs_read_async(r, d).await?.to_owned()))
+    }
+}
+
+impl<'a, T> LBSReadAsync for Cow<'a, [T]>
+where
+    T: LBSReadAsync + Sync,
+    [T]: ToOwned<Owned = Vec<T>>,
+{
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::Owned(Vec::lbs_read_async(r, d).await?))
+    }
+}
+
+impl<'a> LBSReadAsync for Cow<'a, str> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Self::Owned(String::lbs_read_async(r, d).await?))
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for Option<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        match option_tag(u8::lbs_read_async(r, d).await?)? {
+            true => Ok(Some(T::lbs_read_async(r, d).await?)),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<B: LBSReadAsync, C: LBSReadAsync> LBSReadAsync for ControlFlow<B, C> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        match read_field_id(r).await? {
+            0 => Ok(Self::Continue(C::lbs_read_async(r, d).await?)),
+            1 => Ok(Self::Break(B::lbs_read_async(r, d).await?)),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
+impl<T: LBSReadAsync, E: LBSReadAsync> LBSReadAsync for Result<T, E> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        match read_field_id(r).await? {
+            0 => Ok(Ok(T::lbs_read_async(r, d).await?)),
+            1 => Ok(Err(E::lbs_read_async(r, d).await?)),
+            _ => Err(LBSError::UnexpectedVariant),
+        }
+    }
+}
+
+impl LBSReadAsync for Infallible {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        _r: &mut R,
+        _d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Err(LBSError::UnexpectedVariant)
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for OnceLock<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(match Option::<T>::lbs_read_async(r, d).await? {
+            Some(value) => Self::from(value),
+            None => Self::new(),
+        })
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for Vec<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d).await?;
+
+        if l == 0 {
+            return Ok(Self::new());
+        }
+
+        stats::allocation(d);
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_read_async(r, d).await?);
+        }
+
+        Ok(v)
+    }
+}
+
+impl<T: LBSReadAsync, const N: usize> LBSReadAsync for [T; N] {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        check_array_len::<N>(read_len_with(r, d).await?)?;
+        let mut v = Vec::with_capacity(N);
+
+        for _ in 0..N {
+            v.push(T::lbs_read_async(r, d).await?);
+        }
+
+        match v.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("vector has exactly N elements"),
+        }
+    }
+}
+
+impl<T: LBSReadAsync> LBSReadAsync for VecDeque<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Vec::<T>::lbs_read_async(r, d).await?.into())
+    }
+}
+
+impl<K, V, S> LBSReadAsync for HashMap<K, V, S>
+where
+    K: LBSReadAsync + Eq + Hash,
+    V: LBSReadAsync,
+    S: BuildHasher + Default + Send,
+{
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d).await?;
+
+        if l == 0 {
+            return Ok(Self::default());
+        }
+
+        stats::allocation(d);
+        let mut hm = Self::with_capacity_and_hasher(capacity::<(K, V)>(l), S::default());
+
+        for _ in 0..l {
+            let k = K::lbs_read_async(r, d).await?;
+            let v = V::lbs_read_async(r, d).await?;
+            hm.insert(k, v);
+        }
+
+        Ok(hm)
+    }
+}
+
+impl<K, S> LBSReadAsync for HashSet<K, S>
+where
+    K: LBSReadAsync + Eq + Hash,
+    S: BuildHasher + Default + Send,
+{
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d).await?;
+
+        if l == 0 {
+            return Ok(Self::default());
+        }
+
+        stats::allocation(d);
+        let mut hs = Self::with_capacity_and_hasher(capacity::<K>(l), S::default());
+
+        for _ in 0..l {
+            hs.insert(K::lbs_read_async(r, d).await?);
+        }
+
+        Ok(hs)
+    }
+}
+
+impl<K: LBSReadAsync + Ord, V: LBSReadAsync> LBSReadAsync for BTreeMap<K, V> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d).await?;
+        let mut bm = Self::new();
+
+        if l == 0 {
+            return Ok(bm);
+        }
+
+        stats::allocation(d);
+
+        for _ in 0..l {
+            let k = K::lbs_read_async(r, d).await?;
+            let v = V::lbs_read_async(r, d).await?;
+            bm.insert(k, v);
+        }
+
+        Ok(bm)
+    }
+}
+
+impl<T: LBSReadAsync + Ord> LBSReadAsync for BinaryHeap<T> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        Ok(Vec::lbs_read_async(r, d).await?.into())
+    }
+}
+
+impl<K: LBSReadAsync + Ord> LBSReadAsync for BTreeSet<K> {
+    #[inline]
+    async fn lbs_read_async<R: AsyncRead + Unpin + Send>(
+        r: &mut R,
+        d: &mut Decoder,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d).await?;
+        let mut bs = Self::new();
+
+        if l == 0 {
+            return Ok(bs);
+        }
+
+        stats::allocation(d);
+
+        for _ in 0..l {
+            bs.insert(K::lbs_read_async(r, d).await?);
+        }
+
+        Ok(bs)
+    }
+}
+
+#[inline]
+pub async fn read<T: LBSReadAsync, R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<T, LBSError> {
+    T::lbs_read_async(r, &mut Decoder::new()).await
+}
+
+#[inline]
+pub async fn read_with<T: LBSReadAsync, R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+    d: &mut Decoder,
+) -> Result<T, LBSError> {
+    d.enter()?;
+    let result = T::lbs_read_async(r, d).await;
+    d.leave();
+    result
+}
+
+#[inline]
+pub async fn read_len<R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<usize, LBSError> {
+    Ok(r.read_u32_le().await? as usize)
+}
+
+/// Reads length, failing if it exceeds `Limits::max_len` of the decoder.
+#[inline]
+pub async fn read_len_with<R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+    d: &Decoder,
+) -> Result<usize, LBSError> {
+    d.check_len(read_len(r).await?)
+}
+
+/// Same as `lbs::read::read_bytes_into()`, but reads from `AsyncRead`.
+#[inline]
+pub async fn read_bytes_into<R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+    buf: &mut Vec<u8>,
+    l: usize,
+) -> Result<(), LBSError> {
+    buf.clear();
+
+    if l <= MAX_PREALLOCATION {
+        buf.resize(l, 0);
+        r.read_exact(buf).await?;
+        return Ok(());
+    }
+
+    if (&mut *r).take(l as u64).read_to_end(buf).await? < l {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub async fn read_field_count<R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<u16, LBSError> {
+    Ok(r.read_u16_le().await?)
+}
+
+#[inline]
+pub async fn read_field_id<R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<u16, LBSError> {
+    Ok(r.read_u16_le().await?)
+}
+
+/// Reads field count of a struct with compact header.
+#[inline]
+pub async fn read_compact_field_count<R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+) -> Result<u16, LBSError> {
+    read_varint_u16(r).await
+}
+
+/// Reads field id of a struct with compact header, encoded as a delta from the previous one.
+/// `prev` is None before the first field, which is a delta from zero.
+#[inline]
+pub async fn read_compact_field_id<R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+    prev: &mut Option<u16>,
+) -> Result<u16, LBSError> {
+    let id = compact_field_id(*prev, read_varint_u16(r).await?)?;
+    *prev = Some(id);
+    Ok(id)
+}
+
+/// Reads varint, like variant id of an enum with compact header.
+#[inline]
+pub async fn read_varint_u16<R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<u16, LBSError> {
+    // Bytes are gathered up to the last one and decoded like synchronously read ones
+    let mut buf = [0; varint::MAX_LEN];
+
+    for i in 0..buf.len() {
+        buf[i] = r.read_u8().await?;
+
+        if buf[i] & 0x80 == 0 {
+            return varint::read_u16(&mut &buf[..=i]);
+        }
+    }
+
+    Err(LBSError::InvalidVarint)
+}
+
+/// Reads bools of a struct with packed bools, calling `f` with id and value of every bool.
+#[inline]
+pub async fn read_packed_bools<R: AsyncRead + Unpin + Send, F: FnMut(u16, bool) + Send>(
+    r: &mut R,
+    mut f: F,
+) -> Result<(), LBSError> {
+    let base = r.read_u16_le().await?;
+    let count = r.read_u16_le().await?;
+    let mut byte = 0u8;
+
+    for i in 0..count {
+        if i % 8 == 0 {
+            byte = r.read_u8().await?;
+        }
+
+        let id = base
+            .checked_add(i)
+            .ok_or_else(|| LBSError::Parsing("packed bool id overflows u16".to_string()))?;
+        f(id, byte & (1 << (i % 8)) != 0);
+    }
+
+    Ok(())
+}
+
+/// Reads the rest of input, like payload of an unknown enum variant.
+#[inline]
+pub async fn read_to_end<R: AsyncRead + Unpin + Send>(r: &mut R) -> Result<Vec<u8>, LBSError> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Reads string or reference to a shared one. Must be called only if strings are shared.
+#[inline]
+async fn read_shared_str<R: AsyncRead + Unpin + Send>(
+    r: &mut R,
+    d: &mut Decoder,
+) -> Result<Arc<str>, LBSError> {
+    match r.read_u32_le().await? {
+        l if l & SHARED_STRING_FLAG != 0 => d.shared_str(l),
+        l => {
+            let l = d.check_len(l as usize)?;
+            read_bytes_into(r, d.scratch_mut(), l).await?;
+            d.share_scratch_str()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSWrite;
+use std::future::Future;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+/// Asynchronous counterpart of `LBSWrite`, implemented for every `LBSWrite` type.
+///
+/// Value is encoded into a buffer and written with a single `write_all()`, as encoding itself
+/// never waits, and one write is cheaper than a write per field. Writer isn't flushed.
+pub trait LBSWriteAsync {
+    fn lbs_write_async<W: AsyncWrite + Unpin + Send>(
+        &self,
+        w: &mut W,
+    ) -> impl Future<Output = Result<(), LBSError>> + Send;
+
+    /// Same as `lbs_write_async()`, but with options of the given encoder.
+    fn lbs_write_async_with<W: AsyncWrite + Unpin + Send>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> impl Future<Output = Result<(), LBSError>> + Send;
+}
+
+impl<T: LBSWrite + Sync + ?Sized> LBSWriteAsync for T {
+    #[inline]
+    async fn lbs_write_async<W: AsyncWrite + Unpin + Send>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), LBSError> {
+        self.lbs_write_async_with(w, &mut Encoder::new()).await
+    }
+
+    #[inline]
+    async fn lbs_write_async_with<W: AsyncWrite + Unpin + Send>(
+        &self,
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        let mut buf = Vec::new();
+        e.encode(self, &mut buf)?;
+        Ok(w.write_all(&buf).await?)
+    }
+}
+
+#[inline]
+pub async fn write<T: LBSWrite + Sync + ?Sized, W: AsyncWrite + Unpin + Send>(
+    value: &T,
+    w: &mut W,
+) -> Result<(), LBSError> {
+    value.lbs_write_async(w).await
+}
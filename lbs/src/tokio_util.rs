@@ -0,0 +1,114 @@
+use crate::error::LBSError;
+use crate::frame::Framing;
+use crate::frame::FRAME_HEADER_LEN;
+use crate::LBSRead;
+use crate::LBSWrite;
+use bytes::BufMut;
+use bytes::BytesMut;
+use std::marker::PhantomData;
+
+/// `tokio_util` codec of frames written by `frame::Framing`, for values of type `T`.
+///
+/// Drops derived types into `Framed`, `FramedRead` and `FramedWrite`. Values are encoded right
+/// into the write buffer, and decoded only once the whole frame is buffered.
+#[derive(Debug)]
+pub struct LBSCodec<T> {
+    framing: Framing,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> LBSCodec<T> {
+    /// Codec of frames without checksum, see `Framing::new()`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_framing(Framing::new())
+    }
+
+    #[inline]
+    pub const fn with_framing(framing: Framing) -> Self {
+        Self {
+            framing,
+            _value: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn framing(&self) -> &Framing {
+        &self.framing
+    }
+}
+
+impl<T> Default for LBSCodec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for LBSCodec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::with_framing(self.framing)
+    }
+}
+
+impl<T: LBSWrite> ::tokio_util::codec::Encoder<T> for LBSCodec<T> {
+    type Error = LBSError;
+
+    #[inline]
+    fn encode(&mut self, value: T, dst: &mut BytesMut) -> Result<(), LBSError> {
+        encode(&self.framing, &value, dst)
+    }
+}
+
+impl<T: LBSWrite> ::tokio_util::codec::Encoder<&T> for LBSCodec<T> {
+    type Error = LBSError;
+
+    #[inline]
+    fn encode(&mut self, value: &T, dst: &mut BytesMut) -> Result<(), LBSError> {
+        encode(&self.framing, value, dst)
+    }
+}
+
+impl<T: LBSRead> ::tokio_util::codec::Decoder for LBSCodec<T> {
+    type Item = T;
+    type Error = LBSError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, LBSError> {
+        if src.len() < FRAME_HEADER_LEN {
+            src.reserve(FRAME_HEADER_LEN - src.len());
+            return Ok(None);
+        }
+
+        // Oversized and foreign frames fail before they are buffered
+        let header = self.framing.parse_header(src)?;
+        let frame_len = header.size + header.payload_len;
+
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+
+        let header = self.framing.parse_header(src)?;
+        let frame = src.split_to(frame_len);
+        Framing::decode(&header, &frame[header.size..]).map(Some)
+    }
+}
+
+/// Writes header placeholder, then value and fills header in, or leaves `dst` as it was.
+fn encode<T: LBSWrite>(framing: &Framing, value: &T, dst: &mut BytesMut) -> Result<(), LBSError> {
+    let start = dst.len();
+    let header_len = framing.header_len();
+    dst.reserve(header_len + value.lbs_size_hint());
+    dst.put_bytes(0, header_len);
+
+    let result = value
+        .lbs_write(&mut (&mut *dst).writer())
+        .and_then(|_| framing.fill_header(&mut dst[start..]));
+
+    if result.is_err() {
+        dst.truncate(start);
+    }
+
+    result
+}
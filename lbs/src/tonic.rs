@@ -0,0 +1,91 @@
+use crate::error::LBSError;
+use crate::LBSRead;
+use crate::LBSWrite;
+use bytes::Buf;
+use bytes::BufMut;
+use std::marker::PhantomData;
+use tonic::codec::Codec;
+use tonic::codec::DecodeBuf;
+use tonic::codec::Decoder;
+use tonic::codec::EncodeBuf;
+use tonic::codec::Encoder;
+use tonic::Status;
+
+/// gRPC codec, which encodes messages of type `T` and decodes messages of type `U`.
+///
+/// May be used in place of `tonic::codec::ProstCodec` by generated or handwritten services,
+/// for example with `codec_path("lbs::tonic::LBSCodec")` of `tonic-build`.
+/// Failures are reported as `Status::internal`, same as with protobuf.
+#[derive(Debug)]
+pub struct LBSCodec<T, U> {
+    ty: PhantomData<fn(T) -> U>,
+}
+
+/// Encoder of `LBSCodec`.
+#[derive(Debug)]
+pub struct LBSEncoder<T> {
+    ty: PhantomData<fn(T)>,
+}
+
+/// Decoder of `LBSCodec`. Fails if message has trailing bytes.
+#[derive(Debug)]
+pub struct LBSDecoder<U> {
+    ty: PhantomData<fn() -> U>,
+}
+
+impl<T, U> Default for LBSCodec<T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self { ty: PhantomData }
+    }
+}
+
+impl<T, U> Codec for LBSCodec<T, U>
+where
+    T: LBSWrite + Send + 'static,
+    U: LBSRead + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+    type Encoder = LBSEncoder<T>;
+    type Decoder = LBSDecoder<U>;
+
+    #[inline]
+    fn encoder(&mut self) -> Self::Encoder {
+        LBSEncoder { ty: PhantomData }
+    }
+
+    #[inline]
+    fn decoder(&mut self) -> Self::Decoder {
+        LBSDecoder { ty: PhantomData }
+    }
+}
+
+impl<T: LBSWrite> Encoder for LBSEncoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    #[inline]
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.lbs_write(&mut dst.writer()).map_err(status)
+    }
+}
+
+impl<U: LBSRead> Decoder for LBSDecoder<U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let item = U::lbs_read(&mut src.reader()).map_err(status)?;
+
+        match src.remaining() {
+            0 => Ok(Some(item)),
+            n => Err(status(LBSError::TrailingBytes(n))),
+        }
+    }
+}
+
+#[inline]
+fn status(e: LBSError) -> Status {
+    Status::internal(e.to_string())
+}
@@ -0,0 +1,160 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::vectored::VectoredWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::ops::Deref;
+
+/// Value, which records ids of struct fields changed since its changes were last written.
+///
+/// Value is changed with `update()`, which marks the given fields dirty. `write_changes()` writes
+/// only dirty fields with `LBSWrite::lbs_write_fields()` and clears them, so a receiver keeps its
+/// copy in sync with `lbs_merge()`. Wire format is the same as of `T`: writing and reading the
+/// whole value neither inspects nor changes dirty fields, and values are read clean.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tracked<T> {
+    value: T,
+    /// Sorted ids of dirty fields.
+    dirty: Vec<u16>,
+}
+
+impl<T> Tracked<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            dirty: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Changes value, marking field with the given id dirty.
+    #[inline]
+    pub fn update<R>(&mut self, id: u16, f: impl FnOnce(&mut T) -> R) -> R {
+        self.mark(id);
+        f(&mut self.value)
+    }
+
+    /// Marks field with the given id dirty, like after its change through interior mutability.
+    #[inline]
+    pub fn mark(&mut self, id: u16) {
+        if let Err(i) = self.dirty.binary_search(&id) {
+            self.dirty.insert(i, id);
+        }
+    }
+
+    /// Sorted ids of fields changed since changes were last written.
+    #[inline]
+    pub fn dirty(&self) -> &[u16] {
+        &self.dirty
+    }
+
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Forgets changes, like after the whole value was sent.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.dirty.clear();
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: LBSWrite> Tracked<T> {
+    /// Writes dirty fields and clears them, unless writing fails.
+    /// Returns false and writes nothing if there are no changes.
+    #[inline]
+    pub fn write_changes<W: Write>(&mut self, w: &mut W) -> Result<bool, LBSError> {
+        if self.dirty.is_empty() {
+            return Ok(false);
+        }
+
+        self.value.lbs_write_fields(w, &self.dirty)?;
+        self.dirty.clear();
+        Ok(true)
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> From<T> for Tracked<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for Tracked<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.value.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.value.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.value.lbs_must_write()
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.value.lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.value.lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, fields: &[u16]) -> Result<(), LBSError> {
+        self.value.lbs_write_fields(w, fields)
+    }
+}
+
+impl<T: LBSRead> LBSRead for Tracked<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read(r)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    /// Merges into value, leaving dirty fields as they are.
+    #[inline]
+    fn lbs_merge_with<R: Read>(&mut self, r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        self.value.lbs_merge_with(r, d)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::decoder::Decoder;
+use crate::dynamic::Kinds;
+use crate::dynamic::Value;
+use crate::error::LBSError;
+use crate::limits::Limits;
+use crate::schema::Schema;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+
+/// Converter of values between revisions of a format, like regular and `#[lbs(compact)]`
+/// headers or `#[lbs(varint)]` fields, which needs only their schemas and no concrete types.
+///
+/// Values are read as `dynamic::Value` by the source schema and written by the target one,
+/// so archived data is migrated and old and new services are bridged during rollout. Schemas
+/// must describe the same fields and variants: a value with a field unknown to the target one
+/// fails. Opaque types and fields with other codecs are passed through as kinds registered
+/// with `kinds()`.
+#[derive(Debug, Clone)]
+pub struct Transcoder {
+    from: Schema,
+    to: Schema,
+    kinds: Kinds,
+    limits: Limits,
+}
+
+impl Transcoder {
+    #[inline]
+    pub fn new(from: Schema, to: Schema) -> Self {
+        Self {
+            from,
+            to,
+            kinds: Kinds::new(),
+            limits: Limits::new(),
+        }
+    }
+
+    /// Custom kinds, which are known to both revisions.
+    #[inline]
+    pub fn kinds(&mut self, kinds: Kinds) -> &mut Self {
+        self.kinds = kinds;
+        self
+    }
+
+    /// Limits of every value read from input, like `Limits::untrusted()` for archives of
+    /// unknown origin.
+    #[inline]
+    pub fn limits(&mut self, limits: Limits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Transcoder of the opposite direction.
+    #[inline]
+    pub fn reversed(&self) -> Self {
+        Self {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            kinds: self.kinds.clone(),
+            limits: self.limits,
+        }
+    }
+
+    /// Converts a single value.
+    #[inline]
+    pub fn transcode<R: Read, W: Write>(&self, r: &mut R, w: &mut W) -> Result<(), LBSError> {
+        let mut buf = Vec::new();
+        self.transcode_into(r, &mut buf)?;
+        Ok(w.write_all(&buf)?)
+    }
+
+    /// Converts consecutive values until input ends between them and returns their number.
+    ///
+    /// Every value is converted whole before it's written, so output holds only values,
+    /// which were converted, if one fails.
+    pub fn transcode_stream<R: BufRead, W: Write>(
+        &self,
+        r: &mut R,
+        w: &mut W,
+    ) -> Result<u64, LBSError> {
+        self.tee_stream(r, &mut std::io::sink(), w)
+    }
+
+    /// Same as `transcode_stream()`, but also copies input into `original` as it's read,
+    /// so both old and new services get their revision of the stream.
+    pub fn tee_stream<R: BufRead, O: Write, W: Write>(
+        &self,
+        r: &mut R,
+        original: &mut O,
+        w: &mut W,
+    ) -> Result<u64, LBSError> {
+        let mut buf = Vec::new();
+        let mut count = 0;
+
+        while !r.fill_buf()?.is_empty() {
+            buf.clear();
+            self.transcode_into(&mut TeeReader { r, original }, &mut buf)?;
+            w.write_all(&buf)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    #[inline]
+    fn transcode_into<R: Read>(&self, r: &mut R, buf: &mut Vec<u8>) -> Result<(), LBSError> {
+        let mut d = Decoder::new();
+        d.limits(self.limits);
+        let value = Value::read_with_decoder(r, &self.from, &self.kinds, &mut d)?;
+        value.write_with(buf, &self.to, &self.kinds)
+    }
+}
+
+/// Reader, which copies consumed bytes into another writer.
+struct TeeReader<'a, R, O> {
+    r: &'a mut R,
+    original: &'a mut O,
+}
+
+impl<R: Read, O: Write> Read for TeeReader<'_, R, O> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.r.read(buf)?;
+        self.original.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::stats;
+use crate::vectored::VectoredWriter;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use triomphe::Arc;
+
+impl<T: LBSWrite + ?Sized> LBSWrite for Arc<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+}
+
+impl<T: LBSRead> LBSRead for Arc<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read(r)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        stats::allocation(d);
+        Ok(Self::new(T::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        T::lbs_skip(r, d)
+    }
+
+    #[inline]
+    fn lbs_read_projected<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        fields: &[u16],
+    ) -> Result<Self, LBSError> {
+        Ok(Self::new(T::lbs_read_projected(r, d, fields)?))
+    }
+}
+
+/// Decoded into a vector first, which is then moved into a single allocation.
+impl<T: LBSRead> LBSRead for Arc<[T]> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self::from(Vec::<T>::lbs_read(r)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::from(Vec::<T>::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        Vec::<T>::lbs_skip(r, d)
+    }
+}
+
+/// Unlike `std::sync::Arc<str>`, decoded strings are never interned.
+impl LBSRead for Arc<str> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Ok(Self::from(String::lbs_read(r)?))
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        Ok(Self::from(String::lbs_read_with(r, d)?))
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        String::lbs_skip(r, d)
+    }
+}
+
+impl<T: LBSSchema + ?Sized> LBSSchema for Arc<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
+
+impl<T: LBSMaxSize> LBSMaxSize for Arc<T> {
+    const LBS_MAX_SIZE: usize = T::LBS_MAX_SIZE;
+}
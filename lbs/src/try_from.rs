@@ -0,0 +1,51 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Codec of `#[lbs(try_from(<type>))]` fields, which are written and read as proxy `P`.
+///
+/// Value is cloned into the proxy to be written, and the read proxy is converted back with
+/// `TryFrom`, so validated newtypes, like `Email(String)`, need no read functions of their own.
+/// Failed conversion is `LBSError::Parsing` with the conversion error, or with the message of
+/// `#[lbs(try_from(<type>, error = "<message>"))]`.
+pub struct Proxy<P>(PhantomData<P>);
+
+impl<P> Proxy<P> {
+    #[inline]
+    pub fn write<T, W>(value: &T, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>
+    where
+        T: Clone + Into<P>,
+        P: LBSWrite,
+        W: Write,
+    {
+        value.clone().into().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    pub fn read<T, R>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError>
+    where
+        T: TryFrom<P>,
+        T::Error: Display,
+        P: LBSRead,
+        R: Read,
+    {
+        T::try_from(P::lbs_read_with(r, d)?).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+
+    /// Reads value, which conversion fails with the given message.
+    #[inline]
+    pub fn read_or<T, R>(r: &mut R, d: &mut Decoder, message: &str) -> Result<T, LBSError>
+    where
+        T: TryFrom<P>,
+        P: LBSRead,
+        R: Read,
+    {
+        T::try_from(P::lbs_read_with(r, d)?).map_err(|_| LBSError::Parsing(message.to_string()))
+    }
+}
@@ -0,0 +1,177 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::write::write_len;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Durations and timestamps, which can be encoded as `u64` count of time units, like legacy
+/// millisecond fields. Timestamps are counted since Unix epoch.
+///
+/// Encoded form is the same as of `u64`, so such fields stay compatible with integer ones.
+/// Fractions of a unit are truncated. Values, which don't fit into `u64`, fail with
+/// `LBSError::OutOfRange` on write and with `LBSError::InvalidTimestamp` on read.
+pub trait LBSUnitWrite {
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSUnitWrite`.
+pub trait LBSUnitRead: Sized {
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError>;
+}
+
+/// Count of units in duration.
+#[inline]
+fn to_units(duration: Duration, nanos_per_unit: u64) -> Result<u64, LBSError> {
+    (duration.as_nanos() / nanos_per_unit as u128)
+        .try_into()
+        .map_err(|_| LBSError::OutOfRange("u64"))
+}
+
+/// Duration of given count of units.
+#[inline]
+fn from_units(units: u64, nanos_per_unit: u64) -> Result<Duration, LBSError> {
+    let nanos = units as u128 * nanos_per_unit as u128;
+    let secs = (nanos / 1_000_000_000)
+        .try_into()
+        .map_err(|_| LBSError::InvalidTimestamp)?;
+    Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+impl LBSUnitWrite for Duration {
+    #[inline]
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError> {
+        to_units(*self, nanos_per_unit)?.lbs_write(w)
+    }
+}
+
+impl LBSUnitRead for Duration {
+    #[inline]
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        _d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError> {
+        from_units(u64::lbs_read(r)?, nanos_per_unit)
+    }
+}
+
+impl LBSUnitWrite for SystemTime {
+    #[inline]
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError> {
+        self.duration_since(UNIX_EPOCH)
+            .map_err(|_| LBSError::OutOfRange("u64"))?
+            .lbs_unit_write(w, nanos_per_unit)
+    }
+}
+
+impl LBSUnitRead for SystemTime {
+    #[inline]
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError> {
+        UNIX_EPOCH
+            .checked_add(Duration::lbs_unit_read(r, d, nanos_per_unit)?)
+            .ok_or(LBSError::InvalidTimestamp)
+    }
+}
+
+impl<T: LBSUnitWrite> LBSUnitWrite for Option<T> {
+    #[inline]
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_unit_write(w, nanos_per_unit)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSUnitRead> LBSUnitRead for Option<T> {
+    #[inline]
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_unit_read(r, d, nanos_per_unit)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSUnitWrite> LBSUnitWrite for Vec<T> {
+    #[inline]
+    fn lbs_unit_write<W: Write>(&self, w: &mut W, nanos_per_unit: u64) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for e in self {
+            e.lbs_unit_write(w, nanos_per_unit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSUnitRead> LBSUnitRead for Vec<T> {
+    #[inline]
+    fn lbs_unit_read<R: Read>(
+        r: &mut R,
+        d: &mut Decoder,
+        nanos_per_unit: u64,
+    ) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_unit_read(r, d, nanos_per_unit)?);
+        }
+
+        Ok(v)
+    }
+}
+
+macro_rules! unit_codec {
+    ($($unit:ident => $nanos:expr),*) => {
+        $(
+            /// Codec of `#[lbs(unit(...))]` fields with the unit of the same name.
+            pub mod $unit {
+                use super::*;
+
+                #[inline]
+                pub fn write<T: LBSUnitWrite, W: Write>(
+                    value: &T,
+                    w: &mut W,
+                    _e: &mut Encoder,
+                ) -> Result<(), LBSError> {
+                    value.lbs_unit_write(w, $nanos)
+                }
+
+                #[inline]
+                pub fn read<T: LBSUnitRead, R: Read>(
+                    r: &mut R,
+                    d: &mut Decoder,
+                ) -> Result<T, LBSError> {
+                    T::lbs_unit_read(r, d, $nanos)
+                }
+            }
+        )*
+    };
+}
+
+unit_codec!(secs => 1_000_000_000, millis => 1_000_000, micros => 1_000, nanos => 1);
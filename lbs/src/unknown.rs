@@ -0,0 +1,118 @@
+use crate::error::LBSError;
+use crate::indexed::IndexWriter;
+use std::io::Read;
+
+/// Fields unknown to a struct, kept by `#[lbs(unknown_fields)]` to be written back unchanged.
+///
+/// Holds id and encoded value of every field, sorted by id. Values are opaque bytes, so they
+/// are only carried over: a message from a newer producer may be decoded, changed and encoded
+/// again without losing fields added by that producer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UnknownFields {
+    fields: Vec<(u16, Vec<u8>)>,
+}
+
+impl UnknownFields {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts encoded value of a field, returns the previous one.
+    pub fn insert(&mut self, id: u16, value: Vec<u8>) -> Option<Vec<u8>> {
+        match self.fields.binary_search_by_key(&id, |(id, _)| *id) {
+            Ok(i) => Some(std::mem::replace(&mut self.fields[i].1, value)),
+            Err(i) => {
+                self.fields.insert(i, (id, value));
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, id: u16) -> Option<&[u8]> {
+        self.fields
+            .binary_search_by_key(&id, |(id, _)| *id)
+            .ok()
+            .map(|i| self.fields[i].1.as_slice())
+    }
+
+    #[inline]
+    pub fn remove(&mut self, id: u16) -> Option<Vec<u8>> {
+        self.fields
+            .binary_search_by_key(&id, |(id, _)| *id)
+            .ok()
+            .map(|i| self.fields.remove(i).1)
+    }
+
+    /// Ids and encoded values in ascending id order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &[u8])> {
+        self.fields
+            .iter()
+            .map(|(id, value)| (*id, value.as_slice()))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.fields.clear()
+    }
+}
+
+/// Keeps value of an unknown field, which is the rest of `r`. Used by derived implementations.
+#[inline]
+pub fn capture<R: Read>(fields: &mut UnknownFields, id: u16, r: &mut R) -> Result<(), LBSError> {
+    let mut value = Vec::new();
+    r.read_to_end(&mut value)?;
+    fields.insert(id, value);
+    Ok(())
+}
+
+/// Appends unknown fields with ids below `id`, or all remaining ones if it's `None`, starting
+/// at `*next`. Fields with the same id as a known one are dropped, as the known value wins.
+/// Used by derived implementations, which append known fields in between.
+#[inline]
+pub fn write_before(
+    fields: &UnknownFields,
+    next: &mut usize,
+    id: Option<u16>,
+    index: &mut IndexWriter,
+) -> Result<(), LBSError> {
+    while let Some((field_id, value)) = fields.fields.get(*next) {
+        if id.is_some_and(|id| *field_id > id) {
+            break;
+        }
+
+        *next += 1;
+
+        if id != Some(*field_id) {
+            index.field(*field_id, |w| {
+                w.extend_from_slice(value);
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Size of unknown fields in an indexed struct, with their index entries.
+/// Used by derived implementations.
+#[inline]
+pub fn size_hint(fields: &UnknownFields) -> usize {
+    fields
+        .fields
+        .iter()
+        .map(|(_, value)| 10 + value.len())
+        .sum()
+}
@@ -0,0 +1,116 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::capacity;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::write::write_len;
+use std::io::Read;
+use std::io::Write;
+
+/// Values with chars, which can be encoded as UTF-8 (1-4 bytes per char) instead of u32.
+///
+/// Char takes as many bytes as its UTF-8 sequence, with length given by the leading byte,
+/// instead of the regular 4 little-endian bytes. Optionals keep their one byte tag and vectors
+/// their u32 length, which counts chars, not bytes.
+pub trait LBSUtf8Write {
+    fn lbs_utf8_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSUtf8Write`.
+pub trait LBSUtf8Read: Sized {
+    fn lbs_utf8_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+impl LBSUtf8Write for char {
+    #[inline]
+    fn lbs_utf8_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        let mut buf = [0; 4];
+        Ok(w.write_all(self.encode_utf8(&mut buf).as_bytes())?)
+    }
+}
+
+impl LBSUtf8Read for char {
+    #[inline]
+    fn lbs_utf8_read<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+        let mut buf = [0; 4];
+        r.read_exact(&mut buf[..1])?;
+
+        let l = match buf[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(LBSError::InvalidChar),
+        };
+
+        r.read_exact(&mut buf[1..l])?;
+
+        std::str::from_utf8(&buf[..l])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or(LBSError::InvalidChar)
+    }
+}
+
+impl<T: LBSUtf8Write> LBSUtf8Write for Option<T> {
+    #[inline]
+    fn lbs_utf8_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_utf8_write(w)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSUtf8Read> LBSUtf8Read for Option<T> {
+    #[inline]
+    fn lbs_utf8_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_utf8_read(r, d)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSUtf8Write> LBSUtf8Write for Vec<T> {
+    #[inline]
+    fn lbs_utf8_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for e in self {
+            e.lbs_utf8_write(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSUtf8Read> LBSUtf8Read for Vec<T> {
+    #[inline]
+    fn lbs_utf8_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = read_len_with(r, d)?;
+        let mut v = Self::with_capacity(capacity::<T>(l));
+
+        for _ in 0..l {
+            v.push(T::lbs_utf8_read(r, d)?);
+        }
+
+        Ok(v)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSUtf8Write, W: Write>(
+    value: &T,
+    w: &mut W,
+    _e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_utf8_write(w)
+}
+
+#[inline]
+pub fn read<T: LBSUtf8Read, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_utf8_read(r, d)
+}
@@ -1,22 +1,42 @@
 use super::LBSRead;
 use super::LBSWrite;
 use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
 use std::io::Read;
 use std::io::Write;
-use std::str::FromStr;
 use uuid::Uuid;
 
+/// Written as 16 raw bytes.
 impl LBSWrite for Uuid {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.to_string().lbs_write(w)
+        Ok(w.write_all(self.as_bytes())?)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        16
     }
 }
 
 impl LBSRead for Uuid {
     #[inline]
     fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
-        let s = String::lbs_read(r)?;
-        Uuid::from_str(&s).map_err(|e| LBSError::Parsing(e.to_string()))
+        let mut buf = [0; 16];
+        r.read_exact(&mut buf)?;
+        Ok(Uuid::from_bytes(buf))
     }
 }
+
+impl LBSSchema for Uuid {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        Schema::Tuple(vec![Schema::U8; 16])
+    }
+}
+
+impl LBSMaxSize for Uuid {
+    const LBS_MAX_SIZE: usize = 16;
+}
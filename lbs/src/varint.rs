@@ -0,0 +1,262 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::read::read_option_tag;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Maximum length of LEB128-encoded u64.
+pub const MAX_LEN: usize = 10;
+
+/// Writes unsigned LEB128 varint.
+#[inline]
+pub fn write_u64<W: Write>(w: &mut W, mut v: u64) -> Result<(), LBSError> {
+    let mut buf = [0; MAX_LEN];
+    let mut l = 0;
+
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+
+        if v == 0 {
+            buf[l] = byte;
+            l += 1;
+            break;
+        }
+
+        buf[l] = byte | 0x80;
+        l += 1;
+    }
+
+    Ok(w.write_all(&buf[..l])?)
+}
+
+/// Reads unsigned LEB128 varint. Overlong encodings, which end with zero byte after
+/// the first one, fail with `LBSError::InvalidVarint`, so every value has a single encoding.
+#[inline]
+pub fn read_u64<R: Read>(r: &mut R) -> Result<u64, LBSError> {
+    let mut v = 0u64;
+    let mut buf = [0; 1];
+
+    for i in 0..MAX_LEN {
+        r.read_exact(&mut buf)?;
+        let byte = buf[0];
+
+        if (i == MAX_LEN - 1 && byte > 1) || (i > 0 && byte == 0) {
+            return Err(LBSError::InvalidVarint);
+        }
+
+        v |= ((byte & 0x7f) as u64) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+
+    Err(LBSError::InvalidVarint)
+}
+
+/// Number of bytes of LEB128-encoded u64.
+#[inline]
+pub const fn u64_size(v: u64) -> usize {
+    match v {
+        0 => 1,
+        _ => (64 - v.leading_zeros() as usize).div_ceil(7),
+    }
+}
+
+#[inline]
+pub const fn u16_size(v: u16) -> usize {
+    u64_size(v as u64)
+}
+
+#[inline]
+pub fn write_u16<W: Write>(w: &mut W, v: u16) -> Result<(), LBSError> {
+    write_u64(w, v as u64)
+}
+
+#[inline]
+pub fn read_u16<R: Read>(r: &mut R) -> Result<u16, LBSError> {
+    read_u64(r)?.try_into().map_err(|_| LBSError::InvalidVarint)
+}
+
+/// Writes signed integer as zigzag LEB128 varint, so small negative values take few bytes.
+#[inline]
+pub fn write_i64<W: Write>(w: &mut W, v: i64) -> Result<(), LBSError> {
+    write_u64(w, ((v << 1) ^ (v >> 63)) as u64)
+}
+
+/// Reads zigzag LEB128 varint.
+#[inline]
+pub fn read_i64<R: Read>(r: &mut R) -> Result<i64, LBSError> {
+    let zigzag = read_u64(r)?;
+    Ok((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+}
+
+/// Values, which can be encoded with `#[lbs(varint)]` attribute: unsigned integers as LEB128
+/// varints, signed ones as zigzag varints, and lengths of strings and vectors as varints too.
+///
+/// Bytes are written as is, so `Vec<u8>` costs its length and a varint. Small numbers and short
+/// strings take a byte or two instead of fixed width, but encoding differs from the regular one,
+/// so both sides must use it. Nested values keep their own encoding.
+pub trait LBSVarintWrite {
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError>;
+}
+
+/// Counterpart of `LBSVarintWrite`. Values, which don't fit into the type, fail with
+/// `LBSError::InvalidVarint`. Lengths are checked against `Limits::max_len` of the decoder.
+pub trait LBSVarintRead: Sized {
+    fn lbs_varint_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError>;
+}
+
+macro_rules! impl_varint {
+    ($write:ident, $read:ident, $wide:ty => $($t:ty),*) => {
+        $(
+            impl LBSVarintWrite for $t {
+                #[inline]
+                fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    $write(w, *self as $wide)
+                }
+            }
+
+            impl LBSVarintRead for $t {
+                #[inline]
+                fn lbs_varint_read<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+                    $read(r)?.try_into().map_err(|_| LBSError::InvalidVarint)
+                }
+            }
+        )*
+    };
+}
+
+impl_varint!(write_u64, read_u64, u64 => u16, u32, u64, usize);
+impl_varint!(write_i64, read_i64, i64 => i16, i32, i64, isize);
+
+impl LBSVarintWrite for u8 {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+}
+
+impl LBSVarintRead for u8 {
+    #[inline]
+    fn lbs_varint_read<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+        Self::lbs_read(r)
+    }
+}
+
+impl LBSVarintWrite for i8 {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+}
+
+impl LBSVarintRead for i8 {
+    #[inline]
+    fn lbs_varint_read<R: Read>(r: &mut R, _d: &mut Decoder) -> Result<Self, LBSError> {
+        Self::lbs_read(r)
+    }
+}
+
+impl LBSVarintWrite for str {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.len().lbs_varint_write(w)?;
+        Ok(w.write_all(self.as_bytes())?)
+    }
+}
+
+impl LBSVarintWrite for String {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.as_str().lbs_varint_write(w)
+    }
+}
+
+impl LBSVarintRead for String {
+    #[inline]
+    fn lbs_varint_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = d.check_len(len(r)?)? as u64;
+        let mut buf = Vec::new();
+
+        // Buffer grows with the input, so bogus length can't allocate in advance
+        if (r.take(l).read_to_end(&mut buf)? as u64) < l {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Self::from_utf8(buf).map_err(|e| LBSError::Parsing(e.to_string()))
+    }
+}
+
+impl<T: LBSVarintWrite> LBSVarintWrite for Option<T> {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            w.write_all(&[1])?;
+            v.lbs_varint_write(w)
+        } else {
+            Ok(w.write_all(&[0])?)
+        }
+    }
+}
+
+impl<T: LBSVarintRead> LBSVarintRead for Option<T> {
+    #[inline]
+    fn lbs_varint_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        if read_option_tag(r)? {
+            Ok(Some(T::lbs_varint_read(r, d)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: LBSVarintWrite> LBSVarintWrite for Vec<T> {
+    #[inline]
+    fn lbs_varint_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.len().lbs_varint_write(w)?;
+        for e in self {
+            e.lbs_varint_write(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: LBSVarintRead> LBSVarintRead for Vec<T> {
+    #[inline]
+    fn lbs_varint_read<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let l = d.check_len(len(r)?)?;
+        // Length isn't trusted, so vector grows with the input
+        let mut v = Vec::new();
+
+        for _ in 0..l {
+            v.push(T::lbs_varint_read(r, d)?);
+        }
+
+        Ok(v)
+    }
+}
+
+#[inline]
+pub fn write<T: LBSVarintWrite + ?Sized, W: Write>(
+    value: &T,
+    w: &mut W,
+    _e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_varint_write(w)
+}
+
+#[inline]
+pub fn read<T: LBSVarintRead, R: Read>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError> {
+    T::lbs_varint_read(r, d)
+}
+
+/// Reads varint length of a string or a vector.
+#[inline]
+fn len<R: Read>(r: &mut R) -> Result<usize, LBSError> {
+    read_u64(r)?.try_into().map_err(|_| LBSError::InvalidVarint)
+}
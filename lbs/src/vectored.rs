@@ -0,0 +1,133 @@
+use crate::error::LBSError;
+use std::io::ErrorKind;
+use std::io::IoSlice;
+use std::io::Write;
+use std::ops::Range;
+
+/// Default minimal length of a slice which is borrowed instead of being copied.
+pub const DEFAULT_BORROW_THRESHOLD: usize = 512;
+
+/// Writer, which collects encoded value as a list of slices.
+///
+/// Small writes (field headers, numbers, short strings) are copied into an internal buffer,
+/// while large byte slices are borrowed from the value being encoded.
+/// Result is flushed via `write_vectored()`, which avoids copying large blobs.
+#[derive(Debug)]
+pub struct VectoredWriter<'a> {
+    buf: Vec<u8>,
+    parts: Vec<Part<'a>>,
+    threshold: usize,
+}
+
+#[derive(Debug)]
+enum Part<'a> {
+    Owned(Range<usize>),
+    Borrowed(&'a [u8]),
+}
+
+impl<'a> VectoredWriter<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_threshold(DEFAULT_BORROW_THRESHOLD)
+    }
+
+    /// Creates writer borrowing slices of at least `threshold` bytes.
+    #[inline]
+    pub fn with_threshold(threshold: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            parts: Vec::new(),
+            threshold,
+        }
+    }
+
+    /// Appends bytes, borrowing them if they are large enough.
+    #[inline]
+    pub fn write_borrowed(&mut self, bytes: &'a [u8]) {
+        if bytes.len() < self.threshold {
+            self.write_owned(bytes);
+        } else {
+            self.parts.push(Part::Borrowed(bytes));
+        }
+    }
+
+    /// Total number of collected bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.parts.iter().map(|p| self.slice(p).len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Writes all collected bytes into `w` and returns their count.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> Result<usize, LBSError> {
+        let mut slices: Vec<IoSlice> = self
+            .parts
+            .iter()
+            .map(|p| IoSlice::new(self.slice(p)))
+            .collect();
+        let mut slices = slices.as_mut_slice();
+        let total = self.len();
+
+        while !slices.is_empty() {
+            match w.write_vectored(slices) {
+                Ok(0) => return Err(std::io::Error::from(ErrorKind::WriteZero).into()),
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(total)
+    }
+
+    #[inline]
+    fn write_owned(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+
+        let start = self.buf.len();
+        self.buf.extend_from_slice(bytes);
+
+        if let Some(Part::Owned(range)) = self.parts.last_mut() {
+            if range.end == start {
+                range.end = self.buf.len();
+                return;
+            }
+        }
+
+        self.parts.push(Part::Owned(start..self.buf.len()));
+    }
+
+    #[inline]
+    fn slice<'s>(&'s self, part: &'s Part<'a>) -> &'s [u8] {
+        match part {
+            Part::Owned(range) => &self.buf[range.clone()],
+            Part::Borrowed(bytes) => bytes,
+        }
+    }
+}
+
+impl<'a> Default for VectoredWriter<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Write for VectoredWriter<'a> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_owned(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
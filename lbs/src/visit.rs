@@ -0,0 +1,421 @@
+use crate::decoder::Decoder;
+use crate::error::LBSError;
+use crate::read::read_len_with;
+use crate::read::read_option_tag;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::schema::StructSchema;
+use crate::LBSRead;
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::IpAddr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Same as in derived implementations.
+const PACKED_BOOLS_ID: u16 = u16::MAX;
+
+/// Receiver of values, which are met while walking encoded data, see `visit()`.
+///
+/// Every method does nothing by default. Integers are widened to 64 bits, unless they are 128 bits
+/// wide, floats are widened to `f64`. Strings are borrowed from a buffer, which is reused.
+#[allow(unused_variables)]
+pub trait LBSVisitor {
+    /// Struct with `fields` encoded fields starts. Its fields follow, then `on_struct_end()`.
+    #[inline]
+    fn on_struct_start(&mut self, name: &str, fields: u16) {}
+
+    #[inline]
+    fn on_struct_end(&mut self) {}
+
+    /// Field starts. Its value follows, then `on_field_end()`. If false is returned, value is
+    /// consumed without being visited and `on_field_end()` isn't called.
+    #[inline]
+    fn on_field_start(&mut self, id: u16, name: &str) -> bool {
+        true
+    }
+
+    #[inline]
+    fn on_field_end(&mut self, id: u16) {}
+
+    /// Enum variant is met. Its payload, if any, follows.
+    #[inline]
+    fn on_variant(&mut self, enum_name: &str, id: u16, name: &str) {}
+
+    /// Option is met. If it is some, its value follows.
+    #[inline]
+    fn on_option(&mut self, is_some: bool) {}
+
+    /// List of `len` elements starts, then `on_list_end()` follows them.
+    #[inline]
+    fn on_list_start(&mut self, len: usize) {}
+
+    #[inline]
+    fn on_list_end(&mut self) {}
+
+    /// Map of `len` entries starts. Keys and values follow in turn, then `on_map_end()`.
+    #[inline]
+    fn on_map_start(&mut self, len: usize) {}
+
+    #[inline]
+    fn on_map_end(&mut self) {}
+
+    /// Tuple or range of `len` elements starts, then `on_tuple_end()` follows them.
+    #[inline]
+    fn on_tuple_start(&mut self, len: usize) {}
+
+    #[inline]
+    fn on_tuple_end(&mut self) {}
+
+    #[inline]
+    fn on_unit(&mut self) {}
+
+    #[inline]
+    fn on_bool(&mut self, v: bool) {}
+
+    #[inline]
+    fn on_u64(&mut self, v: u64) {}
+
+    #[inline]
+    fn on_i64(&mut self, v: i64) {}
+
+    #[inline]
+    fn on_u128(&mut self, v: u128) {}
+
+    #[inline]
+    fn on_i128(&mut self, v: i128) {}
+
+    #[inline]
+    fn on_f64(&mut self, v: f64) {}
+
+    #[inline]
+    fn on_char(&mut self, v: char) {}
+
+    #[inline]
+    fn on_string(&mut self, v: &str) {}
+
+    #[inline]
+    fn on_duration(&mut self, v: Duration) {}
+
+    #[inline]
+    fn on_system_time(&mut self, v: SystemTime) {}
+
+    #[inline]
+    fn on_ip_addr(&mut self, v: IpAddr) {}
+}
+
+/// Walks a single encoded `T` from reader without constructing it, as described by `T::lbs_schema()`.
+#[inline]
+pub fn visit_as<T, R, V>(r: &mut R, visitor: &mut V) -> Result<(), LBSError>
+where
+    T: LBSSchema + ?Sized,
+    R: Read,
+    V: LBSVisitor + ?Sized,
+{
+    visit(r, &T::lbs_schema(), visitor)
+}
+
+/// Walks a single encoded value of an arbitrary schema from reader.
+///
+/// Memory usage doesn't depend on the number of elements, so huge lists, like exports, may be
+/// filtered or aggregated on the fly. Values with custom codecs, opaque types and unknown fields
+/// can't be walked and are reported as errors.
+#[inline]
+pub fn visit<R, V>(r: &mut R, schema: &Schema, visitor: &mut V) -> Result<(), LBSError>
+where
+    R: Read,
+    V: LBSVisitor + ?Sized,
+{
+    visit_with(r, schema, visitor, &mut Decoder::new())
+}
+
+/// Same as `visit()`, but lengths and nesting of structs and enums, including recursive ones,
+/// are bounded by `Limits` of the decoder.
+pub fn visit_with<R, V>(
+    r: &mut R,
+    schema: &Schema,
+    visitor: &mut V,
+    d: &mut Decoder,
+) -> Result<(), LBSError>
+where
+    R: Read,
+    V: LBSVisitor + ?Sized,
+{
+    Walker {
+        r,
+        d,
+        named: HashMap::new(),
+        buf: Vec::new(),
+    }
+    .value(schema, visitor)
+}
+
+/// Visitor of skipped values.
+pub(crate) struct Skip;
+
+impl LBSVisitor for Skip {}
+
+struct Walker<'r, R> {
+    r: &'r mut R,
+    d: &'r mut Decoder,
+    /// Structs and enums met so far, to resolve `Schema::Ref`.
+    named: HashMap<String, Schema>,
+    /// Reused string buffer.
+    buf: Vec<u8>,
+}
+
+impl<'r, R: Read> Walker<'r, R> {
+    fn value<V: LBSVisitor + ?Sized>(
+        &mut self,
+        schema: &Schema,
+        v: &mut V,
+    ) -> Result<(), LBSError> {
+        match schema {
+            Schema::Unit => v.on_unit(),
+            Schema::Bool => v.on_bool(bool::lbs_read(self.r)?),
+            Schema::U8 => v.on_u64(u8::lbs_read(self.r)? as u64),
+            Schema::U16 => v.on_u64(u16::lbs_read(self.r)? as u64),
+            Schema::U32 => v.on_u64(u32::lbs_read(self.r)? as u64),
+            Schema::U64 => v.on_u64(u64::lbs_read(self.r)?),
+            Schema::U128 => v.on_u128(u128::lbs_read(self.r)?),
+            Schema::I8 => v.on_i64(i8::lbs_read(self.r)? as i64),
+            Schema::I16 => v.on_i64(i16::lbs_read(self.r)? as i64),
+            Schema::I32 => v.on_i64(i32::lbs_read(self.r)? as i64),
+            Schema::I64 => v.on_i64(i64::lbs_read(self.r)?),
+            Schema::I128 => v.on_i128(i128::lbs_read(self.r)?),
+            Schema::F32 => v.on_f64(f32::lbs_read(self.r)? as f64),
+            Schema::F64 => v.on_f64(f64::lbs_read(self.r)?),
+            Schema::Char => v.on_char(char::lbs_read(self.r)?),
+            Schema::String => {
+                let len = read_len_with(self.r, self.d)?;
+                self.buf.clear();
+                self.r.take(len as u64).read_to_end(&mut self.buf)?;
+
+                if self.buf.len() < len {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
+
+                let s =
+                    std::str::from_utf8(&self.buf).map_err(|e| LBSError::Parsing(e.to_string()))?;
+                v.on_string(s);
+            }
+            Schema::Duration => v.on_duration(Duration::lbs_read(self.r)?),
+            Schema::SystemTime => v.on_system_time(SystemTime::lbs_read(self.r)?),
+            Schema::Ipv4Addr | Schema::Ipv6Addr | Schema::IpAddr => {
+                let addr = match schema {
+                    Schema::Ipv4Addr => IpAddr::V4(LBSRead::lbs_read(self.r)?),
+                    Schema::Ipv6Addr => IpAddr::V6(LBSRead::lbs_read(self.r)?),
+                    _ => IpAddr::lbs_read(self.r)?,
+                };
+                v.on_ip_addr(addr);
+            }
+            Schema::Option(inner) => {
+                let is_some = read_option_tag(self.r)?;
+                v.on_option(is_some);
+
+                if is_some {
+                    self.value(inner, v)?;
+                }
+            }
+            Schema::List(inner) => {
+                let len = read_len_with(self.r, self.d)?;
+                v.on_list_start(len);
+
+                for _ in 0..len {
+                    self.value(inner, v)?;
+                }
+
+                v.on_list_end();
+            }
+            Schema::Map(kv) => {
+                let len = read_len_with(self.r, self.d)?;
+                v.on_map_start(len);
+
+                for _ in 0..len {
+                    self.value(&kv.0, v)?;
+                    self.value(&kv.1, v)?;
+                }
+
+                v.on_map_end();
+            }
+            Schema::Tuple(elements) => {
+                v.on_tuple_start(elements.len());
+
+                for element in elements {
+                    self.value(element, v)?;
+                }
+
+                v.on_tuple_end();
+            }
+            Schema::Range(inner) => {
+                v.on_tuple_start(2);
+                self.value(inner, v)?;
+                self.value(inner, v)?;
+                v.on_tuple_end();
+            }
+            Schema::Struct(s) => {
+                self.named
+                    .entry(s.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                self.nested(|walker| match s.indexed {
+                    true => walker.indexed_struct(s, v),
+                    false => walker.struct_fields(s, v),
+                })?;
+            }
+            Schema::Enum(e) => {
+                self.named
+                    .entry(e.name.clone())
+                    .or_insert_with(|| schema.clone());
+
+                let id = match e.compact {
+                    true => crate::varint::read_u16(self.r)?,
+                    false => u16::lbs_read(self.r)?,
+                };
+
+                let variant = e
+                    .variants
+                    .iter()
+                    .find(|v| v.id == id)
+                    .ok_or(LBSError::UnexpectedVariant)?;
+
+                v.on_variant(&e.name, id, &variant.name);
+
+                if let Some(ref payload) = variant.schema {
+                    self.nested(|walker| walker.value(payload, v))?;
+                }
+            }
+            Schema::Ref(name) => {
+                let schema = self
+                    .named
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| LBSError::Parsing(format!("unknown type {}", name)))?;
+                self.value(&schema, v)?;
+            }
+            Schema::Opaque(name) => {
+                return Err(LBSError::Parsing(format!(
+                    "opaque {} can't be visited",
+                    name
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Walks a value nested into a struct or an enum, tracking depth like typed reads.
+    #[inline]
+    fn nested<F>(&mut self, f: F) -> Result<(), LBSError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), LBSError>,
+    {
+        self.d.enter()?;
+        let result = f(self);
+        self.d.leave();
+        result
+    }
+
+    fn struct_fields<V: LBSVisitor + ?Sized>(
+        &mut self,
+        s: &StructSchema,
+        v: &mut V,
+    ) -> Result<(), LBSError> {
+        let count = match s.compact {
+            true => crate::read::read_compact_field_count(self.r)?,
+            false => crate::read::read_field_count(self.r)?,
+        };
+
+        v.on_struct_start(&s.name, count);
+        let mut prev_id = None;
+
+        for _ in 0..count {
+            let id = match s.compact {
+                true => crate::read::read_compact_field_id(self.r, &mut prev_id)?,
+                false => crate::read::read_field_id(self.r)?,
+            };
+
+            if id == PACKED_BOOLS_ID && s.pack_bools {
+                self.packed_bools(s, v).map_err(|e| e.with_field(id))?;
+                continue;
+            }
+
+            self.field(s, id, v).map_err(|e| e.with_field(id))?;
+        }
+
+        v.on_struct_end();
+        Ok(())
+    }
+
+    fn indexed_struct<V: LBSVisitor + ?Sized>(
+        &mut self,
+        s: &StructSchema,
+        v: &mut V,
+    ) -> Result<(), LBSError> {
+        let count = u16::lbs_read(self.r)?;
+        let mut ids = Vec::with_capacity(count as usize);
+
+        // Index entries are id, offset and length, values follow in the same order
+        for _ in 0..count {
+            ids.push(u16::lbs_read(self.r)?);
+            u32::lbs_read(self.r)?;
+            u32::lbs_read(self.r)?;
+        }
+
+        read_len_with(self.r, self.d)?;
+        v.on_struct_start(&s.name, count);
+
+        for id in ids {
+            self.field(s, id, v).map_err(|e| e.with_field(id))?;
+        }
+
+        v.on_struct_end();
+        Ok(())
+    }
+
+    fn field<V: LBSVisitor + ?Sized>(
+        &mut self,
+        s: &StructSchema,
+        id: u16,
+        v: &mut V,
+    ) -> Result<(), LBSError> {
+        let field = s
+            .fields
+            .iter()
+            .find(|f| f.id == id)
+            .ok_or_else(|| LBSError::Parsing(format!("unknown field {} of {}", id, s.name)))?;
+
+        if let Some(ref codec) = field.codec {
+            return Err(LBSError::Parsing(format!("field is encoded by {}", codec)));
+        }
+
+        match v.on_field_start(id, &field.name) {
+            true => {
+                self.value(&field.schema, v)?;
+                v.on_field_end(id);
+            }
+            false => self.value(&field.schema, &mut Skip)?,
+        }
+
+        Ok(())
+    }
+
+    fn packed_bools<V: LBSVisitor + ?Sized>(
+        &mut self,
+        s: &StructSchema,
+        v: &mut V,
+    ) -> Result<(), LBSError> {
+        crate::read::read_packed_bools(self.r, |id, value| {
+            let name = s
+                .fields
+                .iter()
+                .find(|f| f.id == id)
+                .map_or("", |f| f.name.as_str());
+
+            if v.on_field_start(id, name) {
+                v.on_bool(value);
+                v.on_field_end(id);
+            }
+        })
+    }
+}
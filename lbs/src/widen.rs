@@ -0,0 +1,47 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Codec of `#[lbs(widen(<type>, <u16>))]` fields, which were written as narrower integer `N`
+/// before schema version `SINCE`.
+///
+/// Decoders and encoders for an older version, see `Decoder::for_version()`, read and write
+/// the narrow integer, others use the declared type. Values, which don't fit into the narrow
+/// integer, fail with `LBSError::OutOfRange` when written for an older version.
+pub struct Widen<N, const SINCE: u16>(PhantomData<N>);
+
+impl<N, const SINCE: u16> Widen<N, SINCE> {
+    #[inline]
+    pub fn write<T, W>(value: &T, w: &mut W, e: &mut Encoder) -> Result<(), LBSError>
+    where
+        T: LBSWrite + Copy + TryInto<N>,
+        N: LBSWrite,
+        W: Write,
+    {
+        match e.version() {
+            Some(version) if version < SINCE => (*value)
+                .try_into()
+                .map_err(|_| LBSError::OutOfRange(std::any::type_name::<N>()))?
+                .lbs_write_with(w, e),
+            _ => value.lbs_write_with(w, e),
+        }
+    }
+
+    #[inline]
+    pub fn read<T, R>(r: &mut R, d: &mut Decoder) -> Result<T, LBSError>
+    where
+        T: LBSRead + From<N>,
+        N: LBSRead,
+        R: Read,
+    {
+        match d.version() {
+            Some(version) if version < SINCE => Ok(N::lbs_read_with(r, d)?.into()),
+            _ => T::lbs_read_with(r, d),
+        }
+    }
+}
@@ -1,29 +1,129 @@
+use crate::encoder::Encoder;
 use crate::error::LBSError;
+use crate::vectored::VectoredWriter;
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::convert::Infallible;
 use std::convert::TryInto;
+use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::io::Error;
 use std::io::ErrorKind;
 use std::io::Write;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::Ipv6Addr;
+use std::num::NonZeroI128;
+use std::num::NonZeroI16;
+use std::num::NonZeroI32;
+use std::num::NonZeroI64;
+use std::num::NonZeroI8;
+use std::num::NonZeroIsize;
+use std::num::NonZeroU128;
+use std::num::NonZeroU16;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
+use std::num::Saturating;
+use std::num::Wrapping;
+use std::ops::Bound;
+use std::ops::ControlFlow;
 use std::ops::Range;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
 use std::time::SystemTime;
 
 pub trait LBSWrite {
     fn lbs_write<W: std::io::Write>(&self, w: &mut W) -> Result<(), LBSError>;
 
+    /// Same as `lbs_write()`, but applies encoding options.
+    /// Containers must propagate this call to their elements.
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, _e: &mut Encoder) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+
     #[inline]
     fn lbs_must_write(&self) -> bool {
         true
     }
+
+    /// Number of bytes `lbs_write()` is expected to produce, so buffers may be allocated once.
+    /// Exact for built-in types and an upper bound for derived ones, except their fields with
+    /// custom encoding, which aren't counted. Types without a hint return 0.
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        0
+    }
+
+    /// Same as `lbs_write()`, but writes only struct fields with given ids, so a value may be
+    /// sent as a patch of its changed fields, which receiver applies with `lbs_merge()`.
+    /// Types other than structs are written as a whole.
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, _fields: &[u16]) -> Result<(), LBSError> {
+        self.lbs_write(w)
+    }
+
+    /// Sum of hints of a slice of values. Allows fixed-size types to skip iterating.
+    #[inline]
+    fn lbs_size_hint_slice(slice: &[Self]) -> usize
+    where
+        Self: Sized,
+    {
+        slice.iter().map(|e| e.lbs_size_hint()).sum()
+    }
+
+    /// Same as `lbs_write()`, but allows large byte slices to be borrowed instead of copied.
+    /// Containers must propagate this call to their elements.
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.lbs_write(v)
+    }
+
+    /// Writes elements of a slice, but not its length.
+    /// Allows `[u8]` to be written with a single `write_all()`.
+    #[inline]
+    fn lbs_write_slice_with<W: Write>(
+        slice: &[Self],
+        w: &mut W,
+        e: &mut Encoder,
+    ) -> Result<(), LBSError>
+    where
+        Self: Sized,
+    {
+        for element in slice {
+            element.lbs_write_with(w, e)?;
+        }
+        Ok(())
+    }
+
+    /// Writes slice of values into `VectoredWriter`.
+    /// Allows `[u8]` to be written as a single borrowed slice.
+    #[inline]
+    fn lbs_write_slice_vectored<'v>(
+        slice: &'v [Self],
+        v: &mut VectoredWriter<'v>,
+    ) -> Result<(), LBSError>
+    where
+        Self: Sized,
+    {
+        for e in slice {
+            e.lbs_write_vectored(v)?;
+        }
+        Ok(())
+    }
 }
 
 macro_rules! impl_write_primitive {
@@ -33,11 +133,55 @@ macro_rules! impl_write_primitive {
             fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
                 Ok(w.write_all(&self.to_le_bytes())?)
             }
+
+            #[inline]
+            fn lbs_size_hint(&self) -> usize {
+                size_of::<$t>()
+            }
+
+            #[inline]
+            fn lbs_size_hint_slice(slice: &[Self]) -> usize {
+                size_of_val(slice)
+            }
         }
     };
 }
 
-impl_write_primitive!(u8);
+impl LBSWrite for u8 {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        Ok(w.write_all(&self.to_le_bytes())?)
+    }
+
+    #[inline]
+    fn lbs_write_slice_with<W: Write>(
+        slice: &[Self],
+        w: &mut W,
+        _e: &mut Encoder,
+    ) -> Result<(), LBSError> {
+        Ok(w.write_all(slice)?)
+    }
+
+    #[inline]
+    fn lbs_write_slice_vectored<'v>(
+        slice: &'v [Self],
+        v: &mut VectoredWriter<'v>,
+    ) -> Result<(), LBSError> {
+        v.write_borrowed(slice);
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn lbs_size_hint_slice(slice: &[Self]) -> usize {
+        slice.len()
+    }
+}
+
 impl_write_primitive!(u16);
 impl_write_primitive!(u32);
 impl_write_primitive!(u64);
@@ -51,8 +195,75 @@ impl_write_primitive!(i64);
 impl_write_primitive!(isize);
 impl_write_primitive!(i128);
 
-impl_write_primitive!(f32);
-impl_write_primitive!(f64);
+macro_rules! impl_write_non_zero {
+    ($($t:ty),*) => {
+        $(
+            /// Written as the underlying integer.
+            impl LBSWrite for $t {
+                #[inline]
+                fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    self.get().lbs_write(w)
+                }
+
+                #[inline]
+                fn lbs_size_hint(&self) -> usize {
+                    size_of::<$t>()
+                }
+
+                #[inline]
+                fn lbs_size_hint_slice(slice: &[Self]) -> usize {
+                    size_of_val(slice)
+                }
+            }
+        )*
+    };
+}
+
+impl_write_non_zero!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
+    NonZeroU128
+);
+impl_write_non_zero!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroIsize,
+    NonZeroI128
+);
+
+macro_rules! impl_write_float {
+    ($t:ident) => {
+        impl LBSWrite for $t {
+            #[inline]
+            fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                Ok(w.write_all(&self.to_le_bytes())?)
+            }
+
+            #[inline]
+            fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+                e.$t(*self).lbs_write(w)
+            }
+
+            #[inline]
+            fn lbs_size_hint(&self) -> usize {
+                size_of::<$t>()
+            }
+
+            #[inline]
+            fn lbs_size_hint_slice(slice: &[Self]) -> usize {
+                size_of_val(slice)
+            }
+        }
+    };
+}
+
+impl_write_float!(f32);
+impl_write_float!(f64);
 
 impl LBSWrite for () {
     #[inline]
@@ -64,17 +275,50 @@ impl LBSWrite for () {
 impl<T1: LBSWrite, T2: LBSWrite> LBSWrite for (T1, T2) {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.0.lbs_write(w)?;
-        self.1.lbs_write(w)
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.0.lbs_write_with(w, e)?;
+        self.1.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.0.lbs_write_vectored(v)?;
+        self.1.lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.0.lbs_size_hint() + self.1.lbs_size_hint()
     }
 }
 
 impl<T1: LBSWrite, T2: LBSWrite, T3: LBSWrite> LBSWrite for (T1, T2, T3) {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.0.lbs_write(w)?;
-        self.1.lbs_write(w)?;
-        self.2.lbs_write(w)
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.0.lbs_write_with(w, e)?;
+        self.1.lbs_write_with(w, e)?;
+        self.2.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.0.lbs_write_vectored(v)?;
+        self.1.lbs_write_vectored(v)?;
+        self.2.lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.0.lbs_size_hint() + self.1.lbs_size_hint() + self.2.lbs_size_hint()
     }
 }
 
@@ -87,6 +331,16 @@ impl LBSWrite for bool {
             (0_u8).lbs_write(w)
         }
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn lbs_size_hint_slice(slice: &[Self]) -> usize {
+        slice.len()
+    }
 }
 
 impl LBSWrite for char {
@@ -94,6 +348,11 @@ impl LBSWrite for char {
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
         (*self as u32).lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4
+    }
 }
 
 impl LBSWrite for str {
@@ -102,6 +361,23 @@ impl LBSWrite for str {
         write_len(w, self.len())?;
         Ok(w.write_all(self.as_bytes())?)
     }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        e.write_str(self, w)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        v.write_borrowed(self.as_bytes());
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.len()
+    }
 }
 
 impl LBSWrite for String {
@@ -109,6 +385,81 @@ impl LBSWrite for String {
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
         self.as_str().lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        e.write_str(self, w)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_str().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.len()
+    }
+}
+
+/// Written as a string, so only valid UTF-8 can be written.
+impl LBSWrite for OsStr {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        utf8_os_str(self)?.lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        utf8_os_str(self)?.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        utf8_os_str(self)?.lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.len()
+    }
+}
+
+macro_rules! impl_write_os_str {
+    ($($t:ty => $as_os_str:ident),*) => {
+        $(
+            /// Written as a string, so only valid UTF-8 can be written.
+            impl LBSWrite for $t {
+                #[inline]
+                fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    self.$as_os_str().lbs_write(w)
+                }
+
+                #[inline]
+                fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+                    self.$as_os_str().lbs_write_with(w, e)
+                }
+
+                #[inline]
+                fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+                    self.$as_os_str().lbs_write_vectored(v)
+                }
+
+                #[inline]
+                fn lbs_size_hint(&self) -> usize {
+                    self.$as_os_str().lbs_size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_write_os_str!(OsString => as_os_str, Path => as_os_str, PathBuf => as_os_str);
+
+#[inline]
+fn utf8_os_str(s: &OsStr) -> Result<&str, LBSError> {
+    s.to_str()
+        .ok_or_else(|| LBSError::Parsing(format!("{:?} is not valid UTF-8", s)))
 }
 
 impl LBSWrite for Duration {
@@ -117,15 +468,33 @@ impl LBSWrite for Duration {
         self.as_secs().lbs_write(w)?;
         self.subsec_nanos().lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        12
+    }
 }
 
+/// Nanoseconds never exceed 30 bits, so the highest bit marks time before the UNIX epoch.
+/// Time after the epoch is encoded the same way as `Duration` since the epoch.
+pub(crate) const BEFORE_EPOCH_FLAG: u32 = 1 << 31;
+
 impl LBSWrite for SystemTime {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        let dur = self
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map_err(|err| Error::new(ErrorKind::Other, err))?;
-        dur.lbs_write(w)
+        match self.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(dur) => dur.lbs_write(w),
+            Err(err) => {
+                let dur = err.duration();
+                dur.as_secs().lbs_write(w)?;
+                (dur.subsec_nanos() | BEFORE_EPOCH_FLAG).lbs_write(w)
+            }
+        }
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        12
     }
 }
 
@@ -135,6 +504,11 @@ impl LBSWrite for Ipv4Addr {
         let num: u32 = (*self).into();
         num.lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4
+    }
 }
 
 impl LBSWrite for Ipv6Addr {
@@ -143,6 +517,11 @@ impl LBSWrite for Ipv6Addr {
         let num: u128 = (*self).into();
         num.lbs_write(w)
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        16
+    }
 }
 
 impl LBSWrite for IpAddr {
@@ -159,133 +538,924 @@ impl LBSWrite for IpAddr {
             }
         }
     }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        match self {
+            IpAddr::V4(_) => 1 + 4,
+            IpAddr::V6(_) => 1 + 16,
+        }
+    }
 }
 
 impl<T: LBSWrite + PartialOrd> LBSWrite for Range<T> {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.start.lbs_write(w)?;
-        self.end.lbs_write(w)
+        self.lbs_write_with(w, &mut Encoder::new())
     }
-}
 
-impl<T: LBSWrite> LBSWrite for Box<T> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.as_ref().lbs_write(w)
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.start.lbs_write_with(w, e)?;
+        self.end.lbs_write_with(w, e)
     }
-}
 
-impl<T: LBSWrite> LBSWrite for Rc<T> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.as_ref().lbs_write(w)
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.start.lbs_write_vectored(v)?;
+        self.end.lbs_write_vectored(v)
     }
-}
 
-impl<T: LBSWrite> LBSWrite for Arc<T> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.as_ref().lbs_write(w)
+    fn lbs_size_hint(&self) -> usize {
+        self.start.lbs_size_hint() + self.end.lbs_size_hint()
     }
 }
 
-impl<'a, T: LBSWrite + ToOwned + ?Sized> LBSWrite for Cow<'a, T> {
+/// Written as `Range<T>`. Whether iteration over the range was exhausted isn't written.
+impl<T: LBSWrite> LBSWrite for RangeInclusive<T> {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.as_ref().lbs_write(w)
+        self.lbs_write_with(w, &mut Encoder::new())
     }
-}
 
-impl<T: LBSWrite> LBSWrite for Option<T> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        if let Some(v) = self {
-            (1_u8).lbs_write(w)?;
-            v.lbs_write(w)
-        } else {
-            (0_u8).lbs_write(w)
-        }
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.start().lbs_write_with(w, e)?;
+        self.end().lbs_write_with(w, e)
     }
 
     #[inline]
-    fn lbs_must_write(&self) -> bool {
-        self.is_some()
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.start().lbs_write_vectored(v)?;
+        self.end().lbs_write_vectored(v)
     }
-}
 
-impl<T: LBSWrite> LBSWrite for [T] {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        write_len(w, self.len())?;
-        for e in self {
-            e.lbs_write(w)?;
-        }
-        Ok(())
+    fn lbs_size_hint(&self) -> usize {
+        self.start().lbs_size_hint() + self.end().lbs_size_hint()
     }
 }
 
-impl<T: LBSWrite> LBSWrite for Vec<T> {
+/// Written as a derived enum with `Included` under id 0, `Excluded` under id 1 and
+/// `Unbounded` under id 2.
+impl<T: LBSWrite> LBSWrite for Bound<T> {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        self.as_slice().lbs_write(w)
+        self.lbs_write_with(w, &mut Encoder::new())
     }
-}
 
-impl<K: LBSWrite, V: LBSWrite, S> LBSWrite for HashMap<K, V, S> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        write_len(w, self.len())?;
-        for (k, v) in self {
-            k.lbs_write(w)?;
-            v.lbs_write(w)?;
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        match self {
+            Self::Included(v) => {
+                write_field_id(w, 0)?;
+                v.lbs_write_with(w, e)
+            }
+            Self::Excluded(v) => {
+                write_field_id(w, 1)?;
+                v.lbs_write_with(w, e)
+            }
+            Self::Unbounded => write_field_id(w, 2),
         }
-        Ok(())
     }
-}
 
-impl<T: LBSWrite, S> LBSWrite for HashSet<T, S> {
     #[inline]
-    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        write_len(w, self.len())?;
-        for e in self {
-            e.lbs_write(w)?;
+    fn lbs_size_hint(&self) -> usize {
+        match self {
+            Self::Included(v) | Self::Excluded(v) => 2 + v.lbs_size_hint(),
+            Self::Unbounded => 2,
         }
-        Ok(())
     }
 }
 
-impl<K: LBSWrite, V: LBSWrite> LBSWrite for BTreeMap<K, V> {
+impl<T: LBSWrite + ?Sized> LBSWrite for &T {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        write_len(w, self.len())?;
-        for (k, v) in self {
-            k.lbs_write(w)?;
-            v.lbs_write(w)?;
-        }
-        Ok(())
+        (**self).lbs_write(w)
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        (**self).lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        (**self).lbs_must_write()
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        (**self).lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        (**self).lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, fields: &[u16]) -> Result<(), LBSError> {
+        (**self).lbs_write_fields(w, fields)
     }
 }
 
-impl<T: LBSWrite> LBSWrite for BTreeSet<T> {
+impl<T: LBSWrite + ?Sized> LBSWrite for Box<T> {
     #[inline]
     fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
-        write_len(w, self.len())?;
-        for e in self {
-            e.lbs_write(w)?;
-        }
-        Ok(())
+        self.lbs_write_with(w, &mut Encoder::new())
     }
-}
 
-#[inline]
-pub fn write_field_count<W: Write>(w: &mut W, count: u16) -> Result<(), LBSError> {
-    count.lbs_write(w)
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_ref().lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, fields: &[u16]) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_fields(w, fields)
+    }
 }
 
-#[inline]
-pub fn write_field_id<W: Write>(w: &mut W, id: u16) -> Result<(), LBSError> {
-    id.lbs_write(w)
+impl<T: LBSWrite + ?Sized> LBSWrite for Rc<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_ref().lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, fields: &[u16]) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_fields(w, fields)
+    }
+}
+
+impl<T: LBSWrite + ?Sized> LBSWrite for Arc<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_ref().lbs_size_hint()
+    }
+
+    #[inline]
+    fn lbs_write_fields<W: Write>(&self, w: &mut W, fields: &[u16]) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_fields(w, fields)
+    }
+}
+
+/// Ordering and arithmetic adapters are written as their values.
+macro_rules! impl_write_newtype {
+    ($($t:ident),*) => {
+        $(
+            impl<T: LBSWrite> LBSWrite for $t<T> {
+                #[inline]
+                fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+                    self.0.lbs_write(w)
+                }
+
+                #[inline]
+                fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+                    self.0.lbs_write_with(w, e)
+                }
+
+                #[inline]
+                fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+                    self.0.lbs_write_vectored(v)
+                }
+
+                #[inline]
+                fn lbs_size_hint(&self) -> usize {
+                    self.0.lbs_size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_write_newtype!(Reverse, Wrapping, Saturating);
+
+impl<'a, T: LBSWrite + ToOwned + ?Sized> LBSWrite for Cow<'a, T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_ref().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_ref().lbs_size_hint()
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for Option<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        if let Some(v) = self {
+            (1_u8).lbs_write(w)?;
+            v.lbs_write_with(w, e)
+        } else {
+            (0_u8).lbs_write(w)
+        }
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.is_some()
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        if let Some(value) = self {
+            (1_u8).lbs_write(v)?;
+            value.lbs_write_vectored(v)
+        } else {
+            (0_u8).lbs_write(v)
+        }
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        1 + self.as_ref().map_or(0, |v| v.lbs_size_hint())
+    }
+}
+
+/// Written as a derived enum with `Continue` under id 0 and `Break` under id 1.
+impl<B: LBSWrite, C: LBSWrite> LBSWrite for ControlFlow<B, C> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        match self {
+            Self::Continue(v) => {
+                write_field_id(w, 0)?;
+                v.lbs_write_with(w, e)
+            }
+            Self::Break(v) => {
+                write_field_id(w, 1)?;
+                v.lbs_write_with(w, e)
+            }
+        }
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        match self {
+            Self::Continue(v) => 2 + v.lbs_size_hint(),
+            Self::Break(v) => 2 + v.lbs_size_hint(),
+        }
+    }
+}
+
+/// Written as a derived enum with `Ok` under id 0 and `Err` under id 1.
+impl<T: LBSWrite, E: LBSWrite> LBSWrite for Result<T, E> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        match self {
+            Ok(v) => {
+                write_field_id(w, 0)?;
+                v.lbs_write_with(w, e)
+            }
+            Err(v) => {
+                write_field_id(w, 1)?;
+                v.lbs_write_with(w, e)
+            }
+        }
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        match self {
+            Ok(v) => 2 + v.lbs_size_hint(),
+            Err(v) => 2 + v.lbs_size_hint(),
+        }
+    }
+}
+
+/// Never written, as it has no values. Lets never types be used as type parameters,
+/// like in `Result<T, Infallible>` or `ControlFlow<Infallible, T>`.
+impl LBSWrite for Infallible {
+    #[inline]
+    fn lbs_write<W: Write>(&self, _: &mut W) -> Result<(), LBSError> {
+        match *self {}
+    }
+}
+
+/// Written as `Option<T>`. Uninitialized cell isn't written as a struct field, so such
+/// fields should be marked with `#[lbs(optional)]`.
+impl<T: LBSWrite> LBSWrite for OnceLock<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.get().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.get().is_some()
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.get().lbs_size_hint()
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for [T] {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        T::lbs_write_slice_with(self, w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        T::lbs_write_slice_vectored(self, v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + T::lbs_size_hint_slice(self)
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for Vec<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_slice().lbs_size_hint()
+    }
+}
+
+/// Written as a slice, so arrays and vectors are interchangeable.
+impl<T: LBSWrite, const N: usize> LBSWrite for [T; N] {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_slice().lbs_size_hint()
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for VecDeque<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        let (front, back) = self.as_slices();
+        write_len(w, self.len())?;
+        T::lbs_write_slice_with(front, w, e)?;
+        T::lbs_write_slice_with(back, w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        let (front, back) = self.as_slices();
+        write_len(v, self.len())?;
+        T::lbs_write_slice_vectored(front, v)?;
+        T::lbs_write_slice_vectored(back, v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        let (front, back) = self.as_slices();
+        4 + T::lbs_size_hint_slice(front) + T::lbs_size_hint_slice(back)
+    }
+}
+
+impl<K: LBSWrite, V: LBSWrite, S> LBSWrite for HashMap<K, V, S> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for (k, v) in self {
+            k.lbs_write_with(w, e)?;
+            v.lbs_write_with(w, e)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        for (key, value) in self {
+            key.lbs_write_vectored(v)?;
+            value.lbs_write_vectored(v)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self
+            .iter()
+            .map(|(k, v)| k.lbs_size_hint() + v.lbs_size_hint())
+            .sum::<usize>()
+    }
+}
+
+impl<T: LBSWrite, S> LBSWrite for HashSet<T, S> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for element in self {
+            element.lbs_write_with(w, e)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        for e in self {
+            e.lbs_write_vectored(v)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.iter().map(|e| e.lbs_size_hint()).sum::<usize>()
+    }
+}
+
+impl<K: LBSWrite, V: LBSWrite> LBSWrite for BTreeMap<K, V> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for (k, v) in self {
+            k.lbs_write_with(w, e)?;
+            v.lbs_write_with(w, e)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        for (key, value) in self {
+            key.lbs_write_vectored(v)?;
+            value.lbs_write_vectored(v)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self
+            .iter()
+            .map(|(k, v)| k.lbs_size_hint() + v.lbs_size_hint())
+            .sum::<usize>()
+    }
+}
+
+/// Heap is written in its internal order, which is a valid heap on read.
+impl<T: LBSWrite> LBSWrite for BinaryHeap<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        self.as_slice().lbs_write_vectored(v)
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        self.as_slice().lbs_size_hint()
+    }
+}
+
+impl<T: LBSWrite> LBSWrite for BTreeSet<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        write_len(w, self.len())?;
+        for element in self {
+            element.lbs_write_with(w, e)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_write_vectored<'v>(&'v self, v: &mut VectoredWriter<'v>) -> Result<(), LBSError> {
+        write_len(v, self.len())?;
+        for e in self {
+            e.lbs_write_vectored(v)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lbs_size_hint(&self) -> usize {
+        4 + self.iter().map(|e| e.lbs_size_hint()).sum::<usize>()
+    }
+}
+
+#[inline]
+pub fn write_field_count<W: Write>(w: &mut W, count: u16) -> Result<(), LBSError> {
+    count.lbs_write(w)
+}
+
+#[inline]
+pub fn write_field_id<W: Write>(w: &mut W, id: u16) -> Result<(), LBSError> {
+    id.lbs_write(w)
+}
+
+/// Writes field count of a struct with compact header.
+#[inline]
+pub fn write_compact_field_count<W: Write>(w: &mut W, count: u16) -> Result<(), LBSError> {
+    crate::varint::write_u16(w, count)
+}
+
+/// Writes field id of a struct with compact header, as a delta from the previous one.
+/// Fields must be written in ascending id order.
+#[inline]
+pub fn write_compact_field_id<W: Write>(
+    w: &mut W,
+    prev: &mut u16,
+    id: u16,
+) -> Result<(), LBSError> {
+    let delta = id
+        .checked_sub(*prev)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "field ids must be ascending"))?;
+    crate::varint::write_u16(w, delta)?;
+    *prev = id;
+    Ok(())
+}
+
+/// Writes bools of a struct with packed bools as a bitset.
+/// Bit `i` corresponds to a field with id `base + i`.
+#[inline]
+pub fn write_packed_bools<W: Write>(w: &mut W, base: u16, bits: &[bool]) -> Result<(), LBSError> {
+    let count: u16 = bits
+        .len()
+        .try_into()
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "too many packed bools"))?;
+
+    base.lbs_write(w)?;
+    count.lbs_write(w)?;
+
+    for chunk in bits.chunks(8) {
+        let byte = chunk
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (i, bit)| byte | ((*bit as u8) << i));
+        byte.lbs_write(w)?;
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn write_with<T: LBSWrite + ?Sized, W: Write>(
+    value: &T,
+    w: &mut W,
+    e: &mut Encoder,
+) -> Result<(), LBSError> {
+    value.lbs_write_with(w, e)
+}
+
+/// Writes `len` elements of iterator the same way as a slice, so they may be read as a `Vec`.
+/// Fails if iterator yields another number of elements, see also `LBSWriteSeq`.
+#[inline]
+pub fn write_seq<W, I>(w: &mut W, len: usize, iter: I) -> Result<(), LBSError>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: LBSWrite,
+{
+    write_seq_with(w, &mut Encoder::new(), len, iter)
+}
+
+/// Same as `write_seq()`, but applies encoding options.
+pub fn write_seq_with<W, I>(w: &mut W, e: &mut Encoder, len: usize, iter: I) -> Result<(), LBSError>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: LBSWrite,
+{
+    write_len(w, len)?;
+    let mut written = 0;
+
+    for element in iter {
+        if written == len {
+            return Err(seq_len_mismatch(len));
+        }
+
+        element.lbs_write_with(w, e)?;
+        written += 1;
+    }
+
+    match written == len {
+        true => Ok(()),
+        false => Err(seq_len_mismatch(len)),
+    }
+}
+
+#[inline]
+fn seq_len_mismatch(len: usize) -> LBSError {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("sequence doesn't have {} elements", len),
+    )
+    .into()
+}
+
+/// Field of a collection, which is written directly from an iterator of known length,
+/// like a database cursor, and may be read as a `Vec`.
+///
+/// Iterator is consumed by the first write, so writing it again fails.
+pub struct LBSWriteSeq<I> {
+    len: usize,
+    iter: std::cell::Cell<Option<I>>,
+}
+
+impl<I> LBSWriteSeq<I>
+where
+    I: Iterator,
+    I::Item: LBSWrite,
+{
+    #[inline]
+    pub fn new<T: IntoIterator<IntoIter = I>>(len: usize, iter: T) -> Self {
+        Self {
+            len,
+            iter: std::cell::Cell::new(Some(iter.into_iter())),
+        }
+    }
+
+    /// Iterator of exact size.
+    #[inline]
+    pub fn exact<T>(iter: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I>,
+        I: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        Self::new(iter.len(), iter)
+    }
+}
+
+impl<I> LBSWrite for LBSWriteSeq<I>
+where
+    I: Iterator,
+    I::Item: LBSWrite,
+{
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        let iter = self.iter.take().ok_or_else(|| {
+            LBSError::from(Error::new(
+                ErrorKind::InvalidInput,
+                "sequence is already written",
+            ))
+        })?;
+        write_seq_with(w, e, self.len, iter)
+    }
+}
+
+impl<I> std::fmt::Debug for LBSWriteSeq<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LBSWriteSeq")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// Number of bytes, which value is encoded into. Value is encoded without storing the output.
+#[inline]
+pub fn encoded_len<T: LBSWrite + ?Sized>(value: &T) -> Result<usize, LBSError> {
+    let mut w = CountingWriter::new(std::io::sink());
+    value.lbs_write(&mut w)?;
+    Ok(w.count())
+}
+
+/// Adapter, which counts bytes written through it, like to enforce per-message byte budgets.
+#[derive(Debug, Default)]
+pub struct CountingWriter<W> {
+    inner: W,
+    count: usize,
+}
+
+impl<W> CountingWriter<W> {
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// Number of bytes written so far.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Whether values are encoded into the same bytes, like large ones without `PartialEq`
+/// or with a slow one. Encoding of `a` is buffered, and encoding of `b` is compared with it
+/// while being written, so it stops at the first difference.
+pub fn eq_encoded<A, B>(a: &A, b: &B) -> Result<bool, LBSError>
+where
+    A: LBSWrite + ?Sized,
+    B: LBSWrite + ?Sized,
+{
+    let mut expected = Vec::with_capacity(a.lbs_size_hint());
+    a.lbs_write(&mut expected)?;
+
+    let mut w = ComparingWriter {
+        expected: &expected,
+        differs: false,
+    };
+
+    match b.lbs_write(&mut w) {
+        Ok(()) => Ok(w.expected.is_empty()),
+        Err(_) if w.differs => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writer, which fails as soon as bytes written differ from expected ones.
+struct ComparingWriter<'a> {
+    /// Bytes, which weren't compared yet.
+    expected: &'a [u8],
+    differs: bool,
+}
+
+impl Write for ComparingWriter<'_> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.expected.strip_prefix(buf) {
+            Some(rest) => {
+                self.expected = rest;
+                Ok(buf.len())
+            }
+            None => {
+                self.differs = true;
+                Err(Error::other("encodings differ"))
+            }
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 #[inline]
@@ -0,0 +1,118 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::error::LBSError;
+use crate::schema::LBSSchema;
+use crate::schema::Schema;
+use crate::size::LBSMaxSize;
+use crate::LBSRead;
+use crate::LBSWrite;
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use zeroize::Zeroize;
+use zeroize::ZeroizeOnDrop;
+
+/// Value, like a password or a key, which is zeroized when dropped.
+///
+/// Wire format is the same as of `T`. After the value is read or skipped, scratch buffer of
+/// the decoder is zeroized too, so staged bytes don't linger in decoder memory. Strings shared
+/// or interned by the decoder are copies out of reach, so such options shouldn't be enabled
+/// for sensitive input. `Debug` doesn't reveal the value.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Sensitive<T: Zeroize>(T);
+
+impl<T: Zeroize> Sensitive<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> From<T> for Sensitive<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize> Deref for Sensitive<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Sensitive<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for Sensitive<T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Sensitive<T> {}
+
+impl<T: Zeroize> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sensitive(..)")
+    }
+}
+
+impl<T: Zeroize + LBSWrite> LBSWrite for Sensitive<T> {
+    #[inline]
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        self.lbs_write_with(w, &mut Encoder::new())
+    }
+
+    #[inline]
+    fn lbs_write_with<W: Write>(&self, w: &mut W, e: &mut Encoder) -> Result<(), LBSError> {
+        self.0.lbs_write_with(w, e)
+    }
+
+    #[inline]
+    fn lbs_must_write(&self) -> bool {
+        self.0.lbs_must_write()
+    }
+}
+
+impl<T: Zeroize + LBSRead> LBSRead for Sensitive<T> {
+    #[inline]
+    fn lbs_read<R: Read>(r: &mut R) -> Result<Self, LBSError> {
+        Self::lbs_read_with(r, &mut Decoder::new())
+    }
+
+    #[inline]
+    fn lbs_read_with<R: Read>(r: &mut R, d: &mut Decoder) -> Result<Self, LBSError> {
+        let value = T::lbs_read_with(r, d).map(Self);
+        d.zeroize_scratch();
+        value
+    }
+
+    #[inline]
+    fn lbs_skip<R: Read>(r: &mut R, d: &mut Decoder) -> Result<(), LBSError> {
+        let result = T::lbs_skip(r, d);
+        d.zeroize_scratch();
+        result
+    }
+}
+
+impl<T: Zeroize + LBSSchema> LBSSchema for Sensitive<T> {
+    #[inline]
+    fn lbs_schema() -> Schema {
+        T::lbs_schema()
+    }
+}
+
+impl<T: Zeroize + LBSMaxSize> LBSMaxSize for Sensitive<T> {
+    const LBS_MAX_SIZE: usize = T::LBS_MAX_SIZE;
+}
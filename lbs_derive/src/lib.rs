@@ -1,21 +1,26 @@
+use proc_macro2::Literal;
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
+use proc_macro2::TokenTree;
+use quote::format_ident;
 use quote::quote;
 use quote::quote_spanned;
 use quote::ToTokens;
+use std::collections::BTreeMap;
 use std::collections::HashSet;
 use syn::parenthesized;
 use syn::parse::ParseBuffer;
 use syn::parse_macro_input;
 use syn::parse_quote;
 use syn::spanned::Spanned;
+use syn::Attribute;
 use syn::Data;
 use syn::DataEnum;
+use syn::DataStruct;
 use syn::DeriveInput;
 use syn::Expr;
 use syn::Field;
 use syn::Fields;
-use syn::FieldsNamed;
 use syn::GenericParam;
 use syn::Generics;
 use syn::LitInt;
@@ -31,6 +36,47 @@ const ARGUMENT_ID: &str = "id";
 const ARGUMENT_DEFAULT: &str = "default";
 const ARGUMENT_SKIP: &str = "skip";
 const ARGUMENT_OPTIONAL: &str = "optional";
+const ARGUMENT_PARALLEL: &str = "parallel";
+const ARGUMENT_SPARSE: &str = "sparse";
+const ARGUMENT_UTF8: &str = "utf8";
+const ARGUMENT_COMPACT: &str = "compact";
+const ARGUMENT_DYN_IO: &str = "dyn_io";
+const ARGUMENT_PACK_BOOLS: &str = "pack_bools";
+const ARGUMENT_VERSION: &str = "version";
+const ARGUMENT_INDEXED: &str = "indexed";
+const ARGUMENT_CTX: &str = "ctx";
+const ARGUMENT_CRATE: &str = "crate";
+const ARGUMENT_DENY_GAPS: &str = "deny_gaps";
+const ARGUMENT_ONEOF: &str = "oneof";
+const ARGUMENT_SINCE: &str = "since";
+const ARGUMENT_REMOVED: &str = "removed";
+const ARGUMENT_APPEND: &str = "append";
+const ARGUMENT_FROZEN: &str = "frozen";
+const ARGUMENT_FIXED_POINT: &str = "fixed_point";
+const ARGUMENT_OTHER: &str = "other";
+const ARGUMENT_WIDEN: &str = "widen";
+const ARGUMENT_UNIT: &str = "unit";
+const ARGUMENT_DELTA_KEYS: &str = "delta_keys";
+const ARGUMENT_COMPACT_DURATION: &str = "compact_duration";
+const ARGUMENT_VARINT: &str = "varint";
+const ARGUMENT_RELATIVE_TIME: &str = "relative_time";
+const ARGUMENT_DELIMITED: &str = "delimited";
+const ARGUMENT_EXPOSE_SECRETS: &str = "expose_secrets";
+const ARGUMENT_UNKNOWN_FIELDS: &str = "unknown_fields";
+const ARGUMENT_READ_AS: &str = "read_as";
+const ARGUMENT_ORDER: &str = "order";
+const ARGUMENT_AUTO_ID: &str = "auto_id";
+const ARGUMENT_RESERVED: &str = "reserved";
+const ARGUMENT_DEPRECATED_ID: &str = "deprecated_id";
+const ARGUMENT_NOTE: &str = "note";
+const ARGUMENT_REMOTE: &str = "remote";
+const ARGUMENT_WITH: &str = "with";
+const ARGUMENT_DROPPABLE: &str = "droppable";
+const ARGUMENT_PRIORITY: &str = "priority";
+const ARGUMENT_TRY_FROM: &str = "try_from";
+const ARGUMENT_ERROR: &str = "error";
+const UNITS: [&str; 4] = ["secs", "millis", "micros", "nanos"];
+const PACKED_BOOLS_ID: u16 = u16::MAX;
 
 //
 // Types.
@@ -38,34 +84,445 @@ const ARGUMENT_OPTIONAL: &str = "optional";
 
 struct Meta {
     id: Option<u16>,
-    name: syn::Ident,
+    /// Field name or index, or variant name.
+    name: syn::Member,
+    /// Position among fields of a struct or variant.
+    index: usize,
+    ty: Option<syn::Type>,
     default: Option<TokenStream>,
     variant_fields: Option<Fields>,
     required: bool,
     skip: bool,
+    parallel: bool,
+    codec: Option<TokenStream>,
+    packed: bool,
+    ctx: bool,
+    /// Group of mutually exclusive optional fields.
+    oneof: Option<syn::Ident>,
+    /// Schema versions, in which field is present: since one and until removed one.
+    since: Option<u16>,
+    removed: Option<u16>,
+    /// Collection, which is extended rather than replaced on merge.
+    append: bool,
+    /// Variant, which keeps id and payload of unknown variants.
+    other: bool,
+    /// Former ids of a renumbered variant, which are still read as it.
+    read_as: Vec<u16>,
+    /// Value preceded by its length, which is skipped without decoding.
+    delimited: bool,
+    /// `secrecy` secret, which is written only if container exposes secrets.
+    secret: bool,
+    /// `UnknownFields`, which keeps fields unknown to the struct.
+    unknown_fields: bool,
+    /// Note on a retired field, which is reported whenever the field is read.
+    deprecated: Option<String>,
+    /// Value of a foreign type, which is written and read by the given mirror.
+    remote: bool,
+    /// Priority of an optional field, which is omitted when encoding exceeds a byte budget.
+    droppable: Option<u8>,
+    /// Proxy type, which the field is converted from on read, and message of failed conversion.
+    try_from: Option<(syn::Type, Option<String>)>,
     span: Span,
 }
 
+/// Fields, which generated bodies access.
+#[derive(Clone, Copy)]
+enum Place<'a> {
+    /// Fields of `self`.
+    Struct,
+    /// Fields of the given enum variant, which are bound by reference on write
+    /// and are read into a tuple, which the variant is built from.
+    Variant(&'a syn::Member),
+}
+
+/// Write method, which generated body propagates to fields.
+struct WriteMethod {
+    /// Method call with arguments, like `lbs_write_with(w, e)`.
+    call: TokenStream,
+    /// Encoder expression for codecs.
+    encoder: TokenStream,
+    /// Only struct fields with ids listed in `fields` are written.
+    filtered: bool,
+}
+
+struct ContainerMeta {
+    compact: bool,
+    dyn_io: bool,
+    pack_bools: bool,
+    version: Option<u16>,
+    indexed: bool,
+    ctx: Option<syn::Type>,
+    /// Path to lbs, if it's re-exported or renamed.
+    krate: Option<syn::Path>,
+    deny_gaps: bool,
+    /// Schema fingerprints of frozen versions.
+    frozen: Vec<(u16, u64)>,
+    /// Secret fields may be written.
+    expose_secrets: bool,
+    /// Order of written struct fields, if it's given explicitly.
+    order: Option<FieldOrder>,
+    /// Fields and variants without ids are numbered in declaration order.
+    auto_id: bool,
+    /// Ids of deleted fields, or variants of an enum, which must never be taken again.
+    reserved: Vec<u16>,
+    /// Foreign type, which the container mirrors and is written and read in place of.
+    remote: Option<syn::Type>,
+    is_enum: bool,
+}
+
+/// Ids, which `auto_id` container gives out: each one follows the previous id and isn't
+/// reserved.
+struct AutoIds<'a> {
+    next: Option<u16>,
+    reserved: &'a [u16],
+}
+
+/// Order, in which struct fields are written.
+#[derive(Clone, Copy, PartialEq)]
+enum FieldOrder {
+    Declaration,
+    Id,
+}
+
 //
 // Implementations.
 //
 
+impl ContainerMeta {
+    fn from_attrs(attrs: &[Attribute], data: &Data) -> Self {
+        let mut meta = ContainerMeta {
+            compact: false,
+            dyn_io: false,
+            pack_bools: false,
+            version: None,
+            indexed: false,
+            ctx: None,
+            krate: None,
+            deny_gaps: false,
+            frozen: Vec::new(),
+            expose_secrets: false,
+            order: None,
+            auto_id: false,
+            reserved: Vec::new(),
+            remote: None,
+            is_enum: matches!(data, Data::Enum(_)),
+        };
+
+        attrs
+            .iter()
+            .find(|attr| attr.path().is_ident(ATTRIBUTE))
+            .map(|attr| {
+                attr.parse_nested_meta(|arg| {
+                    let arg_name = arg.path.get_ident().unwrap().to_string();
+
+                    match arg_name.as_str() {
+                        ARGUMENT_COMPACT => {
+                            meta.compact = Meta::parse_flag(arg.input, ARGUMENT_COMPACT)
+                        }
+                        ARGUMENT_DYN_IO => {
+                            meta.dyn_io = Meta::parse_flag(arg.input, ARGUMENT_DYN_IO)
+                        }
+                        ARGUMENT_PACK_BOOLS => {
+                            meta.pack_bools = Meta::parse_flag(arg.input, ARGUMENT_PACK_BOOLS)
+                        }
+                        ARGUMENT_INDEXED => {
+                            meta.indexed = Meta::parse_flag(arg.input, ARGUMENT_INDEXED)
+                        }
+                        ARGUMENT_DENY_GAPS => {
+                            meta.deny_gaps = Meta::parse_flag(arg.input, ARGUMENT_DENY_GAPS)
+                        }
+                        ARGUMENT_EXPOSE_SECRETS => {
+                            meta.expose_secrets =
+                                Meta::parse_flag(arg.input, ARGUMENT_EXPOSE_SECRETS)
+                        }
+                        ARGUMENT_VERSION => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.version = Some(
+                                content
+                                    .parse::<LitInt>()
+                                    .expect("version must be numeric")
+                                    .base10_parse()
+                                    .expect("version must fit into u16"),
+                            );
+                        }
+                        ARGUMENT_CTX => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.ctx = Some(content.parse().expect("context type expected"));
+                        }
+                        ARGUMENT_CRATE => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.krate = Some(content.parse().expect("crate path expected"));
+                        }
+                        ARGUMENT_REMOTE => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.remote = Some(content.parse().expect("remote type expected"));
+                        }
+                        ARGUMENT_FROZEN => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.frozen = Self::parse_frozen(content);
+                        }
+                        ARGUMENT_ORDER => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.order = Some(Self::parse_order(content));
+                        }
+                        ARGUMENT_AUTO_ID => {
+                            meta.auto_id = Meta::parse_flag(arg.input, ARGUMENT_AUTO_ID)
+                        }
+                        ARGUMENT_RESERVED => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.reserved = Meta::parse_ids(&content);
+                        }
+                        unknown => panic_unknown_argument(unknown),
+                    }
+
+                    Ok(())
+                })
+            });
+
+        if meta.ctx.is_some() && meta.dyn_io {
+            panic!("context can't be combined with dyn_io");
+        }
+
+        if meta.remote.is_some() && (meta.ctx.is_some() || meta.dyn_io || meta.indexed) {
+            panic!("remote container can't have context, dyn_io or index");
+        }
+
+        if meta.indexed && (meta.compact || meta.pack_bools) {
+            panic!("indexed container can't be compact or have packed bools");
+        }
+
+        if meta.order == Some(FieldOrder::Declaration) && (meta.compact || meta.indexed) {
+            panic!("compact and indexed containers write fields in ascending id order");
+        }
+
+        meta
+    }
+
+    /// Reserved ids of struct fields or enum variants, fields of variants have none.
+    fn reserved(&self, variants: bool) -> &[u16] {
+        match variants == self.is_enum {
+            true => &self.reserved,
+            false => &[],
+        }
+    }
+
+    fn auto_ids(&self, variants: bool) -> AutoIds<'_> {
+        AutoIds {
+            next: self.auto_id.then_some(0),
+            reserved: self.reserved(variants),
+        }
+    }
+
+    /// Parses `declaration` or `id`.
+    fn parse_order(input: ParseBuffer) -> FieldOrder {
+        let order: syn::Ident = input.parse().expect("field order expected");
+
+        match order.to_string().as_str() {
+            "declaration" => FieldOrder::Declaration,
+            "id" => FieldOrder::Id,
+            _ => panic!("field order must be either declaration or id"),
+        }
+    }
+
+    /// Whether struct fields are written in ascending id order, rather than in declaration order.
+    ///
+    /// Compact header encodes ids as deltas and index is searched by id, so their fields are
+    /// always sorted.
+    fn sorts_fields(&self) -> bool {
+        self.compact || self.indexed || self.order == Some(FieldOrder::Id)
+    }
+
+    /// Parses `<version> = <fingerprint>` pairs.
+    fn parse_frozen(input: ParseBuffer) -> Vec<(u16, u64)> {
+        let mut frozen = Vec::new();
+
+        while !input.is_empty() {
+            let version = Meta::parse_version(&input);
+            input
+                .parse::<Token![=]>()
+                .expect("frozen version must be followed by '= <fingerprint>'");
+            let fingerprint = input
+                .parse::<LitInt>()
+                .expect("fingerprint must be numeric")
+                .base10_parse()
+                .expect("fingerprint must fit into u64");
+
+            frozen.push((version, fingerprint));
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>().expect("',' expected");
+            }
+        }
+
+        frozen
+    }
+
+    /// Completes generated code. With custom crate path, code is wrapped into a block, where `lbs`
+    /// is an alias of that path.
+    fn finish(&self, tokens: TokenStream) -> proc_macro::TokenStream {
+        let tokens = match self.krate {
+            Some(ref krate) => quote! {
+                const _: () = {
+                    use #krate as lbs;
+                    #tokens
+                };
+            },
+            None => tokens,
+        };
+
+        proc_macro::TokenStream::from(tokens)
+    }
+
+    fn write_field_count(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::write::write_compact_field_count(w, field_count)?;),
+            false => quote!(lbs::write::write_field_count(w, field_count)?;),
+        }
+    }
+
+    fn write_field_id(&self, id: u16) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::write::write_compact_field_id(w, &mut prev_id, #id)?;),
+            false => quote!(lbs::write::write_field_id(w, #id)?;),
+        }
+    }
+
+    fn write_variant_id(&self, id: impl ToTokens) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::varint::write_u16(w, #id)?),
+            false => quote!(lbs::write::write_field_id(w, #id)?),
+        }
+    }
+
+    fn must_write(&self, m: &Meta, encoder: &TokenStream, place: Place) -> TokenStream {
+        let field = m.write_access(place);
+        let must_write = match (m.ctx, &self.ctx) {
+            (true, Some(ctx)) => {
+                quote!(lbs::ctx::LBSWriteCtx::<#ctx>::lbs_must_write(&#field))
+            }
+            _ if m.secret => quote!(lbs::secrecy::must_write(&#field)),
+            _ if m.remote || m.try_from.is_some() => quote!(true),
+            _ => quote!(#field.lbs_must_write()),
+        };
+
+        // Droppable fields are kept only while encoding fits its budget
+        let must_write = match m.droppable {
+            Some(priority) => quote!((#must_write && lbs::Encoder::keeps(#encoder, #priority))),
+            None => must_write,
+        };
+
+        // Fields absent in the target schema version aren't written at all
+        match m.lifecycle_check(quote!(lbs::Encoder::version(#encoder))) {
+            Some(present) => quote!((#present && #must_write)),
+            None => must_write,
+        }
+    }
+
+    fn read_field_count(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::read::read_compact_field_count(r)?),
+            false => quote!(lbs::read::read_field_count(r)?),
+        }
+    }
+
+    fn read_field_id(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::read::read_compact_field_id(r, &mut prev_id)?),
+            false => quote!(lbs::read::read_field_id(r)?),
+        }
+    }
+
+    fn read_variant_id(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::varint::read_u16(r)?),
+            false => quote!(lbs::read::read_field_id(r)?),
+        }
+    }
+
+    fn read_field_count_async(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::tokio::read::read_compact_field_count(r).await?),
+            false => quote!(lbs::tokio::read::read_field_count(r).await?),
+        }
+    }
+
+    fn read_field_id_async(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::tokio::read::read_compact_field_id(r, &mut prev_id).await?),
+            false => quote!(lbs::tokio::read::read_field_id(r).await?),
+        }
+    }
+
+    fn read_variant_id_async(&self) -> TokenStream {
+        match self.compact {
+            true => quote!(lbs::tokio::read::read_varint_u16(r).await?),
+            false => quote!(lbs::tokio::read::read_field_id(r).await?),
+        }
+    }
+}
+
+impl AutoIds<'_> {
+    /// Id for the next field or variant without one.
+    fn peek(&mut self) -> Option<u16> {
+        while let Some(id) = self.next.filter(|id| self.reserved.contains(id)) {
+            self.next = id.checked_add(1);
+        }
+
+        self.next
+    }
+
+    /// Continues after the id, which was taken.
+    fn advance(&mut self, id: u16) {
+        if self.next.is_some() {
+            self.next = id.checked_add(1);
+        }
+    }
+}
+
 impl Meta {
-    fn from_struct_field(field: &Field) -> Self {
+    /// Parses attributes of a field, which gets `auto_id` unless it's given an id.
+    fn from_struct_field(field: &Field, index: usize, auto_id: Option<u16>) -> Self {
         let mut meta = Meta {
             id: None,
-            name: field
-                .ident
-                .clone()
-                .expect("unnamed fields are not supported"),
+            name: match field.ident {
+                Some(ref ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(index)),
+            },
+            index,
+            ty: Some(field.ty.clone()),
             span: field.span(),
             required: false,
             skip: false,
+            parallel: false,
+            codec: None,
+            packed: false,
+            ctx: false,
+            oneof: None,
+            since: None,
+            removed: None,
+            deprecated: None,
+            remote: false,
+            droppable: None,
+            try_from: None,
+            append: false,
+            other: false,
+            read_as: Vec::new(),
+            delimited: false,
+            secret: false,
+            unknown_fields: false,
             default: None,
             variant_fields: None,
         };
 
         let mut optional = false;
+        let mut deprecated_id = None;
 
         field
             .attrs
@@ -81,6 +538,13 @@ impl Meta {
                             parenthesized!(content in arg.input);
                             meta.id = Some(Self::parse_id(content));
                         }
+                        ARGUMENT_DEPRECATED_ID => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let (id, note) = Self::parse_deprecated_id(&content);
+                            deprecated_id = Some(id);
+                            meta.deprecated = Some(note);
+                        }
                         ARGUMENT_DEFAULT => {
                             let content;
                             parenthesized!(content in arg.input);
@@ -90,6 +554,110 @@ impl Meta {
                         ARGUMENT_OPTIONAL => {
                             optional = Self::parse_flag(arg.input, ARGUMENT_OPTIONAL)
                         }
+                        ARGUMENT_PARALLEL => {
+                            meta.parallel = Self::parse_flag(arg.input, ARGUMENT_PARALLEL)
+                        }
+                        ARGUMENT_SPARSE => {
+                            if Self::parse_flag(arg.input, ARGUMENT_SPARSE) {
+                                meta.set_codec(quote!(lbs::sparse));
+                            }
+                        }
+                        ARGUMENT_UTF8 => {
+                            if Self::parse_flag(arg.input, ARGUMENT_UTF8) {
+                                meta.set_codec(quote!(lbs::utf8));
+                            }
+                        }
+                        ARGUMENT_DELIMITED => {
+                            if Self::parse_flag(arg.input, ARGUMENT_DELIMITED) {
+                                meta.set_codec(quote!(lbs::delimited));
+                                meta.delimited = true;
+                            }
+                        }
+                        ARGUMENT_DELTA_KEYS => {
+                            if Self::parse_flag(arg.input, ARGUMENT_DELTA_KEYS) {
+                                meta.set_codec(quote!(lbs::delta_keys));
+                            }
+                        }
+                        ARGUMENT_COMPACT_DURATION => {
+                            if Self::parse_flag(arg.input, ARGUMENT_COMPACT_DURATION) {
+                                meta.set_codec(quote!(lbs::compact_duration));
+                            }
+                        }
+                        ARGUMENT_VARINT => {
+                            if Self::parse_flag(arg.input, ARGUMENT_VARINT) {
+                                meta.set_codec(quote!(lbs::varint));
+                            }
+                        }
+                        ARGUMENT_RELATIVE_TIME => {
+                            if Self::parse_flag(arg.input, ARGUMENT_RELATIVE_TIME) {
+                                meta.set_codec(quote!(lbs::relative_time));
+                            }
+                        }
+                        ARGUMENT_FIXED_POINT => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let scale = Literal::u64_unsuffixed(Self::parse_scale(&content));
+                            meta.set_codec(quote!(lbs::fixed_point::FixedPoint::<#scale>));
+                        }
+                        ARGUMENT_WIDEN => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let (ty, since) = Self::parse_widen(&content);
+                            meta.set_codec(quote!(lbs::widen::Widen::<#ty, #since>));
+                        }
+                        ARGUMENT_UNIT => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let unit = Self::parse_unit(&content);
+                            meta.set_codec(quote!(lbs::unit::#unit));
+                        }
+                        ARGUMENT_DROPPABLE => {
+                            meta.droppable = Some(match arg.input.peek(syn::token::Paren) {
+                                true => {
+                                    let content;
+                                    parenthesized!(content in arg.input);
+                                    Self::parse_priority(&content)
+                                }
+                                false => 0,
+                            });
+                        }
+                        ARGUMENT_TRY_FROM => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let (proxy, error) = Self::parse_try_from(&content);
+                            meta.set_codec(quote!(lbs::try_from::Proxy::<#proxy>));
+                            meta.try_from = Some((proxy, error));
+                        }
+                        ARGUMENT_WITH => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            let mirror: syn::Path = content.parse().expect("mirror path expected");
+                            meta.set_codec(quote!(#mirror));
+                            meta.remote = true;
+                        }
+                        ARGUMENT_CTX => meta.ctx = Self::parse_flag(arg.input, ARGUMENT_CTX),
+                        ARGUMENT_SINCE => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.since = Some(Self::parse_version(&content));
+                        }
+                        ARGUMENT_REMOVED => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.removed = Some(Self::parse_version(&content));
+                        }
+                        ARGUMENT_ONEOF => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.oneof = Some(content.parse().expect("oneof group name expected"));
+                        }
+                        ARGUMENT_APPEND => {
+                            meta.append = Self::parse_flag(arg.input, ARGUMENT_APPEND)
+                        }
+                        ARGUMENT_UNKNOWN_FIELDS => {
+                            meta.unknown_fields =
+                                Self::parse_flag(arg.input, ARGUMENT_UNKNOWN_FIELDS)
+                        }
                         unknown => panic_unknown_argument(unknown),
                     }
 
@@ -97,24 +665,97 @@ impl Meta {
                 })
             });
 
+        // Unknown fields are kept aside and aren't a field of their own
+        if meta.unknown_fields {
+            if meta.id.is_some() || meta.skip || meta.ctx || meta.codec.is_some() {
+                panic!(
+                    "{} field can have neither {}, nor {}, nor {}, nor encoding attributes",
+                    ARGUMENT_UNKNOWN_FIELDS, ARGUMENT_ID, ARGUMENT_SKIP, ARGUMENT_CTX
+                );
+            }
+
+            meta.skip = true;
+        }
+
+        // Deprecated fields keep their old id, which is reported when read
+        if let Some(id) = deprecated_id {
+            if meta.id.is_some() || meta.skip {
+                panic!(
+                    "{} field can have neither {}, nor {}",
+                    ARGUMENT_DEPRECATED_ID, ARGUMENT_ID, ARGUMENT_SKIP
+                );
+            }
+
+            meta.id = Some(id);
+        }
+
+        // Unnamed fields are identified by their position, unless told otherwise
+        if meta.id.is_none() && !meta.skip {
+            meta.id = auto_id.or(field.ident.is_none().then_some(index as u16));
+        }
+
         let field_type = field.ty.to_token_stream().to_string();
 
-        meta.required = !meta.skip
-            && !optional
-            && !field_type.starts_with("Option <")
-            && !field_type.starts_with("core :: option :: Option <")
-            && !field_type.starts_with(":: core :: option :: Option <");
+        let is_option = field_type.starts_with("Option <")
+            || field_type.starts_with("core :: option :: Option <")
+            || field_type.starts_with(":: core :: option :: Option <");
+
+        if meta.oneof.is_some() && (!is_option || meta.skip) {
+            panic!(
+                "{} fields must be of Option type and not skipped",
+                ARGUMENT_ONEOF
+            );
+        }
+
+        meta.required = !meta.skip && !optional && !is_option;
+
+        if meta.droppable.is_some() && (meta.required || meta.skip) {
+            panic!(
+                "{} fields must be either {} or of Option type and not skipped",
+                ARGUMENT_DROPPABLE, ARGUMENT_OPTIONAL
+            );
+        }
+
+        // Secrets, possibly optional ones, are recognized by their spelling
+        let inner_type = field_type
+            .strip_prefix("Option <")
+            .unwrap_or(&field_type)
+            .trim_start();
+        let inner_type = inner_type.strip_prefix("secrecy :: ").unwrap_or(inner_type);
+        meta.secret = ["Secret <", "SecretString", "SecretVec <"]
+            .iter()
+            .any(|name| inner_type.starts_with(name));
 
         meta.validated()
     }
 
-    fn from_enum_variant(variant: &Variant) -> Self {
+    /// Parses attributes of a variant, which gets `auto_id` unless it's given an id.
+    fn from_enum_variant(variant: &Variant, auto_id: Option<u16>) -> Self {
         let mut meta = Meta {
             id: None,
-            name: variant.ident.clone(),
+            name: syn::Member::Named(variant.ident.clone()),
+            index: 0,
+            ty: None,
             span: variant.span(),
             required: true,
             skip: false,
+            parallel: false,
+            codec: None,
+            packed: false,
+            ctx: false,
+            oneof: None,
+            since: None,
+            removed: None,
+            deprecated: None,
+            remote: false,
+            droppable: None,
+            try_from: None,
+            append: false,
+            other: false,
+            read_as: Vec::new(),
+            delimited: false,
+            secret: false,
+            unknown_fields: false,
             default: None,
             variant_fields: if variant.fields.is_empty() {
                 None
@@ -137,6 +778,13 @@ impl Meta {
                             parenthesized!(content in arg.input);
                             meta.id = Some(Self::parse_id(content));
                         }
+                        ARGUMENT_CTX => meta.ctx = Self::parse_flag(arg.input, ARGUMENT_CTX),
+                        ARGUMENT_OTHER => meta.other = Self::parse_flag(arg.input, ARGUMENT_OTHER),
+                        ARGUMENT_READ_AS => {
+                            let content;
+                            parenthesized!(content in arg.input);
+                            meta.read_as = Self::parse_ids(&content);
+                        }
                         unknown => panic_unknown_argument(unknown),
                     }
 
@@ -144,9 +792,75 @@ impl Meta {
                 })
             });
 
+        if meta.other {
+            let is_id_or_pair = matches!(variant.fields, Fields::Unnamed(ref f) if matches!(f.unnamed.len(), 1 | 2));
+
+            if meta.id.is_some() || meta.ctx || !meta.read_as.is_empty() || !is_id_or_pair {
+                panic!(
+                    "{} variant must carry u16 or (u16, Vec<u8>) and have neither {}, nor {}, nor {}",
+                    ARGUMENT_OTHER, ARGUMENT_ID, ARGUMENT_READ_AS, ARGUMENT_CTX
+                );
+            }
+
+            return meta;
+        }
+
+        meta.id = meta.id.or(auto_id);
         meta.validated()
     }
 
+    /// Name for messages and schemas, like `x` or `0`.
+    fn label(&self) -> String {
+        match self.name {
+            syn::Member::Named(ref ident) => ident.to_string(),
+            syn::Member::Unnamed(ref index) => index.index.to_string(),
+        }
+    }
+
+    /// Field as written: of `self`, or bound by variant pattern.
+    fn write_access(&self, place: Place) -> TokenStream {
+        let field_name = &self.name;
+        match place {
+            Place::Struct => quote!(self.#field_name),
+            Place::Variant(_) => {
+                let binding = field_binding(self.index);
+                quote!((*#binding))
+            }
+        }
+    }
+
+    /// Field as read: of a struct or of a tuple of variant fields.
+    fn read_access(&self, place: Place) -> TokenStream {
+        let field_name = &self.name;
+        match place {
+            Place::Struct => quote!(_self.#field_name),
+            Place::Variant(_) => {
+                let index = syn::Index::from(self.index);
+                quote!(_self.#index)
+            }
+        }
+    }
+
+    /// Pattern of ids, which are read as the variant: its id and former ones.
+    fn read_ids(&self) -> TokenStream {
+        let id = self.id;
+        let read_as = &self.read_as;
+        quote!(#id #(| #read_as)*)
+    }
+
+    /// Whether `other` variant keeps payload of unknown variants besides their id.
+    fn keeps_payload(&self) -> bool {
+        matches!(self.variant_fields, Some(ref fields) if fields.len() == 2)
+    }
+
+    /// Fields of a variant, which payload is written as a struct, rather than as a single value.
+    fn struct_payload(&self) -> Option<&Fields> {
+        match self.variant_fields {
+            Some(ref fields) if is_struct_payload(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
     fn parse_id(input: ParseBuffer) -> u16 {
         input
             .parse::<LitInt>()
@@ -155,65 +869,388 @@ impl Meta {
             .expect("id must fit into u16")
     }
 
-    fn parse_default(input: ParseBuffer) -> TokenStream {
-        input
-            .parse::<Expr>()
-            .expect("default expression expected")
-            .into_token_stream()
-    }
+    /// Id and note of a retired field, like `4, note = "use email instead"`.
+    fn parse_deprecated_id(input: &ParseBuffer) -> (u16, String) {
+        let id = input
+            .parse::<LitInt>()
+            .expect("id must be numeric")
+            .base10_parse()
+            .expect("id must fit into u16");
 
-    fn parse_flag(input: &ParseBuffer, arg_name: &str) -> bool {
-        if input.is_empty() || input.peek(Token![,]) {
-            return true;
+        let mut note = String::new();
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>().expect("comma expected");
+            let name = input.parse::<syn::Ident>().expect("note expected");
+            if name != ARGUMENT_NOTE {
+                panic!("{} expected, got {}", ARGUMENT_NOTE, name);
+            }
+            input.parse::<Token![=]>().expect("= expected");
+            note = input
+                .parse::<syn::LitStr>()
+                .expect("note must be a string")
+                .value();
         }
 
-        panic!("argument '{}' cannot have value", arg_name)
+        (id, note)
     }
 
-    fn validated(self) -> Self {
-        if self.id.is_none() {
-            panic!(
-                "struct field or enum variant must have an id: #[{}({}(<u16>))]",
-                ATTRIBUTE, ARGUMENT_ID
-            )
+    /// Parses `<type>` or `<type>, error = "<message>"`.
+    fn parse_try_from(input: &ParseBuffer) -> (syn::Type, Option<String>) {
+        let proxy = input.parse().expect("proxy type expected");
+
+        if input.is_empty() {
+            return (proxy, None);
         }
 
-        self
+        input.parse::<Token![,]>().expect("comma expected");
+        let name = input.parse::<syn::Ident>().expect("error expected");
+        if name != ARGUMENT_ERROR {
+            panic!("{} expected, got {}", ARGUMENT_ERROR, name);
+        }
+        input.parse::<Token![=]>().expect("= expected");
+        let error = input
+            .parse::<syn::LitStr>()
+            .expect("error must be a string")
+            .value();
+
+        (proxy, Some(error))
     }
-}
 
-//
-// Derive LBSWrite.
-//
+    /// Parses `priority = <u8>`.
+    fn parse_priority(input: &ParseBuffer) -> u8 {
+        let name = input.parse::<syn::Ident>().expect("priority expected");
+        if name != ARGUMENT_PRIORITY {
+            panic!("{} expected, got {}", ARGUMENT_PRIORITY, name);
+        }
+        input.parse::<Token![=]>().expect("= expected");
+        input
+            .parse::<LitInt>()
+            .expect("priority must be numeric")
+            .base10_parse()
+            .expect("priority must fit into u8")
+    }
 
-#[proc_macro_derive(LBSWrite, attributes(lbs))]
-pub fn derive_lbs_write(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    // Parse the input tokens into a syntax tree
-    let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
+    /// Comma-separated ids, like `3, 5`.
+    fn parse_ids(input: &ParseBuffer) -> Vec<u16> {
+        let mut ids = Vec::new();
+
+        while !input.is_empty() {
+            ids.push(
+                input
+                    .parse::<LitInt>()
+                    .expect("id must be numeric")
+                    .base10_parse()
+                    .expect("id must fit into u16"),
+            );
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>().expect("',' expected");
+            }
+        }
 
-    // Add trait bound to every generic type parameter
-    let generics = add_write_trait_bound(input.generics);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        if ids.is_empty() {
+            panic!("{} must list at least one id", ARGUMENT_READ_AS);
+        }
+
+        ids
+    }
+
+    fn parse_version(input: &ParseBuffer) -> u16 {
+        input
+            .parse::<LitInt>()
+            .expect("version must be numeric")
+            .base10_parse()
+            .expect("version must fit into u16")
+    }
+
+    fn parse_scale(input: &ParseBuffer) -> u64 {
+        let scale = input
+            .parse::<LitInt>()
+            .expect("scale must be numeric")
+            .base10_parse()
+            .expect("scale must fit into u64");
+
+        if scale == 0 {
+            panic!("scale must be positive");
+        }
+
+        scale
+    }
+
+    fn parse_unit(input: &ParseBuffer) -> syn::Ident {
+        let unit: syn::Ident = input.parse().expect("time unit expected");
+
+        if !UNITS.contains(&unit.to_string().as_str()) {
+            panic!("unknown time unit '{}', expected one of {:?}", unit, UNITS);
+        }
+
+        unit
+    }
+
+    /// Narrow type and version, since which field has its declared type.
+    fn parse_widen(input: &ParseBuffer) -> (syn::Type, Literal) {
+        let ty = input.parse().expect("narrow integer type expected");
+        input
+            .parse::<Token![,]>()
+            .expect("version, since which field is widened, expected");
+        let since = Literal::u16_unsuffixed(Self::parse_version(input));
+        (ty, since)
+    }
+
+    /// Whether field is present in schema version given by `version` expression of `Option<u16>`,
+    /// or None if it's present in all of them.
+    fn lifecycle_check(&self, version: TokenStream) -> Option<TokenStream> {
+        if self.since.is_none() && self.removed.is_none() {
+            return None;
+        }
+
+        let since = self.since.unwrap_or(0);
+        let removed = match self.removed {
+            Some(removed) => quote!(Some(#removed)),
+            None => quote!(None),
+        };
+
+        Some(quote!(lbs::migrate::is_present(#version, #since, #removed)))
+    }
+
+    fn parse_default(input: ParseBuffer) -> TokenStream {
+        input
+            .parse::<Expr>()
+            .expect("default expression expected")
+            .into_token_stream()
+    }
+
+    fn parse_flag(input: &ParseBuffer, arg_name: &str) -> bool {
+        if input.is_empty() || input.peek(Token![,]) {
+            return true;
+        }
+
+        panic!("argument '{}' cannot have value", arg_name)
+    }
+
+    fn validated(self) -> Self {
+        if self.id.is_none() && !self.skip {
+            panic!(
+                "struct field or enum variant must have an id: #[{}({}(<u16>))]",
+                ATTRIBUTE, ARGUMENT_ID
+            )
+        }
+
+        if self.parallel && self.codec.is_some() {
+            panic!(
+                "{} can't be combined with encoding attributes",
+                ARGUMENT_PARALLEL
+            )
+        }
+
+        if let (Some(since), Some(removed)) = (self.since, self.removed) {
+            if since >= removed {
+                panic!(
+                    "field must be removed after version {}, in which it appears",
+                    since
+                );
+            }
+        }
+
+        if self.ctx && (self.parallel || self.codec.is_some()) {
+            panic!(
+                "{} can't be combined with {} or encoding attributes",
+                ARGUMENT_CTX, ARGUMENT_PARALLEL
+            )
+        }
+
+        if self.secret && (self.parallel || self.ctx || self.codec.is_some()) {
+            panic!(
+                "secret fields can't be combined with {}, {} or encoding attributes",
+                ARGUMENT_PARALLEL, ARGUMENT_CTX
+            )
+        }
+
+        if self.append && (self.skip || self.ctx || self.codec.is_some()) {
+            panic!(
+                "{} can't be combined with {}, {} or encoding attributes",
+                ARGUMENT_APPEND, ARGUMENT_SKIP, ARGUMENT_CTX
+            )
+        }
+
+        self
+    }
+
+    fn set_codec(&mut self, codec: TokenStream) {
+        if self.codec.is_some() {
+            panic!("only one encoding attribute may be used per field")
+        }
+
+        self.codec = Some(codec);
+    }
+}
+
+//
+// Derive LBSWrite.
+//
+
+#[proc_macro_derive(LBSWrite, attributes(lbs))]
+pub fn derive_lbs_write(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    check_frozen(&name, &input.data, &container);
+    let used = serialized_type_params(&input.data, &input.generics);
 
-    // Generate lbs_write() body
-    let write_body = match input.data {
-        Data::Enum(ref data) => generate_write_body_for_enum(data),
+    // Add trait bound to every generic type parameter
+    let generics = add_write_trait_bound(input.generics, &used);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate lbs_write_with() and lbs_write_vectored() bodies
+    let generate_write_body = |method: &WriteMethod| match input.data {
+        Data::Enum(ref data) => generate_write_body_for_enum(data, &container, method),
         Data::Union(_) => panic!("unions are unsupported"),
         Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => generate_write_body_for_struct(fields),
-            Fields::Unnamed(_) => panic!("structs with unnamed fields are unsupported"),
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_write_body_for_struct(&data.fields, &container, method, Place::Struct)
+            }
             Fields::Unit => quote!(Ok(())),
         },
     };
 
+    let write_body = generate_write_body(&WriteMethod {
+        call: quote!(lbs_write_with(w, e)),
+        encoder: quote!(e),
+        filtered: false,
+    });
+
+    // With context, only the context-aware trait is implemented
+    if let Some(ref ctx) = container.ctx {
+        return container.finish(quote! {
+            impl #impl_generics lbs::ctx::LBSWriteCtx<#ctx> for #name #ty_generics #where_clause {
+                #[inline]
+                fn lbs_write_ctx<W: std::io::Write>(&self, w: &mut W, ctx: &mut #ctx) -> core::result::Result<(), lbs::error::LBSError> {
+                    lbs::ctx::LBSWriteCtx::<#ctx>::lbs_write_ctx_with(self, w, &mut lbs::Encoder::new(), ctx)
+                }
+
+                #[inline]
+                fn lbs_write_ctx_with<W: std::io::Write>(&self, w: &mut W, e: &mut lbs::Encoder, ctx: &mut #ctx) -> core::result::Result<(), lbs::error::LBSError> {
+                    #write_body
+                }
+            }
+        });
+    }
+
+    let size_hint_body = match input.data {
+        Data::Enum(ref data) => generate_size_hint_for_enum(data, &container),
+        Data::Union(_) => unreachable!(),
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_size_hint_for_struct(&data.fields, &container, Place::Struct)
+            }
+            Fields::Unit => quote!(0),
+        },
+    };
+
+    // Mirror of a foreign type implements nothing for itself, bodies access the foreign type
+    if let Some(ref remote) = container.remote {
+        return container.finish(quote! {
+            impl #impl_generics lbs::remote::LBSRemoteWrite<#name #ty_generics> for #remote #where_clause {
+                #[inline]
+                fn lbs_remote_write_with<W: std::io::Write>(&self, w: &mut W, e: &mut lbs::Encoder) -> core::result::Result<(), lbs::error::LBSError> {
+                    #write_body
+                }
+
+                #[inline]
+                fn lbs_remote_size_hint(&self) -> usize {
+                    #size_hint_body
+                }
+            }
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Writes value of the mirrored type, like `#[lbs(with(...))]` fields.
+                #[inline]
+                pub fn write<T: lbs::remote::LBSRemoteWrite<Self> + ?Sized, W: std::io::Write>(value: &T, w: &mut W, e: &mut lbs::Encoder) -> core::result::Result<(), lbs::error::LBSError> {
+                    value.lbs_remote_write_with(w, e)
+                }
+            }
+        });
+    }
+
+    // Only fields of structs can be picked
+    let write_fields_method = match input.data {
+        Data::Struct(ref data) if !matches!(data.fields, Fields::Unit) => {
+            let body = generate_write_body_for_struct(
+                &data.fields,
+                &container,
+                &WriteMethod {
+                    call: quote!(lbs_write_with(w, e)),
+                    encoder: quote!(e),
+                    filtered: true,
+                },
+                Place::Struct,
+            );
+            quote! {
+                #[inline]
+                fn lbs_write_fields<W: std::io::Write>(&self, w: &mut W, fields: &[u16]) -> core::result::Result<(), lbs::error::LBSError> {
+                    let e = &mut lbs::Encoder::new();
+                    #body
+                }
+            }
+        }
+        _ => quote!(),
+    };
+
+    let write_vectored_body = match container.indexed {
+        true => quote!(lbs::LBSWrite::lbs_write(self, w)),
+        false => generate_write_body(&WriteMethod {
+            call: quote!(lbs_write_vectored(w)),
+            encoder: quote!(&mut lbs::Encoder::new()),
+            filtered: false,
+        }),
+    };
+
+    // With dyn_io, body is compiled once over `dyn Write` and lbs_write_with() becomes a thin shim
+    let (write_body, dyn_write_impl) = match container.dyn_io {
+        true => (
+            quote!(self.__lbs_write_dyn(w, e)),
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #[doc(hidden)]
+                    #[inline(never)]
+                    fn __lbs_write_dyn(&self, mut w: &mut dyn std::io::Write, e: &mut lbs::Encoder) -> core::result::Result<(), lbs::error::LBSError> {
+                        let w = &mut w;
+                        #write_body
+                    }
+                }
+            },
+        ),
+        false => (write_body, quote!()),
+    };
+
     // Complete trait implementation
-    proc_macro::TokenStream::from(quote! {
+    container.finish(quote! {
+        #dyn_write_impl
+
         impl #impl_generics lbs::LBSWrite for #name #ty_generics #where_clause {
             #[inline]
             fn lbs_write<W: std::io::Write>(&self, w: &mut W) -> core::result::Result<(), lbs::error::LBSError> {
+                self.lbs_write_with(w, &mut lbs::Encoder::new())
+            }
+
+            #[inline]
+            fn lbs_write_with<W: std::io::Write>(&self, w: &mut W, e: &mut lbs::Encoder) -> core::result::Result<(), lbs::error::LBSError> {
                 #write_body
             }
+
+            #[inline]
+            fn lbs_write_vectored<'lbs_v>(&'lbs_v self, w: &mut lbs::vectored::VectoredWriter<'lbs_v>) -> core::result::Result<(), lbs::error::LBSError> {
+                #write_vectored_body
+            }
+
+            #[inline]
+            fn lbs_size_hint(&self) -> usize {
+                #size_hint_body
+            }
+
+            #write_fields_method
         }
     })
 }
@@ -227,286 +1264,2481 @@ pub fn derive_lbs_read(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     // Parse the input tokens into a syntax tree
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    check_frozen(&name, &input.data, &container);
+    let used = serialized_type_params(&input.data, &input.generics);
 
     // Add trait bound LBSRead to every generic type parameter
-    let generics = add_read_trait_bound(input.generics);
+    let generics = add_read_trait_bound(input.generics, &used);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     // Generate lbs_read() body
     let read_body = match input.data {
         Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => generate_read_body_for_struct(fields),
-            Fields::Unnamed(_) => unimplemented!(),
+            Fields::Named(_) | Fields::Unnamed(_) => generate_read_body_for_struct(
+                &data.fields,
+                &container,
+                ReadBody::Full,
+                Place::Struct,
+            ),
+            Fields::Unit => quote!(Ok(Self)),
+        },
+        Data::Enum(ref data) => generate_read_body_for_enum(data, &container, ReadBody::Full),
+        Data::Union(_) => unimplemented!(),
+    };
+
+    // Mirror of a foreign type implements nothing for itself, bodies build the foreign type
+    if let Some(ref remote) = container.remote {
+        return container.finish(quote! {
+            impl #impl_generics lbs::remote::LBSRemoteRead<#name #ty_generics> for #remote #where_clause {
+                #[inline]
+                fn lbs_remote_read_with<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<Self, lbs::error::LBSError> {
+                    #read_body
+                }
+            }
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Reads value of the mirrored type, like `#[lbs(with(...))]` fields.
+                #[inline]
+                pub fn read<T: lbs::remote::LBSRemoteRead<Self>, R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<T, lbs::error::LBSError> {
+                    T::lbs_remote_read_with(r, d)
+                }
+            }
+        });
+    }
+
+    // Generate lbs_read_into_with() and lbs_merge_with() bodies
+    let generate_read_into_body = |body| match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_read_body_for_struct(&data.fields, &container, body, Place::Struct)
+            }
+            Fields::Unit => quote!(Ok(())),
+        },
+        Data::Enum(ref data) => generate_read_into_body_for_enum(data, &container, body),
+        Data::Union(_) => unimplemented!(),
+    };
+    let read_into_body = generate_read_into_body(ReadBody::InPlace);
+    let merge_body = generate_read_into_body(ReadBody::Merge);
+
+    // With dyn_io, bodies are compiled once over `dyn Read` and trait methods become thin shims
+    let (read_body, read_into_body, merge_body, dyn_read_impl) = match container.dyn_io {
+        true => (
+            quote!(Self::__lbs_read_dyn(r, d)),
+            quote!(self.__lbs_read_into_dyn(r, d)),
+            quote!(self.__lbs_merge_dyn(r, d)),
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #[doc(hidden)]
+                    #[inline(never)]
+                    fn __lbs_read_dyn(mut r: &mut dyn std::io::Read, d: &mut lbs::Decoder) -> core::result::Result<Self, lbs::error::LBSError> {
+                        let r = &mut r;
+                        #read_body
+                    }
+
+                    #[doc(hidden)]
+                    #[inline(never)]
+                    fn __lbs_read_into_dyn(&mut self, mut r: &mut dyn std::io::Read, d: &mut lbs::Decoder) -> core::result::Result<(), lbs::error::LBSError> {
+                        let r = &mut r;
+                        #read_into_body
+                    }
+
+                    #[doc(hidden)]
+                    #[inline(never)]
+                    fn __lbs_merge_dyn(&mut self, mut r: &mut dyn std::io::Read, d: &mut lbs::Decoder) -> core::result::Result<(), lbs::error::LBSError> {
+                        let r = &mut r;
+                        #merge_body
+                    }
+                }
+            },
+        ),
+        false => (read_body, read_into_body, merge_body, quote!()),
+    };
+
+    // Projection and skipping are only specialized for structs, which can skip fields by their ids
+    let projected_methods = match input.data {
+        Data::Struct(DataStruct {
+            fields: ref fields @ (Fields::Named(_) | Fields::Unnamed(_)),
+            ..
+        }) => {
+            let projected_body = generate_read_body_for_struct(
+                fields,
+                &container,
+                ReadBody::Projected,
+                Place::Struct,
+            );
+            let report_body =
+                generate_read_body_for_struct(fields, &container, ReadBody::Report, Place::Struct);
+            quote! {
+                #[inline]
+                fn lbs_skip<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<(), lbs::error::LBSError> {
+                    Self::lbs_read_projected(r, d, &[]).map(|_| ())
+                }
+
+                #[inline]
+                fn lbs_read_projected<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder, fields: &[u16]) -> core::result::Result<Self, lbs::error::LBSError> {
+                    #projected_body
+                }
+
+                #[inline]
+                fn lbs_read_with_report<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<(Self, lbs::read::FieldSet), lbs::error::LBSError> {
+                    #report_body
+                }
+            }
+        }
+        _ => quote!(),
+    };
+
+    // Version tag for lbs::migrate
+    let versioned_impl = match container.version {
+        Some(version) => quote! {
+            impl #impl_generics lbs::migrate::Versioned for #name #ty_generics #where_clause {
+                const VERSION: u16 = #version;
+            }
+        },
+        None => quote!(),
+    };
+
+    // With context, only the context-aware trait is implemented
+    if let Some(ref ctx) = container.ctx {
+        return container.finish(quote! {
+            #versioned_impl
+
+            impl #impl_generics lbs::ctx::LBSReadCtx<#ctx> for #name #ty_generics #where_clause {
+                #[inline]
+                fn lbs_read_ctx<R: std::io::Read>(r: &mut R, ctx: &mut #ctx) -> core::result::Result<Self, lbs::error::LBSError> {
+                    <Self as lbs::ctx::LBSReadCtx<#ctx>>::lbs_read_ctx_with(r, &mut lbs::Decoder::new(), ctx)
+                }
+
+                #[inline]
+                fn lbs_read_ctx_with<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder, ctx: &mut #ctx) -> core::result::Result<Self, lbs::error::LBSError> {
+                    #read_body
+                }
+            }
+        });
+    }
+
+    // Complete trait implementation
+    container.finish(quote! {
+        #dyn_read_impl
+        #versioned_impl
+
+        impl #impl_generics lbs::LBSRead for #name #ty_generics #where_clause {
+            #[inline]
+            fn lbs_read<R: std::io::Read>(r: &mut R) -> core::result::Result<Self, lbs::error::LBSError> {
+                Self::lbs_read_with(r, &mut lbs::Decoder::new())
+            }
+
+            #[inline]
+            fn lbs_read_with<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<Self, lbs::error::LBSError> {
+                #read_body
+            }
+
+            #[inline]
+            fn lbs_read_into_with<R: std::io::Read>(&mut self, r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<(), lbs::error::LBSError> {
+                #read_into_body
+            }
+
+            #[inline]
+            fn lbs_merge_with<R: std::io::Read>(&mut self, r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<(), lbs::error::LBSError> {
+                #merge_body
+            }
+
+            #projected_methods
+        }
+    })
+}
+
+//
+// Derive LBSReadAsync.
+//
+
+#[proc_macro_derive(LBSReadAsync, attributes(lbs))]
+pub fn derive_lbs_read_async(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    check_frozen(&name, &input.data, &container);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    if container.ctx.is_some() {
+        panic_async_unsupported("containers with context");
+    }
+
+    // Add trait bound LBSReadAsync to every generic type parameter
+    let generics = add_read_async_trait_bound(input.generics, &used);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Returned future captures references and all type parameters, which must be listed
+    let type_params: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+
+    // Generate lbs_read_async() body
+    let read_body = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => generate_read_body_for_struct(
+                &data.fields,
+                &container,
+                ReadBody::Async,
+                Place::Struct,
+            ),
+            Fields::Unit => quote!(Ok(Self)),
+        },
+        Data::Enum(ref data) => generate_read_body_for_enum(data, &container, ReadBody::Async),
+        Data::Union(_) => unimplemented!(),
+    };
+
+    // Future is boxed, so futures of recursive types have finite size
+    container.finish(quote! {
+        impl #impl_generics lbs::tokio::LBSReadAsync for #name #ty_generics #where_clause {
+            #[inline]
+            fn lbs_read_async<'lbs_r, 'lbs_d, R: lbs::tokio::AsyncRead + Unpin + Send>(r: &'lbs_r mut R, d: &'lbs_d mut lbs::Decoder) -> impl core::future::Future<Output = core::result::Result<Self, lbs::error::LBSError>> + Send + use<'lbs_r, 'lbs_d, R #(, #type_params)*> {
+                Box::pin(async move {
+                    #read_body
+                })
+            }
+        }
+    })
+}
+
+//
+// Derive LBSReadRef.
+//
+
+#[proc_macro_derive(LBSReadRef, attributes(lbs))]
+pub fn derive_lbs_read_ref(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    check_frozen(&name, &input.data, &container);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    if container.ctx.is_some() {
+        panic_ref_unsupported("containers with context");
+    }
+
+    // Input outlives every lifetime of the type, so fields may borrow from it
+    let mut generics = add_read_ref_trait_bound(input.generics.clone(), &used);
+    let mut input_lifetime: syn::LifetimeParam = parse_quote!('lbs_de);
+    input_lifetime.bounds.extend(
+        input
+            .generics
+            .lifetimes()
+            .map(|param| param.lifetime.clone()),
+    );
+    generics
+        .params
+        .insert(0, GenericParam::Lifetime(input_lifetime));
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // Generate lbs_read_ref() body
+    let read_body = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => generate_read_body_for_struct(
+                &data.fields,
+                &container,
+                ReadBody::Borrowed,
+                Place::Struct,
+            ),
             Fields::Unit => quote!(Ok(Self)),
         },
-        Data::Enum(ref data) => generate_read_body_for_enum(data),
+        Data::Enum(ref data) => generate_read_body_for_enum(data, &container, ReadBody::Borrowed),
         Data::Union(_) => unimplemented!(),
     };
 
-    // Complete trait implementation
-    proc_macro::TokenStream::from(quote! {
-        impl #impl_generics lbs::LBSRead for #name #ty_generics #where_clause {
-            #[inline]
-            fn lbs_read<R: std::io::Read>(r: &mut R) -> core::result::Result<Self, lbs::error::LBSError> {
-                #read_body
-            }
+    container.finish(quote! {
+        impl #impl_generics lbs::borrow::LBSReadRef<'lbs_de> for #name #ty_generics #where_clause {
+            #[inline]
+            fn lbs_read_ref(r: &mut &'lbs_de [u8], d: &mut lbs::Decoder) -> core::result::Result<Self, lbs::error::LBSError> {
+                #read_body
+            }
+        }
+    })
+}
+
+//
+// Derive LBSDiff.
+//
+
+#[proc_macro_derive(LBSDiff, attributes(lbs))]
+pub fn derive_lbs_diff(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    // Add trait bound LBSDiff to every generic type parameter (enums are replaced as a whole, so need codecs too)
+    let generics = match input.data {
+        Data::Enum(_) => add_read_trait_bound(
+            add_write_trait_bound(add_diff_trait_bound(input.generics, &used), &used),
+            &used,
+        ),
+        _ => add_diff_trait_bound(input.generics, &used),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate lbs_diff() and lbs_apply() bodies
+    let (diff_body, apply_body) = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_diff_bodies_for_struct(&data.fields, &container)
+            }
+            Fields::Unit => (quote!(Ok(false)), quote!(Ok(()))),
+        },
+        Data::Enum(ref data) => generate_diff_bodies_for_enum(data, &container),
+        Data::Union(_) => panic!("unions are unsupported"),
+    };
+
+    // Complete trait implementation
+    container.finish(quote! {
+        impl #impl_generics lbs::diff::LBSDiff for #name #ty_generics #where_clause {
+            #[inline]
+            fn lbs_diff(&self, other: &Self, w: &mut Vec<u8>) -> core::result::Result<bool, lbs::error::LBSError> {
+                #diff_body
+            }
+
+            #[inline]
+            fn lbs_apply<R: std::io::Read>(&mut self, r: &mut R) -> core::result::Result<(), lbs::error::LBSError> {
+                #apply_body
+            }
+        }
+    })
+}
+
+//
+// Derive LBSSchema.
+//
+
+#[proc_macro_derive(LBSSchema, attributes(lbs))]
+pub fn derive_lbs_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    // Add trait bound LBSSchema to every generic type parameter
+    let generics = add_schema_trait_bound(input.generics, &used);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate lbs_schema() body
+    let schema = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_schema_for_struct(&name.to_string(), &data.fields, &container)
+            }
+            Fields::Unit => quote!(lbs::schema::Schema::Unit),
+        },
+        Data::Enum(ref data) => generate_schema_for_enum(&name, data, &container),
+        Data::Union(_) => panic!("unions are unsupported"),
+    };
+
+    // Compile-time summary for lbs::assert_schema_compatible!()
+    let shape = match input.data {
+        Data::Struct(DataStruct {
+            fields: ref fields @ (Fields::Named(_) | Fields::Unnamed(_)),
+            ..
+        }) => Some(generate_shape_for_struct(fields, &container)),
+        Data::Enum(ref data) => Some(generate_shape_for_enum(data, &container)),
+        _ => None,
+    };
+
+    let shape_impl = match shape {
+        Some(shape) => quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #[doc(hidden)]
+                pub const __LBS_SHAPE: lbs::schema::Shape = #shape;
+            }
+        },
+        None => quote!(),
+    };
+
+    // Complete trait implementation
+    container.finish(quote! {
+        #shape_impl
+
+        impl #impl_generics lbs::schema::LBSSchema for #name #ty_generics #where_clause {
+            fn lbs_schema() -> lbs::schema::Schema {
+                lbs::schema::describe::<Self, _>(stringify!(#name), || #schema)
+            }
+        }
+    })
+}
+
+//
+// Derive LBSMaxSize.
+//
+
+#[proc_macro_derive(LBSMaxSize, attributes(lbs))]
+pub fn derive_lbs_max_size(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    // Add trait bound LBSMaxSize to every serialized generic type parameter
+    let generics = add_max_size_trait_bound(input.generics, &used);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate LBS_MAX_SIZE expression
+    let max_size = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                generate_max_size_for_struct(&data.fields, &container)
+            }
+            Fields::Unit => quote!(0),
+        },
+        Data::Enum(ref data) => generate_max_size_for_enum(data, &container),
+        Data::Union(_) => unimplemented!(),
+    };
+
+    // Complete trait implementation
+    container.finish(quote! {
+        impl #impl_generics lbs::size::LBSMaxSize for #name #ty_generics #where_clause {
+            const LBS_MAX_SIZE: usize = #max_size;
+        }
+    })
+}
+
+//
+// Derive LBSColumnar.
+//
+
+#[proc_macro_derive(LBSColumnar, attributes(lbs))]
+pub fn derive_lbs_columnar(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+    let container = ContainerMeta::from_attrs(&input.attrs, &input.data);
+    let used = serialized_type_params(&input.data, &input.generics);
+
+    // Column values are written and read as usual
+    let generics = add_read_trait_bound(add_write_trait_bound(input.generics, &used), &used);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate lbs_write_columns() and lbs_read_columns() bodies
+    let (write_body, read_body) = match input.data {
+        Data::Struct(DataStruct {
+            fields: ref fields @ (Fields::Named(_) | Fields::Unnamed(_)),
+            ..
+        }) => generate_columnar_bodies(fields, &container),
+        _ => panic!("columnar encoding is only supported for structs with fields"),
+    };
+
+    // Complete trait implementation
+    container.finish(quote! {
+        impl #impl_generics lbs::columnar::LBSColumnar for #name #ty_generics #where_clause {
+            fn lbs_write_columns<W: std::io::Write>(rows: &[Self], w: &mut W, e: &mut lbs::Encoder) -> core::result::Result<(), lbs::error::LBSError> {
+                #write_body
+            }
+
+            fn lbs_read_columns<R: std::io::Read>(r: &mut R, d: &mut lbs::Decoder) -> core::result::Result<Vec<Self>, lbs::error::LBSError> {
+                #read_body
+            }
+        }
+    })
+}
+
+fn generate_write_body_for_struct(
+    fields: &Fields,
+    container: &ContainerMeta,
+    method: &WriteMethod,
+    place: Place,
+) -> TokenStream {
+    // Gather meta
+    let mut meta = gather_struct_meta(fields, container);
+
+    if let Some(m) = meta.iter().find(|m| m.secret && !m.skip) {
+        if !container.expose_secrets {
+            panic!(
+                "field '{}' holds a secret, which is written only if container has #[{}({})]",
+                m.label(),
+                ATTRIBUTE,
+                ARGUMENT_EXPOSE_SECRETS
+            );
+        }
+    }
+
+    if container.sorts_fields() {
+        meta.sort_by_key(|m| m.id);
+    }
+
+    // Requested fields are written unconditionally, so a receiver sees, say, an explicit None,
+    // unless they are absent in the target schema version
+    let must_write = |m: &Meta| match method.filtered {
+        true => {
+            let id = m.id.unwrap();
+            let encoder = &method.encoder;
+            match m.lifecycle_check(quote!(lbs::Encoder::version(#encoder))) {
+                Some(present) => quote!((fields.contains(&#id) && #present)),
+                None => quote!(fields.contains(&#id)),
+            }
+        }
+        false => container.must_write(m, &method.encoder, place),
+    };
+
+    // Field count expressions
+    let field_count_expressions = meta.iter().filter(|m| !m.skip && !m.packed).map(|m| {
+        let must_write = must_write(m);
+        quote_spanned! {m.span=>
+            if #must_write {
+                field_count += 1;
+            }
+        }
+    });
+
+    // Unknown fields of indexed structs are written in between known ones, in id order,
+    // unless only requested fields are
+    let unknown_fields = meta
+        .iter()
+        .find(|m| m.unknown_fields && !method.filtered)
+        .map(|m| m.write_access(place));
+
+    // Write expressions
+    let write_expressions = meta.iter().filter(|m| !m.skip && !m.packed).map(|m| {
+        let field = m.write_access(place);
+        let write_field_id = container.write_field_id(m.id.unwrap());
+        let must_write = must_write(m);
+
+        let call = &method.call;
+        let encoder = &method.encoder;
+
+        let write_expression = if m.ctx {
+            quote_spanned! {m.span=> lbs::ctx::write_with(&#field, w, #encoder, &mut *ctx)?; }
+        } else if m.parallel {
+            quote_spanned! {m.span=> lbs::rayon::write_with(&#field, w, #encoder)?; }
+        } else if let Some(ref codec) = m.codec {
+            quote_spanned! {m.span=> #codec::write(&#field, w, #encoder)?; }
+        } else if m.secret {
+            quote_spanned! {m.span=> lbs::secrecy::write(&#field, w, #encoder)?; }
+        } else {
+            quote_spanned! {m.span=> #field.#call?; }
+        };
+
+        if container.indexed {
+            let id = m.id.unwrap();
+            let unknown_expression = unknown_fields.as_ref().map(|unknown| {
+                quote!(lbs::unknown::write_before(&#unknown, &mut unknown_next, Some(#id), &mut index)?;)
+            });
+            return quote_spanned! {m.span=>
+                #unknown_expression
+                if #must_write {
+                    index.field(#id, |w| {
+                        #write_expression
+                        Ok(())
+                    })?;
+                }
+            };
+        }
+
+        quote_spanned! {m.span=>
+            if #must_write {
+                #write_field_id
+                #write_expression
+            }
+        }
+    });
+
+    let oneof_checks = generate_oneof_checks(&meta, |m| m.write_access(place));
+
+    // Indexed values are buffered to know their offsets
+    if container.indexed {
+        let (unknown_init, unknown_rest) = match unknown_fields {
+            Some(ref unknown) => (
+                quote!(let mut unknown_next = 0;),
+                quote!(lbs::unknown::write_before(&#unknown, &mut unknown_next, None, &mut index)?;),
+            ),
+            None => (quote!(), quote!()),
+        };
+
+        return quote! {
+            #oneof_checks
+            let mut index = lbs::indexed::IndexWriter::new();
+            #unknown_init
+            #(#write_expressions)*
+            #unknown_rest
+            index.finish(w)
+        };
+    }
+
+    // Packed bools are written as one field with the highest id, thus it goes last
+    let packed: Vec<&Meta> = meta.iter().filter(|m| m.packed).collect();
+
+    let (packed_count_expression, packed_write_expression) = match packed.len() {
+        0 => (quote!(), quote!()),
+        _ => {
+            let base = packed.iter().map(|m| m.id.unwrap()).min().unwrap();
+            let bit_count =
+                (packed.iter().map(|m| m.id.unwrap()).max().unwrap() - base) as usize + 1;
+            let write_field_id = container.write_field_id(PACKED_BOOLS_ID);
+
+            let bit_expressions = packed.iter().map(|m| {
+                let field = m.write_access(place);
+                let bit = (m.id.unwrap() - base) as usize;
+                quote_spanned! {m.span=> bits[#bit] = #field; }
+            });
+
+            let count_expression = quote!(field_count += 1;);
+            let write_expression = quote! {
+                let mut bits = [false; #bit_count];
+                #(#bit_expressions)*
+                #write_field_id
+                lbs::write::write_packed_bools(w, #base, &bits)?;
+            };
+
+            // Packed bools are written as a whole, if any of them is requested
+            match method.filtered {
+                true => {
+                    let ids = packed.iter().map(|m| m.id.unwrap());
+                    let requested = quote!(#(fields.contains(&#ids))||*);
+                    (
+                        quote!(if #requested { #count_expression }),
+                        quote!(if #requested { #write_expression }),
+                    )
+                }
+                false => (count_expression, write_expression),
+            }
+        }
+    };
+
+    let write_field_count = container.write_field_count();
+
+    let prev_id = match container.compact {
+        true => quote!(let mut prev_id: u16 = 0;),
+        false => quote!(),
+    };
+
+    // Complete body of lbs_write()
+    quote! {
+        #oneof_checks
+        let mut field_count: u16 = 0;
+
+        #(#field_count_expressions)*
+        #packed_count_expression
+
+        #write_field_count
+
+        if field_count > 0 {
+            #prev_id
+            #(#write_expressions)*
+            #packed_write_expression
+        }
+
+        Ok(())
+    }
+}
+
+fn generate_write_body_for_enum(
+    data: &DataEnum,
+    container: &ContainerMeta,
+    method: &WriteMethod,
+) -> TokenStream {
+    if container.indexed {
+        panic_indexed_enum();
+    }
+
+    // Gather meta
+    let meta = gather_enum_meta(data, container);
+
+    // Write expressions
+    let write_expressions = meta.iter().map(|m| {
+        let variant_name = &m.name;
+        let write_variant_id = container.write_variant_id(m.id.unwrap());
+        let call = &method.call;
+        let encoder = &method.encoder;
+
+        // Fields of struct-like variants are written like fields of a struct, skipped ones aren't bound
+        if let Some(fields) = m.struct_payload() {
+            let bindings = gather_struct_meta(fields, container).into_iter().map(|f| {
+                let field_name = &f.name;
+                match f.skip {
+                    true => quote!(#field_name: _),
+                    false => {
+                        let binding = field_binding(f.index);
+                        quote!(#field_name: #binding)
+                    }
+                }
+            });
+            let write_body = generate_write_body_for_struct(
+                fields,
+                container,
+                method,
+                Place::Variant(variant_name),
+            );
+            return quote_spanned! {m.span=>
+                Self::#variant_name { #(#bindings),* } => {
+                    #write_variant_id;
+                    #write_body
+                },
+            };
+        }
+
+        if m.variant_fields.is_some() {
+            let write_expression = match m.ctx {
+                true => quote!(lbs::ctx::write_with(inner, w, #encoder, &mut *ctx)?),
+                false => quote!(inner.#call?),
+            };
+            return quote_spanned! {m.span=>
+                Self::#variant_name(inner) => {
+                    #write_variant_id;
+                    #write_expression;
+                    Ok(())
+                },
+            };
+        }
+
+        quote_spanned! {m.span=>
+            Self::#variant_name => {
+                #write_variant_id;
+                Ok(())
+            },
+        }
+    });
+
+    // Unknown variant is written back as it was read
+    let other_expression = other_variant(data).map(|m| {
+        let variant_name = &m.name;
+        let write_variant_id = container.write_variant_id(quote!(*id));
+
+        match m.keeps_payload() {
+            true => quote_spanned! {m.span=>
+                Self::#variant_name(id, payload) => {
+                    #write_variant_id;
+                    std::io::Write::write_all(w, payload)?;
+                    Ok(())
+                },
+            },
+            false => quote_spanned! {m.span=>
+                Self::#variant_name(id) => {
+                    #write_variant_id;
+                    Ok(())
+                },
+            },
+        }
+    });
+
+    // Complete body of lbs_write()
+    quote! {
+        match self {
+            #(#write_expressions)*
+            #other_expression
+        }
+    }
+}
+
+/// Ids of compact headers are counted at their largest, fields with custom encoding as empty.
+fn generate_size_hint_for_struct(
+    fields: &Fields,
+    container: &ContainerMeta,
+    place: Place,
+) -> TokenStream {
+    let meta = gather_struct_meta(fields, container);
+    let encoder = quote!(&mut lbs::Encoder::new());
+
+    let id_size = match container.compact {
+        true => quote!(lbs::size::VARINT_U16_MAX_SIZE),
+        false => quote!(2),
+    };
+
+    // Indexed fields are written without ids, but have entries of id, offset and length
+    let (header_size, field_overhead) = match container.indexed {
+        true => (quote!(2 + 4), quote!(10)),
+        false => (id_size.clone(), id_size.clone()),
+    };
+
+    let field_expressions = meta.iter().filter(|m| !m.skip && !m.packed).map(|m| {
+        let field = m.write_access(place);
+        let must_write = container.must_write(m, &encoder, place);
+
+        let field_size = match (&m.codec, m.ctx || m.secret) {
+            (Some(mirror), false) if m.remote => {
+                quote!(lbs::remote::LBSRemoteWrite::<#mirror>::lbs_remote_size_hint(&#field))
+            }
+            (Some(_), _) | (_, true) => quote!(0),
+            (None, false) => quote!(#field.lbs_size_hint()),
+        };
+
+        quote_spanned! {m.span=>
+            if #must_write {
+                size += #field_overhead + #field_size;
+            }
+        }
+    });
+
+    // Packed bools are a field of base, bit count and bits
+    let packed: Vec<u16> = meta
+        .iter()
+        .filter(|m| m.packed)
+        .map(|m| m.id.unwrap())
+        .collect();
+
+    let packed_expression = match (packed.iter().min(), packed.iter().max()) {
+        (Some(min), Some(max)) => {
+            let bytes = (*max - *min) as usize / 8 + 1;
+            quote!(size += #id_size + 2 + 2 + #bytes;)
+        }
+        _ => quote!(),
+    };
+
+    let unknown_expression = meta.iter().find(|m| m.unknown_fields).map(|m| {
+        let field = m.write_access(place);
+        quote!(size += lbs::unknown::size_hint(&#field);)
+    });
+
+    quote! {
+        let mut size: usize = #header_size;
+        #(#field_expressions)*
+        #packed_expression
+        #unknown_expression
+        size
+    }
+}
+
+fn generate_size_hint_for_enum(data: &DataEnum, container: &ContainerMeta) -> TokenStream {
+    let meta = gather_enum_meta(data, container);
+
+    let id_size = |id: u16| match container.compact {
+        true => quote!(lbs::varint::u16_size(#id)),
+        false => quote!(2),
+    };
+
+    let size_expressions = meta.iter().map(|m| {
+        let variant_name = &m.name;
+        let id_size = id_size(m.id.unwrap());
+
+        if let Some(fields) = m.struct_payload() {
+            let bindings = gather_struct_meta(fields, container).into_iter().map(|f| {
+                let field_name = &f.name;
+                match f.skip {
+                    true => quote!(#field_name: _),
+                    false => {
+                        let binding = field_binding(f.index);
+                        quote!(#field_name: #binding)
+                    }
+                }
+            });
+            let size_hint =
+                generate_size_hint_for_struct(fields, container, Place::Variant(variant_name));
+            return quote_spanned! {m.span=>
+                Self::#variant_name { #(#bindings),* } => #id_size + { #size_hint },
+            };
+        }
+
+        match (&m.variant_fields, m.ctx) {
+            (Some(_), false) => quote_spanned! {m.span=>
+                Self::#variant_name(inner) => #id_size + inner.lbs_size_hint(),
+            },
+            (Some(_), true) => quote_spanned! {m.span=>
+                Self::#variant_name(_) => #id_size,
+            },
+            (None, _) => quote_spanned! {m.span=>
+                Self::#variant_name => #id_size,
+            },
+        }
+    });
+
+    let other_expression = other_variant(data).map(|m| {
+        let variant_name = &m.name;
+        match (container.compact, m.keeps_payload()) {
+            (true, true) => quote_spanned! {m.span=>
+                Self::#variant_name(id, payload) => lbs::varint::u16_size(*id) + payload.len(),
+            },
+            (false, true) => quote_spanned! {m.span=>
+                Self::#variant_name(_, payload) => 2 + payload.len(),
+            },
+            (true, false) => quote_spanned! {m.span=>
+                Self::#variant_name(id) => lbs::varint::u16_size(*id),
+            },
+            (false, false) => quote_spanned! {m.span=>
+                Self::#variant_name(_) => 2,
+            },
+        }
+    });
+
+    quote! {
+        match self {
+            #(#size_expressions)*
+            #other_expression
+        }
+    }
+}
+
+/// With `projected`, generated body expects `fields: &[u16]` in scope and skips fields,
+/// which are not listed there.
+/// Flavor of struct read body.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReadBody {
+    /// lbs_read_with()
+    Full,
+    /// lbs_read_projected()
+    Projected,
+    /// lbs_read_with_report()
+    Report,
+    /// lbs_read_into_with()
+    InPlace,
+    /// lbs_merge_with()
+    Merge,
+    /// LBSReadAsync::lbs_read_async()
+    Async,
+    /// LBSReadRef::lbs_read_ref()
+    Borrowed,
+}
+
+fn generate_read_body_for_struct(
+    fields: &Fields,
+    container: &ContainerMeta,
+    body: ReadBody,
+    place: Place,
+) -> TokenStream {
+    let projected = body == ReadBody::Projected;
+    let report = body == ReadBody::Report;
+    let in_place = body == ReadBody::InPlace;
+    let merge = body == ReadBody::Merge;
+    let asynchronous = body == ReadBody::Async;
+    let borrowed = body == ReadBody::Borrowed;
+
+    if asynchronous && container.indexed {
+        panic_async_unsupported("indexed containers");
+    }
+
+    if borrowed && container.indexed {
+        panic_ref_unsupported("indexed containers");
+    }
+
+    // Gather meta.
+    let meta = gather_struct_meta(fields, container);
+
+    let default_value = |f: &Meta| match f.default {
+        Some(ref default) => quote!(#default),
+        None => quote!(Default::default()),
+    };
+
+    // Field initialization expressions.
+    let field_init_expressions = meta.iter().map(|f| {
+        let field_name = &f.name;
+        let default = default_value(f);
+        quote_spanned! {f.span=>
+            #field_name: #default,
+        }
+    });
+
+    // Fields, which were not read in place, are reset to defaults.
+    let read_count = meta.iter().filter(|f| !f.skip).count();
+    let seen_index = |i: usize| meta[..i].iter().filter(|f| !f.skip).count();
+
+    let reset_expressions = meta.iter().enumerate().map(|(i, f)| {
+        let target = f.read_access(place);
+        let default = default_value(f);
+        let seen = seen_index(i);
+        match f.skip {
+            // Unknown fields are cleared before reading, as they are gathered while reading
+            true if f.unknown_fields => quote!(),
+            true => quote_spanned! {f.span=>
+                #target = #default;
+            },
+            false => quote_spanned! {f.span=>
+                if !seen[#seen] {
+                    #target = #default;
+                }
+            },
+        }
+    });
+
+    // Required fields stuff.
+    let required_count = meta.iter().filter(|f| f.required).count();
+    let mut required_index_read = 0usize;
+    let mut required_index_check = 0usize;
+
+    // Read expressions.
+    let read_expressions = meta.iter().enumerate().filter(|(_, f)| !f.skip).map(|(i, f)| {
+        let field_id = f.id;
+        let target = f.read_access(place);
+
+        let read_function = match f.codec {
+            Some(ref codec) => quote!(#codec::read),
+            None => quote!(lbs::read::read_with),
+        };
+
+        // Custom message of failed conversion replaces the conversion error
+        let codec_call = match (&f.codec, &f.try_from) {
+            (Some(codec), Some((_, Some(error)))) => quote!(#codec::read_or(r, d, #error)),
+            _ => quote!(#read_function(r, d)),
+        };
+
+        let read_call = match (f.ctx, asynchronous) {
+            (true, true) => panic_async_unsupported("context fields"),
+            (false, true) if f.codec.is_some() => panic_async_unsupported("encoding attributes"),
+            (false, true) => quote!(lbs::tokio::read::read_with(r, d).await),
+            (true, false) if borrowed => panic_ref_unsupported("context fields"),
+            (true, false) => quote!(lbs::ctx::read_with(r, d, &mut *ctx)),
+            (false, false) if borrowed && f.codec.is_none() => quote!(lbs::borrow::read_with(r, d)),
+            (false, false) => codec_call,
+        };
+
+        // Merged oneof field replaces other fields of its group
+        let oneof_clear_expressions = meta.iter().filter(|o| {
+            merge && o.name != f.name && o.oneof.is_some() && o.oneof == f.oneof
+        }).map(|o| {
+            let other = o.read_access(place);
+            quote!(#other = None;)
+        });
+
+        // Values with custom encoding are always replaced
+        let read_statement = match (body, &f.codec) {
+            (ReadBody::Merge, None) if f.append => {
+                let ty = f.ty.as_ref().unwrap();
+                quote_spanned! {f.span=>
+                    Extend::extend(&mut #target, lbs::read::read_with::<#ty, _>(r, d).map_err(|e| e.with_field(#field_id))?);
+                }
+            }
+            (ReadBody::Merge, None) => quote_spanned! {f.span=>
+                lbs::read::merge_with(&mut #target, r, d).map_err(|e| e.with_field(#field_id))?;
+                #(#oneof_clear_expressions)*
+            },
+            (ReadBody::InPlace, None) => {
+                let seen = seen_index(i);
+                quote_spanned! {f.span=>
+                    lbs::read::read_into_with(&mut #target, r, d).map_err(|e| e.with_field(#field_id))?;
+                    seen[#seen] = true;
+                }
+            }
+            (ReadBody::InPlace, Some(_)) => {
+                let seen = seen_index(i);
+                quote_spanned! {f.span=>
+                    #target = #read_call.map_err(|e| e.with_field(#field_id))?;
+                    seen[#seen] = true;
+                }
+            }
+            (_, _) => quote_spanned! {f.span=>
+                #target = #read_call.map_err(|e| e.with_field(#field_id))?;
+            },
+        };
+
+        // Skipped values are consumed by their type, or read and dropped if codec is custom
+        let skip_expression = match projected {
+            true => {
+                let ty = f.ty.as_ref().unwrap();
+                let skip = match f.codec {
+                    Some(_) if f.delimited => quote!(lbs::delimited::skip(r, d)),
+                    Some(_) if f.try_from.is_some() => {
+                        let proxy = &f.try_from.as_ref().unwrap().0;
+                        quote!(<#proxy as lbs::LBSRead>::lbs_skip(r, d))
+                    }
+                    Some(_) => quote!(#read_function::<#ty, _>(r, d).map(|_| ())),
+                    None => quote!(<#ty as lbs::LBSRead>::lbs_skip(r, d)),
+                };
+                match container.indexed {
+                    true => quote_spanned! {f.span=>
+                        #field_id if !fields.contains(&#field_id) => return Ok(false),
+                    },
+                    false => quote_spanned! {f.span=>
+                        #field_id if !fields.contains(&#field_id) => #skip.map_err(|e| e.with_field(#field_id))?,
+                    },
+                }
+            }
+            false => quote!(),
+        };
+
+        let report_expression = match report {
+            true => quote!(present.insert(#field_id);),
+            false => quote!(),
+        };
+
+        // Retired fields are still read, but their producers are reported
+        let deprecated_expression = match f.deprecated {
+            Some(ref note) => quote_spanned! {f.span=>
+                lbs::metrics::deprecated_field(d, std::any::type_name::<Self>(), #field_id, #note);
+            },
+            None => quote!(),
+        };
+
+        let expr = if f.required {
+            quote_spanned! {f.span=>
+                #skip_expression
+                #field_id => {
+                    #deprecated_expression
+                    #read_statement
+                    required_present[#required_index_read] = true;
+                    #report_expression
+                }
+            }
+        } else {
+            quote_spanned! {f.span=>
+                #skip_expression
+                #field_id => {
+                    #deprecated_expression
+                    #read_statement
+                    #report_expression
+                }
+            }
+        };
+
+        if f.required {
+            required_index_read += 1;
+        }
+
+        expr
+    });
+
+    // Packed bools are also readable by their own ids, for compatibility with unpacked encoding.
+    let packed_bool_expressions =
+        meta.iter()
+            .enumerate()
+            .filter(|(_, f)| f.packed)
+            .map(|(i, f)| {
+                let field_id = f.id;
+                let target = f.read_access(place);
+
+                let report_expression = match report {
+                    true => quote!(present.insert(#field_id);),
+                    false => quote!(),
+                };
+
+                let seen_expression = match in_place {
+                    true => {
+                        let seen = seen_index(i);
+                        quote!(seen[#seen] = true;)
+                    }
+                    false => quote!(),
+                };
+
+                match f.required {
+                    true => {
+                        let required_index = meta[..i].iter().filter(|f| f.required).count();
+                        quote_spanned! {f.span=>
+                            #field_id => {
+                                #target = value;
+                                required_present[#required_index] = true;
+                                #seen_expression
+                                #report_expression
+                            }
+                        }
+                    }
+                    false => quote_spanned! {f.span=>
+                        #field_id => {
+                            #target = value;
+                            #seen_expression
+                            #report_expression
+                        }
+                    },
+                }
+            });
+
+    // Bits in between packed bools are gaps written for other fields, not unknown fields
+    let packed_ids = meta.iter().filter(|f| f.packed).map(|f| f.id);
+    let packed_gap_expression = match (packed_ids.clone().min(), packed_ids.max()) {
+        (Some(base), Some(max)) => quote!(#base..=#max => {}),
+        _ => quote!(),
+    };
+
+    let packed_read_expression = match meta.iter().any(|f| f.packed) {
+        true if asynchronous => quote! {
+            #PACKED_BOOLS_ID => lbs::tokio::read::read_packed_bools(r, |id, value| match id {
+                #(#packed_bool_expressions)*
+                #packed_gap_expression
+                id => lbs::stats::unknown_field(d, std::any::type_name::<Self>(), id),
+            })
+            .await
+            .map_err(|e| e.with_field(#PACKED_BOOLS_ID))?,
+        },
+        true => quote! {
+            #PACKED_BOOLS_ID => lbs::read::read_packed_bools(r, |id, value| match id {
+                #(#packed_bool_expressions)*
+                #packed_gap_expression
+                id => lbs::stats::unknown_field(d, std::any::type_name::<Self>(), id),
+            })
+            .map_err(|e| e.with_field(#PACKED_BOOLS_ID))?,
+        },
+        false => quote!(),
+    };
+
+    // Required check expressions, merged values only overlay present fields.
+    let required_check_expressions = meta.iter().filter(|f| f.required && !merge).map(|f| {
+        let field_id = f.id;
+        let projected_check = match projected {
+            true => quote!(fields.contains(&#field_id) &&),
+            false => quote!(),
+        };
+
+        // Fields absent in the source schema version are left default
+        let lifecycle_check = match f.lifecycle_check(quote!(lbs::Decoder::version(d))) {
+            Some(present) => quote!(#present &&),
+            None => quote!(),
+        };
+
+        let expr = quote_spanned! {f.span=>
+            if #projected_check #lifecycle_check !required_present[#required_index_check] {
+                return Err(lbs::error::LBSError::RequiredButMissing.with_field(#field_id));
+            }
+        };
+
+        required_index_check += 1;
+        expr
+    });
+
+    let (read_field_count, read_field_id) = match asynchronous {
+        true => (
+            container.read_field_count_async(),
+            container.read_field_id_async(),
+        ),
+        false => (container.read_field_count(), container.read_field_id()),
+    };
+
+    let prev_id = match container.compact {
+        true => quote!(let mut prev_id: Option<u16> = None;),
+        false => quote!(),
+    };
+
+    // Unknown fields are gathered, if struct keeps them
+    let unknown_fields = meta.iter().find(|f| f.unknown_fields);
+
+    let unknown_read_statement = match unknown_fields {
+        Some(f) => {
+            let target = f.read_access(place);
+            quote!(lbs::unknown::capture(&mut #target, id, r)?;)
+        }
+        None => quote!(return Ok(false);),
+    };
+
+    let unknown_clear = match (unknown_fields, body) {
+        (Some(f), ReadBody::InPlace) => {
+            let target = f.read_access(place);
+            quote!(#target.clear();)
+        }
+        _ => quote!(),
+    };
+
+    // Indexed fields are read from limited readers, unknown ones are skipped by their length
+    let read_loop = match container.indexed {
+        true => quote! {
+            #unknown_clear
+            lbs::indexed::read_fields(r, |id, r| {
+                lbs::stats::field(d);
+                match id {
+                    #(#read_expressions)*
+                    _ => {
+                        lbs::stats::unknown_field(d, std::any::type_name::<Self>(), id);
+                        #unknown_read_statement
+                    }
+                }
+                Ok(true)
+            })?;
+        },
+        false => quote! {
+            for _ in 0..#read_field_count {
+                lbs::stats::field(d);
+                match #read_field_id {
+                    #(#read_expressions)*
+                    #packed_read_expression
+                    id => lbs::stats::unknown_field(d, std::any::type_name::<Self>(), id),
+                }
+            }
+        },
+    };
+
+    let oneof_checks = generate_oneof_checks(&meta, |m| m.read_access(place));
+
+    // Ids of present fields are gathered only for lbs_read_with_report()
+    let (present, result) = match body {
+        ReadBody::Report => (
+            quote!(let mut present = lbs::read::FieldSet::new();),
+            quote!(Ok((_self, present))),
+        ),
+        ReadBody::InPlace => (quote!(let mut seen = [false; #read_count];), quote!(Ok(()))),
+        ReadBody::Merge => (quote!(), quote!(Ok(()))),
+        _ => (quote!(), quote!(Ok(_self))),
+    };
+
+    // Fields of a variant are read into a tuple, which the variant is built from
+    let (init, result) = match place {
+        Place::Struct => (None, result),
+        Place::Variant(variant_name) => {
+            let defaults = meta.iter().map(default_value);
+            let types = meta.iter().map(|f| f.ty.as_ref().unwrap());
+            let members = meta.iter().map(|f| &f.name);
+            let indexes = meta.iter().map(|f| syn::Index::from(f.index));
+            (
+                Some(quote! {
+                    let mut _self: (#(#types,)*) = (#(#defaults,)*);
+                }),
+                quote!(Ok(Self::#variant_name { #(#members: _self.#indexes),* })),
+            )
+        }
+    };
+
+    let (init, reset) = match (body, init) {
+        (ReadBody::InPlace, _) => (quote!(let _self = self;), quote!(#(#reset_expressions)*)),
+        (ReadBody::Merge, _) => (quote!(let _self = self;), quote!()),
+        (_, Some(init)) => (init, quote!()),
+        _ => (
+            quote! {
+                let mut _self = Self {
+                    #(#field_init_expressions)*
+                };
+            },
+            quote!(),
+        ),
+    };
+
+    // Complete body of lbs_read().
+    quote! {
+        #init
+
+        let mut required_present = [false; #required_count];
+        #present
+        #prev_id
+        #read_loop
+        #reset
+
+        #(#required_check_expressions)*
+        #oneof_checks
+
+        #result
+    }
+}
+
+fn generate_read_body_for_enum(
+    data: &DataEnum,
+    container: &ContainerMeta,
+    body: ReadBody,
+) -> TokenStream {
+    if container.indexed {
+        panic_indexed_enum();
+    }
+
+    let asynchronous = body == ReadBody::Async;
+    let borrowed = body == ReadBody::Borrowed;
+
+    // Gather meta
+    let meta = gather_enum_meta(data, container);
+
+    // Read expressions
+    let read_expressions = meta.iter().map(|m| {
+        let variant_id = m.read_ids();
+        let variant_name = &m.name;
+
+        if let Some(fields) = m.struct_payload() {
+            let read_body = generate_read_body_for_struct(
+                fields,
+                container,
+                body,
+                Place::Variant(variant_name),
+            );
+            return quote_spanned! {m.span=>
+                #variant_id => {
+                    #read_body
+                }
+            };
+        }
+
+        if m.variant_fields.is_some() {
+            let read_call = match (m.ctx, asynchronous) {
+                (true, true) => panic_async_unsupported("context variants"),
+                (false, true) => quote!(lbs::tokio::read::read_with(r, d).await),
+                (true, false) if borrowed => panic_ref_unsupported("context variants"),
+                (true, false) => quote!(lbs::ctx::read_with(r, d, &mut *ctx)),
+                (false, false) if borrowed => quote!(lbs::borrow::read_with(r, d)),
+                (false, false) => quote!(lbs::read::read_with(r, d)),
+            };
+            return quote_spanned! {m.span=>
+                #variant_id => Ok(Self::#variant_name(#read_call?)),
+            };
+        }
+
+        quote_spanned! {m.span=>
+            #variant_id => Ok(Self::#variant_name),
+        }
+    });
+
+    let read_variant_id = match asynchronous {
+        true => container.read_variant_id_async(),
+        false => container.read_variant_id(),
+    };
+
+    // Payload of unknown variant is the rest of input, or there is none
+    let other_expression = match other_variant(data) {
+        Some(m) if !m.keeps_payload() => {
+            let variant_name = &m.name;
+            quote_spanned! {m.span=>
+                id => Ok(Self::#variant_name(id)),
+            }
+        }
+        Some(m) if asynchronous => {
+            let variant_name = &m.name;
+            quote_spanned! {m.span=>
+                id => Ok(Self::#variant_name(id, lbs::tokio::read::read_to_end(r).await?)),
+            }
+        }
+        Some(m) => {
+            let variant_name = &m.name;
+            quote_spanned! {m.span=>
+                id => Ok(Self::#variant_name(id, lbs::read::read_to_end(r)?)),
+            }
+        }
+        None => quote!(_ => Err(lbs::error::LBSError::UnexpectedVariant)),
+    };
+
+    // Complete body of lbs_read()
+    quote! {
+        match #read_variant_id {
+            #(#read_expressions)*
+            #other_expression
+        }
+    }
+}
+
+/// Body of lbs_read_into_with() or lbs_merge_with().
+fn generate_read_into_body_for_enum(
+    data: &DataEnum,
+    container: &ContainerMeta,
+    body: ReadBody,
+) -> TokenStream {
+    let read_into_function = match body {
+        ReadBody::Merge => quote!(lbs::read::merge_with),
+        _ => quote!(lbs::read::read_into_with),
+    };
+
+    if container.indexed {
+        panic_indexed_enum();
+    }
+
+    // Gather meta
+    let meta = gather_enum_meta(data, container);
+
+    // Payload of the same variant is read in place, other variants replace the value
+    let read_expressions = meta.iter().map(|m| {
+        let variant_id = m.read_ids();
+        let variant_name = &m.name;
+
+        // Struct-like variants are always replaced
+        if let Some(fields) = m.struct_payload() {
+            let read_body = generate_read_body_for_struct(
+                fields,
+                container,
+                ReadBody::Full,
+                Place::Variant(variant_name),
+            );
+            return quote_spanned! {m.span=>
+                #variant_id => {
+                    let value: Result<Self, lbs::error::LBSError> = { #read_body };
+                    *self = value?;
+                    Ok(())
+                }
+            };
+        }
+
+        if m.variant_fields.is_some() {
+            return quote_spanned! {m.span=>
+                #variant_id => match self {
+                    Self::#variant_name(value) => #read_into_function(value, r, d),
+                    #[allow(unreachable_patterns)]
+                    _ => {
+                        *self = Self::#variant_name(lbs::read::read_with(r, d)?);
+                        Ok(())
+                    }
+                },
+            };
+        }
+
+        quote_spanned! {m.span=>
+            #variant_id => {
+                *self = Self::#variant_name;
+                Ok(())
+            }
+        }
+    });
+
+    let read_variant_id = container.read_variant_id();
+
+    let other_expression = match other_variant(data) {
+        Some(m) => {
+            let variant_name = &m.name;
+            let value = match m.keeps_payload() {
+                true => quote!(Self::#variant_name(id, lbs::read::read_to_end(r)?)),
+                false => quote!(Self::#variant_name(id)),
+            };
+            quote_spanned! {m.span=>
+                id => {
+                    *self = #value;
+                    Ok(())
+                }
+            }
+        }
+        None => quote!(_ => Err(lbs::error::LBSError::UnexpectedVariant)),
+    };
+
+    // Complete body of lbs_read_into_with() or lbs_merge_with()
+    quote! {
+        match #read_variant_id {
+            #(#read_expressions)*
+            #other_expression
+        }
+    }
+}
+
+fn generate_diff_bodies_for_struct(
+    fields: &Fields,
+    container: &ContainerMeta,
+) -> (TokenStream, TokenStream) {
+    // Gather meta
+    let meta = gather_struct_meta(fields, container);
+    let meta = meta.iter().filter(|m| !m.skip);
+
+    // Diff expressions
+    let diff_expressions = meta.clone().map(|m| {
+        let id = m.id.unwrap();
+        let field_name = &m.name;
+
+        quote_spanned! {m.span=>
+            lbs::diff::diff_field(w, count, #id, &self.#field_name, &other.#field_name)?;
+        }
+    });
+
+    // Apply expressions
+    let apply_expressions = meta.map(|m| {
+        let id = m.id.unwrap();
+        let field_name = &m.name;
+
+        quote_spanned! {m.span=>
+            #id => lbs::diff::LBSDiff::lbs_apply(&mut self.#field_name, r),
+        }
+    });
+
+    // Complete bodies of lbs_diff() and lbs_apply()
+    (
+        quote! {
+            lbs::diff::diff_fields(w, |w, count| {
+                #(#diff_expressions)*
+                Ok(())
+            })
+        },
+        quote! {
+            lbs::diff::apply_fields(r, |r, id| match id {
+                #(#apply_expressions)*
+                _ => Err(lbs::diff::unknown_field(id)),
+            })
+        },
+    )
+}
+
+fn generate_diff_bodies_for_enum(
+    data: &DataEnum,
+    container: &ContainerMeta,
+) -> (TokenStream, TokenStream) {
+    // Gather meta
+    let meta = gather_enum_meta(data, container);
+
+    // Diff expressions, variants with payload recurse if both sides match
+    let diff_expressions = meta.iter().map(|m| {
+        let variant_name = &m.name;
+
+        // Struct-like variants are replaced as a whole
+        match m.variant_fields {
+            Some(ref fields) if is_struct_payload(fields) => quote!(),
+            Some(_) => quote_spanned! {m.span=>
+                (Self::#variant_name(a), Self::#variant_name(b)) => lbs::diff::diff_nested(w, a, b),
+            },
+            None => quote_spanned! {m.span=>
+                (Self::#variant_name, Self::#variant_name) => Ok(false),
+            },
+        }
+    });
+
+    // Apply expressions
+    let apply_expressions = meta
+        .iter()
+        .filter(|m| m.variant_fields.is_some() && m.struct_payload().is_none())
+        .map(|m| {
+            let variant_name = &m.name;
+
+            quote_spanned! {m.span=>
+                Self::#variant_name(inner) => lbs::diff::LBSDiff::lbs_apply(inner, r),
+            }
+        });
+
+    // Complete bodies of lbs_diff() and lbs_apply()
+    (
+        quote! {
+            match (self, other) {
+                #(#diff_expressions)*
+                _ => lbs::diff::diff_replace(w, other),
+            }
+        },
+        quote! {
+            if lbs::diff::read_replace_tag(r)? {
+                *self = lbs::LBSRead::lbs_read(r)?;
+                return Ok(());
+            }
+
+            #[allow(unreachable_patterns)]
+            match self {
+                #(#apply_expressions)*
+                _ => Err(lbs::error::LBSError::UnexpectedVariant),
+            }
+        },
+    )
+}
+
+fn generate_schema_for_struct(
+    name: &str,
+    fields: &Fields,
+    container: &ContainerMeta,
+) -> TokenStream {
+    // Gather meta
+    let meta = gather_struct_meta(fields, container);
+
+    // Field expressions
+    let field_expressions = meta.iter().filter(|m| !m.skip).map(|m| {
+        let id = m.id.unwrap();
+        let field_name = m.label();
+        let required = m.required;
+        let ty = m.ty.as_ref().unwrap();
+
+        let codec = match m.codec {
+            Some(ref codec) => {
+                let codec = codec_name(codec);
+                quote!(Some(#codec.to_string()))
+            }
+            None => quote!(None),
+        };
+
+        quote_spanned! {m.span=>
+            lbs::schema::FieldSchema {
+                id: #id,
+                name: #field_name.to_string(),
+                required: #required,
+                codec: #codec,
+                schema: <#ty as lbs::schema::LBSSchema>::lbs_schema(),
+            },
+        }
+    });
+
+    let compact = container.compact;
+    let pack_bools = container.pack_bools;
+    let indexed = container.indexed;
+
+    quote! {
+        lbs::schema::Schema::Struct(lbs::schema::StructSchema {
+            name: #name.to_string(),
+            fields: vec![#(#field_expressions)*],
+            compact: #compact,
+            pack_bools: #pack_bools,
+            indexed: #indexed,
+        })
+    }
+}
+
+/// Codec is named by the last segment of its path, like `sparse` for `lbs::sparse`,
+/// or by its module and parameters, like `fixed_point(100)` for `lbs::fixed_point::FixedPoint::<100>`.
+fn codec_name(codec: &TokenStream) -> String {
+    let codec = codec.to_string().replace(' ', "");
+
+    match codec.split_once("::<") {
+        Some((path, parameter)) => {
+            let module = path.rsplit("::").nth(1).unwrap();
+            let parameters = parameter.trim_end_matches('>').replace(',', ", ");
+            format!("{}({})", module, parameters)
+        }
+        None => codec.rsplit("::").next().unwrap().to_string(),
+    }
+}
+
+/// Types of fields are compared by their spelling, including codecs.
+fn generate_shape_for_struct(fields: &Fields, container: &ContainerMeta) -> TokenStream {
+    let meta = gather_struct_meta(fields, container);
+
+    let entries = meta.iter().filter(|m| !m.skip).map(|m| {
+        let id = m.id.unwrap();
+        let required = m.required;
+        let ty = type_fingerprint(m.ty.as_ref().unwrap(), m.codec.as_ref());
+
+        quote! {
+            lbs::schema::ShapeEntry { id: #id, required: #required, ty: #ty },
+        }
+    });
+
+    let compact = container.compact;
+    let pack_bools = container.pack_bools;
+    let indexed = container.indexed;
+
+    quote! {
+        lbs::schema::Shape {
+            is_enum: false,
+            compact: #compact,
+            pack_bools: #pack_bools,
+            indexed: #indexed,
+            entries: &[#(#entries)*],
+        }
+    }
+}
+
+fn generate_shape_for_enum(data: &DataEnum, container: &ContainerMeta) -> TokenStream {
+    let meta = gather_enum_meta(data, container);
+
+    let entries = meta.iter().map(|m| {
+        let id = m.id.unwrap();
+        let ty = match m.variant_fields {
+            Some(ref fields) => payload_fingerprint(fields, container),
+            None => 0,
+        };
+
+        quote! {
+            lbs::schema::ShapeEntry { id: #id, required: true, ty: #ty },
+        }
+    });
+
+    let compact = container.compact;
+
+    quote! {
+        lbs::schema::Shape {
+            is_enum: true,
+            compact: #compact,
+            pack_bools: false,
+            indexed: false,
+            entries: &[#(#entries)*],
+        }
+    }
+}
+
+/// FNV-1a hash of type and codec spelling.
+fn type_fingerprint(ty: &syn::Type, codec: Option<&TokenStream>) -> u64 {
+    let mut spelling = ty.to_token_stream().to_string();
+
+    if let Some(codec) = codec {
+        spelling.push_str(" as ");
+        spelling.push_str(&codec.to_string());
+    }
+
+    fnv1a(&spelling)
+}
+
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn generate_schema_for_enum(
+    name: &syn::Ident,
+    data: &DataEnum,
+    container: &ContainerMeta,
+) -> TokenStream {
+    // Gather meta
+    let meta = gather_enum_meta(data, container);
+
+    // Variant expressions
+    let variant_expressions = meta.iter().map(|m| {
+        let id = m.id.unwrap();
+        let variant_name = m.label();
+
+        let schema = match m.variant_fields {
+            Some(ref fields) if is_struct_payload(fields) => {
+                let struct_name = format!("{}::{}", name, variant_name);
+                let schema = generate_schema_for_struct(&struct_name, fields, container);
+                quote!(Some(#schema))
+            }
+            Some(ref fields) => {
+                let ty = &fields.iter().next().unwrap().ty;
+                quote!(Some(<#ty as lbs::schema::LBSSchema>::lbs_schema()))
+            }
+            None => quote!(None),
+        };
+
+        quote_spanned! {m.span=>
+            lbs::schema::VariantSchema {
+                id: #id,
+                name: #variant_name.to_string(),
+                schema: #schema,
+            },
+        }
+    });
+
+    let name = name.to_string();
+    let compact = container.compact;
+
+    quote! {
+        lbs::schema::Schema::Enum(lbs::schema::EnumSchema {
+            name: #name.to_string(),
+            variants: vec![#(#variant_expressions)*],
+            compact: #compact,
+        })
+    }
+}
+
+/// Every field is counted as present.
+fn generate_max_size_for_struct(fields: &Fields, container: &ContainerMeta) -> TokenStream {
+    let meta = gather_struct_meta(fields, container);
+
+    if let Some(m) = meta.iter().find(|m| !m.skip && m.codec.is_some()) {
+        panic!(
+            "field {} with custom encoding has no maximum size",
+            m.label()
+        );
+    }
+
+    let id_size = match container.compact {
+        true => quote!(lbs::size::VARINT_U16_MAX_SIZE),
+        false => quote!(2),
+    };
+
+    // Indexed fields are written without ids, but have entries of id, offset and length
+    let (header_size, field_overhead) = match container.indexed {
+        true => (quote!(2 + 4), quote!(10)),
+        false => (id_size.clone(), id_size.clone()),
+    };
+
+    let field_sizes = meta.iter().filter(|m| !m.skip && !m.packed).map(|m| {
+        let ty = m.ty.as_ref().unwrap();
+        quote_spanned! {m.span=>
+            + #field_overhead + <#ty as lbs::size::LBSMaxSize>::LBS_MAX_SIZE
+        }
+    });
+
+    // Packed bools are written as a single field of base, count and bits
+    let packed: Vec<u16> = meta
+        .iter()
+        .filter(|m| m.packed)
+        .map(|m| m.id.unwrap())
+        .collect();
+    let packed_size = match (packed.iter().min(), packed.iter().max()) {
+        (Some(min), Some(max)) => {
+            let bytes = ((max - min) as usize + 1).div_ceil(8);
+            quote!(+ #id_size + 4 + #bytes)
+        }
+        _ => quote!(),
+    };
+
+    quote! {
+        #header_size #(#field_sizes)* #packed_size
+    }
+}
+
+fn generate_max_size_for_enum(data: &DataEnum, container: &ContainerMeta) -> TokenStream {
+    let meta = gather_enum_meta(data, container);
+
+    if other_variant(data).is_some_and(|m| m.keeps_payload()) {
+        panic!("payload of {} variant has no maximum size", ARGUMENT_OTHER);
+    }
+
+    let id_size = match container.compact {
+        true => quote!(lbs::size::VARINT_U16_MAX_SIZE),
+        false => quote!(2),
+    };
+
+    let payload_size = meta
+        .iter()
+        .fold(quote!(0), |size, m| match m.variant_fields {
+            Some(ref fields) if is_struct_payload(fields) => {
+                let payload = generate_max_size_for_struct(fields, container);
+                quote!(lbs::size::max(#size, #payload))
+            }
+            Some(ref fields) => {
+                let ty = &fields.iter().next().unwrap().ty;
+                quote!(lbs::size::max(#size, <#ty as lbs::size::LBSMaxSize>::LBS_MAX_SIZE))
+            }
+            None => size,
+        });
+
+    quote! {
+        #id_size + #payload_size
+    }
+}
+
+fn generate_columnar_bodies(
+    fields: &Fields,
+    container: &ContainerMeta,
+) -> (TokenStream, TokenStream) {
+    // Gather meta, bools are never packed in columns
+    let mut meta = gather_struct_meta(fields, container);
+    meta.iter_mut().for_each(|m| m.packed = false);
+    meta.sort_by_key(|m| m.id);
+
+    let columns: Vec<&Meta> = meta.iter().filter(|m| !m.skip).collect();
+    let column_count = columns.len() as u16;
+
+    // Column write expressions
+    let write_expressions = columns.iter().map(|m| {
+        let id = m.id.unwrap();
+        let field_name = &m.name;
+
+        let write_expression = match m.codec {
+            Some(ref codec) => quote!(#codec::write(&row.#field_name, w, e)),
+            None => quote!(row.#field_name.lbs_write_with(w, e)),
+        };
+
+        quote_spanned! {m.span=>
+            lbs::columnar::write_column(w, &mut buf, #id, rows, |row, w| #write_expression)?;
         }
-    })
-}
+    });
 
-fn generate_write_body_for_struct(fields: &FieldsNamed) -> TokenStream {
-    // Gather meta
-    let meta = gather_struct_meta(fields);
+    // Row initialization expressions
+    let field_init_expressions = meta.iter().map(|m| {
+        let field_name = &m.name;
+        match m.default {
+            Some(ref default) => quote_spanned! {m.span=> #field_name: #default, },
+            None => quote_spanned! {m.span=> #field_name: Default::default(), },
+        }
+    });
 
-    // Field count expressions
-    let field_count_expressions = meta.iter().filter(|m| !m.skip).map(|m| {
+    // Column read expressions
+    let required: Vec<&&Meta> = columns.iter().filter(|m| m.required).collect();
+    let required_count = required.len();
+
+    let read_expressions = columns.iter().map(|m| {
+        let id = m.id.unwrap();
         let field_name = &m.name;
+
+        let read_call = match (&m.codec, &m.try_from) {
+            (Some(codec), Some((_, Some(error)))) => quote!(#codec::read_or(r, d, #error)),
+            (Some(codec), _) => quote!(#codec::read(r, d)),
+            (None, _) => quote!(lbs::read::read_with(r, d)),
+        };
+
+        let mark_present = match required.iter().position(|r| r.id == m.id) {
+            Some(i) => quote!(required_present[#i] = true;),
+            None => quote!(),
+        };
+
         quote_spanned! {m.span=>
-            if self.#field_name.lbs_must_write() {
-                field_count += 1;
+            #id => {
+                for row in rows.iter_mut() {
+                    row.#field_name = #read_call.map_err(|e| e.with_field(#id))?;
+                }
+                #mark_present
             }
         }
     });
 
-    // Write expressions
-    let write_expressions = meta.iter().filter(|m| !m.skip).map(|m| {
-        let field_id = m.id;
-        let field_name = &m.name;
+    // Required check expressions
+    let required_check_expressions = required.iter().enumerate().map(|(i, m)| {
+        let id = m.id.unwrap();
         quote_spanned! {m.span=>
-            if self.#field_name.lbs_must_write() {
-                lbs::write::write_field_id(w, #field_id)?;
-                self.#field_name.lbs_write(w)?;
+            if !required_present[#i] {
+                return Err(lbs::error::LBSError::RequiredButMissing.with_field(#id));
             }
         }
     });
 
-    // Complete body of lbs_write()
-    quote! {
-        let mut field_count: u16 = 0;
+    (
+        quote! {
+            let mut buf = Vec::new();
+            lbs::write::write_len(w, rows.len())?;
+            lbs::write::write_field_count(w, #column_count)?;
+            #(#write_expressions)*
+            Ok(())
+        },
+        quote! {
+            let row_count = lbs::read::read_len(r)?;
+            let mut rows = Vec::with_capacity(row_count);
+            for _ in 0..row_count {
+                rows.push(Self {
+                    #(#field_init_expressions)*
+                });
+            }
 
-        #(#field_count_expressions)*
+            let mut required_present = [false; #required_count];
 
-        lbs::write::write_field_count(w, field_count)?;
+            lbs::columnar::read_columns(r, |id, r| {
+                match id {
+                    #(#read_expressions)*
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            })?;
 
-        if field_count > 0 {
-            #(#write_expressions)*
-        }
+            // Without rows there are no values to check
+            if row_count > 0 {
+                #(#required_check_expressions)*
+            }
 
-        Ok(())
-    }
+            Ok(rows)
+        },
+    )
 }
 
-fn generate_write_body_for_enum(data: &DataEnum) -> TokenStream {
-    // Gather meta
-    let meta = gather_enum_meta(data);
+fn gather_struct_meta(fields: &Fields, container: &ContainerMeta) -> Vec<Meta> {
+    let mut metas = Vec::new();
+    let mut unique_ids = HashSet::new();
 
-    // Write expressions
-    let write_expressions = meta.iter().map(|m| {
-        let variant_id = m.id;
-        let variant_name = &m.name;
+    let reserved = container.reserved(false);
+    let mut auto_ids = container.auto_ids(false);
 
-        if m.variant_fields.is_some() {
-            return quote_spanned! {m.span=>
-                Self::#variant_name(inner) => {
-                    lbs::write::write_field_id(w, #variant_id)?;
-                    inner.lbs_write(w)?;
-                },
-            };
+    for (index, field) in fields.iter().enumerate() {
+        let mut meta = Meta::from_struct_field(field, index, auto_ids.peek());
+
+        if meta.ctx && container.ctx.is_none() {
+            panic_ctx_without_container();
         }
 
-        quote_spanned! {m.span=>
-            Self::#variant_name => lbs::write::write_field_id(w, #variant_id)?,
+        // Skipped fields may have no id, then they don't take any
+        let Some(id) = meta.id else {
+            metas.push(meta);
+            continue;
+        };
+
+        check_reserved(id, reserved);
+        auto_ids.advance(id);
+
+        if !unique_ids.insert(id) {
+            panic_duplicated_id(id);
         }
-    });
 
-    // Complete body of lbs_write()
-    quote! {
-        match self {
-            #(#write_expressions)*
+        if container.pack_bools {
+            if id == PACKED_BOOLS_ID {
+                panic!("id {} is reserved for packed bools", PACKED_BOOLS_ID);
+            }
+
+            meta.packed = !meta.skip
+                && !meta.parallel
+                && !meta.ctx
+                && meta.since.is_none()
+                && meta.removed.is_none()
+                && meta.codec.is_none()
+                && field.ty.to_token_stream().to_string() == "bool";
         }
-        Ok(())
+
+        metas.push(meta);
     }
-}
 
-fn generate_read_body_for_struct(fields: &FieldsNamed) -> TokenStream {
-    // Gather meta.
-    let meta = gather_struct_meta(fields);
+    if container.deny_gaps {
+        check_gaps(&metas, reserved);
+    }
 
-    // Field initialization expressions.
-    let field_init_expressions = meta.iter().map(|f| {
-        let field_name = &f.name;
-        match f.default {
-            Some(ref default) => quote_spanned! {f.span=>
-                #field_name: #default,
-            },
-            None => quote_spanned! {f.span=>
-                #field_name: Default::default(),
-            },
-        }
-    });
+    // Values of unknown fields can be told apart only by index entries
+    match metas.iter().filter(|m| m.unknown_fields).count() {
+        0 => {}
+        1 if container.indexed => {}
+        1 => panic!(
+            "{} field requires #[{}({})] container",
+            ARGUMENT_UNKNOWN_FIELDS, ATTRIBUTE, ARGUMENT_INDEXED
+        ),
+        _ => panic!("struct can have only one {} field", ARGUMENT_UNKNOWN_FIELDS),
+    }
 
-    // Required fields stuff.
-    let required_count = meta.iter().filter(|f| f.required).count();
-    let mut required_index_read = 0usize;
-    let mut required_index_check = 0usize;
+    metas
+}
 
-    // Read expressions.
-    let read_expressions = meta.iter().filter(|f| !f.skip).map(|f| {
-        let field_id = f.id;
-        let field_name = &f.name;
+fn gather_enum_meta(data: &DataEnum, container: &ContainerMeta) -> Vec<Meta> {
+    let mut metas = Vec::new();
+    let mut unique_ids = HashSet::new();
 
-        let expr = if f.required {
-            quote_spanned! {f.span=>
-                #field_id => {
-                    _self.#field_name = lbs::read::read(r).map_err(|e| e.with_field(#field_id))?;
-                    required_present[#required_index_read] = true;
-                }
-            }
-        } else {
-            quote_spanned! {f.span=>
-                #field_id => _self.#field_name = lbs::read::read(r).map_err(|e| e.with_field(#field_id))?,
-            }
-        };
+    let reserved = container.reserved(true);
+    let mut auto_ids = container.auto_ids(true);
 
-        if f.required {
-            required_index_read += 1;
+    for variant in &data.variants {
+        let meta = Meta::from_enum_variant(variant, auto_ids.peek());
+
+        // Variant for unknown ids isn't listed, see other_variant()
+        if meta.other {
+            continue;
         }
 
-        expr
-    });
+        let id = meta.id.unwrap();
+        check_reserved(id, reserved);
+        auto_ids.advance(id);
 
-    // Required check expressions.
-    let required_check_expressions = meta.iter().filter(|f| f.required).map(|f| {
-        let field_id = f.id;
+        if meta.ctx && container.ctx.is_none() {
+            panic_ctx_without_container();
+        }
 
-        let expr = quote_spanned! {f.span=>
-            if !required_present[#required_index_check] {
-                return Err(lbs::error::LBSError::RequiredButMissing.with_field(#field_id));
+        // Fields of struct-like variants are checked like fields of a struct
+        if let Some(fields) = meta.struct_payload() {
+            if meta.ctx {
+                panic!(
+                    "{} variant must have a single unnamed field, mark its fields instead",
+                    ARGUMENT_CTX
+                );
             }
-        };
 
-        required_index_check += 1;
-        expr
-    });
-
-    // Complete body of lbs_read().
-    quote! {
-        let mut _self = Self {
-            #(#field_init_expressions)*
-        };
-
-        let mut required_present = [false; #required_count];
+            gather_struct_meta(fields, container);
+        }
 
-        for _ in 0..lbs::read::read_field_count(r)? {
-            match lbs::read::read_field_id(r)? {
-                #(#read_expressions)*
-                _ => {},
+        // Former ids can't be taken by other variants
+        for id in std::iter::once(id).chain(meta.read_as.iter().copied()) {
+            if !unique_ids.insert(id) {
+                panic_duplicated_id(id);
             }
         }
 
-        #(#required_check_expressions)*
+        metas.push(meta);
+    }
 
-        Ok(_self)
+    if container.deny_gaps {
+        check_gaps(&metas, reserved);
     }
+
+    metas
 }
 
-fn generate_read_body_for_enum(data: &DataEnum) -> TokenStream {
-    // Gather meta
-    let meta = gather_enum_meta(data);
+/// Variant marked with `#[lbs(other)]`, which keeps id and payload of unknown variants.
+fn other_variant(data: &DataEnum) -> Option<Meta> {
+    let mut others = data
+        .variants
+        .iter()
+        // Only the mark matters, so any id will do
+        .map(|v| Meta::from_enum_variant(v, Some(0)))
+        .filter(|m| m.other);
 
-    // Read expressions
-    let read_expressions = meta.iter().map(|m| {
-        let variant_id = m.id;
-        let variant_name = &m.name;
+    let other = others.next();
 
-        if m.variant_fields.is_some() {
-            return quote_spanned! {m.span=>
-                #variant_id => Ok(Self::#variant_name(lbs::read::read(r)?)),
-            };
+    if others.next().is_some() {
+        panic!("only one variant may be marked as {}", ARGUMENT_OTHER);
+    }
+
+    other
+}
+
+/// Checks, that at most one field of each oneof group is set. Fields are reached by `access`.
+fn generate_oneof_checks(meta: &[Meta], access: impl Fn(&Meta) -> TokenStream) -> TokenStream {
+    let mut groups: BTreeMap<String, Vec<&Meta>> = BTreeMap::new();
+
+    for m in meta {
+        if let Some(ref group) = m.oneof {
+            groups.entry(group.to_string()).or_default().push(m);
         }
+    }
 
-        quote_spanned! {m.span=>
-            #variant_id => Ok(Self::#variant_name),
+    let checks = groups.iter().map(|(group, fields)| {
+        let fields = fields.iter().map(|m| access(m));
+        quote! {
+            if [#(#fields.is_some()),*].iter().filter(|set| **set).count() > 1 {
+                return Err(lbs::error::LBSError::OneofConflict(#group));
+            }
         }
     });
 
-    // Complete body of lbs_read()
-    quote! {
-        match lbs::read::read_field_id(r)? {
-            #(#read_expressions)*
-            _ => Err(lbs::error::LBSError::UnexpectedVariant)
-        }
+    quote!(#(#checks)*)
+}
+
+/// Schema of a frozen container must match fingerprint of its version.
+fn check_frozen(name: &syn::Ident, data: &Data, container: &ContainerMeta) {
+    if container.frozen.is_empty() {
+        return;
+    }
+
+    let Some(version) = container.version else {
+        panic!(
+            "#[{}({}(..))] requires #[{}({}(<u16>))]",
+            ATTRIBUTE, ARGUMENT_FROZEN, ATTRIBUTE, ARGUMENT_VERSION
+        );
+    };
+
+    let fingerprint = schema_fingerprint(data, container);
+
+    match container.frozen.iter().find(|(v, _)| *v == version) {
+        Some((_, frozen)) if *frozen == fingerprint => {}
+        Some(_) => panic!(
+            "schema of {} differs from frozen version {}, bump #[{}({}(..))] and add `{} = {:#018x}` to #[{}({}(..))]",
+            name,
+            version,
+            ATTRIBUTE,
+            ARGUMENT_VERSION,
+            version.saturating_add(1),
+            fingerprint,
+            ATTRIBUTE,
+            ARGUMENT_FROZEN
+        ),
+        None => panic!(
+            "version {} of {} is not frozen, add `{} = {:#018x}` to #[{}({}(..))]",
+            version, name, version, fingerprint, ATTRIBUTE, ARGUMENT_FROZEN
+        ),
     }
 }
 
-fn gather_struct_meta(fields: &FieldsNamed) -> Vec<Meta> {
-    let mut metas = Vec::new();
-    let mut unique_ids = HashSet::new();
+/// FNV-1a hash of everything, which affects wire format: container flags and ids,
+/// requiredness, lifecycle and type spelling of fields or variants. Names don't matter.
+fn schema_fingerprint(data: &Data, container: &ContainerMeta) -> u64 {
+    let mut spelling = format!(
+        "compact={} pack_bools={} indexed={}",
+        container.compact, container.pack_bools, container.indexed
+    );
+
+    let entries: Vec<(u16, String)> = match data {
+        Data::Struct(DataStruct {
+            fields: ref fields @ (Fields::Named(_) | Fields::Unnamed(_)),
+            ..
+        }) => struct_fingerprint_entries(fields, container),
+        Data::Enum(ref data) => gather_enum_meta(data, container)
+            .iter()
+            .map(|m| {
+                let entry = match m.variant_fields {
+                    Some(ref fields) => format!("type={}", payload_fingerprint(fields, container)),
+                    None => "unit".to_string(),
+                };
+                (m.id.unwrap(), entry)
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
 
-    for field in &fields.named {
-        let meta = Meta::from_struct_field(field);
-        let id = meta.id.unwrap();
+    spell_fingerprint_entries(&mut spelling, entries);
+    fnv1a(&spelling)
+}
 
-        if !unique_ids.insert(id) {
-            panic_duplicated_id(id);
-        }
+fn struct_fingerprint_entries(fields: &Fields, container: &ContainerMeta) -> Vec<(u16, String)> {
+    gather_struct_meta(fields, container)
+        .iter()
+        .filter(|m| !m.skip)
+        .map(|m| {
+            let ty = m.ty.as_ref().unwrap();
+            let entry = format!(
+                "required={} since={:?} removed={:?} type={}",
+                m.required,
+                m.since,
+                m.removed,
+                type_fingerprint(ty, m.codec.as_ref())
+            );
+            (m.id.unwrap(), entry)
+        })
+        .collect()
+}
 
-        metas.push(meta);
+fn spell_fingerprint_entries(spelling: &mut String, mut entries: Vec<(u16, String)>) {
+    entries.sort();
+
+    for (id, entry) in entries {
+        spelling.push_str(&format!(" #{} {}", id, entry));
     }
+}
 
-    metas
+/// Type fingerprint of a single payload, or fingerprint of fields of a struct-like one.
+fn payload_fingerprint(fields: &Fields, container: &ContainerMeta) -> u64 {
+    if !is_struct_payload(fields) {
+        return type_fingerprint(&fields.iter().next().unwrap().ty, None);
+    }
+
+    let mut spelling = String::from("struct");
+    spell_fingerprint_entries(&mut spelling, struct_fingerprint_entries(fields, container));
+    fnv1a(&spelling)
 }
 
-fn gather_enum_meta(data: &DataEnum) -> Vec<Meta> {
-    let mut metas = Vec::new();
-    let mut unique_ids = HashSet::new();
+/// Ids must be dense and start from 0, skipped fields without id aside.
+fn check_reserved(id: u16, reserved: &[u16]) {
+    if reserved.contains(&id) {
+        panic!("id {} is reserved and can't be used", id);
+    }
+}
 
-    for variant in &data.variants {
-        if variant.fields.len() > 1 {
-            panic!("unsupported enum variant");
+/// Reserved ids of deleted fields and variants don't leave gaps.
+fn check_gaps(metas: &[Meta], reserved: &[u16]) {
+    let mut ids: Vec<u16> = metas.iter().filter_map(|m| m.id).collect();
+    ids.extend_from_slice(reserved);
+    ids.sort_unstable();
+
+    for (expected, id) in ids.into_iter().enumerate() {
+        if id as usize != expected {
+            panic!(
+                "id {} leaves a gap, next id must be {} due to #[{}({})]",
+                id, expected, ATTRIBUTE, ARGUMENT_DENY_GAPS
+            );
         }
+    }
+}
+
+fn add_write_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
 
-        match variant.fields {
-            Fields::Unit => {}
-            Fields::Unnamed(_) => {}
-            _ => panic!("unsupported enum variant"),
+            type_param.bounds.push(parse_quote!(lbs::LBSWrite));
         }
+    }
+    generics
+}
 
-        let meta = Meta::from_enum_variant(variant);
-        let id = meta.id.unwrap();
+fn add_read_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
 
-        if !unique_ids.insert(id) {
-            panic_duplicated_id(id);
+            type_param.bounds.push(parse_quote!(lbs::LBSRead));
         }
+    }
+    generics
+}
 
-        metas.push(meta);
+fn add_read_async_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
+
+            type_param
+                .bounds
+                .push(parse_quote!(lbs::tokio::LBSReadAsync));
+        }
     }
+    generics
+}
 
-    metas
+fn add_read_ref_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
+
+            type_param
+                .bounds
+                .push(parse_quote!(lbs::borrow::LBSReadRef<'lbs_de>));
+        }
+    }
+    generics
 }
 
-fn add_write_trait_bound(mut generics: Generics) -> Generics {
+fn add_diff_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(parse_quote!(lbs::LBSWrite));
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
+
+            type_param.bounds.push(parse_quote!(lbs::diff::LBSDiff));
         }
     }
     generics
 }
 
-fn add_read_trait_bound(mut generics: Generics) -> Generics {
+fn add_schema_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(parse_quote!(lbs::LBSRead));
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
+
+            type_param.bounds.push(parse_quote!(lbs::schema::LBSSchema));
+        }
+    }
+    generics
+}
+
+fn add_max_size_trait_bound(mut generics: Generics, used: &HashSet<syn::Ident>) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            if !used.contains(&type_param.ident) {
+                continue;
+            }
+
+            type_param.bounds.push(parse_quote!(lbs::size::LBSMaxSize));
         }
     }
     generics
 }
 
+/// Type parameters, which appear in serialized fields and so require bounds.
+/// Skipped fields and markers, like `PhantomData<T>`, don't count.
+fn serialized_type_params(data: &Data, generics: &Generics) -> HashSet<syn::Ident> {
+    let params: HashSet<_> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let mut used = HashSet::new();
+
+    let fields: Vec<&Field> = match data {
+        Data::Struct(data) => data
+            .fields
+            .iter()
+            .enumerate()
+            // Only skipping matters, so any id will do
+            .filter(|(index, f)| !Meta::from_struct_field(f, *index, Some(0)).skip)
+            .map(|(_, f)| f)
+            .collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    for field in fields {
+        collect_type_params(field.ty.to_token_stream(), &params, &mut used);
+    }
+
+    used
+}
+
+fn collect_type_params(
+    tokens: TokenStream,
+    params: &HashSet<syn::Ident>,
+    used: &mut HashSet<syn::Ident>,
+) {
+    let mut tokens = tokens.into_iter();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ref ident) if ident == "PhantomData" => {
+                // Skip generic arguments of the marker, balancing angle brackets, but not arrows
+                let mut depth = 0usize;
+                let mut arrow = false;
+                for token in tokens.by_ref() {
+                    match token {
+                        TokenTree::Punct(ref p) if p.as_char() == '<' => depth += 1,
+                        TokenTree::Punct(ref p) if p.as_char() == '>' && !arrow => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ if depth == 0 => break,
+                        _ => {}
+                    }
+                    arrow = matches!(token, TokenTree::Punct(ref p) if p.as_char() == '-');
+                }
+            }
+            TokenTree::Ident(ident) if params.contains(&ident) => {
+                used.insert(ident);
+            }
+            TokenTree::Group(group) => collect_type_params(group.stream(), params, used),
+            _ => {}
+        }
+    }
+}
+
+/// Payload of a variant with named fields or with several unnamed ones is written as a struct.
+fn is_struct_payload(fields: &Fields) -> bool {
+    matches!(fields, Fields::Named(_)) || fields.len() > 1
+}
+
+/// Name, which a variant pattern binds field at given position to.
+fn field_binding(index: usize) -> syn::Ident {
+    format_ident!("__lbs_field_{}", index)
+}
+
 fn panic_duplicated_id(id: u16) {
     panic!("duplicated id {}", id);
 }
 
+fn panic_indexed_enum() {
+    panic!("indexed container is only supported for structs")
+}
+
+fn panic_async_unsupported(what: &str) -> ! {
+    panic!("LBSReadAsync can't be derived for {}", what)
+}
+
+fn panic_ref_unsupported(what: &str) -> ! {
+    panic!("LBSReadRef can't be derived for {}", what)
+}
+
+fn panic_ctx_without_container() {
+    panic!(
+        "context fields require context type: #[{}({}(<type>))]",
+        ATTRIBUTE, ARGUMENT_CTX
+    )
+}
+
 fn panic_unknown_argument(name: &str) {
     panic!("unknown argument '{}'", name)
 }
@@ -0,0 +1,90 @@
+use actix_web::body::to_bytes;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use actix_web::FromRequest;
+use actix_web::Responder;
+use actix_web::ResponseError;
+use lbs::actix_web::LBSBlob;
+use lbs::actix_web::LBSConfig;
+use lbs::actix_web::LBSPayloadError;
+use lbs::actix_web::CONTENT_TYPE;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    items: Vec<String>,
+}
+
+#[tokio::test]
+async fn actix_web() {
+    let order = Order {
+        id: 7,
+        items: vec!["tea".to_string()],
+    };
+
+    // Responder
+    let req = TestRequest::default().to_http_request();
+    let resp = LBSBlob(order.clone()).respond_to(&req);
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("content-type").unwrap(), CONTENT_TYPE);
+    let body = to_bytes(resp.into_body()).await.unwrap();
+
+    // Extractor
+    let (req, mut payload) = TestRequest::post()
+        .insert_header(("content-type", CONTENT_TYPE))
+        .set_payload(body.clone())
+        .to_http_parts();
+
+    let extracted = LBSBlob::<Order>::from_request(&req, &mut payload).await;
+    assert_eq!(extracted.unwrap().into_inner(), order);
+
+    // Content type is checked
+    let (req, mut payload) = TestRequest::post()
+        .insert_header(("content-type", "application/json"))
+        .set_payload(body.clone())
+        .to_http_parts();
+
+    let e = LBSBlob::<Order>::from_request(&req, &mut payload)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, LBSPayloadError::ContentType));
+    assert_eq!(e.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let (req, mut payload) = TestRequest::post()
+        .app_data(LBSConfig::default().content_type_required(false))
+        .set_payload(body.clone())
+        .to_http_parts();
+
+    assert!(LBSBlob::<Order>::from_request(&req, &mut payload)
+        .await
+        .is_ok());
+
+    // Size is limited
+    let (req, mut payload) = TestRequest::post()
+        .app_data(LBSConfig::default().limit(4))
+        .insert_header(("content-type", CONTENT_TYPE))
+        .set_payload(body.clone())
+        .to_http_parts();
+
+    let e = LBSBlob::<Order>::from_request(&req, &mut payload)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, LBSPayloadError::Overflow(4)));
+    assert_eq!(e.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Malformed body
+    let (req, mut payload) = TestRequest::post()
+        .insert_header(("content-type", CONTENT_TYPE))
+        .set_payload(body.slice(..body.len() - 1))
+        .to_http_parts();
+
+    let e = LBSBlob::<Order>::from_request(&req, &mut payload)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, LBSPayloadError::Decode(_)));
+    assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+}
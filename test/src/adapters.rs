@@ -0,0 +1,64 @@
+use lbs::schema::LBSSchema;
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::collections::BinaryHeap;
+use std::num::Saturating;
+use std::num::Wrapping;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Default)]
+struct Scheduler {
+    #[lbs(id(0))]
+    deadlines: BinaryHeap<Reverse<u64>>,
+    #[lbs(id(1))]
+    latest_first: BTreeMap<Reverse<u32>, String>,
+    #[lbs(id(2))]
+    counter: Wrapping<u16>,
+    #[lbs(id(3))]
+    budget: Saturating<i32>,
+}
+
+#[test]
+fn adapters() {
+    let scheduler = Scheduler {
+        deadlines: [30, 10, 20].into_iter().map(Reverse).collect(),
+        latest_first: [(1, "a"), (3, "c"), (2, "b")]
+            .into_iter()
+            .map(|(k, v)| (Reverse(k), v.to_string()))
+            .collect(),
+        counter: Wrapping(u16::MAX),
+        budget: Saturating(-5),
+    };
+
+    let mut buf = Vec::new();
+    scheduler.lbs_write(&mut buf).unwrap();
+    let mut decoded = Scheduler::lbs_read(&mut buf.as_slice()).unwrap();
+
+    // Heap order is kept
+    assert_eq!(decoded.deadlines.pop(), Some(Reverse(10)));
+    assert_eq!(decoded.deadlines.pop(), Some(Reverse(20)));
+
+    let keys: Vec<u32> = decoded.latest_first.keys().map(|k| k.0).collect();
+    assert_eq!(keys, vec![3, 2, 1]);
+    assert_eq!(decoded.counter, scheduler.counter);
+    assert_eq!(decoded.budget, scheduler.budget);
+
+    // Adapters are written as their values
+    let mut wrapped = Vec::new();
+    Reverse(7u32).lbs_write(&mut wrapped).unwrap();
+    let mut plain = Vec::new();
+    7u32.lbs_write(&mut plain).unwrap();
+    assert_eq!(wrapped, plain);
+
+    assert_eq!(<Reverse<u64> as LBSMaxSize>::LBS_MAX_SIZE, 8);
+    assert_eq!(
+        <BinaryHeap<Reverse<u64>> as LBSSchema>::lbs_schema(),
+        <Vec<u64> as LBSSchema>::lbs_schema()
+    );
+    assert_eq!(
+        <Saturating<i32> as LBSSchema>::lbs_schema(),
+        <i32 as LBSSchema>::lbs_schema()
+    );
+}
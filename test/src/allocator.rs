@@ -0,0 +1,83 @@
+use allocator_api2::alloc::AllocError;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::alloc::Global;
+use allocator_api2::boxed::Box;
+use allocator_api2::vec::Vec;
+use lbs::allocator_api2::read_box_in;
+use lbs::allocator_api2::read_vec_in;
+use lbs::decoder::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// Counts allocations, delegating them to the global allocator.
+#[derive(Clone, Default)]
+struct Counting(Rc<Cell<usize>>);
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Batch {
+    #[lbs(id(0))]
+    ids: Vec<u64, Global>,
+    #[lbs(id(1))]
+    name: Box<String, Global>,
+}
+
+#[test]
+fn allocator() {
+    let mut ids = Vec::new_in(Global);
+    ids.extend([1u64, 2, 3]);
+
+    let batch = Batch {
+        ids,
+        name: Box::new_in("batch".to_string(), Global),
+    };
+
+    let mut buf = std::vec::Vec::new();
+    batch.lbs_write(&mut buf).unwrap();
+
+    // Same encoding as std collections
+    let mut std_buf = std::vec::Vec::new();
+    (vec![1u64, 2, 3], "batch".to_string())
+        .lbs_write(&mut std_buf)
+        .unwrap();
+
+    let mut alloc_buf = std::vec::Vec::new();
+    (&batch.ids, &batch.name).lbs_write(&mut alloc_buf).unwrap();
+    assert_eq!(alloc_buf, std_buf);
+
+    assert_eq!(Batch::lbs_read(&mut buf.as_slice()).unwrap(), batch);
+
+    // Decoding into given allocator
+    let counting = Counting::default();
+    let mut slice = std_buf.as_slice();
+    let mut d = Decoder::new();
+
+    let ids: Vec<u64, Counting> = read_vec_in(&mut slice, &mut d, counting.clone()).unwrap();
+    assert_eq!(ids.as_slice(), &[1, 2, 3]);
+    assert_eq!(counting.0.get(), 1);
+
+    let name: Box<String, Counting> = read_box_in(&mut slice, &mut d, counting.clone()).unwrap();
+    assert_eq!(name.as_str(), "batch");
+    assert_eq!(counting.0.get(), 2);
+
+    // Allocator as context
+    let mut ctx = counting.clone();
+    let ids: Vec<u64, Counting> =
+        lbs::ctx::read_with(&mut std_buf.as_slice(), &mut d, &mut ctx).unwrap();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(counting.0.get(), 3);
+}
@@ -0,0 +1,146 @@
+use lbs::decoder::Decoder;
+use lbs::encoder::Encoder;
+use lbs::error::LBSError;
+use lbs::tokio::LBSReadAsync;
+use lbs::tokio::LBSWriteAsync;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::BufReader;
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Debug, Clone, PartialEq)]
+struct Message {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    topic: Arc<str>,
+    #[lbs(id(2))]
+    tags: Vec<String>,
+    #[lbs(id(3), optional)]
+    sent: Option<SystemTime>,
+    #[lbs(id(4))]
+    headers: HashMap<String, String>,
+    #[lbs(id(5))]
+    body: Body,
+    #[lbs(id(6))]
+    tree: Node,
+    #[lbs(skip)]
+    cached: Option<u8>,
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Debug, Clone, PartialEq, Default)]
+#[lbs(compact)]
+enum Body {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Text(String),
+    #[lbs(id(2))]
+    Flags(Flags),
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Debug, Clone, PartialEq, Default)]
+#[lbs(pack_bools, compact)]
+struct Flags {
+    #[lbs(id(0))]
+    urgent: bool,
+    #[lbs(id(1))]
+    retained: bool,
+    #[lbs(id(2))]
+    priority: (u8, char),
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Debug, Clone, PartialEq, Default)]
+struct Node {
+    #[lbs(id(0))]
+    label: String,
+    #[lbs(id(1))]
+    children: Vec<Node>,
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Debug, PartialEq)]
+struct Envelope<T: Default> {
+    #[lbs(id(0))]
+    value: T,
+}
+
+fn message(id: u64) -> Message {
+    Message {
+        id,
+        topic: Arc::from("events"),
+        tags: vec!["events".to_string(), "a".to_string()],
+        sent: Some(SystemTime::UNIX_EPOCH),
+        headers: HashMap::from([("events".to_string(), "b".to_string())]),
+        body: Body::Flags(Flags {
+            urgent: true,
+            retained: false,
+            priority: (3, 'λ'),
+        }),
+        tree: Node {
+            label: "root".to_string(),
+            children: vec![Node {
+                label: "leaf".to_string(),
+                children: Vec::new(),
+            }],
+        },
+        cached: None,
+    }
+}
+
+#[tokio::test]
+async fn read_async_from_stream() {
+    let (mut tx, rx) = tokio::io::duplex(64);
+    let messages = [message(1), message(2)];
+
+    let writer = tokio::spawn({
+        let messages = messages.clone();
+        async move {
+            for m in &messages {
+                m.lbs_write_async(&mut tx).await.unwrap();
+            }
+        }
+    });
+
+    // Messages are decoded as their bytes arrive, without buffering whole messages first
+    let mut rx = BufReader::new(rx);
+    let mut d = Decoder::new();
+
+    for m in &messages {
+        let read: Message = d.decode_async(&mut rx).await.unwrap();
+        assert_eq!(&read, m);
+    }
+
+    writer.await.unwrap();
+
+    assert!(matches!(
+        Message::lbs_read_async(&mut rx, &mut d).await,
+        Err(LBSError::Io(_))
+    ));
+}
+
+#[tokio::test]
+async fn read_async_matches_read() {
+    let m = message(3);
+    let mut e = Encoder::new();
+    e.share_strings(true);
+    let mut buf = Vec::new();
+    m.lbs_write_async_with(&mut buf, &mut e).await.unwrap();
+
+    let mut d = Decoder::new();
+    d.share_strings(true);
+    let read: Message = d.decode_async(&mut buf.as_slice()).await.unwrap();
+    assert_eq!(read, m);
+    assert_eq!(d.decode::<Message, _>(&mut buf.as_slice()).unwrap(), m);
+
+    let envelope = Envelope {
+        value: Body::Text("text".to_string()),
+    };
+    let mut buf = Vec::new();
+    envelope.lbs_write(&mut buf).unwrap();
+    let read: Envelope<Body> = lbs::tokio::read::read(&mut buf.as_slice()).await.unwrap();
+    assert_eq!(read, envelope);
+}
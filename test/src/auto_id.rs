@@ -0,0 +1,163 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct ProfileV1 {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    email: Option<String>,
+    #[lbs(id(2))]
+    age: u8,
+    #[lbs(id(3))]
+    city: String,
+}
+
+// "email" was deleted, ids of the following fields are kept
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(auto_id, reserved(1))]
+struct ProfileV2 {
+    name: String,
+    age: u8,
+    city: String,
+    #[lbs(skip)]
+    cache: u64,
+    #[lbs(id(10))]
+    tags: Vec<String>,
+    country: Option<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(auto_id)]
+struct Pair(u32, #[lbs(id(5))] String, bool);
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(auto_id, reserved(0, 2), deny_gaps)]
+enum Shape {
+    #[default]
+    Point,
+    Circle(u32),
+    Rect {
+        w: u32,
+        h: u32,
+    },
+}
+
+// Explicit ids of the same layouts
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct ExplicitV2 {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(2))]
+    age: u8,
+    #[lbs(id(3))]
+    city: String,
+    #[lbs(id(10))]
+    tags: Vec<String>,
+    #[lbs(id(11))]
+    country: Option<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+enum ExplicitShape {
+    #[default]
+    #[lbs(id(1))]
+    Point,
+    #[lbs(id(3))]
+    Circle(#[lbs(id(0))] u32),
+    #[lbs(id(4))]
+    Rect {
+        #[lbs(id(0))]
+        w: u32,
+        #[lbs(id(1))]
+        h: u32,
+    },
+}
+
+#[test]
+fn auto_id_matches_explicit_ids() {
+    let v2 = ProfileV2 {
+        name: "bob".to_string(),
+        age: 30,
+        city: "Oslo".to_string(),
+        cache: 7,
+        tags: vec!["a".to_string()],
+        country: Some("NO".to_string()),
+    };
+    let explicit = ExplicitV2 {
+        name: "bob".to_string(),
+        age: 30,
+        city: "Oslo".to_string(),
+        tags: vec!["a".to_string()],
+        country: Some("NO".to_string()),
+    };
+
+    let bytes = lbs::to_vec(&v2).unwrap();
+    assert_eq!(bytes, lbs::to_vec(&explicit).unwrap());
+    assert_eq!(
+        lbs::from_slice::<ProfileV2>(&bytes).unwrap(),
+        ProfileV2 { cache: 0, ..v2 }
+    );
+
+    for (shape, explicit) in [
+        (Shape::Point, ExplicitShape::Point),
+        (Shape::Circle(3), ExplicitShape::Circle(3)),
+        (
+            Shape::Rect { w: 1, h: 2 },
+            ExplicitShape::Rect { w: 1, h: 2 },
+        ),
+    ] {
+        let bytes = lbs::to_vec(&shape).unwrap();
+        assert_eq!(bytes, lbs::to_vec(&explicit).unwrap());
+        assert_eq!(lbs::from_slice::<Shape>(&bytes).unwrap(), shape);
+    }
+}
+
+#[test]
+fn auto_id_read_by_older_version() {
+    let v2 = ProfileV2 {
+        name: "eve".to_string(),
+        age: 41,
+        city: "Rome".to_string(),
+        ..Default::default()
+    };
+
+    // Deleted field is missing, the rest keep their ids
+    let v1: ProfileV1 = lbs::from_slice(&lbs::to_vec(&v2).unwrap()).unwrap();
+    assert_eq!(
+        v1,
+        ProfileV1 {
+            name: "eve".to_string(),
+            email: None,
+            age: 41,
+            city: "Rome".to_string(),
+        }
+    );
+}
+
+#[test]
+fn auto_id_tuple_struct() {
+    // Fields without ids follow the previous one: 0, 5 and 6
+    #[derive(LBSWrite, Default)]
+    struct Explicit {
+        #[lbs(id(0))]
+        a: u32,
+        #[lbs(id(5))]
+        b: String,
+        #[lbs(id(6))]
+        c: bool,
+    }
+
+    let pair = Pair(1, "x".to_string(), true);
+    let bytes = lbs::to_vec(&pair).unwrap();
+    assert_eq!(
+        bytes,
+        lbs::to_vec(&Explicit {
+            a: 1,
+            b: "x".to_string(),
+            c: true
+        })
+        .unwrap()
+    );
+    assert_eq!(lbs::from_slice::<Pair>(&bytes).unwrap(), pair);
+}
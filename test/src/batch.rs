@@ -0,0 +1,94 @@
+use lbs::batch::Batch;
+use lbs::batch::BatchReader;
+use lbs::decoder::Decoder;
+use lbs::error::LBSError;
+use lbs::limits::Limits;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Row {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(1))]
+    name: String,
+}
+
+#[test]
+fn batch() {
+    let rows: Vec<Row> = (0..5)
+        .map(|id| Row {
+            id,
+            name: format!("row{id}"),
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    Batch(rows.clone()).lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        Batch::lbs_read(&mut buf.as_slice()).unwrap(),
+        Batch(rows.clone())
+    );
+
+    // Corrupt the name of record 1 and the payload of record 3
+    let header_len = 4 + 5 * 8;
+    let record_len = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as usize;
+    buf[header_len + record_len + record_len - 1] ^= 1;
+    buf[header_len + 3 * record_len + 2] ^= 1;
+
+    // Strict read fails
+    assert!(matches!(
+        Batch::<Row>::lbs_read(&mut buf.as_slice()),
+        Err(LBSError::ChecksumMismatch)
+    ));
+
+    // Reader skips bad records
+    let mut reader = BatchReader::<_, Row>::new(buf.as_slice()).unwrap();
+    assert_eq!(reader.len(), 5);
+    let mut valid = Vec::new();
+    while let Some(record) = reader.next_valid() {
+        valid.push(record.unwrap());
+    }
+    assert_eq!(
+        valid,
+        [
+            (0, rows[0].clone()),
+            (2, rows[2].clone()),
+            (4, rows[4].clone())
+        ]
+    );
+    assert_eq!(reader.failed(), [1, 3]);
+
+    // Truncated batch ends with I/O error
+    let mut reader = BatchReader::<_, Row>::new(&buf[..buf.len() - 1]).unwrap();
+    let results: Vec<_> = reader.by_ref().map(|(i, r)| (i, r.is_ok())).collect();
+    assert_eq!(results.last(), Some(&(4, false)));
+    assert_eq!(reader.failed(), [1, 3]);
+}
+
+#[test]
+fn batch_untrusted_lengths() {
+    // Record claims 4 GiB, but only a few bytes follow
+    let mut buf = Vec::new();
+    1u32.lbs_write(&mut buf).unwrap();
+    u32::MAX.lbs_write(&mut buf).unwrap();
+    0u32.lbs_write(&mut buf).unwrap();
+    buf.extend_from_slice(&[1, 2, 3]);
+
+    let mut reader = BatchReader::<_, Row>::new(buf.as_slice()).unwrap();
+    assert!(reader.next().unwrap().1.unwrap_err().is_eof());
+
+    let mut d = Decoder::new();
+    d.limits(Limits {
+        max_len: Some(1024),
+        ..Limits::new()
+    });
+    assert!(matches!(
+        BatchReader::<_, Row>::new_with(buf.as_slice(), &d),
+        Err(LBSError::LimitExceeded("length"))
+    ));
+    assert!(matches!(
+        Batch::<Row>::lbs_read_with(&mut buf.as_slice(), &mut d),
+        Err(LBSError::LimitExceeded("length"))
+    ));
+}
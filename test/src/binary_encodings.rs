@@ -0,0 +1,107 @@
+use fraction::Decimal;
+use fraction::Fraction;
+use ipnet::IpNet;
+use lbs::compat::V1;
+use lbs::error::LBSError;
+use lbs::schema::LBSSchema;
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::fmt::Debug;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn roundtrip<T: LBSWrite + LBSRead + PartialEq + Debug>(value: T) -> Vec<u8> {
+    let bytes = lbs::to_vec(&value).unwrap();
+    assert_eq!(bytes.len(), value.lbs_size_hint());
+    assert_eq!(lbs::from_slice::<T>(&bytes).unwrap(), value);
+    bytes
+}
+
+#[test]
+fn binary_uuid() {
+    let uuid = Uuid::from_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    assert_eq!(roundtrip(uuid), uuid.as_bytes());
+    assert_eq!(Uuid::LBS_MAX_SIZE, 16);
+    assert!(lbs::from_slice::<Uuid>(&[0; 15]).unwrap_err().is_eof());
+}
+
+#[test]
+fn binary_ipnet() {
+    let v4 = IpNet::from_str("10.1.0.0/16").unwrap();
+    let bytes = roundtrip(v4);
+    assert_eq!(bytes.len(), 1 + 4 + 1);
+    assert_eq!(*bytes.last().unwrap(), 16);
+    roundtrip(IpNet::from_str("fd00::/8").unwrap());
+
+    let mut invalid = bytes;
+    *invalid.last_mut().unwrap() = 33;
+    assert!(matches!(
+        lbs::from_slice::<IpNet>(&invalid),
+        Err(LBSError::Parsing(_))
+    ));
+    assert_eq!(
+        IpNet::lbs_schema(),
+        lbs::schema::Schema::Tuple(vec![lbs::schema::Schema::IpAddr, lbs::schema::Schema::U8])
+    );
+}
+
+#[test]
+fn binary_fraction() {
+    let bytes = roundtrip(Fraction::new_neg(3u64, 4u64));
+    assert_eq!(bytes[0], 1);
+    assert_eq!(&bytes[1..9], &3u64.to_le_bytes());
+    assert_eq!(&bytes[9..], &4u64.to_le_bytes());
+
+    roundtrip(Fraction::from(0));
+    roundtrip(Fraction::infinity());
+    roundtrip(Fraction::neg_infinity());
+    let nan = lbs::to_vec(&Fraction::nan()).unwrap();
+    assert!(lbs::from_slice::<Fraction>(&nan).unwrap().is_nan());
+
+    // Unreduced input is reduced
+    let mut unreduced = lbs::to_vec(&false).unwrap();
+    unreduced.extend_from_slice(&6u64.to_le_bytes());
+    unreduced.extend_from_slice(&8u64.to_le_bytes());
+    assert_eq!(
+        lbs::from_slice::<Fraction>(&unreduced).unwrap(),
+        Fraction::new(3u64, 4u64)
+    );
+}
+
+#[test]
+fn binary_decimal() {
+    let decimal = Decimal::from_str("-12.345").unwrap();
+    let bytes = roundtrip(decimal);
+    assert_eq!(*bytes.last().unwrap(), 3);
+    assert_eq!(
+        lbs::from_slice::<Decimal>(&bytes).unwrap().to_string(),
+        "-12.345"
+    );
+    assert_eq!(Decimal::LBS_MAX_SIZE, 18);
+}
+
+#[test]
+fn compat_v1_strings() {
+    let uuid = Uuid::from_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    let bytes = lbs::to_vec(&V1(uuid)).unwrap();
+    assert_eq!(
+        bytes,
+        lbs::to_vec("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap()
+    );
+    assert_eq!(lbs::from_slice::<V1<Uuid>>(&bytes).unwrap(), V1(uuid));
+
+    let net = lbs::to_vec("10.1.0.0/16").unwrap();
+    assert_eq!(
+        *lbs::from_slice::<V1<IpNet>>(&net).unwrap(),
+        IpNet::from_str("10.1.0.0/16").unwrap()
+    );
+    let decimal = lbs::to_vec(&V1(Decimal::from_str("-12.345").unwrap())).unwrap();
+    assert_eq!(decimal, lbs::to_vec("-12.345").unwrap());
+
+    assert!(matches!(
+        lbs::from_slice::<V1<Fraction>>(&lbs::to_vec("3/x").unwrap()),
+        Err(LBSError::Parsing(_))
+    ));
+    assert_eq!(V1::<Uuid>::lbs_schema(), lbs::schema::Schema::String);
+}
@@ -0,0 +1,175 @@
+use lbs::borrow::LBSReadRef;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::LBSRead;
+use lbs::LBSReadRef;
+use lbs::LBSWrite;
+use lbs::Options;
+use std::borrow::Cow;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Clone, Default, PartialEq)]
+struct Item {
+    #[lbs(id(0))]
+    sku: String,
+    #[lbs(id(1))]
+    quantity: u32,
+}
+
+/// Response, which borrows items instead of cloning them.
+#[derive(LBSWrite, lbs::LBSSchema)]
+struct ResponseRef<'a> {
+    #[lbs(id(0))]
+    title: &'a str,
+    #[lbs(id(1))]
+    items: &'a [Item],
+    #[lbs(id(2))]
+    featured: Option<&'a Item>,
+}
+
+/// Owned counterpart of `ResponseRef`, as seen by readers.
+#[derive(LBSRead, lbs::LBSSchema, Debug, Default, PartialEq)]
+struct Response {
+    #[lbs(id(0))]
+    title: String,
+    #[lbs(id(1))]
+    items: Vec<Item>,
+    #[lbs(id(2))]
+    featured: Option<Item>,
+}
+
+#[test]
+fn borrowed() {
+    let items = vec![
+        Item {
+            sku: "a-1".to_string(),
+            quantity: 2,
+        },
+        Item {
+            sku: "b-2".to_string(),
+            quantity: 5,
+        },
+    ];
+
+    let response = ResponseRef {
+        title: "cart",
+        items: &items,
+        featured: items.last(),
+    };
+
+    let mut buf = Vec::new();
+    response.lbs_write(&mut buf).unwrap();
+
+    assert_eq!(
+        Response::lbs_read(&mut buf.as_slice()).unwrap(),
+        Response {
+            title: "cart".to_string(),
+            items: items.clone(),
+            featured: Some(items[1].clone()),
+        }
+    );
+}
+
+#[test]
+fn borrowed_schema() {
+    let (Schema::Struct(borrowed), Schema::Struct(owned)) =
+        (ResponseRef::lbs_schema(), Response::lbs_schema())
+    else {
+        unreachable!()
+    };
+
+    assert_eq!(borrowed.fields, owned.fields);
+}
+
+/// View of a log record, which borrows its strings from the input buffer.
+#[derive(LBSWrite, LBSReadRef, Debug, Default, PartialEq)]
+#[lbs(compact, pack_bools)]
+struct RecordView<'a> {
+    #[lbs(id(0))]
+    message: &'a str,
+    #[lbs(id(1))]
+    source: Cow<'a, str>,
+    #[lbs(id(2))]
+    payload: Cow<'a, [u8]>,
+    #[lbs(id(3))]
+    tags: Vec<&'a str>,
+    #[lbs(id(4), varint)]
+    level: u32,
+    #[lbs(id(5))]
+    alert: bool,
+    #[lbs(id(6))]
+    origin: Option<Origin<'a>>,
+}
+
+#[derive(LBSWrite, LBSReadRef, Debug, PartialEq)]
+enum Origin<'a> {
+    #[lbs(id(0))]
+    Host(&'a str),
+    #[lbs(id(1))]
+    Pid(u32),
+}
+
+fn within(bytes: &[u8], s: &str) -> bool {
+    bytes.as_ptr_range().contains(&s.as_ptr())
+}
+
+#[test]
+fn borrowed_read() {
+    let record = RecordView {
+        message: "disk is full",
+        source: Cow::Borrowed("storage"),
+        payload: Cow::Borrowed(&[1, 2, 3]),
+        tags: vec!["disk", "alert"],
+        level: 3,
+        alert: true,
+        origin: Some(Origin::Host("db-1")),
+    };
+
+    let mut buf = Vec::new();
+    record.lbs_write(&mut buf).unwrap();
+    let decoded: RecordView = lbs::borrow::from_slice(&buf).unwrap();
+    assert_eq!(decoded, record);
+
+    // Strings and bytes point into the input
+    assert!(within(&buf, decoded.message));
+    assert!(matches!(decoded.source, Cow::Borrowed(s) if within(&buf, s)));
+    assert!(matches!(decoded.payload, Cow::Borrowed(_)));
+    assert!(decoded.tags.iter().all(|tag| within(&buf, tag)));
+    assert!(matches!(decoded.origin, Some(Origin::Host(host)) if within(&buf, host)));
+
+    buf.push(0);
+    assert!(lbs::borrow::from_slice::<RecordView>(&buf).is_err());
+
+    let truncated = &buf[..buf.len() - 8];
+    assert!(lbs::borrow::from_slice::<RecordView>(truncated).is_err());
+}
+
+#[test]
+fn borrowed_read_shared_strings() {
+    let options = Options::new().share_strings(true).clone();
+
+    #[derive(LBSWrite, LBSReadRef, Debug, Default, PartialEq)]
+    struct Pair<'a> {
+        #[lbs(id(0))]
+        first: Cow<'a, str>,
+        #[lbs(id(1))]
+        second: Cow<'a, str>,
+    }
+
+    let pair = Pair {
+        first: Cow::Borrowed("repeated"),
+        second: Cow::Borrowed("repeated"),
+    };
+
+    let buf = lbs::to_vec_with(&pair, &options).unwrap();
+    let decoded: Pair = lbs::borrow::from_slice_with(&buf, &mut options.decoder()).unwrap();
+    assert_eq!(decoded, pair);
+
+    // Only the first occurrence is in the input
+    assert!(matches!(decoded.first, Cow::Borrowed(_)));
+    assert!(matches!(decoded.second, Cow::Owned(_)));
+
+    // References to shared strings can't be borrowed
+    let tags = vec!["same", "same"];
+    let buf = lbs::to_vec_with(&tags, &options).unwrap();
+    assert!(lbs::borrow::from_slice_with::<Vec<&str>>(&buf, &mut options.decoder()).is_err());
+}
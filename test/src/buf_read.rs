@@ -0,0 +1,48 @@
+use lbs::read::read_buf;
+use lbs::read::BufReadAdapter;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::io::Read;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Row {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    scores: HashMap<String, f32>,
+}
+
+#[test]
+fn buf_read() {
+    let rows: Vec<Row> = (0..100)
+        .map(|id| Row {
+            id,
+            name: format!("row {}", id),
+            scores: HashMap::from([("a".to_string(), id as f32)]),
+        })
+        .collect();
+
+    let mut bytes = Vec::new();
+    for row in &rows {
+        row.lbs_write(&mut bytes).unwrap();
+    }
+
+    // Tiny buffer makes values cross its end
+    let mut r = BufReader::with_capacity(5, bytes.as_slice());
+
+    for row in &rows {
+        assert_eq!(&read_buf::<Row, _>(&mut r).unwrap(), row);
+    }
+
+    assert!(read_buf::<Row, _>(&mut r).unwrap_err().is_eof());
+
+    // Plain reads are passed through
+    let mut r = BufReadAdapter::new(BufReader::new(&bytes[..3]));
+    let mut buf = [0; 8];
+    assert_eq!(r.read(&mut buf).unwrap(), 3);
+    assert_eq!(r.read(&mut buf).unwrap(), 0);
+}
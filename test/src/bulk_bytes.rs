@@ -0,0 +1,141 @@
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::io::Write;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct Blob {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    data: Vec<u8>,
+    #[lbs(id(2))]
+    digest: [u8; 32],
+    #[lbs(id(3))]
+    buffer: BytesMut,
+}
+
+fn blob() -> Blob {
+    Blob {
+        name: "dump".to_string(),
+        data: (0..1_000_000).map(|i| i as u8).collect(),
+        digest: [9; 32],
+        buffer: BytesMut::from(&b"tail"[..]),
+    }
+}
+
+/// Writer, which counts calls.
+#[derive(Default)]
+struct Calls<T> {
+    inner: T,
+    calls: usize,
+}
+
+impl Write for Calls<Vec<u8>> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for Calls<&[u8]> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        self.inner.read(buf)
+    }
+}
+
+#[test]
+fn bulk_bytes_calls() {
+    // Byte sequences are written at once, after their length
+    let mut w = Calls::<Vec<u8>>::default();
+    vec![1u8; 100_000].lbs_write(&mut w).unwrap();
+    assert_eq!(w.calls, 2);
+
+    let mut w = Calls::<Vec<u8>>::default();
+    [1u8; 64].lbs_write(&mut w).unwrap();
+    assert_eq!(w.calls, 2);
+
+    let mut deque = VecDeque::from(vec![1u8; 10]);
+    deque.push_front(0);
+    let mut w = Calls::<Vec<u8>>::default();
+    deque.lbs_write(&mut w).unwrap();
+    assert!(w.calls <= 3);
+    assert_eq!(w.inner, lbs::to_vec(&Vec::from(deque.clone())).unwrap());
+
+    // And read at once, growing past preallocation in chunks
+    let bytes = lbs::to_vec(&blob()).unwrap();
+    let mut r = Calls {
+        inner: bytes.as_slice(),
+        calls: 0,
+    };
+    assert_eq!(Blob::lbs_read(&mut r).unwrap(), blob());
+    assert!(r.calls < 100, "{} reads", r.calls);
+
+    let mut r = Calls {
+        inner: bytes.as_slice(),
+        calls: 0,
+    };
+    Blob::lbs_skip(&mut r, &mut Decoder::new()).unwrap();
+    assert!(r.inner.is_empty());
+    assert!(r.calls < 1000, "{} reads", r.calls);
+}
+
+#[test]
+fn bulk_bytes_in_place() {
+    let bytes = lbs::to_vec(&blob()).unwrap();
+
+    let mut value = Blob::default();
+    value.lbs_read_into(&mut bytes.as_slice()).unwrap();
+    assert_eq!(value, blob());
+
+    // Buffers keep their capacity
+    let mut data = Vec::<u8>::with_capacity(2_000_000);
+    data.lbs_read_into(&mut lbs::to_vec(&vec![5u8; 10]).unwrap().as_slice())
+        .unwrap();
+    assert_eq!(data, vec![5; 10]);
+    assert!(data.capacity() >= 2_000_000);
+
+    let mut buffer = BytesMut::with_capacity(1000);
+    buffer
+        .lbs_read_into(&mut lbs::to_vec(&vec![6u8; 10]).unwrap().as_slice())
+        .unwrap();
+    assert_eq!(&buffer[..], &[6; 10]);
+    assert!(buffer.capacity() >= 1000);
+
+    // Truncated input
+    let bytes = lbs::to_vec(&BytesMut::from(&b"abc"[..])).unwrap();
+    assert!(lbs::from_slice::<BytesMut>(&bytes[..bytes.len() - 1])
+        .unwrap_err()
+        .is_eof());
+    assert!(
+        lbs::from_slice::<Vec<u8>>(&lbs::to_vec(&vec![1u8; 10]).unwrap()[..8])
+            .unwrap_err()
+            .is_eof()
+    );
+}
+
+#[test]
+fn bulk_bytes_buf() {
+    let mut buf = BytesMut::new();
+    lbs::write_buf(&mut buf, &blob()).unwrap();
+    lbs::write_buf(&mut buf, &7u64).unwrap();
+    assert_eq!(buf.len(), lbs::to_vec(&blob()).unwrap().len() + 8);
+
+    // Values are read from chained chunks and the buffer is advanced
+    let bytes = buf.freeze();
+    let (head, tail) = bytes.split_at(1000);
+    let mut chain = Bytes::copy_from_slice(head).chain(Bytes::copy_from_slice(tail));
+    assert_eq!(lbs::read_buf::<Blob, _>(&mut chain).unwrap(), blob());
+    assert_eq!(lbs::read_buf::<u64, _>(&mut chain).unwrap(), 7);
+    assert!(!chain.has_remaining());
+}
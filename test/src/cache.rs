@@ -0,0 +1,78 @@
+use lbs::cache::read_fresh;
+use lbs::cache::Cached;
+use lbs::error::LBSError;
+use lbs::schema::LBSSchema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Clone, PartialEq, Debug)]
+struct Profile {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    karma: i64,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "alice".to_string(),
+        karma: 42,
+    }
+}
+
+#[test]
+fn cache_envelope() {
+    let entry = Cached::for_schema(profile(), Some(Duration::from_secs(60))).with_flags(0b101);
+    assert_eq!(entry.fingerprint, Profile::lbs_schema().fingerprint());
+    assert!(entry.is_fresh());
+
+    let bytes = lbs::to_vec(&entry).unwrap();
+    assert_eq!(bytes.len(), entry.lbs_size_hint());
+
+    let read: Cached<Profile> = lbs::from_slice(&bytes).unwrap();
+    assert_eq!(read, entry);
+    assert_eq!(read.flags, 0b101);
+    assert_eq!(
+        read.expires_at(),
+        Some(entry.created_at + Duration::from_secs(60))
+    );
+    assert_eq!(
+        read.remaining_at(entry.created_at + Duration::from_secs(100)),
+        Some(Duration::ZERO)
+    );
+}
+
+#[test]
+fn cache_read_fresh() {
+    let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let entry = Cached {
+        created_at,
+        ttl: Some(Duration::from_secs(10)),
+        fingerprint: 7,
+        flags: 0,
+        value: profile(),
+    };
+    let bytes = lbs::to_vec(&entry).unwrap();
+
+    let fresh = read_fresh::<Profile, _>(&mut &bytes[..], 7, created_at + Duration::from_secs(9));
+    assert_eq!(fresh.unwrap(), Some(entry.clone()));
+
+    // Clock of the writer may be ahead
+    let early = read_fresh::<Profile, _>(&mut &bytes[..], 7, created_at - Duration::from_secs(1));
+    assert!(early.unwrap().is_some());
+
+    let stale = read_fresh::<Profile, _>(&mut &bytes[..], 7, created_at + Duration::from_secs(10));
+    assert_eq!(stale.unwrap(), None);
+
+    let err = read_fresh::<Profile, _>(&mut &bytes[..], 8, created_at + Duration::from_secs(1))
+        .unwrap_err();
+    assert!(matches!(err, LBSError::SchemaMismatch));
+
+    // Entries without TTL never expire
+    let forever = Cached { ttl: None, ..entry };
+    assert_eq!(forever.expires_at(), None);
+    assert_eq!(forever.remaining_at(SystemTime::now()), None);
+    assert!(forever.is_fresh_at(created_at + Duration::from_secs(u32::MAX as u64)));
+}
@@ -0,0 +1,117 @@
+use lbs::calendar::OrdinalDate;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Report {
+    #[lbs(id(0))]
+    day: chrono::NaiveDate,
+    #[lbs(id(1))]
+    week: Option<chrono::IsoWeek>,
+    #[lbs(id(2))]
+    weekday: Option<chrono::Weekday>,
+    #[lbs(id(3))]
+    month: Option<chrono::Month>,
+    #[lbs(id(4))]
+    period: Option<OrdinalDate>,
+}
+
+fn encode<T: LBSWrite>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn calendar_chrono() {
+    let day = chrono::NaiveDate::from_ymd_opt(2024, 12, 30).unwrap();
+    let report = Report {
+        day,
+        week: Some(chrono::Datelike::iso_week(&day)),
+        weekday: Some(chrono::Weekday::Mon),
+        month: Some(chrono::Month::December),
+        period: OrdinalDate::new(2024, 366),
+    };
+
+    let buf = encode(&report);
+    assert_eq!(Report::lbs_read(&mut buf.as_slice()).unwrap(), report);
+
+    // ISO week of the last days of 2024 belongs to 2025
+    assert_eq!(encode(&report.week.unwrap()), [233, 7, 0, 0, 1]);
+    assert_eq!(encode(&report.day), [232, 7, 0, 0, 109, 1]);
+    assert_eq!(encode(&report.weekday.unwrap()), [0]);
+    assert_eq!(encode(&report.month.unwrap()), [12]);
+}
+
+#[test]
+fn calendar_time() {
+    let date = time::Date::from_calendar_date(2023, time::Month::March, 1).unwrap();
+    let buf = encode(&date);
+
+    // Same wire format as chrono and OrdinalDate
+    assert_eq!(
+        buf,
+        encode(&chrono::NaiveDate::from_ymd_opt(2023, 3, 1).unwrap())
+    );
+    assert_eq!(time::Date::lbs_read(&mut buf.as_slice()).unwrap(), date);
+    assert_eq!(
+        OrdinalDate::lbs_read(&mut buf.as_slice()).unwrap(),
+        OrdinalDate::new(2023, 60).unwrap()
+    );
+
+    for month in [time::Month::January, time::Month::December] {
+        let buf = encode(&month);
+        assert_eq!(time::Month::lbs_read(&mut buf.as_slice()).unwrap(), month);
+    }
+
+    for weekday in [time::Weekday::Monday, time::Weekday::Sunday] {
+        let buf = encode(&weekday);
+        assert_eq!(buf, encode(&chrono::Weekday::try_from(buf[0]).unwrap()));
+        assert_eq!(
+            time::Weekday::lbs_read(&mut buf.as_slice()).unwrap(),
+            weekday
+        );
+    }
+}
+
+#[test]
+fn calendar_out_of_range() {
+    assert!(OrdinalDate::new(2023, 366).is_none());
+    assert!(OrdinalDate::new(2000, 366).is_some());
+    assert!(OrdinalDate::new(1900, 366).is_none());
+    assert!(OrdinalDate::new(2024, 0).is_none());
+
+    let non_leap = encode(&(2023i32, 366u16));
+    assert!(matches!(
+        chrono::NaiveDate::lbs_read(&mut non_leap.as_slice()),
+        Err(LBSError::OutOfRange("OrdinalDate"))
+    ));
+    assert!(matches!(
+        time::Date::lbs_read(&mut non_leap.as_slice()),
+        Err(LBSError::OutOfRange("OrdinalDate"))
+    ));
+
+    let week = encode(&(2023i32, 53u8));
+    assert!(matches!(
+        chrono::IsoWeek::lbs_read(&mut week.as_slice()),
+        Err(LBSError::OutOfRange("IsoWeek"))
+    ));
+
+    assert!(matches!(
+        chrono::Weekday::lbs_read(&mut [7u8].as_slice()),
+        Err(LBSError::OutOfRange("Weekday"))
+    ));
+    assert!(matches!(
+        time::Weekday::lbs_read(&mut [7u8].as_slice()),
+        Err(LBSError::OutOfRange("Weekday"))
+    ));
+    assert!(matches!(
+        chrono::Month::lbs_read(&mut [0u8].as_slice()),
+        Err(LBSError::OutOfRange("Month"))
+    ));
+    assert!(matches!(
+        time::Month::lbs_read(&mut [13u8].as_slice()),
+        Err(LBSError::OutOfRange("Month"))
+    ));
+}
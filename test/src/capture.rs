@@ -0,0 +1,64 @@
+use lbs::capture::read_captured;
+use lbs::capture::CaptureReader;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+struct Record {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(17))]
+    name: String,
+}
+
+fn encoded() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Record {
+        id: 1,
+        name: "name".to_string(),
+    }
+    .lbs_write(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn capture_attached_to_error() {
+    let mut bytes = encoded();
+    let last = bytes.len() - 1;
+    bytes[last] = 0xff;
+
+    let err = read_captured::<Record, _>(&mut bytes.as_slice()).unwrap_err();
+    let capture = err.capture().unwrap();
+
+    assert_eq!(capture.bytes, bytes);
+    assert!(capture.is_complete());
+    assert_eq!(err.field_path(), vec![17]);
+    assert!(matches!(err.root_cause(), LBSError::Parsing(_)));
+    assert!(err.to_string().contains("at byte 18"));
+
+    // Captured input reproduces the error
+    let path = std::env::temp_dir().join(format!("lbs-capture-{}", std::process::id()));
+    capture.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let reproduced = Record::lbs_read(&mut saved.as_slice()).unwrap_err();
+    assert_eq!(reproduced.field_path(), vec![17]);
+}
+
+#[test]
+fn capture_limit_and_stream() {
+    let bytes = [encoded(), encoded()].concat();
+    let mut r = CaptureReader::with_limit(bytes.as_slice(), 4);
+
+    assert!(r.decode::<Record>().is_ok());
+    assert!(r.decode::<Record>().is_ok());
+    assert_eq!(r.offset(), encoded().len() as u64);
+    assert_eq!(r.captured(), &encoded()[..4]);
+
+    let err = r.decode::<Record>().unwrap_err();
+    assert!(err.is_eof());
+    assert_eq!(err.capture().unwrap().offset, 0);
+}
@@ -0,0 +1,111 @@
+use lbs::cas::hex;
+use lbs::cas::Backend;
+use lbs::cas::FsBackend;
+use lbs::cas::MemoryBackend;
+use lbs::cas::Store;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Asset {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    scale: f32,
+    #[lbs(id(2))]
+    tags: BTreeMap<String, String>,
+}
+
+fn asset(name: &str, scale: f32) -> Asset {
+    Asset {
+        name: name.to_string(),
+        scale,
+        tags: BTreeMap::from([("kind".to_string(), "texture".to_string())]),
+    }
+}
+
+#[test]
+fn cas_memory() {
+    let mut store = Store::<_, Sha256>::new(MemoryBackend::new());
+
+    let a = store.put(&asset("grass", 1.0)).unwrap();
+    let b = store.put(&asset("grass", 1.0)).unwrap();
+    let c = store.put(&asset("stone", 1.0)).unwrap();
+
+    // Equal values are stored once, negative zero is canonicalized
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(
+        store.put(&asset("zero", 0.0)).unwrap(),
+        store.put(&asset("zero", -0.0)).unwrap()
+    );
+    assert_eq!(store.backend().len(), 3);
+
+    assert_eq!(store.get::<Asset>(&a).unwrap(), Some(asset("grass", 1.0)));
+    assert!(store.contains(&c).unwrap());
+    assert!(store.remove(&c).unwrap());
+    assert!(!store.remove(&c).unwrap());
+    assert_eq!(store.get::<Asset>(&c).unwrap(), None);
+}
+
+#[test]
+fn cas_fs() {
+    let dir = std::env::temp_dir().join(format!("lbs-cas-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut store = Store::<_, Sha256>::new(FsBackend::open(&dir).unwrap());
+    let hash = store.put(&asset("grass", 2.0)).unwrap();
+
+    let hex = hex(&hash);
+    let path = dir.join(&hex[..2]).join(&hex[2..]);
+    assert!(path.exists());
+    assert_eq!(
+        store.get::<Asset>(&hash).unwrap(),
+        Some(asset("grass", 2.0))
+    );
+
+    // Damaged value is detected
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[0] ^= 0xff;
+    std::fs::write(&path, bytes).unwrap();
+
+    let err = store.get::<Asset>(&hash).unwrap_err();
+    assert!(matches!(err, LBSError::ChecksumMismatch));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cas_fs_concurrent() {
+    let dir = std::env::temp_dir().join(format!("lbs-cas-concurrent-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let backend = FsBackend::open(&dir).unwrap();
+
+    // Clones writing the same value don't share temporary files
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let mut backend = backend.clone();
+            std::thread::spawn(move || backend.put(&[1, 2, 3], b"value").unwrap())
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(backend.get(&[1, 2, 3]).unwrap().unwrap(), b"value");
+    assert_eq!(std::fs::read_dir(dir.join("01")).unwrap().count(), 1);
+
+    // Hash must name both subdirectory and file
+    let mut backend = backend;
+    assert!(matches!(
+        backend.put(&[1], b"value"),
+        Err(LBSError::Parsing(_))
+    ));
+    assert!(matches!(backend.get(&[]), Err(LBSError::Parsing(_))));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
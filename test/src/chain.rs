@@ -0,0 +1,159 @@
+use lbs::chain::Chain;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSDiff, Default, Clone, PartialEq, Debug)]
+struct Ledger {
+    #[lbs(id(0))]
+    balances: BTreeMap<String, i64>,
+    #[lbs(id(1))]
+    version: u64,
+}
+
+const FINGERPRINT: u64 = 0x1ed9;
+
+fn chain_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lbs-chain-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn files(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn chain() {
+    let dir = chain_dir("reload");
+    let mut chain = Chain::<Ledger>::open(&dir, FINGERPRINT)
+        .unwrap()
+        .with_full_every(4);
+    assert_eq!(chain.state(), &Ledger::default());
+
+    let mut ledger = Ledger::default();
+
+    for i in 0..10 {
+        ledger.balances.insert(format!("account{}", i % 3), i);
+        ledger.version += 1;
+        assert!(chain.record(&ledger).unwrap());
+    }
+
+    assert!(!chain.record(&ledger).unwrap());
+    assert_eq!(chain.state(), &ledger);
+    assert_eq!(chain.generation(), 2);
+    assert_eq!(chain.delta_count(), 2);
+    chain.sync().unwrap();
+    drop(chain);
+
+    // Only the latest generation is kept
+    assert_eq!(
+        files(&dir),
+        vec!["0000000000000002.delta", "0000000000000002.full"]
+    );
+
+    let chain = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap();
+    assert_eq!(chain.generation(), 2);
+    assert_eq!(chain.delta_count(), 2);
+    assert_eq!(chain.into_state(), ledger);
+
+    let err = Chain::<Ledger>::open(&dir, 0).unwrap_err();
+    assert!(matches!(err, LBSError::SchemaMismatch));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn chain_torn_and_corrupted() {
+    let dir = chain_dir("damage");
+    let mut chain = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap();
+    let mut ledger = Ledger::default();
+
+    for i in 0..3 {
+        ledger.version = i + 1;
+        chain.record(&ledger).unwrap();
+    }
+
+    chain.sync().unwrap();
+    drop(chain);
+
+    // Torn final patch is discarded
+    let delta = dir.join("0000000000000000.delta");
+    let len = std::fs::metadata(&delta).unwrap().len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&delta)
+        .unwrap()
+        .set_len(len - 1)
+        .unwrap();
+
+    let mut chain = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap();
+    assert_eq!(chain.state().version, 2);
+
+    // Checkpoint survives reopening and corruption of the full snapshot is detected
+    chain.checkpoint().unwrap();
+    drop(chain);
+
+    let full = dir.join("0000000000000001.full");
+    let mut bytes = std::fs::read(&full).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&full, bytes).unwrap();
+
+    let err = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap_err();
+    assert!(matches!(err, LBSError::ChecksumMismatch));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn chain_deltas_of_other_snapshot() {
+    let dir = chain_dir("foreign");
+    let mut chain = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap();
+    chain
+        .record(&Ledger {
+            version: 1,
+            ..Default::default()
+        })
+        .unwrap();
+    chain.checkpoint().unwrap();
+    chain
+        .record(&Ledger {
+            version: 2,
+            ..Default::default()
+        })
+        .unwrap();
+    chain.sync().unwrap();
+    drop(chain);
+
+    // Replace snapshot with a different one of the same generation
+    let other_dir = chain_dir("foreign-other");
+    let mut other = Chain::<Ledger>::open(&other_dir, FINGERPRINT).unwrap();
+    other
+        .record(&Ledger {
+            version: 7,
+            ..Default::default()
+        })
+        .unwrap();
+    other.checkpoint().unwrap();
+    drop(other);
+
+    std::fs::copy(
+        other_dir.join("0000000000000001.full"),
+        dir.join("0000000000000001.full"),
+    )
+    .unwrap();
+
+    let err = Chain::<Ledger>::open(&dir, FINGERPRINT).unwrap_err();
+    assert!(matches!(err, LBSError::Parsing(_)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_dir_all(&other_dir).unwrap();
+}
@@ -0,0 +1,119 @@
+use lbs::chunk::Chunker;
+use lbs::chunk::Reassembler;
+use lbs::chunk::FRAGMENT_HEADER_LEN;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Telemetry {
+    #[lbs(id(0))]
+    host: String,
+    #[lbs(id(1))]
+    samples: Vec<f64>,
+}
+
+#[test]
+fn chunk() {
+    let telemetry = Telemetry {
+        host: "node-1".to_string(),
+        samples: (0..100).map(|i| i as f64).collect(),
+    };
+
+    let mut chunker = Chunker::new(128);
+    let fragments = chunker.chunk(&telemetry).unwrap();
+    assert_eq!(fragments.len(), 7);
+    assert!(fragments.iter().all(|f| f.len() <= 128));
+
+    // Fragments arrive in reverse order, one of them twice
+    let mut reassembler = Reassembler::<Telemetry>::new();
+
+    for f in fragments[1..].iter().rev() {
+        assert!(reassembler.push(f).unwrap().is_none());
+    }
+
+    assert!(reassembler.push(&fragments[1]).unwrap().is_none());
+    assert_eq!(reassembler.pending(), 1);
+    assert_eq!(
+        reassembler.push(&fragments[0]).unwrap(),
+        Some(telemetry.clone())
+    );
+    assert_eq!(reassembler.pending(), 0);
+
+    // Small message takes a single fragment
+    let small = Telemetry {
+        host: "node-2".to_string(),
+        samples: vec![],
+    };
+    let fragments = chunker.chunk(&small).unwrap();
+    assert_eq!(fragments.len(), 1);
+    assert_eq!(reassembler.push(&fragments[0]).unwrap(), Some(small));
+
+    // Incomplete messages are evicted
+    let mut reassembler = Reassembler::<Telemetry>::with_max_pending(2);
+
+    for _ in 0..3 {
+        let fragments = chunker.chunk(&telemetry).unwrap();
+        reassembler.push(&fragments[0]).unwrap();
+    }
+
+    assert_eq!(reassembler.pending(), 2);
+
+    // Malformed fragment
+    let mut fragment = vec![0; FRAGMENT_HEADER_LEN];
+    fragment[4] = 2;
+    fragment[6] = 2;
+    assert!(matches!(
+        reassembler.push(&fragment),
+        Err(LBSError::Parsing(_))
+    ));
+    assert!(reassembler.push(&fragment[..3]).unwrap_err().is_eof());
+}
+
+#[test]
+fn chunk_max_bytes() {
+    let telemetry = Telemetry {
+        host: "node-1".to_string(),
+        samples: (0..100).map(|i| i as f64).collect(),
+    };
+
+    let mut chunker = Chunker::new(128);
+    let payload_len = 128 - FRAGMENT_HEADER_LEN;
+    let mut reassembler = Reassembler::<Telemetry>::new().max_bytes(3 * payload_len);
+
+    // Fragments of the oldest message are dropped to fit new ones
+    let first = chunker.chunk(&telemetry).unwrap();
+    let second = chunker.chunk(&telemetry).unwrap();
+
+    for f in &first[..2] {
+        assert!(reassembler.push(f).unwrap().is_none());
+    }
+
+    for f in &second[..2] {
+        assert!(reassembler.push(f).unwrap().is_none());
+    }
+
+    assert_eq!(reassembler.pending(), 1);
+    assert_eq!(reassembler.buffered(), 2 * payload_len);
+
+    // Message, which doesn't fit alone, fails
+    assert!(reassembler.push(&second[2]).unwrap().is_none());
+    assert!(matches!(
+        reassembler.push(&second[3]),
+        Err(LBSError::LimitExceeded(_))
+    ));
+    assert_eq!(reassembler.pending(), 0);
+    assert_eq!(reassembler.buffered(), 0);
+
+    // Messages within the limit are still reassembled
+    let mut reassembler = Reassembler::<Telemetry>::new().max_bytes(first.len() * payload_len);
+
+    let mut decoded = None;
+
+    for f in &first {
+        decoded = reassembler.push(f).unwrap();
+    }
+
+    assert_eq!(decoded, Some(telemetry));
+    assert_eq!(reassembler.buffered(), 0);
+}
@@ -0,0 +1,55 @@
+use lbs::schema::LBSSchema;
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use palette::Srgb;
+use palette::Srgba;
+use rgb::Bgr;
+use rgb::RGB8;
+use rgb::RGBA;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug, Default)]
+struct Palette {
+    #[lbs(id(0))]
+    swatches: Vec<RGB8>,
+    #[lbs(id(1))]
+    gradient: Vec<RGBA<f32>>,
+    #[lbs(id(2))]
+    background: Srgb,
+    #[lbs(id(3))]
+    overlay: Option<Srgba>,
+}
+
+fn encode<T: LBSWrite>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn color() {
+    let palette = Palette {
+        swatches: vec![RGB8::new(255, 128, 0), RGB8::new(0, 0, 0)],
+        gradient: vec![RGBA::new(0.0, 0.5, 1.0, 0.25)],
+        background: Srgb::new(0.1, 0.2, 0.3),
+        overlay: Some(Srgba::new(1.0, 1.0, 1.0, 0.5)),
+    };
+
+    let buf = encode(&palette);
+    assert_eq!(Palette::lbs_read(&mut buf.as_slice()).unwrap(), palette);
+
+    // Components are written as raw sequences
+    assert_eq!(encode(&RGB8::new(1, 2, 3)), [1, 2, 3]);
+    assert_eq!(encode(&Bgr { b: 3u8, g: 2, r: 1 }), [3, 2, 1]);
+    assert_eq!(
+        encode(&Srgba::new(0.1f32, 0.2, 0.3, 0.4)),
+        encode(&(0.1f32, 0.2f32, (0.3f32, 0.4f32)))
+    );
+
+    assert_eq!(<RGB8 as LBSMaxSize>::LBS_MAX_SIZE, 3);
+    assert_eq!(<Srgba as LBSMaxSize>::LBS_MAX_SIZE, 16);
+    assert_eq!(
+        Srgba::<f32>::lbs_schema(),
+        <RGBA<f32> as LBSSchema>::lbs_schema()
+    );
+}
@@ -0,0 +1,68 @@
+use lbs::error::LBSError;
+use lbs::LBSColumnar;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, LBSColumnar, Default, Clone, PartialEq, Debug)]
+struct Sample {
+    #[lbs(id(0))]
+    timestamp: u64,
+    #[lbs(id(1))]
+    value: f64,
+    #[lbs(id(2))]
+    host: Option<String>,
+    #[lbs(id(3), utf8)]
+    unit: char,
+    #[lbs(id(4), skip)]
+    cache: u32,
+}
+
+#[derive(LBSColumnar, Default, PartialEq, Debug)]
+struct SampleV2 {
+    #[lbs(id(0))]
+    timestamp: u64,
+    #[lbs(id(5))]
+    region: String,
+}
+
+#[test]
+fn columnar() {
+    let rows: Vec<Sample> = (0..100)
+        .map(|i| Sample {
+            timestamp: 1_700_000_000 + i,
+            value: i as f64 / 2.0,
+            host: (i % 3 == 0).then(|| format!("host{}", i % 4)),
+            unit: 'µ',
+            cache: 1,
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    lbs::columnar::write(&mut buf, &rows).unwrap();
+
+    // Timestamps are contiguous: row count, column count, id and length come first
+    let timestamps = &buf[4 + 2 + 2 + 4..][..800];
+    assert_eq!(&timestamps[..8], &1_700_000_000u64.to_le_bytes());
+    assert_eq!(&timestamps[8..16], &1_700_000_001u64.to_le_bytes());
+
+    let decoded: Vec<Sample> = lbs::columnar::read(&mut buf.as_slice()).unwrap();
+    let expected: Vec<Sample> = rows
+        .iter()
+        .cloned()
+        .map(|row| Sample { cache: 0, ..row })
+        .collect();
+    assert_eq!(decoded, expected);
+
+    // Unknown columns are skipped, missing required ones are reported
+    assert!(matches!(
+        lbs::columnar::read::<SampleV2, _>(&mut buf.as_slice()),
+        Err(LBSError::WithField(5, _))
+    ));
+
+    // Empty batch
+    let mut buf = Vec::new();
+    lbs::columnar::write::<Sample, _>(&mut buf, &[]).unwrap();
+    assert!(lbs::columnar::read::<SampleV2, _>(&mut buf.as_slice())
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,103 @@
+use lbs::decoder::Decoder;
+use lbs::error::LBSError;
+use lbs::tokio::LBSReadAsync;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(compact)]
+struct Compact {
+    #[lbs(id(300))]
+    c: u32,
+    #[lbs(id(1))]
+    a: String,
+    #[lbs(id(2))]
+    b: Option<u8>,
+    #[lbs(id(3))]
+    e: Kind,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Regular {
+    #[lbs(id(300))]
+    c: u32,
+    #[lbs(id(1))]
+    a: String,
+    #[lbs(id(2))]
+    b: Option<u8>,
+    #[lbs(id(3))]
+    e: Kind,
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, PartialEq, Debug)]
+#[lbs(compact)]
+struct Pair {
+    #[lbs(id(0))]
+    a: u8,
+    #[lbs(id(1))]
+    b: u8,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+#[lbs(compact)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(200))]
+    Value(u64),
+}
+
+#[test]
+fn compact_header() {
+    let compact = Compact {
+        c: 7,
+        a: "a".into(),
+        b: None,
+        e: Kind::Value(1),
+    };
+    let regular = Regular {
+        c: 7,
+        a: "a".into(),
+        b: None,
+        e: Kind::Value(1),
+    };
+
+    let mut compact_buf = Vec::new();
+    compact.lbs_write(&mut compact_buf).unwrap();
+    let mut regular_buf = Vec::new();
+    regular.lbs_write(&mut regular_buf).unwrap();
+
+    // count: 2 -> 1, ids: 6 -> 4
+    assert_eq!(compact_buf.len() + 3, regular_buf.len());
+    assert_eq!(
+        Compact::lbs_read(&mut compact_buf.as_slice()).unwrap(),
+        compact
+    );
+    assert_eq!(
+        Regular::lbs_read(&mut regular_buf.as_slice()).unwrap(),
+        regular
+    );
+}
+
+#[tokio::test]
+async fn compact_zero_id_delta() {
+    // count, then delta and value of each field
+    let valid = [2, 0, 5, 1, 6];
+    assert_eq!(
+        Pair::lbs_read(&mut valid.as_slice()).unwrap(),
+        Pair { a: 5, b: 6 }
+    );
+
+    // Second field repeats id of the first one
+    let repeated = [2, 0, 5, 0, 6];
+    assert!(matches!(
+        Pair::lbs_read(&mut repeated.as_slice()),
+        Err(LBSError::Parsing(_))
+    ));
+    assert!(matches!(
+        Pair::lbs_read_async(&mut repeated.as_slice(), &mut Decoder::new()).await,
+        Err(LBSError::Parsing(_))
+    ));
+}
@@ -0,0 +1,70 @@
+use lbs::compact_duration::LBSCompactDurationRead;
+use lbs::compact_duration::LBSCompactDurationWrite;
+use lbs::decoder::Decoder;
+use lbs::layout::layout;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug, Default)]
+struct Histogram {
+    #[lbs(id(0), compact_duration)]
+    window: Duration,
+    #[lbs(id(1), compact_duration)]
+    samples: Vec<Duration>,
+    #[lbs(id(2), compact_duration)]
+    max: Option<Duration>,
+}
+
+fn encoded_len(duration: Duration) -> usize {
+    let mut buf = Vec::new();
+    duration.lbs_compact_duration_write(&mut buf).unwrap();
+    assert_eq!(
+        Duration::lbs_compact_duration_read(&mut buf.as_slice(), &mut Decoder::new()).unwrap(),
+        duration
+    );
+    buf.len()
+}
+
+#[test]
+fn compact_duration() {
+    let histogram = Histogram {
+        window: Duration::from_secs(60),
+        samples: vec![
+            Duration::from_micros(250),
+            Duration::from_micros(1500),
+            Duration::from_millis(2300),
+        ],
+        max: Some(Duration::from_millis(2300)),
+    };
+
+    let mut buf = Vec::new();
+    histogram.lbs_write(&mut buf).unwrap();
+    assert_eq!(Histogram::lbs_read(&mut buf.as_slice()).unwrap(), histogram);
+
+    assert_eq!(encoded_len(Duration::ZERO), 1);
+    assert_eq!(encoded_len(Duration::from_secs(3600)), 2);
+    assert_eq!(encoded_len(Duration::from_micros(1500)), 4);
+    assert_eq!(encoded_len(Duration::from_nanos(u64::MAX >> 2)), 10);
+
+    // Longest durations fall back to the regular encoding
+    assert_eq!(encoded_len(Duration::new(u64::MAX, 1)), 13);
+    assert_eq!(encoded_len(Duration::MAX), 13);
+
+    assert!(layout::<Histogram>()
+        .to_string()
+        .contains("#0 window: Duration as compact_duration [variable]"));
+}
+
+#[test]
+fn compact_duration_invalid() {
+    let mut buf = Vec::new();
+    lbs::varint::write_u64(&mut buf, 3).unwrap();
+    assert!(Duration::lbs_compact_duration_read(&mut buf.as_slice(), &mut Decoder::new()).is_err());
+
+    let mut buf = Vec::new();
+    lbs::varint::write_u64(&mut buf, 2).unwrap();
+    0u64.lbs_write(&mut buf).unwrap();
+    2_000_000_000u32.lbs_write(&mut buf).unwrap();
+    assert!(Duration::lbs_compact_duration_read(&mut buf.as_slice(), &mut Decoder::new()).is_err());
+}
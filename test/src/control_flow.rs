@@ -0,0 +1,92 @@
+use lbs::schema::LBSSchema;
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::convert::Infallible;
+use std::ops::ControlFlow;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug)]
+enum Step {
+    #[lbs(id(0))]
+    Continue(u32),
+    #[lbs(id(1))]
+    Break(String),
+}
+
+/// State machine message, which never breaks or fails when instantiated with `Infallible`.
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Transition<B, E> {
+    #[lbs(id(0))]
+    step: Option<ControlFlow<B, u32>>,
+    #[lbs(id(1))]
+    error: Option<E>,
+}
+
+fn encode<T: LBSWrite>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn control_flow() {
+    let steps = [
+        ControlFlow::Continue(7),
+        ControlFlow::Break("done".to_string()),
+    ];
+
+    for step in steps {
+        let mut buf = Vec::new();
+        step.lbs_write(&mut buf).unwrap();
+        assert_eq!(ControlFlow::lbs_read(&mut buf.as_slice()).unwrap(), step);
+
+        // Same as an equivalent derived enum
+        let derived = match &step {
+            ControlFlow::Continue(v) => Step::Continue(*v),
+            ControlFlow::Break(v) => Step::Break(v.clone()),
+        };
+        assert_eq!(encode(&derived), buf);
+    }
+
+    assert_eq!(<ControlFlow<String, u32> as LBSSchema>::lbs_schema(), {
+        let mut schema = Step::lbs_schema();
+        if let lbs::schema::Schema::Enum(ref mut e) = schema {
+            e.name = "ControlFlow".to_string();
+        }
+        schema
+    });
+
+    assert_eq!(<ControlFlow<u8, u64> as LBSMaxSize>::LBS_MAX_SIZE, 10);
+}
+
+#[test]
+fn control_flow_unexpected_variant() {
+    let buf = encode(&2u16);
+    assert!(matches!(
+        ControlFlow::<u8, u8>::lbs_read(&mut buf.as_slice()),
+        Err(lbs::error::LBSError::UnexpectedVariant)
+    ));
+}
+
+#[test]
+fn infallible() {
+    let transition = Transition::<Infallible, Infallible> {
+        step: Some(ControlFlow::Continue(3)),
+        error: None,
+    };
+
+    let mut buf = Vec::new();
+    transition.lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        Transition::<Infallible, Infallible>::lbs_read(&mut buf.as_slice()).unwrap(),
+        transition
+    );
+
+    // Input claiming to hold a never value is rejected
+    let broken = encode(&ControlFlow::<u8, u32>::Break(0));
+    assert!(matches!(
+        ControlFlow::<Infallible, u32>::lbs_read(&mut broken.as_slice()),
+        Err(lbs::error::LBSError::UnexpectedVariant)
+    ));
+    assert_eq!(<Infallible as LBSMaxSize>::LBS_MAX_SIZE, 0);
+}
@@ -0,0 +1,45 @@
+use lbs::write::CountingWriter;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+#[lbs(version(2))]
+struct Event {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    tags: Vec<String>,
+}
+
+#[test]
+fn counting() {
+    let event = Event {
+        name: "login".to_string(),
+        tags: vec!["web".to_string(), "eu".to_string()],
+    };
+
+    let mut w = CountingWriter::new(Vec::new());
+    event.lbs_write(&mut w).unwrap();
+    let count = w.count();
+    assert_eq!(count, w.get_ref().len());
+    assert_eq!(lbs::write::encoded_len(&event).unwrap(), count);
+
+    // Top-level helpers report bytes written
+    let mut buf = Vec::new();
+    assert_eq!(Encoder::new().encode(&event, &mut buf).unwrap(), count);
+
+    let mut buf = Vec::new();
+    assert_eq!(
+        lbs::frame::write_frame(&mut buf, &event).unwrap(),
+        buf.len()
+    );
+    assert_eq!(buf.len(), 4 + count);
+
+    let mut buf = Vec::new();
+    assert_eq!(lbs::migrate::write(&mut buf, &event).unwrap(), buf.len());
+
+    let mut buf = Vec::new();
+    let records = [event.clone(), event];
+    assert_eq!(lbs::batch::write(&mut buf, &records).unwrap(), buf.len());
+}
@@ -0,0 +1,59 @@
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::borrow::Cow;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Clone, Default, PartialEq)]
+struct Tag {
+    #[lbs(id(0))]
+    key: String,
+    #[lbs(id(1))]
+    value: String,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Default, PartialEq)]
+struct Request<'a, T: Clone> {
+    #[lbs(id(0))]
+    name: Cow<'a, str>,
+    #[lbs(id(1))]
+    tags: Cow<'a, [Tag]>,
+    #[lbs(id(2))]
+    payload: Cow<'a, [u8]>,
+    #[lbs(id(3))]
+    extra: Option<Cow<'a, [T]>>,
+}
+
+#[test]
+fn cow_slices() {
+    let tags = [Tag {
+        key: "env".to_string(),
+        value: "prod".to_string(),
+    }];
+
+    let request = Request {
+        name: Cow::Borrowed("deploy"),
+        tags: Cow::Borrowed(&tags),
+        payload: Cow::Owned(vec![1, 2, 3]),
+        extra: Some(Cow::Borrowed(&[7u16, 8][..])),
+    };
+
+    let mut buf = Vec::new();
+    request.lbs_write(&mut buf).unwrap();
+
+    // Slices are read into owned vectors
+    let decoded = Request::<u16>::lbs_read(&mut buf.as_slice()).unwrap();
+    assert!(matches!(decoded.tags, Cow::Owned(_)));
+    assert_eq!(decoded, request);
+
+    // Slices are skipped without decoding
+    let projected: Request<u16> = lbs::read::project(&mut buf.as_slice(), &[0]).unwrap();
+    assert_eq!(projected.name, "deploy");
+    assert!(projected.tags.is_empty());
+
+    // Same wire form as owned vectors
+    let Schema::Struct(schema) = Request::<u16>::lbs_schema() else {
+        unreachable!()
+    };
+    assert_eq!(schema.fields[1].schema, <Vec<Tag>>::lbs_schema());
+}
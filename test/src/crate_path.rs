@@ -0,0 +1,48 @@
+/// Facade, which re-exports lbs under another name.
+mod facade {
+    pub use ::lbs as serialization;
+}
+
+mod shadowed {
+    use super::facade::serialization::schema::LBSSchema;
+    use super::facade::serialization::LBSRead;
+    use super::facade::serialization::LBSWrite;
+
+    /// Unrelated module, which hides the crate, so generated code must use the given path.
+    #[allow(dead_code)]
+    mod lbs {}
+
+    #[derive(
+        LBSWrite, LBSRead, super::facade::serialization::LBSSchema, Debug, Default, PartialEq,
+    )]
+    #[lbs(crate(super::facade::serialization), compact)]
+    pub struct Point {
+        #[lbs(id(0))]
+        pub x: i32,
+        #[lbs(id(1))]
+        pub y: Option<i32>,
+    }
+
+    #[derive(LBSWrite, LBSRead, Debug, Default, PartialEq)]
+    #[lbs(crate(super::facade::serialization))]
+    pub enum Shape {
+        #[default]
+        #[lbs(id(0))]
+        Empty,
+        #[lbs(id(1))]
+        Dot(Point),
+    }
+
+    #[test]
+    fn crate_path() {
+        let shape = Shape::Dot(Point { x: 1, y: Some(-2) });
+
+        let mut buf = Vec::new();
+        shape.lbs_write(&mut buf).unwrap();
+        assert_eq!(Shape::lbs_read(&mut buf.as_slice()).unwrap(), shape);
+        assert!(matches!(
+            Point::lbs_schema(),
+            super::facade::serialization::schema::Schema::Struct(_)
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+use lbs::crypto::decrypt_read;
+use lbs::crypto::encrypt_write;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Secret {
+    #[lbs(id(0))]
+    token: String,
+}
+
+#[test]
+fn crypto() {
+    let key = [7; lbs::crypto::KEY_LEN];
+    let secret = Secret {
+        token: "token".into(),
+    };
+
+    let mut buf = Vec::new();
+    encrypt_write(&mut buf, &key, &secret).unwrap();
+    assert!(!buf.windows(5).any(|w| w == b"token"));
+    assert_eq!(
+        decrypt_read::<Secret, _>(&mut buf.as_slice(), &key).unwrap(),
+        secret
+    );
+
+    // Nonce is random
+    let mut again = Vec::new();
+    encrypt_write(&mut again, &key, &secret).unwrap();
+    assert_ne!(again, buf);
+
+    // Wrong key
+    assert!(matches!(
+        decrypt_read::<Secret, _>(&mut buf.as_slice(), &[8; lbs::crypto::KEY_LEN]),
+        Err(LBSError::AuthenticationFailed)
+    ));
+
+    // Tampered ciphertext
+    let mut tampered = buf.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        decrypt_read::<Secret, _>(&mut tampered.as_slice(), &key),
+        Err(LBSError::AuthenticationFailed)
+    ));
+
+    // Length prefix is authenticated
+    let mut extended = buf.clone();
+    extended[0] += 1;
+    extended.push(0);
+    assert!(matches!(
+        decrypt_read::<Secret, _>(&mut extended.as_slice(), &key),
+        Err(LBSError::AuthenticationFailed)
+    ));
+}
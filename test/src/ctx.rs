@@ -0,0 +1,100 @@
+use lbs::ctx::LBSReadCtx;
+use lbs::ctx::LBSWriteCtx;
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Read;
+use std::io::Write;
+
+/// Protocol version, which changes width of prices, and counter of written prices.
+#[derive(Default)]
+struct Proto {
+    version: u16,
+    prices: usize,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+struct Price(u64);
+
+impl LBSWriteCtx<Proto> for Price {
+    fn lbs_write_ctx<W: Write>(&self, w: &mut W, ctx: &mut Proto) -> Result<(), LBSError> {
+        ctx.prices += 1;
+        match ctx.version {
+            1 => (self.0 as u32).lbs_write(w),
+            _ => self.0.lbs_write(w),
+        }
+    }
+}
+
+impl LBSReadCtx<Proto> for Price {
+    fn lbs_read_ctx<R: Read>(r: &mut R, ctx: &mut Proto) -> Result<Self, LBSError> {
+        match ctx.version {
+            1 => Ok(Self(u32::lbs_read(r)? as u64)),
+            _ => Ok(Self(u64::lbs_read(r)?)),
+        }
+    }
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone, Default)]
+#[lbs(ctx(Proto))]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1), ctx)]
+    price: Price,
+    #[lbs(id(2), ctx)]
+    discounts: Vec<Price>,
+    #[lbs(id(3), ctx)]
+    tip: Option<Price>,
+    #[lbs(id(4), ctx)]
+    payment: Payment,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone, Default)]
+#[lbs(ctx(Proto))]
+enum Payment {
+    #[default]
+    #[lbs(id(0))]
+    Cash,
+    #[lbs(id(1), ctx)]
+    Card(Price),
+}
+
+#[test]
+fn ctx() {
+    let order = Order {
+        id: 1,
+        price: Price(1000),
+        discounts: vec![Price(100), Price(50)],
+        tip: None,
+        payment: Payment::Card(Price(950)),
+    };
+
+    let mut v1 = Proto {
+        version: 1,
+        ..Default::default()
+    };
+    let mut v2 = Proto {
+        version: 2,
+        ..Default::default()
+    };
+
+    let mut b1 = Vec::new();
+    let mut b2 = Vec::new();
+    order.lbs_write_ctx(&mut b1, &mut v1).unwrap();
+    order.lbs_write_ctx(&mut b2, &mut v2).unwrap();
+
+    // Every price is 4 bytes narrower in version 1
+    assert_eq!(b2.len() - b1.len(), 4 * 4);
+    assert_eq!(v1.prices, 4);
+    assert_eq!(v2.prices, 4);
+
+    assert_eq!(
+        Order::lbs_read_ctx(&mut b1.as_slice(), &mut v1).unwrap(),
+        order
+    );
+    assert_eq!(
+        Order::lbs_read_ctx(&mut b2.as_slice(), &mut v2).unwrap(),
+        order
+    );
+}
@@ -0,0 +1,78 @@
+use ipnet::IpNet;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Message {
+    #[lbs(id(0))]
+    id: Uuid,
+    #[lbs(id(1), default(Arc::from("")))]
+    label: Arc<str>,
+    #[lbs(id(2), default(IpNet::from_str("0.0.0.0/0").unwrap()))]
+    net: IpNet,
+    #[lbs(id(3))]
+    tags: Vec<String>,
+    #[lbs(id(4))]
+    unit: Unit,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Unit;
+
+#[test]
+fn decoder_reuse() {
+    let mut decoder = Decoder::new();
+
+    for i in 0..3 {
+        let original = Message {
+            id: Uuid::new_v4(),
+            label: Arc::from(format!("label{}", i)),
+            net: IpNet::from_str("10.0.0.0/8").unwrap(),
+            tags: vec![String::from("a"), String::from("bb")],
+            unit: Unit,
+        };
+
+        let mut buf = Vec::new();
+        original.lbs_write(&mut buf).unwrap();
+
+        let decoded: Message = decoder.decode(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded, original);
+        assert_eq!(Message::lbs_read(&mut buf.as_slice()).unwrap(), original);
+    }
+}
+
+#[test]
+fn decoder_trusted_utf8() {
+    let original = vec![String::from("hello"), String::from("мир")];
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+
+    let mut decoder = Decoder::new();
+    unsafe { decoder.trust_utf8(true) };
+    let decoded: Vec<String> = decoder.decode(&mut buf.as_slice()).unwrap();
+    assert_eq!(decoded, original);
+}
+
+#[test]
+fn decoder_interning() {
+    let original: Vec<Arc<str>> = vec![
+        Arc::from("host-a"),
+        Arc::from("host-b"),
+        Arc::from("host-a"),
+    ];
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+
+    let mut decoder = Decoder::new();
+    decoder.intern_strings(true);
+    let first: Vec<Arc<str>> = decoder.decode(&mut buf.as_slice()).unwrap();
+    let second: Vec<Arc<str>> = decoder.decode(&mut buf.as_slice()).unwrap();
+    assert_eq!(first, original);
+    assert!(Arc::ptr_eq(&first[0], &first[2]));
+    assert!(Arc::ptr_eq(&first[1], &second[1]));
+    assert!(!Arc::ptr_eq(&first[0], &first[1]));
+}
@@ -0,0 +1,104 @@
+use lbs::error::LBSError;
+use lbs::limits::Limits;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use lbs::Options;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Payload {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    values: Vec<u32>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Envelope {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1), delimited)]
+    payload: Payload,
+    #[lbs(id(2))]
+    trace: String,
+}
+
+#[test]
+fn delimited() {
+    let envelope = Envelope {
+        id: 7,
+        payload: Payload {
+            name: "sensor".to_string(),
+            values: vec![1, 2, 3],
+        },
+        trace: "abc".to_string(),
+    };
+
+    let mut buf = Vec::new();
+    envelope.lbs_write(&mut buf).unwrap();
+    assert_eq!(Envelope::lbs_read(&mut buf.as_slice()).unwrap(), envelope);
+
+    // Payload is skipped by its length
+    let projected =
+        Envelope::lbs_read_projected(&mut buf.as_slice(), &mut Decoder::new(), &[0, 2]).unwrap();
+    assert_eq!(projected.payload, Payload::default());
+    assert_eq!(projected.trace, "abc");
+
+    // Value must occupy its bytes
+    let mut payload = Vec::new();
+    envelope.payload.lbs_write(&mut payload).unwrap();
+
+    let mut buf = Vec::new();
+    lbs::write::write_len(&mut buf, payload.len() + 2).unwrap();
+    buf.extend(&payload);
+    buf.extend([0, 0]);
+
+    let err =
+        lbs::delimited::read::<Payload, _>(&mut buf.as_slice(), &mut Decoder::new()).unwrap_err();
+    assert!(matches!(err, LBSError::TrailingBytes(2)));
+
+    // Untrusted length isn't allocated upfront
+    let mut buf = Vec::new();
+    lbs::write::write_len(&mut buf, u32::MAX as usize).unwrap();
+    buf.extend([1, 2, 3]);
+
+    let err =
+        lbs::delimited::read::<Payload, _>(&mut buf.as_slice(), &mut Decoder::new()).unwrap_err();
+    assert!(err.is_eof());
+    assert!(
+        lbs::delimited::skip(&mut buf.as_slice(), &mut Decoder::new())
+            .unwrap_err()
+            .is_eof()
+    );
+}
+
+#[test]
+fn delimited_limits() {
+    let mut options = Options::new();
+    options.limits(Limits {
+        max_len: Some(1024),
+        ..Limits::new()
+    });
+    let mut d = options.decoder();
+
+    let mut buf = Vec::new();
+    lbs::write::write_len(&mut buf, 1025).unwrap();
+    buf.extend([0; 1025]);
+
+    let err = lbs::delimited::read::<Payload, _>(&mut buf.as_slice(), &mut d).unwrap_err();
+    assert!(matches!(err, LBSError::LimitExceeded("length")));
+    let err = lbs::delimited::skip(&mut buf.as_slice(), &mut d).unwrap_err();
+    assert!(matches!(err, LBSError::LimitExceeded("length")));
+
+    // Oversized payload of a field
+    let mut fields = Vec::new();
+    lbs::write::write_field_count(&mut fields, 1).unwrap();
+    lbs::write::write_field_id(&mut fields, 1).unwrap();
+    fields.extend(&buf);
+
+    let err = lbs::from_slice_with::<Envelope>(&fields, &options).unwrap_err();
+    assert!(matches!(
+        err.root_cause(),
+        LBSError::LimitExceeded("length")
+    ));
+}
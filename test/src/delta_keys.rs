@@ -0,0 +1,68 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Routes {
+    #[lbs(id(0), delta_keys)]
+    shards: BTreeMap<u32, u16>,
+    #[lbs(id(1), delta_keys)]
+    replicas: HashMap<u16, String>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct RegularRoutes {
+    #[lbs(id(0))]
+    shards: BTreeMap<u32, u16>,
+    #[lbs(id(1))]
+    replicas: HashMap<u16, String>,
+}
+
+#[derive(LBSRead, Debug)]
+struct NarrowRoutes {
+    #[lbs(id(0), delta_keys)]
+    shards: BTreeMap<u8, u16>,
+}
+
+#[test]
+fn delta_keys() {
+    let shards: BTreeMap<u32, u16> = (1_000_000..1_001_000).map(|k| (k, 7)).collect();
+    let replicas: HashMap<u16, String> = [(3, "a"), (1, "b"), (2, "c"), (900, "d")]
+        .into_iter()
+        .map(|(k, v)| (k, v.to_string()))
+        .collect();
+
+    let routes = Routes {
+        shards: shards.clone(),
+        replicas: replicas.clone(),
+    };
+
+    let mut buf = Vec::new();
+    routes.lbs_write(&mut buf).unwrap();
+    assert_eq!(Routes::lbs_read(&mut buf.as_slice()).unwrap(), routes);
+
+    let mut regular_buf = Vec::new();
+    RegularRoutes { shards, replicas }
+        .lbs_write(&mut regular_buf)
+        .unwrap();
+
+    // Contiguous keys take a byte instead of four
+    assert!(buf.len() + 2900 < regular_buf.len());
+
+    // Keys must fit into declared type
+    let err = NarrowRoutes::lbs_read(&mut buf.as_slice()).unwrap_err();
+    assert!(matches!(err.root_cause(), LBSError::OutOfRange("u8")));
+
+    // Repeated keys are rejected
+    let mut buf = Vec::new();
+    lbs::write::write_len(&mut buf, 2).unwrap();
+    buf.extend([5, 0, 0, 0, 0, 0]);
+    let err = lbs::delta_keys::read::<BTreeMap<u8, u16>, _>(
+        &mut buf.as_slice(),
+        &mut lbs::Decoder::new(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, LBSError::Parsing(_)));
+}
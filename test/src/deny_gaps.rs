@@ -0,0 +1,36 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(deny_gaps)]
+struct Dense {
+    #[lbs(id(1))]
+    f1: u8,
+    #[lbs(id(0))]
+    f0: u8,
+    #[lbs(skip)]
+    cached: u8,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(deny_gaps)]
+enum DenseKind {
+    #[lbs(id(0))]
+    A,
+    #[lbs(id(1))]
+    B(Dense),
+}
+
+#[test]
+fn deny_gaps() {
+    let msg = DenseKind::B(Dense {
+        f1: 1,
+        f0: 0,
+        cached: 0,
+    });
+
+    let mut buf = Vec::with_capacity(128);
+    msg.lbs_write(&mut buf).unwrap();
+    assert_eq!(DenseKind::lbs_read(&mut buf.as_slice()).unwrap(), msg);
+    assert_ne!(msg, DenseKind::A);
+}
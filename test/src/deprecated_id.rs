@@ -0,0 +1,120 @@
+use lbs::metrics::LBSMetrics;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct UserV1 {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(4))]
+    phone: Option<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct UserV2 {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(deprecated_id(4, note = "use contacts instead"))]
+    phone: Option<String>,
+    #[lbs(id(5), optional)]
+    contacts: Vec<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct Team {
+    #[lbs(id(0))]
+    members: Vec<UserV2>,
+    #[lbs(deprecated_id(1))]
+    lead: Option<u32>,
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(&'static str, u16, &'static str)>>);
+
+impl LBSMetrics for Recorder {
+    fn deprecated_field(&self, type_name: &'static str, id: u16, note: &'static str) {
+        self.0.lock().unwrap().push((type_name, id, note));
+    }
+}
+
+fn decoder(recorder: &Arc<Recorder>) -> Decoder {
+    let mut d = Decoder::new();
+    d.metrics(recorder.clone());
+    d
+}
+
+#[test]
+fn deprecated_id() {
+    let recorder = Arc::new(Recorder::default());
+    let old = UserV1 {
+        name: "alice".to_string(),
+        phone: Some("555".to_string()),
+    };
+
+    // Deprecated field is still read, and its reading is reported
+    let bytes = lbs::to_vec(&old).unwrap();
+    let user = decoder(&recorder)
+        .decode::<UserV2, _>(&mut bytes.as_slice())
+        .unwrap();
+    assert_eq!(user.phone.as_deref(), Some("555"));
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [(std::any::type_name::<UserV2>(), 4, "use contacts instead")]
+    );
+
+    // Absent deprecated field isn't reported
+    recorder.0.lock().unwrap().clear();
+    let new = UserV2 {
+        name: "bob".to_string(),
+        phone: None,
+        contacts: vec!["bob@example.com".to_string()],
+    };
+    let bytes = lbs::to_vec(&new).unwrap();
+    let user = decoder(&recorder)
+        .decode::<UserV2, _>(&mut bytes.as_slice())
+        .unwrap();
+    assert_eq!(user, new);
+    assert!(recorder.0.lock().unwrap().is_empty());
+}
+
+#[test]
+fn deprecated_id_nested() {
+    let recorder = Arc::new(Recorder::default());
+    let team = Team {
+        members: vec![
+            UserV2 {
+                phone: Some("1".to_string()),
+                ..Default::default()
+            },
+            UserV2::default(),
+            UserV2 {
+                phone: Some("2".to_string()),
+                ..Default::default()
+            },
+        ],
+        lead: Some(7),
+    };
+
+    let bytes = lbs::to_vec(&team).unwrap();
+    let read = decoder(&recorder)
+        .decode::<Team, _>(&mut bytes.as_slice())
+        .unwrap();
+    assert_eq!(read, team);
+
+    // Every occurrence is reported, notes are optional
+    let user = std::any::type_name::<UserV2>();
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [
+            (user, 4, "use contacts instead"),
+            (user, 4, "use contacts instead"),
+            (std::any::type_name::<Team>(), 1, ""),
+        ]
+    );
+
+    // Reading without a decoder of its own goes to global metrics, which aren't set
+    assert_eq!(lbs::from_slice::<Team>(&bytes).unwrap(), team);
+}
@@ -0,0 +1,134 @@
+use lbs::diff::LBSDiff;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSDiff, Default, Clone, PartialEq, Debug)]
+struct Document {
+    #[lbs(id(0))]
+    title: String,
+    #[lbs(id(1))]
+    body: Vec<u8>,
+    #[lbs(id(2))]
+    author: Option<Author>,
+    #[lbs(id(3))]
+    tags: Vec<String>,
+    #[lbs(id(4))]
+    counters: HashMap<String, u64>,
+    #[lbs(id(5))]
+    sections: BTreeMap<u32, Section>,
+    #[lbs(id(6))]
+    state: State,
+    #[lbs(id(7), skip)]
+    cache: u32,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSDiff, Default, Clone, PartialEq, Debug)]
+struct Author {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    email: String,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSDiff, Default, Clone, PartialEq, Debug)]
+struct Section {
+    #[lbs(id(0))]
+    heading: String,
+    #[lbs(id(1))]
+    words: u32,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSDiff, Default, Clone, PartialEq, Debug)]
+enum State {
+    #[default]
+    #[lbs(id(0))]
+    Draft,
+    #[lbs(id(1))]
+    Review(Author),
+    #[lbs(id(2))]
+    Published(u64),
+}
+
+#[test]
+fn diff() {
+    let a = Document {
+        title: "Title".into(),
+        body: vec![7; 100_000],
+        author: Some(Author {
+            name: "John".into(),
+            email: "john@example.com".into(),
+        }),
+        tags: vec!["a".into(), "b".into(), "c".into()],
+        counters: HashMap::from([("views".into(), 1), ("likes".into(), 2)]),
+        sections: BTreeMap::from([
+            (
+                1,
+                Section {
+                    heading: "One".into(),
+                    words: 10,
+                },
+            ),
+            (
+                2,
+                Section {
+                    heading: "Two".into(),
+                    words: 20,
+                },
+            ),
+        ]),
+        state: State::Review(Author::default()),
+        cache: 1,
+    };
+
+    // Equal values produce empty patch
+    let patch = lbs::diff::diff(&a, &a).unwrap();
+    assert!(patch.is_empty());
+    assert_eq!(lbs::diff::apply(a.clone(), &patch).unwrap(), a);
+
+    // Single nested field change is small
+    let mut b = a.clone();
+    b.author.as_mut().unwrap().email = "john@example.org".into();
+    let patch = lbs::diff::diff(&a, &b).unwrap();
+    assert!(patch.as_bytes().len() < 64);
+    assert_eq!(lbs::diff::apply(a.clone(), &patch).unwrap(), b);
+
+    // Collections, enums and options
+    b.tags[1] = "B".into();
+    b.tags.push("d".into());
+    b.counters.remove("likes");
+    b.counters.insert("shares".into(), 5);
+    *b.counters.get_mut("views").unwrap() += 1;
+    b.sections.get_mut(&2).unwrap().words = 21;
+    b.sections.remove(&1);
+    b.state = State::Review(Author {
+        name: "Reviewer".into(),
+        email: String::new(),
+    });
+    b.cache = 2;
+    let patch = lbs::diff::diff(&a, &b).unwrap();
+    assert!(patch.as_bytes().len() < 256);
+    let mut expected = b.clone();
+    expected.cache = a.cache;
+    assert_eq!(lbs::diff::apply(a.clone(), &patch).unwrap(), expected);
+
+    // Replacements and truncation
+    let mut c = b.clone();
+    c.state = State::Published(42);
+    c.author = None;
+    c.tags.truncate(1);
+    let patch = lbs::diff::diff(&b, &c).unwrap();
+    assert_eq!(lbs::diff::apply(b.clone(), &patch).unwrap(), c);
+
+    // Patch survives encoding
+    let mut buf = Vec::new();
+    patch.lbs_write(&mut buf).unwrap();
+    let decoded = lbs::diff::Patch::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(decoded, patch);
+
+    // Patch with unknown field is rejected
+    let mut author = Author::default();
+    let bad = [1, 0, 9, 0];
+    assert!(author.lbs_apply(&mut bad.as_slice()).is_err());
+}
@@ -0,0 +1,38 @@
+use lbs::digest::HashingReader;
+use lbs::digest::HashingWriter;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use sha2::Digest;
+use sha2::Sha256;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Snapshot {
+    #[lbs(id(0))]
+    version: u64,
+    #[lbs(id(1))]
+    items: Vec<String>,
+}
+
+#[test]
+fn digest_roundtrip() {
+    let original = Snapshot {
+        version: 7,
+        items: vec![String::from("a"), String::from("b")],
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    let expected = Sha256::digest(&buf);
+
+    let mut w = HashingWriter::new(Vec::new(), Sha256::new());
+    original.lbs_write(&mut w).unwrap();
+    let (written, hasher) = w.into_parts();
+    assert_eq!(written, buf);
+    assert_eq!(hasher.finalize(), expected);
+
+    assert_eq!(lbs::digest::hash::<Sha256, _>(&original).unwrap(), expected);
+
+    let mut r = HashingReader::new(buf.as_slice(), Sha256::new());
+    assert_eq!(Snapshot::lbs_read(&mut r).unwrap(), original);
+    assert_eq!(r.into_parts().1.finalize(), expected);
+}
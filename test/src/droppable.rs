@@ -0,0 +1,116 @@
+use lbs::error::LBSError;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Telemetry {
+    #[lbs(id(0))]
+    host: String,
+    #[lbs(id(1))]
+    cpu: f32,
+    #[lbs(id(2), optional, droppable(priority = 2))]
+    note: String,
+    #[lbs(id(3), droppable)]
+    tags: Option<Vec<String>>,
+    #[lbs(id(4))]
+    disks: Vec<Disk>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Disk {
+    #[lbs(id(0))]
+    used: u64,
+    #[lbs(id(1), droppable(priority = 1))]
+    label: Option<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Inventory {
+    #[lbs(id(0), parallel)]
+    disks: Vec<Disk>,
+}
+
+fn telemetry() -> Telemetry {
+    Telemetry {
+        host: "edge-1".to_string(),
+        cpu: 0.5,
+        note: "x".repeat(20),
+        tags: Some(vec!["eu".to_string(), "prod".to_string()]),
+        disks: vec![Disk {
+            used: 10,
+            label: Some("root".to_string()),
+        }],
+    }
+}
+
+#[test]
+fn droppable_fits() {
+    let value = telemetry();
+    let full = lbs::to_vec(&value).unwrap();
+    assert_eq!(lbs::to_vec_within(&value, full.len()).unwrap(), full);
+}
+
+#[test]
+fn droppable_lower_priorities_first() {
+    let value = telemetry();
+    let full = lbs::to_vec(&value).unwrap();
+
+    // Tags go first, then labels of nested disks, then the note
+    let mut expected = value.clone();
+    expected.tags = None;
+    let bytes = lbs::to_vec_within(&value, full.len() - 1).unwrap();
+    assert_eq!(lbs::from_slice::<Telemetry>(&bytes).unwrap(), expected);
+
+    expected.disks[0].label = None;
+    let bytes = lbs::to_vec_within(&value, lbs::to_vec(&expected).unwrap().len()).unwrap();
+    assert_eq!(lbs::from_slice::<Telemetry>(&bytes).unwrap(), expected);
+    let bytes = lbs::to_vec_within(&value, bytes.len() - 1).unwrap();
+    expected.note = String::new();
+    assert_eq!(lbs::from_slice::<Telemetry>(&bytes).unwrap(), expected);
+}
+
+#[test]
+fn droppable_over_budget() {
+    let value = telemetry();
+    let mut buf = Vec::new();
+    let err = Encoder::new()
+        .encode_within(&value, &mut buf, 10)
+        .unwrap_err();
+    assert!(matches!(err, LBSError::LimitExceeded("budget")));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn droppable_explicit() {
+    let value = telemetry();
+    let mut buf = Vec::new();
+    Encoder::new()
+        .drop_fields(Some(1))
+        .encode(&value, &mut buf)
+        .unwrap();
+
+    let mut expected = value;
+    expected.tags = None;
+    expected.disks[0].label = None;
+    assert_eq!(lbs::from_slice::<Telemetry>(&buf).unwrap(), expected);
+}
+
+#[test]
+fn droppable_parallel() {
+    let value = Inventory {
+        disks: (0..10_000)
+            .map(|used| Disk {
+                used,
+                label: Some("data".to_string()),
+            })
+            .collect(),
+    };
+    let full = lbs::to_vec(&value).unwrap();
+
+    // Priorities kept by elements encoded in parallel count too
+    let bytes = lbs::to_vec_within(&value, full.len() - 1).unwrap();
+    let mut expected = value;
+    expected.disks.iter_mut().for_each(|disk| disk.label = None);
+    assert_eq!(lbs::from_slice::<Inventory>(&bytes).unwrap(), expected);
+}
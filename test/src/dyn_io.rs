@@ -0,0 +1,45 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Cursor;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(dyn_io)]
+struct Message<T> {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    inner: Vec<T>,
+    #[lbs(id(2))]
+    kind: Kind,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+#[lbs(dyn_io, compact)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Value(u64),
+}
+
+#[test]
+fn dyn_io() {
+    let original = Message {
+        name: "dyn".into(),
+        inner: vec![1u32, 2, 3],
+        kind: Kind::Value(42),
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    let mut cursor = Cursor::new(Vec::new());
+    original.lbs_write(&mut cursor).unwrap();
+    assert_eq!(cursor.into_inner(), buf);
+
+    assert_eq!(Message::lbs_read(&mut buf.as_slice()).unwrap(), original);
+    assert_eq!(
+        Message::<u32>::lbs_read(&mut Cursor::new(&buf)).unwrap(),
+        original
+    );
+}
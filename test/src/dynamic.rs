@@ -0,0 +1,192 @@
+use lbs::dynamic::Kinds;
+use lbs::dynamic::Value;
+use lbs::schema::LBSSchema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Clone, PartialEq)]
+#[lbs(pack_bools, compact)]
+struct Request {
+    #[lbs(id(0))]
+    path: String,
+    #[lbs(id(1))]
+    retries: u8,
+    #[lbs(id(2))]
+    token: Option<String>,
+    #[lbs(id(3))]
+    traced: bool,
+    #[lbs(id(4))]
+    headers: Vec<(String, String)>,
+    #[lbs(id(5))]
+    method: Method,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Clone, PartialEq, Default)]
+enum Method {
+    #[default]
+    #[lbs(id(0))]
+    Get,
+    #[lbs(id(1))]
+    Post(u32),
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Clone, PartialEq)]
+struct Device {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    mac: Option<macaddr::MacAddr>,
+    #[lbs(id(2), varint)]
+    uptime: u64,
+}
+
+#[test]
+fn dynamic_roundtrip() {
+    let request = Request {
+        path: "/orders".to_string(),
+        retries: 3,
+        token: Some("secret".to_string()),
+        traced: false,
+        headers: vec![("accept".to_string(), "*/*".to_string())],
+        method: Method::Post(42),
+    };
+
+    let mut value = Value::from_typed(&request).unwrap();
+    assert_eq!(value.field(1), Some(&Value::U64(3)));
+    assert_eq!(
+        value.field(5),
+        Some(&Value::Enum(1, Some(Box::new(Value::U64(42)))))
+    );
+
+    // Middleware strips credentials and enables tracing
+    *value.field_mut(2).unwrap() = Value::Option(None);
+    *value.field_mut(3).unwrap() = Value::Bool(true);
+
+    let modified: Request = value.to_typed().unwrap();
+    assert_eq!(
+        modified,
+        Request {
+            token: None,
+            traced: true,
+            ..request.clone()
+        }
+    );
+
+    // Encoded form matches the derived one
+    let mut expected = Vec::new();
+    modified.lbs_write(&mut expected).unwrap();
+    let mut actual = Vec::new();
+    value.write(&mut actual, &Request::lbs_schema()).unwrap();
+    assert_eq!(actual, expected);
+
+    // Values out of range of the declared type are rejected
+    *value.field_mut(1).unwrap() = Value::U64(1000);
+    assert!(value.to_typed::<Request>().is_err());
+}
+
+#[test]
+fn dynamic_custom_kinds() {
+    let device = Device {
+        name: "gateway".to_string(),
+        mac: Some(macaddr::MacAddr6::new(2, 0, 0, 0, 0, 1).into()),
+        uptime: 300,
+    };
+
+    // Neither opaque types nor codec fields are known natively
+    assert!(Value::from_typed(&device).is_err());
+
+    let mut kinds = Kinds::new();
+    kinds.register::<macaddr::MacAddr>("MacAddr").register_with(
+        "varint",
+        |r| {
+            let mut buf = Vec::new();
+            lbs::varint::write_u64(&mut buf, lbs::varint::read_u64(&mut &mut *r)?)?;
+            Ok(buf)
+        },
+        |bytes, w| {
+            lbs::varint::read_u64(&mut &bytes[..])?;
+            Ok(w.write_all(bytes)?)
+        },
+    );
+
+    let mut encoded = Vec::new();
+    device.lbs_write(&mut encoded).unwrap();
+    let schema = Device::lbs_schema();
+    let mut value = Value::read_with(&mut encoded.as_slice(), &schema, &kinds).unwrap();
+
+    assert_eq!(
+        value.field(1),
+        Some(&Value::Option(Some(Box::new(Value::Custom(
+            "MacAddr".to_string(),
+            vec![1, 2, 0, 0, 0, 0, 1]
+        )))))
+    );
+    assert_eq!(
+        value.field(2),
+        Some(&Value::Custom("varint".to_string(), vec![0xac, 0x02]))
+    );
+
+    // Custom values are written back as they were read
+    *value.field_mut(0).unwrap() = Value::String("router".to_string());
+    let mut written = Vec::new();
+    value.write_with(&mut written, &schema, &kinds).unwrap();
+    let decoded = Device::lbs_read(&mut written.as_slice()).unwrap();
+    assert_eq!(
+        decoded,
+        Device {
+            name: "router".to_string(),
+            ..device
+        }
+    );
+
+    // Bytes are validated on write, and kinds must match
+    *value.field_mut(2).unwrap() = Value::Custom("varint".to_string(), vec![0x80]);
+    assert!(value.write_with(&mut Vec::new(), &schema, &kinds).is_err());
+    *value.field_mut(2).unwrap() = Value::Custom("MacAddr".to_string(), vec![0x01]);
+    assert!(value.write_with(&mut Vec::new(), &schema, &kinds).is_err());
+}
+
+#[test]
+fn dynamic_json() {
+    let request = Request {
+        path: "/a\"b".to_string(),
+        retries: 2,
+        token: None,
+        traced: true,
+        headers: vec![("k".to_string(), "v".to_string())],
+        method: Method::Post(7),
+    };
+    let bytes = lbs::to_vec(&request).unwrap();
+    let value = Value::read(&mut bytes.as_slice(), &Request::lbs_schema()).unwrap();
+
+    assert_eq!(
+        value.to_string(),
+        r#"{"0":"/a\"b","1":2,"3":true,"4":[["k","v"]],"5":{"1":7}}"#
+    );
+    assert_eq!(
+        format!("{:#}", value),
+        r#"{
+  "0": "/a\"b",
+  "1": 2,
+  "3": true,
+  "4": [
+    [
+      "k",
+      "v"
+    ]
+  ],
+  "5": {
+    "1": 7
+  }
+}"#
+    );
+
+    let other = Value::List(vec![
+        Value::Enum(0, None),
+        Value::Option(Some(Box::new(Value::F64(f64::NAN)))),
+        Value::Custom("MacAddr".to_string(), vec![0xab, 0x01]),
+        Value::Char('\n'),
+        Value::Struct(Default::default()),
+    ]);
+    assert_eq!(other.to_string(), r#"[0,null,{"MacAddr":"ab01"},"\n",{}]"#);
+}
@@ -0,0 +1,53 @@
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Measurement {
+    #[lbs(id(0))]
+    value: f64,
+    #[lbs(id(1))]
+    samples: Vec<f32>,
+    #[lbs(id(2))]
+    labels: BTreeMap<String, Option<f64>>,
+    #[lbs(id(3))]
+    nested: Option<Box<Measurement>>,
+}
+
+fn measurement(nan: f64, zero: f32) -> Measurement {
+    Measurement {
+        value: nan,
+        samples: vec![1.0, zero, nan as f32],
+        labels: [("a".to_string(), Some(nan))].into(),
+        nested: Some(Box::new(Measurement {
+            value: nan,
+            ..Default::default()
+        })),
+    }
+}
+
+#[test]
+fn encoder_canonical_floats() {
+    let a = measurement(f64::NAN, 0.0);
+    let b = measurement(-f64::from_bits(f64::NAN.to_bits() | 1), -0.0);
+
+    let mut e = Encoder::new();
+    e.canonicalize_nan(true).canonicalize_zero(true);
+
+    let mut buf_a = Vec::new();
+    e.encode(&a, &mut buf_a).unwrap();
+    let mut buf_b = Vec::new();
+    e.encode(&b, &mut buf_b).unwrap();
+    assert_eq!(buf_a, buf_b);
+
+    // Default encoding keeps bytes as is
+    let mut buf_a = Vec::new();
+    a.lbs_write(&mut buf_a).unwrap();
+    let mut buf_b = Vec::new();
+    b.lbs_write(&mut buf_b).unwrap();
+    assert_ne!(buf_a, buf_b);
+
+    let decoded = Measurement::lbs_read(&mut buf_b.as_slice()).unwrap();
+    assert_eq!(decoded.samples[1].to_bits(), (-0.0f32).to_bits());
+}
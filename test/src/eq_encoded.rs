@@ -0,0 +1,94 @@
+use lbs::error::LBSError;
+use lbs::LBSWrite;
+use std::io::Write;
+
+#[derive(LBSWrite, Debug, Clone)]
+struct Document {
+    #[lbs(id(0))]
+    title: String,
+    #[lbs(id(1))]
+    pages: Vec<Vec<u8>>,
+    #[lbs(id(2))]
+    score: f32,
+}
+
+fn document() -> Document {
+    Document {
+        title: "report".to_string(),
+        pages: vec![vec![1; 1000]; 10],
+        score: 0.5,
+    }
+}
+
+/// Value, which counts its writes and fails on the given one.
+struct Failing {
+    fail_at: usize,
+}
+
+impl LBSWrite for Failing {
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        for i in 0..3u8 {
+            if usize::from(i) == self.fail_at {
+                return Err(LBSError::OutOfRange("Failing"));
+            }
+            w.write_all(&[i])?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn eq_encoded() {
+    assert!(lbs::eq_encoded(&document(), &document()).unwrap());
+
+    let mut other = document();
+    other.pages[0][0] = 2;
+    assert!(!lbs::eq_encoded(&document(), &other).unwrap());
+
+    // Longer and shorter encodings
+    let mut longer = document();
+    longer.pages.push(Vec::new());
+    assert!(!lbs::eq_encoded(&document(), &longer).unwrap());
+    assert!(!lbs::eq_encoded(&longer, &document()).unwrap());
+
+    // Equality is wire-level: different types with the same encoding are equal
+    assert!(lbs::eq_encoded(&vec![1u8, 2], &[1u8, 2]).unwrap());
+    assert!(lbs::eq_encoded("ab", &"ab".to_string()).unwrap());
+
+    // Unlike PartialEq, NaNs are equal and signed zeros are not
+    let mut nan = document();
+    nan.score = f32::NAN;
+    assert!(lbs::eq_encoded(&nan, &nan.clone()).unwrap());
+    let mut negative = document();
+    negative.score = -0.0;
+    let mut positive = document();
+    positive.score = 0.0;
+    assert!(!lbs::eq_encoded(&negative, &positive).unwrap());
+}
+
+#[test]
+fn eq_encoded_errors() {
+    let bytes = [0u8, 1, 2];
+    let equal = WriteBytes(&bytes);
+
+    // Errors of encoding are returned, unless encodings already differ
+    assert!(matches!(
+        lbs::eq_encoded(&Failing { fail_at: 1 }, &equal),
+        Err(LBSError::OutOfRange("Failing"))
+    ));
+    assert!(matches!(
+        lbs::eq_encoded(&equal, &Failing { fail_at: 2 }),
+        Err(LBSError::OutOfRange("Failing"))
+    ));
+    assert!(!lbs::eq_encoded(&WriteBytes(&[1]), &Failing { fail_at: 2 }).unwrap());
+    assert!(lbs::eq_encoded(&equal, &Failing { fail_at: 3 }).unwrap());
+}
+
+/// Value, which is written as given bytes.
+struct WriteBytes<'a>(&'a [u8]);
+
+impl LBSWrite for WriteBytes<'_> {
+    fn lbs_write<W: Write>(&self, w: &mut W) -> Result<(), LBSError> {
+        Ok(w.write_all(self.0)?)
+    }
+}
@@ -0,0 +1,92 @@
+use lbs::read::read_field_count;
+use lbs::read::read_field_id;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+struct Declared {
+    #[lbs(id(2))]
+    header: String,
+    #[lbs(id(0))]
+    body: Vec<u8>,
+    #[lbs(id(1))]
+    trailer: u32,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+#[lbs(order(id))]
+struct Sorted {
+    #[lbs(id(2))]
+    header: String,
+    #[lbs(id(0))]
+    body: Vec<u8>,
+    #[lbs(id(1))]
+    trailer: u32,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+#[lbs(order(declaration))]
+enum Chunk {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Data {
+        #[lbs(id(9))]
+        header: String,
+        #[lbs(id(3))]
+        body: Vec<u8>,
+    },
+}
+
+/// Ids of struct fields in the order they were written.
+fn written_ids(mut r: &[u8]) -> Vec<u16> {
+    let count = read_field_count(&mut r).unwrap();
+
+    (0..count)
+        .map(|_| {
+            let id = read_field_id(&mut r).unwrap();
+            match id {
+                0 | 3 => drop(Vec::<u8>::lbs_read(&mut r).unwrap()),
+                1 => drop(u32::lbs_read(&mut r).unwrap()),
+                _ => drop(String::lbs_read(&mut r).unwrap()),
+            }
+            id
+        })
+        .collect()
+}
+
+#[test]
+fn field_order() {
+    let declared = Declared {
+        header: "h".to_string(),
+        body: vec![1, 2],
+        trailer: 3,
+    };
+    let bytes = lbs::to_vec(&declared).unwrap();
+    assert_eq!(written_ids(&bytes), vec![2, 0, 1]);
+
+    let sorted = Sorted {
+        header: "h".to_string(),
+        body: vec![1, 2],
+        trailer: 3,
+    };
+    let sorted_bytes = lbs::to_vec(&sorted).unwrap();
+    assert_eq!(written_ids(&sorted_bytes), vec![0, 1, 2]);
+
+    // Order doesn't matter to readers
+    assert_eq!(
+        lbs::from_slice::<Declared>(&sorted_bytes).unwrap(),
+        declared
+    );
+    assert_eq!(lbs::from_slice::<Sorted>(&bytes).unwrap(), sorted);
+
+    // Payload of a struct-like variant follows the variant id
+    let chunk = Chunk::Data {
+        header: "h".to_string(),
+        body: vec![1],
+    };
+    let bytes = lbs::to_vec(&chunk).unwrap();
+    assert_eq!(written_ids(&bytes[2..]), vec![9, 3]);
+    assert_eq!(lbs::from_slice::<Chunk>(&bytes).unwrap(), chunk);
+}
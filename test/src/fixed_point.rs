@@ -0,0 +1,70 @@
+use lbs::error::LBSError;
+use lbs::layout::layout;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug)]
+struct Quote {
+    #[lbs(id(0), fixed_point(100))]
+    price: f64,
+    #[lbs(id(1), fixed_point(1000000))]
+    latitude: f32,
+    #[lbs(id(2), fixed_point(100))]
+    discount: Option<f64>,
+    #[lbs(id(3), fixed_point(100))]
+    history: Vec<f64>,
+}
+
+#[test]
+fn fixed_point() {
+    let quote = Quote {
+        price: 19.99,
+        latitude: 55.755_825,
+        discount: Some(-0.5),
+        history: vec![0.0, 20.01, 1e9],
+    };
+
+    let mut buf = Vec::new();
+    quote.lbs_write(&mut buf).unwrap();
+    let decoded = Quote::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(decoded, quote);
+
+    // Decoded values encode into the same bytes
+    let mut again = Vec::new();
+    decoded.lbs_write(&mut again).unwrap();
+    assert_eq!(again, buf);
+
+    // Price of 1999 cents takes 2 bytes as varint instead of 8
+    let mut price = Vec::new();
+    lbs::fixed_point::FixedPoint::<100>::write(&19.99f64, &mut price, &mut Default::default())
+        .unwrap();
+    assert_eq!(price.len(), 2);
+
+    // Values are rounded to scale
+    let mut rounded = Vec::new();
+    lbs::fixed_point::FixedPoint::<100>::write(&0.123f64, &mut rounded, &mut Default::default())
+        .unwrap();
+    let value: f64 =
+        lbs::fixed_point::FixedPoint::<100>::read(&mut rounded.as_slice(), &mut Default::default())
+            .unwrap();
+    assert_eq!(value, 0.12);
+
+    // Values out of range are rejected
+    for value in [f64::NAN, f64::INFINITY, 1e20] {
+        let quote = Quote {
+            price: value,
+            latitude: 0.0,
+            discount: None,
+            history: vec![],
+        };
+
+        assert!(matches!(
+            quote.lbs_write(&mut Vec::new()).unwrap_err().root_cause(),
+            LBSError::FixedPointOutOfRange(_, 100)
+        ));
+    }
+
+    assert!(layout::<Quote>()
+        .to_string()
+        .contains("#0 price: f64 as fixed_point(100) [variable]"));
+}
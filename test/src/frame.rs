@@ -0,0 +1,107 @@
+use lbs::error::LBSError;
+use lbs::frame::read_frame;
+use lbs::frame::read_frame_with_limit;
+use lbs::frame::write_frame;
+use lbs::frame::Framing;
+use lbs::frame::FRAME_HEADER_LEN;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Message {
+    #[lbs(id(0))]
+    payload: Vec<u8>,
+}
+
+#[test]
+fn frame_limit() {
+    let original = Message {
+        payload: vec![1; 100],
+    };
+
+    let mut buf = Vec::new();
+    write_frame(&mut buf, &original).unwrap();
+    write_frame(&mut buf, &original).unwrap();
+
+    let mut r = buf.as_slice();
+    assert_eq!(read_frame::<Message, _>(&mut r).unwrap(), original);
+    assert_eq!(
+        read_frame_with_limit::<Message, _>(&mut r, 108).unwrap(),
+        original
+    );
+
+    let err = read_frame_with_limit::<Message, _>(&mut buf.as_slice(), 100).unwrap_err();
+    assert!(matches!(
+        err,
+        LBSError::FrameTooLarge {
+            len: 108,
+            max_len: 100,
+        }
+    ));
+
+    // Corrupted length prefix
+    let corrupted = [0xff, 0xff, 0xff, 0xff, 0];
+    let err = read_frame::<Message, _>(&mut corrupted.as_slice()).unwrap_err();
+    assert!(matches!(err, LBSError::FrameTooLarge { .. }));
+}
+
+#[test]
+fn frame_header() {
+    let original = Message {
+        payload: vec![7; 10],
+    };
+
+    let plain = Framing::new();
+    let mut checked = Framing::new();
+    checked.checksum(true);
+
+    let mut buf = Vec::new();
+    assert_eq!(
+        plain.write(&mut buf, &original).unwrap(),
+        FRAME_HEADER_LEN + 18
+    );
+    assert_eq!(
+        checked.write(&mut buf, &original).unwrap(),
+        FRAME_HEADER_LEN + 22
+    );
+    assert_eq!(&buf[..4], b"LB\x01\x00");
+
+    // Checksum is verified whenever present
+    let mut r = buf.as_slice();
+    assert_eq!(plain.read::<Message, _>(&mut r).unwrap(), original);
+    assert_eq!(plain.read::<Message, _>(&mut r).unwrap(), original);
+    assert!(r.is_empty());
+
+    let mut corrupted = Vec::new();
+    checked.write(&mut corrupted, &original).unwrap();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        checked.read::<Message, _>(&mut corrupted.as_slice()),
+        Err(LBSError::ChecksumMismatch)
+    ));
+
+    let mut future = buf.clone();
+    future[2] = 2;
+    assert!(matches!(
+        plain.read::<Message, _>(&mut future.as_slice()),
+        Err(LBSError::UnsupportedVersion(2))
+    ));
+
+    // Frames without header aren't mistaken for ones with it
+    let mut headless = Vec::new();
+    write_frame(&mut headless, &original).unwrap();
+    assert!(matches!(
+        plain.read::<Message, _>(&mut headless.as_slice()),
+        Err(LBSError::Parsing(_))
+    ));
+
+    let mut small = Framing::new();
+    small.max_len(17);
+    assert!(matches!(
+        small.read::<Message, _>(&mut buf.as_slice()),
+        Err(LBSError::FrameTooLarge {
+            len: 18,
+            max_len: 17,
+        })
+    ));
+}
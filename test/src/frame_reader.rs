@@ -0,0 +1,73 @@
+use lbs::error::LBSError;
+use lbs::frame::write_frame;
+use lbs::tokio::FrameReader;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+struct Event {
+    #[lbs(id(0))]
+    seq: u64,
+    #[lbs(id(1))]
+    payload: String,
+}
+
+fn event(seq: u64) -> Event {
+    Event {
+        seq,
+        payload: "x".repeat(100),
+    }
+}
+
+fn frames(events: &[Event]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for e in events {
+        write_frame(&mut buf, e).unwrap();
+    }
+    buf
+}
+
+#[tokio::test]
+async fn frame_reader_survives_cancellation() {
+    let (mut tx, rx) = tokio::io::duplex(1024);
+    let mut reader = FrameReader::new(rx);
+    let bytes = frames(&[event(1), event(2)]);
+
+    // First frame arrives partially, and reading is cancelled by timeout
+    tx.write_all(&bytes[..50]).await.unwrap();
+    let cancelled =
+        tokio::time::timeout(Duration::from_millis(20), reader.read_frame::<Event>()).await;
+    assert!(cancelled.is_err());
+    assert_eq!(reader.buffered().len(), 50);
+
+    tx.write_all(&bytes[50..]).await.unwrap();
+    drop(tx);
+
+    assert_eq!(reader.read_frame().await.unwrap(), Some(event(1)));
+    assert_eq!(reader.read_frame().await.unwrap(), Some(event(2)));
+    assert_eq!(reader.read_frame::<Event>().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn frame_reader_truncated() {
+    let bytes = frames(&[event(1)]);
+    let mut reader = FrameReader::new(&bytes[..bytes.len() - 1]);
+
+    assert!(matches!(
+        reader.read_frame::<Event>().await,
+        Err(LBSError::Io(_))
+    ));
+}
+
+#[tokio::test]
+async fn frame_reader_limit() {
+    let bytes = frames(&[event(1)]);
+    let mut reader = FrameReader::with_limit(bytes.as_slice(), 16);
+
+    assert!(matches!(
+        reader.read_frame::<Event>().await,
+        Err(LBSError::FrameTooLarge { max_len: 16, .. })
+    ));
+}
@@ -0,0 +1,79 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+use lbs::error::LBSError;
+use lbs::from_bytes;
+use lbs::to_bytes;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Request {
+    #[lbs(id(0))]
+    path: ByteString,
+    #[lbs(id(1))]
+    headers: Vec<(ByteString, ByteString)>,
+    #[lbs(id(2))]
+    body: Bytes,
+    #[lbs(id(3))]
+    trailer: Option<Bytes>,
+}
+
+fn request() -> Request {
+    Request {
+        path: ByteString::from_static("/index.html"),
+        headers: vec![(
+            ByteString::from_static("host"),
+            ByteString::from_static("example.com"),
+        )],
+        body: Bytes::from_static(b"hello"),
+        trailer: Some(Bytes::new()),
+    }
+}
+
+/// Whether `part` points into `whole`.
+fn is_slice_of(part: &[u8], whole: &[u8]) -> bool {
+    let range = whole.as_ptr_range();
+    part.is_empty() || range.contains(&part.as_ptr())
+}
+
+#[test]
+fn from_bytes_shares_buffer() {
+    let encoded = to_bytes(&request()).unwrap();
+    let decoded: Request = from_bytes(&encoded).unwrap();
+
+    assert_eq!(decoded, request());
+    assert!(is_slice_of(decoded.path.as_bytes(), &encoded));
+    assert!(is_slice_of(decoded.headers[0].1.as_bytes(), &encoded));
+    assert!(is_slice_of(&decoded.body, &encoded));
+}
+
+#[test]
+fn from_bytes_trailing() {
+    let mut encoded = Vec::new();
+    request().lbs_write(&mut encoded).unwrap();
+    encoded.push(0);
+
+    assert!(matches!(
+        from_bytes::<Request>(&Bytes::from(encoded)),
+        Err(LBSError::TrailingBytes(1))
+    ));
+}
+
+#[test]
+fn bytes_from_other_readers() {
+    let mut encoded = Vec::new();
+    request().lbs_write(&mut encoded).unwrap();
+
+    let decoded = Request::lbs_read(&mut encoded.as_slice()).unwrap();
+    assert_eq!(decoded, request());
+    assert!(!is_slice_of(&decoded.body, &encoded));
+}
+
+#[test]
+fn bytestring_validates_utf8() {
+    let encoded = to_bytes(&Bytes::from_static(&[0xff])).unwrap();
+    assert!(matches!(
+        from_bytes::<ByteString>(&encoded),
+        Err(LBSError::Parsing(_))
+    ));
+}
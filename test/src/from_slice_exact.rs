@@ -0,0 +1,28 @@
+use lbs::error::LBSError;
+use lbs::read::from_slice_exact;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Message {
+    #[lbs(id(0))]
+    f0: u64,
+    #[lbs(id(1))]
+    f1: Option<u64>,
+}
+
+#[test]
+fn from_slice_exact_trailing_bytes() {
+    let msg = Message { f0: 1, f1: Some(2) };
+    let mut buf = Vec::with_capacity(128);
+    msg.lbs_write(&mut buf).unwrap();
+
+    let decoded: Message = from_slice_exact(&buf).unwrap();
+    assert_eq!(decoded, msg);
+
+    buf.extend_from_slice(&[0, 0]);
+    assert!(matches!(
+        from_slice_exact::<Message>(&buf),
+        Err(LBSError::TrailingBytes(2))
+    ));
+}
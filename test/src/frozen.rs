@@ -0,0 +1,48 @@
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(version(1), frozen(1 = 0x95e94ed008c4aadc))]
+struct ArchiveV1 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    title: String,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(version(2), frozen(1 = 0x95e94ed008c4aadc, 2 = 0x25ea638ff00e8a0a))]
+struct Archive {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    title: String,
+    #[lbs(id(2), since(2))]
+    tags: Vec<String>,
+    #[lbs(skip)]
+    cached: bool,
+}
+
+#[test]
+fn frozen_versions_compatible() {
+    let v1 = ArchiveV1 {
+        id: 1,
+        title: "title".to_string(),
+    };
+
+    let mut buf = Vec::new();
+    v1.lbs_write(&mut buf).unwrap();
+
+    let mut d = Decoder::new();
+    d.for_version(1);
+
+    assert_eq!(
+        d.decode::<Archive, _>(&mut buf.as_slice()).unwrap(),
+        Archive {
+            id: 1,
+            title: "title".to_string(),
+            ..Default::default()
+        }
+    );
+}
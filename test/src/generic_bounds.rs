@@ -0,0 +1,43 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::marker::PhantomData;
+
+/// Not serializable, only used as a tag.
+#[derive(Debug, Default, PartialEq)]
+struct Meters;
+
+/// Handle with a typed tag, which doesn't take part in serialization.
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Debug, Default, PartialEq)]
+struct Handle<Unit, T: Default, C> {
+    #[lbs(id(0))]
+    value: T,
+    #[lbs(id(1), skip)]
+    unit: PhantomData<fn() -> Unit>,
+    #[lbs(id(2), skip)]
+    cache: Option<C>,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Default, PartialEq)]
+enum Reading<T: Default> {
+    #[default]
+    #[lbs(id(0))]
+    None,
+    #[lbs(id(1))]
+    Value(Handle<Meters, T, Vec<Meters>>),
+}
+
+#[test]
+fn generic_bounds() {
+    let reading = Reading::<u32>::Value(Handle {
+        value: 42,
+        unit: PhantomData,
+        cache: None,
+    });
+
+    let mut buf = Vec::new();
+    reading.lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        Reading::<u32>::lbs_read(&mut buf.as_slice()).unwrap(),
+        reading
+    );
+}
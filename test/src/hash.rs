@@ -0,0 +1,76 @@
+use lbs::hash::stable_hash;
+use lbs::hash::stable_hash_with;
+use lbs::hash::Fnv1a;
+use lbs::schema::LBSSchema;
+use lbs::LBSWrite;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::Hasher;
+
+#[derive(LBSWrite, lbs::LBSSchema, Debug, Clone, PartialEq)]
+struct Key {
+    #[lbs(id(0))]
+    tenant: String,
+    #[lbs(id(1))]
+    shard: u32,
+    #[lbs(id(2))]
+    weight: f64,
+    #[lbs(id(3))]
+    tags: BTreeMap<String, u8>,
+}
+
+fn key() -> Key {
+    Key {
+        tenant: "acme".to_string(),
+        shard: 7,
+        weight: 0.5,
+        tags: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+    }
+}
+
+#[test]
+fn hash_stable() {
+    // Hash is that of the encoding
+    let hash = stable_hash::<_, Fnv1a>(&key()).unwrap();
+    let mut hasher = Fnv1a::new();
+    hasher.write(&lbs::to_vec(&key()).unwrap());
+    assert_eq!(hash, hasher.finish());
+
+    // Same as schema fingerprint
+    let schema = Key::lbs_schema();
+    assert_eq!(
+        stable_hash::<_, Fnv1a>(&schema).unwrap(),
+        schema.fingerprint()
+    );
+
+    // Known FNV-1a value of the encoding of 1u8
+    assert_eq!(stable_hash::<_, Fnv1a>(&1u8).unwrap(), 0xaf63bc4c8601b62c);
+
+    let mut other = key();
+    other.shard = 8;
+    assert_ne!(stable_hash::<_, Fnv1a>(&other).unwrap(), hash);
+}
+
+#[test]
+fn hash_canonical() {
+    // Zero and NaN are canonicalized
+    let mut positive = key();
+    positive.weight = 0.0;
+    let mut negative = key();
+    negative.weight = -0.0;
+    assert_eq!(
+        stable_hash::<_, Fnv1a>(&positive).unwrap(),
+        stable_hash::<_, Fnv1a>(&negative).unwrap()
+    );
+
+    assert_eq!(
+        stable_hash::<_, Fnv1a>(&f64::NAN).unwrap(),
+        stable_hash::<_, Fnv1a>(&-f64::from_bits(f64::NAN.to_bits() | 1)).unwrap()
+    );
+
+    // Any hasher may be used
+    assert_eq!(
+        stable_hash::<_, DefaultHasher>(&positive).unwrap(),
+        stable_hash_with(&negative, DefaultHasher::new()).unwrap()
+    );
+}
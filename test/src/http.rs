@@ -0,0 +1,64 @@
+use http::header;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use http::Uri;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug, Default)]
+struct RequestLog {
+    #[lbs(id(0))]
+    method: Method,
+    #[lbs(id(1))]
+    uri: Uri,
+    #[lbs(id(2))]
+    headers: HeaderMap,
+    #[lbs(id(3))]
+    status: Option<StatusCode>,
+    #[lbs(id(4))]
+    body_len: u64,
+}
+
+#[test]
+fn http() {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::HOST, HeaderValue::from_static("example.com"));
+    headers.append(header::ACCEPT, HeaderValue::from_static("text/html"));
+    headers.append(header::ACCEPT, HeaderValue::from_static("application/json"));
+    headers.insert("x-raw", HeaderValue::from_bytes(b"\xfe\xff").unwrap());
+
+    let log = RequestLog {
+        method: Method::from_bytes(b"PURGE").unwrap(),
+        uri: "https://example.com/items?page=2".parse().unwrap(),
+        headers,
+        status: Some(StatusCode::NOT_FOUND),
+        body_len: 42,
+    };
+
+    let mut buf = Vec::new();
+    log.lbs_write(&mut buf).unwrap();
+    let decoded = RequestLog::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(decoded, log);
+
+    // Repeated headers are kept in order
+    let accept: Vec<_> = decoded.headers.get_all(header::ACCEPT).iter().collect();
+    assert_eq!(accept, ["text/html", "application/json"]);
+
+    // Headers are skipped when left out of projection
+    let projected: RequestLog = lbs::read::project(&mut buf.as_slice(), &[4]).unwrap();
+    assert!(projected.headers.is_empty());
+    assert_eq!(projected.body_len, 42);
+}
+
+#[test]
+fn http_invalid() {
+    let mut buf = Vec::new();
+    99u16.lbs_write(&mut buf).unwrap();
+    assert!(StatusCode::lbs_read(&mut buf.as_slice()).is_err());
+
+    let mut buf = Vec::new();
+    "bad header".lbs_write(&mut buf).unwrap();
+    assert!(http::HeaderName::lbs_read(&mut buf.as_slice()).is_err());
+}
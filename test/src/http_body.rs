@@ -0,0 +1,47 @@
+use bytes::Bytes;
+use http_body::Body;
+use http_body_util::Full;
+use lbs::error::LBSError;
+use lbs::http_body::from_body;
+use lbs::http_body::LBSBody;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Upload {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    data: Vec<u8>,
+}
+
+#[tokio::test]
+async fn http_body() {
+    let upload = Upload {
+        name: "a.bin".to_string(),
+        data: vec![1; 100],
+    };
+
+    let mut bytes = Vec::new();
+    upload.lbs_write(&mut bytes).unwrap();
+    assert_eq!(lbs::write::encoded_len(&upload).unwrap(), bytes.len());
+
+    // Size is known before encoding
+    let body = LBSBody::new(upload.clone()).unwrap();
+    assert_eq!(body.len(), bytes.len());
+    assert_eq!(body.size_hint().exact(), Some(bytes.len() as u64));
+    assert_eq!(from_body::<Upload, _>(body, 1024).await.unwrap(), upload);
+
+    // Size is limited
+    let e = from_body::<Upload, _>(LBSBody::new(upload.clone()).unwrap(), 10)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, LBSError::FrameTooLarge { max_len: 10, .. }));
+
+    // Trailing bytes
+    bytes.push(0);
+    let e = from_body::<Upload, _>(Full::new(Bytes::from(bytes)), 1024)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, LBSError::TrailingBytes(1)));
+}
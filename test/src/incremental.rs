@@ -0,0 +1,54 @@
+use lbs::incremental::IncrementalDecoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::task::Poll;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Packet {
+    #[lbs(id(0))]
+    seq: u32,
+    #[lbs(id(1))]
+    payload: String,
+}
+
+#[test]
+fn incremental() {
+    let packets: Vec<Packet> = (0..3)
+        .map(|seq| Packet {
+            seq,
+            payload: "x".repeat(seq as usize * 10),
+        })
+        .collect();
+
+    let mut bytes = Vec::new();
+    for p in &packets {
+        p.lbs_write(&mut bytes).unwrap();
+    }
+
+    // Bytes arrive in small chunks, which split messages
+    let mut d = IncrementalDecoder::<Packet>::new();
+    let mut decoded = Vec::new();
+
+    for chunk in bytes.chunks(7) {
+        let mut poll = d.feed(chunk);
+        while let Poll::Ready(p) = poll {
+            decoded.push(p.unwrap());
+            poll = d.feed(&[]);
+        }
+    }
+
+    assert_eq!(decoded, packets);
+    assert_eq!(d.buffered(), 0);
+
+    // Several messages in a single chunk
+    let mut d = IncrementalDecoder::<Packet>::new();
+    assert!(matches!(d.feed(&bytes), Poll::Ready(Ok(p)) if p == packets[0]));
+    assert!(matches!(d.feed(&[]), Poll::Ready(Ok(p)) if p == packets[1]));
+    assert!(matches!(d.feed(&[]), Poll::Ready(Ok(p)) if p == packets[2]));
+    assert!(d.feed(&[]).is_pending());
+
+    // Malformed message is discarded
+    assert!(d.feed(&[1, 0, 1, 0, 1, 0]).is_pending());
+    assert!(matches!(d.feed(&[0, 0, 0xff]), Poll::Ready(Err(_))));
+    assert_eq!(d.buffered(), 0);
+}
@@ -0,0 +1,89 @@
+use lbs::indexed::IndexedView;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+#[lbs(indexed)]
+struct Record {
+    #[lbs(id(3))]
+    body: Vec<String>,
+    #[lbs(id(0))]
+    key: u64,
+    #[lbs(id(7))]
+    score: Option<f32>,
+    #[lbs(id(5), utf8)]
+    flag: char,
+    #[lbs(id(9), skip)]
+    cache: u8,
+}
+
+#[derive(LBSWrite, LBSRead, Default, PartialEq, Debug)]
+#[lbs(indexed)]
+struct RecordV2 {
+    #[lbs(id(0))]
+    key: u64,
+    #[lbs(id(7))]
+    score: Option<f32>,
+}
+
+#[test]
+fn indexed() {
+    let record = Record {
+        body: vec!["x".repeat(100), "y".repeat(200)],
+        key: 42,
+        score: Some(0.5),
+        flag: 'ж',
+        cache: 1,
+    };
+
+    let mut buf = Vec::new();
+    record.lbs_write(&mut buf).unwrap();
+    buf.extend_from_slice(&[0xff; 3]);
+
+    // Stream roundtrip
+    let mut r = buf.as_slice();
+    let decoded = Record::lbs_read(&mut r).unwrap();
+    assert_eq!(r.len(), 3);
+    assert_eq!(
+        decoded,
+        Record {
+            cache: 0,
+            ..record.clone()
+        }
+    );
+
+    // Vectored output is the same
+    let mut v = lbs::vectored::VectoredWriter::new();
+    record.lbs_write_vectored(&mut v).unwrap();
+    let mut vectored = Vec::new();
+    v.write_to(&mut vectored).unwrap();
+    assert_eq!(vectored, buf[..buf.len() - 3]);
+
+    // Random access
+    let view = IndexedView::new(&buf).unwrap();
+    assert_eq!(view.len(), 4);
+    assert_eq!(view.encoded_len(), buf.len() - 3);
+    assert_eq!(view.get::<u64>(0).unwrap(), Some(42));
+    assert_eq!(view.get::<Option<f32>>(7).unwrap(), Some(Some(0.5)));
+    assert_eq!(view.get::<u64>(9).unwrap(), None);
+    assert!(view.get::<u8>(0).is_err());
+    assert_eq!(view.raw(5).unwrap().unwrap(), "ж".as_bytes());
+
+    // Unknown fields are skipped by length
+    let v2 = RecordV2::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(
+        v2,
+        RecordV2 {
+            key: 42,
+            score: Some(0.5)
+        }
+    );
+
+    // Projection and skipping
+    let projected: Record = lbs::read::project(&mut buf.as_slice(), &[0]).unwrap();
+    assert_eq!(projected.key, 42);
+    assert!(projected.body.is_empty());
+    let mut r = buf.as_slice();
+    Record::lbs_skip(&mut r, &mut lbs::Decoder::new()).unwrap();
+    assert_eq!(r.len(), 3);
+}
@@ -0,0 +1,57 @@
+use lbs::inspect::Annotated;
+use lbs::LBSRead;
+use lbs::LBSSchema;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+enum Role {
+    #[default]
+    #[lbs(id(0))]
+    User,
+    #[lbs(id(1))]
+    Admin(u8),
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct Account {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    email: Option<String>,
+    #[lbs(id(3))]
+    roles: Vec<Role>,
+}
+
+#[test]
+fn inspect() {
+    let account = Account {
+        id: 7,
+        name: "alice".to_string(),
+        email: None,
+        roles: vec![Role::User, Role::Admin(2)],
+    };
+
+    let mut buf = Vec::new();
+    account.lbs_write(&mut buf).unwrap();
+
+    let expected = "\
+000000  03 00                    Account, 3 fields
+000002  00 00                      [0] id
+000004  07 00 00 00                  7
+000008  01 00                      [1] name
+00000a  05 00 00 00 61 6c 69 63      \"alice\"
+000012  65
+000013  03 00                      [3] roles
+000015  02 00 00 00                  2 elements
+000019  00 00                          [0] Role::User (0)
+00001b  01 00                          [1] Role::Admin (1)
+00001d  02                               2
+";
+    assert_eq!(Annotated::<Account>::new(&buf).to_string(), expected);
+
+    // Bytes after the first malformed one are dumped
+    let annotated = format!("{:?}", Annotated::<Account>::new(&buf[..buf.len() - 4]));
+    assert!(annotated.ends_with("000019  00                       <truncated>\n"));
+}
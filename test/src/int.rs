@@ -0,0 +1,80 @@
+use lbs::error::LBSError;
+use lbs::int::I24;
+use lbs::int::I48;
+use lbs::int::U24;
+use lbs::int::U48;
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Default, Debug, PartialEq)]
+struct Sample {
+    #[lbs(id(0))]
+    level: I24,
+    #[lbs(id(1))]
+    offset: U48,
+    #[lbs(id(2))]
+    peaks: Vec<U24>,
+}
+
+fn sample() -> Sample {
+    Sample {
+        level: I24::new(-3).unwrap(),
+        offset: U48::MAX,
+        peaks: vec![U24::MIN, U24::new(0x12_3456).unwrap()],
+    }
+}
+
+#[test]
+fn int_encoding() {
+    assert_eq!(
+        lbs::to_vec(&U24::new(0x12_3456).unwrap()).unwrap(),
+        [0x56, 0x34, 0x12]
+    );
+    assert_eq!(lbs::to_vec(&I24::MIN).unwrap(), [0, 0, 0x80]);
+    assert_eq!(lbs::to_vec(&I48::new(-1).unwrap()).unwrap(), [0xff; 6]);
+    assert_eq!(lbs::to_vec(&U48::MAX).unwrap(), [0xff; 6]);
+    assert_eq!(
+        lbs::from_slice::<I24>(&[0xfe, 0xff, 0xff]).unwrap().get(),
+        -2
+    );
+    assert_eq!(
+        lbs::from_slice::<I48>(&[0xff; 6]).unwrap(),
+        I48::new(-1).unwrap()
+    );
+    assert_eq!(U48::LBS_MAX_SIZE, 6);
+
+    let value = sample();
+    let bytes = lbs::to_vec(&value).unwrap();
+    assert_eq!(bytes.len(), value.lbs_size_hint());
+    assert_eq!(lbs::from_slice::<Sample>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn int_range() {
+    assert_eq!(U24::MAX.get(), 0xff_ffff);
+    assert_eq!(I24::MIN.get(), -0x80_0000);
+    assert_eq!(I48::MAX.get(), (1 << 47) - 1);
+    assert!(U24::new(1 << 24).is_none());
+    assert!(I24::new(1 << 23).is_none());
+    assert!(I24::new(-(1 << 23) - 1).is_none());
+    assert!(matches!(
+        U48::try_from(u64::MAX),
+        Err(LBSError::OutOfRange("U48"))
+    ));
+    assert_eq!(u32::from(U24::try_from(7).unwrap()), 7);
+    assert_eq!(U24::wrapping(0x1ff_ffff), U24::MAX);
+    assert_eq!(I24::wrapping(0xff_ffff), I24::new(-1).unwrap());
+    assert_eq!(I48::from_le_bytes(I48::MIN.to_le_bytes()), I48::MIN);
+    assert_eq!(I24::new(-5).unwrap().to_string(), "-5");
+
+    assert!(lbs::from_slice::<U24>(&[1, 2]).unwrap_err().is_eof());
+}
+
+#[tokio::test]
+async fn int_async() {
+    let bytes = lbs::to_vec(&sample()).unwrap();
+    let read: Sample = lbs::tokio::read::read(&mut bytes.as_slice()).await.unwrap();
+    assert_eq!(read, sample());
+}
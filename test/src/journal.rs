@@ -0,0 +1,110 @@
+use lbs::error::LBSError;
+use lbs::journal::Reader;
+use lbs::journal::Writer;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Entry {
+    #[lbs(id(0))]
+    seq: u64,
+    #[lbs(id(1))]
+    key: String,
+}
+
+fn entry(seq: u64) -> Entry {
+    Entry {
+        seq,
+        key: format!("key{seq}"),
+    }
+}
+
+#[test]
+fn journal_recovery() {
+    let path = std::env::temp_dir().join(format!("lbs-journal-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut w = Writer::open(&path).unwrap();
+    w.append(&entry(0)).unwrap();
+    w.append(&entry(1)).unwrap();
+    w.sync().unwrap();
+    drop(w);
+
+    // Simulate crash in the middle of a write
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+    drop(file);
+
+    let mut r = Reader::open(&path).unwrap();
+    assert_eq!(r.read::<Entry>().unwrap(), Some(entry(0)));
+    assert_eq!(r.read::<Entry>().unwrap(), Some(entry(1)));
+    assert_eq!(r.read::<Entry>().unwrap(), None);
+
+    // Torn record is truncated on open
+    let mut w = Writer::open(&path).unwrap();
+    w.append(&entry(2)).unwrap();
+    w.flush().unwrap();
+    drop(w);
+
+    let mut r = Reader::open(&path).unwrap();
+    for seq in 0..3 {
+        assert_eq!(r.read::<Entry>().unwrap(), Some(entry(seq)));
+    }
+    assert_eq!(r.read::<Entry>().unwrap(), None);
+
+    // Corrupted record
+    let mut bytes = std::fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&path, bytes).unwrap();
+
+    let mut r = Reader::open(&path).unwrap();
+    r.read::<Entry>().unwrap();
+    r.read::<Entry>().unwrap();
+    assert!(matches!(r.read::<Entry>(), Err(LBSError::ChecksumMismatch)));
+
+    // Corrupted final record is truncated on open
+    let mut w = Writer::open(&path).unwrap();
+    w.append(&entry(3)).unwrap();
+    w.flush().unwrap();
+    drop(w);
+
+    let mut r = Reader::open(&path).unwrap();
+    for seq in [0, 1, 3] {
+        assert_eq!(r.read::<Entry>().unwrap(), Some(entry(seq)));
+    }
+    assert_eq!(r.read::<Entry>().unwrap(), None);
+
+    // Corrupted record followed by valid ones is kept: payload of the first one
+    let valid = std::fs::read(&path).unwrap();
+    let mut bytes = valid.clone();
+    bytes[14] ^= 0xff;
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        Writer::open(&path),
+        Err(LBSError::ChecksumMismatch)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+    // Corrupted length of a middle record, which would exceed the file, isn't taken for
+    // a torn record
+    let second = 12 + u32::from_le_bytes(valid[..4].try_into().unwrap()) as usize;
+    let mut bytes = valid.clone();
+    bytes[second + 3] ^= 0x80;
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        Writer::open(&path),
+        Err(LBSError::ChecksumMismatch)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+    let mut r = Reader::open(&path).unwrap();
+    assert_eq!(r.read::<Entry>().unwrap(), Some(entry(0)));
+    assert!(matches!(r.read::<Entry>(), Err(LBSError::ChecksumMismatch)));
+
+    std::fs::remove_file(&path).unwrap();
+}
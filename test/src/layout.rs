@@ -0,0 +1,53 @@
+use lbs::layout::layout;
+
+#[derive(lbs::LBSSchema)]
+#[lbs(pack_bools, compact)]
+#[allow(dead_code)]
+struct Order {
+    #[lbs(id(2))]
+    total: u64,
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(1))]
+    items: Vec<(String, u16)>,
+    #[lbs(id(3))]
+    paid: bool,
+    #[lbs(id(4))]
+    window: std::ops::Range<u32>,
+    #[lbs(id(5))]
+    status: Status,
+}
+
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+enum Status {
+    #[lbs(id(0))]
+    New,
+    #[lbs(id(1))]
+    Shipped(Option<String>),
+}
+
+#[test]
+fn layout_snapshot() {
+    assert_eq!(
+        layout::<Order>().to_string(),
+        "\
+struct Order (compact header)
+  #0 id: u32 [4 bytes]
+  #1 items: [(String, u16)] [variable]
+  #2 total: u64 [8 bytes]
+  #4 window: Range<u32> [8 bytes]
+  #5 status: Status [variable]
+  #3 paid: bool [bit of #65535]
+"
+    );
+
+    assert_eq!(
+        layout::<Status>().to_string(),
+        "\
+enum Status (regular header)
+  #0 New [0 bytes]
+  #1 Shipped: Option<String> [variable]
+"
+    );
+}
@@ -0,0 +1,64 @@
+use lbs::lazy::Lazy;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default, Debug)]
+struct Envelope {
+    #[lbs(id(0))]
+    route: String,
+    #[lbs(id(1))]
+    payload: Lazy<Payload>,
+    #[lbs(id(2))]
+    trailer: u32,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+struct Payload {
+    #[lbs(id(0))]
+    items: Vec<String>,
+    #[lbs(id(1))]
+    weight: f32,
+}
+
+#[test]
+fn lazy() {
+    let payload = Payload {
+        items: vec!["a".into(), "b".into()],
+        weight: -0.0,
+    };
+
+    let envelope = Envelope {
+        route: "route".into(),
+        payload: Lazy::new(payload.clone()),
+        trailer: 7,
+    };
+
+    let mut buf = Vec::new();
+    envelope.lbs_write(&mut buf).unwrap();
+
+    // Payload is captured, not decoded
+    let mut decoded = Envelope::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(decoded.route, "route");
+    assert_eq!(decoded.trailer, 7);
+    assert!(!decoded.payload.is_decoded());
+
+    // Captured bytes are written back untouched, even with encoder options
+    let mut again = Vec::new();
+    let mut encoder = lbs::Encoder::new();
+    encoder.canonicalize_zero(true);
+    decoded.lbs_write_with(&mut again, &mut encoder).unwrap();
+    assert_eq!(again, buf);
+
+    // Value is decoded on access
+    assert_eq!(decoded.payload.get().unwrap(), &payload);
+    assert!(decoded.payload.is_decoded());
+    assert!(decoded.payload.raw().is_some());
+
+    // Modified value is encoded
+    decoded.payload.get_mut().unwrap().items.push("c".into());
+    assert!(decoded.payload.raw().is_none());
+    let mut modified = Vec::new();
+    decoded.lbs_write(&mut modified).unwrap();
+    let decoded = Envelope::lbs_read(&mut modified.as_slice()).unwrap();
+    assert_eq!(decoded.payload.into_inner().unwrap().items, ["a", "b", "c"]);
+}
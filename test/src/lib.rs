@@ -1,4 +1,10 @@
 #![allow(unused_imports, dead_code)]
+#![allow(
+    clippy::approx_constant,
+    clippy::unit_cmp,
+    clippy::bool_assert_comparison,
+    clippy::collapsible_match
+)]
 
 use bytes::Buf;
 use bytes::BufMut;
@@ -29,6 +35,127 @@ use std::time::SystemTime;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+mod actix_web;
+mod adapters;
+mod allocator;
+mod async_io;
+mod auto_id;
+mod batch;
+mod binary_encodings;
+mod borrowed;
+mod buf_read;
+mod bulk_bytes;
+mod cache;
+mod calendar;
+mod capture;
+mod cas;
+mod chain;
+mod chunk;
+mod color;
+mod columnar;
+mod compact;
+mod compact_duration;
+mod control_flow;
+mod counting;
+mod cow;
+mod crate_path;
+mod crypto;
+mod ctx;
+mod decoder;
+mod delimited;
+mod delta_keys;
+mod deny_gaps;
+mod deprecated_id;
+mod diff;
+mod digest;
+mod droppable;
+mod dyn_io;
+mod dynamic;
+mod encoder;
+mod eq_encoded;
+mod field_order;
+mod fixed_point;
+mod frame;
+mod frame_reader;
+mod from_bytes;
+mod from_slice_exact;
+mod frozen;
+mod generic_bounds;
+mod hash;
+mod http;
+mod http_body;
+mod incremental;
+mod indexed;
+mod inspect;
+mod int;
+mod journal;
+mod layout;
+mod lazy;
+mod lifecycle;
+mod limits;
+mod locks;
+mod macaddr;
+mod max_size;
+mod merge;
+mod metrics;
+mod migrate;
+mod mmap;
+mod oneof;
+mod options;
+mod other_variant;
+mod owned;
+mod pack_bools;
+mod project;
+mod raw_value;
+mod rayon;
+mod rdkafka;
+mod read_as;
+mod read_into;
+mod read_report;
+mod redis;
+mod registry;
+mod relative_time;
+mod remote;
+mod rpc;
+mod secrecy;
+mod seq;
+mod serde_interop;
+mod shape;
+mod shared_strings;
+mod signing;
+mod sink;
+mod size_hint;
+mod skip;
+mod skip_value;
+mod sled;
+mod snapshot;
+mod sparse;
+mod spill;
+mod sqlx;
+mod stats;
+mod std_types;
+mod strict_bools;
+mod system_time;
+mod testing;
+mod to_bytes;
+mod tokio_util;
+mod tonic;
+mod tracing;
+mod tracked;
+mod transcode;
+mod triomphe;
+mod try_from;
+mod tuple_fields;
+mod unit;
+mod unknown_fields;
+mod utf8;
+mod varint;
+mod vectored;
+mod visit;
+mod widen;
+mod write_fields;
+mod zeroize;
+
 #[derive(LBSWrite, LBSRead)]
 struct StructOne<'a> {
     #[lbs(id(0))]
@@ -133,26 +260,21 @@ struct StructOne<'a> {
 
 // Field IDs are assigned implicitly, using their index
 #[derive(LBSWrite, LBSRead, Default, PartialEq, Debug)]
+#[lbs(auto_id)]
 struct StructTwo {
-    #[lbs(id(0))]
     id: Uuid,
-    #[lbs(id(1))]
     name: String,
-    #[lbs(id(2))]
     en: Option<EnumOne>,
 }
 
 // Variant IDs are assigned implicitly, using their index
 #[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+#[lbs(auto_id)]
 enum EnumOne {
     #[default]
-    #[lbs(id(0))]
     One,
-    #[lbs(id(1))]
     Two,
-    #[lbs(id(2))]
     Three(String),
-    #[lbs(id(3))]
     Four(EnumTwo),
 }
 
@@ -0,0 +1,65 @@
+use lbs::error::LBSError;
+use lbs::migrate::read_as;
+use lbs::migrate::write_as;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct User {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1), removed(3))]
+    nickname: String,
+    #[lbs(id(2), since(2))]
+    email: String,
+}
+
+#[test]
+fn lifecycle() {
+    let user = User {
+        id: 1,
+        nickname: "bob".to_string(),
+        email: "bob@example.com".to_string(),
+    };
+
+    // Fields missing in a version are neither written nor required
+    let mut buf = Vec::new();
+    write_as(&mut buf, &user, 1).unwrap();
+    let (decoded, version) = read_as::<User, _>(&mut buf.as_slice()).unwrap();
+    assert_eq!(version, 1);
+    assert_eq!(
+        decoded,
+        User {
+            email: String::new(),
+            ..user.clone()
+        }
+    );
+
+    let mut buf = Vec::new();
+    write_as(&mut buf, &user, 2).unwrap();
+    assert_eq!(read_as::<User, _>(&mut buf.as_slice()).unwrap().0, user);
+
+    let mut buf = Vec::new();
+    write_as(&mut buf, &user, 3).unwrap();
+    assert_eq!(
+        read_as::<User, _>(&mut buf.as_slice()).unwrap().0,
+        User {
+            nickname: String::new(),
+            ..user.clone()
+        }
+    );
+
+    // Without version, values are written and read as of the latest one
+    let mut buf = Vec::new();
+    user.lbs_write(&mut buf).unwrap();
+    let mut latest = Vec::new();
+    write_as(&mut latest, &user, 3).unwrap();
+    assert_eq!(buf, latest[2..]);
+
+    let mut buf = Vec::new();
+    write_as(&mut buf, &user, 1).unwrap();
+    assert!(matches!(
+        User::lbs_read(&mut &buf[2..]),
+        Err(LBSError::WithField(2, _))
+    ));
+}
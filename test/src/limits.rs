@@ -0,0 +1,327 @@
+use lbs::borrow::LBSReadRef;
+use lbs::ctx::LBSReadCtx;
+use lbs::ctx::LBSWriteCtx;
+use lbs::dynamic::Kinds;
+use lbs::dynamic::Value;
+use lbs::error::LBSError;
+use lbs::limits::Limits;
+use lbs::schema::LBSSchema;
+use lbs::transcode::Transcoder;
+use lbs::visit::LBSVisitor;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSReadRef;
+use lbs::LBSWrite;
+use lbs::Options;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Debug, PartialEq)]
+struct Node {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    children: Vec<Node>,
+}
+
+#[derive(LBSWrite, LBSRead, LBSReadRef, Default, Debug, PartialEq)]
+struct Linked<'a> {
+    #[lbs(id(0))]
+    name: Cow<'a, str>,
+    #[lbs(id(1))]
+    next: Option<Box<Linked<'a>>>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(ctx(()))]
+struct CtxLinked {
+    #[lbs(id(0), ctx)]
+    next: Option<Box<CtxLinked>>,
+}
+
+fn chain(depth: usize) -> Node {
+    let mut node = Node::default();
+
+    for i in 0..depth {
+        node = Node {
+            name: i.to_string(),
+            children: vec![node],
+        };
+    }
+
+    node
+}
+
+fn options(limits: Limits) -> Options {
+    let mut options = Options::new();
+    options.limits(limits);
+    options
+}
+
+fn limit(err: LBSError) -> &'static str {
+    match err.root_cause() {
+        LBSError::LimitExceeded(limit) => limit,
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[test]
+fn limits_huge_lengths() {
+    // Claimed lengths aren't preallocated, so short input fails quickly without limits
+    let bytes = u32::MAX.to_le_bytes();
+
+    assert!(lbs::from_slice::<Vec<u64>>(&bytes).unwrap_err().is_eof());
+    assert!(lbs::from_slice::<String>(&bytes).unwrap_err().is_eof());
+    assert!(lbs::from_slice::<HashMap<u32, String>>(&bytes)
+        .unwrap_err()
+        .is_eof());
+    assert!(Decoder::new()
+        .read_bytes(&mut &bytes[..])
+        .unwrap_err()
+        .is_eof());
+
+    // Strings longer than preallocation are still read whole
+    let long = "x".repeat(lbs::read::MAX_PREALLOCATION * 3 + 1);
+    let bytes = lbs::to_vec(&long).unwrap();
+    assert_eq!(lbs::from_slice::<String>(&bytes).unwrap(), long);
+    assert!(lbs::from_slice::<String>(&bytes[..bytes.len() - 1])
+        .unwrap_err()
+        .is_eof());
+}
+
+#[test]
+fn limits_len() {
+    let options = options(Limits {
+        max_len: Some(3),
+        ..Limits::new()
+    });
+
+    let ok = lbs::to_vec(&vec![1u8, 2, 3]).unwrap();
+    assert_eq!(
+        lbs::from_slice_with::<Vec<u8>>(&ok, &options).unwrap(),
+        vec![1, 2, 3]
+    );
+
+    let long = lbs::to_vec(&vec![1u8, 2, 3, 4]).unwrap();
+    let err = lbs::from_slice_with::<Vec<u8>>(&long, &options).unwrap_err();
+    assert_eq!(limit(err), "length");
+
+    let node = lbs::to_vec(&Node {
+        name: "four".to_string(),
+        children: Vec::new(),
+    })
+    .unwrap();
+    let err = lbs::from_slice_with::<Node>(&node, &options).unwrap_err();
+    assert_eq!(err.field_path(), vec![0]);
+    assert_eq!(limit(err), "length");
+}
+
+#[test]
+fn limits_depth() {
+    let bytes = lbs::to_vec(&chain(20)).unwrap();
+    let mut d = options(Limits {
+        max_depth: Some(10),
+        ..Limits::new()
+    })
+    .decoder();
+
+    let err = d.decode::<Node, _>(&mut bytes.as_slice()).unwrap_err();
+    assert_eq!(limit(err), "depth");
+
+    // Depth is reset for the next message
+    let shallow = lbs::to_vec(&chain(4)).unwrap();
+    assert_eq!(
+        d.decode::<Node, _>(&mut shallow.as_slice()).unwrap(),
+        chain(4)
+    );
+
+    let options = options(Limits::untrusted());
+    assert_eq!(
+        lbs::from_slice_with::<Node>(&bytes, &options).unwrap(),
+        chain(20)
+    );
+}
+
+#[test]
+fn limits_borrowed_depth() {
+    let mut linked = Linked::default();
+
+    for _ in 0..20 {
+        linked = Linked {
+            name: Cow::Borrowed("node"),
+            next: Some(Box::new(linked)),
+        };
+    }
+
+    let bytes = lbs::to_vec(&linked).unwrap();
+    let limits = Limits {
+        max_depth: Some(10),
+        ..Limits::new()
+    };
+
+    let err = lbs::from_slice_with::<Linked>(&bytes, &options(limits));
+    assert_eq!(limit(err.unwrap_err()), "depth");
+    let err = lbs::borrow::from_slice_with::<Linked>(&bytes, &mut options(limits).decoder());
+    assert_eq!(limit(err.unwrap_err()), "depth");
+
+    let mut d = options(Limits::untrusted()).decoder();
+    assert_eq!(
+        lbs::borrow::from_slice_with::<Linked>(&bytes, &mut d).unwrap(),
+        linked
+    );
+}
+
+#[test]
+fn limits_ctx_depth() {
+    let mut linked = CtxLinked::default();
+
+    for _ in 0..20 {
+        linked = CtxLinked {
+            next: Some(Box::new(linked)),
+        };
+    }
+
+    let mut bytes = Vec::new();
+    linked.lbs_write_ctx(&mut bytes, &mut ()).unwrap();
+
+    let mut d = options(Limits {
+        max_depth: Some(10),
+        ..Limits::new()
+    })
+    .decoder();
+    let err = CtxLinked::lbs_read_ctx_with(&mut bytes.as_slice(), &mut d, &mut ());
+    assert_eq!(limit(err.unwrap_err()), "depth");
+
+    let mut d = options(Limits::untrusted()).decoder();
+    let decoded = CtxLinked::lbs_read_ctx_with(&mut bytes.as_slice(), &mut d, &mut ());
+    assert_eq!(decoded.unwrap(), linked);
+}
+
+#[test]
+fn limits_total_bytes() {
+    let value = vec!["x".repeat(10); 10];
+    let mut bytes = lbs::to_vec(&value).unwrap();
+    let len = bytes.len() as u64;
+    bytes.extend_from_slice(&[0; 8]);
+
+    let exact = options(Limits {
+        max_total_bytes: Some(len),
+        ..Limits::new()
+    });
+    assert_eq!(
+        exact
+            .decoder()
+            .decode::<Vec<String>, _>(&mut bytes.as_slice())
+            .unwrap(),
+        value
+    );
+
+    let short = options(Limits {
+        max_total_bytes: Some(len - 1),
+        ..Limits::new()
+    });
+    let err = short
+        .decoder()
+        .decode::<Vec<String>, _>(&mut bytes.as_slice())
+        .unwrap_err();
+    assert_eq!(limit(err), "total bytes");
+}
+
+#[test]
+fn limits_codec_len() {
+    let bytes = 4u32.to_le_bytes();
+    let mut d = options(Limits {
+        max_len: Some(3),
+        ..Limits::new()
+    })
+    .decoder();
+
+    let err = lbs::fixed_point::FixedPoint::<100>::read::<Vec<f64>, _>(&mut &bytes[..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::unit::millis::read::<Vec<Duration>, _>(&mut &bytes[..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::compact_duration::read::<Vec<Duration>, _>(&mut &bytes[..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::delta_keys::read::<BTreeMap<u32, u8>, _>(&mut &bytes[..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+
+    // Varint length
+    let err = lbs::varint::read::<String, _>(&mut &[4][..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::varint::read::<Vec<u32>, _>(&mut &[4][..], &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+}
+
+#[test]
+fn limits_borrowed_len() {
+    // Claimed lengths aren't preallocated by borrowed reads either
+    let bytes = [0xff, 0xff, 0xff, 0xff, 0];
+    assert!(
+        lbs::borrow::from_slice::<HashMap<u64, (u64, u64, u64)>>(&bytes)
+            .unwrap_err()
+            .is_eof()
+    );
+    assert!(lbs::borrow::from_slice::<HashSet<u64>>(&bytes)
+        .unwrap_err()
+        .is_eof());
+
+    let bytes = 4u32.to_le_bytes();
+    let mut d = options(Limits {
+        max_len: Some(3),
+        ..Limits::new()
+    })
+    .decoder();
+
+    let err = lbs::borrow::from_slice_with::<&[u8]>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<&str>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<Vec<&str>>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<HashMap<u8, &str>>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<HashSet<u8>>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<BTreeMap<u8, &str>>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+    let err = lbs::borrow::from_slice_with::<BTreeSet<u8>>(&bytes, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "length");
+}
+
+struct Nothing;
+
+impl LBSVisitor for Nothing {}
+
+#[test]
+fn limits_schema_depth() {
+    // Recursive types are described by `Schema::Ref`, which is followed as deep as input goes
+    let bytes = lbs::to_vec(&chain(20)).unwrap();
+    let schema = Node::lbs_schema();
+    let limits = Limits {
+        max_depth: Some(10),
+        ..Limits::new()
+    };
+    let mut d = options(limits).decoder();
+
+    let err = lbs::visit::visit_with(&mut bytes.as_slice(), &schema, &mut Nothing, &mut d);
+    assert_eq!(limit(err.unwrap_err()), "depth");
+
+    let err = Value::read_with_decoder(&mut bytes.as_slice(), &schema, &Kinds::new(), &mut d);
+    assert_eq!(limit(err.unwrap_err()), "depth");
+
+    let mut transcoder = Transcoder::new(schema.clone(), schema);
+    let mut out = Vec::new();
+    transcoder
+        .transcode(&mut bytes.as_slice(), &mut out)
+        .unwrap();
+    assert_eq!(out, bytes);
+
+    let err = transcoder
+        .limits(limits)
+        .transcode(&mut bytes.as_slice(), &mut Vec::new());
+    assert_eq!(limit(err.unwrap_err()), "depth");
+}
@@ -0,0 +1,70 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[derive(LBSWrite, LBSRead, Debug, Default)]
+struct SharedState {
+    #[lbs(id(0))]
+    sessions: Mutex<HashMap<u32, String>>,
+    #[lbs(id(1))]
+    config: RwLock<Vec<String>>,
+    #[lbs(id(2), optional)]
+    node_id: OnceCell<u64>,
+    #[lbs(id(3), optional)]
+    region: OnceLock<String>,
+}
+
+#[test]
+fn locks() {
+    let state = SharedState::default();
+    state.sessions.lock().insert(1, "alice".to_string());
+    state.config.write().push("verbose".to_string());
+    state.node_id.set(42).unwrap();
+
+    let mut buf = Vec::new();
+    state.lbs_write(&mut buf).unwrap();
+    let decoded = SharedState::lbs_read(&mut buf.as_slice()).unwrap();
+
+    assert_eq!(*decoded.sessions.lock(), *state.sessions.lock());
+    assert_eq!(*decoded.config.read(), *state.config.read());
+    assert_eq!(decoded.node_id.get(), Some(&42));
+    assert_eq!(decoded.region.get(), None);
+
+    // Locks are written as their values
+    let mut plain = Vec::new();
+    state.config.read().lbs_write(&mut plain).unwrap();
+    let mut locked = Vec::new();
+    state.config.lbs_write(&mut locked).unwrap();
+    assert_eq!(locked, plain);
+}
+
+#[test]
+fn once_cells() {
+    let cell = OnceLock::new();
+    cell.set("eu".to_string()).unwrap();
+
+    let mut buf = Vec::new();
+    cell.lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        OnceLock::<String>::lbs_read(&mut buf.as_slice())
+            .unwrap()
+            .get(),
+        Some(&"eu".to_string())
+    );
+
+    // Cells are written as options
+    let mut option = Vec::new();
+    Some("eu".to_string()).lbs_write(&mut option).unwrap();
+    assert_eq!(buf, option);
+
+    let mut empty = Vec::new();
+    OnceCell::<u8>::new().lbs_write(&mut empty).unwrap();
+    assert!(OnceCell::<u8>::lbs_read(&mut empty.as_slice())
+        .unwrap()
+        .get()
+        .is_none());
+}
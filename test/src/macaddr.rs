@@ -0,0 +1,51 @@
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use macaddr::MacAddr;
+use macaddr::MacAddr6;
+use macaddr::MacAddr8;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug)]
+struct Interface {
+    #[lbs(id(0), default(MacAddr6::nil()))]
+    mac: MacAddr6,
+    #[lbs(id(1))]
+    neighbours: Vec<MacAddr6>,
+    #[lbs(id(2))]
+    eui64: Option<MacAddr8>,
+}
+
+#[test]
+fn macaddr() {
+    let interface = Interface {
+        mac: MacAddr6::new(0x00, 0x1b, 0x44, 0x11, 0x3a, 0xb7),
+        neighbours: vec![MacAddr6::broadcast(), MacAddr6::nil()],
+        eui64: Some(MacAddr8::new(
+            0x02, 0x1b, 0x44, 0xff, 0xfe, 0x11, 0x3a, 0xb7,
+        )),
+    };
+
+    let mut buf = Vec::new();
+    interface.lbs_write(&mut buf).unwrap();
+    assert_eq!(Interface::lbs_read(&mut buf.as_slice()).unwrap(), interface);
+
+    // Addresses are written as raw bytes
+    let mut raw = Vec::new();
+    interface.mac.lbs_write(&mut raw).unwrap();
+    assert_eq!(raw, interface.mac.as_bytes());
+
+    for addr in [
+        MacAddr::from(interface.mac),
+        MacAddr::from(interface.eui64.unwrap()),
+    ] {
+        let mut buf = Vec::new();
+        addr.lbs_write(&mut buf).unwrap();
+        assert_eq!(buf.len(), 1 + addr.as_bytes().len());
+        assert_eq!(MacAddr::lbs_read(&mut buf.as_slice()).unwrap(), addr);
+    }
+
+    assert_eq!(<MacAddr6 as LBSMaxSize>::LBS_MAX_SIZE, 6);
+    assert!(lbs::layout::layout::<Interface>()
+        .to_string()
+        .contains("#0 mac: (u8, u8, u8, u8, u8, u8) [6 bytes]"));
+}
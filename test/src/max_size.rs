@@ -0,0 +1,109 @@
+use lbs::size::LBSMaxSize;
+use lbs::write::encoded_len;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Cursor;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, Debug, PartialEq)]
+struct Point {
+    #[lbs(id(0))]
+    x: i32,
+    #[lbs(id(1))]
+    y: i32,
+    #[lbs(id(2))]
+    label: Option<char>,
+    #[lbs(id(3))]
+    kind: Kind,
+    #[lbs(skip)]
+    cached: Vec<u8>,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, Debug, PartialEq)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Origin,
+    #[lbs(id(1))]
+    Offset(u64),
+    #[lbs(id(2))]
+    Pair((u8, u16)),
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, Debug, PartialEq)]
+#[lbs(compact, pack_bools)]
+struct Flags {
+    #[lbs(id(300))]
+    count: u16,
+    #[lbs(id(0))]
+    a: bool,
+    #[lbs(id(9))]
+    b: bool,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, Debug, PartialEq)]
+#[lbs(indexed)]
+struct Indexed {
+    #[lbs(id(0))]
+    a: u32,
+    #[lbs(id(1))]
+    b: Option<u8>,
+}
+
+#[derive(LBSWrite, lbs::LBSMaxSize)]
+struct Generic<T> {
+    #[lbs(id(0))]
+    value: T,
+}
+
+#[test]
+fn max_size_constants() {
+    assert_eq!(Kind::LBS_MAX_SIZE, 2 + 8);
+    assert_eq!(Point::LBS_MAX_SIZE, 2 + (2 + 4) * 2 + (2 + 5) + (2 + 10));
+    assert_eq!(Flags::LBS_MAX_SIZE, 3 + (3 + 2) + (3 + 2 + 2 + 2));
+    assert_eq!(Indexed::LBS_MAX_SIZE, 2 + 4 + (10 + 4) + (10 + 2));
+    assert_eq!(Generic::<u16>::LBS_MAX_SIZE, 2 + 2 + 2);
+}
+
+#[test]
+fn max_size_bounds_encoded_len() {
+    let point = Point {
+        x: i32::MIN,
+        y: i32::MAX,
+        label: Some('x'),
+        kind: Kind::Offset(u64::MAX),
+        cached: vec![1, 2, 3],
+    };
+
+    assert_eq!(encoded_len(&point).unwrap(), Point::LBS_MAX_SIZE);
+
+    let flags = Flags {
+        count: u16::MAX,
+        a: true,
+        b: true,
+    };
+
+    // Varints are shorter than their maximum
+    assert!(encoded_len(&flags).unwrap() < Flags::LBS_MAX_SIZE);
+
+    let indexed = Indexed { a: 1, b: Some(2) };
+    assert_eq!(encoded_len(&indexed).unwrap(), Indexed::LBS_MAX_SIZE);
+}
+
+#[test]
+fn max_size_stack_buffer() {
+    let point = Point {
+        x: 1,
+        y: -1,
+        label: None,
+        kind: Kind::Pair((1, 2)),
+        cached: vec![],
+    };
+
+    let mut buf = [0u8; Point::LBS_MAX_SIZE];
+    let mut w = Cursor::new(&mut buf[..]);
+    point.lbs_write(&mut w).unwrap();
+
+    let len = w.position() as usize;
+    let read = Point::lbs_read(&mut &buf[..len]).unwrap();
+    assert_eq!(read, point);
+}
@@ -0,0 +1,140 @@
+use lbs::read::merge;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Config {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    limits: Limits,
+    #[lbs(id(2))]
+    hosts: Vec<String>,
+    #[lbs(id(3), append)]
+    tags: Vec<String>,
+    #[lbs(id(4), append)]
+    labels: BTreeMap<String, String>,
+    #[lbs(id(5), oneof(source))]
+    file: Option<String>,
+    #[lbs(id(6), oneof(source))]
+    url: Option<String>,
+    #[lbs(skip)]
+    loaded: bool,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Limits {
+    #[lbs(id(0))]
+    connections: u32,
+    #[lbs(id(1))]
+    timeout: u32,
+}
+
+/// Update with a subset of config fields.
+#[derive(LBSWrite)]
+struct Patch {
+    #[lbs(id(1))]
+    limits: LimitsPatch,
+    #[lbs(id(2))]
+    hosts: Vec<String>,
+    #[lbs(id(3))]
+    tags: Vec<String>,
+    #[lbs(id(4))]
+    labels: BTreeMap<String, String>,
+    #[lbs(id(6))]
+    url: Option<String>,
+}
+
+#[derive(LBSWrite)]
+struct LimitsPatch {
+    #[lbs(id(1))]
+    timeout: u32,
+}
+
+#[derive(LBSWrite)]
+struct EmptyPatch {}
+
+fn config() -> Config {
+    Config {
+        name: "main".to_string(),
+        limits: Limits {
+            connections: 10,
+            timeout: 30,
+        },
+        hosts: vec!["a".to_string()],
+        tags: vec!["x".to_string()],
+        labels: BTreeMap::from([("k".to_string(), "v".to_string())]),
+        file: Some("config.toml".to_string()),
+        url: None,
+        loaded: true,
+    }
+}
+
+fn apply<T: LBSWrite>(config: &mut Config, patch: &T) {
+    let mut buf = Vec::new();
+    patch.lbs_write(&mut buf).unwrap();
+    merge(config, &mut buf.as_slice()).unwrap();
+}
+
+#[test]
+fn merge_empty_patch() {
+    let mut value = config();
+    apply(&mut value, &EmptyPatch {});
+    assert_eq!(value, config());
+}
+
+#[test]
+fn merge_overlays_present_fields() {
+    let mut value = config();
+
+    apply(
+        &mut value,
+        &Patch {
+            limits: LimitsPatch { timeout: 5 },
+            hosts: vec!["b".to_string(), "c".to_string()],
+            tags: vec!["y".to_string()],
+            labels: BTreeMap::from([
+                ("k".to_string(), "w".to_string()),
+                ("l".to_string(), "u".to_string()),
+            ]),
+            url: Some("https://example.com".to_string()),
+        },
+    );
+
+    assert_eq!(
+        value,
+        Config {
+            limits: Limits {
+                connections: 10,
+                timeout: 5,
+            },
+            hosts: vec!["b".to_string(), "c".to_string()],
+            tags: vec!["x".to_string(), "y".to_string()],
+            labels: BTreeMap::from([
+                ("k".to_string(), "w".to_string()),
+                ("l".to_string(), "u".to_string()),
+            ]),
+            file: None,
+            url: Some("https://example.com".to_string()),
+            ..config()
+        }
+    );
+}
+
+#[test]
+fn merge_full_value() {
+    let mut value = config();
+    let mut update = config();
+    update.name = "other".to_string();
+    update.loaded = false;
+
+    let mut buf = Vec::new();
+    update.lbs_write(&mut buf).unwrap();
+    value.lbs_merge(&mut buf.as_slice()).unwrap();
+
+    // Appended collections grow, skipped fields are untouched
+    assert_eq!(value.name, "other");
+    assert_eq!(value.tags, vec!["x".to_string(), "x".to_string()]);
+    assert!(value.loaded);
+}
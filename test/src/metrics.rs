@@ -0,0 +1,67 @@
+use lbs::error::LBSError;
+use lbs::metrics::LBSMetrics;
+use lbs::Decoder;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Event {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(&'static str, &'static str, usize)>>);
+
+impl LBSMetrics for Recorder {
+    fn encoded(&self, type_name: &'static str, bytes: usize, _elapsed: Duration) {
+        self.0.lock().unwrap().push(("encoded", type_name, bytes));
+    }
+
+    fn decoded(&self, type_name: &'static str, bytes: usize, _elapsed: Duration) {
+        self.0.lock().unwrap().push(("decoded", type_name, bytes));
+    }
+
+    fn decode_failed(&self, type_name: &'static str, bytes: usize, _error: &LBSError) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(("decode_failed", type_name, bytes));
+    }
+}
+
+#[test]
+fn metrics() {
+    let recorder = Arc::new(Recorder::default());
+    let event = Event {
+        id: 1,
+        name: "a".to_string(),
+    };
+
+    let mut buf = Vec::new();
+    Encoder::new()
+        .metrics(recorder.clone())
+        .encode(&event, &mut buf)
+        .unwrap();
+
+    let mut d = Decoder::new();
+    d.metrics(recorder.clone());
+    assert_eq!(d.decode::<Event, _>(&mut buf.as_slice()).unwrap(), event);
+    assert!(d.decode::<Event, _>(&mut &buf[..5]).is_err());
+
+    let type_name = std::any::type_name::<Event>();
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [
+            ("encoded", type_name, buf.len()),
+            ("decoded", type_name, buf.len()),
+            ("decode_failed", type_name, 5),
+        ]
+    );
+}
@@ -0,0 +1,131 @@
+use lbs::error::LBSError;
+use lbs::migrate::Migrations;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Default)]
+#[lbs(version(1))]
+struct UserV1 {
+    #[lbs(id(0))]
+    name: String,
+}
+
+#[derive(LBSWrite, LBSRead, Default)]
+#[lbs(version(2))]
+struct UserV2 {
+    #[lbs(id(0))]
+    first_name: String,
+    #[lbs(id(1))]
+    last_name: String,
+}
+
+/// Another type of the same version as `UserV2`.
+#[derive(LBSWrite, LBSRead, Default)]
+#[lbs(version(2))]
+struct Account {
+    #[lbs(id(0))]
+    login: String,
+}
+
+#[derive(LBSWrite, LBSRead, Default, PartialEq, Debug)]
+#[lbs(version(3))]
+struct User {
+    #[lbs(id(0))]
+    first_name: String,
+    #[lbs(id(1))]
+    last_name: String,
+    #[lbs(id(2))]
+    age: Option<u8>,
+}
+
+#[test]
+fn migrate() {
+    let migrations = Migrations::<User>::new()
+        .register(|v2: UserV2| User {
+            first_name: v2.first_name,
+            last_name: v2.last_name,
+            age: None,
+        })
+        .unwrap()
+        .register(|v1: UserV1| {
+            let (first_name, last_name) = v1.name.split_once(' ').unwrap_or((&v1.name, ""));
+            UserV2 {
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+            }
+        })
+        .unwrap();
+
+    let expected = User {
+        first_name: "Ada".into(),
+        last_name: "Lovelace".into(),
+        age: None,
+    };
+
+    let mut buf = Vec::new();
+    let v1 = UserV1 {
+        name: "Ada Lovelace".into(),
+    };
+    lbs::migrate::write(&mut buf, &v1).unwrap();
+    assert_eq!(
+        migrations.migrate_read(&mut buf.as_slice()).unwrap(),
+        expected
+    );
+
+    let mut buf = Vec::new();
+    lbs::migrate::write(&mut buf, &expected).unwrap();
+    assert_eq!(
+        migrations.migrate_read(&mut buf.as_slice()).unwrap(),
+        expected
+    );
+
+    let buf = [9u8, 0];
+    assert!(matches!(
+        migrations.migrate_read(&mut buf.as_slice()),
+        Err(LBSError::UnsupportedVersion(9))
+    ));
+}
+
+#[test]
+fn migrate_conflicts() {
+    let migrations = Migrations::<User>::new()
+        .register(|v2: UserV2| User {
+            first_name: v2.first_name,
+            last_name: v2.last_name,
+            age: None,
+        })
+        .unwrap();
+
+    // Upgrader into a type of the same version as the registered one
+    let err = Migrations::<User>::new()
+        .register(|v2: UserV2| User {
+            first_name: v2.first_name,
+            last_name: v2.last_name,
+            age: None,
+        })
+        .unwrap()
+        .register(|v1: UserV1| Account { login: v1.name })
+        .err()
+        .unwrap();
+    assert!(matches!(err, LBSError::VersionConflict(2)));
+
+    // Second type of a registered version
+    let err = migrations
+        .register(|account: Account| User {
+            first_name: account.login,
+            last_name: String::new(),
+            age: None,
+        })
+        .err()
+        .unwrap();
+    assert!(matches!(err, LBSError::VersionConflict(2)));
+
+    let err = Migrations::<User>::new()
+        .register(|v1: UserV1| UserV2 {
+            first_name: v1.name,
+            last_name: String::new(),
+        })
+        .err()
+        .unwrap();
+    assert!(matches!(err, LBSError::UnsupportedVersion(2)));
+}
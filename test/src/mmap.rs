@@ -0,0 +1,57 @@
+use lbs::error::LBSError;
+use lbs::mmap::MappedFrames;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Write;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(indexed)]
+struct Point {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(1))]
+    label: String,
+}
+
+#[test]
+fn mmap() {
+    let path = std::env::temp_dir().join(format!("lbs-mmap-{}", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+
+    for i in 0..10 {
+        let point = Point {
+            id: i,
+            label: format!("point{i}"),
+        };
+        lbs::frame::write_frame(&mut file, &point).unwrap();
+    }
+
+    drop(file);
+
+    // Frames are decoded or viewed on demand
+    let frames = unsafe { MappedFrames::open(&path) }.unwrap();
+    assert_eq!(frames.len(), 10);
+
+    for (i, frame) in frames.iter().enumerate() {
+        let point: Point = frame.decode().unwrap();
+        assert_eq!(point.id, i as u32);
+        assert_eq!(frame.view().unwrap().get::<u32>(0).unwrap(), Some(i as u32));
+    }
+
+    // Frame is checked against limit
+    assert!(matches!(
+        unsafe { MappedFrames::open_with_limit(&path, 8) },
+        Err(LBSError::FrameTooLarge { .. })
+    ));
+
+    // Truncated frame
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[100, 0, 0, 0, 1]).unwrap();
+    drop(file);
+    assert!(unsafe { MappedFrames::open(&path) }.unwrap_err().is_eof());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,66 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Default, PartialEq)]
+struct Message {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1), oneof(payload))]
+    text: Option<String>,
+    #[lbs(id(2), oneof(payload))]
+    image: Option<Vec<u8>>,
+    #[lbs(id(3), oneof(reply))]
+    reply_to: Option<u64>,
+    #[lbs(id(4), oneof(reply))]
+    forward_from: Option<u64>,
+}
+
+/// Same layout without the constraint, as written by a misbehaving peer.
+#[derive(LBSWrite, Default)]
+struct Unchecked {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    text: Option<String>,
+    #[lbs(id(2))]
+    image: Option<Vec<u8>>,
+}
+
+#[test]
+fn oneof() {
+    let message = Message {
+        id: 1,
+        text: Some("hello".to_string()),
+        reply_to: Some(0),
+        ..Default::default()
+    };
+
+    let mut buf = Vec::new();
+    message.lbs_write(&mut buf).unwrap();
+    assert_eq!(Message::lbs_read(&mut buf.as_slice()).unwrap(), message);
+
+    // Writing of conflicting fields fails
+    let conflicting = Message {
+        forward_from: Some(2),
+        ..message
+    };
+    assert!(matches!(
+        conflicting.lbs_write(&mut Vec::new()),
+        Err(LBSError::OneofConflict("reply"))
+    ));
+
+    // So does reading
+    let mut buf = Vec::new();
+    Unchecked {
+        id: 1,
+        text: Some("hello".to_string()),
+        image: Some(vec![1]),
+    }
+    .lbs_write(&mut buf)
+    .unwrap();
+    assert!(matches!(
+        Message::lbs_read(&mut buf.as_slice()),
+        Err(LBSError::OneofConflict("payload"))
+    ));
+}
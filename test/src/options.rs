@@ -0,0 +1,54 @@
+use lbs::error::LBSError;
+use lbs::from_slice_with;
+use lbs::to_vec_with;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use lbs::Options;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(version(2))]
+struct Reading {
+    #[lbs(id(0))]
+    sensor: String,
+    #[lbs(id(1))]
+    location: String,
+    #[lbs(id(2))]
+    value: f64,
+    #[lbs(id(3), since(2))]
+    unit: String,
+}
+
+#[test]
+fn options() {
+    let reading = Reading {
+        sensor: "north".to_string(),
+        location: "north".to_string(),
+        value: -0.0,
+        unit: "C".to_string(),
+    };
+
+    let options = Options::new();
+    let buf = to_vec_with(&reading, &options).unwrap();
+    assert_eq!(from_slice_with::<Reading>(&buf, &options).unwrap(), reading);
+
+    // Options apply to both sides and to nested values
+    let mut options = Options::new();
+    options.canonical(true).share_strings(true).for_version(1);
+
+    let buf = to_vec_with(&reading, &options).unwrap();
+    let decoded: Reading = from_slice_with(&buf, &options).unwrap();
+    assert_eq!(decoded.location, "north");
+    assert!(decoded.value.is_sign_positive());
+    assert_eq!(decoded.unit, "");
+
+    let plain = to_vec_with(&reading, &Options::new()).unwrap();
+    assert!(buf.len() < plain.len());
+
+    // Trailing bytes are rejected only in exact mode
+    let mut padded = plain.clone();
+    padded.push(0);
+    assert!(from_slice_with::<Reading>(&padded, &Options::new()).is_ok());
+
+    let err = from_slice_with::<Reading>(&padded, Options::new().exact(true)).unwrap_err();
+    assert!(matches!(err, LBSError::TrailingBytes(1)));
+}
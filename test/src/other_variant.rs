@@ -0,0 +1,170 @@
+use lbs::size::LBSMaxSize;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+enum Event {
+    #[lbs(id(0))]
+    Ping,
+    #[lbs(id(1))]
+    Login(String),
+}
+
+/// Older version of `Event`, which forwards variants it doesn't know.
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+enum EventV1 {
+    #[lbs(id(0))]
+    Ping,
+    #[lbs(other)]
+    Unknown(u16, Vec<u8>),
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(compact)]
+enum CompactEvent {
+    #[lbs(id(0))]
+    Ping,
+    #[lbs(id(300))]
+    Login(String),
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(compact)]
+enum CompactEventV1 {
+    #[lbs(id(0))]
+    Ping,
+    #[lbs(other)]
+    Unknown(u16, Vec<u8>),
+}
+
+#[test]
+fn other_variant() {
+    let event = Event::Login("alice".to_string());
+
+    let mut buf = Vec::new();
+    event.lbs_write(&mut buf).unwrap();
+
+    let forwarded = EventV1::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(forwarded, EventV1::Unknown(1, buf[2..].to_vec()));
+
+    // Unknown variant is written back unchanged
+    let mut forwarded_buf = Vec::new();
+    forwarded.lbs_write(&mut forwarded_buf).unwrap();
+    assert_eq!(forwarded_buf, buf);
+    assert_eq!(
+        Event::lbs_read(&mut forwarded_buf.as_slice()).unwrap(),
+        event
+    );
+
+    // Known variants are decoded as usual
+    let mut ping = Vec::new();
+    Event::Ping.lbs_write(&mut ping).unwrap();
+    assert_eq!(
+        EventV1::lbs_read(&mut ping.as_slice()).unwrap(),
+        EventV1::Ping
+    );
+
+    // In place
+    let mut value = EventV1::Ping;
+    lbs::read::read_into(&mut value, &mut buf.as_slice()).unwrap();
+    assert_eq!(value, forwarded);
+
+    // Compact variant ids
+    let event = CompactEvent::Login("bob".to_string());
+    let mut buf = Vec::new();
+    event.lbs_write(&mut buf).unwrap();
+
+    let forwarded = CompactEventV1::lbs_read(&mut buf.as_slice()).unwrap();
+    assert!(matches!(forwarded, CompactEventV1::Unknown(300, _)));
+
+    let mut forwarded_buf = Vec::new();
+    forwarded.lbs_write(&mut forwarded_buf).unwrap();
+    assert_eq!(forwarded_buf, buf);
+}
+
+#[test]
+fn other_variant_frame() {
+    let mut buf = Vec::new();
+    lbs::frame::write_frame(&mut buf, &Event::Login("carol".to_string())).unwrap();
+    lbs::frame::write_frame(&mut buf, &Event::Ping).unwrap();
+
+    // Payload of unknown variant ends with the frame
+    let mut r = buf.as_slice();
+    let first: EventV1 = lbs::frame::read_frame(&mut r).unwrap();
+    let second: EventV1 = lbs::frame::read_frame(&mut r).unwrap();
+    assert!(matches!(first, EventV1::Unknown(1, _)));
+    assert_eq!(second, EventV1::Ping);
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, PartialEq, Debug)]
+enum Status {
+    #[default]
+    #[lbs(id(0))]
+    Active,
+    #[lbs(id(1))]
+    Suspended,
+    #[lbs(id(2))]
+    Archived,
+}
+
+/// Older version of `Status`, which keeps ids of unit variants it doesn't know.
+#[derive(LBSWrite, LBSRead, lbs::LBSMaxSize, Default, PartialEq, Debug)]
+enum StatusV1 {
+    #[default]
+    #[lbs(id(0))]
+    Active,
+    #[lbs(other)]
+    Unknown(u16),
+}
+
+#[derive(LBSWrite, LBSRead, Default, PartialEq, Debug)]
+struct Account {
+    #[lbs(id(0))]
+    status: Status,
+    #[lbs(id(1))]
+    name: String,
+}
+
+#[derive(LBSWrite, LBSRead, Default, PartialEq, Debug)]
+struct AccountV1 {
+    #[lbs(id(0))]
+    status: StatusV1,
+    #[lbs(id(1))]
+    name: String,
+}
+
+#[test]
+fn other_variant_id_only() {
+    let account = Account {
+        status: Status::Archived,
+        name: "dave".to_string(),
+    };
+
+    // Without payload, unknown variants don't consume the rest of input, so enum may be nested
+    let buf = lbs::to_vec(&account).unwrap();
+    let forwarded = lbs::from_slice::<AccountV1>(&buf).unwrap();
+    assert_eq!(
+        forwarded,
+        AccountV1 {
+            status: StatusV1::Unknown(2),
+            name: "dave".to_string(),
+        }
+    );
+
+    // Unknown id is written back
+    let forwarded_buf = lbs::to_vec(&forwarded).unwrap();
+    assert_eq!(forwarded_buf, buf);
+    assert_eq!(lbs::from_slice::<Account>(&forwarded_buf).unwrap(), account);
+    assert_eq!(StatusV1::Unknown(2).lbs_size_hint(), 2);
+
+    // In place
+    let mut status = StatusV1::Active;
+    lbs::read::read_into(
+        &mut status,
+        &mut lbs::to_vec(&Status::Suspended).unwrap().as_slice(),
+    )
+    .unwrap();
+    assert_eq!(status, StatusV1::Unknown(1));
+
+    assert_eq!(StatusV1::LBS_MAX_SIZE, Status::LBS_MAX_SIZE);
+}
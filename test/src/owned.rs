@@ -0,0 +1,90 @@
+use bytes::BytesMut;
+use lbs::error::LBSError;
+use lbs::frame::read_frame;
+use lbs::owned::decode;
+use lbs::owned::decode_frame;
+use lbs::owned::decode_frame_with_limit;
+use lbs::owned::encode;
+use lbs::owned::encode_frame;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+struct Message {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    text: String,
+}
+
+fn message() -> Message {
+    Message {
+        id: 7,
+        text: "hello".to_string(),
+    }
+}
+
+#[test]
+fn owned_roundtrip_vec() {
+    let buf = Vec::with_capacity(64);
+    let (len, buf) = encode(&message(), buf);
+    assert_eq!(len.unwrap(), buf.len());
+    assert_eq!(buf.capacity(), 64);
+
+    let (value, buf) = decode::<Message, _>(buf);
+    assert_eq!(value.unwrap(), message());
+
+    // Buffer is reused for the next message
+    let (len, buf) = encode(&1u8, buf);
+    assert_eq!(len.unwrap(), 1);
+    assert_eq!(buf, vec![1]);
+}
+
+#[test]
+fn owned_roundtrip_bytes_mut() {
+    let (len, buf) = encode(&message(), BytesMut::new());
+    assert_eq!(len.unwrap(), buf.len());
+
+    let (value, _) = decode::<Message, _>(buf);
+    assert_eq!(value.unwrap(), message());
+}
+
+#[test]
+fn owned_decode_keeps_buffer_on_error() {
+    let (value, buf) = decode::<Message, _>(vec![1, 2, 3]);
+    assert!(value.is_err());
+    assert_eq!(buf, vec![1, 2, 3]);
+}
+
+#[test]
+fn owned_frames() {
+    let (len, frame) = encode_frame(&message(), Vec::new());
+    assert_eq!(len.unwrap(), frame.len());
+    assert_eq!(
+        read_frame::<Message, _>(&mut frame.as_slice()).unwrap(),
+        message()
+    );
+
+    // Frame arrives in parts
+    let mut received = BytesMut::new();
+
+    for byte in &frame[..frame.len() - 1] {
+        received.extend_from_slice(&[*byte]);
+        let (value, buf) = decode_frame::<Message, _>(received);
+        assert!(value.unwrap().is_none());
+        received = buf;
+    }
+
+    received.extend_from_slice(&frame[frame.len() - 1..]);
+    received.extend_from_slice(&[0xff]);
+
+    let (value, received) = decode_frame::<Message, _>(received);
+    assert_eq!(value.unwrap(), Some((message(), frame.len())));
+    assert_eq!(received.len(), frame.len() + 1);
+
+    let (value, _) = decode_frame_with_limit::<Message, _>(frame, 4);
+    assert!(matches!(
+        value,
+        Err(LBSError::FrameTooLarge { max_len: 4, .. })
+    ));
+}
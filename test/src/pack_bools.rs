@@ -0,0 +1,61 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+#[lbs(pack_bools)]
+struct Flags {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    a: bool,
+    #[lbs(id(2))]
+    b: bool,
+    #[lbs(id(5), default(true))]
+    c: bool,
+    #[lbs(id(12))]
+    d: bool,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct UnpackedFlags {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    a: bool,
+    #[lbs(id(2))]
+    b: bool,
+    #[lbs(id(5))]
+    c: bool,
+    #[lbs(id(12))]
+    d: bool,
+}
+
+#[test]
+fn pack_bools() {
+    let original = Flags {
+        name: "flags".into(),
+        a: true,
+        b: false,
+        c: false,
+        d: true,
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    // count + name + packed id + base + bit count + 2 bytes of bits
+    assert_eq!(buf.len(), 2 + 2 + 4 + 5 + 2 + 2 + 2 + 2);
+    assert_eq!(Flags::lbs_read(&mut buf.as_slice()).unwrap(), original);
+
+    // Unpacked encoding is still readable
+    let unpacked = UnpackedFlags {
+        name: "flags".into(),
+        a: true,
+        b: false,
+        c: false,
+        d: true,
+    };
+
+    let mut buf = Vec::new();
+    unpacked.lbs_write(&mut buf).unwrap();
+    assert_eq!(Flags::lbs_read(&mut buf.as_slice()).unwrap(), original);
+}
@@ -0,0 +1,83 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+struct Record {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    payload: Vec<Vec<u8>>,
+    #[lbs(id(3), sparse)]
+    sparse: Vec<Option<u32>>,
+    #[lbs(id(4))]
+    nested: Option<Nested>,
+    #[lbs(id(5))]
+    attributes: HashMap<String, String>,
+    #[lbs(id(6))]
+    score: f64,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+struct Nested {
+    #[lbs(id(0))]
+    required: u8,
+    #[lbs(id(1))]
+    tags: Vec<String>,
+}
+
+#[test]
+fn project() {
+    let record = Record {
+        id: 42,
+        name: "record".into(),
+        payload: vec![vec![1; 1000], vec![2; 10]],
+        sparse: vec![None, Some(1), None],
+        nested: Some(Nested {
+            required: 7,
+            tags: vec!["a".into(), "b".into()],
+        }),
+        attributes: HashMap::from([("k".into(), "v".into())]),
+        score: 0.5,
+    };
+
+    let mut buf = Vec::new();
+    record.lbs_write(&mut buf).unwrap();
+
+    // Only selected fields are decoded, rest are default
+    let projected: Record = lbs::read::project(&mut buf.as_slice(), &[0, 6]).unwrap();
+    assert_eq!(
+        projected,
+        Record {
+            id: 42,
+            score: 0.5,
+            ..Default::default()
+        }
+    );
+
+    // All bytes are consumed, so projected values may follow each other
+    buf.extend_from_within(..);
+    let mut r = buf.as_slice();
+    let first: Record = lbs::read::project(&mut r, &[1]).unwrap();
+    let second: Record = lbs::read::project(&mut r, &[4]).unwrap();
+    assert!(r.is_empty());
+    assert_eq!(first.name, record.name);
+    assert_eq!(second.nested, record.nested);
+    assert!(second.name.is_empty());
+
+    // Whole value may be skipped
+    let mut r = buf.as_slice();
+    Record::lbs_skip(&mut r, &mut lbs::Decoder::new()).unwrap();
+    assert_eq!(Record::lbs_read(&mut r).unwrap(), record);
+
+    // Required fields are checked only if projected
+    let buf = [0u8, 0];
+    assert!(lbs::read::project::<Nested, _>(&mut buf.as_slice(), &[]).is_ok());
+    assert!(matches!(
+        lbs::read::project::<Nested, _>(&mut buf.as_slice(), &[0]),
+        Err(LBSError::WithField { .. })
+    ));
+}
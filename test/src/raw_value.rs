@@ -0,0 +1,127 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+use lbs::RawValue;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Envelope<T: Default> {
+    #[lbs(id(0))]
+    kind: u16,
+    #[lbs(id(1), delimited)]
+    payload: T,
+    #[lbs(id(2))]
+    trace: String,
+}
+
+/// Envelope as seen by a gateway, which doesn't know payload types.
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Default, Debug, Clone, PartialEq)]
+struct RawEnvelope {
+    #[lbs(id(0))]
+    kind: u16,
+    #[lbs(id(1))]
+    payload: RawValue,
+    #[lbs(id(2))]
+    trace: String,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Login {
+    #[lbs(id(0))]
+    user: String,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct Resize {
+    #[lbs(id(0))]
+    width: u32,
+    #[lbs(id(1))]
+    height: u32,
+}
+
+#[test]
+fn raw_value_passthrough() {
+    let login = Envelope {
+        kind: 1,
+        payload: Login {
+            user: "alice".to_string(),
+        },
+        trace: "t1".to_string(),
+    };
+    let bytes = lbs::to_vec(&login).unwrap();
+
+    // Gateway forwards payload verbatim
+    let raw = lbs::from_slice::<RawEnvelope>(&bytes).unwrap();
+    assert_eq!(raw.payload.as_bytes(), lbs::to_vec(&login.payload).unwrap());
+    let forwarded = lbs::to_vec(&raw).unwrap();
+    assert_eq!(forwarded, bytes);
+    assert_eq!(forwarded.len(), raw.lbs_size_hint());
+    assert_eq!(
+        lbs::from_slice::<Envelope<Login>>(&forwarded).unwrap(),
+        login
+    );
+
+    // Payload is decoded once kind tells its type
+    match raw.kind {
+        1 => assert_eq!(raw.payload.decode::<Login>().unwrap(), login.payload),
+        kind => panic!("unexpected kind {}", kind),
+    }
+}
+
+#[test]
+fn raw_value_build() {
+    let resize = Resize {
+        width: 640,
+        height: 480,
+    };
+    let raw = RawEnvelope {
+        kind: 2,
+        payload: RawValue::from_value(&resize).unwrap(),
+        trace: String::new(),
+    };
+    let bytes = lbs::to_vec(&raw).unwrap();
+    let read = lbs::from_slice::<Envelope<Resize>>(&bytes).unwrap();
+    assert_eq!(read.payload, resize);
+
+    // Raw values are written like byte vectors
+    assert_eq!(
+        lbs::to_vec(&raw.payload).unwrap(),
+        lbs::to_vec(&raw.payload.clone().into_bytes()).unwrap()
+    );
+
+    // Payload of another type doesn't occupy its bytes
+    let err = raw.payload.decode::<Login>().unwrap_err();
+    assert!(err.is_eof() || matches!(err, LBSError::TrailingBytes(_)));
+    let err = RawValue::from_value(&(1u32, 2u32))
+        .unwrap()
+        .decode::<u32>()
+        .unwrap_err();
+    assert!(matches!(err, LBSError::TrailingBytes(4)));
+}
+
+#[test]
+fn raw_value_in_place_and_skip() {
+    let mut raw = RawValue::from_bytes(vec![0; 64]);
+    let bytes = lbs::to_vec(&RawValue::from(vec![1, 2, 3])).unwrap();
+    raw.lbs_read_into(&mut bytes.as_slice()).unwrap();
+    assert_eq!(raw.as_bytes(), [1, 2, 3]);
+
+    let mut input = bytes.as_slice();
+    RawValue::lbs_skip(&mut input, &mut lbs::Decoder::new()).unwrap();
+    assert!(input.is_empty());
+}
+
+#[tokio::test]
+async fn raw_value_async() {
+    let bytes = lbs::to_vec(&Envelope {
+        kind: 2,
+        payload: Resize {
+            width: 1,
+            height: 2,
+        },
+        trace: "t".to_string(),
+    })
+    .unwrap();
+    let read: RawEnvelope = lbs::tokio::read::read(&mut bytes.as_slice()).await.unwrap();
+    assert_eq!(read, lbs::from_slice::<RawEnvelope>(&bytes).unwrap());
+}
@@ -0,0 +1,72 @@
+use lbs::decoder::Decoder;
+use lbs::encoder::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct State {
+    #[lbs(id(0), parallel)]
+    values: Vec<String>,
+    #[lbs(id(1), parallel)]
+    index: HashMap<u64, u64>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Events {
+    #[lbs(id(0), parallel)]
+    events: Vec<Event>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Event {
+    #[lbs(id(0), relative_time, default(SystemTime::UNIX_EPOCH))]
+    at: SystemTime,
+}
+
+#[test]
+fn parallel_write() {
+    let original = State {
+        values: (0..20_000).map(|i| i.to_string()).collect(),
+        index: (0..20_000).map(|i| (i, i * 2)).collect(),
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    assert_eq!(State::lbs_read(&mut buf.as_slice()).unwrap(), original);
+
+    let mut sequential = Vec::new();
+    original.values.lbs_write(&mut sequential).unwrap();
+    let mut parallel = Vec::new();
+    lbs::rayon::write(&original.values, &mut parallel).unwrap();
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn parallel_relative_time() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let original = Events {
+        events: (0..20_000)
+            .map(|i| Event {
+                at: start + Duration::from_millis(i),
+            })
+            .collect(),
+    };
+
+    // Timestamps are deltas from the preceding element, so they are encoded sequentially
+    let mut buf = Vec::new();
+    Encoder::new().encode(&original, &mut buf).unwrap();
+    let mut sequential = Vec::new();
+    Encoder::new()
+        .encode(&original.events, &mut sequential)
+        .unwrap();
+    assert_eq!(buf[buf.len() - sequential.len()..], sequential);
+
+    let mut d = Decoder::new();
+    assert_eq!(
+        d.decode::<Events, _>(&mut buf.as_slice()).unwrap(),
+        original
+    );
+}
@@ -0,0 +1,66 @@
+use lbs::error::LBSError;
+use lbs::rdkafka::LBSDeserializer;
+use lbs::rdkafka::LBSSerializer;
+use lbs::LBSRead;
+use lbs::LBSSchema;
+use lbs::LBSWrite;
+use rdkafka::message::OwnedMessage;
+use rdkafka::message::Timestamp;
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Clone)]
+struct Click {
+    #[lbs(id(0))]
+    user_id: u64,
+    #[lbs(id(1))]
+    url: String,
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Clone)]
+struct View {
+    #[lbs(id(0))]
+    user_id: u64,
+    #[lbs(id(1))]
+    url: String,
+}
+
+fn message(payload: Option<Vec<u8>>) -> OwnedMessage {
+    OwnedMessage::new(
+        payload,
+        None,
+        "clicks".to_string(),
+        Timestamp::NotAvailable,
+        0,
+        0,
+        None,
+    )
+}
+
+#[test]
+fn rdkafka() {
+    let click = Click {
+        user_id: 1,
+        url: "/".to_string(),
+    };
+
+    // Plain payload
+    let payload = LBSSerializer::new().serialize(&click).unwrap();
+    let d = LBSDeserializer::<Click>::new();
+    assert_eq!(
+        d.payload(&message(Some(payload))).unwrap(),
+        Some(click.clone())
+    );
+    assert_eq!(d.payload(&message(None)).unwrap(), None);
+
+    // Payload with fingerprint is checked by consumer
+    let payload = LBSSerializer::with_schema().serialize(&click).unwrap();
+    let d = LBSDeserializer::<Click>::with_schema();
+    assert_eq!(d.deserialize(&payload).unwrap(), click);
+    assert!(matches!(
+        LBSDeserializer::<View>::with_schema().deserialize(&payload),
+        Err(LBSError::SchemaMismatch)
+    ));
+    assert!(matches!(
+        d.deserialize(&payload[..4]),
+        Err(LBSError::SchemaMismatch)
+    ));
+}
@@ -0,0 +1,105 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+enum StatusV1 {
+    #[lbs(id(0))]
+    Active,
+    #[lbs(id(1))]
+    Suspended(String),
+    #[lbs(id(2))]
+    Banned {
+        #[lbs(id(0))]
+        reason: String,
+    },
+}
+
+/// Variants renumbered into a range of their own, old ids are still read.
+#[derive(LBSWrite, LBSRead, LBSReadAsync, PartialEq, Debug)]
+enum StatusV2 {
+    #[lbs(id(3))]
+    Pending,
+    #[lbs(id(10), read_as(0))]
+    Active,
+    #[lbs(id(11), read_as(1))]
+    Suspended(String),
+    #[lbs(id(12), read_as(2, 7))]
+    Banned {
+        #[lbs(id(0))]
+        reason: String,
+    },
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(compact)]
+enum Level {
+    #[lbs(id(5), read_as(1))]
+    Low,
+    #[lbs(id(6), read_as(2))]
+    High,
+}
+
+#[test]
+fn read_as() {
+    let old = lbs::to_vec(&StatusV1::Suspended("spam".to_string())).unwrap();
+    assert_eq!(
+        lbs::from_slice::<StatusV2>(&old).unwrap(),
+        StatusV2::Suspended("spam".to_string())
+    );
+
+    let old = lbs::to_vec(&StatusV1::Banned {
+        reason: "abuse".to_string(),
+    })
+    .unwrap();
+    let read: StatusV2 = lbs::from_slice(&old).unwrap();
+    assert_eq!(
+        read,
+        StatusV2::Banned {
+            reason: "abuse".to_string()
+        }
+    );
+
+    // Written with the new id
+    let new = lbs::to_vec(&read).unwrap();
+    assert_eq!(&new[..2], &12u16.to_le_bytes());
+    assert_eq!(lbs::from_slice::<StatusV2>(&new).unwrap(), read);
+
+    // Another former id
+    let mut other = 7u16.to_le_bytes().to_vec();
+    other.extend_from_slice(&new[2..]);
+    assert_eq!(lbs::from_slice::<StatusV2>(&other).unwrap(), read);
+
+    let active = lbs::to_vec(&StatusV1::Active).unwrap();
+    assert_eq!(
+        lbs::from_slice::<StatusV2>(&active).unwrap(),
+        StatusV2::Active
+    );
+
+    // Old readers don't know new ids
+    assert!(matches!(
+        lbs::from_slice::<StatusV1>(&lbs::to_vec(&StatusV2::Active).unwrap()),
+        Err(LBSError::UnexpectedVariant)
+    ));
+
+    let mut level = Level::High;
+    level
+        .lbs_read_into(&mut lbs::to_vec(&Level::Low).unwrap().as_slice())
+        .unwrap();
+    assert_eq!(level, Level::Low);
+
+    let compact_old = [1u8];
+    assert_eq!(lbs::from_slice::<Level>(&compact_old).unwrap(), Level::Low);
+    assert!(matches!(
+        lbs::from_slice::<Level>(&[3u8]),
+        Err(LBSError::UnexpectedVariant)
+    ));
+}
+
+#[tokio::test]
+async fn read_as_async() {
+    let old = lbs::to_vec(&StatusV1::Suspended("spam".to_string())).unwrap();
+    let read: StatusV2 = lbs::tokio::read::read(&mut old.as_slice()).await.unwrap();
+    assert_eq!(read, StatusV2::Suspended("spam".to_string()));
+}
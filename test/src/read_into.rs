@@ -0,0 +1,165 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Message {
+    #[lbs(id(0))]
+    topic: String,
+    #[lbs(id(1))]
+    payload: Vec<u8>,
+    #[lbs(id(2))]
+    headers: Vec<Header>,
+    #[lbs(id(3), optional)]
+    key: Option<String>,
+    #[lbs(id(4), optional, default(7))]
+    retries: u8,
+    #[lbs(id(5), optional)]
+    kind: Kind,
+    #[lbs(skip)]
+    scratch: Vec<u8>,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Header {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    value: String,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Text(String),
+    #[lbs(id(2))]
+    Number(u64),
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(dyn_io, compact, pack_bools)]
+struct Dyn {
+    #[lbs(id(0))]
+    names: Vec<String>,
+    #[lbs(id(1))]
+    a: bool,
+    #[lbs(id(2))]
+    b: bool,
+}
+
+#[derive(LBSWrite, Debug, Clone, Default, PartialEq)]
+struct Partial {
+    #[lbs(id(0))]
+    topic: String,
+    #[lbs(id(1))]
+    payload: Vec<u8>,
+    #[lbs(id(2))]
+    headers: Vec<Header>,
+}
+
+fn message(n: usize) -> Message {
+    Message {
+        topic: "t".repeat(n),
+        payload: vec![1; n],
+        headers: (0..n)
+            .map(|i| Header {
+                name: format!("name-{}", i),
+                value: "v".repeat(i),
+            })
+            .collect(),
+        key: Some("k".repeat(n)),
+        retries: n as u8,
+        kind: Kind::Text("x".repeat(n)),
+        scratch: vec![],
+    }
+}
+
+#[test]
+fn read_into_matches_read() {
+    let mut value = message(64);
+    value.scratch = vec![1, 2, 3];
+
+    for n in [3, 0, 10] {
+        let expected = message(n);
+        let mut buf = Vec::new();
+        expected.lbs_write(&mut buf).unwrap();
+
+        value.lbs_read_into(&mut buf.as_slice()).unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(value, Message::lbs_read(&mut buf.as_slice()).unwrap());
+    }
+}
+
+#[test]
+fn read_into_keeps_capacity() {
+    let mut value = message(64);
+    let topic_capacity = value.topic.capacity();
+    let payload_capacity = value.payload.capacity();
+    let header_capacity = value.headers[10].name.capacity();
+
+    let mut buf = Vec::new();
+    message(16).lbs_write(&mut buf).unwrap();
+    value.lbs_read_into(&mut buf.as_slice()).unwrap();
+
+    assert_eq!(value, message(16));
+    assert_eq!(value.topic.capacity(), topic_capacity);
+    assert_eq!(value.payload.capacity(), payload_capacity);
+    assert_eq!(value.headers[10].name.capacity(), header_capacity);
+    assert_eq!(value.headers.capacity(), 64);
+}
+
+#[test]
+fn read_into_resets_absent_fields() {
+    let mut value = message(8);
+    value.scratch = vec![1];
+
+    let partial = Partial {
+        topic: "a".to_string(),
+        payload: vec![2],
+        headers: vec![],
+    };
+
+    let mut buf = Vec::new();
+    partial.lbs_write(&mut buf).unwrap();
+    value.lbs_read_into(&mut buf.as_slice()).unwrap();
+
+    assert_eq!(
+        value,
+        Message {
+            topic: "a".to_string(),
+            payload: vec![2],
+            retries: 7,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn read_into_checks_required() {
+    let mut value = message(8);
+
+    // Struct without fields
+    assert!(value.lbs_read_into(&mut [0u8, 0].as_slice()).is_err());
+}
+
+#[test]
+fn read_into_dyn_io() {
+    let expected = Dyn {
+        names: vec!["a".to_string(), "b".to_string()],
+        a: false,
+        b: true,
+    };
+
+    let mut buf = Vec::new();
+    expected.lbs_write(&mut buf).unwrap();
+
+    let mut value = Dyn {
+        names: vec!["c".to_string(); 5],
+        a: true,
+        b: false,
+    };
+    value.lbs_read_into(&mut buf.as_slice()).unwrap();
+    assert_eq!(value, expected);
+}
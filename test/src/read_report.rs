@@ -0,0 +1,71 @@
+use lbs::read::read_with_report;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(pack_bools)]
+struct Profile {
+    #[lbs(id(0), optional)]
+    name: String,
+    #[lbs(id(1))]
+    age: u8,
+    #[lbs(id(2))]
+    verified: bool,
+    #[lbs(id(3))]
+    bio: Option<String>,
+}
+
+/// Patch, which only carries fields to change.
+#[derive(LBSWrite, Default)]
+#[lbs(pack_bools)]
+struct ProfilePatch {
+    #[lbs(id(1))]
+    age: u8,
+    #[lbs(id(2))]
+    verified: bool,
+}
+
+#[test]
+fn read_report() {
+    let mut profile = Profile {
+        name: "alice".to_string(),
+        age: 30,
+        verified: true,
+        bio: Some("hi".to_string()),
+    };
+
+    // Patch sets age and clears verification, both to default values
+    let mut buf = Vec::new();
+    ProfilePatch::default().lbs_write(&mut buf).unwrap();
+
+    let (patch, present) = read_with_report::<Profile, _>(&mut buf.as_slice()).unwrap();
+    assert_eq!(present.iter().collect::<Vec<_>>(), vec![1, 2]);
+    assert!(!present.contains(0));
+
+    if present.contains(0) {
+        profile.name = patch.name;
+    }
+    if present.contains(1) {
+        profile.age = patch.age;
+    }
+    if present.contains(2) {
+        profile.verified = patch.verified;
+    }
+
+    assert_eq!(
+        profile,
+        Profile {
+            name: "alice".to_string(),
+            age: 0,
+            verified: false,
+            bio: Some("hi".to_string()),
+        }
+    );
+
+    // Types other than structs report nothing
+    let mut buf = Vec::new();
+    7u32.lbs_write(&mut buf).unwrap();
+    let (value, present) = read_with_report::<u32, _>(&mut buf.as_slice()).unwrap();
+    assert_eq!(value, 7);
+    assert!(present.is_empty());
+}
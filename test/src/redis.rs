@@ -0,0 +1,39 @@
+use lbs::redis::LBSBlob;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use redis::FromRedisValue;
+use redis::ToRedisArgs;
+use redis::Value;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Session {
+    #[lbs(id(0))]
+    user_id: u64,
+    #[lbs(id(1))]
+    token: String,
+}
+
+#[test]
+fn redis() {
+    let session = Session {
+        user_id: 42,
+        token: "secret".to_string(),
+    };
+
+    let args = LBSBlob(session.clone()).to_redis_args();
+    assert_eq!(args.len(), 1);
+
+    let mut expected = Vec::new();
+    session.lbs_write(&mut expected).unwrap();
+    assert_eq!(args[0], expected);
+
+    let fetched = LBSBlob::<Session>::from_redis_value(Value::BulkString(expected)).unwrap();
+    assert_eq!(fetched.0, session);
+
+    assert_eq!(
+        Option::<LBSBlob<Session>>::from_redis_value(Value::Nil).unwrap(),
+        None
+    );
+    assert!(LBSBlob::<Session>::from_redis_value(Value::BulkString(vec![1])).is_err());
+    assert!(LBSBlob::<Session>::from_redis_value(Value::Int(1)).is_err());
+}
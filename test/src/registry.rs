@@ -0,0 +1,110 @@
+use lbs::registry::Registry;
+use lbs::registry::RegistryClient;
+use lbs::schema::FieldSchema;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Arc;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default)]
+#[lbs(compact)]
+struct Event {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    tags: Option<Vec<String>>,
+    #[lbs(id(2), sparse)]
+    values: Vec<Option<f64>>,
+    #[lbs(id(3))]
+    kind: Kind,
+    #[lbs(id(4))]
+    tree: Tree,
+    #[lbs(id(5), skip)]
+    cache: HashMap<u64, u64>,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Count(u32),
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default)]
+struct Tree {
+    #[lbs(id(0))]
+    children: Vec<Tree>,
+}
+
+#[test]
+fn schema() {
+    let Schema::Struct(event) = Event::lbs_schema() else {
+        panic!("struct schema expected");
+    };
+
+    assert_eq!(event.name, "Event");
+    assert!(event.compact);
+    assert_eq!(event.fields.len(), 5);
+    assert_eq!(
+        event.fields[1],
+        FieldSchema {
+            id: 1,
+            name: "tags".into(),
+            required: false,
+            codec: None,
+            schema: Schema::Option(Box::new(Schema::List(Box::new(Schema::String)))),
+        }
+    );
+    assert_eq!(event.fields[2].codec.as_deref(), Some("sparse"));
+    assert!(
+        matches!(event.fields[3].schema, Schema::Enum(ref e) if e.variants[1].schema == Some(Schema::U32))
+    );
+
+    // Recursive types refer to themselves by name
+    let Schema::Struct(ref tree) = event.fields[4].schema else {
+        panic!("struct schema expected");
+    };
+    assert_eq!(
+        tree.fields[0].schema,
+        Schema::List(Box::new(Schema::Ref("Tree".into())))
+    );
+
+    // Descriptors are encodable and fingerprints are stable
+    let schema = Event::lbs_schema();
+    let mut buf = Vec::new();
+    schema.lbs_write(&mut buf).unwrap();
+    assert_eq!(Schema::lbs_read(&mut buf.as_slice()).unwrap(), schema);
+    assert_eq!(schema.fingerprint(), Event::lbs_schema().fingerprint());
+    assert_ne!(schema.fingerprint(), Kind::lbs_schema().fingerprint());
+}
+
+#[test]
+fn registry() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let registry = Arc::new(Registry::new());
+    let server = registry.clone();
+    std::thread::spawn(move || server.serve(&listener));
+
+    // Producer registers its schemas at startup
+    let mut producer = RegistryClient::connect(addr).unwrap();
+    let fingerprint = producer.register::<Event>().unwrap();
+    assert_eq!(fingerprint, Event::lbs_schema().fingerprint());
+    assert_eq!(producer.register::<Event>().unwrap(), fingerprint);
+    assert!(registry.get(fingerprint).is_some());
+
+    // Consumer fetches unknown ones
+    let mut consumer = RegistryClient::connect(addr).unwrap();
+    assert!(consumer.cached(fingerprint).is_none());
+    assert_eq!(
+        consumer.fetch(fingerprint).unwrap(),
+        Some(&Event::lbs_schema())
+    );
+    assert!(consumer.cached(fingerprint).is_some());
+    assert_eq!(consumer.fetch(1).unwrap(), None);
+}
@@ -0,0 +1,90 @@
+use lbs::decoder::Decoder;
+use lbs::encoder::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Batch {
+    #[lbs(id(0))]
+    events: Vec<Event>,
+    #[lbs(id(1), relative_time)]
+    flushed: Option<SystemTime>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Event {
+    #[lbs(id(0), relative_time, default(SystemTime::UNIX_EPOCH))]
+    at: SystemTime,
+    #[lbs(id(1))]
+    kind: u8,
+}
+
+fn encode<T: LBSWrite>(value: &T, e: &mut Encoder) -> Vec<u8> {
+    let mut buf = Vec::new();
+    e.encode(value, &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn relative_time() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let offsets = [0, 3, 1, 250, 250, 1000];
+
+    let batch = Batch {
+        events: offsets
+            .iter()
+            .enumerate()
+            .map(|(i, &ms)| Event {
+                at: start + Duration::from_millis(ms),
+                kind: i as u8,
+            })
+            .collect(),
+        flushed: Some(start + Duration::from_secs(2)),
+    };
+
+    let mut e = Encoder::new();
+    let buf = encode(&batch, &mut e);
+    let mut d = Decoder::new();
+    assert_eq!(d.decode::<Batch, _>(&mut buf.as_slice()).unwrap(), batch);
+
+    // Base is reset for every message, so reused encoders and decoders agree
+    assert_eq!(encode(&batch, &mut e), buf);
+    assert_eq!(d.decode::<Batch, _>(&mut buf.as_slice()).unwrap(), batch);
+
+    // First timestamp is relative to the epoch, the rest take a few bytes instead of 12
+    let times: Vec<_> = batch.events.iter().map(|e| e.at).collect();
+    let mut times_buf = Vec::new();
+    lbs::relative_time::write(&times, &mut times_buf, &mut Encoder::new()).unwrap();
+    assert!(times_buf.len() <= 1 + 9 + 5 * (times.len() - 1));
+    assert!(times_buf.len() < times.len() * 12 / 2);
+}
+
+#[test]
+fn relative_time_extremes() {
+    let far = SystemTime::UNIX_EPOCH + Duration::from_secs(200 * 365 * 24 * 3600);
+    let before = SystemTime::UNIX_EPOCH - Duration::from_secs(3600);
+
+    let batch = Batch {
+        events: [far, before, before, far]
+            .into_iter()
+            .map(|at| Event { at, kind: 0 })
+            .collect(),
+        flushed: None,
+    };
+
+    let mut e = Encoder::new();
+    let buf = encode(&batch, &mut e);
+    assert_eq!(Batch::lbs_read(&mut buf.as_slice()).unwrap(), batch);
+}
+
+#[test]
+fn relative_time_invalid() {
+    let mut buf = Vec::new();
+    lbs::varint::write_u64(&mut buf, 3).unwrap();
+    assert!(matches!(
+        lbs::relative_time::read::<SystemTime, _>(&mut buf.as_slice(), &mut Decoder::new()),
+        Err(lbs::error::LBSError::InvalidTimestamp)
+    ));
+}
@@ -0,0 +1,141 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+/// Types of another crate, which implement no lbs traits.
+mod foreign {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Version {
+        pub major: u16,
+        pub minor: u16,
+        pub pre: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Channel {
+        Stable,
+        Nightly(u32),
+    }
+}
+
+use foreign::Channel;
+use foreign::Version;
+
+#[derive(LBSWrite, LBSRead)]
+#[lbs(remote(Version))]
+struct VersionDef {
+    #[lbs(id(0))]
+    major: u16,
+    #[lbs(id(1))]
+    minor: u16,
+    #[lbs(id(2))]
+    pre: Vec<String>,
+}
+
+#[derive(LBSWrite, LBSRead)]
+#[lbs(remote(foreign::Channel))]
+enum ChannelDef {
+    #[lbs(id(0))]
+    Stable,
+    #[lbs(id(1))]
+    Nightly(u32),
+}
+
+#[derive(LBSWrite, LBSRead, Debug, PartialEq)]
+struct Release {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1), with(VersionDef), default(Version { major: 0, minor: 0, pre: Vec::new() }))]
+    version: Version,
+    #[lbs(id(2), with(ChannelDef), default(Channel::Stable))]
+    channel: Channel,
+}
+
+/// Same layout of local types.
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct LocalRelease {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    version: LocalVersion,
+    #[lbs(id(2))]
+    channel: LocalChannel,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct LocalVersion {
+    #[lbs(id(0))]
+    major: u16,
+    #[lbs(id(1))]
+    minor: u16,
+    #[lbs(id(2))]
+    pre: Vec<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+enum LocalChannel {
+    #[default]
+    #[lbs(id(0))]
+    Stable,
+    #[lbs(id(1))]
+    Nightly(u32),
+}
+
+fn release(channel: Channel) -> Release {
+    Release {
+        name: "lbs".to_string(),
+        version: Version {
+            major: 1,
+            minor: 4,
+            pre: vec!["rc".to_string()],
+        },
+        channel,
+    }
+}
+
+#[test]
+fn remote_roundtrip() {
+    for channel in [Channel::Stable, Channel::Nightly(20260101)] {
+        let value = release(channel);
+        let bytes = lbs::to_vec(&value).unwrap();
+        assert_eq!(bytes.len(), value.lbs_size_hint());
+        assert_eq!(lbs::from_slice::<Release>(&bytes).unwrap(), value);
+    }
+
+    let mut bytes = Vec::new();
+    let version = release(Channel::Stable).version;
+    VersionDef::write(&version, &mut bytes, &mut lbs::Encoder::new()).unwrap();
+    let read: Version = VersionDef::read(&mut bytes.as_slice(), &mut lbs::Decoder::new()).unwrap();
+    assert_eq!(read, version);
+}
+
+#[test]
+fn remote_encoding() {
+    // Foreign types are written like local types of the same layout
+    let bytes = lbs::to_vec(&release(Channel::Nightly(7))).unwrap();
+    let local = LocalRelease {
+        name: "lbs".to_string(),
+        version: LocalVersion {
+            major: 1,
+            minor: 4,
+            pre: vec!["rc".to_string()],
+        },
+        channel: LocalChannel::Nightly(7),
+    };
+    assert_eq!(bytes, lbs::to_vec(&local).unwrap());
+    assert_eq!(lbs::from_slice::<LocalRelease>(&bytes).unwrap(), local);
+}
+
+#[test]
+fn remote_projected() {
+    let bytes = lbs::to_vec(&release(Channel::Nightly(7))).unwrap();
+    let read =
+        Release::lbs_read_projected(&mut bytes.as_slice(), &mut lbs::Decoder::new(), &[0]).unwrap();
+    assert_eq!(read.name, "lbs");
+    assert_eq!(read.channel, Channel::Stable);
+
+    let read =
+        LocalRelease::lbs_read_projected(&mut bytes.as_slice(), &mut lbs::Decoder::new(), &[0])
+            .unwrap();
+    assert_eq!(read.name, "lbs");
+    assert_eq!(read.version, LocalVersion::default());
+}
@@ -0,0 +1,115 @@
+use lbs::rpc::Client;
+use lbs::rpc::RpcError;
+use lbs::rpc::Service;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::net::TcpListener;
+use std::time::Duration;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+enum Request {
+    #[default]
+    #[lbs(id(0))]
+    Ping,
+    #[lbs(id(1))]
+    Add((u64, u64)),
+    #[lbs(id(2))]
+    Fail(String),
+    #[lbs(id(3))]
+    Sleep(u64),
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+enum Response {
+    #[default]
+    #[lbs(id(0))]
+    Pong,
+    #[lbs(id(1))]
+    Sum(u64),
+}
+
+struct Calculator;
+
+impl Service for Calculator {
+    type Request = Request;
+    type Response = Response;
+}
+
+fn handle(request: Request) -> Result<Response, String> {
+    match request {
+        Request::Ping => Ok(Response::Pong),
+        Request::Add((a, b)) => Ok(Response::Sum(a + b)),
+        Request::Fail(message) => Err(message),
+        Request::Sleep(ms) => {
+            std::thread::sleep(Duration::from_millis(ms));
+            Ok(Response::Pong)
+        }
+    }
+}
+
+#[test]
+fn rpc_sync() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || lbs::rpc::serve::<Calculator, _>(&listener, handle));
+
+    let mut client = Client::<Calculator>::connect(addr).unwrap();
+    assert_eq!(client.call(&Request::Ping).unwrap(), Response::Pong);
+    assert_eq!(
+        client.call(&Request::Add((2, 3))).unwrap(),
+        Response::Sum(5)
+    );
+
+    let err = client.call(&Request::Fail("boom".into())).unwrap_err();
+    assert!(matches!(err, RpcError::Remote(message) if message == "boom"));
+
+    let err = client
+        .call_with_deadline(&Request::Sleep(200), Duration::from_millis(20))
+        .unwrap_err();
+    assert!(matches!(err, RpcError::DeadlineExceeded));
+
+    // Late response is dropped
+    assert_eq!(
+        client.call(&Request::Add((1, 1))).unwrap(),
+        Response::Sum(2)
+    );
+}
+
+#[tokio::test]
+async fn rpc_tokio() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        lbs::rpc::tokio::serve::<Calculator, _, _>(&listener, |request| async move {
+            match request {
+                Request::Sleep(ms) => {
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    Ok(Response::Pong)
+                }
+                other => handle(other),
+            }
+        })
+        .await
+    });
+
+    let mut client = lbs::rpc::tokio::Client::<Calculator>::connect(addr)
+        .await
+        .unwrap();
+    assert_eq!(
+        client.call(&Request::Add((2, 3))).await.unwrap(),
+        Response::Sum(5)
+    );
+
+    let err = client
+        .call(&Request::Fail("boom".into()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RpcError::Remote(message) if message == "boom"));
+
+    let err = client
+        .call_with_deadline(&Request::Sleep(200), Duration::from_millis(20))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RpcError::DeadlineExceeded));
+}
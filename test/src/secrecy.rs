@@ -0,0 +1,51 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use secrecy::ExposeSecret;
+use secrecy::Secret;
+use secrecy::SecretString;
+use secrecy::SecretVec;
+
+#[derive(LBSWrite, LBSRead)]
+#[lbs(expose_secrets)]
+struct Login {
+    #[lbs(id(0))]
+    user: String,
+    #[lbs(id(1), default(SecretString::new(String::new())))]
+    password: SecretString,
+    #[lbs(id(2))]
+    otp: Option<Secret<u32>>,
+    #[lbs(id(3))]
+    key: Option<SecretVec<u8>>,
+}
+
+#[test]
+fn secrecy() {
+    let login = Login {
+        user: "alice".to_string(),
+        password: SecretString::new("hunter2".to_string()),
+        otp: Some(Secret::new(123456)),
+        key: None,
+    };
+
+    let mut buf = Vec::new();
+    login.lbs_write(&mut buf).unwrap();
+    let Ok(decoded) = Login::lbs_read(&mut buf.as_slice()) else {
+        panic!("login isn't decoded");
+    };
+    assert_eq!(decoded.user, "alice");
+    assert_eq!(decoded.password.expose_secret(), "hunter2");
+    assert_eq!(
+        decoded.otp.as_ref().map(|s| *s.expose_secret()),
+        Some(123456)
+    );
+    assert!(decoded.key.is_none());
+
+    // Wire format is the same as of exposed values
+    let mut plain = Vec::new();
+    "hunter2".lbs_write(&mut plain).unwrap();
+    let mut buf = Vec::new();
+    lbs::secrecy::write(&login.password, &mut buf, &mut lbs::Encoder::new()).unwrap();
+    assert_eq!(buf, plain);
+
+    assert!(!format!("{:?}", decoded.password).contains("hunter2"));
+}
@@ -0,0 +1,62 @@
+use lbs::write::write_seq;
+use lbs::write::LBSWriteSeq;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite)]
+struct ExportOut {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    ids: LBSWriteSeq<Box<dyn Iterator<Item = u64>>>,
+}
+
+#[derive(LBSRead, PartialEq, Debug)]
+struct ExportIn {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    ids: Vec<u64>,
+}
+
+#[test]
+fn seq() {
+    // Field is written from an iterator and read as a Vec
+    let out = ExportOut {
+        name: "users".to_string(),
+        ids: LBSWriteSeq::new(
+            3,
+            Box::new((1u64..).map(|i| i * 10).take(3)) as Box<dyn Iterator<Item = u64>>,
+        ),
+    };
+
+    let mut bytes = Vec::new();
+    out.lbs_write(&mut bytes).unwrap();
+    assert_eq!(
+        ExportIn::lbs_read(&mut bytes.as_slice()).unwrap(),
+        ExportIn {
+            name: "users".to_string(),
+            ids: vec![10, 20, 30],
+        }
+    );
+
+    // Iterator is consumed
+    assert!(out.lbs_write(&mut Vec::new()).is_err());
+
+    // Same as a slice
+    let names = ["a".to_string(), "b".to_string()];
+    let mut seq = Vec::new();
+    write_seq(&mut seq, names.len(), names.iter()).unwrap();
+    let mut slice = Vec::new();
+    names.lbs_write(&mut slice).unwrap();
+    assert_eq!(seq, slice);
+
+    let exact = LBSWriteSeq::exact(names.iter());
+    let mut bytes = Vec::new();
+    exact.lbs_write(&mut bytes).unwrap();
+    assert_eq!(bytes, slice);
+
+    // Length must match
+    assert!(write_seq(&mut Vec::new(), 3, names.iter()).is_err());
+    assert!(write_seq(&mut Vec::new(), 1, names.iter()).is_err());
+}
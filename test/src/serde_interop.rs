@@ -0,0 +1,246 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Order {
+    id: u64,
+    customer: String,
+    note: Option<String>,
+    lines: Vec<Line>,
+    totals: BTreeMap<String, i64>,
+    state: State,
+    origin: (f32, f32),
+    #[serde(with = "serde_bytes_compat")]
+    signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Line(String, u32);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum State {
+    Open,
+    Paid(u64),
+    Shipped {
+        carrier: String,
+        tracking: Option<String>,
+    },
+    Refunded(String, i64),
+}
+
+/// Same layout, derived by lbs.
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct LBSOrder {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    customer: String,
+    #[lbs(id(2))]
+    note: Option<String>,
+    #[lbs(id(3))]
+    lines: Vec<LBSLine>,
+    #[lbs(id(4))]
+    totals: BTreeMap<String, i64>,
+    #[lbs(id(5))]
+    state: LBSState,
+    #[lbs(id(6))]
+    origin: (f32, f32),
+    #[lbs(id(7))]
+    signature: Vec<u8>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+struct LBSLine(String, u32);
+
+#[derive(LBSWrite, LBSRead, Default, Debug, Clone, PartialEq)]
+enum LBSState {
+    #[default]
+    #[lbs(id(0))]
+    Open,
+    #[lbs(id(1))]
+    Paid(u64),
+    #[lbs(id(2))]
+    Shipped {
+        #[lbs(id(0))]
+        carrier: String,
+        #[lbs(id(1))]
+        tracking: Option<String>,
+    },
+    #[lbs(id(3))]
+    Refunded(String, i64),
+}
+
+mod serde_bytes_compat {
+    use serde::Deserializer;
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        struct Bytes;
+
+        impl serde::de::Visitor<'_> for Bytes {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+        }
+
+        d.deserialize_byte_buf(Bytes)
+    }
+}
+
+fn order(state: State) -> Order {
+    Order {
+        id: 42,
+        customer: "alice".to_string(),
+        note: None,
+        lines: vec![Line("apple".to_string(), 3), Line("pear".to_string(), 1)],
+        totals: BTreeMap::from([("eur".to_string(), 1250), ("usd".to_string(), -3)]),
+        state,
+        origin: (1.5, -2.25),
+        signature: vec![0xde, 0xad],
+    }
+}
+
+fn lbs_order(state: LBSState) -> LBSOrder {
+    LBSOrder {
+        id: 42,
+        customer: "alice".to_string(),
+        note: None,
+        lines: vec![
+            LBSLine("apple".to_string(), 3),
+            LBSLine("pear".to_string(), 1),
+        ],
+        totals: BTreeMap::from([("eur".to_string(), 1250), ("usd".to_string(), -3)]),
+        state,
+        origin: (1.5, -2.25),
+        signature: vec![0xde, 0xad],
+    }
+}
+
+#[test]
+fn serde_roundtrip() {
+    let states = [
+        State::Open,
+        State::Paid(100),
+        State::Shipped {
+            carrier: "ups".to_string(),
+            tracking: Some("1Z".to_string()),
+        },
+        State::Refunded("damaged".to_string(), -100),
+    ];
+
+    for state in states {
+        let value = order(state);
+        let bytes = lbs::serde::to_vec(&value).unwrap();
+        assert_eq!(lbs::serde::from_slice::<Order>(&bytes).unwrap(), value);
+
+        let mut buf = Vec::new();
+        lbs::serde::to_writer(&mut buf, &value).unwrap();
+        assert_eq!(buf, bytes);
+        assert_eq!(
+            lbs::serde::from_reader::<Order, _>(&mut buf.as_slice()).unwrap(),
+            value
+        );
+    }
+}
+
+#[test]
+fn serde_interop_with_derive() {
+    let states = [
+        (State::Open, LBSState::Open),
+        (State::Paid(7), LBSState::Paid(7)),
+        (
+            State::Shipped {
+                carrier: "dhl".to_string(),
+                tracking: Some("x".to_string()),
+            },
+            LBSState::Shipped {
+                carrier: "dhl".to_string(),
+                tracking: Some("x".to_string()),
+            },
+        ),
+        (
+            State::Refunded("late".to_string(), 5),
+            LBSState::Refunded("late".to_string(), 5),
+        ),
+    ];
+
+    for (state, lbs_state) in states {
+        let value = order(state);
+        let lbs_value = lbs_order(lbs_state);
+
+        // Serde output is read by derived implementation
+        let bytes = lbs::serde::to_vec(&value).unwrap();
+        assert_eq!(lbs::from_slice::<LBSOrder>(&bytes).unwrap(), lbs_value);
+
+        // Derived output, which omits None fields, is deserialized by serde
+        let bytes = lbs::to_vec(&lbs_value).unwrap();
+        assert_eq!(lbs::serde::from_slice::<Order>(&bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn serde_unsupported() {
+    // Unknown fields can't be skipped without their type
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Narrow {
+        id: u64,
+    }
+
+    let bytes = lbs::serde::to_vec(&order(State::Open)).unwrap();
+    assert!(matches!(
+        lbs::serde::from_slice::<Narrow>(&bytes),
+        Err(LBSError::Parsing(_))
+    ));
+
+    assert!(matches!(
+        lbs::serde::from_slice::<serde_json_like::Any>(&bytes),
+        Err(LBSError::Parsing(_))
+    ));
+
+    let variant = lbs::to_vec(&9u16).unwrap();
+    assert!(matches!(
+        lbs::serde::from_slice::<State>(&variant),
+        Err(LBSError::UnexpectedVariant)
+    ));
+
+    let sequence = lbs::serde::to_vec(&Unsized);
+    assert!(matches!(sequence, Err(LBSError::Parsing(_))));
+}
+
+/// Sequence, which doesn't tell its length upfront.
+struct Unsized;
+
+impl Serialize for Unsized {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq((0..3).filter(|i| i % 2 == 0))
+    }
+}
+
+mod serde_json_like {
+    use serde::Deserialize;
+    use serde::Deserializer;
+
+    /// Value of any type, which needs a self-describing format.
+    #[derive(Debug)]
+    pub struct Any;
+
+    impl<'de> Deserialize<'de> for Any {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            d.deserialize_any(serde::de::IgnoredAny).map(|_| Any)
+        }
+    }
+}
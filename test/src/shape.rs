@@ -0,0 +1,89 @@
+use lbs::schema::check_shapes;
+use lbs::schema::ShapeCompatibility;
+
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+struct UserV1 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    kind: KindV1,
+}
+
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+struct UserV2 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    kind: KindV1,
+    #[lbs(id(3))]
+    email: Option<String>,
+    #[lbs(id(4), skip)]
+    cached: bool,
+}
+
+/// Retyped id and new required field.
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+struct UserV3 {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    kind: KindV1,
+    #[lbs(id(5))]
+    age: u8,
+}
+
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+enum KindV1 {
+    #[lbs(id(0))]
+    Person,
+    #[lbs(id(1))]
+    Bot(String),
+}
+
+#[derive(lbs::LBSSchema)]
+#[allow(dead_code)]
+enum KindV2 {
+    #[lbs(id(0))]
+    Person,
+    #[lbs(id(1))]
+    Bot(String),
+    #[lbs(id(2))]
+    Service,
+}
+
+lbs::assert_schema_compatible!(UserV1, UserV2);
+lbs::assert_schema_compatible!(KindV1, KindV2);
+
+#[test]
+fn shape_compatibility() {
+    assert_eq!(
+        check_shapes(&UserV2::__LBS_SHAPE, &UserV1::__LBS_SHAPE),
+        ShapeCompatibility::Dropped(3)
+    );
+    assert_eq!(
+        check_shapes(&UserV1::__LBS_SHAPE, &UserV3::__LBS_SHAPE),
+        ShapeCompatibility::Changed(0)
+    );
+    assert_eq!(
+        check_shapes(&UserV2::__LBS_SHAPE, &UserV2::__LBS_SHAPE),
+        ShapeCompatibility::Compatible
+    );
+    assert_eq!(
+        check_shapes(&KindV2::__LBS_SHAPE, &KindV1::__LBS_SHAPE),
+        ShapeCompatibility::Dropped(2)
+    );
+    assert_eq!(
+        check_shapes(&KindV1::__LBS_SHAPE, &UserV1::__LBS_SHAPE),
+        ShapeCompatibility::ContainerChanged
+    );
+}
@@ -0,0 +1,131 @@
+use lbs::Decoder;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::sync::Arc;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Dependency {
+    #[lbs(id(0))]
+    package: Arc<str>,
+    #[lbs(id(1))]
+    version: String,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Graph {
+    #[lbs(id(0))]
+    root: String,
+    #[lbs(id(1))]
+    dependencies: Vec<Dependency>,
+}
+
+#[test]
+fn shared_strings() {
+    let graph = Graph {
+        root: "serde".to_string(),
+        dependencies: (0..100)
+            .map(|i| Dependency {
+                package: Arc::from(["serde", "syn", "quote"][i % 3]),
+                version: "1.0".to_string(),
+            })
+            .collect(),
+    };
+
+    let mut plain = Vec::new();
+    graph.lbs_write(&mut plain).unwrap();
+
+    let mut e = Encoder::new();
+    e.share_strings(true);
+    let mut shared = Vec::new();
+    e.encode(&graph, &mut shared).unwrap();
+    assert!(shared.len() < plain.len() * 3 / 4);
+
+    // Same encoder produces the same bytes for the next message
+    let mut again = Vec::new();
+    e.encode(&graph, &mut again).unwrap();
+    assert_eq!(again, shared);
+
+    let mut d = Decoder::new();
+    d.share_strings(true);
+    let decoded: Graph = d.decode(&mut shared.as_slice()).unwrap();
+    assert_eq!(decoded, graph);
+    assert!(Arc::ptr_eq(
+        &decoded.dependencies[0].package,
+        &decoded.dependencies[3].package
+    ));
+
+    // Reference to undefined string
+    let mut bad = Vec::new();
+    (1u32 << 31 | 7).lbs_write(&mut bad).unwrap();
+    assert!(String::lbs_read_with(&mut bad.as_slice(), &mut d).is_err());
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Sample {
+    #[lbs(id(0))]
+    host: String,
+    #[lbs(id(1))]
+    metric: String,
+    #[lbs(id(2))]
+    value: f64,
+}
+
+fn sample(host: &str, value: f64) -> Sample {
+    Sample {
+        host: host.to_string(),
+        metric: "cpu.load".to_string(),
+        value,
+    }
+}
+
+#[test]
+fn shared_strings_dictionary() {
+    let mut e = Encoder::new();
+    e.dictionary(2);
+
+    let mut messages = Vec::new();
+
+    for (i, host) in ["db-1.example.com", "db-1.example.com", "db-2.example.com"]
+        .iter()
+        .enumerate()
+    {
+        let mut buf = Vec::new();
+        e.encode(&sample(host, i as f64), &mut buf).unwrap();
+        messages.push(buf);
+    }
+
+    // Strings of the first message are referenced by later ones, until the table is full
+    assert!(messages[1].len() < messages[0].len());
+    assert_eq!(messages[2].len(), messages[0].len() - "cpu.load".len());
+
+    let mut d = Decoder::new();
+    d.dictionary(2);
+
+    for (i, host) in ["db-1.example.com", "db-1.example.com", "db-2.example.com"]
+        .iter()
+        .enumerate()
+    {
+        let decoded: Sample = d.decode(&mut messages[i].as_slice()).unwrap();
+        assert_eq!(decoded, sample(host, i as f64));
+    }
+
+    // Later messages can't be read without the dictionary of the stream
+    let mut fresh = Decoder::new();
+    fresh.dictionary(2);
+    assert!(fresh
+        .decode::<Sample, _>(&mut messages[1].as_slice())
+        .is_err());
+
+    // Cleared tables start over on both sides
+    e.clear_shared();
+    d.clear_shared();
+    let mut buf = Vec::new();
+    e.encode(&sample("db-3.example.com", 3.0), &mut buf)
+        .unwrap();
+    assert_eq!(buf.len(), messages[0].len());
+    assert_eq!(
+        d.decode::<Sample, _>(&mut buf.as_slice()).unwrap(),
+        sample("db-3.example.com", 3.0)
+    );
+}
@@ -0,0 +1,91 @@
+use lbs::error::LBSError;
+use lbs::signing::SignedMessage;
+use lbs::signing::SigningKey;
+use lbs::signing::VerifyingKey;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Config {
+    #[lbs(id(0))]
+    replicas: u32,
+}
+
+/// Same fields as of `SignedMessage`, to forge arbitrary signatures.
+#[derive(LBSWrite)]
+struct Forged {
+    #[lbs(id(0))]
+    payload: Vec<u8>,
+    #[lbs(id(1))]
+    key_id: String,
+    #[lbs(id(2))]
+    signature: Vec<u8>,
+}
+
+#[test]
+fn signing() {
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let other = SigningKey::from_bytes(&[2; 32]);
+    let config = Config { replicas: 3 };
+
+    let signed = SignedMessage::sign(&config, "deploy-1", &key).unwrap();
+    let mut buf = Vec::new();
+    signed.lbs_write(&mut buf).unwrap();
+
+    let received = SignedMessage::<Config>::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(received.key_id(), "deploy-1");
+    assert_eq!(received.verify(&key.verifying_key()).unwrap(), config);
+
+    // Lookup by key id
+    let lookup = |id: &str| (id == "deploy-1").then(|| key.verifying_key());
+    assert_eq!(received.verify_with(lookup).unwrap(), config);
+
+    // Wrong key
+    assert!(matches!(
+        received.verify(&other.verifying_key()),
+        Err(LBSError::AuthenticationFailed)
+    ));
+
+    // Tampered payload: field header, payload length, then replicas after its own header
+    buf[8 + 4] ^= 1;
+    let tampered = SignedMessage::<Config>::lbs_read(&mut buf.as_slice()).unwrap();
+    assert!(matches!(
+        tampered.verify(&key.verifying_key()),
+        Err(LBSError::AuthenticationFailed)
+    ));
+
+    // Message signed by another key under a known key id
+    let forged = SignedMessage::sign(&config, "deploy-1", &other).unwrap();
+    assert!(forged.verify_with(lookup).is_err());
+    assert!(forged.verify_with(|_| None).is_err());
+}
+
+#[test]
+fn signing_weak_key() {
+    // Identity point as key and signature R with zero s, which are valid for any message
+    // unless verification is strict
+    let mut identity = [0; 32];
+    identity[0] = 1;
+    let key = VerifyingKey::from_bytes(&identity).unwrap();
+
+    let mut payload = Vec::new();
+    Config { replicas: 3 }.lbs_write(&mut payload).unwrap();
+
+    let mut signature = identity.to_vec();
+    signature.extend_from_slice(&[0; 32]);
+
+    let forged = Forged {
+        payload,
+        key_id: "deploy-1".to_string(),
+        signature,
+    };
+
+    let mut buf = Vec::new();
+    forged.lbs_write(&mut buf).unwrap();
+
+    let received = SignedMessage::<Config>::lbs_read(&mut buf.as_slice()).unwrap();
+    assert!(matches!(
+        received.verify(&key),
+        Err(LBSError::AuthenticationFailed)
+    ));
+}
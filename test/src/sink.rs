@@ -0,0 +1,71 @@
+use lbs::sink::LBSWriter;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Packet {
+    #[lbs(id(0))]
+    seq: u32,
+    #[lbs(id(1))]
+    body: String,
+}
+
+/// Backend, which only sums written bytes.
+#[derive(Default)]
+struct Checksum {
+    sum: u64,
+    len: usize,
+}
+
+impl LBSWriter for Checksum {
+    fn put(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.sum += bytes.iter().map(|b| *b as u64).sum::<u64>();
+        self.len += bytes.len();
+        Ok(())
+    }
+}
+
+#[test]
+fn sink() {
+    let packet = Packet {
+        seq: 1,
+        body: "hello".to_string(),
+    };
+
+    let mut expected = Vec::new();
+    packet.lbs_write(&mut expected).unwrap();
+
+    let mut vec = Vec::new();
+    lbs::sink::write(&packet, &mut vec).unwrap();
+    assert_eq!(vec, expected);
+
+    // Slice is advanced past written bytes
+    let mut buf = [0u8; 64];
+    let mut slice = &mut buf[..];
+    lbs::sink::write(&packet, &mut slice).unwrap();
+    let rest = slice.len();
+    assert_eq!(&buf[..64 - rest], &expected[..]);
+
+    let mut small = [0u8; 4];
+    let err = lbs::sink::write(&packet, &mut &mut small[..]).unwrap_err();
+    assert!(
+        matches!(err, lbs::error::LBSError::Io(ref e) if e.kind() == std::io::ErrorKind::WriteZero)
+    );
+
+    let mut bytes = bytes::BytesMut::new();
+    lbs::sink::write(&packet, &mut bytes).unwrap();
+    assert_eq!(&bytes[..], &expected[..]);
+
+    let mut io = lbs::sink::Io(std::io::Cursor::new(Vec::new()));
+    lbs::sink::write(&packet, &mut io).unwrap();
+    assert_eq!(io.0.into_inner(), expected);
+
+    // New backends only implement put()
+    let mut checksum = Checksum::default();
+    lbs::sink::write_with(&packet, &mut checksum, &mut lbs::Encoder::new()).unwrap();
+    assert_eq!(checksum.len, expected.len());
+    assert_eq!(
+        checksum.sum,
+        expected.iter().map(|b| *b as u64).sum::<u64>()
+    );
+}
@@ -0,0 +1,146 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    items: Vec<Item>,
+    #[lbs(id(2))]
+    note: Option<String>,
+    #[lbs(id(3))]
+    tags: BTreeMap<String, u32>,
+    #[lbs(id(4))]
+    state: State,
+    #[lbs(skip)]
+    cached: Vec<u8>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct Item(String, u16, f32);
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+enum State {
+    #[default]
+    #[lbs(id(0))]
+    New,
+    #[lbs(id(1))]
+    Paid(Duration),
+    #[lbs(id(2))]
+    Shipped {
+        #[lbs(id(0))]
+        to: Option<IpAddr>,
+        #[lbs(id(1))]
+        parcels: Vec<u8>,
+    },
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(compact, pack_bools)]
+struct Flags {
+    #[lbs(id(0))]
+    count: u32,
+    #[lbs(id(1))]
+    a: bool,
+    #[lbs(id(9))]
+    b: bool,
+    #[lbs(id(300), varint)]
+    total: u64,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+#[lbs(indexed)]
+struct Indexed {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    values: Vec<i64>,
+}
+
+fn order() -> Order {
+    Order {
+        id: 7,
+        items: vec![
+            Item("apple".to_string(), 3, 0.5),
+            Item("pear".to_string(), 1, 1.25),
+        ],
+        note: Some("fragile".to_string()),
+        tags: BTreeMap::from([("gift".to_string(), 1)]),
+        state: State::Shipped {
+            to: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            parcels: vec![1, 2, 3],
+        },
+        cached: vec![0; 100],
+    }
+}
+
+fn assert_exact<T: LBSWrite + ?Sized>(value: &T) {
+    let bytes = lbs::to_vec(value).unwrap();
+    assert_eq!(value.lbs_size_hint(), bytes.len());
+    assert_eq!(bytes.capacity(), bytes.len());
+}
+
+#[test]
+fn size_hint_builtin() {
+    assert_exact(&0u8);
+    assert_exact(&-1i128);
+    assert_exact(&1.5f64);
+    assert_exact(&'x');
+    assert_exact("text");
+    assert_exact(&vec![1u32, 2, 3]);
+    assert_exact(&vec![0u8; 1000]);
+    assert_exact(&Some(vec![true, false]));
+    assert_exact(&None::<String>);
+    assert_exact(&(1u8, "a".to_string(), Duration::from_secs(1)));
+    assert_exact(&HashSet::from([1u16, 2, 3]));
+    assert_exact(&BTreeMap::from([("k".to_string(), vec![1i8])]));
+    assert_exact(&IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_exact(&Box::new(3u64));
+}
+
+#[test]
+fn size_hint_derived() {
+    assert_exact(&order());
+    assert_exact(&Order::default());
+    assert_exact(&State::Paid(Duration::from_millis(5)));
+    assert_exact(&Indexed {
+        name: "series".to_string(),
+        values: vec![1, 2, 3],
+    });
+
+    // Compact ids and fields with custom encoding aren't counted exactly
+    let flags = Flags {
+        count: 1,
+        a: true,
+        b: false,
+        total: 1 << 40,
+    };
+    let len = lbs::to_vec(&flags).unwrap().len();
+    assert!(flags.lbs_size_hint() <= len + 2 * lbs::size::VARINT_U16_MAX_SIZE);
+    assert!(flags.lbs_size_hint() > 0);
+}
+
+#[test]
+fn to_vec_from_slice() {
+    let order = order();
+    let bytes = lbs::to_vec(&order).unwrap();
+
+    let mut expected = Vec::new();
+    order.lbs_write(&mut expected).unwrap();
+    assert_eq!(bytes, expected);
+
+    let read: Order = lbs::from_slice(&bytes).unwrap();
+    assert_eq!(
+        read,
+        Order {
+            cached: Vec::new(),
+            ..order
+        }
+    );
+}
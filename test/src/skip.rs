@@ -0,0 +1,111 @@
+use lbs::diff::LBSDiff;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, lbs::LBSDiff, Clone, PartialEq, Debug)]
+struct SkippedWithoutId {
+    #[lbs(id(0))]
+    f0: u64,
+    #[lbs(skip)]
+    cached: Option<String>,
+    #[lbs(skip, default(7))]
+    hits: u32,
+    #[lbs(id(1))]
+    f1: String,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(pack_bools)]
+struct PackedWithoutId {
+    #[lbs(id(0))]
+    f0: bool,
+    #[lbs(skip)]
+    cached: bool,
+    #[lbs(id(1))]
+    f1: bool,
+}
+
+fn message() -> SkippedWithoutId {
+    SkippedWithoutId {
+        f0: 1,
+        cached: Some("cached".to_string()),
+        hits: 3,
+        f1: "f1".to_string(),
+    }
+}
+
+#[test]
+fn skip_without_id() {
+    let msg = message();
+    let mut buf = Vec::with_capacity(128);
+    msg.lbs_write(&mut buf).unwrap();
+
+    let decoded = SkippedWithoutId::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(
+        decoded,
+        SkippedWithoutId {
+            cached: None,
+            hits: 7,
+            ..msg
+        }
+    );
+}
+
+#[test]
+fn skip_without_id_pack_bools() {
+    let msg = PackedWithoutId {
+        f0: true,
+        cached: true,
+        f1: true,
+    };
+
+    let mut buf = Vec::with_capacity(128);
+    msg.lbs_write(&mut buf).unwrap();
+
+    let decoded = PackedWithoutId::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(
+        decoded,
+        PackedWithoutId {
+            cached: false,
+            ..msg
+        }
+    );
+}
+
+#[test]
+fn skip_without_id_schema() {
+    let Schema::Struct(schema) = SkippedWithoutId::lbs_schema() else {
+        panic!("struct schema expected");
+    };
+
+    assert_eq!(schema.fields.len(), 2);
+    assert_eq!(
+        (schema.fields[0].id, schema.fields[0].name.as_str()),
+        (0, "f0")
+    );
+    assert_eq!(
+        (schema.fields[1].id, schema.fields[1].name.as_str()),
+        (1, "f1")
+    );
+}
+
+#[test]
+fn skip_without_id_diff() {
+    let a = message();
+    let mut b = a.clone();
+    b.cached = None;
+    b.hits = 4;
+
+    let mut patch = Vec::new();
+    assert!(!a.lbs_diff(&b, &mut patch).unwrap());
+
+    b.f1 = "changed".to_string();
+    assert!(a.lbs_diff(&b, &mut patch).unwrap());
+
+    let mut c = a.clone();
+    c.lbs_apply(&mut patch.as_slice()).unwrap();
+    assert_eq!(c.f1, b.f1);
+    assert_eq!((c.cached, c.hits), (a.cached, a.hits));
+}
@@ -0,0 +1,68 @@
+use lbs::read::read_field_count;
+use lbs::read::read_field_id;
+use lbs::read::skip_value;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::HashMap;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default)]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    items: Vec<(String, u32)>,
+    #[lbs(id(2))]
+    notes: Option<String>,
+    #[lbs(id(3))]
+    total: u64,
+}
+
+/// Hand-written reader, which only needs the total and doesn't depend on other fields.
+fn read_total(mut bytes: &[u8], schema: &Schema) -> u64 {
+    let Schema::Struct(order) = schema else {
+        unreachable!()
+    };
+
+    let mut total = 0;
+
+    for _ in 0..read_field_count(&mut bytes).unwrap() {
+        match read_field_id(&mut bytes).unwrap() {
+            3 => total = u64::lbs_read(&mut bytes).unwrap(),
+            id => {
+                let field = order.fields.iter().find(|f| f.id == id).unwrap();
+                skip_value(&mut bytes, &field.schema).unwrap();
+            }
+        }
+    }
+
+    assert!(bytes.is_empty());
+    total
+}
+
+#[test]
+fn skip_value_by_schema() {
+    let order = Order {
+        id: 7,
+        items: vec![("apple".to_string(), 3), ("pear".to_string(), 1)],
+        notes: Some("ring twice".to_string()),
+        total: 420,
+    };
+
+    let mut buf = Vec::new();
+    order.lbs_write(&mut buf).unwrap();
+    assert_eq!(read_total(&buf, &Order::lbs_schema()), 420);
+}
+
+#[test]
+fn skip_value_by_len() {
+    let mut buf = Vec::new();
+    HashMap::from([(1u8, 2u16)]).lbs_write(&mut buf).unwrap();
+    42u8.lbs_write(&mut buf).unwrap();
+
+    let mut bytes = buf.as_slice();
+    skip_value(&mut bytes, buf.len() - 1).unwrap();
+    assert_eq!(u8::lbs_read(&mut bytes).unwrap(), 42);
+    assert!(skip_value(&mut bytes, 1).is_err());
+}
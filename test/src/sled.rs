@@ -0,0 +1,42 @@
+use lbs::sled::from_ivec;
+use lbs::sled::to_ivec;
+use lbs::sled::LBSTree;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Job {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    attempts: u8,
+}
+
+#[test]
+fn sled() {
+    let job = |name: &str| Job {
+        name: name.to_string(),
+        attempts: 0,
+    };
+
+    let ivec = to_ivec(&job("a")).unwrap();
+    assert_eq!(from_ivec::<Job>(&ivec).unwrap(), job("a"));
+
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let queue = LBSTree::<(u8, u8), Job>::open(&db, "queue").unwrap();
+    assert!(queue.is_empty());
+
+    assert_eq!(queue.insert(&(0, 2), &job("b")).unwrap(), None);
+    assert_eq!(queue.insert(&(0, 1), &job("a")).unwrap(), None);
+    assert_eq!(queue.insert(&(0, 1), &job("c")).unwrap(), Some(job("a")));
+    assert_eq!(queue.get(&(0, 1)).unwrap(), Some(job("c")));
+    assert!(queue.contains_key(&(0, 2)).unwrap());
+    assert_eq!(queue.len(), 2);
+
+    let entries: Vec<_> = queue.iter().map(Result::unwrap).collect();
+    assert_eq!(entries, [((0, 1), job("c")), ((0, 2), job("b"))]);
+
+    assert_eq!(queue.pop_min().unwrap(), Some(((0, 1), job("c"))));
+    assert_eq!(queue.remove(&(0, 2)).unwrap(), Some(job("b")));
+    assert_eq!(queue.pop_min().unwrap(), None);
+}
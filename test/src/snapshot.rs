@@ -0,0 +1,53 @@
+use lbs::error::LBSError;
+use lbs::snapshot::Reader;
+use lbs::snapshot::Writer;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Account {
+    #[lbs(id(0))]
+    balance: i64,
+    #[lbs(id(1))]
+    owner: String,
+}
+
+const KEY_FINGERPRINT: u64 = 1;
+const VALUE_FINGERPRINT: u64 = 0xacc0;
+
+#[test]
+fn snapshot() {
+    let path = std::env::temp_dir().join(format!("lbs-snapshot-{}", std::process::id()));
+
+    let records: Vec<(u64, Account)> = (0..100)
+        .map(|i| {
+            (
+                i,
+                Account {
+                    balance: i as i64 * 10,
+                    owner: format!("owner{i}"),
+                },
+            )
+        })
+        .collect();
+
+    let mut w = Writer::create(&path, KEY_FINGERPRINT, VALUE_FINGERPRINT).unwrap();
+    for (k, v) in &records {
+        w.append(k, v).unwrap();
+    }
+    w.finish().unwrap();
+
+    let mut r = Reader::<u64, Account>::open(&path, KEY_FINGERPRINT, VALUE_FINGERPRINT).unwrap();
+    assert_eq!(r.len(), 100);
+    assert_eq!(r.header().count, 100);
+    assert_eq!(r.read_at(42).unwrap(), records[42]);
+    assert_eq!(r.read_at(7).unwrap(), records[7]);
+
+    let all = r.iter().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(all, records);
+
+    let err = Reader::<u64, Account>::open(&path, KEY_FINGERPRINT, 0).unwrap_err();
+    assert!(matches!(err, LBSError::SchemaMismatch));
+
+    std::fs::remove_file(&path).unwrap();
+}
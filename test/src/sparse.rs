@@ -0,0 +1,40 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Batch {
+    #[lbs(id(0), sparse)]
+    column: Vec<Option<u32>>,
+    #[lbs(id(1), sparse)]
+    tags: HashMap<String, Option<String>>,
+    #[lbs(id(2), sparse)]
+    ordered: BTreeMap<u8, Option<u8>>,
+}
+
+#[test]
+fn sparse() {
+    let original = Batch {
+        column: (0..20)
+            .map(|i| if i % 3 == 0 { Some(i) } else { None })
+            .collect(),
+        tags: [
+            ("a".to_string(), None),
+            ("b".to_string(), Some("b".to_string())),
+        ]
+        .into(),
+        ordered: [(1, Some(1)), (2, None), (3, Some(3))].into(),
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    assert_eq!(Batch::lbs_read(&mut buf.as_slice()).unwrap(), original);
+
+    let mut sparse = Vec::new();
+    lbs::sparse::write(&original.column, &mut sparse, &mut lbs::Encoder::new()).unwrap();
+    let mut regular = Vec::new();
+    original.column.lbs_write(&mut regular).unwrap();
+    // 3 bytes of bitmap instead of 20 option tags
+    assert_eq!(sparse.len() + 17, regular.len());
+}
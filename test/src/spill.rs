@@ -0,0 +1,66 @@
+use lbs::spill::to_spill_with;
+use lbs::spill::SpillWriter;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Read;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+struct Export {
+    #[lbs(id(0))]
+    rows: Vec<String>,
+}
+
+fn export(n: usize) -> Export {
+    Export {
+        rows: (0..n).map(|i| format!("row-{}", i)).collect(),
+    }
+}
+
+#[test]
+fn spill_small_in_memory() {
+    let mut spilled = to_spill_with(&export(3), &mut Encoder::new(), 1024).unwrap();
+    assert!(!spilled.is_spilled());
+    assert!(spilled.path().is_none());
+
+    let mut buf = Vec::new();
+    spilled.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf.len() as u64, spilled.len());
+    assert_eq!(Export::lbs_read(&mut buf.as_slice()).unwrap(), export(3));
+}
+
+#[test]
+fn spill_large_to_file() {
+    let value = export(10_000);
+    let mut spilled = to_spill_with(&value, &mut Encoder::new(), 1024).unwrap();
+    assert!(spilled.is_spilled());
+
+    let path = spilled.path().unwrap().to_path_buf();
+    assert!(path.exists());
+
+    let decoded = Export::lbs_read(&mut spilled).unwrap();
+    assert_eq!(decoded, value);
+
+    drop(spilled);
+    assert!(!path.exists());
+}
+
+#[test]
+fn spill_writer_threshold() {
+    let dir = std::env::temp_dir();
+    let mut w = SpillWriter::in_dir(16, &dir);
+
+    export(0).lbs_write(&mut w).unwrap();
+    assert!(!w.is_spilled());
+
+    export(1).lbs_write(&mut w).unwrap();
+    assert!(w.is_spilled());
+
+    let mut buf = Vec::new();
+    w.finish().unwrap().read_to_end(&mut buf).unwrap();
+
+    let mut r = buf.as_slice();
+    assert_eq!(Export::lbs_read(&mut r).unwrap(), export(0));
+    assert_eq!(Export::lbs_read(&mut r).unwrap(), export(1));
+    assert!(r.is_empty());
+}
@@ -0,0 +1,54 @@
+use lbs::sqlx::LBSBlob;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use sqlx::Connection;
+use sqlx::SqliteConnection;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone, Default)]
+struct Settings {
+    #[lbs(id(0))]
+    theme: String,
+    #[lbs(id(1))]
+    volume: u8,
+}
+
+#[tokio::test]
+async fn sqlx() {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, settings BLOB NOT NULL)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let settings = Settings {
+        theme: "dark".to_string(),
+        volume: 7,
+    };
+
+    sqlx::query("INSERT INTO users (id, settings) VALUES (?, ?)")
+        .bind(1)
+        .bind(LBSBlob(settings.clone()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let (fetched,): (LBSBlob<Settings>,) =
+        sqlx::query_as("SELECT settings FROM users WHERE id = ?")
+            .bind(1)
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+    assert_eq!(fetched.into_inner(), settings);
+
+    // Garbage in the column fails to decode
+    sqlx::query("UPDATE users SET settings = x'0100'")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    assert!(
+        sqlx::query_as::<_, (LBSBlob<Settings>,)>("SELECT settings FROM users")
+            .fetch_one(&mut conn)
+            .await
+            .is_err()
+    );
+}
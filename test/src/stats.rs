@@ -0,0 +1,128 @@
+use lbs::from_slice_with_stats;
+use lbs::Decoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeSet;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(indexed)]
+struct OrderV2 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    items: Vec<String>,
+    #[lbs(id(2))]
+    coupon: String,
+    #[lbs(id(3))]
+    channel: u8,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(indexed)]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    items: Vec<String>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Batch {
+    #[lbs(id(0))]
+    orders: Vec<Order>,
+}
+
+#[test]
+fn stats() {
+    let order = OrderV2 {
+        id: 1,
+        items: vec!["apple".to_string(), "pear".to_string()],
+        coupon: "SALE".to_string(),
+        channel: 2,
+    };
+
+    let mut buf = Vec::new();
+    order.lbs_write(&mut buf).unwrap();
+
+    let (decoded, stats) = from_slice_with_stats::<Order>(&buf).unwrap();
+    assert_eq!(decoded.items, order.items);
+    assert_eq!(stats.bytes, buf.len());
+    assert_eq!(stats.fields, 4);
+    assert_eq!(stats.unknown_fields, 2);
+    assert!(stats.has_unknown_fields());
+
+    let name = std::any::type_name::<Order>();
+    assert_eq!(stats.unknown_ids, BTreeSet::from([(name, 2), (name, 3)]));
+
+    // Vector and two strings
+    assert_eq!(stats.allocations, 3);
+
+    // Nested structs are counted too
+    let batch = Batch {
+        orders: vec![decoded],
+    };
+    let mut buf = Vec::new();
+    batch.lbs_write(&mut buf).unwrap();
+
+    let (_, stats) = from_slice_with_stats::<Batch>(&buf).unwrap();
+    assert_eq!(stats.fields, 3);
+    assert!(!stats.has_unknown_fields());
+
+    // Decoder collects statistics until they are taken
+    let mut d = Decoder::new();
+    assert!(d.take_stats().is_none());
+
+    d.collect_stats();
+    let _: Batch = d.decode(&mut buf.as_slice()).unwrap();
+    let _: Batch = d.decode(&mut buf.as_slice()).unwrap();
+    assert_eq!(d.take_stats().unwrap().fields, 6);
+    assert!(d.take_stats().is_none());
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(pack_bools)]
+struct Flags {
+    #[lbs(id(0))]
+    active: bool,
+    #[lbs(id(1))]
+    level: u8,
+    #[lbs(id(2))]
+    admin: bool,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(pack_bools)]
+struct FlagsV2 {
+    #[lbs(id(0))]
+    active: bool,
+    #[lbs(id(1))]
+    level: u8,
+    #[lbs(id(2))]
+    admin: bool,
+    #[lbs(id(4))]
+    banned: bool,
+}
+
+#[test]
+fn stats_packed_bools() {
+    // Bit of the non-bool field in between packed bools isn't an unknown field
+    let flags = Flags {
+        active: true,
+        level: 3,
+        admin: true,
+    };
+    let (decoded, stats) = from_slice_with_stats::<Flags>(&lbs::to_vec(&flags).unwrap()).unwrap();
+    assert_eq!(decoded, flags);
+    assert!(!stats.has_unknown_fields());
+
+    // Bools beyond known ones are
+    let flags = FlagsV2 {
+        active: true,
+        level: 3,
+        admin: true,
+        banned: false,
+    };
+    let (_, stats) = from_slice_with_stats::<Flags>(&lbs::to_vec(&flags).unwrap()).unwrap();
+    let name = std::any::type_name::<Flags>();
+    assert_eq!(stats.unknown_ids, BTreeSet::from([(name, 3), (name, 4)]));
+}
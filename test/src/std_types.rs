@@ -0,0 +1,205 @@
+use lbs::error::LBSError;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::size::LBSMaxSize;
+use lbs::tokio::LBSReadAsync as _;
+use lbs::LBSRead;
+use lbs::LBSReadAsync;
+use lbs::LBSWrite;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fmt::Debug;
+use std::num::NonZeroI64;
+use std::num::NonZeroU32;
+use std::num::NonZeroU64;
+use std::ops::Bound;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(LBSWrite, LBSRead, LBSReadAsync, Default, Debug, PartialEq)]
+struct Job {
+    #[lbs(id(0))]
+    digest: [u8; 4],
+    #[lbs(id(1))]
+    queue: VecDeque<String>,
+    #[lbs(id(2))]
+    workdir: PathBuf,
+    #[lbs(id(3))]
+    program: OsString,
+    #[lbs(id(4), optional)]
+    user: Option<NonZeroU32>,
+    #[lbs(id(5), optional)]
+    outcome: Option<Result<u64, String>>,
+    #[lbs(id(6), optional)]
+    since: Option<Bound<u64>>,
+    #[lbs(id(7), optional)]
+    retries: Option<RangeInclusive<u8>>,
+}
+
+fn job() -> Job {
+    let mut queue = VecDeque::new();
+    queue.push_back("b".to_string());
+    queue.push_front("a".to_string());
+
+    Job {
+        digest: [1, 2, 3, 4],
+        queue,
+        workdir: PathBuf::from("/var/lib/jobs"),
+        program: OsString::from("worker"),
+        user: NonZeroU32::new(1000),
+        outcome: Some(Err("killed".to_string())),
+        since: Some(Bound::Excluded(5)),
+        retries: Some(1..=3),
+    }
+}
+
+fn roundtrip<T: LBSWrite + LBSRead + PartialEq + Debug>(value: T) {
+    let bytes = lbs::to_vec(&value).unwrap();
+    assert_eq!(bytes.len(), value.lbs_size_hint());
+    assert_eq!(lbs::from_slice::<T>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn std_types_roundtrip() {
+    roundtrip([7u32, 8, 9]);
+    roundtrip([[1u8; 2]; 3]);
+    roundtrip(["x".to_string(), "y".to_string()]);
+    roundtrip::<[u8; 0]>([]);
+    roundtrip::<Result<u8, String>>(Ok(1));
+    roundtrip::<Result<u8, String>>(Err("no".to_string()));
+    roundtrip(VecDeque::from(vec![1u16, 2, 3]));
+    roundtrip(NonZeroU64::MAX);
+    roundtrip(NonZeroI64::new(-1).unwrap());
+    roundtrip(PathBuf::from("a/b.txt"));
+    roundtrip(OsString::from("name"));
+    roundtrip(Bound::Included(1i32));
+    roundtrip(Bound::<i32>::Unbounded);
+    roundtrip(-1i8..=1);
+    roundtrip(job());
+}
+
+#[test]
+fn std_types_encoding() {
+    // Arrays, deques, paths and inclusive ranges are written as vectors, strings and ranges
+    assert_eq!(
+        lbs::to_vec(&[1u8, 2, 3]).unwrap(),
+        lbs::to_vec(&vec![1u8, 2, 3]).unwrap()
+    );
+    let mut deque = VecDeque::from(vec![2u8, 3]);
+    deque.push_front(1);
+    assert_eq!(
+        lbs::to_vec(&deque).unwrap(),
+        lbs::to_vec(&vec![1u8, 2, 3]).unwrap()
+    );
+    assert_eq!(
+        lbs::to_vec(Path::new("a/b")).unwrap(),
+        lbs::to_vec("a/b").unwrap()
+    );
+    assert_eq!(
+        lbs::to_vec(&(1u8..=2)).unwrap(),
+        lbs::to_vec(&(1u8..2)).unwrap()
+    );
+    assert_eq!(
+        lbs::to_vec(&NonZeroU32::new(5).unwrap()).unwrap(),
+        lbs::to_vec(&5u32).unwrap()
+    );
+
+    assert_eq!(
+        <[u16; 3] as LBSSchema>::lbs_schema(),
+        Schema::List(Box::new(Schema::U16))
+    );
+    assert_eq!(
+        <VecDeque<u8> as LBSSchema>::lbs_schema(),
+        Vec::<u8>::lbs_schema()
+    );
+    assert_eq!(PathBuf::lbs_schema(), Schema::String);
+    assert_eq!(<[u32; 4] as LBSMaxSize>::LBS_MAX_SIZE, 20);
+    assert_eq!(<Result<u8, u64> as LBSMaxSize>::LBS_MAX_SIZE, 10);
+    assert_eq!(<Bound<u16> as LBSMaxSize>::LBS_MAX_SIZE, 4);
+}
+
+#[test]
+fn std_types_invalid() {
+    let zero = lbs::to_vec(&0u32).unwrap();
+    assert!(matches!(
+        lbs::from_slice::<NonZeroU32>(&zero),
+        Err(LBSError::OutOfRange("NonZeroU32"))
+    ));
+
+    let variant = lbs::to_vec(&2u16).unwrap();
+    assert!(matches!(
+        lbs::from_slice::<Result<u8, u8>>(&variant),
+        Err(LBSError::UnexpectedVariant)
+    ));
+
+    let short = lbs::to_vec(&[1u8, 2]).unwrap();
+    assert!(matches!(
+        lbs::from_slice::<[u8; 3]>(&short),
+        Err(LBSError::Parsing(_))
+    ));
+}
+
+#[tokio::test]
+async fn std_types_option_tag() {
+    assert_eq!(lbs::from_slice::<Option<u8>>(&[0]).unwrap(), None);
+    assert_eq!(lbs::from_slice::<Option<u8>>(&[1, 7]).unwrap(), Some(7));
+
+    // Tags other than 0 and 1 aren't read as None
+    let bytes = [2, 7];
+
+    assert!(invalid(lbs::from_slice::<Option<u8>>(&bytes[..1])));
+    assert!(invalid(lbs::borrow::from_slice::<Option<&str>>(
+        &bytes[..1]
+    )));
+    assert!(invalid(
+        <Option<u8>>::lbs_read_async(&mut &bytes[..1], &mut lbs::Decoder::new()).await
+    ));
+    assert!(invalid(lbs::varint::read::<Option<u32>, _>(
+        &mut &bytes[..1],
+        &mut lbs::Decoder::new()
+    )));
+    assert!(invalid(lbs::dynamic::Value::read(
+        &mut &bytes[..],
+        &<Option<u8>>::lbs_schema()
+    )));
+    assert!(invalid(<Option<u8>>::lbs_skip(
+        &mut &bytes[..],
+        &mut lbs::Decoder::new()
+    )));
+}
+
+fn invalid<T>(result: Result<T, LBSError>) -> bool {
+    matches!(result, Err(LBSError::Parsing(_)))
+}
+
+#[cfg(unix)]
+#[test]
+fn std_types_non_utf8_path() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(std::ffi::OsStr::from_bytes(&[0x66, 0xff]));
+    assert!(matches!(lbs::to_vec(path), Err(LBSError::Parsing(_))));
+}
+
+#[test]
+fn std_types_in_place() {
+    let mut digest = [9u8; 4];
+    digest
+        .lbs_read_into(&mut lbs::to_vec(&[1u8, 2, 3, 4]).unwrap().as_slice())
+        .unwrap();
+    assert_eq!(digest, [1, 2, 3, 4]);
+
+    let mut deque = VecDeque::from(vec!["a".to_string(); 5]);
+    deque
+        .lbs_read_into(&mut lbs::to_vec(&vec!["b".to_string(); 2]).unwrap().as_slice())
+        .unwrap();
+    assert_eq!(deque, VecDeque::from(vec!["b".to_string(); 2]));
+}
+
+#[tokio::test]
+async fn std_types_async() {
+    let bytes = lbs::to_vec(&job()).unwrap();
+    let read: Job = lbs::tokio::read::read(&mut bytes.as_slice()).await.unwrap();
+    assert_eq!(read, job());
+}
@@ -0,0 +1,42 @@
+use lbs::error::LBSError;
+use lbs::from_slice_with;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use lbs::Options;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Flags {
+    #[lbs(id(0))]
+    enabled: bool,
+    #[lbs(id(1))]
+    visible: Option<bool>,
+}
+
+#[test]
+fn strict_bools() {
+    assert!(!bool::lbs_read(&mut [0u8].as_slice()).unwrap());
+    assert!(bool::lbs_read(&mut [1u8].as_slice()).unwrap());
+    assert!(matches!(
+        bool::lbs_read(&mut [2u8].as_slice()),
+        Err(LBSError::InvalidBool(2))
+    ));
+
+    let flags = Flags {
+        enabled: true,
+        visible: Some(false),
+    };
+    let mut buf = Vec::new();
+    flags.lbs_write(&mut buf).unwrap();
+    assert_eq!(Flags::lbs_read(&mut buf.as_slice()).unwrap(), flags);
+
+    // Garbage of a misbehaving producer is reported with the field it was found in
+    let at = buf.iter().rposition(|&b| b == 0).unwrap();
+    buf[at] = 0xff;
+    let err = Flags::lbs_read(&mut buf.as_slice()).unwrap_err();
+    assert!(matches!(err.root_cause(), LBSError::InvalidBool(0xff)));
+    assert_eq!(err.field_path(), [1]);
+
+    // Lenient decoding keeps treating such bytes as false
+    let decoded: Flags = from_slice_with(&buf, Options::new().lenient_bools(true)).unwrap();
+    assert_eq!(decoded, flags);
+}
@@ -0,0 +1,53 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[test]
+fn system_time_before_epoch() {
+    let times = [
+        SystemTime::UNIX_EPOCH - Duration::new(300_000_000, 123_456_789),
+        SystemTime::UNIX_EPOCH - Duration::from_nanos(1),
+        SystemTime::UNIX_EPOCH,
+        SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 5),
+    ];
+
+    for time in times {
+        let mut buf = Vec::new();
+        time.lbs_write(&mut buf).unwrap();
+        assert_eq!(SystemTime::lbs_read(&mut buf.as_slice()).unwrap(), time);
+    }
+
+    // Time after the epoch is encoded as duration since the epoch
+    let mut time_buf = Vec::new();
+    times[3].lbs_write(&mut time_buf).unwrap();
+    let mut duration_buf = Vec::new();
+    Duration::new(1_700_000_000, 5)
+        .lbs_write(&mut duration_buf)
+        .unwrap();
+    assert_eq!(time_buf, duration_buf);
+}
+
+#[test]
+fn system_time_invalid_nanos() {
+    // Nanoseconds carried into maximal seconds would overflow
+    let mut buf = Vec::new();
+    u64::MAX.lbs_write(&mut buf).unwrap();
+    1_000_000_000u32.lbs_write(&mut buf).unwrap();
+
+    assert!(matches!(
+        SystemTime::lbs_read(&mut buf.as_slice()),
+        Err(LBSError::Parsing(_))
+    ));
+    assert!(matches!(
+        Duration::lbs_read(&mut buf.as_slice()),
+        Err(LBSError::Parsing(_))
+    ));
+
+    buf[11] |= 0x80;
+    assert!(matches!(
+        SystemTime::lbs_read(&mut buf.as_slice()),
+        Err(LBSError::Parsing(_))
+    ));
+}
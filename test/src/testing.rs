@@ -0,0 +1,265 @@
+use arbitrary::Arbitrary;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::testing::check_compatible;
+use lbs::testing::check_roundtrip;
+use lbs::testing::roundtrip_fuzz;
+use lbs::testing::Compatibility;
+use lbs::LBSRead;
+use lbs::LBSSchema;
+use lbs::LBSWrite;
+use proptest::prelude::any;
+use proptest::strategy::Strategy;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, Arbitrary, PartialEq, Debug, Default)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Named(String),
+}
+
+#[derive(LBSWrite, LBSRead, Arbitrary, PartialEq, Debug)]
+struct Record {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    kind: Kind,
+    #[lbs(id(2))]
+    tags: Vec<u8>,
+    #[lbs(id(3))]
+    labels: BTreeMap<u16, Option<bool>>,
+}
+
+#[test]
+fn testing_roundtrip_fuzz() {
+    let mut seed = 0x2545f4914f6cdd1du64;
+
+    for len in 0..256 {
+        let bytes: Vec<u8> = (0..len)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+
+        roundtrip_fuzz::<Record>(&bytes);
+    }
+
+    // Valid encoding is decoded and checked too
+    let mut buf = Vec::new();
+    Record {
+        id: 1,
+        kind: Kind::Named("a".to_string()),
+        tags: vec![1, 2],
+        labels: [(1, None), (2, Some(true))].into(),
+    }
+    .lbs_write(&mut buf)
+    .unwrap();
+    roundtrip_fuzz::<Record>(&buf);
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct UserV1 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct UserV2 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    email: Option<String>,
+}
+
+fn user_v1() -> impl Strategy<Value = UserV1> {
+    (any::<u64>(), ".*").prop_map(|(id, name)| UserV1 { id, name })
+}
+
+#[test]
+fn testing_proptest() {
+    check_roundtrip(user_v1());
+    check_roundtrip(
+        (any::<u64>(), ".*", proptest::option::of("[a-z]+@[a-z]+"))
+            .prop_map(|(id, name, email)| UserV2 { id, name, email }),
+    );
+
+    // New reader understands old data, old reader understands new data without new fields
+    check_compatible::<_, UserV2>(user_v1());
+    check_compatible::<_, UserV1>(user_v1().prop_map(|UserV1 { id, name }| UserV2 {
+        id,
+        name,
+        email: None,
+    }));
+
+    // Removing a required field breaks old readers
+    let result = std::panic::catch_unwind(|| {
+        check_compatible::<_, UserV2>(any::<u64>().prop_map(|id| Id { id }))
+    });
+    assert!(result.is_err());
+}
+
+#[derive(LBSWrite, Debug)]
+struct Id {
+    #[lbs(id(0))]
+    id: u64,
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct UserV3 {
+    #[lbs(id(0))]
+    id: u32,
+    #[lbs(id(2))]
+    email: String,
+    #[lbs(id(3))]
+    admin: bool,
+}
+
+#[test]
+fn testing_assertions() {
+    lbs::assert_roundtrip!(UserV2 {
+        id: 1,
+        name: "a".to_string(),
+        email: Some("a@b".to_string()),
+    });
+
+    lbs::assert_compatible!(UserV1, UserV2);
+    lbs::assert_compatible!(
+        UserV1,
+        UserV2,
+        UserV1::default(),
+        UserV1 {
+            id: 1,
+            name: "a".to_string(),
+        }
+    );
+    assert!(std::panic::catch_unwind(|| lbs::assert_compatible!(UserV2, UserV1)).is_err());
+
+    let (Schema::Struct(v2), Schema::Struct(v3)) = (UserV2::lbs_schema(), UserV3::lbs_schema())
+    else {
+        unreachable!()
+    };
+
+    let compatibility = Compatibility::new(&v2, &v3);
+    assert_eq!(compatibility.dropped, [1]);
+    assert_eq!(compatibility.defaulted, [] as [u16; 0]);
+    assert_eq!(compatibility.missing, [3]);
+    assert_eq!(compatibility.changed, [0, 2]);
+    assert!(!compatibility.is_compatible());
+    assert_eq!(
+        compatibility.to_string(),
+        "dropped [1], defaulted [], missing [3], changed [0, 2]"
+    );
+}
+
+#[test]
+fn testing_golden() {
+    let path = std::env::temp_dir().join(format!("lbs-golden-{}/user_v2.bin", std::process::id()));
+    let user = UserV2 {
+        id: 7,
+        name: "golden".to_string(),
+        email: Some("g@h".to_string()),
+    };
+
+    lbs::testing::write_golden(&path, &user).unwrap();
+    lbs::testing::check_golden(&path, &user);
+
+    let changed = UserV2 {
+        name: "silver".to_string(),
+        ..user
+    };
+    let message = std::panic::catch_unwind(|| lbs::testing::check_golden(&path, &changed))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(message.contains("-    name: \"golden\",\n+    name: \"silver\",\n"));
+    assert!(!message.contains("id: 7"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+#[lbs(pack_bools, compact)]
+struct Order {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    note: Option<String>,
+    #[lbs(id(2))]
+    paid: bool,
+    #[lbs(id(3))]
+    lines: Vec<(String, i16, f32)>,
+    #[lbs(id(4))]
+    totals: BTreeMap<u8, i128>,
+    #[lbs(id(5))]
+    state: State,
+    #[lbs(id(6))]
+    tree: Tree,
+    #[lbs(id(7), optional)]
+    window: std::ops::Range<u32>,
+    #[lbs(id(8))]
+    at: Option<std::time::SystemTime>,
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+enum State {
+    #[default]
+    #[lbs(id(0))]
+    Open,
+    #[lbs(id(1))]
+    Shipped {
+        #[lbs(id(0))]
+        carrier: char,
+    },
+    #[lbs(id(2))]
+    Failed(Option<std::net::IpAddr>, std::time::Duration),
+}
+
+/// Recursive type, which generated values must end.
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct Tree {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    children: Vec<Tree>,
+}
+
+/// Type, which fields can't be generated without their codecs.
+#[derive(LBSWrite, LBSRead, LBSSchema, PartialEq, Debug, Default)]
+struct Encoded {
+    #[lbs(id(0), varint)]
+    n: u64,
+}
+
+#[test]
+fn testing_gen_payload() {
+    let schema = Order::lbs_schema();
+    let mut distinct = std::collections::HashSet::new();
+
+    for seed in 0..200 {
+        let payload = lbs::testing::gen_payload(&schema, seed).unwrap();
+        // Maps and omitted optional fields may be written differently by derived implementation
+        lbs::read::from_slice_exact::<Order>(&payload).unwrap();
+        assert_eq!(lbs::testing::gen_payload(&schema, seed).unwrap(), payload);
+        distinct.insert(payload);
+    }
+
+    assert!(distinct.len() > 190);
+
+    let value = lbs::testing::gen_value(&schema, 7).unwrap();
+    assert_eq!(value.to_typed::<Order>().unwrap(), {
+        let payload = lbs::testing::gen_payload(&schema, 7).unwrap();
+        lbs::from_slice::<Order>(&payload).unwrap()
+    });
+
+    assert!(lbs::testing::gen_payload(&Encoded::lbs_schema(), 0).is_err());
+}
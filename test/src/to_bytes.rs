@@ -0,0 +1,25 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Message {
+    #[lbs(id(0))]
+    topic: String,
+    #[lbs(id(1))]
+    body: Vec<u8>,
+}
+
+#[test]
+fn to_bytes() {
+    let message = Message {
+        topic: "news".to_string(),
+        body: vec![7; 300],
+    };
+
+    let bytes = lbs::to_bytes(&message).unwrap();
+    let mut expected = Vec::new();
+    message.lbs_write(&mut expected).unwrap();
+
+    assert_eq!(bytes, expected);
+    assert_eq!(Message::lbs_read(&mut bytes.as_ref()).unwrap(), message);
+}
@@ -0,0 +1,100 @@
+use bytes::BytesMut;
+use lbs::error::LBSError;
+use lbs::frame::Framing;
+use lbs::tokio_util::LBSCodec;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use tokio_stream::StreamExt;
+use tokio_util::codec::Decoder;
+use tokio_util::codec::Encoder;
+use tokio_util::codec::FramedRead;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, PartialEq)]
+struct Event {
+    #[lbs(id(0))]
+    seq: u64,
+    #[lbs(id(1))]
+    payload: String,
+}
+
+fn event(seq: u64) -> Event {
+    Event {
+        seq,
+        payload: "x".repeat(seq as usize),
+    }
+}
+
+fn checked() -> LBSCodec<Event> {
+    let mut framing = Framing::new();
+    framing.checksum(true);
+    LBSCodec::with_framing(framing)
+}
+
+#[test]
+fn tokio_util_codec() {
+    let mut codec = checked();
+    let mut buf = BytesMut::new();
+    codec.encode(event(1), &mut buf).unwrap();
+    codec.encode(&event(2), &mut buf).unwrap();
+
+    // Same frames as written by framing itself
+    let mut expected = Vec::new();
+    let first = codec.framing().write(&mut expected, &event(1)).unwrap();
+    codec.framing().write(&mut expected, &event(2)).unwrap();
+    assert_eq!(&buf[..], &expected[..]);
+
+    // Frames are decoded only once they are whole
+    let mut partial = BytesMut::new();
+    for (i, byte) in expected.iter().enumerate() {
+        partial.extend_from_slice(&[*byte]);
+        let decoded = codec.decode(&mut partial).unwrap();
+        if i + 1 == first {
+            assert_eq!(decoded, Some(event(1)));
+        } else if i + 1 == expected.len() {
+            assert_eq!(decoded, Some(event(2)));
+        } else {
+            assert_eq!(decoded, None);
+        }
+    }
+    assert!(partial.is_empty());
+
+    // Corrupted payload
+    let last = buf.len() - 1;
+    buf[last] ^= 1;
+    codec.decode(&mut buf).unwrap();
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(LBSError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn tokio_util_codec_limit() {
+    let mut framing = Framing::new();
+    framing.max_len(8);
+    let mut codec = LBSCodec::<Event>::with_framing(framing);
+
+    // Oversized frame fails as soon as its header is buffered
+    let mut frame = Vec::new();
+    Framing::new().write(&mut frame, &event(10)).unwrap();
+    let mut buf = BytesMut::from(&frame[..8]);
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(LBSError::FrameTooLarge { .. })
+    ));
+}
+
+#[tokio::test]
+async fn tokio_util_framed() {
+    let mut buf = BytesMut::new();
+    let mut codec = checked();
+    for seq in 0..5 {
+        codec.encode(event(seq), &mut buf).unwrap();
+    }
+
+    let mut frames = FramedRead::new(&buf[..], checked());
+    for seq in 0..5 {
+        assert_eq!(frames.next().await.unwrap().unwrap(), event(seq));
+    }
+    assert!(frames.next().await.is_none());
+}
@@ -0,0 +1,60 @@
+use lbs::tonic::LBSCodec;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use tonic::codec::Codec;
+use tonic::codec::EncodeBody;
+use tonic::codec::Streaming;
+use tonic::Code;
+use tonic::Status;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct HelloRequest {
+    #[lbs(id(0))]
+    name: String,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Clone)]
+struct Greeting {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    times: u32,
+}
+
+#[tokio::test]
+async fn tonic() {
+    let requests = vec![
+        HelloRequest {
+            name: "Alice".to_string(),
+        },
+        HelloRequest {
+            name: "Bob".to_string(),
+        },
+    ];
+
+    // Client side encodes requests, server side decodes them
+    let mut client = LBSCodec::<HelloRequest, Greeting>::default();
+    let mut server = LBSCodec::<Greeting, HelloRequest>::default();
+
+    let source = tokio_stream::iter(requests.clone().into_iter().map(Ok::<_, Status>));
+    let body = EncodeBody::new_client(client.encoder(), source, None, None);
+    let mut streaming = Streaming::new_request(server.decoder(), body, None, None);
+
+    for expected in requests {
+        assert_eq!(streaming.message().await.unwrap(), Some(expected));
+    }
+
+    assert_eq!(streaming.message().await.unwrap(), None);
+
+    // Message of another type is rejected
+    let source = tokio_stream::iter([Ok::<_, Status>(Greeting {
+        name: "Alice".to_string(),
+        times: 3,
+    })]);
+    let body = EncodeBody::new_client(server.encoder(), source, None, None);
+    let mut streaming = Streaming::new_request(server.decoder(), body, None, None);
+    assert_eq!(
+        streaming.message().await.unwrap_err().code(),
+        Code::Internal
+    );
+}
@@ -0,0 +1,62 @@
+use lbs::Decoder;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Inner {
+    #[lbs(id(0))]
+    value: u64,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Outer {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    inner: Inner,
+}
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn tracing() {
+    let output = Output::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let mut buf = Vec::new();
+    let result = tracing::subscriber::with_default(subscriber, || {
+        Encoder::new().encode(&Outer::default(), &mut buf).unwrap();
+        Decoder::new().decode::<Outer, _>(&mut &buf[..buf.len() - 1])
+    });
+
+    let e = result.unwrap_err();
+    assert_eq!(e.field_path(), [1, 0]);
+    assert!(e.root_cause().is_eof());
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("lbs_encode{type_name=\"test::tracing::Outer\" bytes=22"));
+    assert!(output.contains("lbs_decode failed field_path=[1, 0]"));
+    assert!(output.contains("lbs_decode{type_name=\"test::tracing::Outer\" bytes=21"));
+}
@@ -0,0 +1,91 @@
+use lbs::tracked::Tracked;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Player {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    x: i32,
+    #[lbs(id(2))]
+    y: i32,
+    #[lbs(id(3))]
+    score: u64,
+    #[lbs(id(4))]
+    team: Option<String>,
+}
+
+#[test]
+fn tracked_sync() {
+    let mut local = Tracked::new(Player {
+        name: "p1".to_string(),
+        ..Player::default()
+    });
+
+    // Receiver starts from the whole value
+    let mut remote: Player = lbs::from_slice(&lbs::to_vec(&local).unwrap()).unwrap();
+    assert!(!local.is_dirty());
+
+    local.update(2, |p| p.y = 10);
+    local.update(1, |p| p.x = -3);
+    local.update(2, |p| p.y += 1);
+    assert_eq!(local.dirty(), &[1, 2]);
+
+    let mut patch = Vec::new();
+    assert!(local.write_changes(&mut patch).unwrap());
+    assert!(!local.is_dirty());
+    remote.lbs_merge(&mut patch.as_slice()).unwrap();
+    assert_eq!(&remote, local.get());
+
+    // Only changed fields are sent
+    let mut full = Player::default();
+    full.lbs_merge(&mut patch.as_slice()).unwrap();
+    assert_eq!(full.name, "");
+    assert_eq!((full.x, full.y), (-3, 11));
+
+    // Nothing is written without changes
+    let mut empty = Vec::new();
+    assert!(!local.write_changes(&mut empty).unwrap());
+    assert!(empty.is_empty());
+
+    local.mark(3);
+    local.mark(0);
+    assert_eq!(local.dirty(), &[0, 3]);
+    local.clear();
+    assert!(!local.is_dirty());
+}
+
+#[test]
+fn tracked_wire_format() {
+    let mut tracked = Tracked::from(Player {
+        score: 7,
+        ..Player::default()
+    });
+    tracked.update(3, |p| p.score = 8);
+
+    let bytes = lbs::to_vec(&tracked).unwrap();
+    assert_eq!(bytes, lbs::to_vec(tracked.get()).unwrap());
+    assert!(tracked.is_dirty());
+
+    let read: Tracked<Player> = lbs::from_slice(&bytes).unwrap();
+    assert!(!read.is_dirty());
+    assert_eq!(read.score, 8);
+    assert_eq!(read.into_inner(), tracked.into_inner());
+}
+
+#[test]
+fn tracked_cleared_option() {
+    let mut local = Tracked::new(Player {
+        team: Some("red".to_string()),
+        ..Player::default()
+    });
+    let mut remote: Player = lbs::from_slice(&lbs::to_vec(&local).unwrap()).unwrap();
+
+    // Cleared value is sent as explicit None
+    local.update(4, |p| p.team = None);
+    let mut patch = Vec::new();
+    local.write_changes(&mut patch).unwrap();
+    remote.lbs_merge(&mut patch.as_slice()).unwrap();
+    assert_eq!(remote.team, None);
+}
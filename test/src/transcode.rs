@@ -0,0 +1,156 @@
+use lbs::error::LBSError;
+use lbs::schema::LBSSchema;
+use lbs::transcode::Transcoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Debug, Clone, PartialEq)]
+struct EventV1 {
+    #[lbs(id(0))]
+    seq: u64,
+    #[lbs(id(1))]
+    source: String,
+    #[lbs(id(2))]
+    samples: Vec<i32>,
+    #[lbs(id(3))]
+    level: Option<u16>,
+    #[lbs(id(4))]
+    kind: KindV1,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Debug, Clone, PartialEq)]
+enum KindV1 {
+    #[default]
+    #[lbs(id(0))]
+    Tick,
+    #[lbs(id(1))]
+    Alert(String),
+}
+
+/// Same fields in the compact revision of the format.
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Debug, Clone, PartialEq)]
+#[lbs(compact)]
+struct EventV2 {
+    #[lbs(id(0), varint)]
+    seq: u64,
+    #[lbs(id(1), varint)]
+    source: String,
+    #[lbs(id(2), varint)]
+    samples: Vec<i32>,
+    #[lbs(id(3), varint)]
+    level: Option<u16>,
+    #[lbs(id(4))]
+    kind: KindV2,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, Default, Debug, Clone, PartialEq)]
+#[lbs(compact)]
+enum KindV2 {
+    #[default]
+    #[lbs(id(0))]
+    Tick,
+    #[lbs(id(1))]
+    Alert(String),
+}
+
+fn events() -> (Vec<EventV1>, Vec<EventV2>) {
+    let v1 = (0..3)
+        .map(|i| EventV1 {
+            seq: i,
+            source: format!("sensor-{}", i),
+            samples: vec![-1, 0, 300],
+            level: (i % 2 == 0).then_some(7),
+            kind: match i {
+                1 => KindV1::Alert("hot".to_string()),
+                _ => KindV1::Tick,
+            },
+        })
+        .collect();
+    let v2 = (0..3)
+        .map(|i| EventV2 {
+            seq: i,
+            source: format!("sensor-{}", i),
+            samples: vec![-1, 0, 300],
+            level: (i % 2 == 0).then_some(7),
+            kind: match i {
+                1 => KindV2::Alert("hot".to_string()),
+                _ => KindV2::Tick,
+            },
+        })
+        .collect();
+    (v1, v2)
+}
+
+fn stream<T: LBSWrite>(values: &[T]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    values.iter().for_each(|v| v.lbs_write(&mut buf).unwrap());
+    buf
+}
+
+#[test]
+fn transcode_stream() {
+    let (v1, v2) = events();
+    let old = stream(&v1);
+    let transcoder = Transcoder::new(EventV1::lbs_schema(), EventV2::lbs_schema());
+
+    let mut new = Vec::new();
+    let count = transcoder
+        .transcode_stream(&mut old.as_slice(), &mut new)
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(new, stream(&v2));
+    assert!(new.len() < old.len());
+
+    let mut back = Vec::new();
+    transcoder
+        .reversed()
+        .transcode_stream(&mut new.as_slice(), &mut back)
+        .unwrap();
+    assert_eq!(back, old);
+
+    let mut single = Vec::new();
+    transcoder
+        .transcode(&mut lbs::to_vec(&v1[1]).unwrap().as_slice(), &mut single)
+        .unwrap();
+    assert_eq!(lbs::from_slice::<EventV2>(&single).unwrap(), v2[1]);
+}
+
+#[test]
+fn transcode_tee() {
+    let (v1, v2) = events();
+    let old = stream(&v1);
+    let transcoder = Transcoder::new(EventV1::lbs_schema(), EventV2::lbs_schema());
+
+    let mut original = Vec::new();
+    let mut new = Vec::new();
+    transcoder
+        .tee_stream(&mut old.as_slice(), &mut original, &mut new)
+        .unwrap();
+    assert_eq!(original, old);
+    assert_eq!(new, stream(&v2));
+}
+
+#[test]
+fn transcode_invalid() {
+    let (v1, v2) = events();
+    let transcoder = Transcoder::new(EventV1::lbs_schema(), EventV2::lbs_schema());
+
+    // Truncated stream converts the whole values only
+    let old = stream(&v1);
+    let mut new = Vec::new();
+    let err = transcoder
+        .transcode_stream(&mut &old[..old.len() - 1], &mut new)
+        .unwrap_err();
+    assert!(err.is_eof());
+    assert_eq!(new, stream(&v2[..2]));
+
+    // Values out of range of the target revision fail
+    let narrow = Transcoder::new(u32::lbs_schema(), u16::lbs_schema());
+    let err = narrow
+        .transcode(
+            &mut lbs::to_vec(&70000u32).unwrap().as_slice(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+    assert!(matches!(err, LBSError::Parsing(_)));
+}
@@ -0,0 +1,59 @@
+use lbs::schema::LBSSchema;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use triomphe::Arc;
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug)]
+struct Snapshot {
+    #[lbs(id(0), default(Arc::from("")))]
+    name: Arc<str>,
+    #[lbs(id(1), default(Arc::from(Vec::new())))]
+    samples: Arc<[u32]>,
+    #[lbs(id(2))]
+    parent: Option<Arc<Snapshot>>,
+}
+
+#[derive(LBSWrite, lbs::LBSSchema)]
+struct Plain<'a> {
+    #[lbs(id(0))]
+    name: &'a str,
+    #[lbs(id(1))]
+    samples: &'a [u32],
+    #[lbs(id(2))]
+    parent: Option<&'a str>,
+}
+
+#[test]
+fn triomphe() {
+    let snapshot = Snapshot {
+        name: Arc::from("child"),
+        samples: Arc::from(vec![1, 2, 3]),
+        parent: Some(Arc::new(Snapshot {
+            name: Arc::from("root"),
+            samples: Arc::from(Vec::new()),
+            parent: None,
+        })),
+    };
+
+    let mut buf = Vec::new();
+    snapshot.lbs_write(&mut buf).unwrap();
+    assert_eq!(Snapshot::lbs_read(&mut buf.as_slice()).unwrap(), snapshot);
+
+    // Pointers are written as their values
+    let mut shared = Vec::new();
+    Arc::<str>::from("child").lbs_write(&mut shared).unwrap();
+    let mut plain = Vec::new();
+    "child".lbs_write(&mut plain).unwrap();
+    assert_eq!(shared, plain);
+
+    let mut buf = Vec::new();
+    Plain {
+        name: "child",
+        samples: &[1, 2, 3],
+        parent: None,
+    }
+    .lbs_write(&mut buf)
+    .unwrap();
+    let decoded = Snapshot::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(&*decoded.samples, &[1, 2, 3]);
+}
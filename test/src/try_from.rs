@@ -0,0 +1,118 @@
+use lbs::error::LBSError;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Email(String);
+
+impl TryFrom<String> for Email {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        match s.contains('@') {
+            true => Ok(Self(s)),
+            false => Err(format!("'{}' is not an email", s)),
+        }
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self {
+        email.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Port(u16);
+
+/// Conversion error, which can't be displayed.
+#[derive(Debug)]
+struct ZeroPort;
+
+impl TryFrom<u16> for Port {
+    type Error = ZeroPort;
+
+    fn try_from(port: u16) -> Result<Self, ZeroPort> {
+        match port {
+            0 => Err(ZeroPort),
+            port => Ok(Self(port)),
+        }
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(port: Port) -> Self {
+        port.0
+    }
+}
+
+#[derive(LBSWrite, LBSRead, Debug, PartialEq)]
+struct Account {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1), try_from(String), default(Email("nobody@localhost".to_string())))]
+    email: Email,
+    #[lbs(
+        id(2),
+        try_from(u16, error = "port must not be zero"),
+        default(Port(80))
+    )]
+    port: Port,
+}
+
+/// Same layout of proxies.
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct RawAccount {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    email: String,
+    #[lbs(id(2))]
+    port: u16,
+}
+
+fn raw(email: &str, port: u16) -> Vec<u8> {
+    lbs::to_vec(&RawAccount {
+        name: "alice".to_string(),
+        email: email.to_string(),
+        port,
+    })
+    .unwrap()
+}
+
+#[test]
+fn try_from_roundtrip() {
+    let value = Account {
+        name: "alice".to_string(),
+        email: Email("alice@example.com".to_string()),
+        port: Port(8080),
+    };
+    let bytes = lbs::to_vec(&value).unwrap();
+    assert_eq!(bytes, raw("alice@example.com", 8080));
+    assert_eq!(lbs::from_slice::<Account>(&bytes).unwrap(), value);
+
+    let read =
+        Account::lbs_read_projected(&mut bytes.as_slice(), &mut lbs::Decoder::new(), &[0]).unwrap();
+    assert_eq!(read.email, Email("nobody@localhost".to_string()));
+    assert_eq!(read.port, Port(80));
+}
+
+#[test]
+fn try_from_invalid() {
+    let err = lbs::from_slice::<Account>(&raw("alice", 8080)).unwrap_err();
+    assert_eq!(err.field_path(), vec![1]);
+    assert!(
+        matches!(err.root_cause(), LBSError::Parsing(message) if message == "'alice' is not an email")
+    );
+
+    let err = lbs::from_slice::<Account>(&raw("alice@example.com", 0)).unwrap_err();
+    assert_eq!(err.field_path(), vec![2]);
+    assert!(
+        matches!(err.root_cause(), LBSError::Parsing(message) if message == "port must not be zero")
+    );
+
+    // Failures of the proxy itself aren't replaced
+    let bytes = raw("alice@example.com", 8080);
+    let err = lbs::from_slice::<Account>(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert!(err.is_eof());
+}
@@ -0,0 +1,197 @@
+use lbs::borrow::LBSReadRef;
+use lbs::schema::LBSSchema;
+use lbs::schema::Schema;
+use lbs::size::LBSMaxSize;
+use lbs::write::encoded_len;
+use lbs::LBSRead;
+use lbs::LBSReadRef;
+use lbs::LBSWrite;
+
+#[derive(
+    LBSWrite, LBSRead, LBSReadRef, lbs::LBSSchema, lbs::LBSMaxSize, Default, Debug, PartialEq,
+)]
+struct Point(u32, u32);
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct NamedPoint {
+    #[lbs(id(0))]
+    x: u32,
+    #[lbs(id(1))]
+    y: u32,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+struct Labeled(
+    #[lbs(id(1))] String,
+    #[lbs(id(0))] Option<u8>,
+    #[lbs(skip)] Vec<u8>,
+);
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, lbs::LBSMaxSize, Default, Debug, PartialEq)]
+enum Event {
+    #[default]
+    #[lbs(id(0))]
+    Idle,
+    #[lbs(id(1))]
+    Moved {
+        #[lbs(id(0))]
+        x: i32,
+        #[lbs(id(1))]
+        y: i32,
+    },
+    #[lbs(id(2))]
+    Resized(u32, u32),
+    #[lbs(id(3))]
+    Scrolled(i16),
+}
+
+#[derive(LBSWrite, LBSReadRef, Debug, PartialEq)]
+enum Token<'a> {
+    #[lbs(id(0))]
+    Word {
+        #[lbs(id(0))]
+        text: &'a str,
+        #[lbs(id(1))]
+        position: Option<u32>,
+    },
+}
+
+#[derive(LBSWrite, LBSRead, Default, Debug, PartialEq)]
+enum Sample {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Reading {
+        #[lbs(id(0))]
+        value: f64,
+        #[lbs(skip)]
+        cached: Option<String>,
+    },
+}
+
+#[test]
+fn tuple_struct() {
+    let point = Point(3, 4);
+    let mut buf = Vec::new();
+    point.lbs_write(&mut buf).unwrap();
+
+    assert_eq!(Point::lbs_read(&mut buf.as_slice()).unwrap(), point);
+    assert_eq!(lbs::borrow::from_slice::<Point>(&buf).unwrap(), point);
+
+    // Fields are numbered by position, same as an equivalent named struct
+    let mut named = Vec::new();
+    NamedPoint { x: 3, y: 4 }.lbs_write(&mut named).unwrap();
+    assert_eq!(buf, named);
+    assert_eq!(Point::LBS_MAX_SIZE, buf.len());
+}
+
+#[test]
+fn tuple_struct_ids_and_skip() {
+    let labeled = Labeled("label".to_string(), Some(7), vec![1, 2, 3]);
+    let mut buf = Vec::new();
+    labeled.lbs_write(&mut buf).unwrap();
+
+    let read = Labeled::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(read, Labeled("label".to_string(), Some(7), Vec::new()));
+}
+
+#[test]
+fn variant_fields() {
+    let events = vec![
+        Event::Idle,
+        Event::Moved { x: -1, y: 2 },
+        Event::Resized(640, 480),
+        Event::Scrolled(-3),
+    ];
+
+    let mut buf = Vec::new();
+    events.lbs_write(&mut buf).unwrap();
+    assert_eq!(Vec::<Event>::lbs_read(&mut buf.as_slice()).unwrap(), events);
+
+    // Variant id is followed by fields in the layout of a named struct
+    let mut buf = Vec::new();
+    Event::Resized(640, 480).lbs_write(&mut buf).unwrap();
+
+    let mut expected = Vec::new();
+    2u16.lbs_write(&mut expected).unwrap();
+    NamedPoint { x: 640, y: 480 }
+        .lbs_write(&mut expected)
+        .unwrap();
+    assert_eq!(buf, expected);
+    assert_eq!(
+        encoded_len(&Event::Resized(640, 480)).unwrap(),
+        expected.len()
+    );
+
+    // Replaced on read into another variant
+    let mut event = Event::Scrolled(1);
+    event.lbs_read_into(&mut buf.as_slice()).unwrap();
+    assert_eq!(event, Event::Resized(640, 480));
+}
+
+#[test]
+fn variant_fields_skip() {
+    let sample = Sample::Reading {
+        value: 1.5,
+        cached: Some("1.5".to_string()),
+    };
+
+    let mut buf = Vec::new();
+    sample.lbs_write(&mut buf).unwrap();
+
+    let read = Sample::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(
+        read,
+        Sample::Reading {
+            value: 1.5,
+            cached: None
+        }
+    );
+}
+
+#[test]
+fn variant_fields_borrowed() {
+    let token = Token::Word {
+        text: "hello",
+        position: Some(3),
+    };
+
+    let mut buf = Vec::new();
+    token.lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        Token::lbs_read_ref(&mut buf.as_slice(), &mut Default::default()).unwrap(),
+        token
+    );
+}
+
+#[test]
+fn variant_fields_schema() {
+    let Schema::Enum(schema) = Event::lbs_schema() else {
+        panic!("enum schema expected");
+    };
+
+    let Some(Schema::Struct(ref moved)) = schema.variants[1].schema else {
+        panic!("struct schema expected");
+    };
+
+    assert_eq!(moved.name, "Event::Moved");
+    assert_eq!(moved.fields[0].name, "x");
+    assert_eq!(moved.fields[1].name, "y");
+
+    let Some(Schema::Struct(ref resized)) = schema.variants[2].schema else {
+        panic!("struct schema expected");
+    };
+
+    assert_eq!(resized.fields[0].name, "0");
+    assert_eq!(resized.fields[1].name, "1");
+    assert_eq!(schema.variants[3].schema, Some(Schema::I16));
+
+    let Schema::Struct(point) = Point::lbs_schema() else {
+        panic!("struct schema expected");
+    };
+
+    assert_eq!(point.fields.len(), 2);
+    assert_eq!(point.fields[1].id, 1);
+    assert_eq!(Event::LBS_MAX_SIZE, 2 + Point::LBS_MAX_SIZE);
+}
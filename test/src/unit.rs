@@ -0,0 +1,99 @@
+use chrono::DateTime;
+use chrono::Utc;
+use lbs::error::LBSError;
+use lbs::layout::layout;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Legacy message with raw integer fields.
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct SessionV1 {
+    #[lbs(id(0))]
+    timeout_ms: u64,
+    #[lbs(id(1))]
+    started_at_secs: u64,
+    #[lbs(id(2))]
+    retries_us: Vec<u64>,
+    #[lbs(id(3))]
+    expires_at_ms: Option<u64>,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+struct Session {
+    #[lbs(id(0), unit(millis))]
+    timeout: Duration,
+    #[lbs(id(1), unit(secs), default(UNIX_EPOCH))]
+    started_at: SystemTime,
+    #[lbs(id(2), unit(micros))]
+    retries: Vec<Duration>,
+    #[lbs(id(3), unit(millis))]
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(lbs::LBSSchema)]
+struct Timeout {
+    #[lbs(id(0), unit(millis))]
+    timeout: Duration,
+}
+
+#[test]
+fn unit() {
+    let legacy = SessionV1 {
+        timeout_ms: 1500,
+        started_at_secs: 1_700_000_000,
+        retries_us: vec![250, 1_000_000],
+        expires_at_ms: Some(1_700_000_123_456),
+    };
+
+    let mut buf = Vec::new();
+    legacy.lbs_write(&mut buf).unwrap();
+
+    let session = Session::lbs_read(&mut buf.as_slice()).unwrap();
+    assert_eq!(session.timeout, Duration::from_millis(1500));
+    assert_eq!(
+        session.started_at,
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    );
+    assert_eq!(
+        session.retries,
+        vec![Duration::from_micros(250), Duration::from_secs(1)]
+    );
+    assert_eq!(
+        session.expires_at,
+        DateTime::from_timestamp(1_700_000_123, 456_000_000)
+    );
+
+    // Written back as integers
+    let mut session_buf = Vec::new();
+    session.lbs_write(&mut session_buf).unwrap();
+    assert_eq!(session_buf, buf);
+
+    // Fractions of a unit are truncated
+    let session = Session {
+        timeout: Duration::from_micros(1999),
+        started_at: UNIX_EPOCH,
+        retries: vec![],
+        expires_at: None,
+    };
+    let mut buf = Vec::new();
+    session.lbs_write(&mut buf).unwrap();
+    assert_eq!(
+        SessionV1::lbs_read(&mut buf.as_slice()).unwrap().timeout_ms,
+        1
+    );
+
+    // Timestamps before epoch don't fit
+    let session = Session {
+        started_at: UNIX_EPOCH - Duration::from_secs(1),
+        ..session
+    };
+    let err = session.lbs_write(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), LBSError::OutOfRange(_)));
+
+    assert!(layout::<Timeout>()
+        .to_string()
+        .contains("#0 timeout: Duration as millis [variable]"));
+}
@@ -0,0 +1,83 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use lbs::UnknownFields;
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+#[lbs(indexed)]
+struct UserV2 {
+    #[lbs(id(0))]
+    id: u64,
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(id(2))]
+    email: Option<String>,
+    #[lbs(id(4))]
+    roles: Vec<String>,
+}
+
+#[derive(LBSWrite, LBSRead, Default, Clone, PartialEq, Debug)]
+#[lbs(indexed)]
+struct UserV1 {
+    #[lbs(id(1))]
+    name: String,
+    #[lbs(unknown_fields)]
+    unknown: UnknownFields,
+}
+
+fn user() -> UserV2 {
+    UserV2 {
+        id: 7,
+        name: "alice".to_string(),
+        email: Some("alice@example.com".to_string()),
+        roles: vec!["admin".to_string()],
+    }
+}
+
+#[test]
+fn unknown_fields_roundtrip() {
+    let bytes = lbs::to_vec(&user()).unwrap();
+
+    // Proxy sees only the name, but keeps the rest
+    let mut proxied: UserV1 = lbs::from_slice(&bytes).unwrap();
+    assert_eq!(proxied.name, "alice");
+    assert_eq!(proxied.unknown.len(), 3);
+    assert_eq!(
+        proxied.unknown.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![0, 2, 4]
+    );
+    assert_eq!(proxied.unknown.get(0), Some(&7u64.to_le_bytes()[..]));
+
+    let same = lbs::to_vec(&proxied).unwrap();
+    assert_eq!(same, bytes);
+    assert_eq!(proxied.lbs_size_hint(), bytes.len());
+
+    proxied.name = "bob".to_string();
+    let changed: UserV2 = lbs::from_slice(&lbs::to_vec(&proxied).unwrap()).unwrap();
+    assert_eq!(
+        changed,
+        UserV2 {
+            name: "bob".to_string(),
+            ..user()
+        }
+    );
+}
+
+#[test]
+fn unknown_fields_in_place() {
+    let bytes = lbs::to_vec(&user()).unwrap();
+
+    let mut proxied = UserV1::default();
+    proxied.unknown.insert(9, vec![1, 2, 3]);
+    proxied.lbs_read_into(&mut bytes.as_slice()).unwrap();
+    assert_eq!(proxied.unknown.get(9), None);
+    assert_eq!(proxied.unknown.len(), 3);
+
+    // Known value wins over an unknown one with the same id
+    proxied.unknown.insert(1, vec![0; 4]);
+    let read: UserV2 = lbs::from_slice(&lbs::to_vec(&proxied).unwrap()).unwrap();
+    assert_eq!(read, user());
+
+    assert_eq!(proxied.unknown.remove(1), Some(vec![0; 4]));
+    proxied.unknown.clear();
+    assert!(proxied.unknown.is_empty());
+}
@@ -0,0 +1,31 @@
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Diff {
+    #[lbs(id(0), utf8)]
+    separator: char,
+    #[lbs(id(1), utf8)]
+    marker: Option<char>,
+    #[lbs(id(2), utf8)]
+    inserted: Vec<char>,
+}
+
+#[test]
+fn utf8_char() {
+    let original = Diff {
+        separator: 'a',
+        marker: Some('€'),
+        inserted: "aé€😀".chars().collect(),
+    };
+
+    let mut buf = Vec::new();
+    original.lbs_write(&mut buf).unwrap();
+    // header + 1 + (1 + 3) + (4 + 1 + 2 + 3 + 4)
+    assert_eq!(buf.len(), 2 + 3 * 2 + 1 + 4 + 14);
+    assert_eq!(Diff::lbs_read(&mut buf.as_slice()).unwrap(), original);
+
+    let mut d = lbs::Decoder::new();
+    assert!(lbs::utf8::read::<char, _>(&mut [0xff_u8].as_slice(), &mut d).is_err());
+    assert!(lbs::utf8::read::<char, _>(&mut [0xc3_u8, 0x28].as_slice(), &mut d).is_err());
+}
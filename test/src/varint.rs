@@ -0,0 +1,122 @@
+use lbs::error::LBSError;
+use lbs::layout::layout;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Metric {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    count: u64,
+    #[lbs(id(2))]
+    delta: i32,
+    #[lbs(id(3))]
+    buckets: Vec<u32>,
+    #[lbs(id(4))]
+    label: Option<String>,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug, Default)]
+struct CompactMetric {
+    #[lbs(id(0), varint)]
+    name: String,
+    #[lbs(id(1), varint)]
+    count: u64,
+    #[lbs(id(2), varint)]
+    delta: i32,
+    #[lbs(id(3), varint)]
+    buckets: Vec<u32>,
+    #[lbs(id(4), varint)]
+    label: Option<String>,
+}
+
+fn encode<T: LBSWrite>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.lbs_write(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn varint() {
+    let metric = CompactMetric {
+        name: "rps".to_string(),
+        count: 300,
+        delta: -2,
+        buckets: vec![1, 2, 3, 70000],
+        label: Some("eu".to_string()),
+    };
+
+    let buf = encode(&metric);
+    assert_eq!(
+        CompactMetric::lbs_read(&mut buf.as_slice()).unwrap(),
+        metric
+    );
+
+    let regular = encode(&Metric {
+        name: "rps".to_string(),
+        count: 300,
+        delta: -2,
+        buckets: vec![1, 2, 3, 70000],
+        label: Some("eu".to_string()),
+    });
+    assert!(buf.len() + 20 < regular.len());
+
+    // Fields are written as varints: length then bytes, 300, zigzag -2, ...
+    let mut fields = Vec::new();
+    lbs::varint::write(&metric.name, &mut fields, &mut lbs::Encoder::new()).unwrap();
+    assert_eq!(fields, [3, b'r', b'p', b's']);
+    fields.clear();
+    lbs::varint::write(&metric.count, &mut fields, &mut lbs::Encoder::new()).unwrap();
+    assert_eq!(fields, [0xac, 0x02]);
+    fields.clear();
+    lbs::varint::write(&metric.delta, &mut fields, &mut lbs::Encoder::new()).unwrap();
+    assert_eq!(fields, [3]);
+
+    assert!(layout::<CompactMetric>()
+        .to_string()
+        .contains("#1 count: u64 as varint [variable]"));
+}
+
+#[test]
+fn varint_out_of_range() {
+    #[derive(LBSWrite, Default)]
+    struct Wide {
+        #[lbs(id(0), varint)]
+        value: u64,
+    }
+
+    #[derive(LBSRead, Debug, Default)]
+    struct Narrow {
+        #[lbs(id(0), varint)]
+        #[allow(dead_code)]
+        value: u16,
+    }
+
+    let buf = encode(&Wide { value: 70000 });
+    assert!(Narrow::lbs_read(&mut buf.as_slice()).is_err());
+
+    let buf = encode(&Wide { value: 7000 });
+    assert!(Narrow::lbs_read(&mut buf.as_slice()).is_ok());
+}
+
+#[test]
+fn varint_overlong() {
+    assert_eq!(lbs::varint::read_u64(&mut &[0][..]).unwrap(), 0);
+    assert_eq!(lbs::varint::read_u64(&mut &[0x80, 1][..]).unwrap(), 128);
+
+    // Same values padded with zero groups
+    for bytes in [&[0x80, 0][..], &[0x81, 0x80, 0], &[0x80, 0x81, 0]] {
+        assert!(matches!(
+            lbs::varint::read_u64(&mut &bytes[..]),
+            Err(LBSError::InvalidVarint)
+        ));
+    }
+
+    let mut buf = Vec::new();
+    lbs::varint::write_u64(&mut buf, u64::MAX).unwrap();
+    assert_eq!(
+        lbs::varint::read_u64(&mut buf.as_slice()).unwrap(),
+        u64::MAX
+    );
+}
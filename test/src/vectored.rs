@@ -0,0 +1,51 @@
+use lbs::vectored::VectoredWriter;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+struct Upload {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    blob: Vec<u8>,
+    #[lbs(id(2))]
+    meta: BTreeMap<String, String>,
+    #[lbs(id(3))]
+    comment: Option<String>,
+    #[lbs(id(4))]
+    kind: Kind,
+}
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug, Default)]
+enum Kind {
+    #[default]
+    #[lbs(id(0))]
+    Empty,
+    #[lbs(id(1))]
+    Text(String),
+}
+
+#[test]
+fn vectored_write() {
+    let mut original = Upload {
+        name: String::from("snapshot"),
+        blob: (0..100_000).map(|i| i as u8).collect(),
+        meta: BTreeMap::new(),
+        comment: Some("x".repeat(1024)),
+        kind: Kind::Text(String::from("text")),
+    };
+    original.meta.insert(String::from("k"), String::from("v"));
+
+    let mut expected = Vec::new();
+    original.lbs_write(&mut expected).unwrap();
+
+    let mut v = VectoredWriter::new();
+    original.lbs_write_vectored(&mut v).unwrap();
+    assert_eq!(v.len(), expected.len());
+
+    let mut actual = Vec::new();
+    assert_eq!(v.write_to(&mut actual).unwrap(), expected.len());
+    assert_eq!(actual, expected);
+    assert_eq!(Upload::lbs_read(&mut actual.as_slice()).unwrap(), original);
+}
@@ -0,0 +1,105 @@
+use lbs::visit::visit_as;
+use lbs::visit::LBSVisitor;
+use lbs::LBSRead;
+use lbs::LBSSchema;
+use lbs::LBSWrite;
+use std::collections::BTreeMap;
+
+#[derive(LBSWrite, LBSRead, LBSSchema, Debug, Clone, Default)]
+#[lbs(pack_bools)]
+struct Export {
+    #[lbs(id(0))]
+    rows: Vec<Row>,
+}
+
+#[derive(LBSWrite, LBSRead, LBSSchema, Debug, Clone, Default)]
+#[lbs(pack_bools)]
+struct Row {
+    #[lbs(id(0))]
+    country: String,
+    #[lbs(id(1))]
+    amount: u32,
+    #[lbs(id(2))]
+    comment: Option<String>,
+    #[lbs(id(3))]
+    refunded: bool,
+}
+
+/// Sums amounts of non-refunded rows by country, without decoding comments.
+#[derive(Default)]
+struct Totals {
+    field: Option<u16>,
+    country: String,
+    amount: u64,
+    refunded: bool,
+    totals: BTreeMap<String, u64>,
+    strings: usize,
+}
+
+impl LBSVisitor for Totals {
+    fn on_field_start(&mut self, id: u16, name: &str) -> bool {
+        self.field = Some(id);
+        name != "comment"
+    }
+
+    fn on_string(&mut self, v: &str) {
+        self.strings += 1;
+        if self.field == Some(0) {
+            self.country = v.to_string();
+        }
+    }
+
+    fn on_u64(&mut self, v: u64) {
+        self.amount = v;
+    }
+
+    fn on_bool(&mut self, v: bool) {
+        self.refunded = v;
+    }
+
+    fn on_struct_end(&mut self) {
+        if !self.country.is_empty() && !self.refunded {
+            *self
+                .totals
+                .entry(std::mem::take(&mut self.country))
+                .or_default() += self.amount;
+        }
+        self.country.clear();
+        self.refunded = false;
+    }
+}
+
+#[test]
+fn visit() {
+    let row = |country: &str, amount, refunded| Row {
+        country: country.to_string(),
+        amount,
+        comment: Some("long comment".to_string()),
+        refunded,
+    };
+
+    let export = Export {
+        rows: vec![
+            row("DE", 10, false),
+            row("FR", 20, false),
+            row("DE", 5, false),
+            row("DE", 100, true),
+        ],
+    };
+
+    let mut bytes = Vec::new();
+    export.lbs_write(&mut bytes).unwrap();
+
+    let mut totals = Totals::default();
+    visit_as::<Export, _, _>(&mut bytes.as_slice(), &mut totals).unwrap();
+
+    assert_eq!(
+        totals.totals,
+        BTreeMap::from([("DE".to_string(), 15), ("FR".to_string(), 20)])
+    );
+    assert_eq!(totals.strings, 4);
+
+    // Truncated input
+    let e = visit_as::<Export, _, _>(&mut &bytes[..bytes.len() - 1], &mut Totals::default());
+    assert!(e.unwrap_err().is_eof());
+}
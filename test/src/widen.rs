@@ -0,0 +1,71 @@
+use lbs::error::LBSError;
+use lbs::layout::layout;
+use lbs::Decoder;
+use lbs::Encoder;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+
+#[derive(LBSWrite, LBSRead, PartialEq, Debug)]
+#[lbs(version(1))]
+struct StatsV1 {
+    #[lbs(id(0))]
+    hits: u32,
+    #[lbs(id(1))]
+    delta: i16,
+}
+
+#[derive(LBSWrite, LBSRead, lbs::LBSSchema, PartialEq, Debug)]
+#[lbs(version(2))]
+struct Stats {
+    #[lbs(id(0), widen(u32, 2))]
+    hits: u64,
+    #[lbs(id(1), widen(i16, 2))]
+    delta: i64,
+}
+
+#[test]
+fn widen() {
+    let old = StatsV1 {
+        hits: 42,
+        delta: -7,
+    };
+
+    let mut buf = Vec::new();
+    old.lbs_write(&mut buf).unwrap();
+
+    // Data of older producers is read as narrow integers
+    let mut d = Decoder::new();
+    d.for_version(1);
+    let stats: Stats = d.decode(&mut buf.as_slice()).unwrap();
+    assert_eq!(
+        stats,
+        Stats {
+            hits: 42,
+            delta: -7
+        }
+    );
+
+    // ... and written back for them
+    let mut e = Encoder::new();
+    e.for_version(1);
+    let mut old_buf = Vec::new();
+    stats.lbs_write_with(&mut old_buf, &mut e).unwrap();
+    assert_eq!(old_buf, buf);
+
+    // Latest version uses declared types
+    let stats = Stats {
+        hits: u64::MAX,
+        delta: i64::MIN,
+    };
+    let mut buf = Vec::new();
+    stats.lbs_write(&mut buf).unwrap();
+    assert_eq!(Stats::lbs_read(&mut buf.as_slice()).unwrap(), stats);
+
+    // Wide values don't fit into older version
+    let err = stats.lbs_write_with(&mut Vec::new(), &mut e).unwrap_err();
+    assert!(matches!(err.root_cause(), LBSError::OutOfRange("u32")));
+
+    assert!(layout::<Stats>()
+        .to_string()
+        .contains("#0 hits: u64 as widen(u32, 2) [variable]"));
+}
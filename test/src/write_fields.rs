@@ -0,0 +1,174 @@
+use lbs::read::merge;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use std::sync::Arc;
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+struct Account {
+    #[lbs(id(0))]
+    name: String,
+    #[lbs(id(1))]
+    balance: i64,
+    #[lbs(id(2))]
+    email: Option<String>,
+    #[lbs(id(3))]
+    tags: Vec<String>,
+    #[lbs(skip)]
+    dirty: bool,
+}
+
+/// Hand-written patch of the same fields.
+#[derive(LBSWrite)]
+struct AccountPatch {
+    #[lbs(id(1))]
+    balance: i64,
+    #[lbs(id(3))]
+    tags: Vec<String>,
+}
+
+#[derive(LBSWrite)]
+struct Empty {}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(pack_bools, compact)]
+struct Flags {
+    #[lbs(id(0))]
+    level: u8,
+    #[lbs(id(1))]
+    active: bool,
+    #[lbs(id(2))]
+    admin: bool,
+    #[lbs(id(3))]
+    note: String,
+}
+
+#[derive(LBSWrite, LBSRead, Debug, Clone, Default, PartialEq)]
+#[lbs(indexed)]
+struct Indexed {
+    #[lbs(id(0))]
+    a: u32,
+    #[lbs(id(1))]
+    b: String,
+    #[lbs(id(2))]
+    c: u64,
+}
+
+fn account() -> Account {
+    Account {
+        name: "alice".to_string(),
+        balance: 100,
+        email: Some("a@example.com".to_string()),
+        tags: vec!["vip".to_string()],
+        dirty: true,
+    }
+}
+
+fn write_fields<T: LBSWrite + ?Sized>(value: &T, fields: &[u16]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    value.lbs_write_fields(&mut buf, fields).unwrap();
+    buf
+}
+
+#[test]
+fn write_fields_patch() {
+    let mut changed = account();
+    changed.balance = -5;
+    changed.tags.push("late".to_string());
+    changed.name = "not sent".to_string();
+
+    let patch = write_fields(&changed, &[1, 3]);
+    assert_eq!(
+        patch,
+        lbs::to_vec(&AccountPatch {
+            balance: -5,
+            tags: changed.tags.clone(),
+        })
+        .unwrap()
+    );
+
+    let mut value = account();
+    merge(&mut value, &mut patch.as_slice()).unwrap();
+    assert_eq!(value.name, "alice");
+    assert_eq!(value.balance, -5);
+    assert_eq!(value.tags, changed.tags);
+
+    // All fields or none, unknown and skipped ids are ignored
+    assert_eq!(
+        write_fields(&account(), &[0, 1, 2, 3]),
+        lbs::to_vec(&account()).unwrap()
+    );
+    assert_eq!(
+        write_fields(&account(), &[4, 7]),
+        lbs::to_vec(&Empty {}).unwrap()
+    );
+
+    // Requested absent optional fields are written, so receiver clears them
+    let no_email = Account {
+        email: None,
+        ..account()
+    };
+    let patch_email = write_fields(&no_email, &[2]);
+    assert_ne!(patch_email, lbs::to_vec(&Empty {}).unwrap());
+    let mut value = account();
+    merge(&mut value, &mut patch_email.as_slice()).unwrap();
+    assert_eq!(value.email, None);
+
+    // Wrappers forward the call
+    assert_eq!(write_fields(&Arc::new(changed), &[1, 3]), patch);
+}
+
+#[test]
+fn write_fields_containers() {
+    let flags = Flags {
+        level: 3,
+        active: true,
+        admin: false,
+        note: "n".to_string(),
+    };
+
+    let mut value = Flags::default();
+    merge(&mut value, &mut write_fields(&flags, &[0, 2]).as_slice()).unwrap();
+    assert_eq!(
+        value,
+        Flags {
+            level: 3,
+            active: true,
+            admin: false,
+            note: String::new(),
+        }
+    );
+
+    let mut value = Flags {
+        note: "old".to_string(),
+        ..Flags::default()
+    };
+    merge(&mut value, &mut write_fields(&flags, &[3]).as_slice()).unwrap();
+    assert_eq!(value.note, "n");
+    assert!(!value.active);
+
+    let indexed = Indexed {
+        a: 1,
+        b: "b".to_string(),
+        c: 3,
+    };
+    let mut value = Indexed::default();
+    merge(&mut value, &mut write_fields(&indexed, &[1, 2]).as_slice()).unwrap();
+    assert_eq!(
+        value,
+        Indexed {
+            a: 0,
+            b: "b".to_string(),
+            c: 3,
+        }
+    );
+}
+
+#[test]
+fn write_fields_non_struct() {
+    // Other types are written as a whole
+    assert_eq!(
+        write_fields(&vec![1u8, 2], &[0]),
+        lbs::to_vec(&vec![1u8, 2]).unwrap()
+    );
+    assert_eq!(write_fields("x", &[]), lbs::to_vec("x").unwrap());
+}
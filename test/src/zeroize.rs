@@ -0,0 +1,55 @@
+use lbs::zeroize::Sensitive;
+use lbs::LBSRead;
+use lbs::LBSWrite;
+use zeroize::ZeroizeOnDrop;
+
+#[derive(LBSWrite, LBSRead, Debug, Default)]
+struct Credentials {
+    #[lbs(id(0))]
+    user: String,
+    #[lbs(id(1))]
+    password: Sensitive<String>,
+    #[lbs(id(2))]
+    key: Option<Sensitive<Vec<u8>>>,
+}
+
+fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+#[test]
+fn sensitive() {
+    let credentials = Credentials {
+        user: "alice".to_string(),
+        password: Sensitive::new("hunter2".to_string()),
+        key: Some(vec![1, 2, 3].into()),
+    };
+
+    let mut buf = Vec::new();
+    credentials.lbs_write(&mut buf).unwrap();
+
+    let mut d = lbs::Decoder::new();
+    let decoded = Credentials::lbs_read_with(&mut buf.as_slice(), &mut d).unwrap();
+    assert_eq!(decoded.user, "alice");
+    assert_eq!(*decoded.password, "hunter2");
+    assert_eq!(
+        decoded.key.as_deref().map(Vec::as_slice),
+        Some(&[1, 2, 3][..])
+    );
+
+    // Wire format is the same as of the value
+    let mut plain = Vec::new();
+    "hunter2".lbs_write(&mut plain).unwrap();
+    let mut wrapped = Vec::new();
+    credentials.password.lbs_write(&mut wrapped).unwrap();
+    assert_eq!(wrapped, plain);
+
+    // Value isn't revealed by Debug
+    assert!(!format!("{:?}", decoded).contains("hunter2"));
+    assert_zeroize_on_drop::<Sensitive<String>>();
+}
+
+#[test]
+fn sensitive_invalid_utf8() {
+    let mut buf = Vec::new();
+    vec![0xffu8, 0xfe].lbs_write(&mut buf).unwrap();
+    assert!(Sensitive::<String>::lbs_read(&mut buf.as_slice()).is_err());
+}